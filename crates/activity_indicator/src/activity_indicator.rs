@@ -70,6 +70,8 @@ impl ActivityIndicator {
             })
             .detach();
             cx.observe(&project, |_, _, cx| cx.notify()).detach();
+            cx.observe(project.read(cx).buffer_store(), |_, _, cx| cx.notify())
+                .detach();
 
             if let Some(auto_updater) = auto_updater.as_ref() {
                 cx.observe(auto_updater, |_, _, cx| cx.notify()).detach();
@@ -218,6 +220,36 @@ impl ActivityIndicator {
             };
         }
 
+        // Show the progress of files being opened in large-file mode.
+        let buffer_store = self.project.read(cx).buffer_store().read(cx);
+        let mut loading_large_files = buffer_store.loading_large_files();
+        if let Some(load) = loading_large_files.next() {
+            let mut message = format!(
+                "Loading {} ({}%)",
+                load.path.path.to_string_lossy(),
+                load.percentage()
+            );
+            let additional_load_count = loading_large_files.count();
+            if additional_load_count > 0 {
+                write!(&mut message, " + {} more", additional_load_count).unwrap();
+            }
+
+            return Content {
+                icon: Some(
+                    Icon::new(IconName::ArrowCircle)
+                        .size(IconSize::Small)
+                        .with_animation(
+                            "arrow-circle",
+                            Animation::new(Duration::from_secs(2)).repeat(),
+                            |icon, delta| icon.transform(Transformation::rotate(percentage(delta))),
+                        )
+                        .into_any_element(),
+                ),
+                message,
+                on_click: None,
+            };
+        }
+
         // Show any language server installation info.
         let mut downloading = SmallVec::<[_; 3]>::new();
         let mut checking_for_update = SmallVec::<[_; 3]>::new();
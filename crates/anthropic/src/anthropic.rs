@@ -95,12 +95,65 @@ pub struct Request {
     pub stream: bool,
     pub system: String,
     pub max_tokens: u32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<Tool>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Tool {
+    pub name: String,
+    pub description: String,
+    pub input_schema: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct RequestMessage {
     pub role: Role,
-    pub content: String,
+    pub content: MessageContent,
+}
+
+/// The content of a message, which is either plain text or a list of content blocks.
+///
+/// Blocks are needed to pass tool uses and tool results back to the model.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Text(String),
+    Blocks(Vec<RequestContentBlock>),
+}
+
+impl From<String> for MessageContent {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RequestContentBlock {
+    Text {
+        text: String,
+    },
+    Image {
+        source: ImageSource,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        content: String,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        is_error: bool,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ImageSource {
+    Base64 { media_type: String, data: String },
 }
 
 #[derive(Deserialize, Debug)]
@@ -150,13 +203,21 @@ pub struct Usage {
 #[derive(Deserialize, Debug)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ContentBlock {
-    Text { text: String },
+    Text {
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
 }
 
 #[derive(Deserialize, Debug)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum TextDelta {
     TextDelta { text: String },
+    InputJsonDelta { partial_json: String },
 }
 
 pub async fn stream_completion(
@@ -229,11 +290,12 @@ pub async fn stream_completion(
 //             model: Model::Claude3Opus,
 //             messages: vec![RequestMessage {
 //                 role: Role::User,
-//                 content: "Ping".to_string(),
+//                 content: "Ping".to_string().into(),
 //             }],
 //             stream: true,
 //             system: "Respond to ping with pong".to_string(),
 //             max_tokens: 4096,
+//             tools: Vec::new(),
 //         };
 
 //         let stream = stream_completion(
@@ -3,25 +3,35 @@ pub mod assistant_settings;
 mod completion_provider;
 mod context;
 pub mod context_store;
+mod edit_review;
 mod inline_assistant;
 mod model_selector;
 mod prompt_library;
+mod prompt_template;
 mod prompts;
 mod search;
 mod slash_command;
 mod streaming_diff;
 mod terminal_inline_assistant;
+mod token_budget;
+mod tools;
 
+use anyhow::Result;
 pub use assistant_panel::{AssistantPanel, AssistantPanelEvent};
-use assistant_settings::{AnthropicModel, AssistantSettings, CloudModel, OllamaModel, OpenAiModel};
+use assistant_settings::{
+    AnthropicModel, AssistantSettings, CloudModel, GoogleModel, OllamaModel, OpenAiCompatibleModel,
+    OpenAiModel,
+};
 use assistant_slash_command::SlashCommandRegistry;
+use assistant_tooling::ToolFunctionDefinition;
 use client::{proto, Client};
 use command_palette_hooks::CommandPaletteFilter;
 pub use completion_provider::*;
 pub use context::*;
 pub use context_store::*;
 use fs::Fs;
-use gpui::{actions, AppContext, Global, SharedString, UpdateGlobal};
+use gpui::{actions, AppContext, Global, Img, SharedString, UpdateGlobal};
+use image::{imageops::FilterType, GenericImageView, ImageFormat};
 use indexed_docs::IndexedDocsRegistry;
 pub(crate) use inline_assistant::*;
 pub(crate) use model_selector::*;
@@ -30,11 +40,13 @@ use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsStore};
 use slash_command::{
     active_command, default_command, diagnostics_command, docs_command, fetch_command,
-    file_command, now_command, project_command, prompt_command, search_command, symbols_command,
-    tabs_command, term_command,
+    file_command, now_command, project_command, prompt_command, rules_command, search_command,
+    symbols_command, tabs_command, term_command,
 };
 use std::{
     fmt::{self, Display},
+    io::Cursor,
+    path::Path,
     sync::Arc,
 };
 pub(crate) use streaming_diff::*;
@@ -119,7 +131,9 @@ pub enum LanguageModel {
     Cloud(CloudModel),
     OpenAi(OpenAiModel),
     Anthropic(AnthropicModel),
+    Google(GoogleModel),
     Ollama(OllamaModel),
+    OpenAiCompatible(OpenAiCompatibleModel),
 }
 
 impl Default for LanguageModel {
@@ -133,8 +147,10 @@ impl LanguageModel {
         match self {
             LanguageModel::OpenAi(model) => format!("openai/{}", model.id()),
             LanguageModel::Anthropic(model) => format!("anthropic/{}", model.id()),
+            LanguageModel::Google(model) => format!("google/{}", model.id()),
             LanguageModel::Cloud(model) => format!("zed.dev/{}", model.id()),
             LanguageModel::Ollama(model) => format!("ollama/{}", model.id()),
+            LanguageModel::OpenAiCompatible(model) => format!("openai_compatible/{}", model.name),
         }
     }
 
@@ -142,8 +158,10 @@ impl LanguageModel {
         match self {
             LanguageModel::OpenAi(model) => model.display_name().into(),
             LanguageModel::Anthropic(model) => model.display_name().into(),
+            LanguageModel::Google(model) => model.display_name().into(),
             LanguageModel::Cloud(model) => model.display_name().into(),
             LanguageModel::Ollama(model) => model.display_name().into(),
+            LanguageModel::OpenAiCompatible(model) => model.name.clone(),
         }
     }
 
@@ -151,8 +169,10 @@ impl LanguageModel {
         match self {
             LanguageModel::OpenAi(model) => model.max_token_count(),
             LanguageModel::Anthropic(model) => model.max_token_count(),
+            LanguageModel::Google(model) => model.max_token_count(),
             LanguageModel::Cloud(model) => model.max_token_count(),
             LanguageModel::Ollama(model) => model.max_token_count(),
+            LanguageModel::OpenAiCompatible(model) => model.max_tokens,
         }
     }
 
@@ -160,9 +180,114 @@ impl LanguageModel {
         match self {
             LanguageModel::OpenAi(model) => model.id(),
             LanguageModel::Anthropic(model) => model.id(),
+            LanguageModel::Google(model) => model.id(),
             LanguageModel::Cloud(model) => model.id(),
             LanguageModel::Ollama(model) => model.id(),
+            LanguageModel::OpenAiCompatible(model) => &model.name,
+        }
+    }
+}
+
+/// A request from the model to call one of the tools it was given.
+#[derive(Clone, Serialize, Deserialize, Debug, Eq, PartialEq)]
+pub struct LanguageModelToolCall {
+    pub id: String,
+    pub name: String,
+    /// The arguments of the call, as a JSON object.
+    pub arguments: String,
+}
+
+impl LanguageModelToolCall {
+    pub fn to_proto(&self) -> proto::ToolCall {
+        proto::ToolCall {
+            id: self.id.clone(),
+            variant: Some(proto::tool_call::Variant::Function(
+                proto::tool_call::FunctionCall {
+                    name: self.name.clone(),
+                    arguments: self.arguments.clone(),
+                },
+            )),
+        }
+    }
+}
+
+/// The output of running a tool, which is sent back to the model.
+#[derive(Clone, Serialize, Deserialize, Debug, Eq, PartialEq)]
+pub struct LanguageModelToolResult {
+    pub tool_call_id: String,
+    pub name: String,
+    pub content: String,
+    pub is_error: bool,
+}
+
+impl LanguageModelToolResult {
+    pub fn to_proto(&self) -> proto::LanguageModelRequestMessage {
+        proto::LanguageModelRequestMessage {
+            role: proto::LanguageModelRole::LanguageModelTool as i32,
+            content: self.content.clone(),
+            tool_calls: Vec::new(),
+            tool_call_id: Some(self.tool_call_id.clone()),
+        }
+    }
+}
+
+/// An image sent to the model alongside the text of a message.
+#[derive(Clone, Serialize, Deserialize, Debug, Eq, PartialEq)]
+pub struct LanguageModelImage {
+    /// The image, encoded as a base64 PNG.
+    pub data: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl LanguageModelImage {
+    pub const MEDIA_TYPE: &'static str = "image/png";
+
+    /// Images are downscaled to fit within this many pixels on either side, which
+    /// is the largest size the providers process without resizing them themselves.
+    const MAX_DIMENSION: u32 = 1568;
+
+    /// Decodes an image in any format supported by the `image` crate,
+    /// downscaling it if needed and re-encoding it as a PNG.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut image = image::load_from_memory(bytes)?;
+        let (width, height) = image.dimensions();
+        if width > Self::MAX_DIMENSION || height > Self::MAX_DIMENSION {
+            image = image.resize(
+                Self::MAX_DIMENSION,
+                Self::MAX_DIMENSION,
+                FilterType::Triangle,
+            );
         }
+
+        let mut png = Vec::new();
+        image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+        Ok(Self {
+            data: base64::encode(&png),
+            width: image.width(),
+            height: image.height(),
+        })
+    }
+
+    /// Returns whether the file at the given path is an image that can be sent to the model.
+    pub fn is_supported_path(path: &Path) -> bool {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .map_or(false, |extension| {
+                let extension = extension.to_lowercase();
+                extension != "svg" && Img::extensions().contains(&extension.as_str())
+            })
+    }
+
+    /// Returns the image as a `data:` URL.
+    pub fn to_data_url(&self) -> String {
+        format!("data:{};base64,{}", Self::MEDIA_TYPE, self.data)
+    }
+
+    /// Estimates how many tokens the image costs, using Anthropic's
+    /// formula, which is close to what the other providers charge.
+    pub fn estimate_tokens(&self) -> usize {
+        (self.width as usize * self.height as usize) / 750
     }
 }
 
@@ -170,6 +295,16 @@ impl LanguageModel {
 pub struct LanguageModelRequestMessage {
     pub role: Role,
     pub content: String,
+    /// The images attached to this message, which precede its content.
+    #[serde(default)]
+    pub images: Vec<LanguageModelImage>,
+    /// The tools the model asked to call at the end of this (assistant) message.
+    #[serde(default)]
+    pub tool_calls: Vec<LanguageModelToolCall>,
+    /// The results of the tool calls made in the previous message, which precede
+    /// the content of this (user) message.
+    #[serde(default)]
+    pub tool_results: Vec<LanguageModelToolResult>,
 }
 
 impl LanguageModelRequestMessage {
@@ -177,7 +312,7 @@ impl LanguageModelRequestMessage {
         proto::LanguageModelRequestMessage {
             role: self.role.to_proto() as i32,
             content: self.content.clone(),
-            tool_calls: Vec::new(),
+            tool_calls: self.tool_calls.iter().map(|call| call.to_proto()).collect(),
             tool_call_id: None,
         }
     }
@@ -189,17 +324,42 @@ pub struct LanguageModelRequest {
     pub messages: Vec<LanguageModelRequestMessage>,
     pub stop: Vec<String>,
     pub temperature: f32,
+    /// The tools the model may call. Only sent to providers that support tools.
+    #[serde(default)]
+    pub tools: Vec<ToolFunctionDefinition>,
 }
 
 impl LanguageModelRequest {
     pub fn to_proto(&self) -> proto::CompleteWithLanguageModel {
         proto::CompleteWithLanguageModel {
             model: self.model.id().to_string(),
-            messages: self.messages.iter().map(|m| m.to_proto()).collect(),
+            messages: self
+                .messages
+                .iter()
+                .flat_map(|message| {
+                    message
+                        .tool_results
+                        .iter()
+                        .map(|result| result.to_proto())
+                        .chain(Some(message.to_proto()))
+                })
+                .collect(),
             stop: self.stop.clone(),
             temperature: self.temperature,
             tool_choice: None,
-            tools: Vec::new(),
+            tools: self
+                .tools
+                .iter()
+                .map(|tool| proto::ChatCompletionTool {
+                    variant: Some(proto::chat_completion_tool::Variant::Function(
+                        proto::chat_completion_tool::FunctionObject {
+                            name: tool.name.clone(),
+                            description: Some(tool.description.clone()),
+                            parameters: serde_json::to_string(&tool.parameters).ok(),
+                        },
+                    )),
+                })
+                .collect(),
         }
     }
 
@@ -208,7 +368,9 @@ impl LanguageModelRequest {
         match &self.model {
             LanguageModel::OpenAi(_) => {}
             LanguageModel::Anthropic(_) => {}
+            LanguageModel::Google(_) => {}
             LanguageModel::Ollama(_) => {}
+            LanguageModel::OpenAiCompatible(_) => {}
             LanguageModel::Cloud(model) => match model {
                 CloudModel::Claude3Opus
                 | CloudModel::Claude3Sonnet
@@ -373,6 +535,7 @@ fn register_slash_commands(cx: &mut AppContext) {
     slash_command_registry.register_command(search_command::SearchSlashCommand, true);
     slash_command_registry.register_command(prompt_command::PromptSlashCommand, true);
     slash_command_registry.register_command(default_command::DefaultSlashCommand, true);
+    slash_command_registry.register_command(rules_command::RulesSlashCommand, true);
     slash_command_registry.register_command(term_command::TermSlashCommand, true);
     slash_command_registry.register_command(now_command::NowSlashCommand, true);
     slash_command_registry.register_command(diagnostics_command::DiagnosticsSlashCommand, true);
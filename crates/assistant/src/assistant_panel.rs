@@ -1,22 +1,27 @@
 use crate::{
     assistant_settings::{AssistantDockPosition, AssistantSettings},
+    edit_review::EditReview,
     humanize_token_count, parse_next_edit_suggestion,
     prompt_library::open_prompt_library,
     search::*,
     slash_command::{
         default_command::DefaultSlashCommand,
         docs_command::{DocsSlashCommand, DocsSlashCommandArgs},
+        rules_command::{project_rules, RulesSlashCommand, PROJECT_RULES_LABEL},
         SlashCommandCompletionProvider, SlashCommandRegistry,
     },
     terminal_inline_assistant::TerminalInlineAssistant,
-    ApplyEdit, Assist, CompletionProvider, ConfirmCommand, Context, ContextEvent, ContextId,
-    ContextStore, CycleMessageRole, DeployHistory, DeployPromptLibrary, EditSuggestion,
-    InlineAssist, InlineAssistant, InsertIntoEditor, MessageStatus, ModelSelector,
-    PendingSlashCommand, PendingSlashCommandStatus, QuoteSelection, RemoteContextMetadata,
-    ResetKey, Role, SavedContextMetadata, Split, ToggleFocus, ToggleModelSelector,
+    token_budget::TrimStrategy,
+    tools, ApplyEdit, Assist, CompletionProvider, ConfirmCommand, Context, ContextEvent, ContextId,
+    ContextStore, CycleMessageRole, DeployHistory, DeployPromptLibrary, EditSuggestion, ImageId,
+    InlineAssist, InlineAssistant, InsertIntoEditor, LanguageModelImage, LanguageModelToolCall,
+    LanguageModelToolResult, MessageId, MessageStatus, ModelSelector, PendingSlashCommand,
+    PendingSlashCommandStatus, QuoteSelection, RemoteContextMetadata, ResetKey, Role,
+    SavedContextMetadata, Split, ToggleFocus, ToggleModelSelector,
 };
 use anyhow::{anyhow, Result};
 use assistant_slash_command::{SlashCommand, SlashCommandOutputSection};
+use assistant_tooling::{ProjectContext, ToolFunctionCall, ToolRegistry};
 use breadcrumbs::Breadcrumbs;
 use client::proto;
 use collections::{BTreeSet, HashMap, HashSet};
@@ -30,24 +35,28 @@ use editor::{
 };
 use editor::{display_map::CreaseId, FoldPlaceholder};
 use fs::Fs;
+use futures::future;
 use gpui::{
-    div, percentage, point, Action, Animation, AnimationExt, AnyElement, AnyView, AppContext,
-    AsyncWindowContext, ClipboardItem, DismissEvent, Empty, EventEmitter, FocusHandle,
-    FocusableView, InteractiveElement, IntoElement, Model, ParentElement, Pixels, Render,
-    SharedString, StatefulInteractiveElement, Styled, Subscription, Task, Transformation,
-    UpdateGlobal, View, ViewContext, VisualContext, WeakView, WindowContext,
+    div, img, percentage, point, Action, Animation, AnimationExt, AnyElement, AnyView, AppContext,
+    AsyncWindowContext, ClipboardItem, DismissEvent, Empty, EventEmitter, ExternalPaths,
+    FocusHandle, FocusableView, ImageData, InteractiveElement, IntoElement, Model, ObjectFit,
+    ParentElement, Pixels, PromptLevel, Render, SharedString, StatefulInteractiveElement, Styled,
+    Subscription, Task, Transformation, UpdateGlobal, View, ViewContext, VisualContext, WeakView,
+    WindowContext,
 };
 use indexed_docs::IndexedDocsStore;
 use language::{
-    language_settings::SoftWrap, AutoindentMode, Buffer, LanguageRegistry, LspAdapterDelegate,
-    OffsetRangeExt as _, Point, ToOffset,
+    language_settings::SoftWrap, Buffer, LanguageRegistry, LspAdapterDelegate, OffsetRangeExt as _,
+    Point, ToOffset,
 };
 use multi_buffer::MultiBufferRow;
 use picker::{Picker, PickerDelegate};
-use project::{Project, ProjectLspAdapterDelegate, ProjectTransaction};
+use project::{Project, ProjectLspAdapterDelegate};
 use search::{buffer_search::DivRegistrar, BufferSearchBar};
 use settings::Settings;
-use std::{cmp, fmt::Write, ops::Range, path::PathBuf, sync::Arc, time::Duration};
+use std::{
+    cmp, fmt::Write, ops::ControlFlow, ops::Range, path::PathBuf, sync::Arc, time::Duration,
+};
 use terminal_view::{terminal_panel::TerminalPanel, TerminalView};
 use theme::ThemeSettings;
 use ui::{
@@ -60,9 +69,11 @@ use util::ResultExt;
 use workspace::{
     dock::{DockPosition, Panel, PanelEvent},
     item::{self, BreadcrumbText, FollowableItem, Item, ItemHandle},
+    notifications::NotificationId,
     pane,
     searchable::{SearchEvent, SearchableItem},
-    Pane, Save, ToggleZoom, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView, Workspace,
+    Pane, Save, Toast, ToggleZoom, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView,
+    Workspace,
 };
 use workspace::{searchable::SearchableItemHandle, NewFile};
 
@@ -344,6 +355,30 @@ impl AssistantPanel {
                 );
                 toolbar.add_item(cx.new_view(BufferSearchBar::new), cx)
             });
+            pane.set_custom_drop_handle(cx, |pane, dropped_item, cx| {
+                let Some(paths) = dropped_item.downcast_ref::<ExternalPaths>() else {
+                    return ControlFlow::Continue(());
+                };
+                let Some(context_editor) = pane
+                    .active_item()
+                    .and_then(|item| item.downcast::<ContextEditor>())
+                else {
+                    return ControlFlow::Continue(());
+                };
+                if !paths
+                    .paths()
+                    .iter()
+                    .any(|path| LanguageModelImage::is_supported_path(path))
+                {
+                    return ControlFlow::Continue(());
+                }
+
+                let paths = paths.paths().to_vec();
+                context_editor.update(cx, |context_editor, cx| {
+                    context_editor.attach_images(paths, cx)
+                });
+                ControlFlow::Break(())
+            });
             pane
         });
 
@@ -665,7 +700,7 @@ impl AssistantPanel {
     }
 
     fn deploy_prompt_library(&mut self, _: &DeployPromptLibrary, cx: &mut ViewContext<Self>) {
-        open_prompt_library(self.languages.clone(), cx).detach_and_log_err(cx);
+        open_prompt_library(self.fs.clone(), self.languages.clone(), cx).detach_and_log_err(cx);
     }
 
     fn reset_credentials(&mut self, _: &ResetKey, cx: &mut ViewContext<Self>) {
@@ -963,15 +998,26 @@ pub struct ContextEditor {
     lsp_adapter_delegate: Option<Arc<dyn LspAdapterDelegate>>,
     editor: View<Editor>,
     blocks: HashSet<BlockId>,
+    image_blocks: HashMap<ImageId, BlockId>,
+    /// Decoded images, ready to be painted, keyed by the image they were decoded from.
+    rendered_images: HashMap<ImageId, Arc<ImageData>>,
     scroll_position: Option<ScrollPosition>,
     remote_id: Option<workspace::ViewId>,
     pending_slash_command_creases: HashMap<Range<language::Anchor>, CreaseId>,
     pending_slash_command_blocks: HashMap<Range<language::Anchor>, BlockId>,
+    project: Model<Project>,
+    tool_registry: Arc<ToolRegistry>,
+    /// The tool calls requested by the model's latest response.
+    tool_calls: Vec<ToolFunctionCall>,
+    /// How many times the model was re-run with tool results since the user last assisted.
+    tool_iterations: usize,
     _subscriptions: Vec<Subscription>,
 }
 
 impl ContextEditor {
     const MAX_TAB_TITLE_LEN: usize = 16;
+    const MAX_TOOL_ITERATIONS: usize = 8;
+    const MAX_IMAGE_LINES: u8 = 10;
 
     fn for_context(
         context: Model<Context>,
@@ -996,15 +1042,21 @@ impl ContextEditor {
             editor.set_show_wrap_guides(false, cx);
             editor.set_show_indent_guides(false, cx);
             editor.set_completion_provider(Box::new(completion_provider));
-            editor.set_collaboration_hub(Box::new(project));
+            editor.set_collaboration_hub(Box::new(project.clone()));
             editor
         });
 
+        let tool_registry = Arc::new(tools::project_tool_registry(project.clone()));
+        context.update(cx, |context, _| {
+            context.set_tools(tool_registry.definitions())
+        });
+
         let _subscriptions = vec![
             cx.observe(&context, |_, _, cx| cx.notify()),
             cx.subscribe(&context, Self::handle_context_event),
             cx.subscribe(&editor, Self::handle_editor_event),
             cx.subscribe(&editor, Self::handle_editor_search_event),
+            cx.subscribe(&project, Self::handle_project_event),
         ];
 
         let sections = context.read(cx).slash_command_output_sections().to_vec();
@@ -1013,45 +1065,172 @@ impl ContextEditor {
             editor,
             lsp_adapter_delegate,
             blocks: Default::default(),
+            image_blocks: HashMap::default(),
+            rendered_images: HashMap::default(),
             scroll_position: None,
             remote_id: None,
             fs,
             workspace: workspace.downgrade(),
             pending_slash_command_creases: HashMap::default(),
             pending_slash_command_blocks: HashMap::default(),
+            project,
+            tool_registry,
+            tool_calls: Vec::new(),
+            tool_iterations: 0,
             _subscriptions,
         };
         this.update_message_headers(cx);
+        this.update_image_blocks(cx);
         this.insert_slash_command_output_sections(sections, cx);
         this
     }
 
     fn insert_default_prompt(&mut self, cx: &mut ViewContext<Self>) {
-        let command_name = DefaultSlashCommand.name();
-        self.editor.update(cx, |editor, cx| {
-            editor.insert(&format!("/{command_name}"), cx)
-        });
+        let mut commands = String::new();
+        if project_rules(self.project.read(cx), cx).is_some() {
+            writeln!(commands, "/{}", RulesSlashCommand.name()).unwrap();
+        }
+        write!(commands, "/{}", DefaultSlashCommand.name()).unwrap();
+        self.editor
+            .update(cx, |editor, cx| editor.insert(&commands, cx));
         self.split(&Split, cx);
-        let command = self.context.update(cx, |context, cx| {
+        let commands = self.context.update(cx, |context, cx| {
             let first_message_id = context.messages(cx).next().unwrap().id;
             context.update_metadata(first_message_id, cx, |metadata| {
                 metadata.role = Role::System;
             });
             context.reparse_slash_commands(cx);
-            context.pending_slash_commands()[0].clone()
+            context.pending_slash_commands().to_vec()
         });
 
-        self.run_command(
-            command.source_range,
-            &command.name,
-            command.argument.as_deref(),
-            false,
-            self.workspace.clone(),
-            cx,
-        );
+        for command in commands {
+            self.run_command(
+                command.source_range,
+                &command.name,
+                command.argument.as_deref(),
+                false,
+                self.workspace.clone(),
+                cx,
+            );
+        }
+    }
+
+    fn handle_project_event(
+        &mut self,
+        _: Model<Project>,
+        event: &project::Event,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let project::Event::AssistantRulesChanged = event {
+            let rules = project_rules(self.project.read(cx), cx);
+            self.context.update(cx, |context, cx| {
+                context.replace_slash_command_output_sections(
+                    PROJECT_RULES_LABEL,
+                    rules.as_deref(),
+                    cx,
+                )
+            });
+        }
     }
 
     fn assist(&mut self, _: &Assist, cx: &mut ViewContext<Self>) {
+        let model = CompletionProvider::global(cx).model();
+        let max_token_count = model.max_token_count();
+        if let Some(token_count) = self
+            .context
+            .read(cx)
+            .token_count()
+            .filter(|token_count| *token_count > max_token_count)
+        {
+            self.prompt_to_trim_context(token_count, max_token_count, &model.display_name(), cx);
+            return;
+        }
+
+        self.send_to_model(cx);
+    }
+
+    /// Warns that the context exceeds the model's context window before sending it, and
+    /// offers to trim it with one of the [`TrimStrategy`] variants.
+    fn prompt_to_trim_context(
+        &mut self,
+        token_count: usize,
+        max_token_count: usize,
+        model_name: &str,
+        cx: &mut ViewContext<Self>,
+    ) {
+        const SEND_ANYWAY: &str = "Send Anyway";
+        const CANCEL: &str = "Cancel";
+
+        let detail = format!(
+            "It uses {} tokens, but {} only supports {}. Trim the context before sending it?",
+            humanize_token_count(token_count),
+            model_name,
+            humanize_token_count(max_token_count)
+        );
+        let mut answers = TrimStrategy::ALL
+            .iter()
+            .map(|strategy| strategy.label())
+            .collect::<Vec<_>>();
+        answers.extend([SEND_ANYWAY, CANCEL]);
+        let answer = cx.prompt(
+            PromptLevel::Warning,
+            "This context exceeds the model's context window",
+            Some(&detail),
+            &answers,
+        );
+
+        cx.spawn(|this, mut cx| async move {
+            let answer = answer.await?;
+            if let Some(strategy) = TrimStrategy::ALL.get(answer).copied() {
+                this.update(&mut cx, |this, cx| this.trim_context(strategy, cx))?;
+            } else if answers.get(answer) == Some(&SEND_ANYWAY) {
+                this.update(&mut cx, |this, cx| this.send_to_model(cx))?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn trim_context(&mut self, strategy: TrimStrategy, cx: &mut ViewContext<Self>) {
+        let max_token_count = CompletionProvider::global(cx).model().max_token_count();
+        let trim = self.context.update(cx, |context, cx| {
+            context.trim(strategy, max_token_count, cx)
+        });
+        cx.spawn(|this, mut cx| async move {
+            if trim.await {
+                return Ok(());
+            }
+
+            this.update(&mut cx, |this, cx| {
+                let Some(workspace) = this.workspace.upgrade() else {
+                    return;
+                };
+                workspace.update(cx, |workspace, cx| {
+                    struct TrimContextError;
+
+                    let message = match strategy {
+                        TrimStrategy::DropOldestMessages => {
+                            "There are no messages that can be dropped from this context."
+                        }
+                        TrimStrategy::CollapseLargeSections => {
+                            "There are no large sections to collapse in this context."
+                        }
+                        TrimStrategy::TruncateFilesToSymbols => {
+                            "There are no files with symbols to truncate in this context."
+                        }
+                    };
+                    workspace.show_toast(
+                        Toast::new(NotificationId::unique::<TrimContextError>(), message),
+                        cx,
+                    );
+                })
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn send_to_model(&mut self, cx: &mut ViewContext<Self>) {
+        self.tool_iterations = 0;
         let cursors = self.cursors(cx);
 
         let user_messages = self.context.update(cx, |context, cx| {
@@ -1218,6 +1397,7 @@ impl ContextEditor {
         match event {
             ContextEvent::MessagesEdited => {
                 self.update_message_headers(cx);
+                self.update_image_blocks(cx);
                 self.context.update(cx, |context, cx| {
                     context.save(Some(Duration::from_millis(500)), self.fs.clone(), cx);
                 });
@@ -1431,10 +1611,117 @@ impl ContextEditor {
                     }
                 }
             }
+            ContextEvent::ToolCallsRequested {
+                user_message_id,
+                tool_calls,
+                ..
+            } => {
+                self.run_tool_calls(*user_message_id, tool_calls.clone(), cx);
+            }
+            ContextEvent::ImagesChanged => {
+                self.update_image_blocks(cx);
+                self.context.update(cx, |context, cx| {
+                    context.save(Some(Duration::from_millis(500)), self.fs.clone(), cx);
+                });
+            }
             ContextEvent::Operation(_) => {}
         }
     }
 
+    /// Runs the tools requested by the model, attaches their results to the
+    /// user message that follows the request and, unless the model has called
+    /// tools too many times in a row, asks the model to continue.
+    fn run_tool_calls(
+        &mut self,
+        user_message_id: MessageId,
+        tool_calls: Vec<LanguageModelToolCall>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let mut calls = Vec::new();
+        let mut tasks = Vec::new();
+        for tool_call in tool_calls {
+            let mut call = ToolFunctionCall {
+                id: tool_call.id,
+                name: tool_call.name,
+                ..Default::default()
+            };
+            let arguments = if tool_call.arguments.trim().is_empty() {
+                "{}"
+            } else {
+                tool_call.arguments.as_str()
+            };
+            self.tool_registry
+                .update_tool_call(&mut call, None, Some(arguments), cx);
+            tasks.push(self.tool_registry.execute_tool_call(&mut call, cx));
+            calls.push(call);
+        }
+        let call_ids = calls.iter().map(|call| call.id.clone()).collect::<Vec<_>>();
+        self.tool_calls = calls;
+        cx.notify();
+
+        let project = self.project.downgrade();
+        let fs = self.fs.clone();
+        cx.spawn(|this, mut cx| async move {
+            let results = future::join_all(tasks.into_iter().map(|task| async move {
+                match task {
+                    Some(task) => Some(task.await),
+                    None => None,
+                }
+            }))
+            .await;
+
+            this.update(&mut cx, |this, cx| {
+                // A newer response may have replaced these tool calls in the meantime.
+                if !this
+                    .tool_calls
+                    .iter()
+                    .map(|call| &call.id)
+                    .eq(call_ids.iter())
+                {
+                    return;
+                }
+
+                let mut project_context = ProjectContext::new(project, fs);
+                let tool_results = this
+                    .tool_calls
+                    .iter()
+                    .zip(results)
+                    .map(|(call, result)| {
+                        let (content, is_error) = match result {
+                            Some(Ok(())) => (
+                                this.tool_registry.content_for_tool_call(
+                                    call,
+                                    &mut project_context,
+                                    cx,
+                                ),
+                                false,
+                            ),
+                            Some(Err(error)) => (error.to_string(), true),
+                            None => (format!("No such tool: {}", call.name), true),
+                        };
+                        LanguageModelToolResult {
+                            tool_call_id: call.id.clone(),
+                            name: call.name.clone(),
+                            content,
+                            is_error,
+                        }
+                    })
+                    .collect();
+                this.context.update(cx, |context, cx| {
+                    context.set_tool_results(user_message_id, tool_results, cx)
+                });
+
+                if this.tool_iterations < Self::MAX_TOOL_ITERATIONS {
+                    this.tool_iterations += 1;
+                    this.context.update(cx, |context, cx| {
+                        context.assist(HashSet::from_iter([user_message_id]), cx)
+                    });
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn insert_slash_command_output_sections(
         &mut self,
         sections: impl IntoIterator<Item = SlashCommandOutputSection<language::Anchor>>,
@@ -1609,6 +1896,30 @@ impl ContextEditor {
                                 .relative()
                                 .gap_1()
                                 .child(sender)
+                                .children((!message.tool_calls.is_empty()).then(|| {
+                                    let tool_names = message
+                                        .tool_calls
+                                        .iter()
+                                        .map(|tool_call| tool_call.name.as_str())
+                                        .collect::<Vec<_>>()
+                                        .join(", ");
+                                    h_flex()
+                                        .id("tool_calls")
+                                        .gap_0p5()
+                                        .tooltip(move |cx| {
+                                            Tooltip::text(format!("Called {tool_names}"), cx)
+                                        })
+                                        .child(
+                                            Icon::new(IconName::Bolt)
+                                                .size(IconSize::Small)
+                                                .color(Color::Muted),
+                                        )
+                                        .child(
+                                            Label::new(message.tool_calls.len().to_string())
+                                                .size(LabelSize::Small)
+                                                .color(Color::Muted),
+                                        )
+                                }))
                                 .children(
                                     if let MessageStatus::Error(error) = message.status.clone() {
                                         Some(
@@ -1634,6 +1945,151 @@ impl ContextEditor {
         });
     }
 
+    /// Keeps a block displaying each of the context's images above the line it is attached to.
+    fn update_image_blocks(&mut self, cx: &mut ViewContext<Self>) {
+        let line_height = cx.line_height();
+        let context = self.context.read(cx);
+        let valid_ids = context
+            .image_anchors(cx)
+            .map(|anchor| anchor.id)
+            .collect::<HashSet<_>>();
+        let removed_ids = self
+            .image_blocks
+            .keys()
+            .filter(|id| !valid_ids.contains(*id))
+            .copied()
+            .collect::<Vec<_>>();
+        let new_anchors = context
+            .image_anchors(cx)
+            .filter(|anchor| !self.image_blocks.contains_key(&anchor.id))
+            .cloned()
+            .collect::<Vec<_>>();
+        if removed_ids.is_empty() && new_anchors.is_empty() {
+            return;
+        }
+
+        let mut new_images = Vec::new();
+        for anchor in new_anchors {
+            let Some(image) = context.image(anchor.id) else {
+                continue;
+            };
+            let rendered_image = match self.rendered_images.get(&anchor.id) {
+                Some(rendered_image) => rendered_image.clone(),
+                None => {
+                    let Some(rendered_image) = render_image(image).log_err() else {
+                        continue;
+                    };
+                    self.rendered_images
+                        .insert(anchor.id, rendered_image.clone());
+                    rendered_image
+                }
+            };
+            let lines = (image.height as f32 / line_height.0)
+                .ceil()
+                .clamp(1., Self::MAX_IMAGE_LINES as f32) as u8;
+            let aspect_ratio = image.width as f32 / image.height.max(1) as f32;
+            new_images.push((anchor, rendered_image, lines, aspect_ratio));
+        }
+
+        self.editor.update(cx, |editor, cx| {
+            let buffer = editor.buffer().read(cx).snapshot(cx);
+            let excerpt_id = *buffer.as_singleton().unwrap().0;
+
+            let removed_blocks = removed_ids
+                .iter()
+                .filter_map(|id| self.image_blocks.remove(id))
+                .collect::<HashSet<_>>();
+            editor.remove_blocks(removed_blocks, None, cx);
+
+            let (ids, blocks): (Vec<_>, Vec<_>) = new_images
+                .into_iter()
+                .filter_map(|(anchor, rendered_image, lines, aspect_ratio)| {
+                    let position = buffer.anchor_in_excerpt(excerpt_id, anchor.start)?;
+                    let block = BlockProperties {
+                        position,
+                        height: lines,
+                        style: BlockStyle::Sticky,
+                        render: Box::new(move |cx| {
+                            let height = cx.line_height * lines as f32;
+                            h_flex()
+                                .pl(cx.gutter_dimensions.full_width())
+                                .h(height)
+                                .child(
+                                    img(rendered_image.clone())
+                                        .h(height)
+                                        .w(height * aspect_ratio)
+                                        .object_fit(ObjectFit::Contain),
+                                )
+                                .into_any_element()
+                        }),
+                        disposition: BlockDisposition::Above,
+                    };
+                    Some((anchor.id, block))
+                })
+                .unzip();
+            let block_ids = editor.insert_blocks(blocks, None, cx);
+            self.image_blocks.extend(ids.into_iter().zip(block_ids));
+        });
+    }
+
+    /// Attaches the images at the given paths to the context, each on its own
+    /// line below the cursor.
+    fn attach_images(&mut self, paths: Vec<PathBuf>, cx: &mut ViewContext<Self>) {
+        let paths = paths
+            .into_iter()
+            .filter(|path| LanguageModelImage::is_supported_path(path))
+            .collect::<Vec<_>>();
+        if paths.is_empty() {
+            return;
+        }
+
+        let fs = self.fs.clone();
+        cx.spawn(|this, mut cx| async move {
+            let images = cx
+                .background_executor()
+                .spawn(async move {
+                    let mut images = Vec::new();
+                    for path in paths {
+                        let bytes = fs.load_bytes(&path).await?;
+                        images.push(LanguageModelImage::from_bytes(&bytes)?);
+                    }
+                    anyhow::Ok(images)
+                })
+                .await?;
+
+            this.update(&mut cx, |this, cx| {
+                let cursor_row = this.editor.update(cx, |editor, cx| {
+                    editor.selections.newest::<Point>(cx).head().row
+                });
+                let cursor_offset = this.context.update(cx, |context, cx| {
+                    // Insert an empty line for every image, plus one to keep typing on.
+                    let line_end = context.buffer().update(cx, |buffer, cx| {
+                        let line_end =
+                            Point::new(cursor_row, buffer.line_len(cursor_row)).to_offset(buffer);
+                        buffer.edit(
+                            [(line_end..line_end, "\n".repeat(images.len() + 1))],
+                            None,
+                            cx,
+                        );
+                        line_end
+                    });
+                    let image_count = images.len();
+                    for (ix, image) in images.into_iter().enumerate() {
+                        let position = context.buffer().read(cx).anchor_after(line_end + ix + 1);
+                        context.insert_image(image, position, cx);
+                    }
+                    line_end + image_count + 1
+                });
+                this.editor.update(cx, |editor, cx| {
+                    editor.change_selections(None, cx, |selections| {
+                        selections.select_ranges([cursor_offset..cursor_offset])
+                    });
+                });
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn insert_selection(
         workspace: &mut Workspace,
         _: &InsertIntoEditor,
@@ -1800,7 +2256,7 @@ impl ContextEditor {
 
         let selections = self.editor.read(cx).selections.disjoint_anchors();
         let mut selections = selections.iter().peekable();
-        let selected_suggestions = context
+        let mut selected_suggestions = context
             .edit_suggestions()
             .iter()
             .filter(|suggestion| {
@@ -1828,6 +2284,13 @@ impl ContextEditor {
             })
             .cloned()
             .collect::<Vec<_>>();
+        // When no suggestion is selected, review every suggestion in the context.
+        if selected_suggestions.is_empty() {
+            selected_suggestions = context.edit_suggestions().to_vec();
+        }
+        if selected_suggestions.is_empty() {
+            return;
+        }
 
         let mut opened_buffers: HashMap<PathBuf, Task<Result<Model<Buffer>>>> = HashMap::default();
         project.update(cx, |project, cx| {
@@ -1910,42 +2373,18 @@ impl ContextEditor {
                 })
                 .await;
 
-            let mut project_transaction = ProjectTransaction::default();
-            let (editor, workspace, title) = this.update(&mut cx, |this, cx| {
-                for (buffer_handle, edits) in edits_by_buffer {
-                    buffer_handle.update(cx, |buffer, cx| {
-                        buffer.start_transaction();
-                        buffer.edit(
-                            edits,
-                            Some(AutoindentMode::Block {
-                                original_indent_columns: Vec::new(),
-                            }),
-                            cx,
-                        );
-                        buffer.end_transaction(cx);
-                        if let Some(transaction) = buffer.finalize_last_transaction() {
-                            project_transaction
-                                .0
-                                .insert(buffer_handle.clone(), transaction.clone());
-                        }
-                    });
-                }
-
-                (
-                    this.editor.downgrade(),
-                    this.workspace.clone(),
-                    this.title(cx),
-                )
-            })?;
-
-            Editor::open_project_transaction(
-                &editor,
-                workspace,
-                project_transaction,
-                format!("Edits from {}", title),
-                cx,
-            )
-            .await
+            this.update(&mut cx, |this, cx| {
+                let mut edits = edits_by_buffer.into_iter().collect::<Vec<_>>();
+                edits.sort_by_key(|(buffer, _)| {
+                    buffer.read(cx).file().map(|file| file.path().clone())
+                });
+                let title = format!("Edits from {}", this.title(cx));
+                this.workspace.update(cx, |workspace, cx| {
+                    let project = workspace.project().clone();
+                    let review = cx.new_view(|cx| EditReview::new(title, edits, project, cx));
+                    workspace.add_item_to_active_pane(Box::new(review), None, cx);
+                })
+            })?
         })
         .detach_and_log_err(cx);
     }
@@ -1963,6 +2402,21 @@ impl ContextEditor {
             .unwrap_or_else(|| "New Context".into())
     }
 
+    fn render_review_edits_button(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let focus_handle = self.focus_handle(cx).clone();
+        ButtonLike::new("review_edits_button")
+            .style(ButtonStyle::Filled)
+            .layer(ElevationIndex::ModalSurface)
+            .children(
+                KeyBinding::for_action_in(&ApplyEdit, &focus_handle, cx)
+                    .map(|binding| binding.into_any_element()),
+            )
+            .child(Label::new("Review Edits"))
+            .on_click(move |_event, cx| {
+                focus_handle.dispatch_action(&ApplyEdit, cx);
+            })
+    }
+
     fn render_send_button(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
         let focus_handle = self.focus_handle(cx).clone();
         ButtonLike::new("send_button")
@@ -1979,6 +2433,16 @@ impl ContextEditor {
     }
 }
 
+/// Decodes an image into the BGRA pixels expected by GPUI.
+fn render_image(source: &LanguageModelImage) -> Result<Arc<ImageData>> {
+    let bytes = base64::decode(&source.data)?;
+    let mut data = image::load_from_memory(&bytes)?.into_rgba8();
+    for pixel in data.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
+    Ok(Arc::new(ImageData::new(data)))
+}
+
 impl EventEmitter<EditorEvent> for ContextEditor {}
 impl EventEmitter<SearchEvent> for ContextEditor {}
 
@@ -2007,10 +2471,30 @@ impl Render for ContextEditor {
                             .absolute()
                             .bottom_0()
                             .p_4()
+                            .gap_2()
                             .justify_end()
+                            .when(
+                                !self.context.read(cx).edit_suggestions().is_empty(),
+                                |this| this.child(self.render_review_edits_button(cx)),
+                            )
                             .child(self.render_send_button(cx)),
                     ),
             )
+            .when(!self.tool_calls.is_empty(), |this| {
+                this.child(
+                    v_flex()
+                        .p_2()
+                        .gap_1()
+                        .border_t_1()
+                        .border_color(cx.theme().colors().border_variant)
+                        .bg(cx.theme().colors().editor_background)
+                        .children(
+                            self.tool_calls
+                                .iter()
+                                .filter_map(|call| self.tool_registry.view_for_tool_call(call)),
+                        ),
+                )
+            })
     }
 }
 
@@ -2381,12 +2865,8 @@ impl ContextEditorToolbarItem {
 
     fn render_remaining_tokens(&self, cx: &mut ViewContext<Self>) -> Option<impl IntoElement> {
         let model = CompletionProvider::global(cx).model();
-        let context = &self
-            .active_context_editor
-            .as_ref()?
-            .upgrade()?
-            .read(cx)
-            .context;
+        let active_context_editor = self.active_context_editor.clone()?;
+        let context = &active_context_editor.upgrade()?.read(cx).context;
         let token_count = context.read(cx).token_count()?;
         let max_token_count = model.max_token_count();
 
@@ -2398,21 +2878,79 @@ impl ContextEditorToolbarItem {
         } else {
             Color::Muted
         };
+        let budget_used = (token_count as f32 / max_token_count.max(1) as f32).min(1.);
+        let budget_color = match token_count_color {
+            Color::Muted => Color::Accent,
+            color => color,
+        };
 
         Some(
-            h_flex()
-                .gap_0p5()
-                .child(
-                    Label::new(humanize_token_count(token_count))
-                        .size(LabelSize::Small)
-                        .color(token_count_color),
+            PopoverMenu::new("token-budget-menu")
+                .trigger(
+                    ButtonLike::new("token-budget")
+                        .child(
+                            h_flex()
+                                .gap_1()
+                                .child(
+                                    div()
+                                        .w(px(48.))
+                                        .h(px(4.))
+                                        .rounded_sm()
+                                        .bg(cx.theme().colors().element_background)
+                                        .child(
+                                            div()
+                                                .h_full()
+                                                .w(relative(budget_used))
+                                                .rounded_sm()
+                                                .bg(budget_color.color(cx)),
+                                        ),
+                                )
+                                .child(
+                                    h_flex()
+                                        .gap_0p5()
+                                        .child(
+                                            Label::new(humanize_token_count(token_count))
+                                                .size(LabelSize::Small)
+                                                .color(token_count_color),
+                                        )
+                                        .child(
+                                            Label::new("/")
+                                                .size(LabelSize::Small)
+                                                .color(Color::Muted),
+                                        )
+                                        .child(
+                                            Label::new(humanize_token_count(max_token_count))
+                                                .size(LabelSize::Small)
+                                                .color(Color::Muted),
+                                        ),
+                                ),
+                        )
+                        .tooltip(move |cx| {
+                            Tooltip::with_meta(
+                                format!("{} tokens remaining", remaining_tokens.max(0)),
+                                None,
+                                "Click to trim the context",
+                                cx,
+                            )
+                        }),
                 )
-                .child(Label::new("/").size(LabelSize::Small).color(Color::Muted))
-                .child(
-                    Label::new(humanize_token_count(max_token_count))
-                        .size(LabelSize::Small)
-                        .color(Color::Muted),
-                ),
+                .menu(move |cx| {
+                    let active_context_editor = active_context_editor.clone();
+                    Some(ContextMenu::build(cx, |mut menu, _cx| {
+                        menu = menu.header("Trim Context");
+                        for strategy in TrimStrategy::ALL {
+                            let active_context_editor = active_context_editor.clone();
+                            menu = menu.entry(strategy.label(), None, move |cx| {
+                                active_context_editor
+                                    .update(cx, |context_editor, cx| {
+                                        context_editor.trim_context(strategy, cx)
+                                    })
+                                    .ok();
+                            });
+                        }
+                        menu
+                    }))
+                }),
         )
     }
 }
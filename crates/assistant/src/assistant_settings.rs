@@ -1,7 +1,8 @@
-use std::fmt;
+use std::{collections::BTreeMap, fmt, path::PathBuf};
 
 use crate::{preprocess_anthropic_request, LanguageModel, LanguageModelRequest};
 pub use anthropic::Model as AnthropicModel;
+pub use google_ai::Model as GoogleModel;
 use gpui::Pixels;
 pub use ollama::Model as OllamaModel;
 pub use open_ai::Model as OpenAiModel;
@@ -158,6 +159,97 @@ impl CloudModel {
     }
 }
 
+/// A model served by one of the configured OpenAI-compatible providers.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct OpenAiCompatibleModel {
+    /// The name of the provider serving the model.
+    pub provider: String,
+    pub name: String,
+    pub max_tokens: usize,
+}
+
+/// An API that implements OpenAI's chat completions endpoint, such as vLLM or LM Studio.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct OpenAiCompatibleProviderSettings {
+    /// The name identifying this provider in the `provider` setting and in the model selector.
+    pub name: String,
+    /// The base URL of the API, e.g. `http://localhost:8000/v1`.
+    pub api_url: String,
+    /// The environment variable to read the API key from. When unset, requests are
+    /// sent without an API key.
+    ///
+    /// Default: null
+    #[serde(default)]
+    pub api_key_env_var: Option<String>,
+    /// The models served by this provider.
+    ///
+    /// Default: []
+    #[serde(default)]
+    pub available_models: Vec<OpenAiCompatibleAvailableModel>,
+    /// Additional HTTP headers sent with every request.
+    ///
+    /// Default: {}
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// How long to wait, in seconds, for a slow response before giving up.
+    ///
+    /// Default: null
+    #[serde(default)]
+    pub low_speed_timeout_in_seconds: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct OpenAiCompatibleAvailableModel {
+    /// The name of the model, as expected by the API.
+    pub name: String,
+    /// The context length of the model, in tokens.
+    pub max_tokens: usize,
+}
+
+/// The context length assumed for models missing from a provider's `available_models`.
+const DEFAULT_OPENAI_COMPATIBLE_MAX_TOKENS: usize = 4096;
+
+impl OpenAiCompatibleProviderSettings {
+    pub fn models(&self) -> Vec<OpenAiCompatibleModel> {
+        self.available_models
+            .iter()
+            .map(|model| OpenAiCompatibleModel {
+                provider: self.name.clone(),
+                name: model.name.clone(),
+                max_tokens: model.max_tokens,
+            })
+            .collect()
+    }
+
+    /// Returns the model with the given name, or the first available model if no name is given.
+    pub fn model(&self, name: Option<&str>) -> OpenAiCompatibleModel {
+        let name = name.or_else(|| {
+            self.available_models
+                .first()
+                .map(|model| model.name.as_str())
+        });
+        let max_tokens = self
+            .available_models
+            .iter()
+            .find(|model| Some(model.name.as_str()) == name)
+            .map_or(DEFAULT_OPENAI_COMPATIBLE_MAX_TOKENS, |model| {
+                model.max_tokens
+            });
+        OpenAiCompatibleModel {
+            provider: self.name.clone(),
+            name: name.unwrap_or_default().to_string(),
+            max_tokens,
+        }
+    }
+
+    pub fn headers(&self) -> Vec<(String, String)> {
+        self.headers
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect()
+    }
+}
+
 #[derive(Copy, Clone, Default, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum AssistantDockPosition {
@@ -183,11 +275,20 @@ pub enum AssistantProvider {
         api_url: String,
         low_speed_timeout_in_seconds: Option<u64>,
     },
+    Google {
+        model: GoogleModel,
+        api_url: String,
+        low_speed_timeout_in_seconds: Option<u64>,
+    },
     Ollama {
         model: OllamaModel,
         api_url: String,
         low_speed_timeout_in_seconds: Option<u64>,
     },
+    OpenAiCompatible {
+        provider_name: String,
+        model: Option<String>,
+    },
 }
 
 impl Default for AssistantProvider {
@@ -219,12 +320,24 @@ pub enum AssistantProviderContent {
         api_url: Option<String>,
         low_speed_timeout_in_seconds: Option<u64>,
     },
+    #[serde(rename = "google")]
+    Google {
+        default_model: Option<GoogleModel>,
+        api_url: Option<String>,
+        low_speed_timeout_in_seconds: Option<u64>,
+    },
     #[serde(rename = "ollama")]
     Ollama {
         default_model: Option<OllamaModel>,
         api_url: Option<String>,
         low_speed_timeout_in_seconds: Option<u64>,
     },
+    #[serde(rename = "openai_compatible")]
+    OpenAiCompatible {
+        /// The name of one of the `openai_compatible_providers`.
+        provider_name: String,
+        default_model: Option<String>,
+    },
 }
 
 #[derive(Debug, Default)]
@@ -235,6 +348,19 @@ pub struct AssistantSettings {
     pub default_width: Pixels,
     pub default_height: Pixels,
     pub provider: AssistantProvider,
+    pub openai_compatible_providers: Vec<OpenAiCompatibleProviderSettings>,
+    pub python_package_docs_worktrees: Vec<PathBuf>,
+}
+
+impl AssistantSettings {
+    pub fn openai_compatible_provider(
+        &self,
+        name: &str,
+    ) -> Option<&OpenAiCompatibleProviderSettings> {
+        self.openai_compatible_providers
+            .iter()
+            .find(|provider| provider.name == name)
+    }
 }
 
 /// Assistant panel settings
@@ -277,6 +403,8 @@ impl AssistantSettingsContent {
                 dock: settings.dock,
                 default_width: settings.default_width,
                 default_height: settings.default_height,
+                openai_compatible_providers: None,
+                python_package_docs_worktrees: None,
                 provider: if let Some(open_ai_api_url) = settings.openai_api_url.as_ref() {
                     Some(AssistantProviderContent::OpenAi {
                         default_model: settings.default_open_ai_model.clone(),
@@ -314,45 +442,77 @@ impl AssistantSettingsContent {
     pub fn set_model(&mut self, new_model: LanguageModel) {
         match self {
             AssistantSettingsContent::Versioned(settings) => match settings {
-                VersionedAssistantSettingsContent::V1(settings) => match &mut settings.provider {
-                    Some(AssistantProviderContent::ZedDotDev {
-                        default_model: model,
-                    }) => {
-                        if let LanguageModel::Cloud(new_model) = new_model {
+                VersionedAssistantSettingsContent::V1(settings) => {
+                    match (&mut settings.provider, new_model) {
+                        // Models of OpenAI-compatible providers can be picked regardless of the
+                        // current provider.
+                        (provider, LanguageModel::OpenAiCompatible(model)) => {
+                            *provider = Some(AssistantProviderContent::OpenAiCompatible {
+                                provider_name: model.provider,
+                                default_model: Some(model.name),
+                            });
+                        }
+                        (
+                            Some(AssistantProviderContent::ZedDotDev {
+                                default_model: model,
+                            }),
+                            LanguageModel::Cloud(new_model),
+                        ) => {
                             *model = Some(new_model);
                         }
-                    }
-                    Some(AssistantProviderContent::OpenAi {
-                        default_model: model,
-                        ..
-                    }) => {
-                        if let LanguageModel::OpenAi(new_model) = new_model {
+                        (
+                            Some(AssistantProviderContent::OpenAi {
+                                default_model: model,
+                                ..
+                            }),
+                            LanguageModel::OpenAi(new_model),
+                        ) => {
                             *model = Some(new_model);
                         }
-                    }
-                    Some(AssistantProviderContent::Anthropic {
-                        default_model: model,
-                        ..
-                    }) => {
-                        if let LanguageModel::Anthropic(new_model) = new_model {
+                        (
+                            Some(AssistantProviderContent::Anthropic {
+                                default_model: model,
+                                ..
+                            }),
+                            LanguageModel::Anthropic(new_model),
+                        ) => {
                             *model = Some(new_model);
                         }
-                    }
-                    Some(AssistantProviderContent::Ollama {
-                        default_model: model,
-                        ..
-                    }) => {
-                        if let LanguageModel::Ollama(new_model) = new_model {
+                        (
+                            Some(AssistantProviderContent::Google {
+                                default_model: model,
+                                ..
+                            }),
+                            LanguageModel::Google(new_model),
+                        ) => {
                             *model = Some(new_model);
                         }
-                    }
-                    provider => match new_model {
-                        LanguageModel::Cloud(model) => {
+                        (
+                            Some(AssistantProviderContent::Ollama {
+                                default_model: model,
+                                ..
+                            }),
+                            LanguageModel::Ollama(new_model),
+                        ) => {
+                            *model = Some(new_model);
+                        }
+                        // Models of other providers than the configured one are ignored.
+                        (
+                            Some(
+                                AssistantProviderContent::ZedDotDev { .. }
+                                | AssistantProviderContent::OpenAi { .. }
+                                | AssistantProviderContent::Anthropic { .. }
+                                | AssistantProviderContent::Google { .. }
+                                | AssistantProviderContent::Ollama { .. },
+                            ),
+                            _,
+                        ) => {}
+                        (provider, LanguageModel::Cloud(model)) => {
                             *provider = Some(AssistantProviderContent::ZedDotDev {
                                 default_model: Some(model),
                             })
                         }
-                        LanguageModel::OpenAi(model) => {
+                        (provider, LanguageModel::OpenAi(model)) => {
                             *provider = Some(AssistantProviderContent::OpenAi {
                                 default_model: Some(model),
                                 api_url: None,
@@ -360,22 +520,29 @@ impl AssistantSettingsContent {
                                 available_models: Some(Default::default()),
                             })
                         }
-                        LanguageModel::Anthropic(model) => {
+                        (provider, LanguageModel::Anthropic(model)) => {
                             *provider = Some(AssistantProviderContent::Anthropic {
                                 default_model: Some(model),
                                 api_url: None,
                                 low_speed_timeout_in_seconds: None,
                             })
                         }
-                        LanguageModel::Ollama(model) => {
+                        (provider, LanguageModel::Google(model)) => {
+                            *provider = Some(AssistantProviderContent::Google {
+                                default_model: Some(model),
+                                api_url: None,
+                                low_speed_timeout_in_seconds: None,
+                            })
+                        }
+                        (provider, LanguageModel::Ollama(model)) => {
                             *provider = Some(AssistantProviderContent::Ollama {
                                 default_model: Some(model),
                                 api_url: None,
                                 low_speed_timeout_in_seconds: None,
                             })
                         }
-                    },
-                },
+                    }
+                }
             },
             AssistantSettingsContent::Legacy(settings) => {
                 if let LanguageModel::OpenAi(model) = new_model {
@@ -402,6 +569,8 @@ impl Default for VersionedAssistantSettingsContent {
             default_width: None,
             default_height: None,
             provider: None,
+            openai_compatible_providers: None,
+            python_package_docs_worktrees: None,
         })
     }
 }
@@ -430,9 +599,22 @@ pub struct AssistantSettingsContentV1 {
     default_height: Option<f32>,
     /// The provider of the assistant service.
     ///
-    /// This can either be the internal `zed.dev` service or an external `openai` service,
-    /// each with their respective default models and configurations.
+    /// This can either be the internal `zed.dev` service or an external `openai`,
+    /// `anthropic`, `google` or `ollama` service, each with their respective default
+    /// models and configurations.
     provider: Option<AssistantProviderContent>,
+    /// OpenAI-compatible APIs that can be used as the provider of the assistant service,
+    /// by setting the provider's name to `openai_compatible` and its `provider_name` to
+    /// the name of one of these.
+    ///
+    /// Default: []
+    openai_compatible_providers: Option<Vec<OpenAiCompatibleProviderSettings>>,
+    /// The directories in which `/docs python` may index packages, which runs the Python of
+    /// the project's virtual environment to import them. Only read from the user settings,
+    /// so that a project can't allow itself.
+    ///
+    /// Default: []
+    python_package_docs_worktrees: Option<Vec<PathBuf>>,
 }
 
 #[derive(Clone, Serialize, Deserialize, JsonSchema, Debug)]
@@ -487,6 +669,10 @@ impl Settings for AssistantSettings {
                 &mut settings.default_height,
                 value.default_height.map(Into::into),
             );
+            merge(
+                &mut settings.openai_compatible_providers,
+                value.openai_compatible_providers.clone(),
+            );
             if let Some(provider) = value.provider.clone() {
                 match (&mut settings.provider, provider) {
                     (
@@ -563,6 +749,45 @@ impl Settings for AssistantSettings {
                                 Some(low_speed_timeout_in_seconds_override);
                         }
                     }
+                    (
+                        AssistantProvider::Google {
+                            model,
+                            api_url,
+                            low_speed_timeout_in_seconds,
+                        },
+                        AssistantProviderContent::Google {
+                            default_model: model_override,
+                            api_url: api_url_override,
+                            low_speed_timeout_in_seconds: low_speed_timeout_in_seconds_override,
+                        },
+                    ) => {
+                        merge(model, model_override);
+                        merge(api_url, api_url_override);
+                        if let Some(low_speed_timeout_in_seconds_override) =
+                            low_speed_timeout_in_seconds_override
+                        {
+                            *low_speed_timeout_in_seconds =
+                                Some(low_speed_timeout_in_seconds_override);
+                        }
+                    }
+                    (
+                        AssistantProvider::OpenAiCompatible {
+                            provider_name,
+                            model,
+                        },
+                        AssistantProviderContent::OpenAiCompatible {
+                            provider_name: provider_name_override,
+                            default_model: model_override,
+                        },
+                    ) => {
+                        if *provider_name != provider_name_override {
+                            *provider_name = provider_name_override;
+                            *model = None;
+                        }
+                        if model_override.is_some() {
+                            *model = model_override;
+                        }
+                    }
                     (provider, provider_override) => {
                         *provider = match provider_override {
                             AssistantProviderContent::ZedDotDev {
@@ -591,6 +816,15 @@ impl Settings for AssistantSettings {
                                     .unwrap_or_else(|| anthropic::ANTHROPIC_API_URL.into()),
                                 low_speed_timeout_in_seconds,
                             },
+                            AssistantProviderContent::Google {
+                                default_model: model,
+                                api_url,
+                                low_speed_timeout_in_seconds,
+                            } => AssistantProvider::Google {
+                                model: model.unwrap_or_default(),
+                                api_url: api_url.unwrap_or_else(|| google_ai::API_URL.into()),
+                                low_speed_timeout_in_seconds,
+                            },
                             AssistantProviderContent::Ollama {
                                 default_model: model,
                                 api_url,
@@ -600,12 +834,30 @@ impl Settings for AssistantSettings {
                                 api_url: api_url.unwrap_or_else(|| ollama::OLLAMA_API_URL.into()),
                                 low_speed_timeout_in_seconds,
                             },
+                            AssistantProviderContent::OpenAiCompatible {
+                                provider_name,
+                                default_model,
+                            } => AssistantProvider::OpenAiCompatible {
+                                provider_name,
+                                model: default_model,
+                            },
                         };
                     }
                 }
             }
         }
 
+        for value in [sources.default]
+            .into_iter()
+            .chain(sources.user)
+            .chain(sources.release_channel)
+        {
+            merge(
+                &mut settings.python_package_docs_worktrees,
+                value.upgrade().python_package_docs_worktrees,
+            );
+        }
+
         Ok(settings)
     }
 }
@@ -620,6 +872,7 @@ fn merge<T>(target: &mut T, value: Option<T>) {
 mod tests {
     use gpui::{AppContext, UpdateGlobal};
     use settings::SettingsStore;
+    use std::path::Path;
 
     use super::*;
 
@@ -707,5 +960,149 @@ mod tests {
                 model: CloudModel::Custom("custom".into())
             }
         );
+
+        // Google AI can be configured with one of its models and a custom API URL.
+        SettingsStore::update_global(cx, |store, cx| {
+            store
+                .set_user_settings(
+                    r#"{
+                        "assistant": {
+                            "version": "1",
+                            "provider": {
+                                "name": "google",
+                                "default_model": "gemini-1.5-pro",
+                                "api_url": "test-url"
+                            }
+                        }
+                    }"#,
+                    cx,
+                )
+                .unwrap();
+        });
+        assert_eq!(
+            AssistantSettings::get_global(cx).provider,
+            AssistantProvider::Google {
+                model: GoogleModel::Gemini15Pro,
+                api_url: "test-url".into(),
+                low_speed_timeout_in_seconds: None,
+            }
+        );
+
+        // Several OpenAI-compatible providers can be configured, and one of them used.
+        SettingsStore::update_global(cx, |store, cx| {
+            store
+                .set_user_settings(
+                    r#"{
+                        "assistant": {
+                            "version": "1",
+                            "provider": {
+                                "name": "openai_compatible",
+                                "provider_name": "lm-studio"
+                            },
+                            "openai_compatible_providers": [
+                                {
+                                    "name": "vllm",
+                                    "api_url": "http://localhost:8000/v1",
+                                    "api_key_env_var": "VLLM_API_KEY",
+                                    "available_models": [
+                                        { "name": "llama-3-70b", "max_tokens": 8192 }
+                                    ],
+                                    "headers": { "X-Team": "editor" }
+                                },
+                                {
+                                    "name": "lm-studio",
+                                    "api_url": "http://localhost:1234/v1",
+                                    "available_models": [
+                                        { "name": "qwen2-7b", "max_tokens": 32768 },
+                                        { "name": "phi-3-mini", "max_tokens": 4096 }
+                                    ]
+                                }
+                            ]
+                        }
+                    }"#,
+                    cx,
+                )
+                .unwrap();
+        });
+        let settings = AssistantSettings::get_global(cx);
+        assert_eq!(
+            settings.provider,
+            AssistantProvider::OpenAiCompatible {
+                provider_name: "lm-studio".into(),
+                model: None,
+            }
+        );
+        let vllm = settings.openai_compatible_provider("vllm").unwrap();
+        assert_eq!(vllm.api_key_env_var.as_deref(), Some("VLLM_API_KEY"));
+        assert_eq!(
+            vllm.headers(),
+            [("X-Team".to_string(), "editor".to_string())]
+        );
+        let lm_studio = settings.openai_compatible_provider("lm-studio").unwrap();
+        assert_eq!(
+            lm_studio.model(None),
+            OpenAiCompatibleModel {
+                provider: "lm-studio".into(),
+                name: "qwen2-7b".into(),
+                max_tokens: 32768,
+            }
+        );
+        assert_eq!(lm_studio.model(Some("phi-3-mini")).max_tokens, 4096);
+        assert_eq!(lm_studio.model(Some("unlisted")).max_tokens, 4096);
+    }
+
+    #[gpui::test]
+    fn test_python_package_docs_worktrees(cx: &mut AppContext) {
+        let store = settings::SettingsStore::test(cx);
+        cx.set_global(store);
+        AssistantSettings::register(cx);
+        assert!(AssistantSettings::get_global(cx)
+            .python_package_docs_worktrees
+            .is_empty());
+
+        SettingsStore::update_global(cx, |store, cx| {
+            store
+                .set_user_settings(
+                    r#"{
+                        "assistant": {
+                            "version": "1",
+                            "python_package_docs_worktrees": ["/code/allowed"]
+                        }
+                    }"#,
+                    cx,
+                )
+                .unwrap();
+            store
+                .set_local_settings(
+                    1,
+                    Path::new("/code/untrusted").into(),
+                    Some(
+                        r#"{
+                            "assistant": {
+                                "version": "1",
+                                "python_package_docs_worktrees": ["/code/untrusted"]
+                            }
+                        }"#,
+                    ),
+                    cx,
+                )
+                .unwrap();
+        });
+
+        // A project's own settings can't allow running its Python.
+        let expected = vec![PathBuf::from("/code/allowed")];
+        assert_eq!(
+            AssistantSettings::get_global(cx).python_package_docs_worktrees,
+            expected
+        );
+        assert_eq!(
+            cx.global::<SettingsStore>()
+                .get::<AssistantSettings>(Some(settings::SettingsLocation {
+                    worktree_id: 1,
+                    path: Path::new("/code/untrusted/main.py"),
+                }))
+                .python_package_docs_worktrees,
+            expected
+        );
     }
 }
@@ -2,30 +2,49 @@ mod anthropic;
 mod cloud;
 #[cfg(any(test, feature = "test-support"))]
 mod fake;
+mod google;
 mod ollama;
 mod open_ai;
+mod open_ai_compatible;
 
 pub use anthropic::*;
 pub use cloud::*;
 #[cfg(any(test, feature = "test-support"))]
 pub use fake::*;
+pub use google::*;
 pub use ollama::*;
 pub use open_ai::*;
+pub use open_ai_compatible::*;
 use parking_lot::RwLock;
 use smol::lock::{Semaphore, SemaphoreGuardArc};
 
 use crate::{
-    assistant_settings::{AssistantProvider, AssistantSettings},
-    LanguageModel, LanguageModelRequest,
+    assistant_settings::{AssistantProvider, AssistantSettings, OpenAiCompatibleProviderSettings},
+    LanguageModel, LanguageModelRequest, LanguageModelToolCall,
 };
 use anyhow::Result;
 use client::Client;
-use futures::{future::BoxFuture, stream::BoxStream};
+use futures::{future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
 use gpui::{AnyView, AppContext, BorrowAppContext, Task, WindowContext};
 use settings::{Settings, SettingsStore};
 use std::time::Duration;
 use std::{any::Any, sync::Arc};
 
+/// Returns the settings of the OpenAI-compatible provider with the given name, or empty settings
+/// if there's none, so that completions fail with an explanation.
+fn openai_compatible_provider_settings(
+    settings: &AssistantSettings,
+    provider_name: &str,
+) -> OpenAiCompatibleProviderSettings {
+    settings
+        .openai_compatible_provider(provider_name)
+        .cloned()
+        .unwrap_or_else(|| OpenAiCompatibleProviderSettings {
+            name: provider_name.to_string(),
+            ..Default::default()
+        })
+}
+
 /// Choose which model to use for openai provider.
 /// If the model is not available, try to use the first available model, or fallback to the original model.
 fn choose_openai_model(
@@ -59,6 +78,19 @@ pub struct CompletionResponse {
     _lock: SemaphoreGuardArc,
 }
 
+pub struct ToolCompletionResponse {
+    pub inner: BoxFuture<'static, Result<BoxStream<'static, Result<CompletionEvent>>>>,
+    _lock: SemaphoreGuardArc,
+}
+
+/// An event streamed by a completion that was given tools to call.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CompletionEvent {
+    Text(String),
+    /// A complete tool call. Calls are only reported once all of their arguments were streamed.
+    ToolCall(LanguageModelToolCall),
+}
+
 pub trait LanguageModelCompletionProvider: Send + Sync {
     fn available_models(&self, cx: &AppContext) -> Vec<LanguageModel>;
     fn settings_version(&self) -> usize;
@@ -77,6 +109,24 @@ pub trait LanguageModelCompletionProvider: Send + Sync {
         request: LanguageModelRequest,
     ) -> BoxFuture<'static, Result<BoxStream<'static, Result<String>>>>;
 
+    /// Whether the tools in a request are passed to the model, which may then ask to call them.
+    fn supports_tools(&self) -> bool {
+        false
+    }
+
+    /// Streams a completion that may contain tool calls, in addition to text.
+    fn complete_with_tools(
+        &self,
+        request: LanguageModelRequest,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<CompletionEvent>>>> {
+        let completion = self.complete(request);
+        async move {
+            let stream = completion.await?;
+            Ok(stream.map(|text| text.map(CompletionEvent::Text)).boxed())
+        }
+        .boxed()
+    }
+
     fn as_any_mut(&mut self) -> &mut dyn Any;
 }
 
@@ -152,6 +202,27 @@ impl CompletionProvider {
             }
         })
     }
+
+    pub fn supports_tools(&self) -> bool {
+        self.provider.read().supports_tools()
+    }
+
+    pub fn complete_with_tools(
+        &self,
+        request: LanguageModelRequest,
+        cx: &AppContext,
+    ) -> Task<ToolCompletionResponse> {
+        let rate_limiter = self.request_limiter.clone();
+        let provider = self.provider.clone();
+        cx.background_executor().spawn(async move {
+            let lock = rate_limiter.acquire_arc().await;
+            let response = provider.read().complete_with_tools(request);
+            ToolCompletionResponse {
+                inner: response,
+                _lock: lock,
+            }
+        })
+    }
 }
 
 impl gpui::Global for CompletionProvider {}
@@ -174,7 +245,8 @@ impl CompletionProvider {
     }
 
     pub fn update_settings(&mut self, version: usize, cx: &mut AppContext) {
-        let updated = match &AssistantSettings::get_global(cx).provider {
+        let settings = AssistantSettings::get_global(cx);
+        let updated = match &settings.provider {
             AssistantProvider::ZedDotDev { model } => self
                 .update_current_as::<_, CloudCompletionProvider>(|provider| {
                     provider.update(model.clone(), version);
@@ -204,6 +276,18 @@ impl CompletionProvider {
                     version,
                 );
             }),
+            AssistantProvider::Google {
+                model,
+                api_url,
+                low_speed_timeout_in_seconds,
+            } => self.update_current_as::<_, GoogleCompletionProvider>(|provider| {
+                provider.update(
+                    model.clone(),
+                    api_url.clone(),
+                    low_speed_timeout_in_seconds.map(Duration::from_secs),
+                    version,
+                );
+            }),
             AssistantProvider::Ollama {
                 model,
                 api_url,
@@ -217,6 +301,16 @@ impl CompletionProvider {
                     cx,
                 );
             }),
+            AssistantProvider::OpenAiCompatible {
+                provider_name,
+                model,
+            } => self.update_current_as::<_, OpenAiCompatibleCompletionProvider>(|provider| {
+                provider.update(
+                    openai_compatible_provider_settings(settings, provider_name),
+                    model.clone(),
+                    version,
+                );
+            }),
         };
 
         // Previously configured provider was changed to another one
@@ -235,7 +329,8 @@ fn create_provider_from_settings(
     settings_version: usize,
     cx: &mut AppContext,
 ) -> Arc<RwLock<dyn LanguageModelCompletionProvider>> {
-    match &AssistantSettings::get_global(cx).provider {
+    let settings = AssistantSettings::get_global(cx);
+    match &settings.provider {
         AssistantProvider::ZedDotDev { model } => Arc::new(RwLock::new(
             CloudCompletionProvider::new(model.clone(), client.clone(), settings_version, cx),
         )),
@@ -262,6 +357,17 @@ fn create_provider_from_settings(
             low_speed_timeout_in_seconds.map(Duration::from_secs),
             settings_version,
         ))),
+        AssistantProvider::Google {
+            model,
+            api_url,
+            low_speed_timeout_in_seconds,
+        } => Arc::new(RwLock::new(GoogleCompletionProvider::new(
+            model.clone(),
+            api_url.clone(),
+            client.http_client(),
+            low_speed_timeout_in_seconds.map(Duration::from_secs),
+            settings_version,
+        ))),
         AssistantProvider::Ollama {
             model,
             api_url,
@@ -274,6 +380,15 @@ fn create_provider_from_settings(
             settings_version,
             cx,
         ))),
+        AssistantProvider::OpenAiCompatible {
+            provider_name,
+            model,
+        } => Arc::new(RwLock::new(OpenAiCompatibleCompletionProvider::new(
+            openai_compatible_provider_settings(settings, provider_name),
+            model.clone(),
+            client.http_client(),
+            settings_version,
+        ))),
     }
 }
 
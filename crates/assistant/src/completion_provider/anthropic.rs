@@ -1,12 +1,15 @@
 use crate::{
-    assistant_settings::AnthropicModel, CompletionProvider, LanguageModel, LanguageModelRequest,
-    Role,
+    assistant_settings::AnthropicModel, CompletionEvent, CompletionProvider, LanguageModel,
+    LanguageModelImage, LanguageModelRequest, LanguageModelToolCall, Role,
 };
 use crate::{count_open_ai_tokens, LanguageModelCompletionProvider, LanguageModelRequestMessage};
-use anthropic::{stream_completion, Request, RequestMessage};
+use anthropic::{
+    stream_completion, ImageSource, MessageContent, Request, RequestContentBlock, RequestMessage,
+};
 use anyhow::{anyhow, Result};
+use collections::HashMap;
 use editor::{Editor, EditorElement, EditorStyle};
-use futures::{future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
+use futures::{future, future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
 use gpui::{AnyView, AppContext, FontStyle, Task, TextStyle, View, WhiteSpace};
 use http::HttpClient;
 use settings::Settings;
@@ -119,15 +122,13 @@ impl LanguageModelCompletionProvider for AnthropicCompletionProvider {
                     match response {
                         Ok(response) => match response {
                             anthropic::ResponseEvent::ContentBlockStart {
-                                content_block, ..
-                            } => match content_block {
-                                anthropic::ContentBlock::Text { text } => Some(Ok(text)),
-                            },
-                            anthropic::ResponseEvent::ContentBlockDelta { delta, .. } => {
-                                match delta {
-                                    anthropic::TextDelta::TextDelta { text } => Some(Ok(text)),
-                                }
-                            }
+                                content_block: anthropic::ContentBlock::Text { text },
+                                ..
+                            } => Some(Ok(text)),
+                            anthropic::ResponseEvent::ContentBlockDelta {
+                                delta: anthropic::TextDelta::TextDelta { text },
+                                ..
+                            } => Some(Ok(text)),
                             _ => None,
                         },
                         Err(error) => Some(Err(error)),
@@ -139,6 +140,94 @@ impl LanguageModelCompletionProvider for AnthropicCompletionProvider {
         .boxed()
     }
 
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    fn complete_with_tools(
+        &self,
+        request: LanguageModelRequest,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<CompletionEvent>>>> {
+        let request = self.to_anthropic_request(request);
+
+        let http_client = self.http_client.clone();
+        let api_key = self.api_key.clone();
+        let api_url = self.api_url.clone();
+        let low_speed_timeout = self.low_speed_timeout;
+        async move {
+            let api_key = api_key.ok_or_else(|| anyhow!("missing api key"))?;
+            let response = stream_completion(
+                http_client.as_ref(),
+                &api_url,
+                &api_key,
+                request,
+                low_speed_timeout,
+            )
+            .await?;
+
+            // Tool uses are streamed as content blocks whose input arrives in pieces,
+            // so they're reported once their block is complete.
+            let mut tool_uses = HashMap::<u32, LanguageModelToolCall>::default();
+            let stream = response
+                .filter_map(move |response| {
+                    let event = match response {
+                        Ok(anthropic::ResponseEvent::ContentBlockStart {
+                            index,
+                            content_block,
+                        }) => match content_block {
+                            anthropic::ContentBlock::Text { text } => {
+                                Some(Ok(CompletionEvent::Text(text)))
+                            }
+                            anthropic::ContentBlock::ToolUse { id, name, input } => {
+                                let arguments = match input {
+                                    serde_json::Value::Object(object) if !object.is_empty() => {
+                                        serde_json::Value::Object(object).to_string()
+                                    }
+                                    _ => String::new(),
+                                };
+                                tool_uses.insert(
+                                    index,
+                                    LanguageModelToolCall {
+                                        id,
+                                        name,
+                                        arguments,
+                                    },
+                                );
+                                None
+                            }
+                        },
+                        Ok(anthropic::ResponseEvent::ContentBlockDelta { index, delta }) => {
+                            match delta {
+                                anthropic::TextDelta::TextDelta { text } => {
+                                    Some(Ok(CompletionEvent::Text(text)))
+                                }
+                                anthropic::TextDelta::InputJsonDelta { partial_json } => {
+                                    if let Some(tool_use) = tool_uses.get_mut(&index) {
+                                        tool_use.arguments.push_str(&partial_json);
+                                    }
+                                    None
+                                }
+                            }
+                        }
+                        Ok(anthropic::ResponseEvent::ContentBlockStop { index }) => {
+                            tool_uses.remove(&index).map(|mut tool_use| {
+                                if tool_use.arguments.is_empty() {
+                                    tool_use.arguments = "{}".into();
+                                }
+                                Ok(CompletionEvent::ToolCall(tool_use))
+                            })
+                        }
+                        Ok(_) => None,
+                        Err(error) => Some(Err(error)),
+                    };
+                    future::ready(event)
+                })
+                .boxed();
+            Ok(stream)
+        }
+        .boxed()
+    }
+
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
         self
     }
@@ -196,29 +285,83 @@ impl AnthropicCompletionProvider {
             model,
             messages: request
                 .messages
-                .iter()
+                .into_iter()
                 .map(|msg| RequestMessage {
                     role: match msg.role {
                         Role::User => anthropic::Role::User,
                         Role::Assistant => anthropic::Role::Assistant,
                         Role::System => unreachable!("filtered out by preprocess_request"),
                     },
-                    content: msg.content.clone(),
+                    content: to_anthropic_content(msg),
                 })
                 .collect(),
             stream: true,
             system: system_message,
             max_tokens: 4092,
+            tools: request
+                .tools
+                .into_iter()
+                .map(|tool| anthropic::Tool {
+                    name: tool.name,
+                    description: tool.description,
+                    input_schema: serde_json::to_value(&tool.parameters).unwrap_or_default(),
+                })
+                .collect(),
         }
     }
 }
 
+/// Messages without tool calls or results are sent as plain text, while the others
+/// are broken down into content blocks, with tool results coming first as required
+/// by Anthropic's API.
+fn to_anthropic_content(message: LanguageModelRequestMessage) -> MessageContent {
+    if message.images.is_empty() && message.tool_calls.is_empty() && message.tool_results.is_empty()
+    {
+        return MessageContent::Text(message.content);
+    }
+
+    let mut blocks = Vec::new();
+    for result in message.tool_results {
+        blocks.push(RequestContentBlock::ToolResult {
+            tool_use_id: result.tool_call_id,
+            content: result.content,
+            is_error: result.is_error,
+        });
+    }
+    for image in message.images {
+        blocks.push(RequestContentBlock::Image {
+            source: ImageSource::Base64 {
+                media_type: LanguageModelImage::MEDIA_TYPE.into(),
+                data: image.data,
+            },
+        });
+    }
+    if !message.content.is_empty() {
+        blocks.push(RequestContentBlock::Text {
+            text: message.content,
+        });
+    }
+    for call in message.tool_calls {
+        blocks.push(RequestContentBlock::ToolUse {
+            id: call.id,
+            name: call.name,
+            input: serde_json::from_str(&call.arguments)
+                .unwrap_or_else(|_| serde_json::Value::Object(Default::default())),
+        });
+    }
+    MessageContent::Blocks(blocks)
+}
+
 pub fn preprocess_anthropic_request(request: &mut LanguageModelRequest) {
     let mut new_messages: Vec<LanguageModelRequestMessage> = Vec::new();
     let mut system_message = String::new();
 
     for message in request.messages.drain(..) {
-        if message.content.is_empty() {
+        if message.content.is_empty()
+            && message.images.is_empty()
+            && message.tool_calls.is_empty()
+            && message.tool_results.is_empty()
+        {
             continue;
         }
 
@@ -226,8 +369,15 @@ pub fn preprocess_anthropic_request(request: &mut LanguageModelRequest) {
             Role::User | Role::Assistant => {
                 if let Some(last_message) = new_messages.last_mut() {
                     if last_message.role == message.role {
-                        last_message.content.push_str("\n\n");
-                        last_message.content.push_str(&message.content);
+                        if !message.content.is_empty() {
+                            if !last_message.content.is_empty() {
+                                last_message.content.push_str("\n\n");
+                            }
+                            last_message.content.push_str(&message.content);
+                        }
+                        last_message.images.extend(message.images);
+                        last_message.tool_calls.extend(message.tool_calls);
+                        last_message.tool_results.extend(message.tool_results);
                         continue;
                     }
                 }
@@ -249,6 +399,9 @@ pub fn preprocess_anthropic_request(request: &mut LanguageModelRequest) {
             LanguageModelRequestMessage {
                 role: Role::System,
                 content: system_message,
+                images: Vec::new(),
+                tool_calls: Vec::new(),
+                tool_results: Vec::new(),
             },
         );
     }
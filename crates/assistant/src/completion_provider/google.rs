@@ -0,0 +1,505 @@
+use crate::{
+    assistant_settings::GoogleModel, CompletionProvider, LanguageModel, LanguageModelImage,
+    LanguageModelRequest, Role,
+};
+use crate::{LanguageModelCompletionProvider, LanguageModelRequestMessage};
+use anyhow::{anyhow, Result};
+use editor::{Editor, EditorElement, EditorStyle};
+use futures::{future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
+use google_ai::{
+    count_tokens, stream_generate_content, Content, CountTokensRequest, GenerateContentRequest,
+    GenerationConfig, GenerativeContentBlob, InlineDataPart, Part, TextPart,
+};
+use gpui::{AnyView, AppContext, FontStyle, Task, TextStyle, View, WhiteSpace};
+use http::HttpClient;
+use settings::Settings;
+use std::time::Duration;
+use std::{env, sync::Arc};
+use strum::IntoEnumIterator;
+use theme::ThemeSettings;
+use ui::prelude::*;
+use util::ResultExt;
+
+pub struct GoogleCompletionProvider {
+    api_key: Option<String>,
+    api_url: String,
+    model: GoogleModel,
+    http_client: Arc<dyn HttpClient>,
+    low_speed_timeout: Option<Duration>,
+    settings_version: usize,
+}
+
+impl LanguageModelCompletionProvider for GoogleCompletionProvider {
+    fn available_models(&self, _cx: &AppContext) -> Vec<LanguageModel> {
+        let mut models = GoogleModel::iter()
+            .filter(|model| !matches!(model, GoogleModel::Custom { .. }))
+            .collect::<Vec<_>>();
+        if let GoogleModel::Custom { .. } = &self.model {
+            models.push(self.model.clone());
+        }
+        models.into_iter().map(LanguageModel::Google).collect()
+    }
+
+    fn settings_version(&self) -> usize {
+        self.settings_version
+    }
+
+    fn is_authenticated(&self) -> bool {
+        self.api_key.is_some()
+    }
+
+    fn authenticate(&self, cx: &AppContext) -> Task<Result<()>> {
+        if self.is_authenticated() {
+            Task::ready(Ok(()))
+        } else {
+            let api_url = self.api_url.clone();
+            cx.spawn(|mut cx| async move {
+                let api_key = if let Ok(api_key) = env::var("GOOGLE_AI_API_KEY") {
+                    api_key
+                } else {
+                    let (_, api_key) = cx
+                        .update(|cx| cx.read_credentials(&api_url))?
+                        .await?
+                        .ok_or_else(|| anyhow!("credentials not found"))?;
+                    String::from_utf8(api_key)?
+                };
+                cx.update_global::<CompletionProvider, _>(|provider, _cx| {
+                    provider.update_current_as::<_, GoogleCompletionProvider>(|provider| {
+                        provider.api_key = Some(api_key);
+                    });
+                })
+            })
+        }
+    }
+
+    fn reset_credentials(&self, cx: &AppContext) -> Task<Result<()>> {
+        let delete_credentials = cx.delete_credentials(&self.api_url);
+        cx.spawn(|mut cx| async move {
+            delete_credentials.await.log_err();
+            cx.update_global::<CompletionProvider, _>(|provider, _cx| {
+                provider.update_current_as::<_, GoogleCompletionProvider>(|provider| {
+                    provider.api_key = None;
+                });
+            })
+        })
+    }
+
+    fn authentication_prompt(&self, cx: &mut WindowContext) -> AnyView {
+        cx.new_view(|cx| AuthenticationPrompt::new(self.api_url.clone(), cx))
+            .into()
+    }
+
+    fn model(&self) -> LanguageModel {
+        LanguageModel::Google(self.model.clone())
+    }
+
+    fn count_tokens(
+        &self,
+        request: LanguageModelRequest,
+        _cx: &AppContext,
+    ) -> BoxFuture<'static, Result<usize>> {
+        let model = self.model_for_request(&request);
+        let request = CountTokensRequest {
+            contents: to_google_contents(request.messages),
+        };
+
+        let http_client = self.http_client.clone();
+        let api_key = self.api_key.clone();
+        let api_url = self.api_url.clone();
+        async move {
+            let api_key = api_key.ok_or_else(|| anyhow!("missing api key"))?;
+            let response = count_tokens(
+                http_client.as_ref(),
+                &api_url,
+                &api_key,
+                model.id(),
+                request,
+            )
+            .await?;
+            Ok(response.total_tokens)
+        }
+        .boxed()
+    }
+
+    fn complete(
+        &self,
+        request: LanguageModelRequest,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<String>>>> {
+        let model = self.model_for_request(&request);
+        let request = to_google_request(request);
+
+        let http_client = self.http_client.clone();
+        let api_key = self.api_key.clone();
+        let api_url = self.api_url.clone();
+        let low_speed_timeout = self.low_speed_timeout;
+        async move {
+            let api_key = api_key.ok_or_else(|| anyhow!("missing api key"))?;
+            let response = stream_generate_content(
+                http_client.as_ref(),
+                &api_url,
+                &api_key,
+                model.id(),
+                request,
+                low_speed_timeout,
+            )
+            .await?;
+            let stream = response
+                .filter_map(|response| async move {
+                    match response {
+                        Ok(response) => {
+                            let text = response
+                                .candidates
+                                .into_iter()
+                                .flatten()
+                                .flat_map(|candidate| candidate.content.parts)
+                                .filter_map(|part| match part {
+                                    Part::TextPart(part) => Some(part.text),
+                                    Part::InlineDataPart(_) => None,
+                                })
+                                .collect::<String>();
+                            if text.is_empty() {
+                                None
+                            } else {
+                                Some(Ok(text))
+                            }
+                        }
+                        Err(error) => Some(Err(error)),
+                    }
+                })
+                .boxed();
+            Ok(stream)
+        }
+        .boxed()
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+impl GoogleCompletionProvider {
+    pub fn new(
+        model: GoogleModel,
+        api_url: String,
+        http_client: Arc<dyn HttpClient>,
+        low_speed_timeout: Option<Duration>,
+        settings_version: usize,
+    ) -> Self {
+        Self {
+            api_key: None,
+            api_url,
+            model,
+            http_client,
+            low_speed_timeout,
+            settings_version,
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        model: GoogleModel,
+        api_url: String,
+        low_speed_timeout: Option<Duration>,
+        settings_version: usize,
+    ) {
+        self.model = model;
+        self.api_url = api_url;
+        self.low_speed_timeout = low_speed_timeout;
+        self.settings_version = settings_version;
+    }
+
+    fn model_for_request(&self, request: &LanguageModelRequest) -> GoogleModel {
+        match &request.model {
+            LanguageModel::Google(model) => model.clone(),
+            _ => self.model.clone(),
+        }
+    }
+}
+
+fn to_google_request(request: LanguageModelRequest) -> GenerateContentRequest {
+    GenerateContentRequest {
+        contents: to_google_contents(request.messages),
+        generation_config: Some(GenerationConfig {
+            candidate_count: Some(1),
+            stop_sequences: (!request.stop.is_empty()).then_some(request.stop),
+            max_output_tokens: None,
+            temperature: Some(request.temperature as f64),
+            top_p: None,
+            top_k: None,
+        }),
+        safety_settings: None,
+    }
+}
+
+/// Gemini only knows about users and models, and expects the two to take turns,
+/// so system messages are sent as user messages and consecutive messages with the
+/// same role are merged.
+fn to_google_contents(messages: Vec<LanguageModelRequestMessage>) -> Vec<Content> {
+    let mut contents: Vec<(google_ai::Role, String, Vec<LanguageModelImage>)> = Vec::new();
+    for message in messages {
+        if message.content.is_empty() && message.images.is_empty() {
+            continue;
+        }
+
+        let role = match message.role {
+            Role::User | Role::System => google_ai::Role::User,
+            Role::Assistant => google_ai::Role::Model,
+        };
+        if let Some((last_role, last_text, last_images)) = contents.last_mut() {
+            if *last_role == role {
+                if !message.content.is_empty() {
+                    if !last_text.is_empty() {
+                        last_text.push_str("\n\n");
+                    }
+                    last_text.push_str(&message.content);
+                }
+                last_images.extend(message.images);
+                continue;
+            }
+        }
+        contents.push((role, message.content, message.images));
+    }
+
+    contents
+        .into_iter()
+        .map(|(role, text, images)| {
+            let mut parts = images
+                .into_iter()
+                .map(|image| {
+                    Part::InlineDataPart(InlineDataPart {
+                        inline_data: GenerativeContentBlob {
+                            mime_type: LanguageModelImage::MEDIA_TYPE.into(),
+                            data: image.data,
+                        },
+                    })
+                })
+                .collect::<Vec<_>>();
+            if !text.is_empty() {
+                parts.push(Part::TextPart(TextPart { text }));
+            }
+            Content { parts, role }
+        })
+        .collect()
+}
+
+struct AuthenticationPrompt {
+    api_key: View<Editor>,
+    api_url: String,
+}
+
+impl AuthenticationPrompt {
+    fn new(api_url: String, cx: &mut WindowContext) -> Self {
+        Self {
+            api_key: cx.new_view(|cx| {
+                let mut editor = Editor::single_line(cx);
+                editor.set_placeholder_text("AIzaSy0000000000000000000000000000000", cx);
+                editor
+            }),
+            api_url,
+        }
+    }
+
+    fn save_api_key(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let api_key = self.api_key.read(cx).text(cx);
+        if api_key.is_empty() {
+            return;
+        }
+
+        let write_credentials = cx.write_credentials(&self.api_url, "Bearer", api_key.as_bytes());
+        cx.spawn(|_, mut cx| async move {
+            write_credentials.await?;
+            cx.update_global::<CompletionProvider, _>(|provider, _cx| {
+                provider.update_current_as::<_, GoogleCompletionProvider>(|provider| {
+                    provider.api_key = Some(api_key);
+                });
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn render_api_key_editor(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let settings = ThemeSettings::get_global(cx);
+        let text_style = TextStyle {
+            color: cx.theme().colors().text,
+            font_family: settings.ui_font.family.clone(),
+            font_features: settings.ui_font.features.clone(),
+            font_size: rems(0.875).into(),
+            font_weight: settings.ui_font.weight,
+            font_style: FontStyle::Normal,
+            line_height: relative(1.3),
+            background_color: None,
+            underline: None,
+            strikethrough: None,
+            white_space: WhiteSpace::Normal,
+        };
+        EditorElement::new(
+            &self.api_key,
+            EditorStyle {
+                background: cx.theme().colors().editor_background,
+                local_player: cx.theme().players().local(),
+                text: text_style,
+                ..Default::default()
+            },
+        )
+    }
+}
+
+impl Render for AuthenticationPrompt {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        const INSTRUCTIONS: [&str; 4] = [
+            "To use the assistant panel or inline assistant, you need to add your Google AI API key.",
+            "You can create an API key at: https://aistudio.google.com/app/apikey",
+            "",
+            "Paste your Google AI API key below and hit enter to use the assistant:",
+        ];
+
+        v_flex()
+            .p_4()
+            .size_full()
+            .on_action(cx.listener(Self::save_api_key))
+            .children(
+                INSTRUCTIONS.map(|instruction| Label::new(instruction).size(LabelSize::Small)),
+            )
+            .child(
+                h_flex()
+                    .w_full()
+                    .my_2()
+                    .px_2()
+                    .py_1()
+                    .bg(cx.theme().colors().editor_background)
+                    .rounded_md()
+                    .child(self.render_api_key_editor(cx)),
+            )
+            .child(
+                Label::new(
+                    "You can also assign the GOOGLE_AI_API_KEY environment variable and restart Zed.",
+                )
+                .size(LabelSize::Small),
+            )
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new("Click on").size(LabelSize::Small))
+                    .child(Icon::new(IconName::ZedAssistant).size(IconSize::XSmall))
+                    .child(
+                        Label::new("in the status bar to close this panel.").size(LabelSize::Small),
+                    ),
+            )
+            .into_any()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::AsyncReadExt;
+    use gpui::TestAppContext;
+    use http::{AsyncBody, FakeHttpClient, Response};
+    use parking_lot::Mutex;
+
+    #[gpui::test]
+    async fn test_google_completion_provider(cx: &mut TestAppContext) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let http_client = FakeHttpClient::create({
+            let requests = requests.clone();
+            move |request| {
+                let requests = requests.clone();
+                async move {
+                    let uri = request.uri().to_string();
+                    let mut body = String::new();
+                    request.into_body().read_to_string(&mut body).await?;
+                    requests.lock().push((uri.clone(), body));
+
+                    let response = if uri.contains(":streamGenerateContent") {
+                        [
+                            r#"data: {"candidates": [{"index": 0, "content": {"role": "model", "parts": [{"text": "Hello"}]}}]}"#,
+                            "",
+                            r#"data: {"candidates": [{"index": 0, "content": {"role": "model", "parts": [{"text": ", world!"}]}, "finishReason": "STOP"}]}"#,
+                            "",
+                        ]
+                        .join("\n")
+                    } else {
+                        r#"{"totalTokens": 42}"#.to_string()
+                    };
+                    Ok(Response::builder()
+                        .status(200)
+                        .body(AsyncBody::from(response))
+                        .unwrap())
+                }
+            }
+        });
+
+        let mut provider = GoogleCompletionProvider::new(
+            GoogleModel::Gemini15Pro,
+            "http://google.test".into(),
+            http_client,
+            None,
+            0,
+        );
+        assert!(provider
+            .complete(LanguageModelRequest::default())
+            .await
+            .is_err());
+
+        provider.api_key = Some("the-api-key".into());
+        let request = || LanguageModelRequest {
+            model: LanguageModel::Google(GoogleModel::Gemini15Pro),
+            messages: vec![
+                LanguageModelRequestMessage {
+                    role: Role::System,
+                    content: "Be concise.".into(),
+                    images: Vec::new(),
+                    tool_calls: Vec::new(),
+                    tool_results: Vec::new(),
+                },
+                LanguageModelRequestMessage {
+                    role: Role::User,
+                    content: "Say hello.".into(),
+                    images: Vec::new(),
+                    tool_calls: Vec::new(),
+                    tool_results: Vec::new(),
+                },
+                LanguageModelRequestMessage {
+                    role: Role::Assistant,
+                    content: String::new(),
+                    images: Vec::new(),
+                    tool_calls: Vec::new(),
+                    tool_results: Vec::new(),
+                },
+            ],
+            stop: Vec::new(),
+            temperature: 1.0,
+            tools: Vec::new(),
+        };
+
+        let stream = provider.complete(request()).await.unwrap();
+        let chunks = stream.map(|chunk| chunk.unwrap()).collect::<Vec<_>>().await;
+        assert_eq!(chunks, ["Hello", ", world!"]);
+
+        let token_count = cx
+            .update(|cx| provider.count_tokens(request(), cx))
+            .await
+            .unwrap();
+        assert_eq!(token_count, 42);
+
+        let requests = requests.lock();
+        assert_eq!(
+            requests
+                .iter()
+                .map(|(uri, _)| uri.as_str())
+                .collect::<Vec<_>>(),
+            [
+                "http://google.test/v1beta/models/gemini-1.5-pro:streamGenerateContent?alt=sse&key=the-api-key",
+                "http://google.test/v1beta/models/gemini-1.5-pro:countTokens?key=the-api-key",
+            ]
+        );
+        let body = serde_json::from_str::<serde_json::Value>(&requests[0].1).unwrap();
+        assert_eq!(
+            body["contents"],
+            serde_json::json!([{
+                "role": "user",
+                "parts": [{ "text": "Be concise.\n\nSay hello." }],
+            }])
+        );
+    }
+}
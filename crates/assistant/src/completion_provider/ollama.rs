@@ -1,6 +1,7 @@
 use crate::LanguageModelCompletionProvider;
 use crate::{
-    assistant_settings::OllamaModel, CompletionProvider, LanguageModel, LanguageModelRequest, Role,
+    assistant_settings::OllamaModel, CompletionProvider, LanguageModel, LanguageModelImage,
+    LanguageModelRequest, Role,
 };
 use anyhow::Result;
 use futures::StreamExt as _;
@@ -86,7 +87,13 @@ impl LanguageModelCompletionProvider for OllamaCompletionProvider {
             .iter()
             .map(|msg| msg.content.chars().count())
             .sum::<usize>()
-            / 4;
+            / 4
+            + request
+                .messages
+                .iter()
+                .flat_map(|msg| &msg.images)
+                .map(LanguageModelImage::estimate_tokens)
+                .sum::<usize>();
 
         async move { Ok(token_count) }.boxed()
     }
@@ -109,7 +116,7 @@ impl LanguageModelCompletionProvider for OllamaCompletionProvider {
                     match response {
                         Ok(delta) => {
                             let content = match delta.message {
-                                ChatMessage::User { content } => content,
+                                ChatMessage::User { content, .. } => content,
                                 ChatMessage::Assistant { content } => content,
                                 ChatMessage::System { content } => content,
                             };
@@ -241,6 +248,8 @@ impl OllamaCompletionProvider {
                 .map(|msg| match msg.role {
                     Role::User => ChatMessage::User {
                         content: msg.content,
+                        images: (!msg.images.is_empty())
+                            .then(|| msg.images.into_iter().map(|image| image.data).collect()),
                     },
                     Role::Assistant => ChatMessage::Assistant {
                         content: msg.content,
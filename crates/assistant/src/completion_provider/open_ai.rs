@@ -2,14 +2,19 @@ use crate::assistant_settings::CloudModel;
 use crate::assistant_settings::{AssistantProvider, AssistantSettings};
 use crate::LanguageModelCompletionProvider;
 use crate::{
-    assistant_settings::OpenAiModel, CompletionProvider, LanguageModel, LanguageModelRequest, Role,
+    assistant_settings::OpenAiModel, CompletionEvent, CompletionProvider, LanguageModel,
+    LanguageModelImage, LanguageModelRequest, LanguageModelToolCall, Role,
 };
 use anyhow::{anyhow, Result};
 use editor::{Editor, EditorElement, EditorStyle};
 use futures::{future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
 use gpui::{AnyView, AppContext, FontStyle, Task, TextStyle, View, WhiteSpace};
 use http::HttpClient;
-use open_ai::{stream_completion, Request, RequestMessage, Role as OpenAiRole};
+use open_ai::{
+    stream_completion, FunctionContent, FunctionDefinition, ImageUrl, MessageContent, MessagePart,
+    Request, RequestMessage, ResponseStreamEvent, Role as OpenAiRole, ToolCall, ToolCallContent,
+    ToolDefinition,
+};
 use settings::Settings;
 use std::time::Duration;
 use std::{env, sync::Arc};
@@ -64,31 +69,152 @@ impl OpenAiCompletionProvider {
             _ => self.model.clone(),
         };
 
-        Request {
-            model,
-            messages: request
-                .messages
-                .into_iter()
-                .map(|msg| match msg.role {
-                    Role::User => RequestMessage::User {
-                        content: msg.content,
-                    },
-                    Role::Assistant => RequestMessage::Assistant {
-                        content: Some(msg.content),
-                        tool_calls: Vec::new(),
-                    },
-                    Role::System => RequestMessage::System {
-                        content: msg.content,
-                    },
-                })
-                .collect(),
-            stream: true,
-            stop: request.stop,
-            temperature: request.temperature,
-            tools: Vec::new(),
-            tool_choice: None,
+        to_open_ai_request(model, request)
+    }
+}
+
+pub(crate) fn to_open_ai_request(model: OpenAiModel, request: LanguageModelRequest) -> Request {
+    let mut messages = Vec::new();
+    for message in request.messages {
+        // Tool results are passed to OpenAI as messages of their own, which must directly
+        // follow the assistant message that called the tools.
+        let has_tool_results = !message.tool_results.is_empty();
+        for result in message.tool_results {
+            messages.push(RequestMessage::Tool {
+                content: result.content,
+                tool_call_id: result.tool_call_id,
+            });
+        }
+
+        match message.role {
+            Role::User => {
+                if message.images.is_empty() {
+                    if !(has_tool_results && message.content.is_empty()) {
+                        messages.push(RequestMessage::User {
+                            content: message.content.into(),
+                        });
+                    }
+                } else {
+                    let mut parts = message
+                        .images
+                        .iter()
+                        .map(|image| MessagePart::ImageUrl {
+                            image_url: ImageUrl {
+                                url: image.to_data_url(),
+                            },
+                        })
+                        .collect::<Vec<_>>();
+                    if !message.content.is_empty() {
+                        parts.push(MessagePart::Text {
+                            text: message.content,
+                        });
+                    }
+                    messages.push(RequestMessage::User {
+                        content: MessageContent::Multipart(parts),
+                    });
+                }
+            }
+            Role::Assistant => messages.push(RequestMessage::Assistant {
+                content: if message.content.is_empty() && !message.tool_calls.is_empty() {
+                    None
+                } else {
+                    Some(message.content)
+                },
+                tool_calls: message
+                    .tool_calls
+                    .into_iter()
+                    .map(|call| ToolCall {
+                        id: call.id,
+                        content: ToolCallContent::Function {
+                            function: FunctionContent {
+                                name: call.name,
+                                arguments: call.arguments,
+                            },
+                        },
+                    })
+                    .collect(),
+            }),
+            Role::System => messages.push(RequestMessage::System {
+                content: message.content,
+            }),
         }
     }
+
+    Request {
+        model,
+        messages,
+        stream: true,
+        stop: request.stop,
+        temperature: request.temperature,
+        tools: request
+            .tools
+            .into_iter()
+            .map(|tool| ToolDefinition::Function {
+                function: FunctionDefinition {
+                    name: tool.name,
+                    description: Some(tool.description),
+                    parameters: serde_json::to_value(&tool.parameters)
+                        .ok()
+                        .and_then(|parameters| parameters.as_object().cloned()),
+                },
+            })
+            .collect(),
+        tool_choice: None,
+    }
+}
+
+/// Turns OpenAI's response events into completion events, accumulating the streamed
+/// tool calls until the model finishes its message.
+pub(crate) fn open_ai_completion_events(
+    events: BoxStream<'static, Result<ResponseStreamEvent>>,
+) -> BoxStream<'static, Result<CompletionEvent>> {
+    let mut tool_calls: Vec<LanguageModelToolCall> = Vec::new();
+    events
+        .flat_map(move |event| {
+            let mut completion_events = Vec::new();
+            match event {
+                Ok(mut event) => {
+                    if let Some(choice) = event.choices.pop() {
+                        if let Some(text) = choice.delta.content {
+                            completion_events.push(Ok(CompletionEvent::Text(text)));
+                        }
+
+                        for chunk in choice.delta.tool_calls.unwrap_or_default() {
+                            while tool_calls.len() <= chunk.index {
+                                tool_calls.push(LanguageModelToolCall {
+                                    id: String::new(),
+                                    name: String::new(),
+                                    arguments: String::new(),
+                                });
+                            }
+                            let call = &mut tool_calls[chunk.index];
+                            if let Some(id) = chunk.id {
+                                call.id = id;
+                            }
+                            if let Some(function) = chunk.function {
+                                if let Some(name) = function.name {
+                                    call.name.push_str(&name);
+                                }
+                                if let Some(arguments) = function.arguments {
+                                    call.arguments.push_str(&arguments);
+                                }
+                            }
+                        }
+
+                        if choice.finish_reason.is_some() {
+                            completion_events.extend(
+                                tool_calls
+                                    .drain(..)
+                                    .map(|call| Ok(CompletionEvent::ToolCall(call))),
+                            );
+                        }
+                    }
+                }
+                Err(error) => completion_events.push(Err(error)),
+            }
+            futures::stream::iter(completion_events)
+        })
+        .boxed()
 }
 
 impl LanguageModelCompletionProvider for OpenAiCompletionProvider {
@@ -212,6 +338,35 @@ impl LanguageModelCompletionProvider for OpenAiCompletionProvider {
         .boxed()
     }
 
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    fn complete_with_tools(
+        &self,
+        request: LanguageModelRequest,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<CompletionEvent>>>> {
+        let request = self.to_open_ai_request(request);
+
+        let http_client = self.http_client.clone();
+        let api_key = self.api_key.clone();
+        let api_url = self.api_url.clone();
+        let low_speed_timeout = self.low_speed_timeout;
+        async move {
+            let api_key = api_key.ok_or_else(|| anyhow!("missing api key"))?;
+            let response = stream_completion(
+                http_client.as_ref(),
+                &api_url,
+                &api_key,
+                request,
+                low_speed_timeout,
+            )
+            .await?;
+            Ok(open_ai_completion_events(response))
+        }
+        .boxed()
+    }
+
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
         self
     }
@@ -223,6 +378,12 @@ pub fn count_open_ai_tokens(
 ) -> BoxFuture<'static, Result<usize>> {
     background_executor
         .spawn(async move {
+            let image_tokens = request
+                .messages
+                .iter()
+                .flat_map(|message| &message.images)
+                .map(LanguageModelImage::estimate_tokens)
+                .sum::<usize>();
             let messages = request
                 .messages
                 .into_iter()
@@ -232,25 +393,36 @@ pub fn count_open_ai_tokens(
                         Role::Assistant => "assistant".into(),
                         Role::System => "system".into(),
                     },
-                    content: Some(message.content),
+                    content: Some(
+                        message
+                            .tool_results
+                            .into_iter()
+                            .map(|result| result.content)
+                            .chain(message.tool_calls.into_iter().map(|call| call.arguments))
+                            .chain(Some(message.content))
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    ),
                     name: None,
                     function_call: None,
                 })
                 .collect::<Vec<_>>();
 
-            match request.model {
+            let text_tokens = match request.model {
                 LanguageModel::Anthropic(_)
                 | LanguageModel::Cloud(CloudModel::Claude3_5Sonnet)
                 | LanguageModel::Cloud(CloudModel::Claude3Opus)
                 | LanguageModel::Cloud(CloudModel::Claude3Sonnet)
                 | LanguageModel::Cloud(CloudModel::Claude3Haiku)
-                | LanguageModel::OpenAi(OpenAiModel::Custom { .. }) => {
+                | LanguageModel::OpenAi(OpenAiModel::Custom { .. })
+                | LanguageModel::OpenAiCompatible(_) => {
                     // Tiktoken doesn't yet support these models, so we manually use the
                     // same tokenizer as GPT-4.
                     tiktoken_rs::num_tokens_from_messages("gpt-4", &messages)
                 }
                 _ => tiktoken_rs::num_tokens_from_messages(request.model.id(), &messages),
-            }
+            }?;
+            Ok(text_tokens + image_tokens)
         })
         .boxed()
 }
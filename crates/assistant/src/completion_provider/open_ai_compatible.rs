@@ -0,0 +1,467 @@
+use super::open_ai::{count_open_ai_tokens, open_ai_completion_events, to_open_ai_request};
+use crate::assistant_settings::{OpenAiCompatibleModel, OpenAiCompatibleProviderSettings};
+use crate::{
+    assistant_settings::OpenAiModel, CompletionEvent, CompletionProvider, LanguageModel,
+    LanguageModelCompletionProvider, LanguageModelRequest,
+};
+use anyhow::{anyhow, Result};
+use editor::{Editor, EditorElement, EditorStyle};
+use futures::{future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
+use gpui::{AnyView, AppContext, FontStyle, Task, TextStyle, View, WhiteSpace};
+use http::HttpClient;
+use open_ai::{stream_completion_with_headers, ResponseStreamEvent};
+use settings::Settings;
+use std::time::Duration;
+use std::{env, sync::Arc};
+use theme::ThemeSettings;
+use ui::prelude::*;
+use util::ResultExt;
+
+/// Completes with one of the OpenAI-compatible providers configured in the assistant settings.
+pub struct OpenAiCompatibleCompletionProvider {
+    api_key: Option<String>,
+    settings: OpenAiCompatibleProviderSettings,
+    model: OpenAiCompatibleModel,
+    http_client: Arc<dyn HttpClient>,
+    settings_version: usize,
+}
+
+impl OpenAiCompatibleCompletionProvider {
+    pub fn new(
+        settings: OpenAiCompatibleProviderSettings,
+        model: Option<String>,
+        http_client: Arc<dyn HttpClient>,
+        settings_version: usize,
+    ) -> Self {
+        Self {
+            api_key: None,
+            model: settings.model(model.as_deref()),
+            settings,
+            http_client,
+            settings_version,
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        settings: OpenAiCompatibleProviderSettings,
+        model: Option<String>,
+        settings_version: usize,
+    ) {
+        // A key read for another provider must not be sent to this one.
+        if settings.name != self.settings.name
+            || settings.api_url != self.settings.api_url
+            || settings.api_key_env_var != self.settings.api_key_env_var
+        {
+            self.api_key = None;
+        }
+        self.model = settings.model(model.as_deref());
+        self.settings = settings;
+        self.settings_version = settings_version;
+    }
+
+    fn stream_completion(
+        &self,
+        request: LanguageModelRequest,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<ResponseStreamEvent>>>> {
+        let model = match &request.model {
+            LanguageModel::OpenAiCompatible(model) if model.provider == self.settings.name => {
+                model.clone()
+            }
+            _ => self.model.clone(),
+        };
+        let request = to_open_ai_request(
+            OpenAiModel::Custom {
+                name: model.name,
+                max_tokens: model.max_tokens,
+            },
+            request,
+        );
+
+        let http_client = self.http_client.clone();
+        let api_key = self.api_key.clone();
+        let requires_api_key = self.requires_api_key();
+        let provider_name = self.settings.name.clone();
+        let api_url = self.settings.api_url.clone();
+        let headers = self.settings.headers();
+        let low_speed_timeout = self
+            .settings
+            .low_speed_timeout_in_seconds
+            .map(Duration::from_secs);
+        async move {
+            if api_url.is_empty() {
+                return Err(anyhow!(
+                    "no OpenAI-compatible provider named {provider_name:?} is configured"
+                ));
+            }
+            if requires_api_key && api_key.is_none() {
+                return Err(anyhow!("missing api key"));
+            }
+
+            stream_completion_with_headers(
+                http_client.as_ref(),
+                &api_url,
+                api_key.as_deref(),
+                &headers,
+                request,
+                low_speed_timeout,
+            )
+            .await
+        }
+        .boxed()
+    }
+
+    fn requires_api_key(&self) -> bool {
+        self.settings.api_key_env_var.is_some()
+    }
+}
+
+impl LanguageModelCompletionProvider for OpenAiCompatibleCompletionProvider {
+    fn available_models(&self, _cx: &AppContext) -> Vec<LanguageModel> {
+        let mut models = self.settings.models();
+        if !self.model.name.is_empty() && !models.contains(&self.model) {
+            models.push(self.model.clone());
+        }
+        models
+            .into_iter()
+            .map(LanguageModel::OpenAiCompatible)
+            .collect()
+    }
+
+    fn settings_version(&self) -> usize {
+        self.settings_version
+    }
+
+    fn is_authenticated(&self) -> bool {
+        !self.requires_api_key() || self.api_key.is_some()
+    }
+
+    fn authenticate(&self, cx: &AppContext) -> Task<Result<()>> {
+        if self.is_authenticated() {
+            return Task::ready(Ok(()));
+        }
+
+        let api_url = self.settings.api_url.clone();
+        let api_key_env_var = self.settings.api_key_env_var.clone();
+        cx.spawn(|mut cx| async move {
+            let api_key = if let Some(api_key) =
+                api_key_env_var.and_then(|api_key_env_var| env::var(api_key_env_var).ok())
+            {
+                api_key
+            } else {
+                let (_, api_key) = cx
+                    .update(|cx| cx.read_credentials(&api_url))?
+                    .await?
+                    .ok_or_else(|| anyhow!("credentials not found"))?;
+                String::from_utf8(api_key)?
+            };
+            cx.update_global::<CompletionProvider, _>(|provider, _cx| {
+                provider.update_current_as::<_, Self>(|provider| {
+                    provider.api_key = Some(api_key);
+                });
+            })
+        })
+    }
+
+    fn reset_credentials(&self, cx: &AppContext) -> Task<Result<()>> {
+        let delete_credentials = cx.delete_credentials(&self.settings.api_url);
+        cx.spawn(|mut cx| async move {
+            delete_credentials.await.log_err();
+            cx.update_global::<CompletionProvider, _>(|provider, _cx| {
+                provider.update_current_as::<_, Self>(|provider| {
+                    provider.api_key = None;
+                });
+            })
+        })
+    }
+
+    fn authentication_prompt(&self, cx: &mut WindowContext) -> AnyView {
+        let settings = self.settings.clone();
+        cx.new_view(|cx| AuthenticationPrompt::new(settings, cx))
+            .into()
+    }
+
+    fn model(&self) -> LanguageModel {
+        LanguageModel::OpenAiCompatible(self.model.clone())
+    }
+
+    fn count_tokens(
+        &self,
+        request: LanguageModelRequest,
+        cx: &AppContext,
+    ) -> BoxFuture<'static, Result<usize>> {
+        count_open_ai_tokens(request, cx.background_executor())
+    }
+
+    fn complete(
+        &self,
+        request: LanguageModelRequest,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<String>>>> {
+        let response = self.stream_completion(request);
+        async move {
+            let stream = response
+                .await?
+                .filter_map(|response| async move {
+                    match response {
+                        Ok(mut response) => Some(Ok(response.choices.pop()?.delta.content?)),
+                        Err(error) => Some(Err(error)),
+                    }
+                })
+                .boxed();
+            Ok(stream)
+        }
+        .boxed()
+    }
+
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    fn complete_with_tools(
+        &self,
+        request: LanguageModelRequest,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<CompletionEvent>>>> {
+        let response = self.stream_completion(request);
+        async move { Ok(open_ai_completion_events(response.await?)) }.boxed()
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+struct AuthenticationPrompt {
+    api_key: View<Editor>,
+    settings: OpenAiCompatibleProviderSettings,
+}
+
+impl AuthenticationPrompt {
+    fn new(settings: OpenAiCompatibleProviderSettings, cx: &mut WindowContext) -> Self {
+        Self {
+            api_key: cx.new_view(|cx| {
+                let mut editor = Editor::single_line(cx);
+                editor.set_placeholder_text("API key", cx);
+                editor
+            }),
+            settings,
+        }
+    }
+
+    fn save_api_key(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let api_key = self.api_key.read(cx).text(cx);
+        if api_key.is_empty() {
+            return;
+        }
+
+        let write_credentials =
+            cx.write_credentials(&self.settings.api_url, "Bearer", api_key.as_bytes());
+        cx.spawn(|_, mut cx| async move {
+            write_credentials.await?;
+            cx.update_global::<CompletionProvider, _>(|provider, _cx| {
+                provider.update_current_as::<_, OpenAiCompatibleCompletionProvider>(|provider| {
+                    provider.api_key = Some(api_key);
+                });
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn render_api_key_editor(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let settings = ThemeSettings::get_global(cx);
+        let text_style = TextStyle {
+            color: cx.theme().colors().text,
+            font_family: settings.ui_font.family.clone(),
+            font_features: settings.ui_font.features.clone(),
+            font_size: rems(0.875).into(),
+            font_weight: settings.ui_font.weight,
+            font_style: FontStyle::Normal,
+            line_height: relative(1.3),
+            background_color: None,
+            underline: None,
+            strikethrough: None,
+            white_space: WhiteSpace::Normal,
+        };
+        EditorElement::new(
+            &self.api_key,
+            EditorStyle {
+                background: cx.theme().colors().editor_background,
+                local_player: cx.theme().players().local(),
+                text: text_style,
+                ..Default::default()
+            },
+        )
+    }
+}
+
+impl Render for AuthenticationPrompt {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let instructions = [
+            format!(
+                "To use the assistant panel or inline assistant with {}, you need to add its API key.",
+                self.settings.name
+            ),
+            format!("The key will be sent to {}.", self.settings.api_url),
+            String::new(),
+            "Paste the API key below and hit enter to use the assistant:".to_string(),
+        ];
+
+        v_flex()
+            .p_4()
+            .size_full()
+            .on_action(cx.listener(Self::save_api_key))
+            .children(
+                instructions.map(|instruction| Label::new(instruction).size(LabelSize::Small)),
+            )
+            .child(
+                h_flex()
+                    .w_full()
+                    .my_2()
+                    .px_2()
+                    .py_1()
+                    .bg(cx.theme().colors().editor_background)
+                    .rounded_md()
+                    .child(self.render_api_key_editor(cx)),
+            )
+            .children(self.settings.api_key_env_var.as_ref().map(|api_key_env_var| {
+                Label::new(format!(
+                    "You can also assign the {api_key_env_var} environment variable and restart Zed."
+                ))
+                .size(LabelSize::Small)
+            }))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new("Click on").size(LabelSize::Small))
+                    .child(Icon::new(IconName::ZedAssistant).size(IconSize::XSmall))
+                    .child(
+                        Label::new("in the status bar to close this panel.").size(LabelSize::Small),
+                    ),
+            )
+            .into_any()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LanguageModelRequestMessage, Role};
+    use futures::AsyncReadExt;
+    use gpui::TestAppContext;
+    use http::{AsyncBody, FakeHttpClient, Response};
+    use parking_lot::Mutex;
+
+    #[gpui::test]
+    async fn test_open_ai_compatible_completion_provider(_cx: &mut TestAppContext) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let http_client = FakeHttpClient::create({
+            let requests = requests.clone();
+            move |request| {
+                let requests = requests.clone();
+                async move {
+                    let uri = request.uri().to_string();
+                    let authorization = request
+                        .headers()
+                        .get("Authorization")
+                        .map(|value| value.to_str().unwrap().to_string());
+                    let team = request
+                        .headers()
+                        .get("X-Team")
+                        .map(|value| value.to_str().unwrap().to_string());
+                    let mut body = String::new();
+                    request.into_body().read_to_string(&mut body).await?;
+                    let body = serde_json::from_str::<serde_json::Value>(&body).unwrap();
+                    requests
+                        .lock()
+                        .push((uri, authorization, team, body["model"].clone()));
+
+                    let response = [
+                        r#"data: {"created": 0, "model": "llama-3-70b", "choices": [{"index": 0, "delta": {"role": "assistant", "content": "Hi"}}]}"#,
+                        "",
+                        r#"data: {"created": 0, "model": "llama-3-70b", "choices": [{"index": 0, "delta": {"content": " there"}, "finish_reason": "stop"}]}"#,
+                        "",
+                        "data: [DONE]",
+                    ]
+                    .join("\n");
+                    Ok(Response::builder()
+                        .status(200)
+                        .body(AsyncBody::from(response))
+                        .unwrap())
+                }
+            }
+        });
+
+        let settings = |api_key_env_var: Option<&str>| OpenAiCompatibleProviderSettings {
+            name: "vllm".into(),
+            api_url: "http://vllm.test/v1".into(),
+            api_key_env_var: api_key_env_var.map(Into::into),
+            available_models: vec![crate::assistant_settings::OpenAiCompatibleAvailableModel {
+                name: "llama-3-70b".into(),
+                max_tokens: 8192,
+            }],
+            headers: [("X-Team".to_string(), "editor".to_string())]
+                .into_iter()
+                .collect(),
+            low_speed_timeout_in_seconds: None,
+        };
+        let request = || LanguageModelRequest {
+            model: LanguageModel::default(),
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: "Hello".into(),
+                images: Vec::new(),
+                tool_calls: Vec::new(),
+                tool_results: Vec::new(),
+            }],
+            stop: Vec::new(),
+            temperature: 1.0,
+            tools: Vec::new(),
+        };
+
+        // Providers without an API key env var don't need to be authenticated.
+        let mut provider =
+            OpenAiCompatibleCompletionProvider::new(settings(None), None, http_client, 0);
+        assert!(provider.is_authenticated());
+        assert_eq!(
+            provider.model(),
+            LanguageModel::OpenAiCompatible(OpenAiCompatibleModel {
+                provider: "vllm".into(),
+                name: "llama-3-70b".into(),
+                max_tokens: 8192,
+            })
+        );
+        let chunks = provider
+            .complete(request())
+            .await
+            .unwrap()
+            .map(|chunk| chunk.unwrap())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(chunks, ["Hi", " there"]);
+
+        // Providers with one can't be used until a key is read.
+        provider.update(settings(Some("VLLM_API_KEY")), None, 1);
+        assert!(!provider.is_authenticated());
+        assert!(provider.complete(request()).await.is_err());
+
+        provider.api_key = Some("the-api-key".into());
+        provider.complete(request()).await.unwrap();
+
+        assert_eq!(
+            *requests.lock(),
+            [
+                (
+                    "http://vllm.test/v1/chat/completions".to_string(),
+                    None,
+                    Some("editor".to_string()),
+                    serde_json::json!("llama-3-70b"),
+                ),
+                (
+                    "http://vllm.test/v1/chat/completions".to_string(),
+                    Some("Bearer the-api-key".to_string()),
+                    Some("editor".to_string()),
+                    serde_json::json!("llama-3-70b"),
+                ),
+            ]
+        );
+    }
+}
@@ -1,18 +1,28 @@
 use crate::{
-    slash_command::SlashCommandLine, CompletionProvider, LanguageModelRequest,
-    LanguageModelRequestMessage, MessageId, MessageStatus, Role,
+    slash_command::SlashCommandLine,
+    token_budget::{self, TrimStrategy},
+    CompletionEvent, CompletionProvider, LanguageModelImage, LanguageModelRequest,
+    LanguageModelRequestMessage, LanguageModelToolCall, LanguageModelToolResult, MessageId,
+    MessageStatus, Role,
 };
 use anyhow::{anyhow, Context as _, Result};
 use assistant_slash_command::{
     SlashCommandOutput, SlashCommandOutputSection, SlashCommandRegistry,
 };
+use assistant_tooling::ToolFunctionDefinition;
 use client::{proto, telemetry::Telemetry};
 use clock::ReplicaId;
 use collections::{HashMap, HashSet};
 use fs::Fs;
 use futures::{future::Shared, FutureExt, StreamExt};
-use gpui::{AppContext, Context as _, EventEmitter, Model, ModelContext, Subscription, Task};
-use language::{AnchorRangeExt, Bias, Buffer, LanguageRegistry, OffsetRangeExt, Point, ToOffset};
+use gpui::{
+    AppContext, AsyncAppContext, Context as _, EventEmitter, Model, ModelContext, Subscription,
+    Task,
+};
+use language::{
+    AnchorRangeExt, Bias, Buffer, Language, LanguageRegistry, OffsetRangeExt, ParseStatus, Point,
+    ToOffset, ToPoint,
+};
 use open_ai::Model as OpenAiModel;
 use paths::contexts_dir;
 use serde::{Deserialize, Serialize};
@@ -25,8 +35,8 @@ use std::{
     time::{Duration, Instant},
 };
 use telemetry_events::AssistantKind;
-use ui::SharedString;
-use util::{post_inc, TryFutureExt};
+use ui::{IconName, SharedString};
+use util::{post_inc, ResultExt, TryFutureExt};
 use uuid::Uuid;
 
 #[derive(Clone, Eq, PartialEq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
@@ -68,6 +78,11 @@ pub enum ContextOperation {
         sections: Vec<SlashCommandOutputSection<language::Anchor>>,
         version: clock::Global,
     },
+    InsertImage {
+        anchor: ImageAnchor,
+        image: LanguageModelImage,
+        version: clock::Global,
+    },
     BufferOperation(language::Operation),
 }
 
@@ -93,6 +108,16 @@ impl ContextOperation {
                             message.status.context("invalid status")?,
                         ),
                         timestamp: id.0,
+                        tool_calls: message
+                            .tool_calls
+                            .into_iter()
+                            .map(tool_call_from_proto)
+                            .collect(),
+                        tool_results: message
+                            .tool_results
+                            .into_iter()
+                            .map(tool_result_from_proto)
+                            .collect(),
                     },
                     version: language::proto::deserialize_version(&insert.version),
                 })
@@ -107,6 +132,16 @@ impl ContextOperation {
                     timestamp: language::proto::deserialize_timestamp(
                         update.timestamp.context("invalid timestamp")?,
                     ),
+                    tool_calls: update
+                        .tool_calls
+                        .into_iter()
+                        .map(tool_call_from_proto)
+                        .collect(),
+                    tool_results: update
+                        .tool_results
+                        .into_iter()
+                        .map(tool_result_from_proto)
+                        .collect(),
                 },
                 version: language::proto::deserialize_version(&update.version),
             }),
@@ -144,6 +179,23 @@ impl ContextOperation {
                     version: language::proto::deserialize_version(&finished.version),
                 })
             }
+            proto::context_operation::Variant::InsertImage(insert) => Ok(Self::InsertImage {
+                anchor: ImageAnchor {
+                    id: ImageId(language::proto::deserialize_timestamp(
+                        insert.id.context("invalid id")?,
+                    )),
+                    start: language::proto::deserialize_anchor(
+                        insert.anchor.context("invalid anchor")?,
+                    )
+                    .context("invalid anchor")?,
+                },
+                image: LanguageModelImage {
+                    data: insert.data,
+                    width: insert.width,
+                    height: insert.height,
+                },
+                version: language::proto::deserialize_version(&insert.version),
+            }),
             proto::context_operation::Variant::BufferOperation(op) => Ok(Self::BufferOperation(
                 language::proto::deserialize_operation(
                     op.operation.context("invalid buffer operation")?,
@@ -166,6 +218,16 @@ impl ContextOperation {
                             start: Some(language::proto::serialize_anchor(&anchor.start)),
                             role: metadata.role.to_proto() as i32,
                             status: Some(metadata.status.to_proto()),
+                            tool_calls: metadata
+                                .tool_calls
+                                .iter()
+                                .map(tool_call_to_proto)
+                                .collect(),
+                            tool_results: metadata
+                                .tool_results
+                                .iter()
+                                .map(tool_result_to_proto)
+                                .collect(),
                         }),
                         version: language::proto::serialize_version(version),
                     },
@@ -183,6 +245,12 @@ impl ContextOperation {
                         status: Some(metadata.status.to_proto()),
                         timestamp: Some(language::proto::serialize_timestamp(metadata.timestamp)),
                         version: language::proto::serialize_version(version),
+                        tool_calls: metadata.tool_calls.iter().map(tool_call_to_proto).collect(),
+                        tool_results: metadata
+                            .tool_results
+                            .iter()
+                            .map(tool_result_to_proto)
+                            .collect(),
                     },
                 )),
             },
@@ -225,6 +293,22 @@ impl ContextOperation {
                     },
                 )),
             },
+            Self::InsertImage {
+                anchor,
+                image,
+                version,
+            } => proto::ContextOperation {
+                variant: Some(proto::context_operation::Variant::InsertImage(
+                    proto::context_operation::InsertImage {
+                        id: Some(language::proto::serialize_timestamp(anchor.id.0)),
+                        anchor: Some(language::proto::serialize_anchor(&anchor.start)),
+                        data: image.data.clone(),
+                        width: image.width,
+                        height: image.height,
+                        version: language::proto::serialize_version(version),
+                    },
+                )),
+            },
             Self::BufferOperation(operation) => proto::ContextOperation {
                 variant: Some(proto::context_operation::Variant::BufferOperation(
                     proto::context_operation::BufferOperation {
@@ -241,6 +325,7 @@ impl ContextOperation {
             Self::UpdateMessage { metadata, .. } => metadata.timestamp,
             Self::UpdateSummary { summary, .. } => summary.timestamp,
             Self::SlashCommandFinished { id, .. } => id.0,
+            Self::InsertImage { anchor, .. } => anchor.id.0,
             Self::BufferOperation(_) => {
                 panic!("reading the timestamp of a buffer operation is not supported")
             }
@@ -253,7 +338,8 @@ impl ContextOperation {
             Self::InsertMessage { version, .. }
             | Self::UpdateMessage { version, .. }
             | Self::UpdateSummary { version, .. }
-            | Self::SlashCommandFinished { version, .. } => version,
+            | Self::SlashCommandFinished { version, .. }
+            | Self::InsertImage { version, .. } => version,
             Self::BufferOperation(_) => {
                 panic!("reading the version of a buffer operation is not supported")
             }
@@ -261,6 +347,40 @@ impl ContextOperation {
     }
 }
 
+fn tool_call_from_proto(call: proto::ContextToolCall) -> LanguageModelToolCall {
+    LanguageModelToolCall {
+        id: call.id,
+        name: call.name,
+        arguments: call.arguments,
+    }
+}
+
+fn tool_call_to_proto(call: &LanguageModelToolCall) -> proto::ContextToolCall {
+    proto::ContextToolCall {
+        id: call.id.clone(),
+        name: call.name.clone(),
+        arguments: call.arguments.clone(),
+    }
+}
+
+fn tool_result_from_proto(result: proto::ContextToolResult) -> LanguageModelToolResult {
+    LanguageModelToolResult {
+        tool_call_id: result.tool_call_id,
+        name: result.name,
+        content: result.content,
+        is_error: result.is_error,
+    }
+}
+
+fn tool_result_to_proto(result: &LanguageModelToolResult) -> proto::ContextToolResult {
+    proto::ContextToolResult {
+        tool_call_id: result.tool_call_id.clone(),
+        name: result.name.clone(),
+        content: result.content.clone(),
+        is_error: result.is_error,
+    }
+}
+
 #[derive(Clone)]
 pub enum ContextEvent {
     MessagesEdited,
@@ -276,6 +396,14 @@ pub enum ContextEvent {
         sections: Vec<SlashCommandOutputSection<language::Anchor>>,
         run_commands_in_output: bool,
     },
+    /// The model finished a message by asking to call tools, whose results
+    /// are expected on the user message that follows it.
+    ToolCallsRequested {
+        assistant_message_id: MessageId,
+        user_message_id: MessageId,
+        tool_calls: Vec<LanguageModelToolCall>,
+    },
+    ImagesChanged,
     Operation(ContextOperation),
 }
 
@@ -292,11 +420,26 @@ pub struct MessageAnchor {
     pub start: language::Anchor,
 }
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ImageId(clock::Lamport);
+
+/// The position of an image in the context, which is displayed above the line
+/// containing `start` and removed along with the character `start` points to.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ImageAnchor {
+    pub id: ImageId,
+    pub start: language::Anchor,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct MessageMetadata {
     pub role: Role,
     status: MessageStatus,
     timestamp: clock::Lamport,
+    #[serde(default)]
+    pub tool_calls: Vec<LanguageModelToolCall>,
+    #[serde(default)]
+    pub tool_results: Vec<LanguageModelToolResult>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -307,6 +450,8 @@ pub struct Message {
     pub anchor: language::Anchor,
     pub role: Role,
     pub status: MessageStatus,
+    pub tool_calls: Vec<LanguageModelToolCall>,
+    pub tool_results: Vec<LanguageModelToolResult>,
 }
 
 impl Message {
@@ -314,6 +459,9 @@ impl Message {
         LanguageModelRequestMessage {
             role: self.role,
             content: buffer.text_for_range(self.offset_range.clone()).collect(),
+            images: Vec::new(),
+            tool_calls: self.tool_calls.clone(),
+            tool_results: self.tool_results.clone(),
         }
     }
 }
@@ -338,8 +486,11 @@ pub struct Context {
     edits_since_last_slash_command_parse: language::Subscription,
     finished_slash_commands: HashSet<SlashCommandId>,
     slash_command_output_sections: Vec<SlashCommandOutputSection<language::Anchor>>,
+    image_anchors: Vec<ImageAnchor>,
+    images: HashMap<ImageId, LanguageModelImage>,
     message_anchors: Vec<MessageAnchor>,
     messages_metadata: HashMap<MessageId, MessageMetadata>,
+    tools: Vec<ToolFunctionDefinition>,
     summary: Option<ContextSummary>,
     pending_summary: Task<Option<()>>,
     completion_count: usize,
@@ -400,10 +551,13 @@ impl Context {
             operations: Vec::new(),
             message_anchors: Default::default(),
             messages_metadata: Default::default(),
+            tools: Vec::new(),
             edit_suggestions: Vec::new(),
             pending_slash_commands: Vec::new(),
             finished_slash_commands: HashSet::default(),
             slash_command_output_sections: Vec::new(),
+            image_anchors: Vec::new(),
+            images: HashMap::default(),
             edits_since_last_slash_command_parse,
             summary: None,
             pending_summary: Task::ready(None),
@@ -434,6 +588,8 @@ impl Context {
                 role: Role::User,
                 status: MessageStatus::Done,
                 timestamp: first_message_id.0,
+                tool_calls: Vec::new(),
+                tool_results: Vec::new(),
             },
         );
         this.message_anchors.push(message);
@@ -479,6 +635,16 @@ impl Context {
                     }
                 })
                 .collect(),
+            images: self
+                .image_anchors(cx)
+                .filter_map(|anchor| {
+                    Some(SavedImage {
+                        id: anchor.id,
+                        start: anchor.start.to_offset(buffer),
+                        image: self.images.get(&anchor.id)?.clone(),
+                    })
+                })
+                .collect(),
         }
     }
 
@@ -595,6 +761,7 @@ impl Context {
     fn flush_ops(&mut self, cx: &mut ModelContext<Context>) {
         let mut messages_changed = false;
         let mut summary_changed = false;
+        let mut images_changed = false;
 
         self.pending_ops.sort_unstable_by_key(|op| op.timestamp());
         for op in mem::take(&mut self.pending_ops) {
@@ -658,6 +825,12 @@ impl Context {
                         });
                     }
                 }
+                ContextOperation::InsertImage { anchor, image, .. } => {
+                    if !self.images.contains_key(&anchor.id) {
+                        self.insert_image_anchor(anchor, image, cx);
+                        images_changed = true;
+                    }
+                }
                 ContextOperation::BufferOperation(_) => unreachable!(),
             }
 
@@ -675,6 +848,12 @@ impl Context {
             cx.emit(ContextEvent::SummaryChanged);
             cx.notify();
         }
+
+        if images_changed {
+            self.count_remaining_tokens(cx);
+            cx.emit(ContextEvent::ImagesChanged);
+            cx.notify();
+        }
     }
 
     fn can_apply_op(&self, op: &ContextOperation, cx: &AppContext) -> bool {
@@ -712,6 +891,11 @@ impl Context {
                         observed_start && observed_end
                     })
             }
+            ContextOperation::InsertImage { anchor, .. } => self
+                .buffer
+                .read(cx)
+                .version
+                .observed(anchor.start.timestamp),
             ContextOperation::BufferOperation(_) => {
                 panic!("buffer operations should always be applied")
             }
@@ -747,6 +931,78 @@ impl Context {
         &self.slash_command_output_sections
     }
 
+    /// Returns the anchors of the images that are still part of the context.
+    pub fn image_anchors<'a>(
+        &'a self,
+        cx: &'a AppContext,
+    ) -> impl 'a + Iterator<Item = &'a ImageAnchor> {
+        let buffer = self.buffer.read(cx);
+        self.image_anchors
+            .iter()
+            .filter(|anchor| anchor.start.is_valid(buffer))
+    }
+
+    pub fn image(&self, id: ImageId) -> Option<&LanguageModelImage> {
+        self.images.get(&id)
+    }
+
+    /// Attaches an image to the context, displayed above the line containing `position`.
+    pub fn insert_image(
+        &mut self,
+        image: LanguageModelImage,
+        position: language::Anchor,
+        cx: &mut ModelContext<Self>,
+    ) -> ImageId {
+        let version = self.version.clone();
+        let anchor = ImageAnchor {
+            id: ImageId(self.next_timestamp()),
+            start: position,
+        };
+        let id = anchor.id;
+        self.insert_image_anchor(anchor.clone(), image.clone(), cx);
+        self.push_op(
+            ContextOperation::InsertImage {
+                anchor,
+                image,
+                version,
+            },
+            cx,
+        );
+        self.count_remaining_tokens(cx);
+        cx.emit(ContextEvent::ImagesChanged);
+        cx.notify();
+        id
+    }
+
+    fn insert_image_anchor(
+        &mut self,
+        anchor: ImageAnchor,
+        image: LanguageModelImage,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let buffer = self.buffer.read(cx);
+        let ix = match self.image_anchors.binary_search_by(|probe| {
+            probe
+                .start
+                .cmp(&anchor.start, buffer)
+                .then(probe.id.cmp(&anchor.id))
+        }) {
+            Ok(ix) | Err(ix) => ix,
+        };
+        self.images.insert(anchor.id, image);
+        self.image_anchors.insert(ix, anchor);
+    }
+
+    fn images_in_range(&self, range: Range<usize>, buffer: &Buffer) -> Vec<LanguageModelImage> {
+        self.image_anchors
+            .iter()
+            .filter(|anchor| {
+                anchor.start.is_valid(buffer) && range.contains(&anchor.start.to_offset(buffer))
+            })
+            .filter_map(|anchor| self.images.get(&anchor.id).cloned())
+            .collect()
+    }
+
     fn set_language(&mut self, cx: &mut ModelContext<Self>) {
         let markdown = self.language_registry.language_for_name("Markdown");
         cx.spawn(|this, mut cx| async move {
@@ -1006,7 +1262,25 @@ impl Context {
         let insert_output_task = cx.spawn(|this, mut cx| {
             let command_range = command_range.clone();
             async move {
-                let output = output.await;
+                let mut output = output.await;
+                let images = match output.as_mut() {
+                    Ok(output) if !output.images.is_empty() => {
+                        let images = mem::take(&mut output.images);
+                        cx.background_executor()
+                            .spawn(async move {
+                                images
+                                    .into_iter()
+                                    .filter_map(|image| {
+                                        let decoded = LanguageModelImage::from_bytes(&image.bytes)
+                                            .log_err()?;
+                                        Some((image.offset, decoded))
+                                    })
+                                    .collect::<Vec<_>>()
+                            })
+                            .await
+                    }
+                    _ => Vec::new(),
+                };
                 this.update(&mut cx, |this, cx| match output {
                     Ok(mut output) => {
                         if insert_trailing_newline {
@@ -1015,7 +1289,7 @@ impl Context {
 
                         let version = this.version.clone();
                         let command_id = SlashCommandId(this.next_timestamp());
-                        let (operation, event) = this.buffer.update(cx, |buffer, cx| {
+                        let (operation, event, images) = this.buffer.update(cx, |buffer, cx| {
                             let start = command_range.start.to_offset(buffer);
                             let old_end = command_range.end.to_offset(buffer);
                             let new_end = start + output.text.len();
@@ -1042,6 +1316,11 @@ impl Context {
                                 buffer.anchor_after(start)..buffer.anchor_before(new_end);
                             this.finished_slash_commands.insert(command_id);
 
+                            let images = images
+                                .into_iter()
+                                .map(|(offset, image)| (buffer.anchor_after(start + offset), image))
+                                .collect::<Vec<_>>();
+
                             (
                                 ContextOperation::SlashCommandFinished {
                                     id: command_id,
@@ -1054,11 +1333,15 @@ impl Context {
                                     sections,
                                     run_commands_in_output: output.run_commands_in_text,
                                 },
+                                images,
                             )
                         });
 
                         this.push_op(operation, cx);
                         cx.emit(event);
+                        for (position, image) in images {
+                            this.insert_image(image, position, cx);
+                        }
                     }
                     Err(error) => {
                         if let Some(pending_command) =
@@ -1088,6 +1371,254 @@ impl Context {
         }
     }
 
+    /// Replaces the text of every slash command output section with the given label, or
+    /// removes those sections if `text` is `None`.
+    pub fn replace_slash_command_output_sections(
+        &mut self,
+        label: &str,
+        text: Option<&str>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.edit_slash_command_output_sections(
+            |section, _, _| (section.label == label).then(|| text.unwrap_or("").to_string()),
+            cx,
+        );
+    }
+
+    /// Replaces the text of the slash command output sections for which `new_text` returns
+    /// a value, given each section with its current range and text. Sections are visited
+    /// from last to first, so the ranges of the remaining sections are unaffected by earlier
+    /// edits. Sections replaced with an empty string are removed, and sections whose text
+    /// was deleted are left alone. Returns whether any section was edited.
+    fn edit_slash_command_output_sections(
+        &mut self,
+        mut new_text: impl FnMut(
+            &SlashCommandOutputSection<language::Anchor>,
+            &Range<usize>,
+            &str,
+        ) -> Option<String>,
+        cx: &mut ModelContext<Self>,
+    ) -> bool {
+        let version = self.version.clone();
+        let (edited, replaced_sections) = self.buffer.update(cx, |buffer, cx| {
+            let mut edited = false;
+            let mut replaced_sections = Vec::new();
+            for ix in (0..self.slash_command_output_sections.len()).rev() {
+                let section = &self.slash_command_output_sections[ix];
+                let range = section.range.to_offset(buffer);
+                if range.is_empty() {
+                    continue;
+                }
+                let old_text = buffer.text_for_range(range.clone()).collect::<String>();
+                let Some(new_text) = new_text(section, &range, &old_text) else {
+                    continue;
+                };
+
+                edited = true;
+                let section = self.slash_command_output_sections.remove(ix);
+                buffer.edit([(range.clone(), new_text.as_str())], None, cx);
+                if !new_text.is_empty() {
+                    replaced_sections.push(SlashCommandOutputSection {
+                        range: buffer.anchor_after(range.start)
+                            ..buffer.anchor_before(range.start + new_text.len()),
+                        icon: section.icon,
+                        label: section.label,
+                    });
+                }
+            }
+            (edited, replaced_sections)
+        });
+
+        for section in replaced_sections {
+            let command_id = SlashCommandId(self.next_timestamp());
+            self.finished_slash_commands.insert(command_id);
+            let buffer = self.buffer.read(cx);
+            self.slash_command_output_sections.push(section.clone());
+            self.slash_command_output_sections
+                .sort_by(|a, b| a.range.cmp(&b.range, buffer));
+
+            let output_range = section.range.clone();
+            self.push_op(
+                ContextOperation::SlashCommandFinished {
+                    id: command_id,
+                    output_range: output_range.clone(),
+                    sections: vec![section.clone()],
+                    version: version.clone(),
+                },
+                cx,
+            );
+            cx.emit(ContextEvent::SlashCommandFinished {
+                output_range,
+                sections: vec![section],
+                run_commands_in_output: false,
+            });
+        }
+        edited
+    }
+
+    /// Trims the context with the given strategy so that it fits in `max_token_count`
+    /// tokens, returning whether anything was removed.
+    pub fn trim(
+        &mut self,
+        strategy: TrimStrategy,
+        max_token_count: usize,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<bool> {
+        match strategy {
+            TrimStrategy::DropOldestMessages => {
+                Task::ready(self.drop_oldest_messages(max_token_count, cx))
+            }
+            TrimStrategy::CollapseLargeSections => {
+                Task::ready(self.edit_leaf_slash_command_output_sections(
+                    |_, text| token_budget::summarize_section(text),
+                    cx,
+                ))
+            }
+            TrimStrategy::TruncateFilesToSymbols => self.truncate_files_to_symbols(cx),
+        }
+    }
+
+    /// Removes the oldest messages other than system messages and the last message,
+    /// until the context is estimated to fit in `max_token_count` tokens.
+    fn drop_oldest_messages(
+        &mut self,
+        max_token_count: usize,
+        cx: &mut ModelContext<Self>,
+    ) -> bool {
+        let Some(token_count) = self.token_count.filter(|count| *count > max_token_count) else {
+            return false;
+        };
+        let len = self.buffer.read(cx).len();
+        let len_to_remove = len * (token_count - max_token_count) / token_count;
+
+        let mut messages = self.messages(cx).collect::<Vec<_>>();
+        messages.pop();
+        let mut ranges_to_remove = Vec::new();
+        let mut removed_len = 0;
+        for message in messages {
+            if removed_len >= len_to_remove {
+                break;
+            }
+            if message.role == Role::System || message.offset_range.len() <= 1 {
+                continue;
+            }
+
+            // Messages start after the newline that ends the previous message, so deleting
+            // that newline removes the message instead of merging it into its neighbor. The
+            // first message can't be removed, so only its text is deleted.
+            let range = message.offset_range;
+            let range = range.start.saturating_sub(1)..range.end - 1;
+            removed_len += range.len();
+            ranges_to_remove.push((range, ""));
+        }
+
+        if ranges_to_remove.is_empty() {
+            return false;
+        }
+        self.buffer
+            .update(cx, |buffer, cx| buffer.edit(ranges_to_remove, None, cx));
+        true
+    }
+
+    /// Like [`Self::edit_slash_command_output_sections`], but only for sections that don't
+    /// contain other sections, such as the files within a directory's section.
+    fn edit_leaf_slash_command_output_sections(
+        &mut self,
+        mut new_text: impl FnMut(&SlashCommandOutputSection<language::Anchor>, &str) -> Option<String>,
+        cx: &mut ModelContext<Self>,
+    ) -> bool {
+        let buffer = self.buffer.read(cx);
+        let section_ranges = self
+            .slash_command_output_sections
+            .iter()
+            .map(|section| section.range.to_offset(buffer))
+            .collect::<Vec<_>>();
+        let leaf_section_ranges = section_ranges
+            .iter()
+            .filter(|range| {
+                !section_ranges.iter().any(|other| {
+                    other != *range && range.start <= other.start && other.end <= range.end
+                })
+            })
+            .cloned()
+            .collect::<HashSet<_>>();
+
+        self.edit_slash_command_output_sections(
+            |section, range, text| {
+                if leaf_section_ranges.contains(range) {
+                    new_text(section, text)
+                } else {
+                    None
+                }
+            },
+            cx,
+        )
+    }
+
+    /// Reduces the files included by slash commands to the symbols mentioned elsewhere in
+    /// the context, keeping only the first line of every other symbol.
+    fn truncate_files_to_symbols(&mut self, cx: &mut ModelContext<Self>) -> Task<bool> {
+        let buffer = self.buffer.read(cx);
+        let text = buffer.text();
+        let mut file_ranges = self
+            .slash_command_output_sections
+            .iter()
+            .filter(|section| section.icon == IconName::File)
+            .map(|section| section.range.to_offset(buffer))
+            .filter(|range| !range.is_empty())
+            .collect::<Vec<_>>();
+        file_ranges.sort_by_key(|range| range.start);
+
+        let mut conversation = String::new();
+        let mut conversation_end = 0;
+        for range in &file_ranges {
+            if range.start > conversation_end {
+                conversation.push_str(&text[conversation_end..range.start]);
+            }
+            conversation_end = conversation_end.max(range.end);
+        }
+        conversation.push_str(&text[conversation_end..]);
+        let relevant_names = token_budget::words(&conversation);
+        let files = file_ranges
+            .into_iter()
+            .map(|range| text[range].to_string())
+            .collect::<Vec<_>>();
+        let language_registry = self.language_registry.clone();
+
+        cx.spawn(|this, mut cx| async move {
+            let mut truncated_files = HashMap::default();
+            for file in files {
+                let Some((fence, content, rest)) = token_budget::split_code_block(&file) else {
+                    continue;
+                };
+                let Some(path) = token_budget::path_from_fence(fence) else {
+                    continue;
+                };
+                let Some(language) = language_registry.language_for_file_path(&path).await.ok()
+                else {
+                    continue;
+                };
+
+                let Some(symbols) = file_symbols(content, language, &mut cx).await.log_err() else {
+                    continue;
+                };
+                if symbols.is_empty() {
+                    continue;
+                }
+                let content = token_budget::truncate_to_symbols(content, &symbols, &relevant_names);
+                truncated_files.insert(file.clone(), format!("{fence}\n{content}{rest}"));
+            }
+
+            this.update(&mut cx, |this, cx| {
+                this.edit_leaf_slash_command_output_sections(
+                    |_, text| truncated_files.remove(text),
+                    cx,
+                )
+            })
+            .unwrap_or(false)
+        })
+    }
+
     pub fn completion_provider_changed(&mut self, cx: &mut ModelContext<Self>) {
         self.count_remaining_tokens(cx);
     }
@@ -1141,7 +1672,7 @@ impl Context {
             }
 
             let request = self.to_completion_request(cx);
-            let stream = CompletionProvider::global(cx).complete(request, cx);
+            let stream = CompletionProvider::global(cx).complete_with_tools(request, cx);
             let assistant_message = self
                 .insert_message_after(last_message_id, Role::Assistant, MessageStatus::Pending, cx)
                 .unwrap();
@@ -1150,6 +1681,7 @@ impl Context {
             let user_message = self
                 .insert_message_after(assistant_message.id, Role::User, MessageStatus::Done, cx)
                 .unwrap();
+            let user_message_id = user_message.id;
             user_messages.push(user_message);
 
             let task = cx.spawn({
@@ -1158,13 +1690,20 @@ impl Context {
                     let mut response_latency = None;
                     let stream_completion = async {
                         let request_start = Instant::now();
-                        let mut messages = stream.await.inner.await?;
+                        let mut events = stream.await.inner.await?;
+                        let mut tool_calls = Vec::new();
 
-                        while let Some(message) = messages.next().await {
+                        while let Some(event) = events.next().await {
                             if response_latency.is_none() {
                                 response_latency = Some(request_start.elapsed());
                             }
-                            let text = message?;
+                            let text = match event? {
+                                CompletionEvent::Text(text) => text,
+                                CompletionEvent::ToolCall(tool_call) => {
+                                    tool_calls.push(tool_call);
+                                    continue;
+                                }
+                            };
 
                             this.update(&mut cx, |this, cx| {
                                 let message_ix = this
@@ -1204,15 +1743,16 @@ impl Context {
                             this.summarize(cx);
                         })?;
 
-                        anyhow::Ok(())
+                        anyhow::Ok(tool_calls)
                     };
 
                     let result = stream_completion.await;
 
                     this.update(&mut cx, |this, cx| {
-                        let error_message = result
-                            .err()
-                            .map(|error| error.to_string().trim().to_string());
+                        let (tool_calls, error_message) = match result {
+                            Ok(tool_calls) => (tool_calls, None),
+                            Err(error) => (Vec::new(), Some(error.to_string().trim().to_string())),
+                        };
 
                         this.update_metadata(assistant_message_id, cx, |metadata| {
                             if let Some(error_message) = error_message.as_ref() {
@@ -1220,9 +1760,18 @@ impl Context {
                                     MessageStatus::Error(SharedString::from(error_message.clone()));
                             } else {
                                 metadata.status = MessageStatus::Done;
+                                metadata.tool_calls = tool_calls.clone();
                             }
                         });
 
+                        if !tool_calls.is_empty() {
+                            cx.emit(ContextEvent::ToolCallsRequested {
+                                assistant_message_id,
+                                user_message_id,
+                                tool_calls,
+                            });
+                        }
+
                         if let Some(telemetry) = this.telemetry.as_ref() {
                             let model = CompletionProvider::global(cx).model();
                             telemetry.report_assistant_event(
@@ -1248,19 +1797,46 @@ impl Context {
     }
 
     pub fn to_completion_request(&self, cx: &AppContext) -> LanguageModelRequest {
+        let buffer = self.buffer.read(cx);
         let messages = self
             .messages(cx)
             .filter(|message| matches!(message.status, MessageStatus::Done))
-            .map(|message| message.to_request_message(self.buffer.read(cx)));
+            .map(|message| LanguageModelRequestMessage {
+                images: self.images_in_range(message.offset_range.clone(), buffer),
+                ..message.to_request_message(buffer)
+            });
 
+        let completion_provider = CompletionProvider::global(cx);
         LanguageModelRequest {
-            model: CompletionProvider::global(cx).model(),
+            model: completion_provider.model(),
             messages: messages.collect(),
             stop: vec![],
             temperature: 1.0,
+            tools: if completion_provider.supports_tools() {
+                self.tools.clone()
+            } else {
+                Vec::new()
+            },
         }
     }
 
+    /// Sets the tools offered to the model on subsequent completions, if the
+    /// completion provider supports them.
+    pub fn set_tools(&mut self, tools: Vec<ToolFunctionDefinition>) {
+        self.tools = tools;
+    }
+
+    pub fn set_tool_results(
+        &mut self,
+        message_id: MessageId,
+        tool_results: Vec<LanguageModelToolResult>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.update_metadata(message_id, cx, |metadata| {
+            metadata.tool_results = tool_results;
+        });
+    }
+
     pub fn cancel_last_assist(&mut self) -> bool {
         self.pending_completions.pop().is_some()
     }
@@ -1337,6 +1913,8 @@ impl Context {
                 role,
                 status,
                 timestamp: anchor.id.0,
+                tool_calls: Vec::new(),
+                tool_results: Vec::new(),
             };
             self.insert_message(anchor.clone(), metadata.clone(), cx);
             self.push_op(
@@ -1401,6 +1979,8 @@ impl Context {
                 role,
                 status: MessageStatus::Done,
                 timestamp: suffix.id.0,
+                tool_calls: Vec::new(),
+                tool_results: Vec::new(),
             };
             self.insert_message(suffix.clone(), suffix_metadata.clone(), cx);
             self.push_op(
@@ -1450,6 +2030,8 @@ impl Context {
                         role,
                         status: MessageStatus::Done,
                         timestamp: selection.id.0,
+                        tool_calls: Vec::new(),
+                        tool_results: Vec::new(),
                     };
                     self.insert_message(selection.clone(), selection_metadata.clone(), cx);
                     self.push_op(
@@ -1501,18 +2083,27 @@ impl Context {
                 return;
             }
 
+            // Tool calls are left out, as no tools are offered when summarizing.
             let messages = self
                 .messages(cx)
-                .map(|message| message.to_request_message(self.buffer.read(cx)))
+                .map(|message| LanguageModelRequestMessage {
+                    tool_calls: Vec::new(),
+                    tool_results: Vec::new(),
+                    ..message.to_request_message(self.buffer.read(cx))
+                })
                 .chain(Some(LanguageModelRequestMessage {
                     role: Role::User,
                     content: "Summarize the context into a short title without punctuation.".into(),
+                    images: Vec::new(),
+                    tool_calls: Vec::new(),
+                    tool_results: Vec::new(),
                 }));
             let request = LanguageModelRequest {
                 model: CompletionProvider::global(cx).model(),
                 messages: messages.collect(),
                 stop: vec![],
                 temperature: 1.0,
+                tools: Vec::new(),
             };
 
             let stream = CompletionProvider::global(cx).complete(request, cx);
@@ -1631,6 +2222,8 @@ impl Context {
                     anchor: message_anchor.start,
                     role: metadata.role,
                     status: metadata.status.clone(),
+                    tool_calls: metadata.tool_calls.clone(),
+                    tool_results: metadata.tool_results.clone(),
                 });
             }
             None
@@ -1838,6 +2431,13 @@ pub struct SavedMessage {
     pub metadata: MessageMetadata,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct SavedImage {
+    pub id: ImageId,
+    pub start: usize,
+    pub image: LanguageModelImage,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct SavedContext {
     pub id: Option<ContextId>,
@@ -1848,6 +2448,8 @@ pub struct SavedContext {
     pub summary: String,
     pub slash_command_output_sections:
         Vec<assistant_slash_command::SlashCommandOutputSection<usize>>,
+    #[serde(default)]
+    pub images: Vec<SavedImage>,
 }
 
 impl SavedContext {
@@ -1907,6 +2509,8 @@ impl SavedContext {
                         role: message.metadata.role,
                         status: message.metadata.status,
                         timestamp: message.metadata.timestamp,
+                        tool_calls: message.metadata.tool_calls,
+                        tool_results: message.metadata.tool_results,
                     },
                     version: version.clone(),
                 });
@@ -1923,12 +2527,27 @@ impl SavedContext {
                     role: metadata.role,
                     status: metadata.status,
                     timestamp,
+                    tool_calls: metadata.tool_calls,
+                    tool_results: metadata.tool_results,
                 },
                 version: version.clone(),
             });
             version.observe(timestamp);
         }
 
+        for saved_image in self.images {
+            operations.push(ContextOperation::InsertImage {
+                anchor: ImageAnchor {
+                    id: saved_image.id,
+                    start: buffer.read(cx).anchor_after(saved_image.start),
+                },
+                image: saved_image.image,
+                version: version.clone(),
+            });
+            version.observe(saved_image.id.0);
+            next_timestamp.observe(saved_image.id.0);
+        }
+
         let timestamp = next_timestamp.tick();
         operations.push(ContextOperation::SlashCommandFinished {
             id: SlashCommandId(timestamp),
@@ -2017,12 +2636,15 @@ impl SavedContextV0_3_0 {
                             role: metadata.role,
                             status: metadata.status.clone(),
                             timestamp,
+                            tool_calls: Vec::new(),
+                            tool_results: Vec::new(),
                         },
                     })
                 })
                 .collect(),
             summary: self.summary,
             slash_command_output_sections: self.slash_command_output_sections,
+            images: Vec::new(),
         }
     }
 }
@@ -2093,6 +2715,40 @@ pub struct SavedContextMetadata {
     pub mtime: chrono::DateTime<chrono::Local>,
 }
 
+/// Returns the outline of a file's content as a list of symbols.
+async fn file_symbols(
+    content: &str,
+    language: Arc<Language>,
+    cx: &mut AsyncAppContext,
+) -> Result<Vec<token_budget::Symbol>> {
+    let buffer = cx.new_model(|cx| Buffer::local(content, cx).with_language(language, cx))?;
+    let mut parse_status = buffer.read_with(cx, |buffer, _| buffer.parse_status())?;
+    while *parse_status.borrow() != ParseStatus::Idle {
+        parse_status.changed().await?;
+    }
+
+    buffer.read_with(cx, |buffer, _| {
+        let snapshot = buffer.snapshot();
+        let Some(outline) = snapshot.outline(None) else {
+            return Vec::new();
+        };
+        outline
+            .items
+            .into_iter()
+            .map(|item| token_budget::Symbol {
+                depth: item.depth,
+                rows: item.range.start.to_point(&snapshot).row
+                    ..item.range.end.to_point(&snapshot).row + 1,
+                names: item
+                    .name_ranges
+                    .iter()
+                    .map(|range| item.text[range.clone()].to_string())
+                    .collect(),
+            })
+            .collect()
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2104,7 +2760,7 @@ mod tests {
     use assistant_slash_command::{ArgumentCompletion, SlashCommand};
     use fs::FakeFs;
     use gpui::{AppContext, TestAppContext, WeakView};
-    use language::LspAdapterDelegate;
+    use language::{LanguageConfig, LanguageMatcher, LspAdapterDelegate};
     use parking_lot::Mutex;
     use project::Project;
     use rand::prelude::*;
@@ -2343,6 +2999,232 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    fn test_drop_oldest_messages(cx: &mut AppContext) {
+        let settings_store = SettingsStore::test(cx);
+        cx.set_global(settings_store);
+        FakeCompletionProvider::setup_test(cx);
+        assistant_panel::init(cx);
+        let registry = Arc::new(LanguageRegistry::test(cx.background_executor().clone()));
+
+        let context = cx.new_model(|cx| Context::local(registry, None, cx));
+        let buffer = context.read(cx).buffer.clone();
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(0..0, "sys\nold\nans\nnew")], None, cx)
+        });
+        let message_1 = context.read(cx).message_anchors[0].clone();
+        context.update(cx, |context, cx| {
+            context.update_metadata(message_1.id, cx, |metadata| {
+                metadata.role = Role::System;
+            });
+        });
+        let (_, message_2) = context.update(cx, |context, cx| context.split_message(3..3, cx));
+        let (_, message_3) = context.update(cx, |context, cx| context.split_message(7..7, cx));
+        let (_, message_4) = context.update(cx, |context, cx| context.split_message(11..11, cx));
+        let (message_2, message_3, message_4) =
+            (message_2.unwrap(), message_3.unwrap(), message_4.unwrap());
+        assert_eq!(
+            messages(&context, cx),
+            vec![
+                (message_1.id, Role::System, 0..4),
+                (message_2.id, Role::User, 4..8),
+                (message_3.id, Role::User, 8..12),
+                (message_4.id, Role::User, 12..15),
+            ]
+        );
+
+        // The context fits, so nothing is dropped.
+        context.update(cx, |context, cx| {
+            context.token_count = Some(90);
+            assert!(!context.drop_oldest_messages(100, cx));
+        });
+
+        // A third of the context has to go, which takes the two oldest user messages. The
+        // system message and the last message are always kept.
+        context.update(cx, |context, cx| {
+            context.token_count = Some(150);
+            assert!(context.drop_oldest_messages(100, cx));
+        });
+        assert_eq!(buffer.read(cx).text(), "sys\nnew");
+        assert_eq!(
+            messages(&context, cx),
+            vec![
+                (message_1.id, Role::System, 0..4),
+                (message_4.id, Role::User, 4..7),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    fn test_replace_slash_command_output_sections(cx: &mut AppContext) {
+        let settings_store = SettingsStore::test(cx);
+        cx.set_global(settings_store);
+        FakeCompletionProvider::setup_test(cx);
+        assistant_panel::init(cx);
+        let registry = Arc::new(LanguageRegistry::test(cx.background_executor().clone()));
+
+        let context = cx.new_model(|cx| Context::local(registry, None, cx));
+        let buffer = context.read(cx).buffer.clone();
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(0..0, "a\nRULES\nb\nOTHER\nc\n")], None, cx)
+        });
+        context.update(cx, |context, cx| {
+            let buffer = context.buffer.read(cx);
+            context.slash_command_output_sections = vec![
+                SlashCommandOutputSection {
+                    range: buffer.anchor_after(2)..buffer.anchor_before(7),
+                    icon: IconName::Book,
+                    label: "Project Rules".into(),
+                },
+                SlashCommandOutputSection {
+                    range: buffer.anchor_after(10)..buffer.anchor_before(15),
+                    icon: IconName::File,
+                    label: "Other".into(),
+                },
+            ];
+        });
+        let sections = |cx: &AppContext| {
+            let context = context.read(cx);
+            let buffer = context.buffer.read(cx);
+            context
+                .slash_command_output_sections
+                .iter()
+                .map(|section| (section.range.to_offset(buffer), section.label.to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        // Only the sections with the given label are replaced.
+        context.update(cx, |context, cx| {
+            context.replace_slash_command_output_sections("Project Rules", Some("NEW RULES"), cx)
+        });
+        assert_eq!(buffer.read(cx).text(), "a\nNEW RULES\nb\nOTHER\nc\n");
+        assert_eq!(
+            sections(cx),
+            [
+                (2..11, "Project Rules".to_string()),
+                (14..19, "Other".to_string())
+            ]
+        );
+
+        // Replacing a section without text removes it.
+        context.update(cx, |context, cx| {
+            context.replace_slash_command_output_sections("Project Rules", None, cx)
+        });
+        assert_eq!(buffer.read(cx).text(), "a\n\nb\nOTHER\nc\n");
+        assert_eq!(sections(cx), [(5..10, "Other".to_string())]);
+
+        // Replacing a label that has no section does nothing.
+        context.update(cx, |context, cx| {
+            context.replace_slash_command_output_sections("Project Rules", Some("RULES"), cx)
+        });
+        assert_eq!(buffer.read(cx).text(), "a\n\nb\nOTHER\nc\n");
+    }
+
+    #[gpui::test]
+    async fn test_trim(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            FakeCompletionProvider::setup_test(cx);
+            assistant_panel::init(cx);
+        });
+        let registry = Arc::new(LanguageRegistry::test(cx.executor()));
+        registry.add(Arc::new(
+            Language::new(
+                LanguageConfig {
+                    name: "Rust".into(),
+                    matcher: LanguageMatcher {
+                        path_suffixes: vec!["rs".to_string()],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                Some(tree_sitter_rust::language()),
+            )
+            .with_outline_query(r#"(function_item "fn" @context name: (_) @name) @item"#)
+            .unwrap(),
+        ));
+
+        let context = cx.new_model(|cx| Context::local(registry, None, cx));
+        let buffer = context.read_with(cx, |context, _| context.buffer.clone());
+        let file = "```rs src/lib.rs\nfn a() {\n    1\n}\n\nfn b() {\n    2\n}\n```\n";
+        let notes = format!(
+            "```txt notes.txt\n{}```\n",
+            (0..1000)
+                .map(|ix| format!("line {ix}\n"))
+                .collect::<String>()
+        );
+        context.update(cx, |context, cx| {
+            let prefix = "What does `b` return?\n";
+            context.buffer.update(cx, |buffer, cx| {
+                buffer.edit([(0..0, format!("{prefix}{file}{notes}"))], None, cx)
+            });
+            let buffer = context.buffer.read(cx);
+            let file_start = prefix.len();
+            let notes_start = file_start + file.len();
+            context.slash_command_output_sections = vec![
+                SlashCommandOutputSection {
+                    range: buffer.anchor_after(file_start)..buffer.anchor_before(notes_start),
+                    icon: IconName::File,
+                    label: "src/lib.rs".into(),
+                },
+                SlashCommandOutputSection {
+                    range: buffer.anchor_after(notes_start)
+                        ..buffer.anchor_before(notes_start + notes.len()),
+                    icon: IconName::Book,
+                    label: "notes".into(),
+                },
+            ];
+        });
+
+        // Files are reduced to the symbols mentioned in the conversation.
+        let trimmed = context
+            .update(cx, |context, cx| {
+                context.trim(TrimStrategy::TruncateFilesToSymbols, 0, cx)
+            })
+            .await;
+        assert!(trimmed);
+        assert_eq!(
+            buffer.read_with(cx, |buffer, _| buffer.text()),
+            format!("What does `b` return?\n```rs src/lib.rs\nfn a() {{\n…\nfn b() {{\n    2\n}}\n```\n{notes}")
+        );
+
+        // Large sections are collapsed, and small ones are left alone.
+        let trimmed = context
+            .update(cx, |context, cx| {
+                context.trim(TrimStrategy::CollapseLargeSections, 0, cx)
+            })
+            .await;
+        assert!(trimmed);
+        let text = buffer.read_with(cx, |buffer, _| buffer.text());
+        assert!(text.starts_with("What does `b` return?\n```rs src/lib.rs\nfn a() {\n…\n"));
+        assert!(text.contains("line 7\n… 992 more lines collapsed to save tokens\n```"));
+        assert!(!text.contains("line 8\n"));
+        let sections = context.read_with(cx, |context, cx| {
+            let buffer = context.buffer.read(cx);
+            context
+                .slash_command_output_sections
+                .iter()
+                .map(|section| {
+                    buffer
+                        .text_for_range(section.range.clone())
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(sections.len(), 2);
+        assert!(sections[0].starts_with("```rs src/lib.rs\n"));
+        assert!(sections[1].starts_with("```txt notes.txt\nline 0\n"));
+
+        // Nothing is left to trim.
+        let trimmed = context
+            .update(cx, |context, cx| {
+                context.trim(TrimStrategy::CollapseLargeSections, 0, cx)
+            })
+            .await;
+        assert!(!trimmed);
+    }
+
     #[gpui::test]
     fn test_messages_for_offsets(cx: &mut AppContext) {
         let settings_store = SettingsStore::test(cx);
@@ -2660,6 +3542,16 @@ mod tests {
                 (message_2.id, Role::System, 6..6),
             ]
         );
+        let image = LanguageModelImage {
+            data: "aW1hZ2U=".into(),
+            width: 1,
+            height: 1,
+        };
+        context.update(cx, |context, cx| {
+            let position = context.buffer.read(cx).anchor_after(4);
+            context.insert_image(image.clone(), position, cx);
+        });
+        assert_eq!(cx.read(|cx| images(&context, cx)), [(4, image.clone())]);
 
         let serialized_context = context.read_with(cx, |context, cx| context.serialize(cx));
         let deserialized_context = cx.new_model(|cx| {
@@ -2685,6 +3577,46 @@ mod tests {
                 (message_2.id, Role::System, 6..6),
             ]
         );
+        assert_eq!(
+            cx.read(|cx| images(&deserialized_context, cx)),
+            [(4, image.clone())]
+        );
+        let request =
+            deserialized_context.read_with(cx, |context, cx| context.to_completion_request(cx));
+        assert_eq!(
+            request
+                .messages
+                .iter()
+                .map(|message| message.images.len())
+                .collect::<Vec<_>>(),
+            [0, 1, 0]
+        );
+
+        // Images are replicated to collaborators through the proto form of the operations.
+        let ops = context
+            .read_with(cx, |context, cx| {
+                context.serialize_ops(&ContextVersion::default(), cx)
+            })
+            .await
+            .into_iter()
+            .map(ContextOperation::from_proto)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let context_id = context.read_with(cx, |context, _| context.id().clone());
+        let replica = cx.new_model(|cx| {
+            Context::new(
+                context_id,
+                1,
+                language::Capability::ReadWrite,
+                registry.clone(),
+                None,
+                cx,
+            )
+        });
+        replica
+            .update(cx, |replica, cx| replica.apply_ops(ops, cx))
+            .unwrap();
+        assert_eq!(cx.read(|cx| images(&replica, cx)), [(4, image.clone())]);
     }
 
     #[gpui::test(iterations = 100)]
@@ -2841,6 +3773,7 @@ mod tests {
                             Task::ready(Ok(SlashCommandOutput {
                                 text: output_text,
                                 sections,
+                                images: Vec::new(),
                                 run_commands_in_text: false,
                             })),
                             true,
@@ -2871,6 +3804,28 @@ mod tests {
                     });
                     mutation_count -= 1;
                 }
+                85..=89 if mutation_count > 0 => {
+                    context.update(cx, |context, cx| {
+                        let offset = context.buffer.read(cx).random_byte_range(0, &mut rng).start;
+                        let image = LanguageModelImage {
+                            data: RandomCharIter::new(&mut rng)
+                                .filter(|c| c.is_ascii_alphanumeric())
+                                .take(8)
+                                .collect(),
+                            width: rng.gen_range(1..=100),
+                            height: rng.gen_range(1..=100),
+                        };
+                        log::info!(
+                            "Context {}: insert image at {} with {:?}",
+                            context_index,
+                            offset,
+                            image
+                        );
+                        let position = context.buffer.read(cx).anchor_after(offset);
+                        context.insert_image(image, position, cx);
+                    });
+                    mutation_count -= 1;
+                }
                 _ => {
                     let replica_id = context_index as ReplicaId;
                     if network.lock().is_disconnected(replica_id) {
@@ -2952,6 +3907,15 @@ mod tests {
                 );
             }
         });
+        let first_images = cx.read(|cx| images(&contexts[0], cx));
+        for context in &contexts[1..] {
+            assert_eq!(
+                cx.read(|cx| images(context, cx)),
+                first_images,
+                "Context {} images != Context 0 images",
+                context.read_with(cx, |context, cx| context.buffer.read(cx).replica_id())
+            );
+        }
     }
 
     fn messages(context: &Model<Context>, cx: &AppContext) -> Vec<(MessageId, Role, Range<usize>)> {
@@ -2962,6 +3926,18 @@ mod tests {
             .collect()
     }
 
+    fn images(context: &Model<Context>, cx: &AppContext) -> Vec<(usize, LanguageModelImage)> {
+        let context = context.read(cx);
+        let buffer = context.buffer.read(cx);
+        context
+            .image_anchors(cx)
+            .map(|anchor| {
+                let image = context.image(anchor.id).unwrap().clone();
+                (anchor.start.to_offset(buffer), image)
+            })
+            .collect()
+    }
+
     #[derive(Clone)]
     struct FakeSlashCommand(String);
 
@@ -3002,6 +3978,7 @@ mod tests {
             Task::ready(Ok(SlashCommandOutput {
                 text: format!("Executed fake command: {}", self.0),
                 sections: vec![],
+                images: Vec::new(),
                 run_commands_in_text: false,
             }))
         }
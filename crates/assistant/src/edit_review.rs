@@ -0,0 +1,765 @@
+use collections::HashSet;
+use editor::{
+    display_map::{BlockDisposition, BlockId, BlockProperties, BlockStyle, RenderBlock},
+    Editor, EditorEvent, ExcerptId, MultiBuffer, DEFAULT_MULTIBUFFER_CONTEXT,
+};
+use gpui::{
+    AnyElement, AnyView, AppContext, EventEmitter, FocusHandle, FocusableView, Model, Render,
+    Subscription, View, ViewContext, WeakView,
+};
+use language::{AutoindentMode, Buffer, Capability, OffsetRangeExt as _, Point, ToPoint as _};
+use project::{Project, ProjectTransaction};
+use similar::TextDiff;
+use std::{any::TypeId, ops::Range};
+use ui::prelude::*;
+use workspace::item::{Item, ItemEvent, TabContentParams};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum HunkStatus {
+    Pending,
+    Accepted,
+    Rejected,
+}
+
+/// A contiguous run of changed lines proposed by the assistant.
+struct ProposedHunk {
+    /// The lines of the original buffer replaced by the hunk.
+    old_range: Range<language::Anchor>,
+    new_text: String,
+    /// A read-only editor displaying `new_text`.
+    new_text_editor: Option<View<Editor>>,
+    status: HunkStatus,
+}
+
+struct ProposedFileEdits {
+    buffer: Model<Buffer>,
+    excerpt_id: ExcerptId,
+    hunks: Vec<ProposedHunk>,
+}
+
+/// A multibuffer showing edits the assistant proposed across several files,
+/// which lets the user accept or reject each hunk before applying them as a
+/// single project transaction.
+pub struct EditReview {
+    title: SharedString,
+    editor: View<Editor>,
+    multibuffer: Model<MultiBuffer>,
+    files: Vec<ProposedFileEdits>,
+    blocks: HashSet<BlockId>,
+    applied: bool,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl EditReview {
+    pub fn new(
+        title: String,
+        edits: Vec<(Model<Buffer>, Vec<(Range<language::Anchor>, String)>)>,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let replica_id = project.read(cx).replica_id();
+        let multibuffer = cx.new_model(|_| {
+            MultiBuffer::new(replica_id, Capability::ReadWrite).with_title(title.clone())
+        });
+
+        let mut files = Vec::new();
+        for (buffer, edits) in edits {
+            let snapshot = buffer.read(cx).snapshot();
+            let language = snapshot.language().cloned();
+            let mut hunks = Vec::new();
+            for (range, new_text) in edits {
+                let range = range.to_offset(&snapshot);
+                let old_text = snapshot.text_for_range(range.clone()).collect::<String>();
+                for (old_range, new_range) in line_hunks(&old_text, &new_text) {
+                    let new_text = new_text[new_range].to_string();
+                    let new_text_editor = (!new_text.is_empty()).then(|| {
+                        let language = language.clone();
+                        let text = new_text.trim_end_matches('\n').to_string();
+                        cx.new_view(|cx| {
+                            let buffer = cx.new_model(|cx| {
+                                let mut buffer = Buffer::local(text, cx);
+                                buffer.set_language(language, cx);
+                                buffer
+                            });
+                            let mut editor = Editor::for_buffer(buffer, None, cx);
+                            editor.set_show_gutter(false, cx);
+                            editor.set_show_wrap_guides(false, cx);
+                            editor.scroll_manager.set_forbid_vertical_scroll(true);
+                            editor.set_read_only(true);
+                            editor
+                        })
+                    });
+                    hunks.push(ProposedHunk {
+                        old_range: snapshot.anchor_after(range.start + old_range.start)
+                            ..snapshot.anchor_before(range.start + old_range.end),
+                        new_text,
+                        new_text_editor,
+                        status: HunkStatus::Pending,
+                    });
+                }
+            }
+            if hunks.is_empty() {
+                continue;
+            }
+
+            hunks.sort_by(|a, b| a.old_range.start.cmp(&b.old_range.start, &snapshot));
+            let excerpt_ranges = multibuffer.update(cx, |multibuffer, cx| {
+                multibuffer.push_excerpts_with_context_lines(
+                    buffer.clone(),
+                    hunks
+                        .iter()
+                        .map(|hunk| hunk.old_range.to_point(&snapshot))
+                        .collect(),
+                    DEFAULT_MULTIBUFFER_CONTEXT,
+                    cx,
+                )
+            });
+            if let Some(excerpt_range) = excerpt_ranges.first() {
+                files.push(ProposedFileEdits {
+                    buffer,
+                    excerpt_id: excerpt_range.start.excerpt_id,
+                    hunks,
+                });
+            }
+        }
+
+        let editor =
+            cx.new_view(|cx| Editor::for_multibuffer(multibuffer.clone(), Some(project), true, cx));
+        let _subscriptions = vec![cx.subscribe(&editor, |_, _, event: &EditorEvent, cx| {
+            cx.emit(event.clone())
+        })];
+
+        let mut this = Self {
+            title: title.into(),
+            editor,
+            multibuffer,
+            files,
+            blocks: HashSet::default(),
+            applied: false,
+            _subscriptions,
+        };
+        this.update_blocks(cx);
+        this
+    }
+
+    fn set_hunk_status(
+        &mut self,
+        file_ix: usize,
+        hunk_ix: Option<usize>,
+        status: HunkStatus,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(file) = self.files.get_mut(file_ix) else {
+            return;
+        };
+        for (ix, hunk) in file.hunks.iter_mut().enumerate() {
+            if hunk_ix.map_or(true, |hunk_ix| hunk_ix == ix) {
+                hunk.status = status;
+            }
+        }
+        self.update_blocks(cx);
+        cx.notify();
+    }
+
+    fn set_all_hunk_statuses(&mut self, status: HunkStatus, cx: &mut ViewContext<Self>) {
+        for hunk in self.files.iter_mut().flat_map(|file| file.hunks.iter_mut()) {
+            hunk.status = status;
+        }
+        self.update_blocks(cx);
+        cx.notify();
+    }
+
+    fn hunk_count(&self, status: HunkStatus) -> usize {
+        self.files
+            .iter()
+            .flat_map(|file| &file.hunks)
+            .filter(|hunk| hunk.status == status)
+            .count()
+    }
+
+    /// Applies the accepted hunks as one project transaction, which can be
+    /// undone at once from this editor.
+    fn apply(&mut self, cx: &mut ViewContext<Self>) {
+        if self.applied || self.hunk_count(HunkStatus::Accepted) == 0 {
+            return;
+        }
+
+        let mut project_transaction = ProjectTransaction::default();
+        for file in &self.files {
+            let edits = file
+                .hunks
+                .iter()
+                .filter(|hunk| hunk.status == HunkStatus::Accepted)
+                .map(|hunk| (hunk.old_range.clone(), hunk.new_text.clone()))
+                .collect::<Vec<_>>();
+            if edits.is_empty() {
+                continue;
+            }
+
+            file.buffer.update(cx, |buffer, cx| {
+                buffer.finalize_last_transaction();
+                buffer.start_transaction();
+                buffer.edit(
+                    edits,
+                    Some(AutoindentMode::Block {
+                        original_indent_columns: Vec::new(),
+                    }),
+                    cx,
+                );
+                buffer.end_transaction(cx);
+                if let Some(transaction) = buffer.finalize_last_transaction() {
+                    project_transaction
+                        .0
+                        .insert(file.buffer.clone(), transaction.clone());
+                }
+            });
+        }
+
+        self.multibuffer.update(cx, |multibuffer, cx| {
+            multibuffer.push_transaction(&project_transaction.0, cx)
+        });
+        self.applied = true;
+        self.update_blocks(cx);
+        cx.notify();
+    }
+
+    fn update_blocks(&mut self, cx: &mut ViewContext<Self>) {
+        enum ProposedDeletion {}
+
+        let review = cx.view().downgrade();
+        let old_blocks = std::mem::take(&mut self.blocks);
+        let mut new_blocks = Vec::new();
+        let mut deleted_rows = Vec::new();
+        let multibuffer = self.multibuffer.read(cx).snapshot(cx);
+
+        if !self.applied {
+            for (file_ix, file) in self.files.iter().enumerate() {
+                let buffer = file.buffer.read(cx);
+                let Some(first_hunk) = file.hunks.first() else {
+                    continue;
+                };
+
+                let first_row = first_hunk
+                    .old_range
+                    .start
+                    .to_point(buffer)
+                    .row
+                    .saturating_sub(DEFAULT_MULTIBUFFER_CONTEXT);
+                if let Some(position) = multibuffer.anchor_in_excerpt(
+                    file.excerpt_id,
+                    buffer.anchor_before(Point::new(first_row, 0)),
+                ) {
+                    new_blocks.push(BlockProperties {
+                        position,
+                        height: 1,
+                        style: BlockStyle::Sticky,
+                        render: render_file_controls(review.clone(), file_ix),
+                        disposition: BlockDisposition::Above,
+                    });
+                }
+
+                for (hunk_ix, hunk) in file.hunks.iter().enumerate() {
+                    let old_range = hunk.old_range.to_offset(buffer);
+                    if hunk.status != HunkStatus::Rejected && !old_range.is_empty() {
+                        let last_deleted = buffer.anchor_before(old_range.end - 1);
+                        if let Some((start, end)) = multibuffer
+                            .anchor_in_excerpt(file.excerpt_id, hunk.old_range.start)
+                            .zip(multibuffer.anchor_in_excerpt(file.excerpt_id, last_deleted))
+                        {
+                            deleted_rows.push(start..=end);
+                        }
+                    }
+
+                    // Show the new text after the lines it replaces.
+                    let disposition = if buffer.offset_to_point(old_range.end).column == 0 {
+                        BlockDisposition::Above
+                    } else {
+                        BlockDisposition::Below
+                    };
+                    let Some(position) =
+                        multibuffer.anchor_in_excerpt(file.excerpt_id, hunk.old_range.end)
+                    else {
+                        continue;
+                    };
+                    let new_line_count = hunk
+                        .new_text_editor
+                        .as_ref()
+                        .map_or(0, |_| hunk.new_text.trim_end_matches('\n').lines().count());
+                    new_blocks.push(BlockProperties {
+                        position,
+                        height: (new_line_count + 1).min(u8::MAX as usize) as u8,
+                        style: BlockStyle::Flex,
+                        render: render_hunk(
+                            review.clone(),
+                            file_ix,
+                            hunk_ix,
+                            hunk.status,
+                            hunk.new_text_editor.clone(),
+                        ),
+                        disposition,
+                    });
+                }
+            }
+        }
+
+        self.editor.update(cx, |editor, cx| {
+            editor.clear_row_highlights::<ProposedDeletion>();
+            for rows in deleted_rows {
+                editor.highlight_rows::<ProposedDeletion>(
+                    rows,
+                    Some(cx.theme().status().deleted_background),
+                    false,
+                    cx,
+                );
+            }
+            editor.remove_blocks(old_blocks, None, cx);
+            self.blocks = editor
+                .insert_blocks(new_blocks, None, cx)
+                .into_iter()
+                .collect();
+        });
+    }
+}
+
+fn render_file_controls(review: WeakView<EditReview>, file_ix: usize) -> RenderBlock {
+    Box::new(move |cx| {
+        h_flex()
+            .id(("file-controls", file_ix))
+            .pl(cx.gutter_dimensions.full_width())
+            .gap_1()
+            .child(
+                Button::new(("accept-file", file_ix), "Accept File")
+                    .icon(IconName::Check)
+                    .icon_position(IconPosition::Start)
+                    .label_size(LabelSize::Small)
+                    .on_click({
+                        let review = review.clone();
+                        move |_, cx| {
+                            review
+                                .update(cx, |review, cx| {
+                                    review.set_hunk_status(file_ix, None, HunkStatus::Accepted, cx)
+                                })
+                                .ok();
+                        }
+                    }),
+            )
+            .child(
+                Button::new(("reject-file", file_ix), "Reject File")
+                    .icon(IconName::Close)
+                    .icon_position(IconPosition::Start)
+                    .label_size(LabelSize::Small)
+                    .on_click({
+                        let review = review.clone();
+                        move |_, cx| {
+                            review
+                                .update(cx, |review, cx| {
+                                    review.set_hunk_status(file_ix, None, HunkStatus::Rejected, cx)
+                                })
+                                .ok();
+                        }
+                    }),
+            )
+            .into_any_element()
+    })
+}
+
+fn render_hunk(
+    review: WeakView<EditReview>,
+    file_ix: usize,
+    hunk_ix: usize,
+    status: HunkStatus,
+    new_text_editor: Option<View<Editor>>,
+) -> RenderBlock {
+    Box::new(move |cx| {
+        let set_status = |status: HunkStatus| {
+            let review = review.clone();
+            move |_: &gpui::ClickEvent, cx: &mut WindowContext| {
+                review
+                    .update(cx, |review, cx| {
+                        review.set_hunk_status(file_ix, Some(hunk_ix), status, cx)
+                    })
+                    .ok();
+            }
+        };
+        let id = file_ix * 10_000 + hunk_ix;
+
+        v_flex()
+            .id(("hunk", id))
+            .size_full()
+            .children(new_text_editor.clone().map(|editor| {
+                div()
+                    .flex_1()
+                    .pl(cx.gutter_dimensions.full_width())
+                    .when(status != HunkStatus::Rejected, |this| {
+                        this.bg(cx.theme().status().created_background)
+                    })
+                    .child(editor)
+            }))
+            .child(
+                h_flex()
+                    .h(cx.line_height)
+                    .pl(cx.gutter_dimensions.full_width())
+                    .gap_1()
+                    .child(
+                        Button::new(("accept-hunk", id), "Accept")
+                            .label_size(LabelSize::Small)
+                            .selected(status == HunkStatus::Accepted)
+                            .style(if status == HunkStatus::Accepted {
+                                ButtonStyle::Filled
+                            } else {
+                                ButtonStyle::Subtle
+                            })
+                            .on_click(set_status(HunkStatus::Accepted)),
+                    )
+                    .child(
+                        Button::new(("reject-hunk", id), "Reject")
+                            .label_size(LabelSize::Small)
+                            .selected(status == HunkStatus::Rejected)
+                            .style(if status == HunkStatus::Rejected {
+                                ButtonStyle::Filled
+                            } else {
+                                ButtonStyle::Subtle
+                            })
+                            .on_click(set_status(HunkStatus::Rejected)),
+                    ),
+            )
+            .into_any_element()
+    })
+}
+
+/// Returns the line-level changes between `old` and `new`, as pairs of byte
+/// ranges in `old` and `new`.
+fn line_hunks(old: &str, new: &str) -> Vec<(Range<usize>, Range<usize>)> {
+    let old_line_offsets = line_offsets(old);
+    let new_line_offsets = line_offsets(new);
+    TextDiff::from_lines(old, new)
+        .grouped_ops(0)
+        .into_iter()
+        .filter_map(|group| {
+            let first = group.first()?;
+            let last = group.last()?;
+            let old_rows = first.old_range().start..last.old_range().end;
+            let new_rows = first.new_range().start..last.new_range().end;
+            Some((
+                old_line_offsets[old_rows.start]..old_line_offsets[old_rows.end],
+                new_line_offsets[new_rows.start]..new_line_offsets[new_rows.end],
+            ))
+        })
+        .collect()
+}
+
+/// Returns the offset at which each line of `text` starts, followed by the length of `text`.
+fn line_offsets(text: &str) -> Vec<usize> {
+    let mut offsets = vec![0];
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        offset += line.len();
+        offsets.push(offset);
+    }
+    offsets
+}
+
+impl EventEmitter<EditorEvent> for EditReview {}
+
+impl FocusableView for EditReview {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl Render for EditReview {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let total = self
+            .files
+            .iter()
+            .map(|file| file.hunks.len())
+            .sum::<usize>();
+        let accepted = self.hunk_count(HunkStatus::Accepted);
+        let rejected = self.hunk_count(HunkStatus::Rejected);
+        let status = if self.applied {
+            format!("Applied {accepted} of {total} hunks")
+        } else {
+            format!("{accepted} accepted, {rejected} rejected, {total} proposed")
+        };
+
+        v_flex()
+            .size_full()
+            .child(
+                h_flex()
+                    .p_2()
+                    .gap_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .bg(cx.theme().colors().editor_background)
+                    .child(
+                        Label::new(status)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(div().flex_1())
+                    .when(!self.applied, |this| {
+                        this.child(
+                            Button::new("accept-all", "Accept All")
+                                .label_size(LabelSize::Small)
+                                .on_click(cx.listener(|this, _, cx| {
+                                    this.set_all_hunk_statuses(HunkStatus::Accepted, cx)
+                                })),
+                        )
+                        .child(
+                            Button::new("reject-all", "Reject All")
+                                .label_size(LabelSize::Small)
+                                .on_click(cx.listener(|this, _, cx| {
+                                    this.set_all_hunk_statuses(HunkStatus::Rejected, cx)
+                                })),
+                        )
+                        .child(
+                            Button::new("apply", "Apply Accepted")
+                                .style(ButtonStyle::Filled)
+                                .label_size(LabelSize::Small)
+                                .disabled(accepted == 0)
+                                .on_click(cx.listener(|this, _, cx| this.apply(cx))),
+                        )
+                    }),
+            )
+            .child(div().flex_1().child(self.editor.clone()))
+    }
+}
+
+impl Item for EditReview {
+    type Event = EditorEvent;
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn tab_content(&self, params: TabContentParams, _: &WindowContext) -> AnyElement {
+        h_flex()
+            .gap_2()
+            .child(Icon::new(IconName::Sparkle).color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            }))
+            .child(Label::new(self.title.clone()).color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            }))
+            .into_any_element()
+    }
+
+    fn tab_tooltip_text(&self, _: &AppContext) -> Option<SharedString> {
+        Some(self.title.clone())
+    }
+
+    fn deactivated(&mut self, cx: &mut ViewContext<Self>) {
+        self.editor.update(cx, |editor, cx| editor.deactivated(cx));
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &AppContext,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::Item),
+    ) {
+        self.editor.for_each_project_item(cx, f)
+    }
+
+    fn is_singleton(&self, _: &AppContext) -> bool {
+        false
+    }
+
+    fn is_dirty(&self, cx: &AppContext) -> bool {
+        self.multibuffer.read(cx).is_dirty(cx)
+    }
+
+    fn has_conflict(&self, cx: &AppContext) -> bool {
+        self.multibuffer.read(cx).has_conflict(cx)
+    }
+
+    fn can_save(&self, _: &AppContext) -> bool {
+        true
+    }
+
+    fn save(
+        &mut self,
+        format: bool,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> gpui::Task<anyhow::Result<()>> {
+        self.editor.save(format, project, cx)
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a View<Self>,
+        _: &'a AppContext,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use editor::actions::{Redo, Undo};
+    use fs::FakeFs;
+    use gpui::{TestAppContext, VisualTestContext};
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    async fn test_accept_and_reject_hunks(cx: &mut TestAppContext) {
+        let (review, a, b, cx) = build_review(cx).await;
+
+        review.update(cx, |review, cx| {
+            assert_eq!(review.hunk_count(HunkStatus::Pending), 3);
+
+            review.set_hunk_status(0, Some(0), HunkStatus::Accepted, cx);
+            review.set_hunk_status(0, Some(1), HunkStatus::Rejected, cx);
+            assert_eq!(review.hunk_count(HunkStatus::Accepted), 1);
+            assert_eq!(review.hunk_count(HunkStatus::Rejected), 1);
+            assert_eq!(review.hunk_count(HunkStatus::Pending), 1);
+
+            review.set_hunk_status(1, None, HunkStatus::Accepted, cx);
+            review.set_hunk_status(1, None, HunkStatus::Rejected, cx);
+            assert_eq!(review.hunk_count(HunkStatus::Rejected), 2);
+
+            review.apply(cx);
+        });
+
+        cx.read(|cx| {
+            assert_eq!(a.read(cx).text(), "one\nTWO\nthree\nfour\n");
+            assert_eq!(b.read(cx).text(), "alpha\nbeta\n");
+        });
+
+        // Once applied, the review doesn't apply again.
+        review.update(cx, |review, cx| {
+            review.set_all_hunk_statuses(HunkStatus::Accepted, cx);
+            review.apply(cx);
+        });
+        cx.read(|cx| {
+            assert_eq!(a.read(cx).text(), "one\nTWO\nthree\nfour\n");
+            assert_eq!(b.read(cx).text(), "alpha\nbeta\n");
+        });
+    }
+
+    #[gpui::test]
+    async fn test_apply_and_undo(cx: &mut TestAppContext) {
+        let (review, a, b, cx) = build_review(cx).await;
+
+        review.update(cx, |review, cx| {
+            // Nothing is applied until a hunk is accepted.
+            review.apply(cx);
+            assert!(!review.applied);
+
+            review.set_all_hunk_statuses(HunkStatus::Accepted, cx);
+            review.apply(cx);
+            assert!(review.applied);
+        });
+        cx.read(|cx| {
+            assert_eq!(a.read(cx).text(), "one\nTWO\nthree\nFOUR\n");
+            assert_eq!(b.read(cx).text(), "alpha\nBETA\n");
+        });
+
+        // The edits across both files are undone and redone as a single transaction.
+        let editor = cx.read(|cx| review.read(cx).editor.clone());
+        editor.update(cx, |editor, cx| editor.undo(&Undo, cx));
+        cx.read(|cx| {
+            assert_eq!(a.read(cx).text(), "one\ntwo\nthree\nfour\n");
+            assert_eq!(b.read(cx).text(), "alpha\nbeta\n");
+        });
+
+        editor.update(cx, |editor, cx| editor.redo(&Redo, cx));
+        cx.read(|cx| {
+            assert_eq!(a.read(cx).text(), "one\nTWO\nthree\nFOUR\n");
+            assert_eq!(b.read(cx).text(), "alpha\nBETA\n");
+        });
+
+        // Undoing a single file afterwards doesn't touch the other one.
+        a.update(cx, |buffer, cx| buffer.undo(cx));
+        cx.read(|cx| {
+            assert_eq!(a.read(cx).text(), "one\ntwo\nthree\nfour\n");
+            assert_eq!(b.read(cx).text(), "alpha\nBETA\n");
+        });
+    }
+
+    async fn build_review(
+        cx: &mut TestAppContext,
+    ) -> (
+        View<EditReview>,
+        Model<Buffer>,
+        Model<Buffer>,
+        &mut VisualTestContext,
+    ) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            editor::init(cx);
+        });
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "a.txt": "one\ntwo\nthree\nfour\n",
+                "b.txt": "alpha\nbeta\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+        let a = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/root/a.txt", cx)
+            })
+            .await
+            .unwrap();
+        let b = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/root/b.txt", cx)
+            })
+            .await
+            .unwrap();
+
+        let edits = cx.read(|cx| {
+            let whole_buffer = |buffer: &Model<Buffer>| {
+                let buffer = buffer.read(cx);
+                buffer.anchor_before(0)..buffer.anchor_after(buffer.len())
+            };
+            vec![
+                (
+                    a.clone(),
+                    vec![(whole_buffer(&a), "one\nTWO\nthree\nFOUR\n".to_string())],
+                ),
+                (
+                    b.clone(),
+                    vec![(whole_buffer(&b), "alpha\nBETA\n".to_string())],
+                ),
+            ]
+        });
+        let (review, cx) = cx
+            .add_window_view(|cx| EditReview::new("Edits".to_string(), edits, project.clone(), cx));
+        (review, a, b, cx)
+    }
+
+    #[test]
+    fn test_line_hunks() {
+        let old = "a\nb\nc\nd\n";
+        let new = "a\nB\nc\nd\ne\n";
+        let hunks = line_hunks(old, new);
+        assert_eq!(hunks, [(2..4, 2..4), (8..8, 8..10)]);
+        assert_eq!(&new[hunks[1].1.clone()], "e\n");
+
+        assert_eq!(line_hunks("a\nb\n", "b\n"), [(0..2, 0..0)]);
+        assert!(line_hunks("same\n", "same\n").is_empty());
+    }
+}
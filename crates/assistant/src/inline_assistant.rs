@@ -1,7 +1,8 @@
 use crate::{
     assistant_settings::AssistantSettings, humanize_token_count, prompts::generate_content_prompt,
-    AssistantPanel, AssistantPanelEvent, CompletionProvider, Hunk, LanguageModelRequest,
-    LanguageModelRequestMessage, Role, StreamingDiff,
+    slash_command::rules_command::project_rules, AssistantPanel, AssistantPanelEvent,
+    CompletionProvider, Hunk, LanguageModelRequest, LanguageModelRequestMessage, Role,
+    StreamingDiff,
 };
 use anyhow::{anyhow, Context as _, Result};
 use client::telemetry::Telemetry;
@@ -745,8 +746,8 @@ impl InlineAssistant {
         cx: &mut WindowContext,
     ) -> Task<Result<LanguageModelRequest>> {
         cx.spawn(|mut cx| async move {
-            let (user_prompt, context_request, project_name, buffer, range, model) = cx
-                .read_global(|this: &InlineAssistant, cx: &WindowContext| {
+            let (user_prompt, context_request, project_name, project_rules, buffer, range, model) =
+                cx.read_global(|this: &InlineAssistant, cx: &WindowContext| {
                     let assist = this.assists.get(&assist_id).context("invalid assist")?;
                     let decorations = assist.decorations.as_ref().context("invalid assist")?;
                     let editor = assist.editor.upgrade().context("invalid assist")?;
@@ -778,6 +779,15 @@ impl InlineAssistant {
                                 .join("/"),
                         )
                     });
+                    // An included assistant context carries its own copy of the rules.
+                    let project_rules = if context_request.is_some() {
+                        None
+                    } else {
+                        assist.workspace.as_ref().and_then(|workspace| {
+                            let workspace = workspace.upgrade()?;
+                            project_rules(workspace.read(cx).project().read(cx), cx)
+                        })
+                    };
                     let buffer = editor.read(cx).buffer().read(cx).snapshot(cx);
                     let range = assist.codegen.read(cx).range.clone();
                     let model = CompletionProvider::global(cx).model();
@@ -785,6 +795,7 @@ impl InlineAssistant {
                         user_prompt,
                         context_request,
                         project_name,
+                        project_rules,
                         buffer,
                         range,
                         model,
@@ -839,11 +850,22 @@ impl InlineAssistant {
             let mut messages = Vec::new();
             if let Some(context_request) = context_request {
                 messages = context_request.messages;
+            } else if let Some(project_rules) = project_rules {
+                messages.push(LanguageModelRequestMessage {
+                    role: Role::System,
+                    content: project_rules,
+                    images: Vec::new(),
+                    tool_calls: Vec::new(),
+                    tool_results: Vec::new(),
+                });
             }
 
             messages.push(LanguageModelRequestMessage {
                 role: Role::User,
                 content: prompt,
+                images: Vec::new(),
+                tool_calls: Vec::new(),
+                tool_results: Vec::new(),
             });
 
             Ok(LanguageModelRequest {
@@ -851,6 +873,7 @@ impl InlineAssistant {
                 messages,
                 stop: vec!["|END|>".to_string()],
                 temperature,
+                tools: Vec::new(),
             })
         })
     }
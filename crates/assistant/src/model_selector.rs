@@ -1,8 +1,11 @@
 use std::sync::Arc;
 
-use crate::{assistant_settings::AssistantSettings, CompletionProvider, ToggleModelSelector};
+use crate::{
+    assistant_settings::{AssistantProvider, AssistantSettings},
+    CompletionProvider, LanguageModel, ToggleModelSelector,
+};
 use fs::Fs;
-use settings::update_settings_file;
+use settings::{update_settings_file, Settings};
 use ui::{prelude::*, ButtonLike, ContextMenu, PopoverMenu, PopoverMenuHandle, Tooltip};
 
 #[derive(IntoElement)]
@@ -23,25 +26,45 @@ impl RenderOnce for ModelSelector {
             .with_handle(self.handle)
             .menu(move |cx| {
                 ContextMenu::build(cx, |mut menu, cx| {
-                    for model in CompletionProvider::global(cx).available_models(cx) {
-                        menu = menu.custom_entry(
-                            {
-                                let model = model.clone();
-                                move |_| Label::new(model.display_name()).into_any_element()
-                            },
+                    let settings = AssistantSettings::get_global(cx);
+                    let mut has_entries = false;
+                    // The models of OpenAI-compatible providers are listed below, under each
+                    // provider's name.
+                    if !matches!(
+                        settings.provider,
+                        AssistantProvider::OpenAiCompatible { .. }
+                    ) {
+                        for model in CompletionProvider::global(cx).available_models(cx) {
+                            menu = add_model_entry(menu, model, &self.fs);
+                            has_entries = true;
+                        }
+                    }
+
+                    for provider in &settings.openai_compatible_providers {
+                        let models = match &settings.provider {
+                            AssistantProvider::OpenAiCompatible { provider_name, .. }
+                                if *provider_name == provider.name =>
                             {
-                                let fs = self.fs.clone();
-                                let model = model.clone();
-                                move |cx| {
-                                    let model = model.clone();
-                                    update_settings_file::<AssistantSettings>(
-                                        fs.clone(),
-                                        cx,
-                                        move |settings| settings.set_model(model),
-                                    );
-                                }
-                            },
-                        );
+                                CompletionProvider::global(cx).available_models(cx)
+                            }
+                            _ => provider
+                                .models()
+                                .into_iter()
+                                .map(LanguageModel::OpenAiCompatible)
+                                .collect(),
+                        };
+                        if models.is_empty() {
+                            continue;
+                        }
+
+                        if has_entries {
+                            menu = menu.separator();
+                        }
+                        menu = menu.header(provider.name.clone());
+                        has_entries = true;
+                        for model in models {
+                            menu = add_model_entry(menu, model, &self.fs);
+                        }
                     }
                     menu
                 })
@@ -80,3 +103,21 @@ impl RenderOnce for ModelSelector {
             .attach(gpui::AnchorCorner::BottomLeft)
     }
 }
+
+fn add_model_entry(menu: ContextMenu, model: LanguageModel, fs: &Arc<dyn Fs>) -> ContextMenu {
+    menu.custom_entry(
+        {
+            let model = model.clone();
+            move |_| Label::new(model.display_name()).into_any_element()
+        },
+        {
+            let fs = fs.clone();
+            move |cx| {
+                let model = model.clone();
+                update_settings_file::<AssistantSettings>(fs.clone(), cx, move |settings| {
+                    settings.set_model(model)
+                });
+            }
+        },
+    )
+}
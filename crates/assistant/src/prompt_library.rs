@@ -1,20 +1,22 @@
 use crate::{
-    slash_command::SlashCommandCompletionProvider, AssistantPanel, CompletionProvider,
-    InlineAssist, InlineAssistant, LanguageModelRequest, LanguageModelRequestMessage, Role,
+    prompt_template::MarkdownPrompt, slash_command::SlashCommandCompletionProvider, AssistantPanel,
+    CompletionProvider, InlineAssist, InlineAssistant, LanguageModelRequest,
+    LanguageModelRequestMessage, Role,
 };
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Utc};
 use collections::{HashMap, HashSet};
 use editor::{actions::Tab, CurrentLineHighlight, Editor, EditorElement, EditorEvent, EditorStyle};
+use fs::Fs;
 use futures::{
     future::{self, BoxFuture, Shared},
-    FutureExt,
+    FutureExt, StreamExt,
 };
 use fuzzy::StringMatchCandidate;
 use gpui::{
     actions, point, size, transparent_black, AppContext, BackgroundExecutor, Bounds, EventEmitter,
-    Global, HighlightStyle, PromptLevel, ReadGlobal, Subscription, Task, TextStyle,
-    TitlebarOptions, UpdateGlobal, View, WindowBounds, WindowHandle, WindowOptions,
+    Global, HighlightStyle, PathPromptOptions, PromptLevel, ReadGlobal, Subscription, Task,
+    TextStyle, TitlebarOptions, UpdateGlobal, View, WindowBounds, WindowHandle, WindowOptions,
 };
 use heed::{types::SerdeBincode, Database, RoTxn};
 use language::{language_settings::SoftWrap, Buffer, LanguageRegistry};
@@ -26,7 +28,7 @@ use settings::Settings;
 use std::{
     cmp::Reverse,
     future::Future,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{atomic::AtomicBool, Arc},
     time::Duration,
 };
@@ -45,7 +47,9 @@ actions!(
         NewPrompt,
         DeletePrompt,
         DuplicatePrompt,
-        ToggleDefaultPrompt
+        ToggleDefaultPrompt,
+        ImportPrompts,
+        ExportPrompt
     ]
 );
 
@@ -67,6 +71,7 @@ pub fn init(cx: &mut AppContext) {
 /// initialized. If it was initialized successfully, it returns a window handle
 /// to a prompt library.
 pub fn open_prompt_library(
+    fs: Arc<dyn Fs>,
     language_registry: Arc<LanguageRegistry>,
     cx: &mut AppContext,
 ) -> Task<Result<WindowHandle<PromptLibrary>>> {
@@ -95,7 +100,7 @@ pub fn open_prompt_library(
                         window_bounds: Some(WindowBounds::Windowed(bounds)),
                         ..Default::default()
                     },
-                    |cx| cx.new_view(|cx| PromptLibrary::new(store, language_registry, cx)),
+                    |cx| cx.new_view(|cx| PromptLibrary::new(store, fs, language_registry, cx)),
                 )
             })?
         })
@@ -104,6 +109,7 @@ pub fn open_prompt_library(
 
 pub struct PromptLibrary {
     store: Arc<PromptStore>,
+    fs: Arc<dyn Fs>,
     language_registry: Arc<LanguageRegistry>,
     prompt_editors: HashMap<PromptId, PromptEditor>,
     active_prompt_id: Option<PromptId>,
@@ -276,6 +282,7 @@ impl PickerDelegate for PromptPickerDelegate {
 impl PromptLibrary {
     fn new(
         store: Arc<PromptStore>,
+        fs: Arc<dyn Fs>,
         language_registry: Arc<LanguageRegistry>,
         cx: &mut ViewContext<Self>,
     ) -> Self {
@@ -294,6 +301,7 @@ impl PromptLibrary {
         });
         Self {
             store: store.clone(),
+            fs,
             language_registry,
             prompt_editors: HashMap::default(),
             active_prompt_id: None,
@@ -614,6 +622,97 @@ impl PromptLibrary {
         }
     }
 
+    /// Imports prompts from Markdown files, or from every Markdown file in a directory.
+    /// Prompts whose title matches an existing prompt replace it.
+    pub fn import_prompts(&mut self, cx: &mut ViewContext<Self>) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: true,
+            multiple: true,
+        });
+        cx.spawn(|this, mut cx| async move {
+            let Some(paths) = paths.await.ok().flatten() else {
+                return Ok(());
+            };
+            this.update(&mut cx, |this, cx| {
+                this.import_prompts_from_paths(paths, cx)
+            })?
+            .await
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn import_prompts_from_paths(
+        &mut self,
+        paths: Vec<PathBuf>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        let fs = self.fs.clone();
+        cx.spawn(|this, mut cx| async move {
+            let prompts = read_markdown_prompts(fs.as_ref(), &paths).await?;
+
+            let saves = this.update(&mut cx, |this, cx| {
+                let saves = prompts
+                    .into_iter()
+                    .map(|prompt| {
+                        let id = prompt
+                            .title
+                            .as_deref()
+                            .and_then(|title| this.store.id_for_title(title))
+                            .unwrap_or_else(PromptId::new);
+                        this.prompt_editors.remove(&id);
+                        this.store.save(
+                            id,
+                            prompt.title.map(SharedString::from),
+                            prompt.default,
+                            prompt.body.into(),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                if let Some(active_prompt_id) = this.active_prompt_id {
+                    if !this.prompt_editors.contains_key(&active_prompt_id) {
+                        this.set_active_prompt(None, cx);
+                    }
+                }
+                this.picker.update(cx, |picker, cx| picker.refresh(cx));
+                saves
+            })?;
+            future::try_join_all(saves).await?;
+            this.update(&mut cx, |this, cx| {
+                this.picker.update(cx, |picker, cx| picker.refresh(cx))
+            })
+        })
+    }
+
+    /// Exports the active prompt as a Markdown file with front matter.
+    pub fn export_active_prompt(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(prompt_id) = self.active_prompt_id else {
+            return;
+        };
+        let (Some(prompt_metadata), Some(prompt_editor)) = (
+            self.store.metadata(prompt_id),
+            self.prompt_editors.get(&prompt_id),
+        ) else {
+            return;
+        };
+
+        let title = prompt_editor.title_editor.read(cx).text(cx);
+        let prompt = MarkdownPrompt {
+            title: (!title.is_empty()).then_some(title),
+            default: prompt_metadata.default,
+            body: prompt_editor.body_editor.read(cx).text(cx),
+        };
+        let path = cx.prompt_for_new_path(paths::home_dir());
+        let fs = self.fs.clone();
+        cx.spawn(|_, _| async move {
+            let Some(path) = path.await.ok().flatten() else {
+                return Ok(());
+            };
+            fs.atomic_write(path, prompt.to_markdown()).await
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn focus_active_prompt(&mut self, _: &Tab, cx: &mut ViewContext<Self>) {
         if let Some(active_prompt) = self.active_prompt_id {
             self.prompt_editors[&active_prompt]
@@ -741,9 +840,13 @@ impl PromptLibrary {
                                     messages: vec![LanguageModelRequestMessage {
                                         role: Role::System,
                                         content: body.to_string(),
+                                        images: Vec::new(),
+                                        tool_calls: Vec::new(),
+                                        tool_results: Vec::new(),
                                     }],
                                     stop: Vec::new(),
                                     temperature: 1.,
+                                    tools: Vec::new(),
                                 },
                                 cx,
                             )
@@ -776,6 +879,17 @@ impl PromptLibrary {
                     .w_full()
                     .flex_none()
                     .justify_end()
+                    .child(
+                        IconButton::new("import-prompts", IconName::ArrowDownFromLine)
+                            .style(ButtonStyle::Transparent)
+                            .shape(IconButtonShape::Square)
+                            .tooltip(move |cx| {
+                                Tooltip::for_action("Import Prompts", &ImportPrompts, cx)
+                            })
+                            .on_click(|_, cx| {
+                                cx.dispatch_action(Box::new(ImportPrompts));
+                            }),
+                    )
                     .child(
                         IconButton::new("new-prompt", IconName::Plus)
                             .style(ButtonStyle::Transparent)
@@ -970,6 +1084,25 @@ impl PromptLibrary {
                                                         ));
                                                     }),
                                                 )
+                                                .child(
+                                                    IconButton::new(
+                                                        "export-prompt",
+                                                        IconName::ArrowUpFromLine,
+                                                    )
+                                                    .style(ButtonStyle::Transparent)
+                                                    .shape(IconButtonShape::Square)
+                                                    .size(ButtonSize::Large)
+                                                    .tooltip(move |cx| {
+                                                        Tooltip::for_action(
+                                                            "Export Prompt",
+                                                            &ExportPrompt,
+                                                            cx,
+                                                        )
+                                                    })
+                                                    .on_click(|_, cx| {
+                                                        cx.dispatch_action(Box::new(ExportPrompt));
+                                                    }),
+                                                )
                                                 .child(
                                                     IconButton::new(
                                                         "toggle-default-prompt",
@@ -1032,6 +1165,8 @@ impl Render for PromptLibrary {
             .on_action(cx.listener(|this, &ToggleDefaultPrompt, cx| {
                 this.toggle_default_for_active_prompt(cx)
             }))
+            .on_action(cx.listener(|this, &ImportPrompts, cx| this.import_prompts(cx)))
+            .on_action(cx.listener(|this, &ExportPrompt, cx| this.export_active_prompt(cx)))
             .size_full()
             .overflow_hidden()
             .font(ui_font)
@@ -1298,9 +1433,130 @@ impl PromptStore {
     }
 }
 
+/// Reads the prompts stored in the given Markdown files, or in the Markdown files
+/// directly inside the given directories.
+async fn read_markdown_prompts(fs: &dyn Fs, paths: &[PathBuf]) -> Result<Vec<MarkdownPrompt>> {
+    let mut prompts = Vec::new();
+    for path in paths {
+        if fs.is_dir(path).await {
+            let mut entries = fs
+                .read_dir(path)
+                .await?
+                .collect::<Vec<_>>()
+                .await
+                .into_iter()
+                .collect::<Result<Vec<_>>>()?;
+            entries.sort();
+            for entry in entries {
+                if entry
+                    .extension()
+                    .map_or(false, |extension| extension == "md")
+                {
+                    prompts.push(read_markdown_prompt(fs, &entry).await?);
+                }
+            }
+        } else {
+            prompts.push(read_markdown_prompt(fs, path).await?);
+        }
+    }
+    Ok(prompts)
+}
+
+async fn read_markdown_prompt(fs: &dyn Fs, path: &Path) -> Result<MarkdownPrompt> {
+    let text = fs
+        .load(path)
+        .await
+        .map_err(|error| anyhow!("failed to read {:?}: {}", path, error))?;
+    let mut prompt = MarkdownPrompt::parse(&text);
+    if prompt.title.is_none() {
+        prompt.title = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned());
+    }
+    Ok(prompt)
+}
+
 /// Wraps a shared future to a prompt store so it can be assigned as a context global.
 pub struct GlobalPromptStore(
     Shared<BoxFuture<'static, Result<Arc<PromptStore>, Arc<anyhow::Error>>>>,
 );
 
 impl Global for GlobalPromptStore {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    async fn test_import_prompts(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+        });
+
+        let db_dir = tempfile::tempdir().unwrap();
+        let store = PromptStore::new(db_dir.path().join("prompts.mdb"), cx.executor())
+            .await
+            .map(Arc::new)
+            .unwrap();
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/prompts",
+            json!({
+                "review.md": "---\ntitle: Review\ndefault: true\n---\nReview {{file}}.\n",
+                "explain.md": "---\r\ntitle: Explain\r\n---\r\nExplain {{selection}}.\r\n",
+                "summarize.md": "Summarize {{selection}}.\n",
+                "notes.txt": "Not a prompt.\n",
+            }),
+        )
+        .await;
+        fs.insert_tree(
+            "/other",
+            json!({ "review.md": "---\ntitle: Review\n---\nReview it again.\n" }),
+        )
+        .await;
+        let language_registry = Arc::new(LanguageRegistry::test(cx.executor()));
+        let library = cx
+            .add_window(|cx| PromptLibrary::new(store.clone(), fs.clone(), language_registry, cx));
+
+        let import = |path: &str, cx: &mut TestAppContext| {
+            library
+                .update(cx, |library, cx| {
+                    library.import_prompts_from_paths(vec![PathBuf::from(path)], cx)
+                })
+                .unwrap()
+        };
+        let prompt = |title: &str| {
+            let id = store.id_for_title(title).unwrap();
+            (store.metadata(id).unwrap(), store.load(id))
+        };
+
+        import("/prompts", cx).await.unwrap();
+        assert_eq!(store.metadata_cache.read().metadata.len(), 3);
+        let (review, body) = prompt("Review");
+        assert!(review.default);
+        assert_eq!(body.await.unwrap(), "Review {{file}}.\n");
+        let (explain, body) = prompt("Explain");
+        assert!(!explain.default);
+        assert_eq!(body.await.unwrap(), "Explain {{selection}}.\n");
+        // Prompts without a title are named after their file.
+        let (_, body) = prompt("summarize");
+        assert_eq!(body.await.unwrap(), "Summarize {{selection}}.\n");
+
+        // Importing a prompt with an existing title replaces it.
+        import("/other/review.md", cx).await.unwrap();
+        assert_eq!(store.metadata_cache.read().metadata.len(), 3);
+        let (review, body) = prompt("Review");
+        assert!(!review.default);
+        assert_eq!(body.await.unwrap(), "Review it again.\n");
+
+        assert!(import("/missing.md", cx).await.is_err());
+    }
+}
@@ -0,0 +1,165 @@
+use collections::HashMap;
+use std::{fmt::Write, ops::Range};
+
+/// Variables whose values are provided by the active editor when a prompt is inserted.
+pub const BUILT_IN_VARIABLES: [&str; 3] = ["selection", "file", "language"];
+
+/// Returns the name and byte range of every `{{variable}}` placeholder in `text`.
+fn placeholders(text: &str) -> impl Iterator<Item = (&str, Range<usize>)> {
+    let mut offset = 0;
+    std::iter::from_fn(move || loop {
+        let start = offset + text[offset..].find("{{")?;
+        let end = start + 2 + text[start + 2..].find("}}")? + 2;
+        offset = end;
+
+        let name = text[start + 2..end - 2].trim();
+        let is_identifier = !name.is_empty()
+            && name
+                .chars()
+                .all(|char| char.is_alphanumeric() || char == '_' || char == '-');
+        if is_identifier {
+            return Some((name, start..end));
+        } else {
+            offset = start + 2;
+        }
+    })
+}
+
+/// Returns the distinct variables referenced by a prompt, in the order they first appear.
+pub fn variables(text: &str) -> Vec<String> {
+    let mut variables = Vec::<String>::new();
+    for (name, _) in placeholders(text) {
+        if !variables.iter().any(|variable| variable == name) {
+            variables.push(name.to_string());
+        }
+    }
+    variables
+}
+
+/// Replaces every placeholder that has a value in `values`, leaving the others untouched.
+pub fn render(text: &str, values: &HashMap<String, String>) -> String {
+    let mut rendered = String::with_capacity(text.len());
+    let mut last_end = 0;
+    for (name, range) in placeholders(text) {
+        if let Some(value) = values.get(name) {
+            rendered.push_str(&text[last_end..range.start]);
+            rendered.push_str(value);
+            last_end = range.end;
+        }
+    }
+    rendered.push_str(&text[last_end..]);
+    rendered
+}
+
+/// A prompt stored as a Markdown file, with its metadata in front matter:
+///
+/// ```markdown
+/// ---
+/// title: Explain code
+/// default: false
+/// ---
+/// Explain the following code: {{selection}}
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MarkdownPrompt {
+    pub title: Option<String>,
+    pub default: bool,
+    pub body: String,
+}
+
+impl MarkdownPrompt {
+    pub fn parse(text: &str) -> Self {
+        // Accept files saved with CRLF line endings, which the prompt editor normalizes anyway.
+        let text = &text.replace("\r\n", "\n");
+        let mut prompt = MarkdownPrompt::default();
+        let front_matter = text.strip_prefix("---\n").and_then(|rest| {
+            rest.split_once("\n---\n")
+                .or_else(|| rest.split_once("\n---"))
+        });
+        let Some((front_matter, body)) = front_matter else {
+            prompt.body = text.to_string();
+            return prompt;
+        };
+
+        for line in front_matter.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .unwrap_or(value);
+            match key.trim() {
+                "title" if !value.is_empty() => prompt.title = Some(value.to_string()),
+                "default" => prompt.default = value == "true",
+                _ => {}
+            }
+        }
+        prompt.body = body.to_string();
+        prompt
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::from("---\n");
+        if let Some(title) = &self.title {
+            writeln!(markdown, "title: {title}").unwrap();
+        }
+        writeln!(markdown, "default: {}", self.default).unwrap();
+        markdown.push_str("---\n");
+        markdown.push_str(&self.body);
+        markdown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variables_and_render() {
+        let text =
+            "Explain {{selection}} from {{ file }} to {{audience}}, {{selection}} {{not a var}}";
+        assert_eq!(variables(text), ["selection", "file", "audience"]);
+
+        let values = HashMap::from_iter([
+            ("selection".to_string(), "foo()".to_string()),
+            ("file".to_string(), "src/main.rs".to_string()),
+        ]);
+        assert_eq!(
+            render(text, &values),
+            "Explain foo() from src/main.rs to {{audience}}, foo() {{not a var}}"
+        );
+    }
+
+    #[test]
+    fn test_markdown_prompt() {
+        let prompt = MarkdownPrompt {
+            title: Some("Review".into()),
+            default: true,
+            body: "Review {{file}}.\n".into(),
+        };
+        let markdown = prompt.to_markdown();
+        assert_eq!(
+            markdown,
+            "---\ntitle: Review\ndefault: true\n---\nReview {{file}}.\n"
+        );
+        assert_eq!(MarkdownPrompt::parse(&markdown), prompt);
+
+        assert_eq!(
+            MarkdownPrompt::parse(
+                "---\r\ntitle: Review\r\ndefault: true\r\n---\r\nReview {{file}}.\r\n"
+            ),
+            prompt
+        );
+
+        assert_eq!(
+            MarkdownPrompt::parse("No front matter\n"),
+            MarkdownPrompt {
+                title: None,
+                default: false,
+                body: "No front matter\n".into(),
+            }
+        );
+    }
+}
@@ -26,6 +26,7 @@ pub mod file_command;
 pub mod now_command;
 pub mod project_command;
 pub mod prompt_command;
+pub mod rules_command;
 pub mod search_command;
 pub mod symbols_command;
 pub mod tabs_command;
@@ -93,6 +93,7 @@ impl SlashCommand for ActiveSlashCommand {
                         false,
                         None,
                     )],
+                    images: Vec::new(),
                     run_commands_in_text: has_diagnostics,
                 })
             })
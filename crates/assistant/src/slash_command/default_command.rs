@@ -72,6 +72,7 @@ impl SlashCommand for DefaultSlashCommand {
                     label: "Default".into(),
                 }],
                 text,
+                images: Vec::new(),
                 run_commands_in_text: true,
             })
         })
@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 
@@ -8,24 +8,42 @@ use assistant_slash_command::{
 };
 use gpui::{AppContext, Model, Task, WeakView};
 use indexed_docs::{
-    DocsDotRsProvider, IndexedDocsRegistry, IndexedDocsStore, LocalRustdocProvider, PackageName,
-    ProviderId,
+    DocsDotRsProvider, IndexedDocsProvider, IndexedDocsRegistry, IndexedDocsStore,
+    LocalRustdocProvider, NpmPackageProvider, PackageName, ProviderId, PythonPackageProvider,
 };
 use language::LspAdapterDelegate;
-use project::{Project, ProjectPath};
+use project::{Fs, Project, ProjectPath};
+use settings::Settings as _;
 use ui::prelude::*;
-use util::{maybe, ResultExt};
+use util::{maybe, paths::home_dir, ResultExt};
 use workspace::Workspace;
 
+use crate::assistant_settings::AssistantSettings;
+
+/// The manifest files at the root of projects whose npm packages can be indexed.
+const NPM_MANIFEST_FILE_NAMES: &[&str] = &["package.json"];
+
+/// The manifest files at the root of projects whose Python packages can be indexed.
+const PYTHON_MANIFEST_FILE_NAMES: &[&str] = &["pyproject.toml", "setup.py", "requirements.txt"];
+
 pub(crate) struct DocsSlashCommand;
 
 impl DocsSlashCommand {
     pub const NAME: &'static str = "docs";
 
     fn path_to_cargo_toml(project: Model<Project>, cx: &mut AppContext) -> Option<Arc<Path>> {
+        Self::path_to_worktree_file(project, "Cargo.toml", cx)
+    }
+
+    /// Returns the absolute path of a file at the root of the first worktree of the project.
+    fn path_to_worktree_file(
+        project: Model<Project>,
+        file_name: &str,
+        cx: &mut AppContext,
+    ) -> Option<Arc<Path>> {
         let worktree = project.read(cx).worktrees().next()?;
         let worktree = worktree.read(cx);
-        let entry = worktree.entry_for_path("Cargo.toml")?;
+        let entry = worktree.entry_for_path(file_name)?;
         let path = ProjectPath {
             worktree_id: worktree.id(),
             path: entry.path.clone(),
@@ -35,11 +53,19 @@ impl DocsSlashCommand {
         ))
     }
 
-    /// Ensures that the indexed doc providers for Rust are registered.
+    fn project(workspace: Option<WeakView<Workspace>>, cx: &AppContext) -> Result<Model<Project>> {
+        let workspace = workspace.ok_or_else(|| anyhow!("no workspace"))?;
+        let workspace = workspace
+            .upgrade()
+            .ok_or_else(|| anyhow!("workspace was dropped"))?;
+        Ok(workspace.read(cx).project().clone())
+    }
+
+    /// Ensures that the indexed doc providers are registered.
     ///
     /// Ideally we would do this sooner, but we need to wait until we're able to
     /// access the workspace so we can read the project.
-    fn ensure_rust_doc_providers_are_registered(
+    fn ensure_docs_providers_are_registered(
         &self,
         workspace: Option<WeakView<Workspace>>,
         cx: &mut AppContext,
@@ -50,11 +76,7 @@ impl DocsSlashCommand {
             .is_none()
         {
             let index_provider_deps = maybe!({
-                let workspace = workspace.clone().ok_or_else(|| anyhow!("no workspace"))?;
-                let workspace = workspace
-                    .upgrade()
-                    .ok_or_else(|| anyhow!("workspace was dropped"))?;
-                let project = workspace.read(cx).project().clone();
+                let project = Self::project(workspace.clone(), cx)?;
                 let fs = project.read(cx).fs().clone();
                 let cargo_workspace_root = Self::path_to_cargo_toml(project, cx)
                     .and_then(|path| path.parent().map(|path| path.to_path_buf()))
@@ -71,16 +93,25 @@ impl DocsSlashCommand {
             }
         }
 
+        self.ensure_project_docs_provider_is_registered(
+            NPM_MANIFEST_FILE_NAMES,
+            NpmPackageProvider::new,
+            workspace.clone(),
+            cx,
+        );
+        self.ensure_project_docs_provider_is_registered(
+            PYTHON_MANIFEST_FILE_NAMES,
+            PythonPackageProvider::new,
+            workspace.clone(),
+            cx,
+        );
+
         if indexed_docs_registry
             .get_provider_store(DocsDotRsProvider::id())
             .is_none()
         {
             let http_client = maybe!({
-                let workspace = workspace.ok_or_else(|| anyhow!("no workspace"))?;
-                let workspace = workspace
-                    .upgrade()
-                    .ok_or_else(|| anyhow!("workspace was dropped"))?;
-                let project = workspace.read(cx).project().clone();
+                let project = Self::project(workspace, cx)?;
                 anyhow::Ok(project.read(cx).client().http_client().clone())
             });
 
@@ -90,6 +121,72 @@ impl DocsSlashCommand {
             }
         }
     }
+
+    /// Returns the directory of the first of the given manifest files found at the root of the
+    /// project's first worktree.
+    fn project_root(
+        project: Model<Project>,
+        manifest_file_names: &[&str],
+        cx: &mut AppContext,
+    ) -> Option<PathBuf> {
+        manifest_file_names.iter().find_map(|file_name| {
+            Self::path_to_worktree_file(project.clone(), file_name, cx)
+                .and_then(|path| path.parent().map(|path| path.to_path_buf()))
+        })
+    }
+
+    /// Registers a provider that indexes the packages installed in the project, when the project
+    /// has one of the given manifest files at its root.
+    ///
+    /// Each project has its own packages, so the provider is replaced when docs are used from
+    /// a different project.
+    fn ensure_project_docs_provider_is_registered<P>(
+        &self,
+        manifest_file_names: &[&str],
+        new_provider: impl FnOnce(Arc<dyn Fs>, PathBuf) -> P,
+        workspace: Option<WeakView<Workspace>>,
+        cx: &mut AppContext,
+    ) where
+        P: IndexedDocsProvider + Send + Sync + 'static,
+    {
+        let Ok(project) = Self::project(workspace, cx) else {
+            return;
+        };
+        let fs = project.read(cx).fs().clone();
+        let Some(project_root) = Self::project_root(project, manifest_file_names, cx) else {
+            return;
+        };
+        let provider = new_provider(fs, project_root);
+
+        let indexed_docs_registry = IndexedDocsRegistry::global(cx);
+        if indexed_docs_registry
+            .get_provider_store(provider.id())
+            .map_or(false, |store| {
+                store.database_path() == provider.database_path()
+            })
+        {
+            return;
+        }
+        indexed_docs_registry.register_provider(Box::new(provider));
+    }
+
+    /// Returns whether the user allowed `/docs python` to index the packages of the project,
+    /// which runs the Python of the project's virtual environment.
+    fn is_python_package_indexing_allowed(
+        workspace: Option<WeakView<Workspace>>,
+        cx: &mut AppContext,
+    ) -> bool {
+        let Ok(project) = Self::project(workspace, cx) else {
+            return false;
+        };
+        let Some(project_root) = Self::project_root(project, PYTHON_MANIFEST_FILE_NAMES, cx) else {
+            return false;
+        };
+        is_in_worktrees(
+            &project_root,
+            &AssistantSettings::get_global(cx).python_package_docs_worktrees,
+        )
+    }
 }
 
 impl SlashCommand for DocsSlashCommand {
@@ -116,7 +213,9 @@ impl SlashCommand for DocsSlashCommand {
         workspace: Option<WeakView<Workspace>>,
         cx: &mut AppContext,
     ) -> Task<Result<Vec<ArgumentCompletion>>> {
-        self.ensure_rust_doc_providers_are_registered(workspace, cx);
+        let is_python_package_indexing_allowed =
+            Self::is_python_package_indexing_allowed(workspace.clone(), cx);
+        self.ensure_docs_providers_are_registered(workspace, cx);
 
         let indexed_docs_registry = IndexedDocsRegistry::global(cx);
         let args = DocsSlashCommandArgs::parse(&query);
@@ -166,6 +265,17 @@ impl SlashCommand for DocsSlashCommand {
                 } => {
                     let store = store?;
 
+                    if index
+                        && provider == PythonPackageProvider::id()
+                        && !is_python_package_indexing_allowed
+                    {
+                        return Ok(vec![ArgumentCompletion {
+                            label: "Add this project to `assistant.python_package_docs_worktrees` to index its packages.".to_string(),
+                            new_text: String::new(),
+                            run_command: false,
+                        }]);
+                    }
+
                     if index {
                         // We don't need to hold onto this task, as the `IndexedDocsStore` will hold it
                         // until it completes.
@@ -259,12 +369,23 @@ impl SlashCommand for DocsSlashCommand {
                         label: format!("docs ({provider}): {key}",).into(),
                     })
                     .collect(),
+                images: Vec::new(),
                 run_commands_in_text: false,
             })
         })
     }
 }
 
+/// Returns whether the path is within one of the given worktrees, which may start with `~`.
+fn is_in_worktrees(path: &Path, worktrees: &[PathBuf]) -> bool {
+    worktrees
+        .iter()
+        .any(|worktree| match worktree.strip_prefix("~") {
+            Ok(worktree) => path.starts_with(home_dir().join(worktree)),
+            Err(_) => path.starts_with(worktree),
+        })
+}
+
 fn is_item_path_delimiter(char: char) -> bool {
     !char.is_alphanumeric() && char != '-' && char != '_'
 }
@@ -292,7 +413,17 @@ impl DocsSlashCommandArgs {
 
         let provider = ProviderId(provider.into());
 
-        if let Some((package, rest)) = argument.split_once(is_item_path_delimiter) {
+        // Scoped npm packages, such as `@types/node`, have a delimiter in their name.
+        let scope_len = if argument.starts_with('@') {
+            argument.find('/').map_or(argument.len(), |ix| ix + 1)
+        } else {
+            0
+        };
+        let package_and_rest = argument[scope_len..]
+            .split_once(is_item_path_delimiter)
+            .map(|(name, rest)| (&argument[..scope_len + name.len()], rest));
+
+        if let Some((package, rest)) = package_and_rest {
             if rest.trim().is_empty() {
                 Self::SearchPackageDocs {
                     provider,
@@ -401,6 +532,23 @@ mod tests {
             }
         );
 
+        assert_eq!(
+            DocsSlashCommandArgs::parse("npm @types/node"),
+            DocsSlashCommandArgs::SearchPackageDocs {
+                provider: ProviderId("npm".into()),
+                package: "@types/node".into(),
+                index: false
+            }
+        );
+        assert_eq!(
+            DocsSlashCommandArgs::parse("npm @types/node/"),
+            DocsSlashCommandArgs::SearchPackageDocs {
+                provider: ProviderId("npm".into()),
+                package: "@types/node".into(),
+                index: true
+            }
+        );
+
         assert_eq!(
             DocsSlashCommandArgs::parse("rustdoc gpui::foo::bar::Baz"),
             DocsSlashCommandArgs::SearchItemDocs {
@@ -417,5 +565,30 @@ mod tests {
                 item_path: "gleam_stdlib/gleam/int".into()
             }
         );
+        assert_eq!(
+            DocsSlashCommandArgs::parse("npm @types/node/fs.d.ts"),
+            DocsSlashCommandArgs::SearchItemDocs {
+                provider: ProviderId("npm".into()),
+                package: "@types/node".into(),
+                item_path: "@types/node/fs.d.ts".into()
+            }
+        );
+    }
+
+    #[test]
+    fn test_is_in_worktrees() {
+        let worktrees = [PathBuf::from("/code/allowed"), PathBuf::from("~/projects")];
+        assert!(is_in_worktrees(Path::new("/code/allowed"), &worktrees));
+        assert!(is_in_worktrees(
+            Path::new("/code/allowed/service"),
+            &worktrees
+        ));
+        assert!(is_in_worktrees(
+            &home_dir().join("projects/app"),
+            &worktrees
+        ));
+        assert!(!is_in_worktrees(Path::new("/code/allowed-not"), &worktrees));
+        assert!(!is_in_worktrees(Path::new("/code"), &worktrees));
+        assert!(!is_in_worktrees(Path::new("/code/allowed"), &[]));
     }
 }
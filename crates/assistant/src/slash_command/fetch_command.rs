@@ -158,6 +158,7 @@ impl SlashCommand for FetchSlashCommand {
                     icon: IconName::AtSign,
                     label: format!("fetch {}", url).into(),
                 }],
+                images: Vec::new(),
                 run_commands_in_text: false,
             })
         })
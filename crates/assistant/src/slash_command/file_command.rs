@@ -1,6 +1,9 @@
 use super::{diagnostics_command::write_single_file_diagnostics, SlashCommand, SlashCommandOutput};
+use crate::LanguageModelImage;
 use anyhow::{anyhow, Result};
-use assistant_slash_command::{ArgumentCompletion, SlashCommandOutputSection};
+use assistant_slash_command::{
+    ArgumentCompletion, SlashCommandOutputImage, SlashCommandOutputSection,
+};
 use fuzzy::PathMatch;
 use gpui::{AppContext, Model, Task, View, WeakView};
 use language::{BufferSnapshot, LineEnding, LspAdapterDelegate};
@@ -150,7 +153,7 @@ impl SlashCommand for FileSlashCommand {
         let task = collect_files(workspace.read(cx).project().clone(), argument, cx);
 
         cx.foreground_executor().spawn(async move {
-            let (text, ranges) = task.await?;
+            let (text, ranges, images) = task.await?;
             Ok(SlashCommandOutput {
                 text,
                 sections: ranges
@@ -164,6 +167,7 @@ impl SlashCommand for FileSlashCommand {
                         )
                     })
                     .collect(),
+                images,
                 run_commands_in_text: true,
             })
         })
@@ -176,16 +180,23 @@ enum EntryType {
     Directory,
 }
 
+type CollectedFiles = (
+    String,
+    Vec<(Range<usize>, PathBuf, EntryType)>,
+    Vec<SlashCommandOutputImage>,
+);
+
 fn collect_files(
     project: Model<Project>,
     glob_input: &str,
     cx: &mut AppContext,
-) -> Task<Result<(String, Vec<(Range<usize>, PathBuf, EntryType)>)>> {
+) -> Task<Result<CollectedFiles>> {
     let Ok(matcher) = PathMatcher::new(&[glob_input.to_owned()]) else {
         return Task::ready(Err(anyhow!("invalid path")));
     };
 
     let project_handle = project.downgrade();
+    let fs = project.read(cx).fs().clone();
     let snapshots = project
         .read(cx)
         .worktrees()
@@ -194,6 +205,7 @@ fn collect_files(
     cx.spawn(|mut cx| async move {
         let mut text = String::new();
         let mut ranges = Vec::new();
+        let mut images = Vec::new();
         for snapshot in snapshots {
             let worktree_id = snapshot.id();
             let mut directory_stack: Vec<(Arc<Path>, String, usize)> = Vec::new();
@@ -263,6 +275,18 @@ fn collect_files(
                         directory_stack.push((entry.path.clone(), entry_name, entry_start));
                     }
                     text.push('\n');
+                } else if entry.is_file() && LanguageModelImage::is_supported_path(&entry.path) {
+                    // Images are attached to the context instead of being inserted as text.
+                    let abs_path = snapshot.abs_path().join(&entry.path);
+                    if let Some(bytes) = fs.load_bytes(&abs_path).await.log_err() {
+                        text.push_str(&filename);
+                        text.push('\n');
+                        images.push(SlashCommandOutputImage {
+                            offset: text.len(),
+                            bytes,
+                        });
+                        text.push('\n');
+                    }
                 } else if entry.is_file() {
                     let Some(open_buffer_task) = project_handle
                         .update(&mut cx, |project, cx| {
@@ -301,7 +325,7 @@ fn collect_files(
                 ranges.push((start..text.len(), root_path, EntryType::Directory));
             }
         }
-        Ok((text, ranges))
+        Ok((text, ranges, images))
     })
 }
 
@@ -58,6 +58,7 @@ impl SlashCommand for NowSlashCommand {
                 icon: IconName::CountdownTimer,
                 label: now.to_rfc2822().into(),
             }],
+            images: Vec::new(),
             run_commands_in_text: false,
         }))
     }
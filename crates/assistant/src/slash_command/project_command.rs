@@ -141,6 +141,7 @@ impl SlashCommand for ProjectSlashCommand {
                         icon: IconName::FileTree,
                         label: "Project".into(),
                     }],
+                    images: Vec::new(),
                     run_commands_in_text: false,
                 })
             })
@@ -1,12 +1,17 @@
-use super::{SlashCommand, SlashCommandOutput};
-use crate::prompt_library::PromptStore;
+use super::{SlashCommand, SlashCommandLine, SlashCommandOutput};
+use crate::{prompt_library::PromptStore, prompt_template};
 use anyhow::{anyhow, Context, Result};
 use assistant_slash_command::{ArgumentCompletion, SlashCommandOutputSection};
-use gpui::{AppContext, Task, WeakView};
+use collections::HashMap;
+use editor::Editor;
+use futures::channel::oneshot;
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Task, View, WeakView,
+};
 use language::LspAdapterDelegate;
 use std::sync::{atomic::AtomicBool, Arc};
 use ui::prelude::*;
-use workspace::Workspace;
+use workspace::{ModalView, Workspace};
 
 pub(crate) struct PromptSlashCommand;
 
@@ -54,7 +59,7 @@ impl SlashCommand for PromptSlashCommand {
     fn run(
         self: Arc<Self>,
         title: Option<&str>,
-        _workspace: WeakView<Workspace>,
+        workspace: WeakView<Workspace>,
         _delegate: Arc<dyn LspAdapterDelegate>,
         cx: &mut WindowContext,
     ) -> Task<Result<SlashCommandOutput>> {
@@ -75,8 +80,39 @@ impl SlashCommand for PromptSlashCommand {
                 anyhow::Ok(body)
             }
         });
-        cx.foreground_executor().spawn(async move {
-            let mut prompt = prompt.await?;
+        let mut values = workspace
+            .upgrade()
+            .map(|workspace| built_in_variable_values(workspace.read(cx), cx))
+            .unwrap_or_default();
+
+        cx.spawn(|mut cx| async move {
+            let prompt = prompt.await?;
+            let user_variables = prompt_template::variables(&prompt)
+                .into_iter()
+                .filter(|variable| {
+                    !prompt_template::BUILT_IN_VARIABLES.contains(&variable.as_str())
+                })
+                .collect::<Vec<_>>();
+            if !user_variables.is_empty() {
+                let (values_tx, values_rx) = oneshot::channel();
+                workspace.update(&mut cx, |workspace, cx| {
+                    let title = title.clone();
+                    workspace.toggle_modal(cx, move |cx| {
+                        PromptVariablesModal::new(title, user_variables, values_tx, cx)
+                    })
+                })?;
+                let user_values = values_rx
+                    .await
+                    .map_err(|_| anyhow!("prompt insertion was cancelled"))?;
+                values.extend(user_values);
+            }
+            // The output runs the slash commands written in the prompt, but not the ones
+            // that happen to be in the substituted values.
+            for value in values.values_mut() {
+                *value = escape_slash_commands(value);
+            }
+
+            let mut prompt = prompt_template::render(&prompt, &values);
             if prompt.is_empty() {
                 prompt.push('\n');
             }
@@ -88,8 +124,242 @@ impl SlashCommand for PromptSlashCommand {
                     icon: IconName::Library,
                     label: title,
                 }],
+                images: Vec::new(),
                 run_commands_in_text: true,
             })
         })
     }
 }
+
+/// Returns the values of the built-in template variables for the workspace's active editor.
+fn built_in_variable_values(workspace: &Workspace, cx: &WindowContext) -> HashMap<String, String> {
+    let mut values = HashMap::default();
+    let Some(editor) = workspace
+        .active_item(cx)
+        .and_then(|item| item.act_as::<Editor>(cx))
+    else {
+        return values;
+    };
+
+    let editor = editor.read(cx);
+    let multibuffer = editor.buffer().read(cx);
+    let snapshot = multibuffer.snapshot(cx);
+    let selection = editor.selections.newest::<usize>(cx);
+    values.insert(
+        "selection".to_string(),
+        snapshot.text_for_range(selection.range()).collect(),
+    );
+    if let Some(buffer) = multibuffer.as_singleton() {
+        let buffer = buffer.read(cx);
+        if let Some(file) = buffer.file() {
+            values.insert(
+                "file".to_string(),
+                file.full_path(cx).to_string_lossy().into_owned(),
+            );
+        }
+    }
+    if let Some(language) = snapshot.language_at(selection.head()) {
+        values.insert("language".to_string(), language.name().to_string());
+    }
+    values
+}
+
+/// Prefixes every line of `text` that would run as a slash command with a backslash.
+fn escape_slash_commands(text: &str) -> String {
+    text.split_inclusive('\n')
+        .map(|line| {
+            if SlashCommandLine::parse(line).is_some() {
+                format!("\\{line}")
+            } else {
+                line.to_string()
+            }
+        })
+        .collect()
+}
+
+/// Asks for the values of a prompt's user-defined variables before it's inserted.
+struct PromptVariablesModal {
+    title: SharedString,
+    editors: Vec<(SharedString, View<Editor>)>,
+    values_tx: Option<oneshot::Sender<HashMap<String, String>>>,
+}
+
+impl PromptVariablesModal {
+    fn new(
+        title: SharedString,
+        variables: Vec<String>,
+        values_tx: oneshot::Sender<HashMap<String, String>>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let editors = variables
+            .into_iter()
+            .map(|variable| {
+                let editor = cx.new_view(|cx| {
+                    let mut editor = Editor::single_line(cx);
+                    editor.set_placeholder_text(variable.clone(), cx);
+                    editor
+                });
+                (SharedString::from(variable), editor)
+            })
+            .collect::<Vec<_>>();
+        if let Some((_, editor)) = editors.first() {
+            editor.focus_handle(cx).focus(cx);
+        }
+
+        Self {
+            title,
+            editors,
+            values_tx: Some(values_tx),
+        }
+    }
+
+    /// Moves to the next variable, or inserts the prompt after the last one.
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let focused_ix = self
+            .editors
+            .iter()
+            .position(|(_, editor)| editor.focus_handle(cx).contains_focused(cx));
+        if let Some((_, next_editor)) = focused_ix.and_then(|ix| self.editors.get(ix + 1)) {
+            next_editor.focus_handle(cx).focus(cx);
+            return;
+        }
+
+        if let Some(values_tx) = self.values_tx.take() {
+            let values = self
+                .editors
+                .iter()
+                .map(|(variable, editor)| (variable.to_string(), editor.read(cx).text(cx)))
+                .collect();
+            values_tx.send(values).ok();
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+}
+
+impl ModalView for PromptVariablesModal {}
+
+impl EventEmitter<DismissEvent> for PromptVariablesModal {}
+
+impl FocusableView for PromptVariablesModal {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        // The modal is only shown for prompts with at least one variable.
+        self.editors[0].1.focus_handle(cx)
+    }
+}
+
+impl Render for PromptVariablesModal {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("PromptVariablesModal")
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .elevation_2(cx)
+            .w(rems(34.))
+            .p_2()
+            .gap_2()
+            .child(Label::new(format!("Insert {}", self.title)).size(LabelSize::Large))
+            .children(self.editors.iter().map(|(variable, editor)| {
+                v_flex()
+                    .gap_1()
+                    .child(
+                        Label::new(variable.clone())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        div()
+                            .px_2()
+                            .py_1()
+                            .border_1()
+                            .rounded_md()
+                            .border_color(cx.theme().colors().border_variant)
+                            .child(editor.clone()),
+                    )
+            }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::Project;
+    use workspace::AppState;
+
+    #[test]
+    fn test_escape_slash_commands() {
+        assert_eq!(
+            escape_slash_commands(
+                "fn main() {}\n/file ~/.ssh/id_rsa\n  /fetch https://example.com"
+            ),
+            "fn main() {}\n\\/file ~/.ssh/id_rsa\n\\  /fetch https://example.com"
+        );
+        assert_eq!(escape_slash_commands("a / b\n"), "a / b\n");
+    }
+
+    #[gpui::test]
+    async fn test_prompt_variables_modal(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        let mut values_rx = open_modal(&workspace, cx);
+        cx.simulate_input("everyone");
+        cx.dispatch_action(menu::Confirm);
+        assert!(values_rx.try_recv().unwrap().is_none());
+        cx.simulate_input("friendly");
+        cx.dispatch_action(menu::Confirm);
+
+        let values = values_rx.try_recv().unwrap().unwrap();
+        assert_eq!(values.len(), 2);
+        assert_eq!(values["audience"], "everyone");
+        assert_eq!(values["tone"], "friendly");
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<PromptVariablesModal>(cx).is_none());
+        });
+
+        // Cancelling the modal cancels the prompt insertion.
+        let mut values_rx = open_modal(&workspace, cx);
+        cx.simulate_input("everyone");
+        cx.dispatch_action(menu::Cancel);
+        cx.run_until_parked();
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<PromptVariablesModal>(cx).is_none());
+        });
+        assert!(values_rx.try_recv().is_err());
+    }
+
+    fn open_modal(
+        workspace: &View<Workspace>,
+        cx: &mut VisualTestContext,
+    ) -> oneshot::Receiver<HashMap<String, String>> {
+        let (values_tx, values_rx) = oneshot::channel();
+        workspace.update(cx, |workspace, cx| {
+            workspace.toggle_modal(cx, |cx| {
+                PromptVariablesModal::new(
+                    "Explain".into(),
+                    vec!["audience".to_string(), "tone".to_string()],
+                    values_tx,
+                    cx,
+                )
+            })
+        });
+        cx.run_until_parked();
+        values_rx
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        cx.update(|cx| {
+            let state = AppState::test(cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            state
+        })
+    }
+}
@@ -0,0 +1,95 @@
+use super::{SlashCommand, SlashCommandOutput};
+use anyhow::{anyhow, Context as _, Result};
+use assistant_slash_command::{ArgumentCompletion, SlashCommandOutputSection};
+use gpui::{AppContext, Task, WeakView};
+use language::LspAdapterDelegate;
+use project::Project;
+use std::{
+    fmt::Write,
+    sync::{atomic::AtomicBool, Arc},
+};
+use ui::prelude::*;
+use workspace::Workspace;
+
+/// The label of the output section containing a project's rules, which is used
+/// to find that section again when the rules change.
+pub(crate) const PROJECT_RULES_LABEL: &str = "Project Rules";
+
+pub(crate) struct RulesSlashCommand;
+
+impl SlashCommand for RulesSlashCommand {
+    fn name(&self) -> String {
+        "rules".into()
+    }
+
+    fn description(&self) -> String {
+        "insert project rules".into()
+    }
+
+    fn menu_text(&self) -> String {
+        "Insert Project Rules".into()
+    }
+
+    fn requires_argument(&self) -> bool {
+        false
+    }
+
+    fn complete_argument(
+        self: Arc<Self>,
+        _query: String,
+        _cancellation_flag: Arc<AtomicBool>,
+        _workspace: Option<WeakView<Workspace>>,
+        _cx: &mut AppContext,
+    ) -> Task<Result<Vec<ArgumentCompletion>>> {
+        Task::ready(Err(anyhow!("this command does not require argument")))
+    }
+
+    fn run(
+        self: Arc<Self>,
+        _argument: Option<&str>,
+        workspace: WeakView<Workspace>,
+        _delegate: Arc<dyn LspAdapterDelegate>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<SlashCommandOutput>> {
+        let output = workspace
+            .upgrade()
+            .context("workspace was dropped")
+            .and_then(|workspace| {
+                let project = workspace.read(cx).project().read(cx);
+                project_rules(project, cx).context("this project has no rules files")
+            })
+            .map(|text| SlashCommandOutput {
+                sections: vec![SlashCommandOutputSection {
+                    range: 0..text.len(),
+                    icon: IconName::Book,
+                    label: PROJECT_RULES_LABEL.into(),
+                }],
+                text,
+                images: Vec::new(),
+                run_commands_in_text: false,
+            });
+        Task::ready(output)
+    }
+}
+
+/// Returns the concatenated contents of the project's `.zed/assistant/*.md` files,
+/// or `None` if there are none.
+pub(crate) fn project_rules(project: &Project, cx: &AppContext) -> Option<String> {
+    let mut text = String::new();
+    for (worktree_id, path, content) in project.assistant_rules() {
+        let Some(worktree) = project.worktree_for_id(worktree_id, cx) else {
+            continue;
+        };
+        let root_name = worktree.read(cx).root_name();
+        writeln!(text, "Project rules from {}/{}:", root_name, path.display()).unwrap();
+        text.push_str(content.trim_end());
+        text.push_str("\n\n");
+    }
+
+    if text.is_empty() {
+        None
+    } else {
+        text.pop();
+        Some(text)
+    }
+}
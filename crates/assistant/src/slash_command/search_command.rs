@@ -7,7 +7,7 @@ use anyhow::Result;
 use assistant_slash_command::{ArgumentCompletion, SlashCommandOutputSection};
 use gpui::{AppContext, Task, WeakView};
 use language::{CodeLabel, LineEnding, LspAdapterDelegate};
-use semantic_index::SemanticIndex;
+use semantic_index::{SearchFilter, SemanticIndex};
 use std::{
     fmt::Write,
     path::PathBuf,
@@ -25,7 +25,7 @@ impl SlashCommand for SearchSlashCommand {
     }
 
     fn label(&self, cx: &AppContext) -> CodeLabel {
-        create_label_for_command("search", &["--n"], cx)
+        create_label_for_command("search", &["--n", "--path=<glob>", "--language=<name>"], cx)
     }
 
     fn description(&self) -> String {
@@ -79,6 +79,10 @@ impl SlashCommand for SearchSlashCommand {
         }
         query.pop();
 
+        let (query, filter) = match SearchFilter::extract(&query) {
+            Ok(query_and_filter) => query_and_filter,
+            Err(error) => return Task::ready(Err(error)),
+        };
         if query.is_empty() {
             return Task::ready(Err(anyhow::anyhow!("missing search query")));
         }
@@ -91,7 +95,7 @@ impl SlashCommand for SearchSlashCommand {
         cx.spawn(|cx| async move {
             let results = project_index
                 .read_with(&cx, |project_index, cx| {
-                    project_index.search(query.clone(), limit.unwrap_or(5), cx)
+                    project_index.hybrid_search(query.clone(), limit.unwrap_or(5), filter, cx)
                 })?
                 .await?;
 
@@ -163,6 +167,7 @@ impl SlashCommand for SearchSlashCommand {
                     SlashCommandOutput {
                         text,
                         sections,
+                        images: Vec::new(),
                         run_commands_in_text: false,
                     }
                 })
@@ -79,6 +79,7 @@ impl SlashCommand for OutlineSlashCommand {
                         label: path.to_string_lossy().to_string().into(),
                     }],
                     text: outline_text,
+                    images: Vec::new(),
                     run_commands_in_text: false,
                 })
             })
@@ -109,6 +109,7 @@ impl SlashCommand for TabsSlashCommand {
                 Ok(SlashCommandOutput {
                     text,
                     sections,
+                    images: Vec::new(),
                     run_commands_in_text: has_diagnostics,
                 })
             }),
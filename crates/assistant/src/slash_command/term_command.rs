@@ -95,6 +95,7 @@ impl SlashCommand for TermSlashCommand {
                 icon: IconName::Terminal,
                 label: "Terminal".into(),
             }],
+            images: Vec::new(),
             run_commands_in_text: false,
         }))
     }
@@ -262,6 +262,9 @@ impl TerminalInlineAssistant {
         messages.push(LanguageModelRequestMessage {
             role: Role::User,
             content: prompt,
+            images: Vec::new(),
+            tool_calls: Vec::new(),
+            tool_results: Vec::new(),
         });
 
         Ok(LanguageModelRequest {
@@ -269,6 +272,7 @@ impl TerminalInlineAssistant {
             messages,
             stop: Vec::new(),
             temperature: 1.0,
+            tools: Vec::new(),
         })
     }
 
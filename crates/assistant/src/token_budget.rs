@@ -0,0 +1,254 @@
+use collections::HashSet;
+use std::{fmt::Write, ops::Range, path::PathBuf};
+
+/// Slash command sections longer than this many bytes are collapsed by
+/// [`TrimStrategy::CollapseLargeSections`].
+pub const LARGE_SECTION_LEN: usize = 4096;
+
+/// The number of lines kept at the start of a collapsed section.
+const SUMMARY_LINE_COUNT: usize = 8;
+
+/// Ways of bringing a context that exceeds the model's context window back under budget.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrimStrategy {
+    /// Removes the oldest non-system messages until the context fits.
+    DropOldestMessages,
+    /// Replaces large slash command sections with their first few lines.
+    CollapseLargeSections,
+    /// Reduces file sections to the symbols mentioned in the conversation.
+    TruncateFilesToSymbols,
+}
+
+impl TrimStrategy {
+    pub const ALL: [Self; 3] = [
+        Self::DropOldestMessages,
+        Self::CollapseLargeSections,
+        Self::TruncateFilesToSymbols,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::DropOldestMessages => "Drop Oldest Messages",
+            Self::CollapseLargeSections => "Collapse Large Sections",
+            Self::TruncateFilesToSymbols => "Truncate Files to Symbols",
+        }
+    }
+}
+
+/// Returns a shorter version of a large section's text, keeping its code fences balanced,
+/// or `None` if the section is small enough already.
+pub fn summarize_section(text: &str) -> Option<String> {
+    if text.len() <= LARGE_SECTION_LEN {
+        return None;
+    }
+
+    let mut lines = text.lines().collect::<Vec<_>>();
+    let mut summary = String::new();
+    let mut closing_fence = None;
+    if lines.len() > 1 && lines[0].starts_with("```") {
+        summary.push_str(lines.remove(0));
+        summary.push('\n');
+        if let Some(ix) = lines.iter().rposition(|line| line.starts_with("```")) {
+            closing_fence = Some(lines[ix]);
+            lines.truncate(ix);
+        }
+    }
+
+    let kept_lines = lines.len().min(SUMMARY_LINE_COUNT);
+    for line in &lines[..kept_lines] {
+        summary.push_str(line);
+        summary.push('\n');
+    }
+    writeln!(
+        summary,
+        "… {} more lines collapsed to save tokens",
+        lines.len() - kept_lines
+    )
+    .unwrap();
+    if let Some(closing_fence) = closing_fence {
+        summary.push_str(closing_fence);
+        summary.push('\n');
+    }
+    if !text.ends_with('\n') {
+        summary.pop();
+    }
+
+    (summary.len() < text.len()).then_some(summary)
+}
+
+/// A symbol from a file's outline.
+pub struct Symbol {
+    pub depth: usize,
+    /// The rows spanned by the symbol, with an exclusive end.
+    pub rows: Range<u32>,
+    pub names: Vec<String>,
+}
+
+/// Keeps the full text of the symbols named in `relevant_names` and only the first line of
+/// the others, replacing each run of omitted lines with an ellipsis.
+pub fn truncate_to_symbols(
+    text: &str,
+    symbols: &[Symbol],
+    relevant_names: &HashSet<String>,
+) -> String {
+    let lines = text.split_inclusive('\n').collect::<Vec<_>>();
+    let mut keep = vec![false; lines.len()];
+    let mut ix = 0;
+    while let Some(symbol) = symbols.get(ix) {
+        ix += 1;
+        let start = symbol.rows.start as usize;
+        if symbol
+            .names
+            .iter()
+            .any(|name| relevant_names.contains(name))
+        {
+            let end = (symbol.rows.end as usize).min(lines.len());
+            if let Some(rows) = keep.get_mut(start..end) {
+                rows.fill(true);
+            }
+            while symbols
+                .get(ix)
+                .map_or(false, |child| child.depth > symbol.depth)
+            {
+                ix += 1;
+            }
+        } else if let Some(keep) = keep.get_mut(start) {
+            *keep = true;
+        }
+    }
+
+    let mut truncated = String::new();
+    let mut omitting = false;
+    for (line, keep) in lines.into_iter().zip(keep) {
+        if keep {
+            truncated.push_str(line);
+            omitting = false;
+        } else if !omitting {
+            if !truncated.is_empty() && !truncated.ends_with('\n') {
+                truncated.push('\n');
+            }
+            truncated.push_str("…\n");
+            omitting = true;
+        }
+    }
+    truncated
+}
+
+/// Splits a code block produced by a slash command into its opening fence line, its
+/// contents, and everything from the closing fence onwards.
+pub fn split_code_block(text: &str) -> Option<(&str, &str, &str)> {
+    let (fence, rest) = text.split_once('\n')?;
+    if !fence.starts_with("```") {
+        return None;
+    }
+    let closing_fence_ix = if rest.starts_with("```") {
+        0
+    } else {
+        rest.rfind("\n```")? + 1
+    };
+    Some((fence, &rest[..closing_fence_ix], &rest[closing_fence_ix..]))
+}
+
+/// Returns the path in a code block fence such as "```rs src/main.rs:1-10". Info strings
+/// without a path separator or an extension, such as "```rust", aren't paths.
+pub fn path_from_fence(fence: &str) -> Option<PathBuf> {
+    let path = fence.trim_start_matches('`').split_whitespace().last()?;
+    let path = match path.rsplit_once(':') {
+        Some((path, row_range))
+            if row_range
+                .chars()
+                .all(|char| char.is_ascii_digit() || char == '-') =>
+        {
+            path
+        }
+        _ => path,
+    };
+    let path = PathBuf::from(path);
+    if path.components().count() > 1 || path.extension().is_some() {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+/// Returns the identifiers mentioned in `text`.
+pub fn words(text: &str) -> HashSet<String> {
+    text.split(|char: char| !char.is_alphanumeric() && char != '_')
+        .filter(|word| !word.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_summarize_section() {
+        assert_eq!(summarize_section("short"), None);
+
+        let body = (0..1000)
+            .map(|ix| format!("line {ix}\n"))
+            .collect::<String>();
+        let summary = summarize_section(&format!("```rs src/lib.rs\n{body}```")).unwrap();
+        assert_eq!(
+            summary,
+            "```rs src/lib.rs\nline 0\nline 1\nline 2\nline 3\nline 4\nline 5\nline 6\nline 7\n… 992 more lines collapsed to save tokens\n```"
+        );
+    }
+
+    #[test]
+    fn test_truncate_to_symbols() {
+        let text = "use std::fmt;\n\nimpl Foo {\n    fn a() {\n        1\n    }\n\n    fn b() {\n        2\n    }\n}\n";
+        let symbols = [
+            Symbol {
+                depth: 0,
+                rows: 2..11,
+                names: vec!["Foo".into()],
+            },
+            Symbol {
+                depth: 1,
+                rows: 3..6,
+                names: vec!["a".into()],
+            },
+            Symbol {
+                depth: 1,
+                rows: 7..10,
+                names: vec!["b".into()],
+            },
+        ];
+
+        assert_eq!(
+            truncate_to_symbols(text, &symbols, &words("what does b return?")),
+            "…\nimpl Foo {\n    fn a() {\n…\n    fn b() {\n        2\n    }\n…\n"
+        );
+        assert_eq!(
+            truncate_to_symbols(text, &symbols, &words("explain Foo")),
+            "…\nimpl Foo {\n    fn a() {\n        1\n    }\n\n    fn b() {\n        2\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_code_blocks() {
+        assert_eq!(
+            split_code_block("```rs src/main.rs\nfn main() {}\n```\nerror: oops"),
+            Some(("```rs src/main.rs", "fn main() {}\n", "```\nerror: oops"))
+        );
+        assert_eq!(split_code_block("no fence\n"), None);
+        assert_eq!(
+            path_from_fence("```rs src/main.rs:1-10").as_deref(),
+            Some(Path::new("src/main.rs"))
+        );
+        assert_eq!(
+            path_from_fence("```Cargo.toml").as_deref(),
+            Some(Path::new("Cargo.toml"))
+        );
+        assert_eq!(
+            path_from_fence("```rs src/bin/Makefile").as_deref(),
+            Some(Path::new("src/bin/Makefile"))
+        );
+        assert_eq!(path_from_fence("```rust"), None);
+        assert_eq!(path_from_fence("```"), None);
+    }
+}
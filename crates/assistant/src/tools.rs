@@ -0,0 +1,315 @@
+mod diagnostics_tool;
+mod list_directory_tool;
+mod project_search_tool;
+mod propose_edit_tool;
+mod read_file_tool;
+mod symbol_lookup_tool;
+
+use anyhow::{anyhow, Result};
+use assistant_tooling::{LanguageModelTool, ProjectContext, ToolRegistry, ToolView};
+use gpui::{AppContext, Model, Task, View, WindowContext};
+use project::{Project, ProjectPath};
+use schemars::JsonSchema;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{marker::PhantomData, path::Path, sync::Arc};
+use ui::{prelude::*, Tooltip};
+use util::ResultExt;
+
+use diagnostics_tool::DiagnosticsTool;
+use list_directory_tool::ListDirectoryTool;
+use project_search_tool::ProjectSearchTool;
+pub(crate) use propose_edit_tool::ProposeEditTool;
+use read_file_tool::ReadFileTool;
+use symbol_lookup_tool::SymbolLookupTool;
+
+/// Returns a registry of the tools the assistant can call to explore and edit the given project.
+pub(crate) fn project_tool_registry(project: Model<Project>) -> ToolRegistry {
+    let mut registry = ToolRegistry::new();
+    registry
+        .register(ReadOnlyTool::<ReadFileTool>::new(project.clone()))
+        .log_err();
+    registry
+        .register(ReadOnlyTool::<ListDirectoryTool>::new(project.clone()))
+        .log_err();
+    registry
+        .register(ReadOnlyTool::<ProjectSearchTool>::new(project.clone()))
+        .log_err();
+    registry
+        .register(ReadOnlyTool::<SymbolLookupTool>::new(project.clone()))
+        .log_err();
+    registry
+        .register(ReadOnlyTool::<DiagnosticsTool>::new(project.clone()))
+        .log_err();
+    registry.register(ProposeEditTool::new(project)).log_err();
+    registry
+}
+
+/// A tool that reads from the project without modifying it, and can thus run
+/// without asking the user first.
+trait ProjectReadTool: 'static {
+    type Input: DeserializeOwned + JsonSchema + 'static;
+
+    fn name() -> &'static str;
+    fn description() -> &'static str;
+    fn icon() -> IconName;
+    /// Describes the call to the user, e.g. "Read zed/README.md".
+    fn label(input: &Self::Input) -> String;
+    /// Runs the tool, returning the output passed to the model.
+    fn run(
+        input: &Self::Input,
+        project: Model<Project>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>>;
+}
+
+struct ReadOnlyTool<T> {
+    project: Model<Project>,
+    _tool: PhantomData<T>,
+}
+
+impl<T: ProjectReadTool> ReadOnlyTool<T> {
+    fn new(project: Model<Project>) -> Self {
+        Self {
+            project,
+            _tool: PhantomData,
+        }
+    }
+}
+
+impl<T: ProjectReadTool> LanguageModelTool for ReadOnlyTool<T> {
+    type View = ReadOnlyToolView<T>;
+
+    fn name(&self) -> String {
+        T::name().into()
+    }
+
+    fn description(&self) -> String {
+        T::description().into()
+    }
+
+    fn view(&self, cx: &mut WindowContext) -> View<Self::View> {
+        let project = self.project.clone();
+        cx.new_view(|_| ReadOnlyToolView {
+            project,
+            input: None,
+            state: ToolCallState::Pending,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+enum ToolCallState {
+    #[default]
+    Pending,
+    Running,
+    Done(String),
+    Failed(String),
+}
+
+struct ReadOnlyToolView<T: ProjectReadTool> {
+    project: Model<Project>,
+    input: Option<T::Input>,
+    state: ToolCallState,
+}
+
+impl<T: ProjectReadTool> Render for ReadOnlyToolView<T> {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let label = self
+            .input
+            .as_ref()
+            .map_or_else(|| T::name().to_string(), T::label);
+        render_tool_call_header(T::icon(), label, &self.state)
+    }
+}
+
+impl<T: ProjectReadTool> ToolView for ReadOnlyToolView<T> {
+    type Input = T::Input;
+    type SerializedState = ToolCallState;
+
+    fn generate(&self, _project: &mut ProjectContext, _cx: &mut ViewContext<Self>) -> String {
+        match &self.state {
+            ToolCallState::Done(output) | ToolCallState::Failed(output) => output.clone(),
+            ToolCallState::Pending | ToolCallState::Running => String::new(),
+        }
+    }
+
+    fn set_input(&mut self, input: Self::Input, cx: &mut ViewContext<Self>) {
+        self.input = Some(input);
+        cx.notify();
+    }
+
+    fn execute(&mut self, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        let Some(input) = self.input.as_ref() else {
+            return Task::ready(Err(anyhow!("invalid arguments for {}", T::name())));
+        };
+        let run = T::run(input, self.project.clone(), cx);
+        self.state = ToolCallState::Running;
+        cx.notify();
+
+        cx.spawn(|this, mut cx| async move {
+            let result = run.await;
+            this.update(&mut cx, |this, cx| {
+                this.state = match &result {
+                    Ok(output) => ToolCallState::Done(output.clone()),
+                    Err(error) => ToolCallState::Failed(error.to_string()),
+                };
+                cx.notify();
+            })?;
+            result.map(|_| ())
+        })
+    }
+
+    fn serialize(&self, _cx: &mut ViewContext<Self>) -> Self::SerializedState {
+        self.state.clone()
+    }
+
+    fn deserialize(
+        &mut self,
+        state: Self::SerializedState,
+        _cx: &mut ViewContext<Self>,
+    ) -> Result<()> {
+        self.state = state;
+        Ok(())
+    }
+}
+
+fn render_tool_call_header(icon: IconName, label: String, state: &ToolCallState) -> Div {
+    h_flex()
+        .gap_2()
+        .child(Icon::new(icon).size(IconSize::Small).color(Color::Muted))
+        .child(Label::new(label).size(LabelSize::Small))
+        .child(match state {
+            ToolCallState::Pending => div(),
+            ToolCallState::Running => div().child(
+                Label::new("Running…")
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            ),
+            ToolCallState::Done(_) => div().child(
+                Icon::new(IconName::Check)
+                    .size(IconSize::Small)
+                    .color(Color::Success),
+            ),
+            ToolCallState::Failed(error) => {
+                let error = SharedString::from(error.clone());
+                div().child(
+                    div()
+                        .id("tool-call-error")
+                        .tooltip(move |cx| Tooltip::text(error.clone(), cx))
+                        .child(
+                            Icon::new(IconName::XCircle)
+                                .size(IconSize::Small)
+                                .color(Color::Error),
+                        ),
+                )
+            }
+        })
+}
+
+/// Resolves a path given by the model, which starts with the name of one of the
+/// project's worktrees. Paths relative to the root of a single-worktree project
+/// are accepted as well.
+fn resolve_project_path(project: &Project, path: &str, cx: &AppContext) -> Result<ProjectPath> {
+    let path = Path::new(path.trim_start_matches("./"));
+    for worktree in project.visible_worktrees(cx) {
+        let worktree = worktree.read(cx);
+        if let Ok(relative_path) = path.strip_prefix(worktree.root_name()) {
+            return Ok(ProjectPath {
+                worktree_id: worktree.id(),
+                path: Arc::from(relative_path),
+            });
+        }
+    }
+
+    let mut worktrees = project.visible_worktrees(cx);
+    if let (Some(worktree), None) = (worktrees.next(), worktrees.next()) {
+        let worktree = worktree.read(cx);
+        if path.is_relative() && worktree.entry_for_path(path).is_some() {
+            return Ok(ProjectPath {
+                worktree_id: worktree.id(),
+                path: Arc::from(path),
+            });
+        }
+    }
+
+    Err(anyhow!("{} does not exist in the project", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::{TestAppContext, VisualTestContext};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::PathBuf;
+
+    #[gpui::test]
+    async fn test_resolve_project_path(cx: &mut TestAppContext) {
+        let settings_store = cx.update(SettingsStore::test);
+        cx.set_global(settings_store);
+        cx.update(Project::init_settings);
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "zed": {
+                    "src": { "main.rs": "fn main() {}" },
+                    "README.md": "# Zed",
+                }
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/root/zed".as_ref()], cx).await;
+
+        cx.read(|cx| {
+            let project = project.read(cx);
+            let resolve = |path: &str| {
+                resolve_project_path(project, path, cx)
+                    .ok()
+                    .map(|project_path| PathBuf::from(project_path.path.as_ref()))
+            };
+
+            assert_eq!(
+                resolve("zed/src/main.rs"),
+                Some(PathBuf::from("src/main.rs"))
+            );
+            assert_eq!(resolve("./zed/README.md"), Some(PathBuf::from("README.md")));
+            assert_eq!(resolve("zed"), Some(PathBuf::from("")));
+            assert_eq!(resolve("src/main.rs"), Some(PathBuf::from("src/main.rs")));
+            assert_eq!(resolve("src/lib.rs"), None);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_read_file_tool_refuses_private_files(cx: &mut TestAppContext) {
+        let settings_store = cx.update(SettingsStore::test);
+        cx.set_global(settings_store);
+        cx.update(Project::init_settings);
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "zed": {
+                    ".env": "API_KEY=secret",
+                    "README.md": "# Zed",
+                }
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/root/zed".as_ref()], cx).await;
+        let cx = cx.add_empty_window();
+
+        let read_file = |path: &str, cx: &mut VisualTestContext| {
+            let input = serde_json::from_value(json!({ "path": path })).unwrap();
+            let project = project.clone();
+            cx.update(|cx| ReadFileTool::run(&input, project, cx))
+        };
+
+        let readme = read_file("zed/README.md", cx).await.unwrap();
+        assert!(readme.contains("# Zed"));
+
+        let error = read_file("zed/.env", cx).await.unwrap_err();
+        assert!(error.to_string().contains("private"));
+    }
+}
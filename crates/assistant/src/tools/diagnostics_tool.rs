@@ -0,0 +1,71 @@
+use super::ProjectReadTool;
+use crate::slash_command::diagnostics_command::{collect_diagnostics, Options};
+use anyhow::Result;
+use gpui::{Model, Task, WindowContext};
+use project::Project;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use ui::IconName;
+use util::paths::PathMatcher;
+
+pub(super) struct DiagnosticsTool;
+
+#[derive(Deserialize, JsonSchema)]
+pub(super) struct DiagnosticsInput {
+    /// A glob restricting the files whose diagnostics are reported,
+    /// e.g. `zed/src/**/*.rs`. All files are included when omitted.
+    path: Option<String>,
+    /// Whether to report warnings in addition to errors.
+    #[serde(default)]
+    include_warnings: bool,
+}
+
+impl ProjectReadTool for DiagnosticsTool {
+    type Input = DiagnosticsInput;
+
+    fn name() -> &'static str {
+        "diagnostics"
+    }
+
+    fn description() -> &'static str {
+        "Reports the errors, and optionally the warnings, that language servers currently show in the project, along with the surrounding code."
+    }
+
+    fn icon() -> IconName {
+        IconName::ExclamationTriangle
+    }
+
+    fn label(input: &Self::Input) -> String {
+        match &input.path {
+            Some(path) => format!("Check diagnostics in {path}"),
+            None => "Check diagnostics".into(),
+        }
+    }
+
+    fn run(
+        input: &Self::Input,
+        project: Model<Project>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let path_matcher = match input
+            .path
+            .as_ref()
+            .map(|path| PathMatcher::new(&[path.clone()]))
+            .transpose()
+        {
+            Ok(path_matcher) => path_matcher,
+            Err(error) => return Task::ready(Err(error.into())),
+        };
+        let options = Options {
+            include_warnings: input.include_warnings,
+            path_matcher,
+        };
+
+        let diagnostics = collect_diagnostics(project, options, cx);
+        cx.background_executor().spawn(async move {
+            Ok(diagnostics
+                .await?
+                .map_or_else(|| "No diagnostics found.".into(), |(text, _)| text))
+        })
+    }
+}
@@ -0,0 +1,87 @@
+use super::{resolve_project_path, ProjectReadTool};
+use anyhow::{anyhow, Result};
+use gpui::{AppContext, Model, Task, WindowContext};
+use project::Project;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::fmt::Write as _;
+use ui::IconName;
+
+pub(super) struct ListDirectoryTool;
+
+#[derive(Deserialize, JsonSchema)]
+pub(super) struct ListDirectoryInput {
+    /// The path of the directory, starting with the name of the project's root directory.
+    /// Leave it empty to list the project's root directories.
+    #[serde(default)]
+    path: String,
+}
+
+impl ProjectReadTool for ListDirectoryTool {
+    type Input = ListDirectoryInput;
+
+    fn name() -> &'static str {
+        "list_directory"
+    }
+
+    fn description() -> &'static str {
+        "Lists the files and directories directly inside of a directory of the project."
+    }
+
+    fn icon() -> IconName {
+        IconName::Folder
+    }
+
+    fn label(input: &Self::Input) -> String {
+        if input.path.is_empty() {
+            "List project roots".into()
+        } else {
+            format!("List {}", input.path)
+        }
+    }
+
+    fn run(
+        input: &Self::Input,
+        project: Model<Project>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        Task::ready(list_directory(project.read(cx), &input.path, cx))
+    }
+}
+
+fn list_directory(project: &Project, path: &str, cx: &AppContext) -> Result<String> {
+    let mut text = String::new();
+    if path.is_empty() || path == "." {
+        for worktree in project.visible_worktrees(cx) {
+            writeln!(text, "{}/", worktree.read(cx).root_name())?;
+        }
+        return Ok(text);
+    }
+
+    let project_path = resolve_project_path(project, path, cx)?;
+    let worktree = project
+        .worktree_for_id(project_path.worktree_id, cx)
+        .ok_or_else(|| anyhow!("{path} does not exist in the project"))?;
+    let snapshot = worktree.read(cx).snapshot();
+    let entry = snapshot
+        .entry_for_path(&project_path.path)
+        .ok_or_else(|| anyhow!("{path} does not exist in the project"))?;
+    if !entry.is_dir() {
+        return Err(anyhow!("{path} is a file, not a directory"));
+    }
+
+    for child in snapshot.child_entries(&project_path.path) {
+        let Some(name) = child.path.file_name() else {
+            continue;
+        };
+        if child.is_dir() {
+            writeln!(text, "{}/", name.to_string_lossy())?;
+        } else {
+            writeln!(text, "{}", name.to_string_lossy())?;
+        }
+    }
+    if text.is_empty() {
+        text.push_str("The directory is empty.");
+    }
+    Ok(text)
+}
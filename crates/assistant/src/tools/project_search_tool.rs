@@ -0,0 +1,137 @@
+use super::ProjectReadTool;
+use anyhow::Result;
+use gpui::{Model, Task, WindowContext};
+use language::{Point, ToPoint};
+use project::{search::SearchQuery, Project, SearchResult};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::fmt::Write as _;
+use ui::IconName;
+use util::paths::PathMatcher;
+
+/// The maximum number of matching lines returned to the model.
+const MAX_MATCHES: usize = 100;
+
+pub(super) struct ProjectSearchTool;
+
+#[derive(Deserialize, JsonSchema)]
+pub(super) struct ProjectSearchInput {
+    /// The text to search for.
+    query: String,
+    /// Whether the query is a regular expression.
+    #[serde(default)]
+    regex: bool,
+    /// A glob restricting the searched files, e.g. `**/*.rs`.
+    include: Option<String>,
+}
+
+impl ProjectSearchTool {
+    fn query(input: &ProjectSearchInput) -> Result<SearchQuery> {
+        let files_to_include = match &input.include {
+            Some(glob) => PathMatcher::new(&[glob.clone()])?,
+            None => PathMatcher::default(),
+        };
+        if input.regex {
+            SearchQuery::regex(
+                &input.query,
+                false,
+                false,
+                false,
+                files_to_include,
+                PathMatcher::default(),
+            )
+        } else {
+            SearchQuery::text(
+                &input.query,
+                false,
+                false,
+                false,
+                files_to_include,
+                PathMatcher::default(),
+            )
+        }
+    }
+}
+
+impl ProjectReadTool for ProjectSearchTool {
+    type Input = ProjectSearchInput;
+
+    fn name() -> &'static str {
+        "project_search"
+    }
+
+    fn description() -> &'static str {
+        "Searches the files of the project for text, returning the matching lines along with their paths and line numbers."
+    }
+
+    fn icon() -> IconName {
+        IconName::MagnifyingGlass
+    }
+
+    fn label(input: &Self::Input) -> String {
+        match &input.include {
+            Some(include) => format!("Search for `{}` in {include}", input.query),
+            None => format!("Search for `{}`", input.query),
+        }
+    }
+
+    fn run(
+        input: &Self::Input,
+        project: Model<Project>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let query = match Self::query(input) {
+            Ok(query) => query,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        let results = project.update(cx, |project, cx| project.search(query, cx));
+
+        cx.spawn(|cx| async move {
+            let mut text = String::new();
+            let mut match_count = 0;
+            'results: while let Ok(result) = results.recv().await {
+                let SearchResult::Buffer { buffer, ranges } = result else {
+                    break;
+                };
+                // Matches in private files aren't shown, as they may contain secrets.
+                let (snapshot, path) = buffer.read_with(&cx, |buffer, cx| {
+                    (
+                        buffer.snapshot(),
+                        buffer
+                            .file()
+                            .filter(|file| !file.is_private())
+                            .map(|file| file.full_path(cx)),
+                    )
+                })?;
+                let Some(path) = path else {
+                    continue;
+                };
+
+                let mut last_row = None;
+                for range in ranges {
+                    let row = range.start.to_point(&snapshot).row;
+                    if last_row == Some(row) {
+                        continue;
+                    }
+                    last_row = Some(row);
+
+                    if match_count == MAX_MATCHES {
+                        writeln!(text, "(only the first {MAX_MATCHES} matches are shown)")?;
+                        break 'results;
+                    }
+                    match_count += 1;
+
+                    let line = snapshot
+                        .text_for_range(Point::new(row, 0)..Point::new(row, snapshot.line_len(row)))
+                        .collect::<String>();
+                    writeln!(text, "{}:{}: {}", path.display(), row + 1, line.trim())?;
+                }
+            }
+
+            if text.is_empty() {
+                text.push_str("No matches found.");
+            }
+            Ok(text)
+        })
+    }
+}
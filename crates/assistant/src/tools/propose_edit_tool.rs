@@ -0,0 +1,273 @@
+use super::{render_tool_call_header, resolve_project_path, ToolCallState};
+use anyhow::{anyhow, Result};
+use assistant_tooling::{LanguageModelTool, ProjectContext, ToolView};
+use futures::channel::oneshot;
+use gpui::{Model, Task, View, WindowContext};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use similar::{ChangeTag, TextDiff};
+use std::ops::Range;
+use theme::ThemeSettings;
+use ui::prelude::*;
+
+/// The maximum number of diff lines shown while an edit awaits confirmation.
+const MAX_DIFF_LINES: usize = 20;
+
+/// Proposes an edit to a file, which is only applied once the user accepts it.
+pub(crate) struct ProposeEditTool {
+    project: Model<Project>,
+}
+
+impl ProposeEditTool {
+    pub(crate) fn new(project: Model<Project>) -> Self {
+        Self { project }
+    }
+}
+
+#[derive(Clone, Deserialize, JsonSchema)]
+pub(crate) struct ProposeEditInput {
+    /// The path of the file to edit, starting with the name of the project's root directory.
+    path: String,
+    /// The text to replace, which must appear exactly once in the file. Include enough
+    /// surrounding lines to make it unique. When empty, `new_text` is appended to the file.
+    old_text: String,
+    /// The text to replace `old_text` with.
+    new_text: String,
+}
+
+impl LanguageModelTool for ProposeEditTool {
+    type View = ProposeEditView;
+
+    fn name(&self) -> String {
+        "propose_edit".into()
+    }
+
+    fn description(&self) -> String {
+        "Proposes replacing a snippet of an existing file in the project with new text. The user reviews the diff and either applies or rejects the edit.".into()
+    }
+
+    fn view(&self, cx: &mut WindowContext) -> View<Self::View> {
+        let project = self.project.clone();
+        cx.new_view(|_| ProposeEditView {
+            project,
+            input: None,
+            diff: Vec::new(),
+            confirmation: None,
+            outcome: None,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) enum EditOutcome {
+    Applied,
+    Rejected,
+    Failed(String),
+}
+
+pub(crate) struct ProposeEditView {
+    project: Model<Project>,
+    input: Option<ProposeEditInput>,
+    diff: Vec<(ChangeTag, SharedString)>,
+    confirmation: Option<oneshot::Sender<bool>>,
+    outcome: Option<EditOutcome>,
+}
+
+impl ProposeEditView {
+    fn confirm(&mut self, apply: bool, cx: &mut ViewContext<Self>) {
+        if let Some(confirmation) = self.confirmation.take() {
+            confirmation.send(apply).ok();
+            cx.notify();
+        }
+    }
+
+    fn render_diff(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let hidden_lines = self.diff.len().saturating_sub(MAX_DIFF_LINES);
+        v_flex()
+            .font_family(ThemeSettings::get_global(cx).buffer_font.family.clone())
+            .children(self.diff.iter().take(MAX_DIFF_LINES).map(|(tag, line)| {
+                let (prefix, color) = match tag {
+                    ChangeTag::Insert => ("+", Color::Created),
+                    ChangeTag::Delete => ("-", Color::Deleted),
+                    ChangeTag::Equal => (" ", Color::Muted),
+                };
+                Label::new(format!("{prefix} {line}"))
+                    .size(LabelSize::Small)
+                    .color(color)
+            }))
+            .when(hidden_lines > 0, |this| {
+                this.child(
+                    Label::new(format!("… {hidden_lines} more lines"))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+            })
+    }
+}
+
+impl Render for ProposeEditView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let label = self.input.as_ref().map_or_else(
+            || "Edit".to_string(),
+            |input| format!("Edit {}", input.path),
+        );
+        let state = match &self.outcome {
+            None => ToolCallState::Pending,
+            Some(EditOutcome::Applied) => ToolCallState::Done(String::new()),
+            Some(EditOutcome::Rejected) => ToolCallState::Failed("Rejected".into()),
+            Some(EditOutcome::Failed(error)) => ToolCallState::Failed(error.clone()),
+        };
+
+        v_flex()
+            .gap_1()
+            .child(render_tool_call_header(IconName::Pencil, label, &state))
+            .when(self.confirmation.is_some(), |this| {
+                this.child(self.render_diff(cx)).child(
+                    h_flex()
+                        .gap_1()
+                        .child(
+                            Button::new("apply-edit", "Apply")
+                                .style(ButtonStyle::Filled)
+                                .on_click(cx.listener(|this, _, cx| this.confirm(true, cx))),
+                        )
+                        .child(
+                            Button::new("reject-edit", "Reject")
+                                .on_click(cx.listener(|this, _, cx| this.confirm(false, cx))),
+                        ),
+                )
+            })
+    }
+}
+
+impl ToolView for ProposeEditView {
+    type Input = ProposeEditInput;
+    type SerializedState = Option<EditOutcome>;
+
+    fn generate(&self, _project: &mut ProjectContext, _cx: &mut ViewContext<Self>) -> String {
+        let path = self.input.as_ref().map_or("", |input| input.path.as_str());
+        match &self.outcome {
+            Some(EditOutcome::Applied) => format!("Applied the edit to {path}."),
+            Some(EditOutcome::Rejected) => {
+                format!("The user rejected the proposed edit to {path}.")
+            }
+            Some(EditOutcome::Failed(error)) => format!("Failed to edit {path}: {error}"),
+            None => String::new(),
+        }
+    }
+
+    fn set_input(&mut self, input: Self::Input, cx: &mut ViewContext<Self>) {
+        self.input = Some(input);
+        cx.notify();
+    }
+
+    fn execute(&mut self, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        let Some(input) = self.input.clone() else {
+            return Task::ready(Err(anyhow!("invalid arguments for propose_edit")));
+        };
+        let project_path = match resolve_project_path(self.project.read(cx), &input.path, cx) {
+            Ok(project_path) => project_path,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        let project = self.project.clone();
+        let open_buffer = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+
+        cx.spawn(|this, mut cx| async move {
+            let result = async {
+                let buffer = open_buffer.await?;
+                let text = buffer.read_with(&cx, |buffer, _| buffer.text())?;
+                let range = find_edit_range(&text, &input.old_text)?;
+                let diff = TextDiff::from_lines(&text[range], input.new_text.as_str())
+                    .iter_all_changes()
+                    .map(|change| {
+                        let line = change.value().trim_end_matches('\n').to_string();
+                        (change.tag(), SharedString::from(line))
+                    })
+                    .collect();
+
+                let (confirmation_tx, confirmation_rx) = oneshot::channel();
+                this.update(&mut cx, |this, cx| {
+                    this.diff = diff;
+                    this.confirmation = Some(confirmation_tx);
+                    cx.notify();
+                })?;
+                if !confirmation_rx.await.unwrap_or(false) {
+                    return Ok(EditOutcome::Rejected);
+                }
+
+                // The buffer may have changed while the user was reviewing the edit.
+                buffer.update(&mut cx, |buffer, cx| {
+                    let range = find_edit_range(&buffer.text(), &input.old_text)?;
+                    buffer.edit([(range, input.new_text.as_str())], None, cx);
+                    anyhow::Ok(())
+                })??;
+                project
+                    .update(&mut cx, |project, cx| project.save_buffer(buffer, cx))?
+                    .await?;
+                anyhow::Ok(EditOutcome::Applied)
+            }
+            .await;
+
+            let outcome = result.unwrap_or_else(|error| EditOutcome::Failed(error.to_string()));
+            this.update(&mut cx, |this, cx| {
+                this.confirmation = None;
+                this.diff.clear();
+                this.outcome = Some(outcome.clone());
+                cx.notify();
+            })?;
+            match outcome {
+                EditOutcome::Applied => Ok(()),
+                EditOutcome::Rejected => Err(anyhow!(
+                    "The user rejected the proposed edit to {}.",
+                    input.path
+                )),
+                EditOutcome::Failed(error) => Err(anyhow!(error)),
+            }
+        })
+    }
+
+    fn serialize(&self, _cx: &mut ViewContext<Self>) -> Self::SerializedState {
+        self.outcome.clone()
+    }
+
+    fn deserialize(
+        &mut self,
+        outcome: Self::SerializedState,
+        _cx: &mut ViewContext<Self>,
+    ) -> Result<()> {
+        self.outcome = outcome;
+        Ok(())
+    }
+}
+
+/// Returns the range of the only occurrence of `old_text` within `text`, or an
+/// empty range at the end of `text` if `old_text` is empty.
+fn find_edit_range(text: &str, old_text: &str) -> Result<Range<usize>> {
+    if old_text.is_empty() {
+        return Ok(text.len()..text.len());
+    }
+
+    let mut matches = text.match_indices(old_text);
+    match (matches.next(), matches.next()) {
+        (Some((start, _)), None) => Ok(start..start + old_text.len()),
+        (Some(_), Some(_)) => Err(anyhow!(
+            "the text to replace appears more than once in the file"
+        )),
+        (None, _) => Err(anyhow!("the text to replace was not found in the file")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_edit_range() {
+        let text = "fn a() {}\nfn b() {}\nfn a() {}\n";
+        assert_eq!(find_edit_range(text, "fn b").unwrap(), 10..14);
+        assert_eq!(find_edit_range(text, "").unwrap(), text.len()..text.len());
+        assert!(find_edit_range(text, "fn a() {}").is_err());
+        assert!(find_edit_range(text, "fn c() {}").is_err());
+    }
+}
@@ -0,0 +1,96 @@
+use super::{resolve_project_path, ProjectReadTool};
+use crate::slash_command::file_command::codeblock_fence_for_path;
+use anyhow::{anyhow, Result};
+use gpui::{Model, Task, WindowContext};
+use language::Point;
+use project::Project;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::path::Path;
+use ui::IconName;
+
+pub(super) struct ReadFileTool;
+
+#[derive(Deserialize, JsonSchema)]
+pub(super) struct ReadFileInput {
+    /// The path of the file, starting with the name of the project's root directory.
+    path: String,
+    /// The 1-based line at which to start reading. Defaults to the start of the file.
+    start_line: Option<u32>,
+    /// The 1-based line at which to stop reading, inclusive. Defaults to the end of the file.
+    end_line: Option<u32>,
+}
+
+impl ProjectReadTool for ReadFileTool {
+    type Input = ReadFileInput;
+
+    fn name() -> &'static str {
+        "read_file"
+    }
+
+    fn description() -> &'static str {
+        "Reads the contents of a file in the project, optionally restricted to a range of lines."
+    }
+
+    fn icon() -> IconName {
+        IconName::File
+    }
+
+    fn label(input: &Self::Input) -> String {
+        match (input.start_line, input.end_line) {
+            (Some(start), Some(end)) => format!("Read {}:{start}-{end}", input.path),
+            (Some(start), None) => format!("Read {}:{start}-", input.path),
+            (None, Some(end)) => format!("Read {}:1-{end}", input.path),
+            (None, None) => format!("Read {}", input.path),
+        }
+    }
+
+    fn run(
+        input: &Self::Input,
+        project: Model<Project>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let project_path = match resolve_project_path(project.read(cx), &input.path, cx) {
+            Ok(project_path) => project_path,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        // Private files, such as `.env` files, may contain secrets, which must not be sent to the
+        // model without the user's consent.
+        let is_private = project
+            .read(cx)
+            .entry_for_path(&project_path, cx)
+            .map_or(false, |entry| entry.is_private);
+        if is_private {
+            return Task::ready(Err(anyhow!(
+                "{} is a private file, which can't be read",
+                input.path
+            )));
+        }
+
+        let open_buffer = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+        let path = input.path.clone();
+        let start_line = input.start_line;
+        let end_line = input.end_line;
+        cx.spawn(|cx| async move {
+            let buffer = open_buffer.await?;
+            let snapshot = buffer.read_with(&cx, |buffer, _| buffer.snapshot())?;
+
+            let max_row = snapshot.max_point().row;
+            let start_row = start_line.map_or(0, |line| line.saturating_sub(1).min(max_row));
+            let end_row = end_line
+                .map_or(max_row, |line| line.saturating_sub(1).min(max_row))
+                .max(start_row);
+            let range = Point::new(start_row, 0)..Point::new(end_row, snapshot.line_len(end_row));
+
+            let row_range =
+                (start_line.is_some() || end_line.is_some()).then(|| start_row..end_row);
+            let mut text = codeblock_fence_for_path(Some(Path::new(&path)), row_range);
+            text.extend(snapshot.text_for_range(range));
+            if !text.ends_with('\n') {
+                text.push('\n');
+            }
+            text.push_str("```");
+            Ok(text)
+        })
+    }
+}
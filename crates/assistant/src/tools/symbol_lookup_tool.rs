@@ -0,0 +1,78 @@
+use super::ProjectReadTool;
+use anyhow::Result;
+use gpui::{Model, Task, WindowContext};
+use project::Project;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::{fmt::Write as _, path::PathBuf};
+use ui::IconName;
+
+/// The maximum number of symbols returned to the model.
+const MAX_SYMBOLS: usize = 50;
+
+pub(super) struct SymbolLookupTool;
+
+#[derive(Deserialize, JsonSchema)]
+pub(super) struct SymbolLookupInput {
+    /// The name of the symbol, or part of it.
+    query: String,
+}
+
+impl ProjectReadTool for SymbolLookupTool {
+    type Input = SymbolLookupInput;
+
+    fn name() -> &'static str {
+        "symbol_lookup"
+    }
+
+    fn description() -> &'static str {
+        "Looks up the definitions of functions, types and other symbols across the project by name, using the project's language servers."
+    }
+
+    fn icon() -> IconName {
+        IconName::Code
+    }
+
+    fn label(input: &Self::Input) -> String {
+        format!("Look up `{}`", input.query)
+    }
+
+    fn run(
+        input: &Self::Input,
+        project: Model<Project>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let query = input.query.clone();
+        let symbols = project.update(cx, |project, cx| project.symbols(&query, cx));
+        cx.spawn(|mut cx| async move {
+            let symbols = symbols.await?;
+            cx.update(|cx| {
+                let mut text = String::new();
+                for symbol in symbols.iter().take(MAX_SYMBOLS) {
+                    let Some(worktree) = project
+                        .read(cx)
+                        .worktree_for_id(symbol.path.worktree_id, cx)
+                    else {
+                        continue;
+                    };
+                    let mut path = PathBuf::from(worktree.read(cx).root_name());
+                    path.push(&symbol.path.path);
+                    writeln!(
+                        text,
+                        "{} ({}:{})",
+                        symbol.label.text,
+                        path.display(),
+                        symbol.range.start.0.row + 1
+                    )?;
+                }
+                if symbols.len() > MAX_SYMBOLS {
+                    writeln!(text, "(only the first {MAX_SYMBOLS} symbols are shown)")?;
+                }
+                if text.is_empty() {
+                    write!(text, "No symbols found matching `{query}`.")?;
+                }
+                Ok(text)
+            })?
+        })
+    }
+}
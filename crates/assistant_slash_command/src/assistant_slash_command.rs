@@ -64,9 +64,19 @@ pub type RenderFoldPlaceholder = Arc<
 pub struct SlashCommandOutput {
     pub text: String,
     pub sections: Vec<SlashCommandOutputSection<usize>>,
+    pub images: Vec<SlashCommandOutputImage>,
     pub run_commands_in_text: bool,
 }
 
+/// An image produced by a slash command, such as `/file` with the path of an image.
+#[derive(Clone, Debug)]
+pub struct SlashCommandOutputImage {
+    /// The offset in the output's text before which the image is displayed.
+    pub offset: usize,
+    /// The encoded image, in any format supported by the `image` crate.
+    pub bytes: Vec<u8>,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SlashCommandOutputSection<T> {
     pub range: Range<T>,
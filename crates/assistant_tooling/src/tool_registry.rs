@@ -61,7 +61,7 @@ enum SavedToolFunctionCallState {
     ExecutedTool(Box<RawValue>),
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ToolFunctionDefinition {
     pub name: String,
     pub description: String,
@@ -217,6 +217,16 @@ impl ToolRegistry {
         }
     }
 
+    /// Returns the view displaying the given tool call, once the tool being called is known.
+    pub fn view_for_tool_call(&self, tool_call: &ToolFunctionCall) -> Option<AnyView> {
+        match &tool_call.state {
+            ToolFunctionCallState::KnownTool(view) | ToolFunctionCallState::ExecutedTool(view) => {
+                Some(view.view())
+            }
+            ToolFunctionCallState::Initializing | ToolFunctionCallState::NoSuchTool => None,
+        }
+    }
+
     pub fn content_for_tool_call(
         &self,
         tool_call: &ToolFunctionCall,
@@ -0,0 +1,541 @@
+use editor::{scroll::Autoscroll, Editor};
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, rems, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model,
+    Render, Subscription, Task, View, ViewContext, VisualContext, WeakView,
+};
+use language::Point;
+use ordered_float::OrderedFloat;
+use picker::{Picker, PickerDelegate};
+use project::{bookmark_store::BookmarkId, Project, ProjectPath};
+use std::{cmp::Reverse, path::PathBuf, sync::Arc};
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+actions!(bookmarks, [Toggle, EditLabel, ClearAll]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(BookmarksView::register).detach();
+    cx.observe_new_views(BookmarkLabelEditor::register).detach();
+}
+
+pub struct BookmarksView {
+    picker: View<Picker<BookmarksViewDelegate>>,
+}
+
+impl ModalView for BookmarksView {}
+
+impl EventEmitter<DismissEvent> for BookmarksView {}
+
+impl FocusableView for BookmarksView {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for BookmarksView {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl BookmarksView {
+    fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+        workspace.register_action(|workspace, _: &Toggle, cx| {
+            let project = workspace.project().clone();
+            let handle = cx.view().downgrade();
+            workspace.toggle_modal(cx, move |cx| BookmarksView::new(handle, project, cx));
+        });
+        workspace.register_action(|workspace, _: &ClearAll, cx| {
+            let bookmark_store = workspace.project().read(cx).bookmark_store().clone();
+            bookmark_store.update(cx, |store, cx| store.clear(cx));
+        });
+    }
+
+    fn new(
+        workspace: WeakView<Workspace>,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let delegate = BookmarksViewDelegate::new(cx.view().downgrade(), workspace, project, cx);
+        let picker =
+            cx.new_view(|cx| Picker::uniform_list(delegate, cx).max_height(Some(vh(0.75, cx))));
+        Self { picker }
+    }
+}
+
+struct BookmarkEntry {
+    id: BookmarkId,
+    project_path: ProjectPath,
+    point: Point,
+    /// The bookmark's label, or the text of its line if it has no label.
+    title: String,
+    /// The path of the bookmarked file, suffixed with the bookmark's line number.
+    location: String,
+}
+
+pub struct BookmarksViewDelegate {
+    bookmarks_view: WeakView<BookmarksView>,
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    entries: Vec<BookmarkEntry>,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl BookmarksViewDelegate {
+    fn new(
+        bookmarks_view: WeakView<BookmarksView>,
+        workspace: WeakView<Workspace>,
+        project: Model<Project>,
+        cx: &mut ViewContext<BookmarksView>,
+    ) -> Self {
+        let project_handle = project.read(cx);
+        let mut entries = project_handle
+            .bookmark_store()
+            .read(cx)
+            .bookmarks()
+            .iter()
+            .map(|bookmark| {
+                let point = bookmark.point(cx);
+                let line_text = bookmark.buffer().map(|buffer| {
+                    let buffer = buffer.read(cx);
+                    let line_end = Point::new(point.row, buffer.line_len(point.row));
+                    buffer
+                        .text_for_range(Point::new(point.row, 0)..line_end)
+                        .collect::<String>()
+                });
+                let title = bookmark
+                    .label
+                    .as_ref()
+                    .map(|label| label.to_string())
+                    .or_else(|| {
+                        line_text
+                            .map(|text| text.trim().to_string())
+                            .filter(|text| !text.is_empty())
+                    })
+                    .unwrap_or_else(|| format!("Line {}", point.row + 1));
+
+                let mut full_path = PathBuf::new();
+                if let Some(worktree) =
+                    project_handle.worktree_for_id(bookmark.project_path.worktree_id, cx)
+                {
+                    full_path.push(worktree.read(cx).root_name());
+                }
+                full_path.push(&bookmark.project_path.path);
+                let location = format!("{}:{}", full_path.to_string_lossy(), point.row + 1);
+
+                BookmarkEntry {
+                    id: bookmark.id,
+                    project_path: bookmark.project_path.clone(),
+                    point,
+                    title,
+                    location,
+                }
+            })
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| {
+            (&a.project_path.path, a.point.row).cmp(&(&b.project_path.path, b.point.row))
+        });
+
+        let candidates = entries
+            .iter()
+            .enumerate()
+            .map(|(id, entry)| {
+                StringMatchCandidate::new(id, format!("{} {}", entry.title, entry.location))
+            })
+            .collect();
+
+        Self {
+            bookmarks_view,
+            workspace,
+            project,
+            entries,
+            candidates,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for BookmarksViewDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Search bookmarks...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        if query.is_empty() {
+            self.matches = self
+                .candidates
+                .iter()
+                .map(|candidate| StringMatch {
+                    candidate_id: candidate.id,
+                    score: Default::default(),
+                    positions: Default::default(),
+                    string: candidate.string.clone(),
+                })
+                .collect();
+        } else {
+            let mut matches = cx.background_executor().block(fuzzy::match_strings(
+                &self.candidates,
+                &query,
+                false,
+                100,
+                &Default::default(),
+                cx.background_executor().clone(),
+            ));
+            matches.sort_by_key(|mat| (Reverse(OrderedFloat(mat.score)), mat.candidate_id));
+            self.matches = matches;
+        }
+        self.selected_index = 0;
+        Task::ready(())
+    }
+
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(entry) = self
+            .matches
+            .get(self.selected_index)
+            .and_then(|mat| self.entries.get(mat.candidate_id))
+        else {
+            return;
+        };
+
+        let bookmark_id = entry.id;
+        let fallback_point = entry.point;
+        let open_buffer = self.project.update(cx, |project, cx| {
+            project.open_buffer(entry.project_path.clone(), cx)
+        });
+        let project = self.project.clone();
+        let workspace = self.workspace.clone();
+        cx.spawn(|_, mut cx| async move {
+            let buffer = open_buffer.await?;
+            workspace.update(&mut cx, |workspace, cx| {
+                // Opening the buffer anchors the bookmark, so its position reflects
+                // any changes made to the file since the picker was deployed.
+                let point = project
+                    .read(cx)
+                    .bookmark_store()
+                    .read(cx)
+                    .bookmark(bookmark_id)
+                    .map_or(fallback_point, |bookmark| bookmark.point(cx));
+                let pane = if secondary {
+                    workspace.adjacent_pane(cx)
+                } else {
+                    workspace.active_pane().clone()
+                };
+
+                let editor = workspace.open_project_item::<Editor>(pane, buffer, cx);
+                editor.update(cx, |editor, cx| {
+                    editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                        s.select_ranges([point..point])
+                    });
+                });
+            })?;
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.bookmarks_view
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        let entry = self.entries.get(mat.candidate_id)?;
+
+        // Candidates are made of the title and the location, separated by a space.
+        let location_start = entry.title.len() + 1;
+        let title_positions = mat
+            .positions
+            .iter()
+            .copied()
+            .filter(|position| *position < entry.title.len())
+            .collect();
+        let location_positions = mat
+            .positions
+            .iter()
+            .filter_map(|position| position.checked_sub(location_start))
+            .collect();
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .start_slot(
+                    Icon::new(IconName::StarFilled)
+                        .size(IconSize::Small)
+                        .color(Color::Accent),
+                )
+                .child(
+                    v_flex()
+                        .child(HighlightedLabel::new(entry.title.clone(), title_positions))
+                        .child(
+                            HighlightedLabel::new(entry.location.clone(), location_positions)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                ),
+        )
+    }
+}
+
+/// A modal for labeling the bookmark under the cursor, creating the bookmark if needed.
+pub struct BookmarkLabelEditor {
+    label_editor: View<Editor>,
+    active_editor: View<Editor>,
+    bookmark_id: Option<BookmarkId>,
+    project: Model<Project>,
+    _subscription: Subscription,
+}
+
+impl ModalView for BookmarkLabelEditor {}
+
+impl EventEmitter<DismissEvent> for BookmarkLabelEditor {}
+
+impl FocusableView for BookmarkLabelEditor {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.label_editor.focus_handle(cx)
+    }
+}
+
+impl BookmarkLabelEditor {
+    fn register(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+        let handle = cx.view().downgrade();
+        editor
+            .register_action(move |_: &EditLabel, cx| {
+                let Some(editor) = handle.upgrade() else {
+                    return;
+                };
+                let Some(workspace) = editor.read(cx).workspace() else {
+                    return;
+                };
+                workspace.update(cx, |workspace, cx| {
+                    let project = workspace.project().clone();
+                    workspace
+                        .toggle_modal(cx, move |cx| BookmarkLabelEditor::new(editor, project, cx));
+                })
+            })
+            .detach();
+    }
+
+    fn new(
+        active_editor: View<Editor>,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let bookmark_id = active_editor.read(cx).bookmark_at_cursor(cx);
+        let current_label = bookmark_id
+            .and_then(|id| {
+                let bookmark_store = project.read(cx).bookmark_store().read(cx);
+                bookmark_store.bookmark(id)?.label.clone()
+            })
+            .unwrap_or_default();
+
+        let label_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Bookmark label", cx);
+            editor.set_text(current_label, cx);
+            editor.select_all(&editor::actions::SelectAll, cx);
+            editor
+        });
+        let subscription = cx.subscribe(&label_editor, |_, _, event, cx| {
+            if let editor::EditorEvent::Blurred = event {
+                cx.emit(DismissEvent);
+            }
+        });
+
+        Self {
+            label_editor,
+            active_editor,
+            bookmark_id,
+            project,
+            _subscription: subscription,
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let label = self.label_editor.read(cx).text(cx).trim().to_string();
+        let label = (!label.is_empty()).then_some(label);
+        if let Some(bookmark_id) = self.bookmark_id {
+            let bookmark_store = self.project.read(cx).bookmark_store().clone();
+            bookmark_store.update(cx, |store, cx| store.set_label(bookmark_id, label, cx));
+        } else {
+            self.active_editor.update(cx, |editor, cx| {
+                editor.add_bookmark(label, cx);
+            });
+        }
+        self.active_editor.focus_handle(cx).focus(cx);
+        cx.emit(DismissEvent);
+    }
+}
+
+impl Render for BookmarkLabelEditor {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let help_text = if self.bookmark_id.is_some() {
+            "Rename the bookmark on this line"
+        } else {
+            "Add a labeled bookmark on this line"
+        };
+
+        div()
+            .elevation_2(cx)
+            .key_context("BookmarkLabelEditor")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .w_96()
+            .child(
+                v_flex()
+                    .px_1()
+                    .pt_0p5()
+                    .gap_px()
+                    .child(
+                        v_flex()
+                            .py_0p5()
+                            .px_1()
+                            .child(div().px_1().py_0p5().child(self.label_editor.clone())),
+                    )
+                    .child(
+                        div()
+                            .h_px()
+                            .w_full()
+                            .bg(cx.theme().colors().element_background),
+                    )
+                    .child(
+                        h_flex()
+                            .justify_between()
+                            .px_2()
+                            .py_1()
+                            .child(Label::new(help_text).color(Color::Muted)),
+                    ),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::FakeFs;
+    use serde_json::json;
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_bookmarks_view(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a.txt": "alpha\nbeta\ngamma\n",
+                "b.txt": "one\ntwo\nthree\n",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+
+        project.update(cx, |project, cx| {
+            project.bookmark_store().update(cx, |store, cx| {
+                store.add_detached(
+                    (worktree_id, "b.txt").into(),
+                    Point::new(2, 0),
+                    Some("three".into()),
+                    cx,
+                );
+                store.add_detached((worktree_id, "a.txt").into(), Point::new(1, 0), None, cx);
+            })
+        });
+
+        let picker = open_bookmarks_view(&workspace, cx);
+        picker.update(cx, |picker, _| {
+            let titles = picker
+                .delegate
+                .matches
+                .iter()
+                .map(|mat| picker.delegate.entries[mat.candidate_id].title.clone())
+                .collect::<Vec<_>>();
+            // Bookmarks in files that aren't open are listed by their line number.
+            assert_eq!(titles, ["Line 2", "three"]);
+        });
+
+        cx.simulate_input("three");
+        picker.update(cx, |picker, _| {
+            assert_eq!(picker.delegate.matches.len(), 1);
+        });
+        cx.dispatch_action(menu::Confirm);
+        cx.run_until_parked();
+
+        let editor = workspace.update(cx, |workspace, cx| {
+            workspace.active_item_as::<Editor>(cx).unwrap()
+        });
+        editor.update(cx, |editor, cx| {
+            assert_eq!(
+                editor.selections.newest::<Point>(cx).head(),
+                Point::new(2, 0)
+            );
+            assert_eq!(editor.bookmark_anchors(cx).len(), 1);
+        });
+    }
+
+    fn open_bookmarks_view(
+        workspace: &View<Workspace>,
+        cx: &mut VisualTestContext,
+    ) -> View<Picker<BookmarksViewDelegate>> {
+        cx.dispatch_action(Toggle);
+        workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_modal::<BookmarksView>(cx)
+                .unwrap()
+                .read(cx)
+                .picker
+                .clone()
+        })
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        cx.update(|cx| {
+            let state = AppState::test(cx);
+            language::init(cx);
+            super::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            state
+        })
+    }
+}
@@ -16,7 +16,7 @@ pub fn language_model_request_to_open_ai(
 
                 let openai_message = match role {
                     proto::LanguageModelRole::LanguageModelUser => open_ai::RequestMessage::User {
-                        content: message.content,
+                        content: message.content.into(),
                     },
                     proto::LanguageModelRole::LanguageModelAssistant => {
                         open_ai::RequestMessage::Assistant {
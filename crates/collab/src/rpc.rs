@@ -4555,11 +4555,12 @@ async fn complete_with_google_ai(
     api_key: Arc<str>,
 ) -> Result<()> {
     let mut stream = google_ai::stream_generate_content(
-        session.http_client.clone(),
+        session.http_client.as_ref(),
         google_ai::API_URL,
         api_key.as_ref(),
         &request.model.clone(),
         crate::ai::language_model_request_to_google_ai(request)?,
+        None,
     )
     .await
     .context("google_ai::stream_generate_content request failed")?;
@@ -4617,11 +4618,11 @@ async fn complete_with_anthropic(
             match message.role() {
                 LanguageModelRole::LanguageModelUser => Some(anthropic::RequestMessage {
                     role: anthropic::Role::User,
-                    content: message.content,
+                    content: message.content.into(),
                 }),
                 LanguageModelRole::LanguageModelAssistant => Some(anthropic::RequestMessage {
                     role: anthropic::Role::Assistant,
-                    content: message.content,
+                    content: message.content.into(),
                 }),
                 // Anthropic's API breaks system instructions out as a separate field rather
                 // than having a system message role.
@@ -4649,6 +4650,7 @@ async fn complete_with_anthropic(
             stream: true,
             system: system_message,
             max_tokens: 4092,
+            tools: Vec::new(),
         },
         None,
     )
@@ -4686,6 +4688,8 @@ async fn complete_with_anthropic(
                             })?;
                         }
                     }
+                    // Tools aren't sent to Anthropic from here, so it won't ask to use any.
+                    anthropic::ContentBlock::ToolUse { .. } => {}
                 }
             }
             anthropic::ResponseEvent::ContentBlockDelta { delta, .. } => match delta {
@@ -4702,6 +4706,7 @@ async fn complete_with_anthropic(
                         }],
                     })?;
                 }
+                anthropic::TextDelta::InputJsonDelta { .. } => {}
             },
             anthropic::ResponseEvent::MessageDelta { delta, .. } => {
                 if let Some(stop_reason) = delta.stop_reason {
@@ -4768,6 +4773,7 @@ async fn count_tokens_with_language_model(
         session.http_client.as_ref(),
         google_ai::API_URL,
         &api_key,
+        &request.model.clone(),
         crate::ai::count_tokens_request_to_google_ai(request)?,
     )
     .await?;
@@ -1257,5 +1257,13 @@ mod tests {
         fn load(&self, _: &AppContext) -> Task<Result<String>> {
             unimplemented!()
         }
+
+        fn load_with_encoding(
+            &self,
+            _: Option<language::Encoding>,
+            _: &AppContext,
+        ) -> Task<Result<(String, language::Encoding)>> {
+            unimplemented!()
+        }
     }
 }
@@ -214,7 +214,9 @@ gpui::actions!(
         GoToHunk,
         GoToImplementation,
         GoToImplementationSplit,
+        GoToNextBookmark,
         GoToPrevDiagnostic,
+        GoToPrevBookmark,
         GoToPrevHunk,
         GoToTypeDefinition,
         GoToTypeDefinitionSplit,
@@ -287,6 +289,7 @@ gpui::actions!(
         ShowCharacterPalette,
         ShowInlineCompletion,
         ShowSignatureHelp,
+        ShowWordCompletions,
         ShuffleLines,
         SortLinesCaseInsensitive,
         SortLinesCaseSensitive,
@@ -294,6 +297,7 @@ gpui::actions!(
         Tab,
         TabPrev,
         ToggleAutoSignatureHelp,
+        ToggleBookmark,
         ToggleGitBlame,
         ToggleGitBlameInline,
         ToggleSelectionMenu,
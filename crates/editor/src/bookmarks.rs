@@ -0,0 +1,167 @@
+use gpui::{AppContext, ViewContext};
+use multi_buffer::{Anchor, MultiBufferRow, ToPoint as _};
+use project::bookmark_store::BookmarkId;
+use text::Point;
+use ui::{prelude::*, IconButton, IconButtonShape};
+
+use crate::{
+    scroll::Autoscroll, Direction, DisplayRow, Editor, EditorSnapshot, GoToNextBookmark,
+    GoToPrevBookmark, ToDisplayPoint, ToggleBookmark,
+};
+
+impl Editor {
+    pub fn toggle_bookmark(&mut self, _: &ToggleBookmark, cx: &mut ViewContext<Self>) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let head = self.selections.newest_anchor().head();
+        let Some((buffer, anchor)) = self.buffer.read(cx).text_anchor_for_position(head, cx) else {
+            return;
+        };
+
+        let bookmark_store = project.read(cx).bookmark_store().clone();
+        bookmark_store.update(cx, |store, cx| {
+            store.toggle(&buffer, anchor, cx);
+        });
+    }
+
+    /// Adds a bookmark with the given label at the head of the newest selection.
+    pub fn add_bookmark(
+        &mut self,
+        label: Option<String>,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<BookmarkId> {
+        let bookmark_store = self.project.as_ref()?.read(cx).bookmark_store().clone();
+        let head = self.selections.newest_anchor().head();
+        let (buffer, anchor) = self.buffer.read(cx).text_anchor_for_position(head, cx)?;
+        bookmark_store.update(cx, |store, cx| store.add(&buffer, anchor, label, cx))
+    }
+
+    /// Returns the bookmark on the row of the newest selection's head, if any.
+    pub fn bookmark_at_cursor(&self, cx: &AppContext) -> Option<BookmarkId> {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let head_row = self
+            .selections
+            .newest_anchor()
+            .head()
+            .to_point(&snapshot)
+            .row;
+        self.bookmark_anchors(cx)
+            .into_iter()
+            .find(|(_, anchor)| anchor.to_point(&snapshot).row == head_row)
+            .map(|(bookmark_id, _)| bookmark_id)
+    }
+
+    pub fn go_to_next_bookmark(&mut self, _: &GoToNextBookmark, cx: &mut ViewContext<Self>) {
+        self.go_to_bookmark(Direction::Next, cx);
+    }
+
+    pub fn go_to_prev_bookmark(&mut self, _: &GoToPrevBookmark, cx: &mut ViewContext<Self>) {
+        self.go_to_bookmark(Direction::Prev, cx);
+    }
+
+    fn go_to_bookmark(&mut self, direction: Direction, cx: &mut ViewContext<Self>) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let mut points = self
+            .bookmark_anchors(cx)
+            .into_iter()
+            .map(|(_, anchor)| anchor.to_point(&snapshot))
+            .collect::<Vec<_>>();
+        points.sort();
+
+        let head = self.selections.newest::<Point>(cx).head();
+        let target = match direction {
+            Direction::Next => points
+                .iter()
+                .find(|point| point.row > head.row)
+                .or_else(|| points.first()),
+            Direction::Prev => points
+                .iter()
+                .rev()
+                .find(|point| point.row < head.row)
+                .or_else(|| points.last()),
+        };
+
+        if let Some(target) = target.copied() {
+            self.change_selections(Some(Autoscroll::center()), cx, |s| {
+                s.select_ranges([target..target])
+            });
+        }
+    }
+
+    /// Returns the bookmarks located within the excerpts of this editor.
+    pub fn bookmark_anchors(&self, cx: &AppContext) -> Vec<(BookmarkId, Anchor)> {
+        let Some(project) = self.project.as_ref() else {
+            return Vec::new();
+        };
+        let bookmark_store = project.read(cx).bookmark_store().read(cx);
+        if bookmark_store.bookmarks().is_empty() {
+            return Vec::new();
+        }
+
+        let multi_buffer = self.buffer.read(cx);
+        let multi_buffer_snapshot = multi_buffer.snapshot(cx);
+        let mut anchors = Vec::new();
+        for buffer in multi_buffer.all_buffers() {
+            let bookmarks = bookmark_store
+                .bookmarks_for_buffer(&buffer)
+                .collect::<Vec<_>>();
+            if bookmarks.is_empty() {
+                continue;
+            }
+
+            let buffer_snapshot = buffer.read(cx).snapshot();
+            for (excerpt_id, excerpt_range) in multi_buffer.excerpts_for_buffer(&buffer, cx) {
+                let range = excerpt_range.context;
+                for (bookmark_id, anchor) in &bookmarks {
+                    if range.start.cmp(anchor, &buffer_snapshot).is_gt()
+                        || range.end.cmp(anchor, &buffer_snapshot).is_lt()
+                    {
+                        continue;
+                    }
+                    if let Some(anchor) =
+                        multi_buffer_snapshot.anchor_in_excerpt(excerpt_id, *anchor)
+                    {
+                        anchors.push((*bookmark_id, anchor));
+                    }
+                }
+            }
+        }
+        anchors
+    }
+
+    pub(crate) fn bookmark_display_rows(
+        &self,
+        snapshot: &EditorSnapshot,
+        cx: &AppContext,
+    ) -> Vec<(BookmarkId, DisplayRow)> {
+        self.bookmark_anchors(cx)
+            .into_iter()
+            .filter_map(|(bookmark_id, anchor)| {
+                let point = anchor.to_point(&snapshot.buffer_snapshot);
+                if snapshot.is_line_folded(MultiBufferRow(point.row)) {
+                    return None;
+                }
+                Some((bookmark_id, point.to_display_point(snapshot).row()))
+            })
+            .collect()
+    }
+
+    pub(crate) fn render_bookmark_indicator(
+        &self,
+        bookmark_id: BookmarkId,
+        row: DisplayRow,
+        cx: &mut ViewContext<Self>,
+    ) -> IconButton {
+        IconButton::new(("bookmark_indicator", row.0 as usize), IconName::StarFilled)
+            .shape(IconButtonShape::Square)
+            .icon_size(IconSize::XSmall)
+            .icon_color(Color::Accent)
+            .on_click(cx.listener(move |editor, _e, cx| {
+                if let Some(project) = editor.project.as_ref() {
+                    let bookmark_store = project.read(cx).bookmark_store().clone();
+                    bookmark_store.update(cx, |store, cx| store.remove(bookmark_id, cx));
+                }
+            }))
+    }
+}
@@ -15,6 +15,7 @@
 pub mod actions;
 mod blame_entry_tooltip;
 mod blink_manager;
+mod bookmarks;
 mod debounced_delay;
 pub mod display_map;
 mod editor_settings;
@@ -36,6 +37,7 @@ mod rust_analyzer_ext;
 pub mod scroll;
 mod selections_collection;
 pub mod tasks;
+mod word_completions;
 
 #[cfg(test)]
 mod editor_tests;
@@ -56,7 +58,7 @@ use convert_case::{Case, Casing};
 use debounced_delay::DebouncedDelay;
 use display_map::*;
 pub use display_map::{DisplayPoint, FoldPlaceholder};
-pub use editor_settings::{CurrentLineHighlight, EditorSettings};
+pub use editor_settings::{CurrentLineHighlight, EditorSettings, WordCompletionsMode};
 use element::LineWithInvisibles;
 pub use element::{
     CursorLayout, EditorElement, HighlightedRange, HighlightedRangeLine, PointForPosition,
@@ -94,6 +96,7 @@ use language::{
 use language::{point_to_lsp, BufferRow, Runnable, RunnableRange};
 use linked_editing_ranges::refresh_linked_ranges;
 use task::{ResolvedTask, TaskTemplate, TaskVariables};
+use word_completions::word_completions;
 
 use hover_links::{HoverLink, HoveredLinkState, InlayHighlight};
 pub use lsp::CompletionContext;
@@ -112,8 +115,8 @@ use ordered_float::OrderedFloat;
 use parking_lot::{Mutex, RwLock};
 use project::project_settings::{GitGutterSetting, ProjectSettings};
 use project::{
-    CodeAction, Completion, FormatTrigger, Item, Location, Project, ProjectPath,
-    ProjectTransaction, TaskSourceKind, WorktreeId,
+    bookmark_store::BookmarkStoreEvent, CodeAction, Completion, FormatTrigger, Item, Location,
+    Project, ProjectPath, ProjectTransaction, TaskSourceKind, WorktreeId,
 };
 use rand::prelude::*;
 use rpc::{proto::*, ErrorExt};
@@ -1760,6 +1763,12 @@ impl Editor {
                 project_subscriptions.push(cx.observe(&task_inventory, |editor, _, cx| {
                     editor.tasks_update_task = Some(editor.refresh_runnables(cx));
                 }));
+                let bookmark_store = project.read(cx).bookmark_store().clone();
+                project_subscriptions.push(
+                    cx.subscribe(&bookmark_store, |_, _, _: &BookmarkStoreEvent, cx| {
+                        cx.notify()
+                    }),
+                );
             }
         }
 
@@ -1912,6 +1921,16 @@ impl Editor {
             let should_auto_hide_scrollbars = cx.should_auto_hide_scrollbars();
             cx.set_global(ScrollbarAutoHide(should_auto_hide_scrollbars));
 
+            let is_large_file = this
+                .buffer
+                .read(cx)
+                .as_singleton()
+                .map_or(false, |buffer| buffer.read(cx).is_large_file());
+            if is_large_file {
+                this.read_only = ProjectSettings::get_global(cx).large_files.read_only();
+                this.git_blame_inline_enabled = false;
+            }
+
             if this.git_blame_inline_enabled {
                 this.git_blame_inline_enabled = true;
                 this.start_git_blame_inline(false, cx);
@@ -4047,7 +4066,6 @@ impl Editor {
                 return;
             };
 
-        let query = Self::completion_query(&self.buffer.read(cx).read(cx), position);
         let is_followup_invoke = {
             let context_menu_state = self.context_menu.read();
             matches!(
@@ -4071,7 +4089,38 @@ impl Editor {
             trigger_kind,
         };
         let completions = provider.completions(&buffer, buffer_position, completion_context, cx);
+        self.show_completions_menu(position, buffer, completions, cx);
+    }
+
+    /// Shows completions made of the words in open buffers, regardless of language servers.
+    pub fn show_word_completions(&mut self, _: &ShowWordCompletions, cx: &mut ViewContext<Self>) {
+        if self.pending_rename.is_some() {
+            return;
+        }
+
+        let Some(provider) = self.completion_provider.as_ref() else {
+            return;
+        };
+
+        let position = self.selections.newest_anchor().head();
+        let Some((buffer, buffer_position)) =
+            self.buffer.read(cx).text_anchor_for_position(position, cx)
+        else {
+            return;
+        };
+
+        let completions = provider.word_completions(&buffer, buffer_position, cx);
+        self.show_completions_menu(position, buffer, completions, cx);
+    }
 
+    fn show_completions_menu(
+        &mut self,
+        position: Anchor,
+        buffer: Model<Buffer>,
+        completions: Task<Result<Vec<Completion>>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let query = Self::completion_query(&self.buffer.read(cx).read(cx), position);
         let id = post_inc(&mut self.next_completion_id);
         let task = cx.spawn(|this, mut cx| {
             async move {
@@ -11778,6 +11827,16 @@ pub trait CompletionProvider {
         cx: &mut ViewContext<Editor>,
     ) -> Task<Result<Vec<Completion>>>;
 
+    /// Completions made of the words in open buffers, shown by [`ShowWordCompletions`].
+    fn word_completions(
+        &self,
+        _buffer: &Model<Buffer>,
+        _buffer_position: text::Anchor,
+        _cx: &mut ViewContext<Editor>,
+    ) -> Task<Result<Vec<Completion>>> {
+        Task::ready(Ok(Vec::new()))
+    }
+
     fn resolve_completions(
         &self,
         buffer: Model<Buffer>,
@@ -11903,18 +11962,66 @@ impl CompletionProvider for Model<Project> {
         options: CompletionContext,
         cx: &mut ViewContext<Editor>,
     ) -> Task<Result<Vec<Completion>>> {
-        self.update(cx, |project, cx| {
-            let snippets = snippet_completions(project, buffer, buffer_position, cx);
-            let project_completions = project.completions(&buffer, buffer_position, options, cx);
-            cx.background_executor().spawn(async move {
-                let mut completions = project_completions.await?;
-                //let snippets = snippets.into_iter().;
-                completions.extend(snippets);
-                Ok(completions)
-            })
+        let settings = EditorSettings::get_global(cx).word_completions;
+        let project = self.clone();
+        let buffer = buffer.clone();
+        let (snippets, project_completions, eager_word_completions) =
+            self.update(cx, |project, cx| {
+                let snippets = snippet_completions(project, &buffer, buffer_position, cx);
+                // In `always` mode, the words are gathered while the language servers compute
+                // their completions. In `fallback` mode, they're only gathered once it's known
+                // that the language servers returned too few completions.
+                let word_completions = (settings.mode == WordCompletionsMode::Always)
+                    .then(|| word_completions(project, &buffer, buffer_position, cx));
+                let project_completions =
+                    project.completions(&buffer, buffer_position, options, cx);
+                (snippets, project_completions, word_completions)
+            });
+        cx.spawn(|_, mut cx| async move {
+            let mut completions = project_completions.await?;
+            let word_completions = match eager_word_completions {
+                Some(word_completions) => Some(word_completions),
+                None if settings.mode == WordCompletionsMode::Fallback
+                    && completions.len() < settings.min_lsp_completions =>
+                {
+                    Some(project.update(&mut cx, |project, cx| {
+                        word_completions(project, &buffer, buffer_position, cx)
+                    })?)
+                }
+                None => None,
+            };
+            if let Some(word_completions) = word_completions {
+                let word_completions = word_completions.await;
+                let existing_labels = completions
+                    .iter()
+                    .map(|completion| {
+                        completion.label.text[completion.label.filter_range.clone()].to_string()
+                    })
+                    .collect::<HashSet<_>>();
+                completions.extend(
+                    word_completions
+                        .into_iter()
+                        .filter(|completion| !existing_labels.contains(&completion.new_text)),
+                );
+            }
+            completions.extend(snippets);
+            Ok(completions)
         })
     }
 
+    fn word_completions(
+        &self,
+        buffer: &Model<Buffer>,
+        buffer_position: text::Anchor,
+        cx: &mut ViewContext<Editor>,
+    ) -> Task<Result<Vec<Completion>>> {
+        let word_completions = self.update(cx, |project, cx| {
+            word_completions(project, buffer, buffer_position, cx)
+        });
+        cx.background_executor()
+            .spawn(async move { Ok(word_completions.await) })
+    }
+
     fn resolve_completions(
         &self,
         buffer: Model<Buffer>,
@@ -11,6 +11,7 @@ pub struct EditorSettings {
     pub show_completions_on_input: bool,
     pub show_completion_documentation: bool,
     pub completion_documentation_secondary_query_debounce: u64,
+    pub word_completions: WordCompletions,
     pub use_on_type_format: bool,
     pub toolbar: Toolbar,
     pub scrollbar: Scrollbar,
@@ -78,6 +79,28 @@ pub struct Jupyter {
     pub enabled: bool,
 }
 
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct WordCompletions {
+    pub mode: WordCompletionsMode,
+    pub min_lsp_completions: usize,
+    pub min_word_length: usize,
+}
+
+/// When to offer the words of open buffers as completions.
+///
+/// Default: never
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WordCompletionsMode {
+    /// Only when language servers return fewer than `min_lsp_completions` completions,
+    /// including when no language server is running.
+    Fallback,
+    /// Always, alongside the completions of language servers.
+    Always,
+    /// Never, unless requested with `editor::ShowWordCompletions`.
+    Never,
+}
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct Toolbar {
     pub breadcrumbs: bool,
@@ -100,6 +123,7 @@ pub struct Gutter {
     pub line_numbers: bool,
     pub code_actions: bool,
     pub runnables: bool,
+    pub bookmarks: bool,
     pub folds: bool,
 }
 
@@ -178,6 +202,8 @@ pub struct EditorSettingsContent {
     ///
     /// Default: 300 ms
     pub completion_documentation_secondary_query_debounce: Option<u64>,
+    /// Word completions related settings
+    pub word_completions: Option<WordCompletionsContent>,
     /// Whether to use additional LSP queries to format (and amend) the code after
     /// every "trigger" symbol input, defined by LSP server capabilities.
     ///
@@ -250,6 +276,24 @@ pub struct EditorSettingsContent {
     pub jupyter: Option<Jupyter>,
 }
 
+// Word completions related settings
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct WordCompletionsContent {
+    /// When to offer the words of open buffers as completions.
+    ///
+    /// Default: never
+    pub mode: Option<WordCompletionsMode>,
+    /// In `fallback` mode, word completions are offered when language servers
+    /// return fewer completions than this.
+    ///
+    /// Default: 5
+    pub min_lsp_completions: Option<usize>,
+    /// Words shorter than this number of characters are never offered.
+    ///
+    /// Default: 3
+    pub min_word_length: Option<usize>,
+}
+
 // Toolbar related settings
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct ToolbarContent {
@@ -312,6 +356,10 @@ pub struct GutterContent {
     ///
     /// Default: true
     pub runnables: Option<bool>,
+    /// Whether to show bookmark indicators in the gutter.
+    ///
+    /// Default: true
+    pub bookmarks: Option<bool>,
     /// Whether to show fold buttons in the gutter.
     ///
     /// Default: true
@@ -2,7 +2,7 @@ use super::*;
 use crate::{
     scroll::scroll_amount::ScrollAmount,
     test::{
-        assert_text_with_selections, build_editor, editor_hunks,
+        assert_text_with_selections, build_editor, build_editor_with_project, editor_hunks,
         editor_lsp_test_context::EditorLspTestContext, editor_test_context::EditorTestContext,
         expanded_hunks, expanded_hunks_background_highlights, select_ranges,
     },
@@ -6075,6 +6075,50 @@ async fn test_snippets(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_large_file_editor(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+    update_test_project_settings(cx, |settings| {
+        settings.large_files.threshold = Some(64);
+    });
+
+    let text = "fn main() {}\n".repeat(10);
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "large.rs": text.clone() }))
+        .await;
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    project.read_with(cx, |project, _| project.languages().add(rust_lang()));
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/large.rs", cx)
+        })
+        .await
+        .unwrap();
+    let multi_buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer.clone(), cx));
+    let cx = cx.add_empty_window();
+
+    // Large files are read-only by default.
+    let editor =
+        cx.new_view(|cx| build_editor_with_project(project.clone(), multi_buffer.clone(), cx));
+    editor.update(cx, |editor, cx| {
+        assert!(editor.read_only(cx));
+        editor.handle_input("x", cx);
+        assert_eq!(editor.text(cx), text);
+    });
+
+    update_test_project_settings(cx, |settings| {
+        settings.large_files.read_only = Some(false);
+    });
+    let editor =
+        cx.new_view(|cx| build_editor_with_project(project.clone(), multi_buffer.clone(), cx));
+    editor.update(cx, |editor, cx| {
+        assert!(!editor.read_only(cx));
+        editor.handle_input("x", cx);
+        assert_eq!(editor.text(cx), format!("x{text}"));
+    });
+}
+
 #[gpui::test]
 async fn test_document_format_during_save(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -7709,6 +7753,72 @@ async fn test_completion_page_up_down_keys(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_word_completions(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|settings, cx| {
+            settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                settings.word_completions = Some(editor_settings::WordCompletionsContent {
+                    mode: Some(WordCompletionsMode::Fallback),
+                    ..Default::default()
+                });
+            });
+        });
+    });
+
+    // Without a language server, words from the buffer are offered while typing.
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_state("alpha alphabet beta\nbeˇ");
+    cx.simulate_keystroke("t");
+    cx.executor().run_until_parked();
+    cx.update_editor(|editor, _| {
+        if let Some(ContextMenu::Completions(menu)) = editor.context_menu.read().as_ref() {
+            assert_eq!(
+                menu.matches.iter().map(|m| &m.string).collect::<Vec<_>>(),
+                &["beta"]
+            );
+        } else {
+            panic!("expected completion menu to be open");
+        }
+    });
+    cx.update_editor(|editor, cx| {
+        editor
+            .confirm_completion(&ConfirmCompletion::default(), cx)
+            .unwrap()
+    })
+    .await
+    .unwrap();
+    cx.assert_editor_state("alpha alphabet beta\nbetaˇ");
+
+    // Word completions can be requested explicitly, even when disabled while typing.
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|settings, cx| {
+            settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                settings.word_completions = Some(editor_settings::WordCompletionsContent {
+                    mode: Some(WordCompletionsMode::Never),
+                    ..Default::default()
+                });
+            });
+        });
+    });
+    cx.set_state("alpha alphabet beta\nalpˇ");
+    cx.simulate_keystroke("h");
+    cx.executor().run_until_parked();
+    assert!(cx.editor(|e, _| e.context_menu.read().is_none()));
+    cx.update_editor(|editor, cx| editor.show_word_completions(&ShowWordCompletions, cx));
+    cx.executor().run_until_parked();
+    cx.update_editor(|editor, _| {
+        if let Some(ContextMenu::Completions(menu)) = editor.context_menu.read().as_ref() {
+            let mut words = menu.matches.iter().map(|m| &m.string).collect::<Vec<_>>();
+            words.sort();
+            assert_eq!(words, &["alpha", "alphabet"]);
+        } else {
+            panic!("expected completion menu to be open");
+        }
+    });
+}
+
 #[gpui::test]
 async fn test_no_duplicated_completion_requests(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
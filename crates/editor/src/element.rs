@@ -25,7 +25,7 @@ use crate::{
     Selection, SoftWrap, ToPoint, CURSORS_VISIBLE_FOR, MAX_LINE_LEN,
 };
 use client::ParticipantIndex;
-use collections::{BTreeMap, HashMap};
+use collections::{BTreeMap, HashMap, HashSet};
 use git::{blame::BlameEntry, diff::DiffHunkStatus, Oid};
 use gpui::{
     anchored, deferred, div, fill, outline, point, px, quad, relative, size, svg,
@@ -289,6 +289,9 @@ impl EditorElement {
         register_action(view, cx, Editor::go_to_prev_diagnostic);
         register_action(view, cx, Editor::go_to_hunk);
         register_action(view, cx, Editor::go_to_prev_hunk);
+        register_action(view, cx, Editor::toggle_bookmark);
+        register_action(view, cx, Editor::go_to_next_bookmark);
+        register_action(view, cx, Editor::go_to_prev_bookmark);
         register_action(view, cx, |editor, a, cx| {
             editor.go_to_definition(a, cx).detach_and_log_err(cx);
         });
@@ -318,6 +321,7 @@ impl EditorElement {
         register_action(view, cx, Editor::unfold_at);
         register_action(view, cx, Editor::fold_selected_ranges);
         register_action(view, cx, Editor::show_completions);
+        register_action(view, cx, Editor::show_word_completions);
         register_action(view, cx, Editor::toggle_code_actions);
         register_action(view, cx, Editor::open_excerpts);
         register_action(view, cx, Editor::open_excerpts_in_split);
@@ -1616,6 +1620,52 @@ impl EditorElement {
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
+    fn layout_bookmark_indicators(
+        &self,
+        line_height: Pixels,
+        rows: Range<DisplayRow>,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        gutter_dimensions: &GutterDimensions,
+        gutter_hitbox: &Hitbox,
+        snapshot: &EditorSnapshot,
+        occupied_row: Option<DisplayRow>,
+        show_runnables: bool,
+        cx: &mut WindowContext,
+    ) -> Vec<AnyElement> {
+        self.editor.update(cx, |editor, cx| {
+            // Run and code action indicators take precedence over bookmarks on the same row.
+            let mut occupied_rows = occupied_row.into_iter().collect::<HashSet<_>>();
+            if show_runnables {
+                occupied_rows.extend(editor.tasks.values().map(|tasks| {
+                    let multibuffer_point = tasks.offset.0.to_point(&snapshot.buffer_snapshot);
+                    multibuffer_point.to_display_point(snapshot).row()
+                }));
+            }
+
+            editor
+                .bookmark_display_rows(snapshot, cx)
+                .into_iter()
+                .filter(|(_, display_row)| {
+                    rows.contains(display_row) && !occupied_rows.contains(display_row)
+                })
+                .unique_by(|(_, display_row)| *display_row)
+                .map(|(bookmark_id, display_row)| {
+                    let button = editor.render_bookmark_indicator(bookmark_id, display_row, cx);
+                    prepaint_gutter_button(
+                        button,
+                        display_row,
+                        line_height,
+                        gutter_dimensions,
+                        scroll_pixel_position,
+                        gutter_hitbox,
+                        cx,
+                    )
+                })
+                .collect_vec()
+        })
+    }
+
     fn layout_code_actions_indicator(
         &self,
         line_height: Pixels,
@@ -3085,6 +3135,10 @@ impl EditorElement {
                 }
             });
 
+            for bookmark_indicator in layout.bookmark_indicators.iter_mut() {
+                bookmark_indicator.paint(cx);
+            }
+
             for test_indicators in layout.test_indicators.iter_mut() {
                 test_indicators.paint(cx);
             }
@@ -5142,6 +5196,25 @@ impl Element for EditorElement {
                         vec![]
                     };
 
+                    let bookmark_indicators = if gutter_settings.bookmarks {
+                        self.layout_bookmark_indicators(
+                            line_height,
+                            start_row..end_row,
+                            scroll_pixel_position,
+                            &gutter_dimensions,
+                            &gutter_hitbox,
+                            &snapshot,
+                            code_actions_indicator
+                                .as_ref()
+                                .and(newest_selection_head)
+                                .map(|head| head.row()),
+                            gutter_settings.runnables,
+                            cx,
+                        )
+                    } else {
+                        vec![]
+                    };
+
                     self.layout_signature_help(
                         &hitbox,
                         content_origin,
@@ -5252,6 +5325,7 @@ impl Element for EditorElement {
                         selections,
                         mouse_context_menu,
                         test_indicators,
+                        bookmark_indicators,
                         code_actions_indicator,
                         gutter_fold_toggles,
                         crease_trailers,
@@ -5384,6 +5458,7 @@ pub struct EditorLayout {
     selections: Vec<(PlayerColor, Vec<SelectionLayout>)>,
     code_actions_indicator: Option<AnyElement>,
     test_indicators: Vec<AnyElement>,
+    bookmark_indicators: Vec<AnyElement>,
     gutter_fold_toggles: Vec<Option<AnyElement>>,
     crease_trailers: Vec<Option<CreaseTrailerLayout>>,
     mouse_context_menu: Option<AnyElement>,
@@ -5,6 +5,7 @@ use crate::{
 };
 use anyhow::{anyhow, Context as _, Result};
 use collections::HashSet;
+use file_icons::FileIcons;
 use futures::future::try_join_all;
 use git::repository::GitFileStatus;
 use gpui::{
@@ -614,8 +615,18 @@ impl Item for Editor {
             Some(util::truncate_and_trailoff(&description, MAX_TAB_TITLE_LEN))
         });
 
+        let file_icon = FileIcons::should_show(ItemSettings::get_global(cx).file_icons, cx)
+            .then(|| {
+                let file = self.buffer.read(cx).as_singleton()?.read(cx).file()?;
+                FileIcons::get_icon(file.path(), cx)
+            })
+            .flatten();
+
         h_flex()
             .gap_2()
+            .when_some(file_icon, |this, icon| {
+                this.child(Icon::from_path(icon.to_string()).color(label_color))
+            })
             .child(
                 Label::new(self.title(cx).to_string())
                     .color(label_color)
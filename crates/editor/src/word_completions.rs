@@ -0,0 +1,138 @@
+use std::{iter, mem, ops::Range};
+
+use collections::HashSet;
+use fuzzy::StringMatchCandidate;
+use gpui::{AppContext, Model, Task};
+use language::{char_kind, Buffer, BufferSnapshot, CharKind, CodeLabel};
+use lsp::{CompletionItemKind, LanguageServerId};
+use project::{Completion, Project};
+use settings::Settings;
+use text::{Bias, ToOffset as _};
+
+use crate::EditorSettings;
+
+/// The maximum number of bytes scanned for words in each buffer.
+const MAX_SCANNED_LEN: usize = 1024 * 1024;
+/// The maximum number of word completions offered at once.
+const MAX_WORD_COMPLETIONS: usize = 50;
+
+/// Returns completions made of the words of the given buffer and of the project's other open
+/// buffers, ranked by how well they match the word before the cursor.
+pub(crate) fn word_completions(
+    project: &Project,
+    buffer: &Model<Buffer>,
+    buffer_position: text::Anchor,
+    cx: &mut AppContext,
+) -> Task<Vec<Completion>> {
+    let min_word_length = EditorSettings::get_global(cx)
+        .word_completions
+        .min_word_length;
+    let snapshot = buffer.read(cx).snapshot();
+    let offset = buffer_position.to_offset(&snapshot);
+    let (word_range, kind) = snapshot.surrounding_word(offset);
+    let (query_start, current_word) = if kind == Some(CharKind::Word) {
+        let current_word = snapshot.text_for_range(word_range.clone()).collect();
+        (word_range.start, current_word)
+    } else {
+        (offset, String::new())
+    };
+    let query = snapshot
+        .text_for_range(query_start..offset)
+        .collect::<String>();
+    let old_range = snapshot.anchor_before(query_start)..buffer_position;
+
+    // Only the surroundings of the cursor are scanned in very long buffers.
+    let scanned_range = snapshot.clip_offset(offset.saturating_sub(MAX_SCANNED_LEN / 2), Bias::Left)
+        ..snapshot.clip_offset(offset + MAX_SCANNED_LEN / 2, Bias::Right);
+    let other_snapshots = project
+        .opened_buffers(cx)
+        .into_iter()
+        .filter(|other_buffer| other_buffer != buffer)
+        .filter_map(|other_buffer| {
+            let other_buffer = other_buffer.read(cx);
+            (!other_buffer.is_large_file() && other_buffer.len() <= MAX_SCANNED_LEN)
+                .then(|| other_buffer.snapshot())
+        })
+        .collect::<Vec<_>>();
+
+    let executor = cx.background_executor().clone();
+    cx.background_executor().spawn(async move {
+        let mut seen_words = HashSet::default();
+        let mut candidates = Vec::new();
+        let scanned_buffers = iter::once((&snapshot, scanned_range)).chain(
+            other_snapshots
+                .iter()
+                .map(|other_snapshot| (other_snapshot, 0..other_snapshot.len())),
+        );
+        for (snapshot, range) in scanned_buffers {
+            for word in words_in_range(snapshot, range, min_word_length) {
+                if word != current_word && seen_words.insert(word.clone()) {
+                    candidates.push(StringMatchCandidate::new(candidates.len(), word));
+                }
+            }
+        }
+
+        let mut matches = fuzzy::match_strings(
+            &candidates,
+            &query,
+            false,
+            MAX_WORD_COMPLETIONS,
+            &Default::default(),
+            executor,
+        )
+        .await;
+        matches.truncate(MAX_WORD_COMPLETIONS);
+        matches
+            .into_iter()
+            .map(|mat| word_completion(mat.string, old_range.clone()))
+            .collect()
+    })
+}
+
+fn words_in_range(
+    snapshot: &BufferSnapshot,
+    range: Range<usize>,
+    min_word_length: usize,
+) -> Vec<String> {
+    let scope = snapshot.language().map(|language| language.default_scope());
+    let is_completable = |word: &str| {
+        word.chars().count() >= min_word_length && !word.starts_with(|c: char| c.is_ascii_digit())
+    };
+
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for chunk in snapshot.text_for_range(range) {
+        for c in chunk.chars() {
+            if char_kind(&scope, c) == CharKind::Word {
+                word.push(c);
+            } else if !word.is_empty() {
+                let word = mem::take(&mut word);
+                if is_completable(&word) {
+                    words.push(word);
+                }
+            }
+        }
+    }
+    if is_completable(&word) {
+        words.push(word);
+    }
+    words
+}
+
+fn word_completion(word: String, old_range: Range<text::Anchor>) -> Completion {
+    Completion {
+        old_range,
+        new_text: word.clone(),
+        label: CodeLabel::plain(word.clone(), None),
+        server_id: LanguageServerId(usize::MAX),
+        documentation: None,
+        lsp_completion: lsp::CompletionItem {
+            label: word,
+            kind: Some(CompletionItemKind::TEXT),
+            sort_text: Some(char::MAX.to_string()),
+            ..Default::default()
+        },
+        confirm: None,
+        show_new_completions_on_confirm: false,
+    }
+}
@@ -0,0 +1,440 @@
+mod active_buffer_encoding;
+
+pub use active_buffer_encoding::ActiveBufferEncoding;
+use anyhow::anyhow;
+use editor::Editor;
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model,
+    ParentElement, PromptLevel, Render, Styled, View, ViewContext, VisualContext, WeakView,
+};
+use language::{Buffer, Encoding};
+use picker::{Picker, PickerDelegate};
+use project::Project;
+use std::sync::Arc;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{notifications::DetachAndPromptErr, ModalView, Workspace};
+
+actions!(encoding_selector, [ReopenWithEncoding, SaveWithEncoding]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(EncodingSelector::register).detach();
+}
+
+/// What to do with the encoding picked in the [EncodingSelector].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EncodingAction {
+    /// Decode the file on disk again, replacing the contents of the buffer.
+    Reopen,
+    /// Encode the contents of the buffer, and save them.
+    Save,
+}
+
+pub struct EncodingSelector {
+    picker: View<Picker<EncodingSelectorDelegate>>,
+}
+
+impl EncodingSelector {
+    fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+        workspace.register_action(move |workspace, _: &ReopenWithEncoding, cx| {
+            Self::toggle(workspace, EncodingAction::Reopen, cx);
+        });
+        workspace.register_action(move |workspace, _: &SaveWithEncoding, cx| {
+            Self::toggle(workspace, EncodingAction::Save, cx);
+        });
+    }
+
+    fn toggle(
+        workspace: &mut Workspace,
+        action: EncodingAction,
+        cx: &mut ViewContext<Workspace>,
+    ) -> Option<()> {
+        let (_, buffer, _) = workspace
+            .active_item(cx)?
+            .act_as::<Editor>(cx)?
+            .read(cx)
+            .active_excerpt(cx)?;
+        // Only files on this machine are decoded and encoded by this instance of Zed.
+        buffer.read(cx).file()?.as_local()?;
+        let project = workspace.project().clone();
+
+        workspace.toggle_modal(cx, move |cx| {
+            EncodingSelector::new(buffer, project, action, cx)
+        });
+        Some(())
+    }
+
+    fn new(
+        buffer: Model<Buffer>,
+        project: Model<Project>,
+        action: EncodingAction,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let delegate =
+            EncodingSelectorDelegate::new(cx.view().downgrade(), buffer, project, action);
+
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl Render for EncodingSelector {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for EncodingSelector {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for EncodingSelector {}
+impl ModalView for EncodingSelector {}
+
+pub struct EncodingSelectorDelegate {
+    encoding_selector: WeakView<EncodingSelector>,
+    buffer: Model<Buffer>,
+    project: Model<Project>,
+    action: EncodingAction,
+    encodings: Vec<Encoding>,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl EncodingSelectorDelegate {
+    fn new(
+        encoding_selector: WeakView<EncodingSelector>,
+        buffer: Model<Buffer>,
+        project: Model<Project>,
+        action: EncodingAction,
+    ) -> Self {
+        let encodings = Encoding::all();
+        let candidates = encodings
+            .iter()
+            .enumerate()
+            .map(|(candidate_id, encoding)| {
+                StringMatchCandidate::new(candidate_id, encoding.name().to_string())
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            encoding_selector,
+            buffer,
+            project,
+            action,
+            encodings,
+            candidates,
+            matches: vec![],
+            selected_index: 0,
+        }
+    }
+
+    /// Reloads the buffer with the given encoding, asking the user whether to discard the
+    /// buffer's unsaved changes first, since reloading replaces them with the contents on disk.
+    fn reopen_with_encoding(&self, encoding: Encoding, cx: &mut ViewContext<Picker<Self>>) {
+        let buffer = self.buffer.clone();
+        let answer = buffer.read(cx).is_dirty().then(|| {
+            cx.prompt(
+                PromptLevel::Warning,
+                "This file has unsaved changes. Do you want to discard them and reopen it with a different encoding?",
+                None,
+                &["Discard and Reopen", "Cancel"],
+            )
+        });
+        cx.spawn(|_, mut cx| async move {
+            if let Some(answer) = answer {
+                if !matches!(answer.await, Ok(0)) {
+                    return Ok(());
+                }
+            }
+            let reload = buffer.update(&mut cx, |buffer, cx| {
+                buffer.reload_with_encoding(encoding, cx)
+            })?;
+            // The reload is dropped without a result if the file couldn't be read, or if the
+            // buffer was edited in the meantime.
+            reload
+                .await
+                .map_err(|_| anyhow!("Couldn't reopen the file with {encoding}"))?;
+            Ok(())
+        })
+        .detach_and_prompt_err("Failed to reopen with encoding", cx, |error, _| {
+            Some(error.to_string())
+        });
+    }
+
+    fn save_with_encoding(&self, encoding: Encoding, cx: &mut ViewContext<Picker<Self>>) {
+        let buffer = self.buffer.clone();
+        let previous_encoding = buffer.update(cx, |buffer, _| {
+            let previous_encoding = buffer.encoding();
+            buffer.set_encoding(encoding);
+            previous_encoding
+        });
+        let save = self
+            .project
+            .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx));
+        cx.spawn(|_, mut cx| async move {
+            if let Err(error) = save.await {
+                buffer.update(&mut cx, |buffer, _| buffer.set_encoding(previous_encoding))?;
+                return Err(error);
+            }
+            Ok(())
+        })
+        .detach_and_prompt_err("Failed to save with encoding", cx, |error, _| {
+            Some(error.to_string())
+        });
+    }
+}
+
+impl PickerDelegate for EncodingSelectorDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        match self.action {
+            EncodingAction::Reopen => "Reopen with encoding...".into(),
+            EncodingAction::Save => "Save with encoding...".into(),
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(mat) = self.matches.get(self.selected_index) {
+            let encoding = self.encodings[mat.candidate_id];
+            match self.action {
+                EncodingAction::Reopen => self.reopen_with_encoding(encoding, cx),
+                EncodingAction::Save => self.save_with_encoding(encoding, cx),
+            }
+        }
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.encoding_selector
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> gpui::Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, candidate)| StringMatch {
+                        candidate_id: index,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = &self.matches[ix];
+        let mut label = mat.string.clone();
+        if self.encodings[mat.candidate_id] == self.buffer.read(cx).encoding() {
+            label.push_str(" (current)");
+        }
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(HighlightedLabel::new(label, mat.positions.clone())),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::{FakeFs, Fs as _};
+    use serde_json::json;
+    use std::path::Path;
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_reopen_with_encoding(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/dir", json!({ "notes.txt": "café\n" }))
+            .await;
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let (editor, buffer) = open_editor(&workspace, "notes.txt", cx).await;
+
+        select_encoding(&workspace, ReopenWithEncoding, "windows-1252", cx);
+        buffer.read_with(cx, |buffer, _| {
+            assert_eq!(buffer.text(), "cafÃ©\n");
+            assert_eq!(buffer.encoding().name(), "windows-1252");
+        });
+
+        // Unsaved changes are only discarded once the user confirms it.
+        editor.update(cx, |editor, cx| editor.handle_input("x", cx));
+        select_encoding(&workspace, ReopenWithEncoding, "UTF-8", cx);
+        assert!(cx.has_pending_prompt());
+        cx.simulate_prompt_answer(1);
+        cx.run_until_parked();
+        buffer.read_with(cx, |buffer, _| {
+            assert_eq!(buffer.text(), "xcafÃ©\n");
+            assert_eq!(buffer.encoding().name(), "windows-1252");
+        });
+
+        select_encoding(&workspace, ReopenWithEncoding, "UTF-8", cx);
+        assert!(cx.has_pending_prompt());
+        cx.simulate_prompt_answer(0);
+        cx.run_until_parked();
+        buffer.read_with(cx, |buffer, _| {
+            assert_eq!(buffer.text(), "café\n");
+            assert!(buffer.encoding().is_utf8());
+            assert!(!buffer.is_dirty());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_save_with_encoding(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/dir", json!({ "notes.txt": "café\n" }))
+            .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let (_, buffer) = open_editor(&workspace, "notes.txt", cx).await;
+
+        select_encoding(&workspace, SaveWithEncoding, "UTF-16LE", cx);
+        buffer.read_with(cx, |buffer, _| {
+            assert_eq!(buffer.text(), "café\n");
+            assert_eq!(buffer.encoding().name(), "UTF-16LE");
+            assert!(!buffer.is_dirty());
+        });
+        let expected_bytes = [0xFF, 0xFE]
+            .into_iter()
+            .chain("café\n".encode_utf16().flat_map(u16::to_le_bytes))
+            .collect::<Vec<u8>>();
+        assert_eq!(
+            fs.load_bytes(Path::new("/dir/notes.txt")).await.unwrap(),
+            expected_bytes
+        );
+    }
+
+    async fn open_editor(
+        workspace: &View<Workspace>,
+        path: &str,
+        cx: &mut VisualTestContext,
+    ) -> (View<Editor>, Model<Buffer>) {
+        let worktree_id = workspace.update(cx, |workspace, cx| {
+            workspace
+                .project()
+                .read(cx)
+                .worktrees()
+                .next()
+                .unwrap()
+                .read(cx)
+                .id()
+        });
+        let editor = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_path((worktree_id, path), None, true, cx)
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+        let buffer = editor.update(cx, |editor, cx| {
+            editor.buffer().read(cx).as_singleton().unwrap()
+        });
+        (editor, buffer)
+    }
+
+    /// Opens the encoding selector with the given action, and confirms the given encoding.
+    fn select_encoding(
+        workspace: &View<Workspace>,
+        action: impl gpui::Action,
+        encoding_name: &str,
+        cx: &mut VisualTestContext,
+    ) {
+        cx.dispatch_action(action);
+        let picker = workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_modal::<EncodingSelector>(cx)
+                .unwrap()
+                .read(cx)
+                .picker
+                .clone()
+        });
+        picker.update(cx, |picker, cx| picker.set_query(encoding_name, cx));
+        cx.run_until_parked();
+        picker.update(cx, |picker, cx| {
+            let ix = picker
+                .delegate
+                .matches
+                .iter()
+                .position(|mat| mat.string == encoding_name)
+                .unwrap();
+            picker.set_selected_index(ix, false, cx);
+        });
+        cx.dispatch_action(menu::Confirm);
+        cx.run_until_parked();
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        cx.update(|cx| {
+            let state = AppState::test(cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            crate::init(cx);
+            state
+        })
+    }
+}
@@ -0,0 +1,124 @@
+use std::borrow::Cow;
+use std::sync::{Arc, OnceLock};
+
+use anyhow::{anyhow, Result};
+use futures::FutureExt;
+use gpui::{AppContext, Task};
+use language::{ContextProvider, Location};
+use task::{RevealStrategy, TaskTemplate, TaskTemplates, TaskVariables, VariableName};
+
+use crate::wasm_host::{wit, WasmExtension};
+
+/// The tasks an extension's Wasm module provides for one of the extension's languages.
+pub(crate) struct ExtensionLanguageTasks {
+    pub(crate) extension: WasmExtension,
+    pub(crate) templates: TaskTemplates,
+}
+
+impl ExtensionLanguageTasks {
+    pub(crate) async fn load(extension: WasmExtension, language_name: Arc<str>) -> Result<Self> {
+        let templates = extension
+            .call(|extension, store| {
+                async move {
+                    extension
+                        .call_tasks_for_language(store, &language_name)
+                        .await?
+                        .map_err(|e| anyhow!("{}", e))
+                }
+                .boxed()
+            })
+            .await?;
+
+        Ok(Self {
+            extension,
+            templates: TaskTemplates(templates.into_iter().map(task_template).collect()),
+        })
+    }
+}
+
+/// Provides the tasks of a language from an extension: the ones in the language's `tasks.json`,
+/// and the ones the extension's Wasm module provides once it's loaded, along with their task
+/// variables.
+pub struct ExtensionContextProvider {
+    pub(crate) language_name: Arc<str>,
+    pub(crate) templates: Option<TaskTemplates>,
+    pub(crate) extension_tasks: Arc<OnceLock<ExtensionLanguageTasks>>,
+}
+
+impl ContextProvider for ExtensionContextProvider {
+    fn build_context(
+        &self,
+        variables: &TaskVariables,
+        _location: &Location,
+        cx: &mut AppContext,
+    ) -> Task<Result<TaskVariables>> {
+        let Some(extension_tasks) = self.extension_tasks.get() else {
+            return Task::ready(Ok(TaskVariables::default()));
+        };
+        let extension = extension_tasks.extension.clone();
+        let language_name = self.language_name.clone();
+        let variables = variables
+            .clone()
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect::<Vec<_>>();
+
+        cx.background_executor().spawn(async move {
+            let variables = extension
+                .call(|extension, store| {
+                    async move {
+                        extension
+                            .call_build_task_variables(store, &language_name, &variables)
+                            .await?
+                            .map_err(|e| anyhow!("{}", e))
+                    }
+                    .boxed()
+                })
+                .await?;
+
+            Ok(variables
+                .into_iter()
+                .map(|(name, value)| (VariableName::Custom(Cow::Owned(name)), value))
+                .collect())
+        })
+    }
+
+    fn associated_tasks(
+        &self,
+        _: Option<Arc<dyn language::File>>,
+        _: &AppContext,
+    ) -> Option<TaskTemplates> {
+        let extension_templates = self
+            .extension_tasks
+            .get()
+            .map(|extension_tasks| &extension_tasks.templates);
+        if self.templates.is_none() && extension_templates.is_none() {
+            return None;
+        }
+
+        Some(TaskTemplates(
+            self.templates
+                .iter()
+                .chain(extension_templates)
+                .flat_map(|templates| templates.0.iter().cloned())
+                .collect(),
+        ))
+    }
+}
+
+pub(crate) fn task_template(template: wit::TaskTemplate) -> TaskTemplate {
+    TaskTemplate {
+        label: template.label,
+        command: template.command,
+        args: template.args,
+        env: template.env.into_iter().collect(),
+        cwd: template.cwd,
+        use_new_terminal: template.use_new_terminal,
+        allow_concurrent_runs: template.allow_concurrent_runs,
+        reveal: match template.reveal {
+            wit::RevealStrategy::Always => RevealStrategy::Always,
+            wit::RevealStrategy::Never => RevealStrategy::Never,
+        },
+        tags: template.tags,
+    }
+}
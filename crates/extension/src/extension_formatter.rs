@@ -0,0 +1,86 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::FutureExt;
+use language::{FormatterCommand, FormatterOutput, FormatterProvider, LspAdapterDelegate};
+use wasmtime_wasi::WasiView;
+
+use crate::wasm_host::{wit, WasmExtension, WasmHost};
+
+pub struct ExtensionFormatter {
+    pub(crate) extension: WasmExtension,
+    pub(crate) host: Arc<WasmHost>,
+    pub(crate) name: Arc<str>,
+    pub(crate) languages: Vec<Arc<str>>,
+}
+
+#[async_trait]
+impl FormatterProvider for ExtensionFormatter {
+    fn name(&self) -> Arc<str> {
+        self.name.clone()
+    }
+
+    fn languages(&self) -> &[Arc<str>] {
+        &self.languages
+    }
+
+    async fn format(
+        &self,
+        language_name: Arc<str>,
+        text: String,
+        buffer_abs_path: Option<PathBuf>,
+        delegate: Arc<dyn LspAdapterDelegate>,
+    ) -> Result<FormatterOutput> {
+        let output = self
+            .extension
+            .call({
+                let name = self.name.clone();
+                |extension, store| {
+                    async move {
+                        let resource = store.data_mut().table().push(delegate)?;
+                        let buffer_path = buffer_abs_path
+                            .as_ref()
+                            .map(|path| path.to_string_lossy().to_string());
+                        let output = extension
+                            .call_format_buffer(
+                                store,
+                                &name,
+                                &language_name,
+                                &text,
+                                buffer_path.as_deref(),
+                                resource,
+                            )
+                            .await?
+                            .map_err(|e| anyhow!("{}", e))?;
+
+                        anyhow::Ok(output)
+                    }
+                    .boxed()
+                }
+            })
+            .await?;
+
+        Ok(match output {
+            wit::FormatterOutput::Command(command) => FormatterOutput::Command(FormatterCommand {
+                path: self
+                    .host
+                    .path_from_extension(&self.extension.manifest.id, Path::new(&command.command)),
+                arguments: command.args,
+                env: command.env.into_iter().collect(),
+            }),
+            wit::FormatterOutput::Edits(edits) => FormatterOutput::Edits(
+                edits
+                    .into_iter()
+                    .map(|edit| {
+                        (
+                            edit.range.start as usize..edit.range.end as usize,
+                            edit.new_text,
+                        )
+                    })
+                    .collect(),
+            ),
+        })
+    }
+}
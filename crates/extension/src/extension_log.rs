@@ -0,0 +1,157 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use collections::BTreeMap;
+use futures::channel::mpsc::UnboundedSender;
+use gpui::{EventEmitter, ModelContext};
+use wasmtime_wasi as wasi;
+
+/// The maximum number of log entries that are kept for each extension.
+const MAX_STORED_LOG_ENTRIES: usize = 2000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExtensionLogKind {
+    /// A line the extension's Wasm module wrote to stdout.
+    Stdout,
+    /// A line the extension's Wasm module wrote to stderr, including the messages of panics.
+    Stderr,
+    /// An error returned by a function the extension called, or by a call into the extension.
+    Error,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExtensionLogEntry {
+    pub kind: ExtensionLogKind,
+    pub message: String,
+}
+
+pub(crate) type ExtensionLogSender = UnboundedSender<(Arc<str>, ExtensionLogEntry)>;
+
+pub enum ExtensionLogEvent {
+    NewEntry {
+        extension_id: Arc<str>,
+        entry: ExtensionLogEntry,
+    },
+}
+
+/// The output of extensions' Wasm modules, and the errors of the calls between extensions and
+/// Zed, which are shown in the extension log view.
+#[derive(Default)]
+pub struct ExtensionLogStore {
+    logs: BTreeMap<Arc<str>, VecDeque<ExtensionLogEntry>>,
+}
+
+impl EventEmitter<ExtensionLogEvent> for ExtensionLogStore {}
+
+impl ExtensionLogStore {
+    /// Returns the IDs of the extensions that have logged something.
+    pub fn extension_ids(&self) -> impl Iterator<Item = &Arc<str>> {
+        self.logs.keys()
+    }
+
+    pub fn entries(&self, extension_id: &str) -> impl Iterator<Item = &ExtensionLogEntry> {
+        self.logs.get(extension_id).into_iter().flatten()
+    }
+
+    pub(crate) fn add_entry(
+        &mut self,
+        extension_id: Arc<str>,
+        entry: ExtensionLogEntry,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let entries = self.logs.entry(extension_id.clone()).or_default();
+        while entries.len() >= MAX_STORED_LOG_ENTRIES {
+            entries.pop_front();
+        }
+        entries.push_back(entry.clone());
+        cx.emit(ExtensionLogEvent::NewEntry {
+            extension_id,
+            entry,
+        });
+        cx.notify();
+    }
+
+    pub fn clear(&mut self, extension_id: &str, cx: &mut ModelContext<Self>) {
+        if self.logs.remove(extension_id).is_some() {
+            cx.notify();
+        }
+    }
+}
+
+/// The stdout or stderr of an extension's Wasm module, which sends each line it's written to
+/// the extension's log.
+#[derive(Clone)]
+pub(crate) struct ExtensionLogOutput {
+    pub(crate) extension_id: Arc<str>,
+    pub(crate) kind: ExtensionLogKind,
+    pub(crate) log_tx: ExtensionLogSender,
+}
+
+impl wasi::StdoutStream for ExtensionLogOutput {
+    fn stream(&self) -> Box<dyn wasi::HostOutputStream> {
+        Box::new(ExtensionLogOutputStream {
+            output: self.clone(),
+            pending_line: Vec::new(),
+        })
+    }
+
+    fn isatty(&self) -> bool {
+        false
+    }
+}
+
+struct ExtensionLogOutputStream {
+    output: ExtensionLogOutput,
+    /// The bytes written after the last newline.
+    pending_line: Vec<u8>,
+}
+
+impl ExtensionLogOutputStream {
+    fn send_line(&self, line: &[u8]) {
+        let message = String::from_utf8_lossy(line).trim_end().to_string();
+        self.output
+            .log_tx
+            .unbounded_send((
+                self.output.extension_id.clone(),
+                ExtensionLogEntry {
+                    kind: self.output.kind,
+                    message,
+                },
+            ))
+            .ok();
+    }
+}
+
+#[async_trait::async_trait]
+impl wasi::Subscribe for ExtensionLogOutputStream {
+    async fn ready(&mut self) {}
+}
+
+#[async_trait::async_trait]
+impl wasi::HostOutputStream for ExtensionLogOutputStream {
+    fn write(&mut self, bytes: bytes::Bytes) -> wasi::StreamResult<()> {
+        self.pending_line.extend_from_slice(&bytes);
+        while let Some(newline_ix) = self.pending_line.iter().position(|byte| *byte == b'\n') {
+            let line = self.pending_line.drain(..=newline_ix).collect::<Vec<_>>();
+            self.send_line(&line);
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> wasi::StreamResult<()> {
+        Ok(())
+    }
+
+    fn check_write(&mut self) -> wasi::StreamResult<usize> {
+        // Writes are never blocked, since they're only buffered until the next newline.
+        Ok(1024 * 1024)
+    }
+}
+
+impl Drop for ExtensionLogOutputStream {
+    fn drop(&mut self) {
+        if !self.pending_line.is_empty() {
+            self.send_line(&self.pending_line);
+        }
+    }
+}
@@ -69,6 +69,8 @@ pub struct ExtensionManifest {
     #[serde(default)]
     pub themes: Vec<PathBuf>,
     #[serde(default)]
+    pub icon_themes: Vec<PathBuf>,
+    #[serde(default)]
     pub languages: Vec<PathBuf>,
     #[serde(default)]
     pub grammars: BTreeMap<Arc<str>, GrammarManifestEntry>,
@@ -79,7 +81,11 @@ pub struct ExtensionManifest {
     #[serde(default)]
     pub indexed_docs_providers: BTreeMap<Arc<str>, IndexedDocsProviderEntry>,
     #[serde(default)]
+    pub formatters: BTreeMap<Arc<str>, FormatterManifestEntry>,
+    #[serde(default)]
     pub snippets: Option<PathBuf>,
+    #[serde(default)]
+    pub capabilities: Vec<ExtensionCapability>,
 }
 
 #[derive(Clone, Default, PartialEq, Eq, Debug, Deserialize, Serialize)]
@@ -144,7 +150,62 @@ pub struct SlashCommandManifestEntry {
 #[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
 pub struct IndexedDocsProviderEntry {}
 
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct FormatterManifestEntry {
+    /// The languages the formatter supports. The formatter can be used for any language if this
+    /// is empty.
+    #[serde(default)]
+    pub languages: Vec<Arc<str>>,
+}
+
+/// A capability an extension must declare in its manifest in order to use the corresponding
+/// extension API.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+#[serde(tag = "kind")]
+pub enum ExtensionCapability {
+    /// Running the given command as a child process, with arguments matching the given patterns.
+    ///
+    /// A `*` pattern matches any single argument, and a trailing `**` pattern matches any number
+    /// of remaining arguments.
+    #[serde(rename = "process:exec")]
+    ProcessExec { command: String, args: Vec<String> },
+}
+
+impl ExtensionCapability {
+    fn allows_exec(&self, command: &str, args: &[String]) -> bool {
+        match self {
+            Self::ProcessExec {
+                command: allowed_command,
+                args: allowed_args,
+            } => {
+                if allowed_command != command {
+                    return false;
+                }
+
+                for (ix, allowed_arg) in allowed_args.iter().enumerate() {
+                    if allowed_arg == "**" && ix == allowed_args.len() - 1 {
+                        return true;
+                    }
+                    match args.get(ix) {
+                        Some(arg) if allowed_arg == "*" || allowed_arg == arg => {}
+                        _ => return false,
+                    }
+                }
+                args.len() == allowed_args.len()
+            }
+        }
+    }
+}
+
 impl ExtensionManifest {
+    /// Returns whether the extension declares a capability to run the given command with the
+    /// given arguments.
+    pub fn allows_exec(&self, command: &str, args: &[String]) -> bool {
+        self.capabilities
+            .iter()
+            .any(|capability| capability.allows_exec(command, args))
+    }
+
     pub async fn load(fs: Arc<dyn Fs>, extension_dir: &Path) -> Result<Self> {
         let extension_name = extension_dir
             .file_name()
@@ -194,6 +255,7 @@ fn manifest_from_old_manifest(
             themes.dedup();
             themes
         },
+        icon_themes: Vec::new(),
         languages: {
             let mut languages = manifest_json.languages.into_values().collect::<Vec<_>>();
             languages.sort();
@@ -208,6 +270,8 @@ fn manifest_from_old_manifest(
         language_servers: Default::default(),
         slash_commands: BTreeMap::default(),
         indexed_docs_providers: BTreeMap::default(),
+        formatters: BTreeMap::default(),
         snippets: None,
+        capabilities: Vec::new(),
     }
 }
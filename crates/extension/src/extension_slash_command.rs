@@ -122,6 +122,7 @@ impl SlashCommand for ExtensionSlashCommand {
                         label: section.label.into(),
                     })
                     .collect(),
+                images: Vec::new(),
                 run_commands_in_text: false,
             })
         })
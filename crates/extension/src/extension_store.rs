@@ -1,5 +1,8 @@
 pub mod extension_builder;
+mod extension_context_provider;
+mod extension_formatter;
 mod extension_indexed_docs_provider;
+mod extension_log;
 mod extension_lsp_adapter;
 mod extension_manifest;
 mod extension_settings;
@@ -9,6 +12,8 @@ mod wasm_host;
 #[cfg(test)]
 mod extension_store_test;
 
+use crate::extension_context_provider::{ExtensionContextProvider, ExtensionLanguageTasks};
+use crate::extension_formatter::ExtensionFormatter;
 use crate::extension_indexed_docs_provider::ExtensionIndexedDocsProvider;
 use crate::extension_manifest::SchemaVersion;
 use crate::extension_slash_command::ExtensionSlashCommand;
@@ -18,9 +23,10 @@ use assistant_slash_command::SlashCommandRegistry;
 use async_compression::futures::bufread::GzipDecoder;
 use async_tar::Archive;
 use client::{telemetry::Telemetry, Client, ExtensionMetadata, GetExtensionsResponse};
-use collections::{btree_map, BTreeMap, HashSet};
+use collections::{btree_map, BTreeMap, HashMap, HashSet};
+use db::kvp::KEY_VALUE_STORE;
 use extension_builder::{CompileExtensionOptions, ExtensionBuilder};
-use fs::{Fs, RemoveOptions};
+use fs::{Fs, RemoveOptions, Watcher};
 use futures::{
     channel::{
         mpsc::{unbounded, UnboundedSender},
@@ -39,7 +45,6 @@ use language::{
     LanguageConfig, LanguageMatcher, LanguageQueries, LanguageRegistry, QUERY_FILENAME_PREFIXES,
 };
 use node_runtime::NodeRuntime;
-use project::ContextProviderWithTasks;
 use release_channel::ReleaseChannel;
 use semantic_version::SemanticVersion;
 use serde::{Deserialize, Serialize};
@@ -50,19 +55,24 @@ use std::str::FromStr;
 use std::{
     cmp::Ordering,
     path::{self, Path, PathBuf},
-    sync::Arc,
+    sync::{Arc, OnceLock},
     time::{Duration, Instant},
 };
 use theme::{ThemeRegistry, ThemeSettings};
 use url::Url;
 use util::{maybe, ResultExt};
 use wasm_host::{
+    process_exec_approval_key,
     wit::{is_supported_wasm_api_version, wasm_api_version_range},
     WasmExtension, WasmHost,
 };
 
+pub use extension_log::{
+    ExtensionLogEntry, ExtensionLogEvent, ExtensionLogKind, ExtensionLogStore,
+};
 pub use extension_manifest::{
-    ExtensionLibraryKind, ExtensionManifest, GrammarManifestEntry, OldExtensionManifest,
+    ExtensionCapability, ExtensionLibraryKind, ExtensionManifest, GrammarManifestEntry,
+    OldExtensionManifest,
 };
 pub use extension_settings::ExtensionSettings;
 
@@ -120,6 +130,11 @@ pub struct ExtensionStore {
     modified_extensions: HashSet<Arc<str>>,
     wasm_host: Arc<WasmHost>,
     wasm_extensions: Vec<(Arc<ExtensionManifest>, WasmExtension)>,
+    log_store: Model<ExtensionLogStore>,
+    dev_extension_watchers: HashMap<Arc<str>, Task<()>>,
+    /// The dev extensions that changed while they were being rebuilt, and that are rebuilt again
+    /// once that finishes.
+    pending_dev_extension_rebuilds: HashSet<Arc<str>>,
     tasks: Vec<Task<()>>,
 }
 
@@ -148,6 +163,8 @@ impl Global for GlobalExtensionStore {}
 pub struct ExtensionIndex {
     pub extensions: BTreeMap<Arc<str>, ExtensionIndexEntry>,
     pub themes: BTreeMap<Arc<str>, ExtensionIndexThemeEntry>,
+    #[serde(default)]
+    pub icon_themes: BTreeMap<Arc<str>, ExtensionIndexIconThemeEntry>,
     pub languages: BTreeMap<Arc<str>, ExtensionIndexLanguageEntry>,
 }
 
@@ -163,6 +180,12 @@ pub struct ExtensionIndexThemeEntry {
     path: PathBuf,
 }
 
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Serialize)]
+pub struct ExtensionIndexIconThemeEntry {
+    extension: Arc<str>,
+    path: PathBuf,
+}
+
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize, Serialize)]
 pub struct ExtensionIndexLanguageEntry {
     extension: Arc<str>,
@@ -239,6 +262,8 @@ impl ExtensionStore {
         let index_path = extensions_dir.join("index.json");
 
         let (reload_tx, mut reload_rx) = unbounded();
+        let (log_tx, mut log_rx) = unbounded();
+        let log_store = cx.new_model(|_| ExtensionLogStore::default());
         let mut this = Self {
             extension_index: Default::default(),
             installed_dir,
@@ -253,9 +278,13 @@ impl ExtensionStore {
                 node_runtime,
                 language_registry.clone(),
                 work_dir,
+                log_tx,
                 cx,
             ),
             wasm_extensions: Vec::new(),
+            log_store: log_store.clone(),
+            dev_extension_watchers: HashMap::default(),
+            pending_dev_extension_rebuilds: HashSet::default(),
             fs,
             http_client,
             telemetry,
@@ -355,6 +384,16 @@ impl ExtensionStore {
             .map(drop)
         }));
 
+        this.tasks.push(cx.spawn(|_, mut cx| async move {
+            while let Some((extension_id, entry)) = log_rx.next().await {
+                log_store
+                    .update(&mut cx, |log_store, cx| {
+                        log_store.add_entry(extension_id, entry, cx)
+                    })
+                    .ok();
+            }
+        }));
+
         // Watch the installed extensions directory for changes. Whenever changes are
         // detected, rebuild the extension index, and load/unload any extensions that
         // have been added, removed, or modified.
@@ -406,6 +445,11 @@ impl ExtensionStore {
         self.installed_dir.clone()
     }
 
+    /// Returns the log of the output and errors of extensions' Wasm modules.
+    pub fn log_store(&self) -> &Model<ExtensionLogStore> {
+        &self.log_store
+    }
+
     pub fn outstanding_operations(&self) -> &BTreeMap<Arc<str>, ExtensionOperation> {
         &self.outstanding_operations
     }
@@ -432,6 +476,23 @@ impl ExtensionStore {
             .filter_map(|(name, theme)| theme.extension.as_ref().eq(extension_id).then_some(name))
     }
 
+    /// Returns the names of icon themes provided by extensions.
+    pub fn extension_icon_themes<'a>(
+        &'a self,
+        extension_id: &'a str,
+    ) -> impl Iterator<Item = &'a Arc<str>> {
+        self.extension_index
+            .icon_themes
+            .iter()
+            .filter_map(|(name, icon_theme)| {
+                icon_theme
+                    .extension
+                    .as_ref()
+                    .eq(extension_id)
+                    .then_some(name)
+            })
+    }
+
     pub fn fetch_extensions(
         &self,
         search: Option<&str>,
@@ -799,6 +860,12 @@ impl ExtensionStore {
 
             this.update(&mut cx, |this, cx| this.reload(None, cx))?
                 .await;
+
+            // Ask the user again if the extension is reinstalled and tries to run processes.
+            KEY_VALUE_STORE
+                .delete_kvp(process_exec_approval_key(&extension_id))
+                .await
+                .log_err();
             anyhow::Ok(())
         })
         .detach_and_log_err(cx)
@@ -888,7 +955,12 @@ impl ExtensionStore {
         let fs = self.fs.clone();
 
         match self.outstanding_operations.entry(extension_id.clone()) {
-            btree_map::Entry::Occupied(_) => return,
+            btree_map::Entry::Occupied(e) => {
+                if matches!(e.get(), ExtensionOperation::Upgrade) {
+                    self.pending_dev_extension_rebuilds.insert(extension_id);
+                }
+                return;
+            }
             btree_map::Entry::Vacant(e) => e.insert(ExtensionOperation::Upgrade),
         };
 
@@ -913,10 +985,18 @@ impl ExtensionStore {
             })?;
 
             if result.is_ok() {
-                this.update(&mut cx, |this, cx| this.reload(Some(extension_id), cx))?
-                    .await;
+                this.update(&mut cx, |this, cx| {
+                    this.reload(Some(extension_id.clone()), cx)
+                })?
+                .await;
             }
 
+            this.update(&mut cx, |this, cx| {
+                if this.pending_dev_extension_rebuilds.remove(&extension_id) {
+                    this.rebuild_dev_extension(extension_id, cx);
+                }
+            })?;
+
             result
         })
         .detach_and_log_err(cx)
@@ -1012,6 +1092,17 @@ impl ExtensionStore {
                 }
             })
             .collect::<Vec<_>>();
+        let icon_themes_to_remove = old_index
+            .icon_themes
+            .iter()
+            .filter_map(|(name, entry)| {
+                if extensions_to_unload.contains(&entry.extension) {
+                    Some(name.clone().into())
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
         let languages_to_remove = old_index
             .languages
             .iter()
@@ -1024,22 +1115,35 @@ impl ExtensionStore {
             })
             .collect::<Vec<_>>();
         let mut grammars_to_remove = Vec::new();
+        let mut lsp_adapters_to_replace = Vec::new();
         for extension_id in &extensions_to_unload {
             let Some(extension) = old_index.extensions.get(extension_id) else {
                 continue;
             };
             grammars_to_remove.extend(extension.manifest.grammars.keys().cloned());
+            let is_reloading = extensions_to_load.contains(extension_id);
             for (language_server_name, config) in extension.manifest.language_servers.iter() {
                 for language in config.languages() {
-                    self.language_registry
-                        .remove_lsp_adapter(&language, language_server_name);
+                    // The adapters of a reloaded extension are kept until the reloaded ones
+                    // replace them, at which point their language servers are restarted.
+                    if is_reloading {
+                        lsp_adapters_to_replace.push((language, language_server_name.clone()));
+                    } else {
+                        self.language_registry
+                            .remove_lsp_adapter(&language, language_server_name);
+                    }
                 }
             }
+            for formatter_name in extension.manifest.formatters.keys() {
+                self.language_registry.remove_formatter(formatter_name);
+            }
         }
 
         self.wasm_extensions
             .retain(|(extension, _)| !extensions_to_unload.contains(&extension.id));
         self.theme_registry.remove_user_themes(&themes_to_remove);
+        self.theme_registry
+            .remove_icon_themes(&icon_themes_to_remove);
         self.language_registry
             .remove_languages(&languages_to_remove, &grammars_to_remove);
 
@@ -1050,6 +1154,7 @@ impl ExtensionStore {
             .collect::<Vec<_>>();
         let mut grammars_to_add = Vec::new();
         let mut themes_to_add = Vec::new();
+        let mut icon_themes_to_add = Vec::new();
         let mut snippets_to_add = Vec::new();
         for extension_id in &extensions_to_load {
             let Some(extension) = new_index.extensions.get(extension_id) else {
@@ -1068,6 +1173,12 @@ impl ExtensionStore {
                 path.extend([Path::new(extension_id.as_ref()), theme_path.as_path()]);
                 path
             }));
+            icon_themes_to_add.extend(extension.manifest.icon_themes.iter().map(
+                |icon_theme_path| {
+                    let extension_dir = self.installed_dir.join(extension_id.as_ref());
+                    (extension_dir.join(icon_theme_path), extension_dir)
+                },
+            ));
             snippets_to_add.extend(extension.manifest.snippets.iter().map(|snippets_path| {
                 let mut path = self.installed_dir.clone();
                 path.extend([Path::new(extension_id.as_ref()), snippets_path.as_path()]);
@@ -1078,12 +1189,22 @@ impl ExtensionStore {
         self.language_registry
             .register_wasm_grammars(grammars_to_add);
 
+        // The tasks of the languages that are provided by Wasm modules, which are set once the
+        // extensions' Wasm modules are loaded.
+        let mut language_tasks_to_load = Vec::new();
         for (language_name, language) in languages_to_add {
             let mut language_path = self.installed_dir.clone();
             language_path.extend([
                 Path::new(language.extension.as_ref()),
                 language.path.as_path(),
             ]);
+            let extension_tasks = Arc::new(OnceLock::new());
+            language_tasks_to_load.push((
+                language.extension.clone(),
+                language_name.clone(),
+                extension_tasks.clone(),
+            ));
+            let language_name = language_name.clone();
             self.language_registry.register_language(
                 language_name.clone(),
                 language.grammar.clone(),
@@ -1092,14 +1213,16 @@ impl ExtensionStore {
                     let config = std::fs::read_to_string(language_path.join("config.toml"))?;
                     let config: LanguageConfig = ::toml::from_str(&config)?;
                     let queries = load_plugin_queries(&language_path);
-                    let tasks = std::fs::read_to_string(language_path.join("tasks.json"))
+                    let templates = std::fs::read_to_string(language_path.join("tasks.json"))
                         .ok()
-                        .and_then(|contents| {
-                            let definitions = serde_json_lenient::from_str(&contents).log_err()?;
-                            Some(Arc::new(ContextProviderWithTasks::new(definitions)) as Arc<_>)
-                        });
+                        .and_then(|contents| serde_json_lenient::from_str(&contents).log_err());
+                    let context_provider = ExtensionContextProvider {
+                        language_name: language_name.clone(),
+                        templates,
+                        extension_tasks: extension_tasks.clone(),
+                    };
 
-                    Ok((config, queries, tasks))
+                    Ok((config, queries, Some(Arc::new(context_provider) as Arc<_>)))
                 },
             );
         }
@@ -1115,6 +1238,7 @@ impl ExtensionStore {
             .collect::<Vec<_>>();
 
         self.extension_index = new_index;
+        self.watch_dev_extensions(cx);
         cx.notify();
         cx.emit(Event::ExtensionsUpdated);
 
@@ -1130,6 +1254,14 @@ impl ExtensionStore {
                                 .log_err();
                         }
 
+                        // The icons of an icon theme are relative to the extension's directory.
+                        for (icon_theme_path, extension_dir) in &icon_themes_to_add {
+                            theme_registry
+                                .load_icon_theme(icon_theme_path, extension_dir, fs.clone())
+                                .await
+                                .log_err();
+                        }
+
                         for snippets_path in &snippets_to_add {
                             if let Some(snippets_contents) = fs.load(snippets_path).await.log_err()
                             {
@@ -1174,7 +1306,27 @@ impl ExtensionStore {
                 })
                 .await;
 
+                let wasm_extension = wasm_extension.inspect_err(|error| {
+                    wasm_host.log_error(&extension.manifest.id, error);
+                });
                 if let Some(wasm_extension) = wasm_extension.log_err() {
+                    for (extension_id, language_name, extension_tasks) in &language_tasks_to_load {
+                        if extension_id != &extension.manifest.id {
+                            continue;
+                        }
+                        if let Some(language_tasks) = ExtensionLanguageTasks::load(
+                            wasm_extension.clone(),
+                            language_name.clone(),
+                        )
+                        .await
+                        .with_context(|| {
+                            format!("failed to load tasks for language {language_name}")
+                        })
+                        .log_err()
+                        {
+                            extension_tasks.set(language_tasks).ok();
+                        }
+                    }
                     wasm_extensions.push((extension.manifest.clone(), wasm_extension));
                 } else {
                     this.update(&mut cx, |_, cx| {
@@ -1187,6 +1339,11 @@ impl ExtensionStore {
             this.update(&mut cx, |this, cx| {
                 this.reload_complete_senders.clear();
 
+                for (language, language_server_name) in &lsp_adapters_to_replace {
+                    this.language_registry
+                        .remove_lsp_adapter(language, language_server_name);
+                }
+
                 for (manifest, wasm_extension) in &wasm_extensions {
                     for (language_server_id, language_server_config) in &manifest.language_servers {
                         for language in language_server_config.languages() {
@@ -1230,6 +1387,16 @@ impl ExtensionStore {
                             },
                         ));
                     }
+
+                    for (formatter_name, formatter) in &manifest.formatters {
+                        this.language_registry
+                            .register_formatter(Arc::new(ExtensionFormatter {
+                                extension: wasm_extension.clone(),
+                                host: this.wasm_host.clone(),
+                                name: formatter_name.clone(),
+                                languages: formatter.languages.clone(),
+                            }));
+                    }
                 }
 
                 this.wasm_extensions.extend(wasm_extensions);
@@ -1239,6 +1406,76 @@ impl ExtensionStore {
         })
     }
 
+    /// Watches the source directories of the dev extensions, rebuilding and reloading an
+    /// extension whenever its source files change.
+    fn watch_dev_extensions(&mut self, cx: &mut ModelContext<Self>) {
+        let dev_extension_ids = self
+            .extension_index
+            .extensions
+            .iter()
+            .filter(|(_, extension)| extension.dev)
+            .map(|(extension_id, _)| extension_id.clone())
+            .collect::<HashSet<_>>();
+        self.dev_extension_watchers
+            .retain(|extension_id, _| dev_extension_ids.contains(extension_id));
+
+        for extension_id in dev_extension_ids {
+            if self.dev_extension_watchers.contains_key(&extension_id) {
+                continue;
+            }
+
+            let fs = self.fs.clone();
+            let installed_path = self.installed_dir.join(extension_id.as_ref());
+            let watcher = cx.spawn({
+                let extension_id = extension_id.clone();
+                |this, mut cx| async move {
+                    let Some(source_path) = fs.canonicalize(&installed_path).await.log_err() else {
+                        return;
+                    };
+
+                    let (mut events, watcher) = fs.watch(&source_path, FS_WATCH_LATENCY).await;
+                    watch_dev_extension_source_dirs(
+                        fs.as_ref(),
+                        watcher.as_ref(),
+                        &source_path,
+                        source_path.clone(),
+                    )
+                    .await;
+                    while let Some(paths) = events.next().await {
+                        let changed_paths = paths
+                            .into_iter()
+                            .filter(|path| is_dev_extension_source_path(&source_path, path))
+                            .collect::<Vec<_>>();
+                        if changed_paths.is_empty() {
+                            continue;
+                        }
+
+                        for path in changed_paths {
+                            if fs.is_dir(&path).await {
+                                watch_dev_extension_source_dirs(
+                                    fs.as_ref(),
+                                    watcher.as_ref(),
+                                    &source_path,
+                                    path,
+                                )
+                                .await;
+                            }
+                        }
+
+                        log::info!("dev extension {extension_id} changed, rebuilding");
+                        let rebuilt = this.update(&mut cx, |this, cx| {
+                            this.rebuild_dev_extension(extension_id.clone(), cx)
+                        });
+                        if rebuilt.is_err() {
+                            break;
+                        }
+                    }
+                }
+            });
+            self.dev_extension_watchers.insert(extension_id, watcher);
+        }
+    }
+
     fn rebuild_extension_index(&self, cx: &mut ModelContext<Self>) -> Task<ExtensionIndex> {
         let fs = self.fs.clone();
         let work_dir = self.wasm_host.work_dir.clone();
@@ -1362,6 +1599,35 @@ impl ExtensionStore {
             }
         }
 
+        if let Ok(mut icon_theme_paths) = fs.read_dir(&extension_dir.join("icon_themes")).await {
+            while let Some(icon_theme_path) = icon_theme_paths.next().await {
+                let icon_theme_path = icon_theme_path?;
+                let Ok(relative_path) = icon_theme_path.strip_prefix(&extension_dir) else {
+                    continue;
+                };
+
+                let Some(icon_theme) = ThemeRegistry::read_icon_theme(&icon_theme_path, fs.clone())
+                    .await
+                    .log_err()
+                else {
+                    continue;
+                };
+
+                let relative_path = relative_path.to_path_buf();
+                if !extension_manifest.icon_themes.contains(&relative_path) {
+                    extension_manifest.icon_themes.push(relative_path.clone());
+                }
+
+                index.icon_themes.insert(
+                    icon_theme.name.into(),
+                    ExtensionIndexIconThemeEntry {
+                        extension: extension_id.clone(),
+                        path: relative_path,
+                    },
+                );
+            }
+        }
+
         let extension_wasm_path = extension_dir.join("extension.wasm");
         if fs.is_file(&extension_wasm_path).await {
             extension_manifest
@@ -1382,6 +1648,50 @@ impl ExtensionStore {
     }
 }
 
+/// Returns whether a change to the given path in a dev extension's directory should rebuild the
+/// extension, which excludes the files that building the extension writes.
+/// Watches the given directory of a dev extension and all of its source directories, since the
+/// watchers returned by [`Fs::watch`] don't watch subdirectories on every platform.
+async fn watch_dev_extension_source_dirs(
+    fs: &dyn Fs,
+    watcher: &dyn Watcher,
+    extension_path: &Path,
+    dir: PathBuf,
+) {
+    let mut dirs = vec![dir];
+    while let Some(dir) = dirs.pop() {
+        watcher.add(&dir).log_err();
+
+        let Ok(mut children) = fs.read_dir(&dir).await else {
+            continue;
+        };
+        while let Some(child) = children.next().await {
+            let Ok(child) = child else {
+                continue;
+            };
+            if is_dev_extension_source_path(extension_path, &child) && fs.is_dir(&child).await {
+                dirs.push(child);
+            }
+        }
+    }
+}
+
+fn is_dev_extension_source_path(extension_path: &Path, path: &Path) -> bool {
+    let Ok(relative_path) = path.strip_prefix(extension_path) else {
+        return false;
+    };
+    let Some(path::Component::Normal(first_component)) = relative_path.components().next() else {
+        return false;
+    };
+    let first_component = first_component.to_string_lossy();
+
+    !(first_component.starts_with('.')
+        || matches!(
+            first_component.as_ref(),
+            "target" | "grammars" | "extension.wasm" | "Cargo.lock"
+        ))
+}
+
 fn load_plugin_queries(root_path: &Path) -> LanguageQueries {
     let mut result = LanguageQueries::default();
     if let Some(entries) = std::fs::read_dir(root_path).log_err() {
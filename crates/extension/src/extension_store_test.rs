@@ -1,19 +1,31 @@
+use crate::extension_context_provider::{
+    task_template, ExtensionContextProvider, ExtensionLanguageTasks,
+};
 use crate::extension_manifest::SchemaVersion;
 use crate::extension_settings::ExtensionSettings;
+use crate::wasm_host::{
+    process_exec_approval_key, process_exec_capabilities_hash, wit, WasmExtension, WasmHost,
+};
 use crate::{
-    Event, ExtensionIndex, ExtensionIndexEntry, ExtensionIndexLanguageEntry,
-    ExtensionIndexThemeEntry, ExtensionManifest, ExtensionStore, GrammarManifestEntry,
+    is_dev_extension_source_path, watch_dev_extension_source_dirs, Event, ExtensionCapability,
+    ExtensionIndex, ExtensionIndexEntry, ExtensionIndexLanguageEntry, ExtensionIndexThemeEntry,
+    ExtensionLogEntry, ExtensionLogKind, ExtensionManifest, ExtensionStore, GrammarManifestEntry,
     RELOAD_DEBOUNCE_DURATION,
 };
+use anyhow::anyhow;
 use assistant_slash_command::SlashCommandRegistry;
 use async_compression::futures::bufread::GzipEncoder;
 use collections::BTreeMap;
-use fs::{FakeFs, Fs, RealFs};
+use db::kvp::KEY_VALUE_STORE;
+use fs::{FakeFs, Fs, RealFs, Watcher};
 use futures::{io::BufReader, AsyncReadExt, StreamExt};
-use gpui::{Context, SemanticVersion, TestAppContext};
+use gpui::{Context, SemanticVersion, TestAppContext, VisualTestContext};
 use http::{FakeHttpClient, Response};
 use indexed_docs::IndexedDocsRegistry;
-use language::{LanguageMatcher, LanguageRegistry, LanguageServerBinaryStatus, LanguageServerName};
+use language::{
+    ContextProvider as _, LanguageMatcher, LanguageRegistry, LanguageServerBinaryStatus,
+    LanguageServerName,
+};
 use node_runtime::FakeNodeRuntime;
 use parking_lot::Mutex;
 use project::{Project, DEFAULT_COMPLETION_CONTEXT};
@@ -23,10 +35,12 @@ use snippet_provider::SnippetRegistry;
 use std::{
     ffi::OsString,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{Arc, OnceLock},
 };
+use task::{RevealStrategy, TaskTemplate, TaskTemplates};
 use theme::ThemeRegistry;
 use util::test::temp_tree;
+use wasmtime_wasi::{HostOutputStream as _, StdoutStream as _};
 
 #[cfg(test)]
 #[ctor::ctor]
@@ -150,6 +164,7 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                         authors: Vec::new(),
                         repository: None,
                         themes: Default::default(),
+                        icon_themes: Vec::new(),
                         lib: Default::default(),
                         languages: vec!["languages/erb".into(), "languages/ruby".into()],
                         grammars: [
@@ -161,7 +176,9 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                         language_servers: BTreeMap::default(),
                         slash_commands: BTreeMap::default(),
                         indexed_docs_providers: BTreeMap::default(),
+                        formatters: BTreeMap::default(),
                         snippets: None,
+                        capabilities: Vec::new(),
                     }),
                     dev: false,
                 },
@@ -181,13 +198,16 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                             "themes/monokai-pro.json".into(),
                             "themes/monokai.json".into(),
                         ],
+                        icon_themes: Vec::new(),
                         lib: Default::default(),
                         languages: Default::default(),
                         grammars: BTreeMap::default(),
                         language_servers: BTreeMap::default(),
                         slash_commands: BTreeMap::default(),
                         indexed_docs_providers: BTreeMap::default(),
+                        formatters: BTreeMap::default(),
                         snippets: None,
+                        capabilities: Vec::new(),
                     }),
                     dev: false,
                 },
@@ -255,6 +275,7 @@ async fn test_extension_store(cx: &mut TestAppContext) {
         ]
         .into_iter()
         .collect(),
+        icon_themes: BTreeMap::default(),
     };
 
     let language_registry = Arc::new(LanguageRegistry::test(cx.executor()));
@@ -344,13 +365,16 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                 authors: vec![],
                 repository: None,
                 themes: vec!["themes/gruvbox.json".into()],
+                icon_themes: Vec::new(),
                 lib: Default::default(),
                 languages: Default::default(),
                 grammars: BTreeMap::default(),
                 language_servers: BTreeMap::default(),
                 slash_commands: BTreeMap::default(),
                 indexed_docs_providers: BTreeMap::default(),
+                formatters: BTreeMap::default(),
                 snippets: None,
+                capabilities: Vec::new(),
             }),
             dev: false,
         },
@@ -452,6 +476,432 @@ async fn test_extension_store(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_extension_process_exec_capabilities(cx: &mut TestAppContext) {
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/the-extension-dir/zed-rust-tools",
+        json!({
+            "extension.toml": r#"
+                id = "zed-rust-tools"
+                name = "Zed Rust Tools"
+                version = "1.0.0"
+                schema_version = 1
+
+                [[capabilities]]
+                kind = "process:exec"
+                command = "cargo"
+                args = ["metadata", "**"]
+
+                [[capabilities]]
+                kind = "process:exec"
+                command = "go"
+                args = ["env", "*"]
+            "#,
+        }),
+    )
+    .await;
+
+    let manifest =
+        ExtensionManifest::load(fs.clone(), Path::new("/the-extension-dir/zed-rust-tools"))
+            .await
+            .unwrap();
+    assert_eq!(
+        manifest.capabilities,
+        [
+            ExtensionCapability::ProcessExec {
+                command: "cargo".into(),
+                args: vec!["metadata".into(), "**".into()],
+            },
+            ExtensionCapability::ProcessExec {
+                command: "go".into(),
+                args: vec!["env".into(), "*".into()],
+            },
+        ]
+    );
+
+    let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+    assert!(manifest.allows_exec("cargo", &args(&["metadata"])));
+    assert!(manifest.allows_exec("cargo", &args(&["metadata", "--format-version", "1"])));
+    assert!(!manifest.allows_exec("cargo", &args(&["build"])));
+    assert!(manifest.allows_exec("go", &args(&["env", "GOPATH"])));
+    assert!(!manifest.allows_exec("go", &args(&["env"])));
+    assert!(!manifest.allows_exec("go", &args(&["env", "GOPATH", "GOROOT"])));
+    assert!(!manifest.allows_exec("rm", &args(&["-rf", "/"])));
+}
+
+#[gpui::test]
+async fn test_extension_process_exec_approval(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.executor().allow_parking();
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/the-extension-dir/installed/zed-go-tools",
+        json!({
+            "extension.toml": r#"
+                id = "zed-go-tools"
+                name = "Zed Go Tools"
+                version = "1.0.0"
+                schema_version = 1
+
+                [[capabilities]]
+                kind = "process:exec"
+                command = "go"
+                args = ["env", "*"]
+            "#,
+        }),
+    )
+    .await;
+    let manifest = ExtensionManifest::load(
+        fs.clone(),
+        Path::new("/the-extension-dir/installed/zed-go-tools"),
+    )
+    .await
+    .unwrap();
+    let wasm_host = test_wasm_host(fs, cx);
+    let cx = cx.add_empty_window();
+    cx.update(|cx| cx.activate_window());
+
+    let ensure_approved = |manifest: &ExtensionManifest, cx: &VisualTestContext| {
+        let wasm_host = wasm_host.clone();
+        let manifest = manifest.clone();
+        cx.executor()
+            .spawn(async move { wasm_host.ensure_process_exec_approved(&manifest).await })
+    };
+    let stored_approval = |manifest: &ExtensionManifest| {
+        KEY_VALUE_STORE
+            .read_kvp(&process_exec_approval_key(&manifest.id))
+            .unwrap()
+    };
+
+    // When the user denies the extension to run processes, the command fails, and the user
+    // isn't asked again.
+    let approval = ensure_approved(&manifest, cx);
+    cx.run_until_parked();
+    assert!(cx.has_pending_prompt());
+    cx.simulate_prompt_answer(1);
+    assert!(approval.await.is_err());
+    assert_eq!(
+        stored_approval(&manifest),
+        Some(format!(
+            "false:{}",
+            process_exec_capabilities_hash(&manifest).unwrap()
+        ))
+    );
+
+    let approval = ensure_approved(&manifest, cx);
+    cx.run_until_parked();
+    assert!(!cx.has_pending_prompt());
+    assert!(approval.await.is_err());
+
+    // A stored approval of the extension's capabilities is used without asking the user.
+    KEY_VALUE_STORE
+        .write_kvp(
+            process_exec_approval_key(&manifest.id),
+            format!(
+                "true:{}",
+                process_exec_capabilities_hash(&manifest).unwrap()
+            ),
+        )
+        .await
+        .unwrap();
+    let approval = ensure_approved(&manifest, cx);
+    cx.run_until_parked();
+    assert!(!cx.has_pending_prompt());
+    approval.await.unwrap();
+
+    // Once an update of the extension changes its capabilities, the user is asked again, only
+    // once for concurrent commands.
+    let mut updated_manifest = manifest.clone();
+    updated_manifest
+        .capabilities
+        .push(ExtensionCapability::ProcessExec {
+            command: "go".into(),
+            args: vec!["run".into(), "**".into()],
+        });
+    let first_approval = ensure_approved(&updated_manifest, cx);
+    let second_approval = ensure_approved(&updated_manifest, cx);
+    cx.run_until_parked();
+    cx.simulate_prompt_answer(0);
+    first_approval.await.unwrap();
+    cx.run_until_parked();
+    assert!(!cx.has_pending_prompt());
+    second_approval.await.unwrap();
+    assert_eq!(
+        stored_approval(&updated_manifest),
+        Some(format!(
+            "true:{}",
+            process_exec_capabilities_hash(&updated_manifest).unwrap()
+        ))
+    );
+}
+
+#[gpui::test]
+async fn test_extension_context_provider_tasks(cx: &mut TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/the-extension-dir/installed/zed-gleam",
+        json!({
+            "extension.toml": r#"
+                id = "zed-gleam"
+                name = "Zed Gleam"
+                version = "1.0.0"
+                schema_version = 1
+            "#,
+        }),
+    )
+    .await;
+    let manifest = ExtensionManifest::load(
+        fs.clone(),
+        Path::new("/the-extension-dir/installed/zed-gleam"),
+    )
+    .await
+    .unwrap();
+
+    let wasm_host = test_wasm_host(fs, cx);
+
+    let tasks_json_template = TaskTemplate {
+        label: "gleam run".into(),
+        command: "gleam".into(),
+        args: vec!["run".into()],
+        ..Default::default()
+    };
+    let extension_tasks = Arc::new(OnceLock::new());
+    let provider = ExtensionContextProvider {
+        language_name: "Gleam".into(),
+        templates: Some(TaskTemplates(vec![tasks_json_template.clone()])),
+        extension_tasks: extension_tasks.clone(),
+    };
+    let provider_without_tasks_json = ExtensionContextProvider {
+        language_name: "Gleam".into(),
+        templates: None,
+        extension_tasks: extension_tasks.clone(),
+    };
+
+    // Before the extension's Wasm module is loaded, only the tasks in `tasks.json` are provided.
+    cx.update(|cx| {
+        assert_eq!(
+            provider.associated_tasks(None, cx),
+            Some(TaskTemplates(vec![tasks_json_template.clone()]))
+        );
+        assert_eq!(provider_without_tasks_json.associated_tasks(None, cx), None);
+    });
+
+    let extension_template = task_template(wit::TaskTemplate {
+        label: "gleam test".into(),
+        command: "gleam".into(),
+        args: vec!["test".into()],
+        env: vec![("GLEAM_LOG".into(), "debug".into())],
+        cwd: Some("test".into()),
+        use_new_terminal: true,
+        allow_concurrent_runs: false,
+        reveal: wit::RevealStrategy::Never,
+        tags: vec!["gleam-test".into()],
+    });
+    assert_eq!(
+        extension_template,
+        TaskTemplate {
+            label: "gleam test".into(),
+            command: "gleam".into(),
+            args: vec!["test".into()],
+            env: [("GLEAM_LOG".to_string(), "debug".to_string())]
+                .into_iter()
+                .collect(),
+            cwd: Some("test".into()),
+            use_new_terminal: true,
+            allow_concurrent_runs: false,
+            reveal: RevealStrategy::Never,
+            tags: vec!["gleam-test".into()],
+        }
+    );
+    extension_tasks
+        .set(ExtensionLanguageTasks {
+            extension: WasmExtension::fake(wasm_host, Arc::new(manifest)),
+            templates: TaskTemplates(vec![extension_template.clone()]),
+        })
+        .ok()
+        .unwrap();
+
+    // Once it's loaded, the extension's tasks follow the ones in `tasks.json`.
+    cx.update(|cx| {
+        assert_eq!(
+            provider.associated_tasks(None, cx),
+            Some(TaskTemplates(vec![
+                tasks_json_template.clone(),
+                extension_template.clone()
+            ]))
+        );
+        assert_eq!(
+            provider_without_tasks_json.associated_tasks(None, cx),
+            Some(TaskTemplates(vec![extension_template.clone()]))
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_extension_log_store(cx: &mut TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/the-extension-dir", json!({ "installed": {} }))
+        .await;
+
+    let store = cx.new_model(|cx| {
+        ExtensionStore::new(
+            PathBuf::from("/the-extension-dir"),
+            None,
+            fs.clone(),
+            FakeHttpClient::with_200_response(),
+            None,
+            FakeNodeRuntime::new(),
+            Arc::new(LanguageRegistry::test(cx.background_executor().clone())),
+            Arc::new(ThemeRegistry::new(Box::new(()))),
+            SlashCommandRegistry::new(),
+            Arc::new(IndexedDocsRegistry::new(cx.background_executor().clone())),
+            Arc::new(SnippetRegistry::new()),
+            cx,
+        )
+    });
+    cx.executor().run_until_parked();
+
+    let (wasm_host, log_store) = store.read_with(cx, |store, _| {
+        (store.wasm_host.clone(), store.log_store().clone())
+    });
+    let extension_id: Arc<str> = "zed-gleam".into();
+
+    // The lines the extension's Wasm module writes are logged once they're complete, and the
+    // last one once the output is closed.
+    let mut stdout = wasm_host
+        .log_output(&extension_id, ExtensionLogKind::Stdout)
+        .stream();
+    stdout.write("fetching gleam...\ndo".into()).unwrap();
+    stdout.write("ne\nusing gleam v1.2.3".into()).unwrap();
+    drop(stdout);
+    let mut stderr = wasm_host
+        .log_output(&extension_id, ExtensionLogKind::Stderr)
+        .stream();
+    stderr.write("thread 'main' panicked\n".into()).unwrap();
+    drop(stderr);
+
+    // So are the errors of the calls between the extension and Zed.
+    wasm_host.log_error(
+        &extension_id,
+        &anyhow!("failed to fetch the latest release"),
+    );
+    cx.executor().run_until_parked();
+
+    let entry = |kind, message: &str| ExtensionLogEntry {
+        kind,
+        message: message.into(),
+    };
+    log_store.read_with(cx, |log_store, _| {
+        assert_eq!(
+            log_store.extension_ids().collect::<Vec<_>>(),
+            [&extension_id]
+        );
+        assert_eq!(
+            log_store
+                .entries(&extension_id)
+                .cloned()
+                .collect::<Vec<_>>(),
+            [
+                entry(ExtensionLogKind::Stdout, "fetching gleam..."),
+                entry(ExtensionLogKind::Stdout, "done"),
+                entry(ExtensionLogKind::Stdout, "using gleam v1.2.3"),
+                entry(ExtensionLogKind::Stderr, "thread 'main' panicked"),
+                entry(
+                    ExtensionLogKind::Error,
+                    "failed to fetch the latest release"
+                ),
+            ]
+        );
+    });
+}
+
+#[test]
+fn test_is_dev_extension_source_path() {
+    let extension_path = Path::new("/projects/zed-gleam");
+    let is_source_path =
+        |path: &str| is_dev_extension_source_path(extension_path, &extension_path.join(path));
+
+    assert!(is_source_path("extension.toml"));
+    assert!(is_source_path("Cargo.toml"));
+    assert!(is_source_path("src/gleam.rs"));
+    assert!(is_source_path("languages/gleam/highlights.scm"));
+
+    assert!(!is_source_path("extension.wasm"));
+    assert!(!is_source_path("Cargo.lock"));
+    assert!(!is_source_path("target/wasm32-wasi/release/zed_gleam.wasm"));
+    assert!(!is_source_path("grammars/gleam/src/parser.c"));
+    assert!(!is_source_path(".git/index"));
+    assert!(!is_source_path(".DS_Store"));
+    assert!(!is_dev_extension_source_path(
+        extension_path,
+        Path::new("/projects/other-extension/src/lib.rs")
+    ));
+}
+
+#[gpui::test]
+async fn test_watch_dev_extension_source_dirs(cx: &mut TestAppContext) {
+    #[derive(Default)]
+    struct RecordingWatcher(Mutex<Vec<PathBuf>>);
+
+    impl Watcher for RecordingWatcher {
+        fn add(&self, path: &Path) -> anyhow::Result<()> {
+            self.0.lock().push(path.to_path_buf());
+            Ok(())
+        }
+
+        fn remove(&self, _: &Path) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/projects/zed-gleam",
+        json!({
+            "extension.toml": "",
+            "src": {
+                "gleam.rs": "",
+                "language_server": { "mod.rs": "" },
+            },
+            "languages": { "gleam": { "highlights.scm": "" } },
+            "grammars": { "gleam": { "src": {} } },
+            "target": { "release": {} },
+            ".git": { "objects": {} },
+        }),
+    )
+    .await;
+
+    let extension_path = Path::new("/projects/zed-gleam");
+    let watcher = RecordingWatcher::default();
+    watch_dev_extension_source_dirs(
+        fs.as_ref(),
+        &watcher,
+        extension_path,
+        extension_path.to_path_buf(),
+    )
+    .await;
+
+    let mut watched_dirs = watcher.0.lock().clone();
+    watched_dirs.sort();
+    assert_eq!(
+        watched_dirs,
+        [
+            "/projects/zed-gleam",
+            "/projects/zed-gleam/languages",
+            "/projects/zed-gleam/languages/gleam",
+            "/projects/zed-gleam/src",
+            "/projects/zed-gleam/src/language_server",
+        ]
+        .map(PathBuf::from)
+    );
+}
+
 #[gpui::test]
 async fn test_extension_store_with_test_extension(cx: &mut TestAppContext) {
     init_test(cx);
@@ -723,17 +1173,12 @@ async fn test_extension_store_with_test_extension(cx: &mut TestAppContext) {
     );
     assert_eq!(language_server_version.lock().http_request_count, 0);
 
-    // Reload the extension, clearing its cache.
-    // Start a new instance of the language server.
+    // Reload the extension, clearing its cache. This restarts the language server
+    // with the reloaded extension.
     extension_store
         .update(cx, |store, cx| store.reload(Some("gleam".into()), cx))
         .await;
 
-    cx.executor().run_until_parked();
-    project.update(cx, |project, cx| {
-        project.restart_language_servers_for_buffers([buffer.clone()], cx)
-    });
-
     // The extension re-fetches the latest version of the language server.
     let fake_server = fake_servers.next().await.unwrap();
     let new_expected_server_path =
@@ -761,3 +1206,19 @@ fn init_test(cx: &mut TestAppContext) {
         language::init(cx);
     });
 }
+
+fn test_wasm_host(fs: Arc<dyn Fs>, cx: &mut TestAppContext) -> Arc<WasmHost> {
+    let language_registry = Arc::new(LanguageRegistry::test(cx.executor()));
+    let (log_tx, _) = futures::channel::mpsc::unbounded();
+    cx.update(|cx| {
+        WasmHost::new(
+            fs,
+            FakeHttpClient::with_200_response(),
+            FakeNodeRuntime::new(),
+            language_registry,
+            PathBuf::from("/the-extension-dir/work"),
+            log_tx,
+            cx,
+        )
+    })
+}
@@ -1,7 +1,11 @@
 pub(crate) mod wit;
 
-use crate::ExtensionManifest;
+use crate::extension_log::{
+    ExtensionLogEntry, ExtensionLogKind, ExtensionLogOutput, ExtensionLogSender,
+};
+use crate::{ExtensionCapability, ExtensionManifest};
 use anyhow::{anyhow, bail, Context as _, Result};
+use db::kvp::KEY_VALUE_STORE;
 use fs::{normalize_path, Fs};
 use futures::future::LocalBoxFuture;
 use futures::{
@@ -12,12 +16,13 @@ use futures::{
     future::BoxFuture,
     Future, FutureExt, StreamExt as _,
 };
-use gpui::{AppContext, AsyncAppContext, BackgroundExecutor, Task};
+use gpui::{AppContext, AsyncAppContext, BackgroundExecutor, PromptLevel, Task};
 use http::HttpClient;
 use language::LanguageRegistry;
 use node_runtime::NodeRuntime;
 use release_channel::ReleaseChannel;
 use semantic_version::SemanticVersion;
+use sha2::{Digest, Sha256};
 use std::{
     path::{Path, PathBuf},
     sync::{Arc, OnceLock},
@@ -37,6 +42,10 @@ pub(crate) struct WasmHost {
     pub(crate) language_registry: Arc<LanguageRegistry>,
     fs: Arc<dyn Fs>,
     pub(crate) work_dir: PathBuf,
+    log_tx: ExtensionLogSender,
+    /// Held while checking whether an extension may run processes, so that only one approval
+    /// prompt is shown at a time.
+    process_exec_approval_lock: futures::lock::Mutex<()>,
     _main_thread_message_task: Task<()>,
     main_thread_message_tx: mpsc::UnboundedSender<MainThreadCall>,
 }
@@ -44,6 +53,7 @@ pub(crate) struct WasmHost {
 #[derive(Clone)]
 pub struct WasmExtension {
     tx: UnboundedSender<ExtensionCall>,
+    host: Arc<WasmHost>,
     pub(crate) manifest: Arc<ExtensionManifest>,
     #[allow(unused)]
     pub zed_api_version: SemanticVersion,
@@ -83,6 +93,7 @@ impl WasmHost {
         node_runtime: Arc<dyn NodeRuntime>,
         language_registry: Arc<LanguageRegistry>,
         work_dir: PathBuf,
+        log_tx: ExtensionLogSender,
         cx: &mut AppContext,
     ) -> Arc<Self> {
         let (tx, mut rx) = mpsc::unbounded::<MainThreadCall>();
@@ -98,6 +109,8 @@ impl WasmHost {
             http_client,
             node_runtime,
             language_registry,
+            log_tx,
+            process_exec_approval_lock: Default::default(),
             release_channel: ReleaseChannel::global(cx),
             _main_thread_message_task: task,
             main_thread_message_tx: tx,
@@ -153,6 +166,7 @@ impl WasmHost {
             Ok(WasmExtension {
                 manifest,
                 tx,
+                host: this,
                 zed_api_version,
             })
         })
@@ -178,7 +192,9 @@ impl WasmHost {
         let dir_perms = wasi::DirPerms::all();
 
         Ok(wasi::WasiCtxBuilder::new()
-            .inherit_stdio()
+            .inherit_stdin()
+            .stdout(self.log_output(&manifest.id, ExtensionLogKind::Stdout))
+            .stderr(self.log_output(&manifest.id, ExtensionLogKind::Stderr))
             .preopened_dir(current_dir_preopen, dir_perms, perms, ".")
             .preopened_dir(work_dir_preopen, dir_perms, perms, &extension_work_dir)
             .env("PWD", &extension_work_dir)
@@ -186,6 +202,124 @@ impl WasmHost {
             .build())
     }
 
+    fn on_main_thread<T, Fn>(&self, f: Fn) -> impl 'static + Future<Output = T>
+    where
+        T: 'static + Send,
+        Fn: 'static + Send + for<'a> FnOnce(&'a mut AsyncAppContext) -> LocalBoxFuture<'a, T>,
+    {
+        let (return_tx, return_rx) = oneshot::channel();
+        self.main_thread_message_tx
+            .clone()
+            .unbounded_send(Box::new(move |cx| {
+                async {
+                    let result = f(cx).await;
+                    return_tx.send(result).ok();
+                }
+                .boxed_local()
+            }))
+            .expect("main thread message channel should not be closed yet");
+        async move { return_rx.await.expect("main thread message channel") }
+    }
+
+    /// Ensures the user has allowed the extension to run processes, asking them the first time
+    /// the extension tries to, and again once the processes it declares have changed.
+    pub(crate) async fn ensure_process_exec_approved(
+        &self,
+        manifest: &ExtensionManifest,
+    ) -> Result<()> {
+        // Concurrent calls wait for the prompt that's already shown, and then use its answer.
+        let _approval_guard = self.process_exec_approval_lock.lock().await;
+
+        let key = process_exec_approval_key(&manifest.id);
+        let capabilities_hash = process_exec_capabilities_hash(manifest)?;
+        let stored_approval = KEY_VALUE_STORE.read_kvp(&key)?.and_then(|value| {
+            let (approved, hash) = value.split_once(':')?;
+            (hash == capabilities_hash).then(|| approved == "true")
+        });
+        let approved = match stored_approval {
+            Some(approved) => approved,
+            None => {
+                let approved = self.prompt_for_process_exec_approval(manifest).await?;
+                KEY_VALUE_STORE
+                    .write_kvp(key, format!("{approved}:{capabilities_hash}"))
+                    .await?;
+                approved
+            }
+        };
+
+        if approved {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "the user has not allowed extension {} to run processes",
+                manifest.id
+            ))
+        }
+    }
+
+    async fn prompt_for_process_exec_approval(&self, manifest: &ExtensionManifest) -> Result<bool> {
+        let message = format!("Allow the {} extension to run processes?", manifest.name);
+        let detail = manifest
+            .capabilities
+            .iter()
+            .map(|capability| match capability {
+                ExtensionCapability::ProcessExec { command, args } => {
+                    format!("{command} {}", args.join(" "))
+                        .trim_end()
+                        .to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let answer = self
+            .on_main_thread(move |cx| {
+                async move {
+                    let window = cx
+                        .update(|cx| cx.active_window())?
+                        .context("no window to ask for approval in")?;
+                    window.update(cx, |_, cx| {
+                        cx.prompt(
+                            PromptLevel::Warning,
+                            &message,
+                            Some(&format!("It can run:\n{detail}")),
+                            &["Allow", "Deny"],
+                        )
+                    })
+                }
+                .boxed_local()
+            })
+            .await?;
+
+        Ok(answer.await? == 0)
+    }
+
+    /// Returns the stdout or stderr of the extension with the given ID's Wasm module, which
+    /// writes to the extension's log.
+    pub(crate) fn log_output(
+        &self,
+        extension_id: &Arc<str>,
+        kind: ExtensionLogKind,
+    ) -> ExtensionLogOutput {
+        ExtensionLogOutput {
+            extension_id: extension_id.clone(),
+            kind,
+            log_tx: self.log_tx.clone(),
+        }
+    }
+
+    /// Adds an error to the log of the extension with the given ID.
+    pub fn log_error(&self, extension_id: &Arc<str>, error: &anyhow::Error) {
+        self.log_tx
+            .unbounded_send((
+                extension_id.clone(),
+                ExtensionLogEntry {
+                    kind: ExtensionLogKind::Error,
+                    message: format!("{error:#}"),
+                },
+            ))
+            .ok();
+    }
+
     pub fn path_from_extension(&self, id: &Arc<str>, path: &Path) -> PathBuf {
         let extension_work_dir = self.work_dir.join(id.as_ref());
         normalize_path(&extension_work_dir.join(path))
@@ -246,12 +380,14 @@ fn parse_wasm_extension_version_custom_section(data: &[u8]) -> Option<SemanticVe
 }
 
 impl WasmExtension {
-    pub async fn call<T, Fn>(&self, f: Fn) -> T
+    /// Calls into the extension, adding the error the call fails with, if any, to the
+    /// extension's log.
+    pub async fn call<T, Fn>(&self, f: Fn) -> Result<T>
     where
         T: 'static + Send,
         Fn: 'static
             + Send
-            + for<'a> FnOnce(&'a mut Extension, &'a mut Store<WasmState>) -> BoxFuture<'a, T>,
+            + for<'a> FnOnce(&'a mut Extension, &'a mut Store<WasmState>) -> BoxFuture<'a, Result<T>>,
     {
         let (return_tx, return_rx) = oneshot::channel();
         self.tx
@@ -264,7 +400,25 @@ impl WasmExtension {
                 .boxed()
             }))
             .expect("wasm extension channel should not be closed yet");
-        return_rx.await.expect("wasm extension channel")
+        let result = return_rx.await.expect("wasm extension channel");
+        if let Err(error) = &result {
+            self.host.log_error(&self.manifest.id, error);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+impl WasmExtension {
+    /// Returns an extension that has no Wasm module, for testing what doesn't call into it.
+    pub(crate) fn fake(host: Arc<WasmHost>, manifest: Arc<ExtensionManifest>) -> Self {
+        let (tx, _) = mpsc::unbounded();
+        Self {
+            tx,
+            host,
+            manifest,
+            zed_api_version: SemanticVersion::default(),
+        }
     }
 }
 
@@ -274,24 +428,29 @@ impl WasmState {
         T: 'static + Send,
         Fn: 'static + Send + for<'a> FnOnce(&'a mut AsyncAppContext) -> LocalBoxFuture<'a, T>,
     {
-        let (return_tx, return_rx) = oneshot::channel();
-        self.host
-            .main_thread_message_tx
-            .clone()
-            .unbounded_send(Box::new(move |cx| {
-                async {
-                    let result = f(cx).await;
-                    return_tx.send(result).ok();
-                }
-                .boxed_local()
-            }))
-            .expect("main thread message channel should not be closed yet");
-        async move { return_rx.await.expect("main thread message channel") }
+        self.host.on_main_thread(f)
     }
 
     fn work_dir(&self) -> PathBuf {
         self.host.work_dir.join(self.manifest.id.as_ref())
     }
+
+    fn log_error(&self, error: &anyhow::Error) {
+        self.host.log_error(&self.manifest.id, error);
+    }
+}
+
+/// Returns the key under which the user's decision on whether the given extension may run
+/// processes is stored.
+pub(crate) fn process_exec_approval_key(extension_id: &str) -> String {
+    format!("extension-process-exec-approval-{extension_id}")
+}
+
+/// Returns a hash of the processes the extension declares it runs, which is stored along with
+/// the user's decision, so that an update of the extension that changes them is approved again.
+pub(crate) fn process_exec_capabilities_hash(manifest: &ExtensionManifest) -> Result<String> {
+    let capabilities = serde_json::to_vec(&manifest.capabilities)?;
+    Ok(format!("{:x}", Sha256::digest(capabilities)))
 }
 
 impl wasi::WasiView for WasmState {
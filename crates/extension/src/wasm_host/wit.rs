@@ -21,7 +21,8 @@ pub use latest::CodeLabelSpanLiteral;
 pub use latest::{
     zed::extension::lsp::{Completion, CompletionKind, InsertTextFormat, Symbol, SymbolKind},
     zed::extension::slash_command::{SlashCommandArgumentCompletion, SlashCommandOutput},
-    CodeLabel, CodeLabelSpan, Command, Range, SlashCommand,
+    zed::extension::tasks::{RevealStrategy, TaskTemplate},
+    CodeLabel, CodeLabelSpan, Command, FormatterOutput, Range, SlashCommand,
 };
 pub use since_v0_0_4::LanguageServerConfig;
 
@@ -307,14 +308,72 @@ impl Extension {
             }
         }
     }
+
+    pub async fn call_format_buffer(
+        &self,
+        store: &mut Store<WasmState>,
+        formatter_name: &str,
+        language_name: &str,
+        text: &str,
+        buffer_path: Option<&str>,
+        resource: Resource<Arc<dyn LspAdapterDelegate>>,
+    ) -> Result<Result<FormatterOutput, String>> {
+        match self {
+            Extension::V007(ext) => {
+                ext.call_format_buffer(
+                    store,
+                    formatter_name,
+                    language_name,
+                    text,
+                    buffer_path,
+                    resource,
+                )
+                .await
+            }
+            Extension::V001(_) | Extension::V004(_) | Extension::V006(_) => {
+                Err(anyhow!("`format_buffer` not available prior to v0.0.7"))
+            }
+        }
+    }
+
+    pub async fn call_tasks_for_language(
+        &self,
+        store: &mut Store<WasmState>,
+        language_name: &str,
+    ) -> Result<Result<Vec<TaskTemplate>, String>> {
+        match self {
+            Extension::V007(ext) => ext.call_tasks_for_language(store, language_name).await,
+            Extension::V001(_) | Extension::V004(_) | Extension::V006(_) => Ok(Ok(Vec::new())),
+        }
+    }
+
+    pub async fn call_build_task_variables(
+        &self,
+        store: &mut Store<WasmState>,
+        language_name: &str,
+        variables: &[(String, String)],
+    ) -> Result<Result<Vec<(String, String)>, String>> {
+        match self {
+            Extension::V007(ext) => {
+                ext.call_build_task_variables(store, language_name, variables)
+                    .await
+            }
+            Extension::V001(_) | Extension::V004(_) | Extension::V006(_) => Ok(Ok(Vec::new())),
+        }
+    }
 }
 
 trait ToWasmtimeResult<T> {
-    fn to_wasmtime_result(self) -> wasmtime::Result<Result<T, String>>;
+    /// Converts the result of a function the extension called into the result it's returned,
+    /// adding the error, if any, to the extension's log.
+    fn to_wasmtime_result(self, state: &WasmState) -> wasmtime::Result<Result<T, String>>;
 }
 
 impl<T> ToWasmtimeResult<T> for Result<T> {
-    fn to_wasmtime_result(self) -> wasmtime::Result<Result<T, String>> {
-        Ok(self.map_err(|error| error.to_string()))
+    fn to_wasmtime_result(self, state: &WasmState) -> wasmtime::Result<Result<T, String>> {
+        Ok(self.map_err(|error| {
+            state.log_error(&error);
+            error.to_string()
+        }))
     }
 }
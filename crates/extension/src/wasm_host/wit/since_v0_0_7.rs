@@ -57,7 +57,7 @@ impl HostKeyValueStore for WasmState {
         value: String,
     ) -> wasmtime::Result<Result<(), String>> {
         let kv_store = self.table.get(&kv_store)?;
-        kv_store.insert(key, value).await.to_wasmtime_result()
+        kv_store.insert(key, value).await.to_wasmtime_result(self)
     }
 
     fn drop(&mut self, _worktree: Resource<ExtensionKeyValueStore>) -> Result<()> {
@@ -116,6 +116,18 @@ impl HostWorktree for WasmState {
             .map(|path| path.to_string_lossy().to_string()))
     }
 
+    async fn run_command(
+        &mut self,
+        delegate: Resource<Arc<dyn LspAdapterDelegate>>,
+        command: ProcessCommand,
+    ) -> wasmtime::Result<Result<ProcessOutput, String>> {
+        let delegate = self.table.get(&delegate)?;
+        let root_path = delegate.worktree_root_path().to_path_buf();
+        run_command(self, command, root_path)
+            .await
+            .to_wasmtime_result(self)
+    }
+
     fn drop(&mut self, _worktree: Resource<Worktree>) -> Result<()> {
         // We only ever hand out borrows of worktrees.
         Ok(())
@@ -156,7 +168,7 @@ impl http_client::Host for WasmState {
             })
         })
         .await
-        .to_wasmtime_result()
+        .to_wasmtime_result(self)
     }
 }
 
@@ -168,7 +180,7 @@ impl nodejs::Host for WasmState {
             .binary_path()
             .await
             .map(|path| path.to_string_lossy().to_string())
-            .to_wasmtime_result()
+            .to_wasmtime_result(self)
     }
 
     async fn npm_package_latest_version(
@@ -179,7 +191,7 @@ impl nodejs::Host for WasmState {
             .node_runtime
             .npm_package_latest_version(&package_name)
             .await
-            .to_wasmtime_result()
+            .to_wasmtime_result(self)
     }
 
     async fn npm_package_installed_version(
@@ -190,7 +202,7 @@ impl nodejs::Host for WasmState {
             .node_runtime
             .npm_package_installed_version(&self.work_dir(), &package_name)
             .await
-            .to_wasmtime_result()
+            .to_wasmtime_result(self)
     }
 
     async fn npm_install_package(
@@ -202,7 +214,7 @@ impl nodejs::Host for WasmState {
             .node_runtime
             .npm_install_packages(&self.work_dir(), &[(&package_name, &version)])
             .await
-            .to_wasmtime_result()
+            .to_wasmtime_result(self)
     }
 }
 
@@ -245,7 +257,7 @@ impl github::Host for WasmState {
             Ok(release.into())
         })
         .await
-        .to_wasmtime_result()
+        .to_wasmtime_result(self)
     }
 
     async fn github_release_by_tag_name(
@@ -260,7 +272,7 @@ impl github::Host for WasmState {
             Ok(release.into())
         })
         .await
-        .to_wasmtime_result()
+        .to_wasmtime_result(self)
     }
 }
 
@@ -284,9 +296,85 @@ impl platform::Host for WasmState {
     }
 }
 
+#[async_trait]
+impl process::Host for WasmState {
+    async fn run_command(
+        &mut self,
+        command: process::Command,
+    ) -> wasmtime::Result<Result<process::Output, String>> {
+        run_command(self, command, util::paths::home_dir().clone())
+            .await
+            .to_wasmtime_result(self)
+    }
+}
+
+/// Runs a command of the extension in the given directory, which must not be writable by the
+/// extension, since programs like `cargo` and `go` read their configuration from the directory
+/// they run in.
+async fn run_command(
+    state: &mut WasmState,
+    command: process::Command,
+    cwd: PathBuf,
+) -> Result<process::Output> {
+    if !state.manifest.allows_exec(&command.command, &command.args) {
+        bail!(
+            "extension {} is not allowed to run `{} {}`; it must be declared as a `process:exec` capability in extension.toml",
+            state.manifest.id,
+            command.command,
+            command.args.join(" ")
+        );
+    }
+    if let Some((name, _)) = command
+        .env
+        .iter()
+        .find(|(name, _)| !ALLOWED_COMMAND_ENV_VARS.contains(&name.as_str()))
+    {
+        bail!(
+            "extension {} is not allowed to set the environment variable {name} of the commands it runs",
+            state.manifest.id,
+        );
+    }
+    state
+        .host
+        .ensure_process_exec_approved(&state.manifest)
+        .await?;
+
+    let output = smol::process::Command::new(&command.command)
+        .args(&command.args)
+        .envs(command.env)
+        .current_dir(&cwd)
+        .output()
+        .await
+        .with_context(|| format!("failed to run `{}`", command.command))?;
+
+    Ok(process::Output {
+        status: output.status.code(),
+        stdout: output.stdout,
+        stderr: output.stderr,
+    })
+}
+
+/// The environment variables that extensions can set for the commands they run, which only change
+/// how the commands format their output. Any other variable could make a command that an extension
+/// is allowed to run load or run other programs, such as `LD_PRELOAD` or `RUSTC_WRAPPER`.
+const ALLOWED_COMMAND_ENV_VARS: &[&str] = &[
+    "CLICOLOR",
+    "CLICOLOR_FORCE",
+    "COLUMNS",
+    "FORCE_COLOR",
+    "LANG",
+    "LC_ALL",
+    "NO_COLOR",
+    "RUST_BACKTRACE",
+    "TERM",
+];
+
 #[async_trait]
 impl slash_command::Host for WasmState {}
 
+#[async_trait]
+impl tasks::Host for WasmState {}
+
 #[async_trait]
 impl ExtensionImports for WasmState {
     async fn get_settings(
@@ -338,7 +426,7 @@ impl ExtensionImports for WasmState {
             .boxed_local()
         })
         .await?
-        .to_wasmtime_result()
+        .to_wasmtime_result(self)
     }
 
     async fn set_language_server_installation_status(
@@ -431,7 +519,7 @@ impl ExtensionImports for WasmState {
             Ok(())
         })
         .await
-        .to_wasmtime_result()
+        .to_wasmtime_result(self)
     }
 
     async fn make_file_executable(&mut self, path: String) -> wasmtime::Result<Result<(), String>> {
@@ -447,7 +535,7 @@ impl ExtensionImports for WasmState {
 
             return fs::set_permissions(&path, Permissions::from_mode(0o755))
                 .map_err(|error| anyhow!("failed to set permissions for path {path:?}: {error}"))
-                .to_wasmtime_result();
+                .to_wasmtime_result(self);
         }
 
         #[cfg(not(unix))]
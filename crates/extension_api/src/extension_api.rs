@@ -1,5 +1,6 @@
 //! The Zed Rust Extension API allows you write extensions for [Zed](https://zed.dev/) in Rust.
 
+pub mod process;
 /// Provides access to Zed settings.
 pub mod settings;
 
@@ -28,8 +29,9 @@ pub use wit::{
     zed::extension::slash_command::{
         SlashCommand, SlashCommandArgumentCompletion, SlashCommandOutput, SlashCommandOutputSection,
     },
+    zed::extension::tasks::{RevealStrategy, TaskTemplate, TaskVariables},
     CodeLabel, CodeLabelSpan, CodeLabelSpanLiteral, Command, DownloadedFileType, EnvVars,
-    KeyValueStore, LanguageServerInstallationStatus, Range, Worktree,
+    FormatterOutput, KeyValueStore, LanguageServerInstallationStatus, Range, TextEdit, Worktree,
 };
 
 // Undocumented WIT re-exports.
@@ -138,6 +140,36 @@ pub trait Extension: Send + Sync {
     ) -> Result<(), String> {
         Err("`index_docs` not implemented".to_string())
     }
+
+    /// Returns how to format the text of a buffer with the specified formatter, either as a
+    /// command to run or as edits to the text.
+    fn format_buffer(
+        &self,
+        _formatter_name: String,
+        _language_name: String,
+        _text: String,
+        _buffer_path: Option<String>,
+        _worktree: &Worktree,
+    ) -> Result<FormatterOutput, String> {
+        Err("`format_buffer` not implemented".to_string())
+    }
+
+    /// Returns the task templates for the specified language, which is provided by this extension.
+    fn tasks_for_language(&self, _language_name: String) -> Result<Vec<TaskTemplate>, String> {
+        Ok(Vec::new())
+    }
+
+    /// Returns the custom task variables for the location a task is spawned from in a buffer of
+    /// the specified language, given the task variables Zed has built for it.
+    ///
+    /// The returned variables are available to task templates as `$ZED_CUSTOM_<name>`.
+    fn build_task_variables(
+        &self,
+        _language_name: String,
+        _variables: TaskVariables,
+    ) -> Result<TaskVariables, String> {
+        Ok(Vec::new())
+    }
 }
 
 /// Registers the provided type as a Zed extension.
@@ -268,6 +300,27 @@ impl wit::Guest for Component {
     ) -> Result<(), String> {
         extension().index_docs(provider, package, database)
     }
+
+    fn format_buffer(
+        formatter_name: String,
+        language_name: String,
+        text: String,
+        buffer_path: Option<String>,
+        worktree: &Worktree,
+    ) -> Result<FormatterOutput, String> {
+        extension().format_buffer(formatter_name, language_name, text, buffer_path, worktree)
+    }
+
+    fn tasks_for_language(language_name: String) -> Result<Vec<TaskTemplate>, String> {
+        extension().tasks_for_language(language_name)
+    }
+
+    fn build_task_variables(
+        language_name: String,
+        variables: TaskVariables,
+    ) -> Result<TaskVariables, String> {
+        extension().build_task_variables(language_name, variables)
+    }
 }
 
 /// The ID of a language server.
@@ -0,0 +1,58 @@
+//! Running processes from extensions.
+//!
+//! Extensions can only run the commands matching the `process:exec` capabilities declared in
+//! their `extension.toml`, once the user has allowed them to:
+//!
+//! ```toml
+//! [[capabilities]]
+//! kind = "process:exec"
+//! command = "cargo"
+//! args = ["metadata", "**"]
+//! ```
+//!
+//! Commands run with the user's permissions, either in the user's home directory or, with
+//! [`Command::output_in_worktree`], in the root of a worktree.
+
+pub use crate::wit::zed::extension::process::{Command, Output};
+use crate::{wit, Result, Worktree};
+
+impl Command {
+    /// Returns a new [`Command`] for the given program.
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            command: program.into(),
+            args: Vec::new(),
+            env: Vec::new(),
+        }
+    }
+
+    /// Adds an argument to pass to the program.
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Adds arguments to pass to the program.
+    pub fn args(mut self, args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets an environment variable for the program.
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Runs the command in the user's home directory and waits for it to finish, returning its
+    /// output.
+    pub fn output(&self) -> Result<Output> {
+        wit::zed::extension::process::run_command(self)
+    }
+
+    /// Runs the command in the root of the given worktree and waits for it to finish, returning
+    /// its output.
+    pub fn output_in_worktree(&self, worktree: &Worktree) -> Result<Output> {
+        worktree.run_command(self)
+    }
+}
@@ -14,7 +14,7 @@ use extension::{
     ExtensionManifest,
 };
 use language::LanguageConfig;
-use theme::ThemeRegistry;
+use theme::{IconThemeContent, ThemeRegistry};
 use tree_sitter::{Language, Query, WasmStore};
 
 #[derive(Parser, Debug)]
@@ -71,6 +71,7 @@ async fn main() -> Result<()> {
     let grammars = test_grammars(&manifest, &extension_path, &mut wasm_store)?;
     test_languages(&manifest, &extension_path, &grammars)?;
     test_themes(&manifest, &extension_path, fs.clone()).await?;
+    test_icon_themes(&manifest, &extension_path, fs.clone()).await?;
 
     let archive_dir = output_dir.join("archive");
     fs::remove_dir_all(&archive_dir).ok();
@@ -158,6 +159,36 @@ async fn copy_extension_resources(
         }
     }
 
+    if !manifest.icon_themes.is_empty() {
+        let output_icon_themes_dir = output_dir.join("icon_themes");
+        fs::create_dir_all(&output_icon_themes_dir)?;
+        for icon_theme_path in &manifest.icon_themes {
+            let icon_theme_file_name = icon_theme_path
+                .file_name()
+                .ok_or_else(|| anyhow!("invalid icon theme path"))?;
+            fs::copy(
+                extension_path.join(icon_theme_path),
+                output_icon_themes_dir.join(icon_theme_file_name),
+            )
+            .with_context(|| {
+                format!("failed to copy icon theme '{}'", icon_theme_path.display())
+            })?;
+
+            // The icons are referenced relative to the extension's root, so they keep their paths.
+            let icon_theme =
+                ThemeRegistry::read_icon_theme(&extension_path.join(icon_theme_path), fs.clone())
+                    .await?;
+            for icon_path in icon_paths(&icon_theme) {
+                let output_icon_path = output_dir.join(icon_path);
+                if let Some(parent) = output_icon_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::copy(extension_path.join(icon_path), &output_icon_path)
+                    .with_context(|| format!("failed to copy icon '{}'", icon_path))?;
+            }
+        }
+    }
+
     if !manifest.languages.is_empty() {
         let output_languages_dir = output_dir.join("languages");
         fs::create_dir_all(&output_languages_dir)?;
@@ -263,3 +294,42 @@ async fn test_themes(
 
     Ok(())
 }
+
+async fn test_icon_themes(
+    manifest: &ExtensionManifest,
+    extension_path: &Path,
+    fs: Arc<dyn Fs>,
+) -> Result<()> {
+    for relative_icon_theme_path in &manifest.icon_themes {
+        let icon_theme_path = extension_path.join(relative_icon_theme_path);
+        let icon_theme = ThemeRegistry::read_icon_theme(&icon_theme_path, fs.clone()).await?;
+        for icon_path in icon_paths(&icon_theme) {
+            if !extension_path.join(icon_path).is_file() {
+                bail!(
+                    "icon theme {} references missing icon '{}'",
+                    icon_theme.name,
+                    icon_path
+                );
+            }
+        }
+        log::info!("loaded icon theme {}", icon_theme.name);
+    }
+
+    Ok(())
+}
+
+/// Returns the paths of the icons an icon theme references, relative to the extension's root.
+fn icon_paths(icon_theme: &IconThemeContent) -> impl Iterator<Item = &String> {
+    icon_theme
+        .file_names
+        .values()
+        .chain(icon_theme.file_suffixes.values())
+        .chain(&icon_theme.default_file)
+        .chain(
+            icon_theme
+                .folder
+                .iter()
+                .chain(&icon_theme.chevron)
+                .flat_map(|icons| [&icons.collapsed, &icons.expanded]),
+        )
+}
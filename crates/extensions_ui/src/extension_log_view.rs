@@ -0,0 +1,235 @@
+use editor::{actions::MoveToEnd, Editor, EditorEvent};
+use extension::{
+    ExtensionLogEntry, ExtensionLogEvent, ExtensionLogKind, ExtensionLogStore, ExtensionStore,
+};
+use gpui::{
+    actions, AnchorCorner, AnyElement, AppContext, EventEmitter, FocusHandle, FocusableView,
+    IntoElement, Model, ParentElement, Render, Styled, Subscription, View, ViewContext,
+    VisualContext, WindowContext,
+};
+use std::sync::Arc;
+use ui::{prelude::*, Button, ContextMenu, Label, PopoverMenu};
+use workspace::{
+    item::{Item, TabContentParams},
+    Workspace,
+};
+
+actions!(debug, [OpenExtensionLogs]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(|workspace, _: &OpenExtensionLogs, cx| {
+            let log_store = ExtensionStore::global(cx).read(cx).log_store().clone();
+            workspace.add_item_to_active_pane(
+                Box::new(cx.new_view(|cx| ExtensionLogView::new(log_store, cx))),
+                None,
+                cx,
+            );
+        });
+    })
+    .detach();
+}
+
+/// Shows the output of an extension's Wasm module and the errors of its calls, which is the
+/// console to use while developing an extension.
+pub struct ExtensionLogView {
+    editor: View<Editor>,
+    _editor_subscription: Subscription,
+    log_store: Model<ExtensionLogStore>,
+    current_extension_id: Option<Arc<str>>,
+    focus_handle: FocusHandle,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ExtensionLogView {
+    fn new(log_store: Model<ExtensionLogStore>, cx: &mut ViewContext<Self>) -> Self {
+        let events_subscription = cx.subscribe(&log_store, |log_view, _, event, cx| match event {
+            ExtensionLogEvent::NewEntry {
+                extension_id,
+                entry,
+            } => {
+                if log_view.current_extension_id.is_none() {
+                    log_view.show_logs_for_extension(extension_id.clone(), cx);
+                } else if log_view.current_extension_id.as_ref() == Some(extension_id) {
+                    log_view.editor.update(cx, |editor, cx| {
+                        editor.set_read_only(false);
+                        let last_point = editor.buffer().read(cx).len(cx);
+                        editor.edit(
+                            vec![(last_point..last_point, format!("{}\n", log_line(entry)))],
+                            cx,
+                        );
+                        editor.set_read_only(true);
+                    });
+                }
+            }
+        });
+        let model_changes_subscription = cx.observe(&log_store, |_, _, cx| cx.notify());
+        let (editor, editor_subscription) = Self::editor_for_logs(String::new(), cx);
+
+        let focus_handle = cx.focus_handle();
+        let focus_subscription = cx.on_focus(&focus_handle, |log_view, cx| {
+            cx.focus_view(&log_view.editor);
+        });
+
+        let first_extension_id = log_store.read(cx).extension_ids().next().cloned();
+        let mut this = Self {
+            editor,
+            _editor_subscription: editor_subscription,
+            log_store,
+            current_extension_id: None,
+            focus_handle,
+            _subscriptions: vec![
+                events_subscription,
+                model_changes_subscription,
+                focus_subscription,
+            ],
+        };
+        if let Some(extension_id) = first_extension_id {
+            this.show_logs_for_extension(extension_id, cx);
+        }
+        this
+    }
+
+    fn editor_for_logs(
+        log_contents: String,
+        cx: &mut ViewContext<Self>,
+    ) -> (View<Editor>, Subscription) {
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::multi_line(cx);
+            editor.set_text(log_contents, cx);
+            editor.move_to_end(&MoveToEnd, cx);
+            editor.set_read_only(true);
+            editor.set_show_inline_completions(false);
+            editor
+        });
+        let editor_subscription = cx.subscribe(
+            &editor,
+            |_, _, event: &EditorEvent, cx: &mut ViewContext<ExtensionLogView>| {
+                cx.emit(event.clone())
+            },
+        );
+        (editor, editor_subscription)
+    }
+
+    fn show_logs_for_extension(&mut self, extension_id: Arc<str>, cx: &mut ViewContext<Self>) {
+        let log_contents = self
+            .log_store
+            .read(cx)
+            .entries(&extension_id)
+            .map(|entry| format!("{}\n", log_line(entry)))
+            .collect::<String>();
+        let (editor, editor_subscription) = Self::editor_for_logs(log_contents, cx);
+        self.editor = editor;
+        self._editor_subscription = editor_subscription;
+        self.current_extension_id = Some(extension_id);
+        cx.notify();
+        cx.focus(&self.focus_handle);
+    }
+
+    fn clear_logs(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(extension_id) = self.current_extension_id.clone() else {
+            return;
+        };
+        self.log_store
+            .update(cx, |log_store, cx| log_store.clear(&extension_id, cx));
+        self.show_logs_for_extension(extension_id, cx);
+    }
+
+    fn render_extension_menu(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let extension_ids = self
+            .log_store
+            .read(cx)
+            .extension_ids()
+            .cloned()
+            .collect::<Vec<_>>();
+        let log_view = cx.view().clone();
+
+        PopoverMenu::new("extension-log-menu")
+            .anchor(AnchorCorner::TopLeft)
+            .trigger(Button::new(
+                "extension-log-menu-header",
+                self.current_extension_id
+                    .as_ref()
+                    .map_or(SharedString::from("No extension selected"), |id| {
+                        SharedString::from(id.to_string())
+                    }),
+            ))
+            .menu(move |cx| {
+                let extension_ids = extension_ids.clone();
+                let log_view = log_view.clone();
+                Some(ContextMenu::build(cx, move |mut menu, cx| {
+                    for extension_id in extension_ids {
+                        menu = menu.entry(
+                            extension_id.to_string(),
+                            None,
+                            cx.handler_for(&log_view, move |log_view, cx| {
+                                log_view.show_logs_for_extension(extension_id.clone(), cx);
+                            }),
+                        );
+                    }
+                    menu
+                }))
+            })
+    }
+}
+
+fn log_line(entry: &ExtensionLogEntry) -> String {
+    let prefix = match entry.kind {
+        ExtensionLogKind::Stdout => "[stdout]",
+        ExtensionLogKind::Stderr => "[stderr]",
+        ExtensionLogKind::Error => "[error]",
+    };
+    format!("{prefix} {}", entry.message)
+}
+
+impl Render for ExtensionLogView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .size_full()
+            .child(
+                h_flex()
+                    .p_1()
+                    .gap_2()
+                    .justify_between()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(self.render_extension_menu(cx))
+                    .child(
+                        Button::new("clear-extension-log", "Clear")
+                            .disabled(self.current_extension_id.is_none())
+                            .on_click(cx.listener(|log_view, _, cx| log_view.clear_logs(cx))),
+                    ),
+            )
+            .child(div().flex_1().child(self.editor.clone()))
+    }
+}
+
+impl FocusableView for ExtensionLogView {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<EditorEvent> for ExtensionLogView {}
+
+impl Item for ExtensionLogView {
+    type Event = EditorEvent;
+
+    fn to_item_events(event: &Self::Event, f: impl FnMut(workspace::item::ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn tab_content(&self, params: TabContentParams, _: &WindowContext<'_>) -> AnyElement {
+        Label::new("Extension Logs")
+            .color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        None
+    }
+}
@@ -1,4 +1,5 @@
 mod components;
+mod extension_log_view;
 mod extension_suggest;
 mod extension_version_selector;
 
@@ -34,6 +35,8 @@ use workspace::{
 actions!(zed, [Extensions, InstallDevExtension]);
 
 pub fn init(cx: &mut AppContext) {
+    extension_log_view::init(cx);
+
     cx.observe_new_views(move |workspace: &mut Workspace, cx| {
         workspace
             .register_action(move |workspace, _: &Extensions, cx| {
@@ -189,6 +192,25 @@ impl ExtensionsPage {
                     )
                 })
                 .ok();
+            return;
+        }
+
+        let icon_themes = extension_store
+            .extension_icon_themes(extension_id)
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+        if !icon_themes.is_empty() {
+            workspace
+                .update(cx, |workspace, cx| {
+                    theme_selector::icon_theme_selector::toggle(
+                        workspace,
+                        &theme_selector::icon_theme_selector::Toggle {
+                            icon_themes_filter: Some(icon_themes),
+                        },
+                        cx,
+                    )
+                })
+                .ok();
         }
     }
 
@@ -1,10 +1,13 @@
 #[cfg(test)]
 mod file_finder_tests;
 
+mod file_finder_settings;
 mod new_path_prompt;
 
 use collections::{BTreeSet, HashMap};
 use editor::{scroll::Autoscroll, Bias, Editor};
+use file_finder_settings::FileFinderSettings;
+use file_icons::FileIcons;
 use fuzzy::{CharBag, PathMatch, PathMatchCandidate};
 use gpui::{
     actions, rems, Action, AnyElement, AppContext, DismissEvent, EventEmitter, FocusHandle,
@@ -39,6 +42,7 @@ pub struct FileFinder {
 }
 
 pub fn init(cx: &mut AppContext) {
+    FileFinderSettings::register(cx);
     cx.observe_new_views(FileFinder::register).detach();
     cx.observe_new_views(NewPathPrompt::register).detach();
 }
@@ -959,12 +963,24 @@ impl PickerDelegate for FileFinderDelegate {
                 .size(IconSize::Small.rems())
                 .into_any_element(),
         };
+        let file_icons = FileIcons::should_show(FileFinderSettings::get_global(cx).file_icons, cx);
+        let file_icon = match &path_match {
+            Match::History(found_path, _) => FileIcons::get_icon(&found_path.project.path, cx),
+            Match::Search(search_match) => FileIcons::get_icon(&search_match.0.path, cx),
+        }
+        .filter(|_| file_icons)
+        .map(|icon| {
+            Icon::from_path(icon.to_string())
+                .color(Color::Muted)
+                .size(IconSize::Small)
+        });
         let (file_name, file_name_positions, full_path, full_path_positions) =
             self.labels_for_match(path_match, cx, ix);
 
         Some(
             ListItem::new(ix)
                 .spacing(ListItemSpacing::Sparse)
+                .start_slot::<Icon>(file_icon)
                 .end_slot::<AnyElement>(Some(icon))
                 .inset(true)
                 .selected(selected)
@@ -0,0 +1,246 @@
+//! A local history of file contents, independent of any version control system.
+//!
+//! Whenever a file is opened or saved in a local project, its contents are recorded in a local
+//! database, so that earlier revisions can be compared and restored from the timeline view.
+
+mod file_history_settings;
+mod persistence;
+mod timeline;
+
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use collections::HashMap;
+use gpui::{AppContext, Model, ViewContext};
+use language::Buffer;
+use project::{buffer_store::BufferStoreEvent, PathChange, Project, UpdatedEntriesSet, WorktreeId};
+use settings::Settings;
+use util::ResultExt;
+use workspace::Workspace;
+
+pub use file_history_settings::FileHistorySettings;
+pub use persistence::{Revision, RevisionId, RevisionLimits, DB};
+pub use timeline::{TimelineView, ToggleTimeline};
+
+pub fn init(cx: &mut AppContext) {
+    FileHistorySettings::register(cx);
+    cx.observe_new_views(register_workspace).detach();
+    timeline::init(cx);
+}
+
+fn register_workspace(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let project = workspace.project().clone();
+    if !project.read(cx).is_local() {
+        return;
+    }
+
+    let buffer_store = project.read(cx).buffer_store().clone();
+    cx.subscribe(&buffer_store, |_, _, event, cx| match event {
+        // Recording opened files preserves their contents before they're first overwritten.
+        BufferStoreEvent::BufferAdded(buffer) | BufferStoreEvent::BufferSaved { buffer, .. } => {
+            record_revision(buffer, cx)
+        }
+        BufferStoreEvent::BufferChangedFilePath { .. } => {}
+    })
+    .detach();
+    cx.subscribe(&project, |_, project, event, cx| {
+        if let project::Event::WorktreeUpdatedEntries(worktree_id, changes) = event {
+            track_renames(&project, *worktree_id, changes, cx);
+        }
+    })
+    .detach();
+}
+
+fn record_revision(buffer: &Model<Buffer>, cx: &mut AppContext) {
+    let settings = FileHistorySettings::get_global(cx);
+    if !settings.enabled {
+        return;
+    }
+
+    let buffer = buffer.read(cx);
+    let Some(file) = buffer.file() else {
+        return;
+    };
+    if file.is_private() || buffer.len() as u64 > settings.max_file_size {
+        return;
+    }
+    let Some(local_file) = file.as_local() else {
+        return;
+    };
+
+    let abs_path = local_file.abs_path(cx);
+    let content = buffer.text();
+    let limits = settings.limits();
+    cx.background_executor()
+        .spawn(async move {
+            DB.record_revision(abs_path, content, unix_timestamp(), limits)
+                .await
+                .log_err();
+        })
+        .detach();
+}
+
+/// Moves the history of files renamed on disk to their new path.
+///
+/// The worktree keeps the entry ids of renamed files, so a rename is reported as a removal and
+/// an addition of two paths sharing the same entry id.
+fn track_renames(
+    project: &Model<Project>,
+    worktree_id: WorktreeId,
+    changes: &UpdatedEntriesSet,
+    cx: &mut AppContext,
+) {
+    let Some(worktree) = project.read(cx).worktree_for_id(worktree_id, cx) else {
+        return;
+    };
+    let worktree_abs_path = worktree.read(cx).abs_path();
+
+    let removed_paths = changes
+        .iter()
+        .filter(|(_, _, change)| *change == PathChange::Removed)
+        .map(|(path, entry_id, _)| (*entry_id, path))
+        .collect::<HashMap<_, _>>();
+    if removed_paths.is_empty() {
+        return;
+    }
+
+    let renames = changes
+        .iter()
+        .filter(|(_, _, change)| *change == PathChange::Added)
+        .filter_map(|(new_path, entry_id, _)| {
+            let old_path = removed_paths.get(entry_id)?;
+            Some((
+                worktree_abs_path.join(old_path),
+                worktree_abs_path.join(new_path),
+            ))
+        })
+        .collect::<Vec<(PathBuf, PathBuf)>>();
+    if renames.is_empty() {
+        return;
+    }
+
+    cx.background_executor()
+        .spawn(async move {
+            for (old_path, new_path) in renames {
+                DB.rename_path(old_path, new_path).await.log_err();
+            }
+        })
+        .detach();
+}
+
+fn unix_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::Path, sync::Arc};
+
+    use gpui::TestAppContext;
+    use project::{FakeFs, Fs, RenameOptions};
+    use serde_json::json;
+    use workspace::AppState;
+
+    use super::*;
+
+    #[gpui::test]
+    async fn test_record_revisions_on_open_and_save(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/test_record_revisions", json!({ "a.txt": "one\n" }))
+            .await;
+        let project = Project::test(fs, ["/test_record_revisions".as_ref()], cx).await;
+        cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/test_record_revisions/a.txt", cx)
+            })
+            .await
+            .unwrap();
+        flush_history(cx).await;
+        assert_eq!(
+            revision_contents("/test_record_revisions/a.txt"),
+            ["one\n"],
+            "Opening a file records its contents"
+        );
+
+        buffer.update(cx, |buffer, cx| buffer.edit([(0..3, "two")], None, cx));
+        project
+            .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+            .await
+            .unwrap();
+        flush_history(cx).await;
+        assert_eq!(
+            revision_contents("/test_record_revisions/a.txt"),
+            ["two\n", "one\n"],
+            "Saving a file records its new contents"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_track_renames(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/test_track_renames", json!({ "a.txt": "one\n" }))
+            .await;
+        let project = Project::test(fs.clone(), ["/test_track_renames".as_ref()], cx).await;
+        cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+
+        project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/test_track_renames/a.txt", cx)
+            })
+            .await
+            .unwrap();
+        flush_history(cx).await;
+
+        fs.rename(
+            Path::new("/test_track_renames/a.txt"),
+            Path::new("/test_track_renames/b.txt"),
+            RenameOptions::default(),
+        )
+        .await
+        .unwrap();
+        flush_history(cx).await;
+
+        assert!(revision_contents("/test_track_renames/a.txt").is_empty());
+        assert_eq!(
+            revision_contents("/test_track_renames/b.txt"),
+            ["one\n"],
+            "The history of a renamed file follows it to its new path"
+        );
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        // Writes to the history database happen on its own thread.
+        cx.executor().allow_parking();
+        cx.update(|cx| {
+            let state = AppState::test(cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            super::init(cx);
+            state
+        })
+    }
+
+    /// Waits for the revisions queued by the project's events to be written to the database.
+    async fn flush_history(cx: &mut TestAppContext) {
+        cx.run_until_parked();
+        DB.write(|_| ()).await;
+    }
+
+    fn revision_contents(path: &str) -> Vec<String> {
+        DB.revisions(Path::new(path))
+            .unwrap()
+            .into_iter()
+            .filter_map(|revision| DB.revision_content(revision.id).unwrap())
+            .collect()
+    }
+}
@@ -0,0 +1,57 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+use crate::persistence::RevisionLimits;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct FileHistorySettings {
+    pub enabled: bool,
+    pub max_file_size: u64,
+    pub max_revisions_per_file: usize,
+    pub max_total_size: u64,
+}
+
+impl FileHistorySettings {
+    pub fn limits(&self) -> RevisionLimits {
+        RevisionLimits {
+            max_revisions_per_file: self.max_revisions_per_file,
+            max_total_size: self.max_total_size,
+        }
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct FileHistorySettingsContent {
+    /// Whether to keep a local history of the contents of saved files.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// Files larger than this size (in bytes) are not recorded in the history.
+    ///
+    /// Default: 1048576
+    pub max_file_size: Option<u64>,
+    /// The maximum number of revisions kept for every file.
+    /// The oldest revisions are discarded first.
+    ///
+    /// Default: 50
+    pub max_revisions_per_file: Option<usize>,
+    /// The maximum size (in bytes) of the contents stored in the history, across all files.
+    /// The oldest revisions are discarded first.
+    ///
+    /// Default: 268435456
+    pub max_total_size: Option<u64>,
+}
+
+impl Settings for FileHistorySettings {
+    const KEY: Option<&'static str> = Some("file_history");
+
+    type FileContent = FileHistorySettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut gpui::AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
@@ -0,0 +1,285 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result};
+use db::{
+    define_connection, query,
+    sqlez::{bindable::Column, connection::Connection, statement::Statement},
+    sqlez_macros::sql,
+};
+use sha2::{Digest, Sha256};
+
+define_connection!(
+    // Current schema shape using pseudo-rust syntax:
+    // file_history_contents(
+    //   content_hash: String, // sha256 of the content
+    //   content: String,
+    // )
+    //
+    // file_history_revisions(
+    //   revision_id: i64,
+    //   path: PathBuf, // absolute path of the file
+    //   content_hash: String,
+    //   size: u64, // size of the content, in bytes
+    //   timestamp: i64, // seconds since the unix epoch
+    // )
+    pub static ref DB: FileHistoryDb<()> =
+        &[sql!(
+            CREATE TABLE file_history_contents(
+                content_hash TEXT PRIMARY KEY,
+                content TEXT NOT NULL
+            ) STRICT;
+
+            CREATE TABLE file_history_revisions(
+                revision_id INTEGER PRIMARY KEY AUTOINCREMENT,
+                path BLOB NOT NULL,
+                content_hash TEXT NOT NULL,
+                size INTEGER NOT NULL,
+                timestamp INTEGER NOT NULL,
+                FOREIGN KEY(content_hash) REFERENCES file_history_contents(content_hash)
+            ) STRICT;
+
+            CREATE INDEX file_history_revisions_by_path ON file_history_revisions(path);
+        )];
+);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RevisionId(i64);
+
+impl Column for RevisionId {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (id, next_index) = i64::column(statement, start_index)?;
+        Ok((Self(id), next_index))
+    }
+}
+
+/// A snapshot of the contents of a file, taken when it was opened or saved.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Revision {
+    pub id: RevisionId,
+    /// Seconds since the unix epoch.
+    pub timestamp: i64,
+    /// The size of the revision's contents, in bytes.
+    pub size: u64,
+}
+
+/// Bounds the amount of history kept in the database.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RevisionLimits {
+    pub max_revisions_per_file: usize,
+    pub max_total_size: u64,
+}
+
+impl FileHistoryDb {
+    /// Records a revision of the file at the given path, unless its contents are identical to the
+    /// latest revision of that file. Contents are stored once, no matter how many revisions share
+    /// them. Returns the id of the new revision, if one was recorded.
+    pub async fn record_revision(
+        &self,
+        path: PathBuf,
+        content: String,
+        timestamp: i64,
+        limits: RevisionLimits,
+    ) -> Result<Option<RevisionId>> {
+        self.write(move |conn| {
+            conn.with_savepoint("record_file_revision", || {
+                let content_hash = content_hash(&content);
+                let latest_hash = conn.select_row_bound::<&Path, String>(sql!(
+                    SELECT content_hash FROM file_history_revisions
+                    WHERE path = ?
+                    ORDER BY revision_id DESC
+                    LIMIT 1
+                ))?(path.as_path())?;
+                if latest_hash.as_ref() == Some(&content_hash) {
+                    return Ok(None);
+                }
+
+                conn.exec_bound(sql!(
+                    INSERT OR IGNORE INTO file_history_contents(content_hash, content)
+                    VALUES (?, ?)
+                ))?((content_hash.as_str(), content.as_str()))
+                .context("Inserting revision contents")?;
+                let revision_id = conn.select_row_bound::<_, RevisionId>(sql!(
+                    INSERT INTO file_history_revisions(path, content_hash, size, timestamp)
+                    VALUES (?, ?, ?, ?)
+                    RETURNING revision_id
+                ))?((
+                    path.as_path(),
+                    content_hash.as_str(),
+                    content.len() as u64,
+                    timestamp,
+                ))?
+                .context("Inserting revision")?;
+
+                prune_revisions(conn, &path, limits).context("Pruning revisions")?;
+                Ok(Some(revision_id))
+            })
+        })
+        .await
+    }
+
+    /// Returns the revisions of the file at the given path, newest first.
+    pub fn revisions(&self, path: &Path) -> Result<Vec<Revision>> {
+        Ok(self.select_bound::<&Path, (RevisionId, i64, u64)>(sql!(
+            SELECT revision_id, timestamp, size FROM file_history_revisions
+            WHERE path = ?
+            ORDER BY revision_id DESC
+        ))?(path)?
+        .into_iter()
+        .map(|(id, timestamp, size)| Revision {
+            id,
+            timestamp,
+            size,
+        })
+        .collect())
+    }
+
+    pub fn revision_content(&self, revision_id: RevisionId) -> Result<Option<String>> {
+        self.select_row_bound::<i64, String>(sql!(
+            SELECT file_history_contents.content
+            FROM file_history_revisions
+            JOIN file_history_contents
+                ON file_history_revisions.content_hash = file_history_contents.content_hash
+            WHERE file_history_revisions.revision_id = ?
+        ))?(revision_id.0)
+    }
+
+    query! {
+        pub async fn rename_path(old_path: PathBuf, new_path: PathBuf) -> Result<()> {
+            UPDATE file_history_revisions
+            SET path = ?2
+            WHERE path = ?1
+        }
+    }
+}
+
+fn prune_revisions(conn: &Connection, path: &Path, limits: RevisionLimits) -> Result<()> {
+    conn.exec_bound(sql!(
+        DELETE FROM file_history_revisions
+        WHERE path = ?1 AND revision_id NOT IN (
+            SELECT revision_id FROM file_history_revisions
+            WHERE path = ?1
+            ORDER BY revision_id DESC
+            LIMIT ?2
+        )
+    ))?((path, limits.max_revisions_per_file))?;
+
+    // Contents shared by several revisions only count once towards the total size, as part of
+    // the newest revision that has them. Once the running total of the sizes, from the newest
+    // revision to the oldest, exceeds the limit, all older revisions are deleted.
+    conn.exec_bound(sql!(
+        DELETE FROM file_history_revisions
+        WHERE revision_id IN (
+            SELECT revision_id FROM (
+                SELECT
+                    revision_id,
+                    SUM(CASE WHEN revision_id = newest_revision_id THEN size ELSE 0 END)
+                        OVER (ORDER BY revision_id DESC) AS total_size
+                FROM (
+                    SELECT
+                        revision_id,
+                        size,
+                        MAX(revision_id) OVER (PARTITION BY content_hash) AS newest_revision_id
+                    FROM file_history_revisions
+                )
+            )
+            WHERE total_size > ?
+        )
+    ))?(limits.max_total_size)?;
+
+    conn.exec(sql!(
+        DELETE FROM file_history_contents
+        WHERE content_hash NOT IN (SELECT content_hash FROM file_history_revisions)
+    ))?()
+}
+
+fn content_hash(content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_record_revisions() {
+        let db = FileHistoryDb(db::open_test_db("test_record_revisions").await);
+        let limits = RevisionLimits {
+            max_revisions_per_file: 2,
+            max_total_size: 1024,
+        };
+        let path = PathBuf::from("/dir/a.txt");
+
+        let first = db
+            .record_revision(path.clone(), "one".into(), 1, limits)
+            .await
+            .unwrap();
+        assert!(first.is_some());
+
+        // Recording the same contents again doesn't create a new revision.
+        let duplicate = db
+            .record_revision(path.clone(), "one".into(), 2, limits)
+            .await
+            .unwrap();
+        assert_eq!(duplicate, None);
+
+        db.record_revision(path.clone(), "two".into(), 3, limits)
+            .await
+            .unwrap();
+        db.record_revision(path.clone(), "three".into(), 4, limits)
+            .await
+            .unwrap();
+
+        // Only the newest revisions are kept.
+        let revisions = db.revisions(&path).unwrap();
+        assert_eq!(
+            revisions
+                .iter()
+                .map(|revision| revision.timestamp)
+                .collect::<Vec<_>>(),
+            [4, 3]
+        );
+        assert_eq!(
+            db.revision_content(revisions[1].id).unwrap().as_deref(),
+            Some("two")
+        );
+        assert_eq!(db.revision_content(first.unwrap()).unwrap(), None);
+
+        let new_path = PathBuf::from("/dir/b.txt");
+        db.rename_path(path.clone(), new_path.clone())
+            .await
+            .unwrap();
+        assert!(db.revisions(&path).unwrap().is_empty());
+        assert_eq!(db.revisions(&new_path).unwrap(), revisions);
+    }
+
+    #[gpui::test]
+    async fn test_total_size_limit() {
+        let db = FileHistoryDb(db::open_test_db("test_total_size_limit").await);
+        let limits = RevisionLimits {
+            max_revisions_per_file: 10,
+            max_total_size: 10,
+        };
+
+        db.record_revision("/a.txt".into(), "aaaa".into(), 1, limits)
+            .await
+            .unwrap();
+        // Identical contents are stored once, so they don't count twice.
+        db.record_revision("/b.txt".into(), "aaaa".into(), 2, limits)
+            .await
+            .unwrap();
+        db.record_revision("/b.txt".into(), "bbbbbb".into(), 3, limits)
+            .await
+            .unwrap();
+        assert_eq!(db.revisions(Path::new("/a.txt")).unwrap().len(), 1);
+        assert_eq!(db.revisions(Path::new("/b.txt")).unwrap().len(), 2);
+
+        db.record_revision("/c.txt".into(), "cc".into(), 4, limits)
+            .await
+            .unwrap();
+        assert!(db.revisions(Path::new("/a.txt")).unwrap().is_empty());
+        assert_eq!(db.revisions(Path::new("/b.txt")).unwrap().len(), 1);
+        assert_eq!(db.revisions(Path::new("/c.txt")).unwrap().len(), 1);
+    }
+}
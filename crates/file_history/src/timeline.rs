@@ -0,0 +1,563 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Result;
+use editor::{actions::ExpandAllHunkDiffs, Editor, EditorMode, MultiBuffer};
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, rems, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model,
+    Render, Task, View, ViewContext, VisualContext, WeakView,
+};
+use language::Buffer;
+use picker::{Picker, PickerDelegate};
+use similar::{ChangeTag, TextDiff};
+use time::OffsetDateTime;
+use time_format::TimestampFormat;
+use ui::{prelude::*, KeyBinding, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+use crate::{Revision, DB};
+
+actions!(file_history, [ToggleTimeline]);
+
+pub(crate) fn init(cx: &mut AppContext) {
+    cx.observe_new_views(TimelineView::register).detach();
+}
+
+/// Lists the recorded revisions of the file open in an editor, to compare them against the
+/// current contents of the buffer or restore them.
+pub struct TimelineView {
+    picker: View<Picker<TimelineViewDelegate>>,
+}
+
+impl ModalView for TimelineView {}
+
+impl EventEmitter<DismissEvent> for TimelineView {}
+
+impl FocusableView for TimelineView {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for TimelineView {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl TimelineView {
+    fn register(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+        if editor.mode() != EditorMode::Full {
+            return;
+        }
+
+        let handle = cx.view().downgrade();
+        editor
+            .register_action(move |_: &ToggleTimeline, cx| {
+                let Some(editor) = handle.upgrade() else {
+                    return;
+                };
+                let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() else {
+                    return;
+                };
+                let Some(abs_path) = buffer
+                    .read(cx)
+                    .file()
+                    .and_then(|file| Some(file.as_local()?.abs_path(cx)))
+                else {
+                    return;
+                };
+                let Some(workspace) = editor.read(cx).workspace() else {
+                    return;
+                };
+
+                workspace.update(cx, |workspace, cx| {
+                    let workspace_handle = cx.view().downgrade();
+                    workspace.toggle_modal(cx, move |cx| {
+                        TimelineView::new(workspace_handle, editor, buffer, abs_path, cx)
+                    });
+                })
+            })
+            .detach();
+    }
+
+    fn new(
+        workspace: WeakView<Workspace>,
+        editor: View<Editor>,
+        buffer: Model<Buffer>,
+        abs_path: PathBuf,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let delegate = TimelineViewDelegate {
+            timeline_view: cx.view().downgrade(),
+            workspace,
+            editor,
+            buffer: buffer.clone(),
+            file_name: abs_path
+                .file_name()
+                .map(|file_name| file_name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            entries: Vec::new(),
+            matches: Vec::new(),
+            selected_index: 0,
+        };
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+
+        let current_text = buffer.read(cx).text();
+        let load_revisions = cx
+            .background_executor()
+            .spawn(async move { load_revisions(&abs_path, &current_text) });
+        let picker_handle = picker.downgrade();
+        cx.spawn(|_, mut cx| async move {
+            let revisions = load_revisions.await?;
+            picker_handle.update(&mut cx, |picker, cx| {
+                let now = OffsetDateTime::now_utc();
+                let timezone = cx.local_timezone();
+                picker.delegate.entries = revisions
+                    .into_iter()
+                    .filter_map(|(revision, content, insertions, deletions)| {
+                        let timestamp =
+                            OffsetDateTime::from_unix_timestamp(revision.timestamp).ok()?;
+                        Some(TimelineEntry {
+                            relative_time: time_format::format_localized_timestamp(
+                                timestamp,
+                                now,
+                                timezone,
+                                TimestampFormat::Relative,
+                            ),
+                            absolute_time: time_format::format_localized_timestamp(
+                                timestamp,
+                                now,
+                                timezone,
+                                TimestampFormat::EnhancedAbsolute,
+                            ),
+                            revision,
+                            content,
+                            insertions,
+                            deletions,
+                        })
+                    })
+                    .collect();
+                picker.refresh(cx);
+            })
+        })
+        .detach_and_log_err(cx);
+
+        Self { picker }
+    }
+}
+
+/// Loads the revisions of a file, along with their contents and the number of lines inserted and
+/// deleted to get from each revision to the current text.
+fn load_revisions(
+    abs_path: &Path,
+    current_text: &str,
+) -> Result<Vec<(Revision, Arc<str>, usize, usize)>> {
+    let mut revisions = Vec::new();
+    for revision in DB.revisions(abs_path)? {
+        let Some(content) = DB.revision_content(revision.id)? else {
+            continue;
+        };
+
+        let mut insertions = 0;
+        let mut deletions = 0;
+        for change in TextDiff::from_lines(content.as_str(), current_text).iter_all_changes() {
+            match change.tag() {
+                ChangeTag::Insert => insertions += 1,
+                ChangeTag::Delete => deletions += 1,
+                ChangeTag::Equal => {}
+            }
+        }
+        revisions.push((revision, Arc::from(content), insertions, deletions));
+    }
+    Ok(revisions)
+}
+
+struct TimelineEntry {
+    revision: Revision,
+    content: Arc<str>,
+    relative_time: String,
+    absolute_time: String,
+    insertions: usize,
+    deletions: usize,
+}
+
+pub struct TimelineViewDelegate {
+    timeline_view: WeakView<TimelineView>,
+    workspace: WeakView<Workspace>,
+    editor: View<Editor>,
+    buffer: Model<Buffer>,
+    file_name: String,
+    entries: Vec<TimelineEntry>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl TimelineViewDelegate {
+    /// Opens a read-only editor showing the differences between the revision and the buffer.
+    fn compare(&self, entry: &TimelineEntry, cx: &mut ViewContext<Picker<Self>>) {
+        let buffer = self.buffer.read(cx);
+        let current_text = buffer.text();
+        let language = buffer.language().cloned();
+        let revision_content = entry.content.to_string();
+        let title = format!("{} ({})", self.file_name, entry.absolute_time);
+
+        self.workspace
+            .update(cx, |workspace, cx| {
+                let project = workspace.project().clone();
+                let diff_buffer = project.update(cx, |project, cx| {
+                    project.create_local_buffer(&current_text, language, cx)
+                });
+                let multi_buffer = cx.new_model(|cx| {
+                    MultiBuffer::singleton(diff_buffer.clone(), cx).with_title(title)
+                });
+                let editor = cx.new_view(|cx| {
+                    let mut editor =
+                        Editor::for_multibuffer(multi_buffer, Some(project.clone()), false, cx);
+                    editor.set_read_only(true);
+                    cx.subscribe(&diff_buffer, |editor, _, event, cx| {
+                        if let language::Event::DiffBaseChanged = event {
+                            editor.expand_all_hunk_diffs(&ExpandAllHunkDiffs, cx);
+                        }
+                    })
+                    .detach();
+                    editor
+                });
+                diff_buffer.update(cx, |buffer, cx| {
+                    buffer.set_diff_base(Some(revision_content), cx)
+                });
+                workspace.add_item_to_active_pane(Box::new(editor), None, cx);
+            })
+            .log_err();
+    }
+
+    /// Replaces the contents of the buffer with the revision, as a single undoable edit.
+    fn restore(&self, entry: &TimelineEntry, cx: &mut ViewContext<Picker<Self>>) {
+        let buffer = self.buffer.clone();
+        let diff = buffer.read(cx).diff(entry.content.to_string(), cx);
+        cx.spawn(|_, mut cx| async move {
+            let diff = diff.await;
+            buffer.update(&mut cx, |buffer, cx| {
+                buffer.apply_diff(diff, cx);
+            })
+        })
+        .detach_and_log_err(cx);
+
+        let focus_handle = self.editor.focus_handle(cx);
+        cx.focus(&focus_handle);
+    }
+}
+
+impl PickerDelegate for TimelineViewDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        format!("Search revisions of {}...", self.file_name).into()
+    }
+
+    fn no_matches_text(&self, _cx: &mut WindowContext) -> SharedString {
+        "No revisions recorded for this file".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let candidates = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(id, entry)| {
+                StringMatchCandidate::new(
+                    id,
+                    format!("{} {}", entry.relative_time, entry.absolute_time),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if query.is_empty() {
+            self.matches = candidates
+                .into_iter()
+                .map(|candidate| StringMatch {
+                    candidate_id: candidate.id,
+                    score: Default::default(),
+                    positions: Default::default(),
+                    string: candidate.string,
+                })
+                .collect();
+        } else {
+            // Revisions are kept in chronological order, rather than sorted by score.
+            let mut matches = cx.background_executor().block(fuzzy::match_strings(
+                &candidates,
+                &query,
+                false,
+                candidates.len(),
+                &Default::default(),
+                cx.background_executor().clone(),
+            ));
+            matches.sort_by_key(|mat| mat.candidate_id);
+            self.matches = matches;
+        }
+        self.selected_index = 0;
+        Task::ready(())
+    }
+
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(entry) = self
+            .matches
+            .get(self.selected_index)
+            .and_then(|mat| self.entries.get(mat.candidate_id))
+        else {
+            return;
+        };
+
+        if secondary {
+            self.restore(entry, cx);
+        } else {
+            self.compare(entry, cx);
+        }
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.timeline_view
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        let entry = self.entries.get(mat.candidate_id)?;
+
+        let changes = if entry.insertions == 0 && entry.deletions == 0 {
+            h_flex().child(
+                Label::new("Current")
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+        } else {
+            h_flex()
+                .gap_1()
+                .child(
+                    Label::new(format!("+{}", entry.insertions))
+                        .size(LabelSize::Small)
+                        .color(Color::Created),
+                )
+                .child(
+                    Label::new(format!("-{}", entry.deletions))
+                        .size(LabelSize::Small)
+                        .color(Color::Deleted),
+                )
+        };
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .start_slot(
+                    Icon::new(IconName::HistoryRerun)
+                        .size(IconSize::Small)
+                        .color(Color::Muted),
+                )
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(Label::new(entry.relative_time.clone()))
+                        .child(
+                            Label::new(entry.absolute_time.clone())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                )
+                .end_slot(
+                    h_flex().gap_2().child(changes).child(
+                        Label::new(format_size(entry.revision.size))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+                ),
+        )
+    }
+
+    fn render_footer(&self, cx: &mut ViewContext<Picker<Self>>) -> Option<gpui::AnyElement> {
+        if self.matches.is_empty() {
+            return None;
+        }
+
+        Some(
+            h_flex()
+                .w_full()
+                .p_2()
+                .gap_2()
+                .justify_end()
+                .border_t_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(
+                    Button::new("compare-revision", "Compare")
+                        .label_size(LabelSize::Small)
+                        .when_some(KeyBinding::for_action(&menu::Confirm, cx), |button, kb| {
+                            button.key_binding(kb)
+                        })
+                        .on_click(|_, cx| cx.dispatch_action(Box::new(menu::Confirm))),
+                )
+                .child(
+                    Button::new("restore-revision", "Restore")
+                        .label_size(LabelSize::Small)
+                        .when_some(
+                            KeyBinding::for_action(&menu::SecondaryConfirm, cx),
+                            |button, kb| button.key_binding(kb),
+                        )
+                        .on_click(|_, cx| cx.dispatch_action(Box::new(menu::SecondaryConfirm))),
+                )
+                .into_any_element(),
+        )
+    }
+}
+
+fn format_size(size: u64) -> String {
+    const KB: u64 = 1024;
+    const MB: u64 = 1024 * KB;
+    if size >= MB {
+        format!("{:.1} MB", size as f64 / MB as f64)
+    } else if size >= KB {
+        format!("{:.1} KB", size as f64 / KB as f64)
+    } else {
+        format!("{size} B")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use settings::Settings;
+    use workspace::AppState;
+
+    use super::*;
+    use crate::FileHistorySettings;
+
+    #[gpui::test]
+    async fn test_timeline_compare_and_restore(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/test_timeline", json!({ "a.txt": "one\n" }))
+            .await;
+        let limits = cx.update(|cx| FileHistorySettings::get_global(cx).limits());
+        DB.record_revision("/test_timeline/a.txt".into(), "zero\n".into(), 1, limits)
+            .await
+            .unwrap();
+
+        let project = Project::test(fs, ["/test_timeline".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let worktree_id = project.update(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+        let editor = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_path((worktree_id, "a.txt"), None, true, cx)
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+        cx.run_until_parked();
+        DB.write(|_| ()).await;
+
+        // Opening the file recorded its current contents, listed before the older revision.
+        let picker = open_timeline(&workspace, cx);
+        picker.update(cx, |picker, _| {
+            let entries = picker
+                .delegate
+                .matches
+                .iter()
+                .map(|mat| {
+                    let entry = &picker.delegate.entries[mat.candidate_id];
+                    (entry.content.to_string(), entry.insertions, entry.deletions)
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                entries,
+                [("one\n".to_string(), 0, 0), ("zero\n".to_string(), 1, 1)]
+            );
+        });
+
+        // Comparing opens a read-only editor with the current contents of the file.
+        cx.dispatch_action(menu::SelectNext);
+        cx.dispatch_action(menu::Confirm);
+        cx.run_until_parked();
+        let diff_editor = workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<TimelineView>(cx).is_none());
+            workspace.active_item_as::<Editor>(cx).unwrap()
+        });
+        assert_ne!(diff_editor.entity_id(), editor.entity_id());
+        diff_editor.update(cx, |diff_editor, cx| {
+            assert!(diff_editor.read_only(cx));
+            assert_eq!(diff_editor.text(cx), "one\n");
+        });
+
+        // Restoring replaces the contents of the buffer, as a single undoable edit.
+        workspace.update(cx, |workspace, cx| workspace.activate_item(&editor, cx));
+        open_timeline(&workspace, cx);
+        cx.dispatch_action(menu::SelectNext);
+        cx.dispatch_action(menu::SecondaryConfirm);
+        cx.run_until_parked();
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<TimelineView>(cx).is_none());
+        });
+        editor.update(cx, |editor, cx| {
+            assert_eq!(editor.text(cx), "zero\n");
+            editor.undo(&editor::actions::Undo, cx);
+            assert_eq!(editor.text(cx), "one\n");
+        });
+    }
+
+    fn open_timeline(
+        workspace: &View<Workspace>,
+        cx: &mut VisualTestContext,
+    ) -> View<Picker<TimelineViewDelegate>> {
+        cx.dispatch_action(ToggleTimeline);
+        cx.run_until_parked();
+        workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_modal::<TimelineView>(cx)
+                .unwrap()
+                .read(cx)
+                .picker
+                .clone()
+        })
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        // Writes to the history database happen on its own thread.
+        cx.executor().allow_parking();
+        cx.update(|cx| {
+            let state = AppState::test(cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            crate::init(cx);
+            state
+        })
+    }
+}
@@ -4,6 +4,8 @@ use collections::HashMap;
 
 use gpui::{AppContext, AssetSource, Global};
 use serde_derive::Deserialize;
+use settings::Settings;
+use theme::{IconTheme, ThemeSettings};
 use util::{maybe, paths::PathExt};
 
 #[derive(Deserialize, Debug)]
@@ -48,7 +50,17 @@ impl FileIcons {
             })
     }
 
+    /// Returns whether to show file icons where they're optional, which is when the given
+    /// setting turns them on, or when an icon theme is selected.
+    pub fn should_show(enabled_in_settings: bool, cx: &AppContext) -> bool {
+        enabled_in_settings || active_icon_theme(cx).is_some()
+    }
+
     pub fn get_icon(path: &Path, cx: &AppContext) -> Option<Arc<str>> {
+        if let Some(icon) = active_icon_theme(cx).and_then(|theme| theme.file_icon(path)) {
+            return Some(icon.into());
+        }
+
         let this = cx.try_global::<Self>()?;
 
         // FIXME: Associate a type with the languages and have the file's language
@@ -74,6 +86,10 @@ impl FileIcons {
     }
 
     pub fn get_folder_icon(expanded: bool, cx: &AppContext) -> Option<Arc<str>> {
+        if let Some(icon) = active_icon_theme(cx).and_then(|theme| theme.folder_icon(expanded)) {
+            return Some(icon.into());
+        }
+
         let this = cx.try_global::<Self>()?;
 
         let key = if expanded {
@@ -86,6 +102,10 @@ impl FileIcons {
     }
 
     pub fn get_chevron_icon(expanded: bool, cx: &AppContext) -> Option<Arc<str>> {
+        if let Some(icon) = active_icon_theme(cx).and_then(|theme| theme.chevron_icon(expanded)) {
+            return Some(icon.into());
+        }
+
         let this = cx.try_global::<Self>()?;
 
         let key = if expanded {
@@ -97,3 +117,9 @@ impl FileIcons {
         this.get_type_icon(key)
     }
 }
+
+/// Returns the icon theme selected in the settings, whose icons take precedence over the built-in
+/// ones.
+fn active_icon_theme(cx: &AppContext) -> Option<Arc<IconTheme>> {
+    ThemeSettings::get_global(cx).active_icon_theme(cx)
+}
@@ -1,26 +1,85 @@
-use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{anyhow, Result};
 use futures::{io::BufReader, stream::BoxStream, AsyncBufReadExt, AsyncReadExt, StreamExt};
-use http::HttpClient;
+use http::{AsyncBody, HttpClient, Method, Request as HttpRequest};
+use isahc::config::Configurable;
 use serde::{Deserialize, Serialize};
+use strum::EnumIter;
 
 pub const API_URL: &str = "https://generativelanguage.googleapis.com";
 
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, EnumIter)]
+pub enum Model {
+    #[serde(rename = "gemini-1.5-pro")]
+    Gemini15Pro,
+    #[serde(rename = "gemini-1.5-flash")]
+    #[default]
+    Gemini15Flash,
+    #[serde(rename = "custom")]
+    Custom { name: String, max_tokens: usize },
+}
+
+impl Model {
+    pub fn from_id(id: &str) -> Result<Self> {
+        if id.starts_with("gemini-1.5-pro") {
+            Ok(Self::Gemini15Pro)
+        } else if id.starts_with("gemini-1.5-flash") {
+            Ok(Self::Gemini15Flash)
+        } else {
+            Err(anyhow!("invalid model id: {id}"))
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Gemini15Pro => "gemini-1.5-pro",
+            Self::Gemini15Flash => "gemini-1.5-flash",
+            Self::Custom { name, .. } => name,
+        }
+    }
+
+    pub fn display_name(&self) -> &str {
+        match self {
+            Self::Gemini15Pro => "Gemini 1.5 Pro",
+            Self::Gemini15Flash => "Gemini 1.5 Flash",
+            Self::Custom { name, .. } => name,
+        }
+    }
+
+    pub fn max_token_count(&self) -> usize {
+        match self {
+            Self::Gemini15Pro => 2_000_000,
+            Self::Gemini15Flash => 1_000_000,
+            Self::Custom { max_tokens, .. } => *max_tokens,
+        }
+    }
+}
+
 pub async fn stream_generate_content(
-    client: Arc<dyn HttpClient>,
+    client: &dyn HttpClient,
     api_url: &str,
     api_key: &str,
     model: &str,
     request: GenerateContentRequest,
+    low_speed_timeout: Option<Duration>,
 ) -> Result<BoxStream<'static, Result<GenerateContentResponse>>> {
     let uri = format!(
         "{}/v1beta/models/{model}:streamGenerateContent?alt=sse&key={}",
         api_url, api_key
     );
+    let mut request_builder = HttpRequest::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("Content-Type", "application/json");
 
-    let request = serde_json::to_string(&request)?;
-    let mut response = client.post_json(&uri, request.into()).await?;
+    if let Some(low_speed_timeout) = low_speed_timeout {
+        request_builder = request_builder.low_speed_timeout(100, low_speed_timeout);
+    };
+
+    let request = request_builder.body(AsyncBody::from(serde_json::to_string(&request)?))?;
+    let mut response = client.send(request).await?;
     if response.status().is_success() {
         let reader = BufReader::new(response.into_body());
         Ok(reader
@@ -52,14 +111,15 @@ pub async fn stream_generate_content(
     }
 }
 
-pub async fn count_tokens<T: HttpClient>(
+pub async fn count_tokens<T: HttpClient + ?Sized>(
     client: &T,
     api_url: &str,
     api_key: &str,
+    model: &str,
     request: CountTokensRequest,
 ) -> Result<CountTokensResponse> {
     let uri = format!(
-        "{}/v1beta/models/gemini-pro:countTokens?key={}",
+        "{}/v1beta/models/{model}:countTokens?key={}",
         api_url, api_key
     );
     let request = serde_json::to_string(&request)?;
@@ -124,7 +184,7 @@ pub struct Content {
     pub role: Role,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum Role {
     User,
@@ -10,7 +10,7 @@ use crate::{
     Path, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler,
     PlatformWindow, Point, PolychromeSprite, PromptLevel, Quad, Render, RenderGlyphParams,
     RenderImageParams, RenderSvgParams, ResizeEdge, ScaledPixels, Scene, Shadow, SharedString,
-    Size, StrikethroughStyle, Style, SubscriberSet, Subscription, TaffyLayoutEngine, Task,
+    Size, StrikethroughStyle, Style, SubscriberSet, Subscription, SvgSize, TaffyLayoutEngine, Task,
     TextStyle, TextStyleRefinement, TransformationMatrix, Underline, UnderlineStyle, View,
     VisualContext, WeakView, WindowAppearance, WindowBackgroundAppearance, WindowBounds,
     WindowControls, WindowDecorations, WindowOptions, WindowParams, WindowTextSystem,
@@ -1320,6 +1320,23 @@ impl<'a> WindowContext<'a> {
         self.window.scale_factor
     }
 
+    /// Rasterizes the given SVG data at the window's scale factor, so that it can be displayed
+    /// as an image. The resulting image is sized in device pixels.
+    pub fn rasterize_svg(&self, bytes: &[u8]) -> Result<ImageData> {
+        let pixmap = self
+            .svg_renderer
+            .render_pixmap(bytes, SvgSize::ScaleFactor(self.scale_factor()))?;
+        let mut buffer = image::RgbaImage::from_raw(pixmap.width(), pixmap.height(), pixmap.take())
+            .context("invalid SVG pixmap")?;
+
+        // Convert from RGBA to BGRA.
+        for pixel in buffer.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+
+        Ok(ImageData::new(buffer))
+    }
+
     /// The size of an em for the base font of the application. Adjusting this value allows the
     /// UI to scale, just like zooming a web page.
     pub fn rem_size(&self) -> Pixels {
@@ -2,6 +2,8 @@ mod providers;
 mod registry;
 mod store;
 
+pub use crate::providers::npm::*;
+pub use crate::providers::python::*;
 pub use crate::providers::rustdoc::*;
 pub use crate::registry::*;
 pub use crate::store::*;
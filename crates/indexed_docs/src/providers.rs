@@ -1 +1,21 @@
+pub mod npm;
+pub mod python;
 pub mod rustdoc;
+
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+/// Returns the directory for the database of a provider that indexes the packages installed in
+/// a single project, so that the packages of different projects are kept apart.
+fn project_database_dir(provider_dir: &str, project_root: &Path) -> PathBuf {
+    let digest = Sha256::digest(project_root.to_string_lossy().as_bytes());
+    let project_dir = digest[..8]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    paths::support_dir()
+        .join("docs")
+        .join(provider_dir)
+        .join(project_dir)
+}
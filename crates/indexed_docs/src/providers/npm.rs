@@ -0,0 +1,243 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use collections::VecDeque;
+use fs::Fs;
+use futures::StreamExt;
+use serde::Deserialize;
+
+use crate::providers::project_database_dir;
+use crate::{IndexedDocsDatabase, IndexedDocsProvider, PackageName, ProviderId};
+
+/// The maximum number of type declaration files to index for a single package.
+const MAX_DECLARATION_FILES: usize = 500;
+
+/// The fields of a `package.json` that are shown in the docs for a package.
+#[derive(Debug, Default, Deserialize)]
+struct PackageJson {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    version: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+/// Indexes the npm packages installed in a project's `node_modules`, using their READMEs and
+/// TypeScript declaration files.
+pub struct NpmPackageProvider {
+    fs: Arc<dyn Fs>,
+    project_root: PathBuf,
+}
+
+impl NpmPackageProvider {
+    pub fn id() -> ProviderId {
+        ProviderId("npm".into())
+    }
+
+    pub fn new(fs: Arc<dyn Fs>, project_root: PathBuf) -> Self {
+        Self { fs, project_root }
+    }
+}
+
+#[async_trait]
+impl IndexedDocsProvider for NpmPackageProvider {
+    fn id(&self) -> ProviderId {
+        Self::id()
+    }
+
+    fn database_path(&self) -> PathBuf {
+        project_database_dir("npm", &self.project_root).join("npm-db.1.mdb")
+    }
+
+    async fn index(&self, package: PackageName, database: Arc<IndexedDocsDatabase>) -> Result<()> {
+        let node_modules_path = self.project_root.join("node_modules");
+        let package_path = node_modules_path.join(package.as_ref());
+        if !self.fs.is_dir(&package_path).await {
+            if self.fs.is_dir(&node_modules_path).await {
+                bail!("no package named '{package}' in node_modules. if it's a dependency, try running `npm install`");
+            } else {
+                bail!("no node_modules directory. run `npm install`");
+            }
+        }
+
+        let package_json = self
+            .fs
+            .load(&package_path.join("package.json"))
+            .await
+            .ok()
+            .and_then(|contents| serde_json::from_str::<PackageJson>(&contents).ok())
+            .unwrap_or_default();
+        let readme = load_readme(self.fs.as_ref(), &package_path).await;
+        database
+            .insert(
+                package.to_string(),
+                package_markdown(&package, &package_json, readme.as_deref()),
+            )
+            .await?;
+
+        // Packages without their own types are often typed by a package from DefinitelyTyped.
+        let mut declaration_files =
+            find_declaration_files(self.fs.as_ref(), &package_path, MAX_DECLARATION_FILES).await?;
+        let mut types_root = package_path.clone();
+        if declaration_files.is_empty() {
+            let types_path = node_modules_path.join(types_package_name(&package));
+            if self.fs.is_dir(&types_path).await {
+                declaration_files =
+                    find_declaration_files(self.fs.as_ref(), &types_path, MAX_DECLARATION_FILES)
+                        .await?;
+                types_root = types_path;
+            }
+        }
+
+        for path in declaration_files {
+            let Ok(contents) = self.fs.load(&path).await else {
+                continue;
+            };
+            let Ok(relative_path) = path.strip_prefix(&types_root) else {
+                continue;
+            };
+            let key = format!("{package}/{}", relative_path.to_string_lossy());
+            let markdown = format!("# {key}\n\n```ts\n{}\n```\n", contents.trim_end());
+            database.insert(key, markdown).await?;
+        }
+
+        Ok(())
+    }
+}
+
+async fn load_readme(fs: &dyn Fs, package_path: &Path) -> Option<String> {
+    for file_name in [
+        "README.md",
+        "readme.md",
+        "Readme.md",
+        "README.markdown",
+        "README",
+    ] {
+        if let Ok(contents) = fs.load(&package_path.join(file_name)).await {
+            return Some(contents);
+        }
+    }
+    None
+}
+
+fn package_markdown(
+    package: &PackageName,
+    package_json: &PackageJson,
+    readme: Option<&str>,
+) -> String {
+    let name = package_json.name.as_deref().unwrap_or(package.as_ref());
+    let mut markdown = match &package_json.version {
+        Some(version) => format!("# {name} {version}\n\n"),
+        None => format!("# {name}\n\n"),
+    };
+    if let Some(description) = &package_json.description {
+        markdown.push_str(description);
+        markdown.push_str("\n\n");
+    }
+    if let Some(readme) = readme {
+        markdown.push_str(readme.trim());
+        markdown.push('\n');
+    }
+    markdown
+}
+
+/// Returns the name of the DefinitelyTyped package for the given package, e.g. `@types/node` for
+/// `node` and `@types/babel__core` for `@babel/core`.
+fn types_package_name(package: &str) -> String {
+    match package.strip_prefix('@') {
+        Some(scoped_package) => format!("@types/{}", scoped_package.replacen('/', "__", 1)),
+        None => format!("@types/{package}"),
+    }
+}
+
+/// Returns the paths of the TypeScript declaration files in a package, excluding the ones of the
+/// packages nested in it.
+async fn find_declaration_files(
+    fs: &dyn Fs,
+    package_path: &Path,
+    limit: usize,
+) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs_to_visit = VecDeque::from([package_path.to_path_buf()]);
+    while let Some(dir) = dirs_to_visit.pop_front() {
+        let mut entries = fs.read_dir(&dir).await?;
+        while let Some(entry) = entries.next().await {
+            let path = entry?;
+            let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            if fs.is_dir(&path).await {
+                if file_name != "node_modules" && !file_name.starts_with('.') {
+                    dirs_to_visit.push_back(path);
+                }
+            } else if is_declaration_file(file_name) {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    files.truncate(limit);
+    Ok(files)
+}
+
+fn is_declaration_file(file_name: &str) -> bool {
+    [".d.ts", ".d.mts", ".d.cts"]
+        .iter()
+        .any(|extension| file_name.ends_with(extension))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_types_package_name() {
+        assert_eq!(types_package_name("node"), "@types/node");
+        assert_eq!(types_package_name("@babel/core"), "@types/babel__core");
+    }
+
+    #[test]
+    fn test_is_declaration_file() {
+        assert!(is_declaration_file("index.d.ts"));
+        assert!(is_declaration_file("index.d.mts"));
+        assert!(!is_declaration_file("index.ts"));
+        assert!(!is_declaration_file("index.js"));
+    }
+
+    #[test]
+    fn test_package_markdown() {
+        let package_json = PackageJson {
+            name: Some("left-pad".into()),
+            version: Some("1.3.0".into()),
+            description: Some("String left pad".into()),
+        };
+        assert_eq!(
+            package_markdown(
+                &"left-pad".into(),
+                &package_json,
+                Some("## Usage\n\nleftPad('foo', 5)\n\n")
+            ),
+            indoc! {"
+                # left-pad 1.3.0
+
+                String left pad
+
+                ## Usage
+
+                leftPad('foo', 5)
+            "}
+        );
+
+        assert_eq!(
+            package_markdown(&"left-pad".into(), &PackageJson::default(), None),
+            "# left-pad\n\n"
+        );
+    }
+}
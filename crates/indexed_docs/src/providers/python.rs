@@ -0,0 +1,149 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use fs::Fs;
+use serde::Deserialize;
+
+use crate::providers::project_database_dir;
+use crate::{IndexedDocsDatabase, IndexedDocsProvider, PackageName, ProviderId};
+
+/// A script that imports a package and prints the docstrings of its modules as Markdown.
+const DOCSTRINGS_SCRIPT: &str = include_str!("./python/docstrings.py");
+
+/// The maximum number of modules to index for a single package.
+const MAX_MODULES: usize = 500;
+
+/// The virtual environments in a project that are used to find its packages, in order.
+const VIRTUAL_ENV_DIRS: &[&str] = &[".venv", "venv", "env"];
+
+#[derive(Debug, Deserialize)]
+struct ModuleDocs {
+    name: String,
+    markdown: String,
+}
+
+/// Indexes the docstrings of the Python packages installed in a project's virtual environment,
+/// or in the `python3` on the `PATH` when the project doesn't have one.
+///
+/// Indexing imports the package and its modules, just as `pydoc` does, which runs code from the
+/// project. Callers must only index packages of projects that the user has allowed to do so.
+pub struct PythonPackageProvider {
+    fs: Arc<dyn Fs>,
+    project_root: PathBuf,
+}
+
+impl PythonPackageProvider {
+    pub fn id() -> ProviderId {
+        ProviderId("python".into())
+    }
+
+    pub fn new(fs: Arc<dyn Fs>, project_root: PathBuf) -> Self {
+        Self { fs, project_root }
+    }
+
+    async fn python_path(&self) -> PathBuf {
+        for virtual_env_dir in VIRTUAL_ENV_DIRS {
+            let python_path = if cfg!(windows) {
+                self.project_root
+                    .join(virtual_env_dir)
+                    .join("Scripts/python.exe")
+            } else {
+                self.project_root.join(virtual_env_dir).join("bin/python")
+            };
+            if self.fs.is_file(&python_path).await {
+                return python_path;
+            }
+        }
+
+        PathBuf::from(if cfg!(windows) { "python" } else { "python3" })
+    }
+}
+
+#[async_trait]
+impl IndexedDocsProvider for PythonPackageProvider {
+    fn id(&self) -> ProviderId {
+        Self::id()
+    }
+
+    fn database_path(&self) -> PathBuf {
+        project_database_dir("python", &self.project_root).join("python-db.1.mdb")
+    }
+
+    async fn index(&self, package: PackageName, database: Arc<IndexedDocsDatabase>) -> Result<()> {
+        let python_path = self.python_path().await;
+        let output = smol::process::Command::new(&python_path)
+            .current_dir(&self.project_root)
+            .arg("-c")
+            .arg(DOCSTRINGS_SCRIPT)
+            .arg(package.as_ref())
+            .arg(MAX_MODULES.to_string())
+            .output()
+            .await
+            .with_context(|| format!("failed to run {python_path:?}"))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let error = stderr.lines().last().unwrap_or_default();
+            bail!("failed to import '{package}' with {python_path:?}: {error}");
+        }
+
+        let modules = serde_json::from_slice::<Vec<ModuleDocs>>(&output.stdout)
+            .context("invalid output from the Python docstrings script")?;
+        for module in modules {
+            database.insert(module.name, module.markdown).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::Path;
+
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_python_path(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/with-venv",
+            json!({
+                "pyproject.toml": "",
+                "env": { "bin": { "python": "" }, "Scripts": { "python.exe": "" } },
+                ".venv": { "bin": { "python": "" }, "Scripts": { "python.exe": "" } },
+            }),
+        )
+        .await;
+        fs.insert_tree("/without-venv", json!({ "requirements.txt": "" }))
+            .await;
+
+        // The `.venv` directory is preferred over the other virtual environments.
+        let provider = PythonPackageProvider::new(fs.clone(), "/with-venv".into());
+        assert!(provider
+            .python_path()
+            .await
+            .starts_with(Path::new("/with-venv/.venv")));
+
+        let provider = PythonPackageProvider::new(fs.clone(), "/without-venv".into());
+        assert!(provider.python_path().await.is_relative());
+    }
+
+    #[gpui::test]
+    async fn test_database_path(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        let database_path =
+            |root: &str| PythonPackageProvider::new(fs.clone(), root.into()).database_path();
+
+        // Each project has its own database, since it has its own packages.
+        assert_eq!(database_path("/project-a"), database_path("/project-a"));
+        assert_ne!(database_path("/project-a"), database_path("/project-b"));
+        assert!(database_path("/project-a").starts_with(paths::support_dir().join("docs/python")));
+    }
+}
@@ -0,0 +1,77 @@
+"""Prints the docs of a Python package and its submodules as Markdown.
+
+Usage: python docstrings.py <package> <max modules>
+
+Writes a JSON array of `{"name": ..., "markdown": ...}` objects to stdout, one per module.
+Modules that fail to import are skipped.
+"""
+
+import importlib
+import inspect
+import json
+import pkgutil
+import sys
+
+
+def signature(member):
+    try:
+        return str(inspect.signature(member))
+    except (TypeError, ValueError):
+        return "(...)"
+
+
+def member_markdown(name, member):
+    doc = inspect.getdoc(member) or ""
+    if inspect.isclass(member):
+        lines = [f"### class {name}{signature(member)}", "", doc]
+        for method_name, method in inspect.getmembers(member, inspect.isroutine):
+            if method_name.startswith("_") and method_name != "__init__":
+                continue
+            method_doc = inspect.getdoc(method) or ""
+            lines += ["", f"#### {name}.{method_name}{signature(method)}", "", method_doc]
+        return "\n".join(lines)
+    if inspect.isroutine(member):
+        return "\n".join([f"### {name}{signature(member)}", "", doc])
+    return None
+
+
+def module_markdown(module):
+    lines = [f"# {module.__name__}", "", inspect.getdoc(module) or ""]
+    public_names = getattr(module, "__all__", None)
+    for name, member in inspect.getmembers(module):
+        if public_names is not None:
+            if name not in public_names:
+                continue
+        elif name.startswith("_") or getattr(member, "__module__", None) != module.__name__:
+            continue
+        markdown = member_markdown(name, member)
+        if markdown:
+            lines += ["", markdown]
+    return "\n".join(lines).strip() + "\n"
+
+
+def main():
+    package_name, max_modules = sys.argv[1], int(sys.argv[2])
+    # Keep anything the modules print while being imported out of the output.
+    output, sys.stdout = sys.stdout, sys.stderr
+    package = importlib.import_module(package_name)
+    modules = [package]
+    submodules = pkgutil.walk_packages(
+        getattr(package, "__path__", []), package_name + ".", onerror=lambda name: None
+    )
+    for info in submodules:
+        if len(modules) >= max_modules:
+            break
+        if any(part.startswith("_") for part in info.name.split(".")):
+            continue
+        try:
+            modules.append(importlib.import_module(info.name))
+        except BaseException:
+            continue
+
+    docs = [{"name": module.__name__, "markdown": module_markdown(module)} for module in modules]
+    json.dump(docs, output)
+
+
+if __name__ == "__main__":
+    main()
@@ -85,6 +85,11 @@ impl IndexedDocsStore {
         }
     }
 
+    /// Returns the path to the database of this store's provider.
+    pub fn database_path(&self) -> PathBuf {
+        self.provider.database_path()
+    }
+
     pub fn latest_error_for_package(&self, package: &PackageName) -> Option<Arc<str>> {
         self.latest_errors_by_package.read().get(package).cloned()
     }
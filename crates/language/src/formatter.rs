@@ -0,0 +1,43 @@
+use std::{ops::Range, path::PathBuf, sync::Arc};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use collections::HashMap;
+
+use crate::LspAdapterDelegate;
+
+/// A formatter registered by an extension, which is used for the languages whose `formatter`
+/// setting is `{"extension": "<name>"}`.
+#[async_trait]
+pub trait FormatterProvider: Send + Sync {
+    /// Returns the name the formatter is selected by.
+    fn name(&self) -> Arc<str>;
+
+    /// Returns the languages the formatter supports, or an empty list if it supports any.
+    fn languages(&self) -> &[Arc<str>];
+
+    /// Returns how to format the given text of a buffer in the given language.
+    async fn format(
+        &self,
+        language_name: Arc<str>,
+        text: String,
+        buffer_abs_path: Option<PathBuf>,
+        delegate: Arc<dyn LspAdapterDelegate>,
+    ) -> Result<FormatterOutput>;
+}
+
+/// How to format a buffer, as returned by a [`FormatterProvider`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatterOutput {
+    /// A command that reads the buffer's text from stdin and writes the formatted text to stdout.
+    Command(FormatterCommand),
+    /// Edits to the buffer's text, whose ranges are byte offsets into the text that was formatted.
+    Edits(Vec<(Range<usize>, String)>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatterCommand {
+    pub path: PathBuf,
+    pub arguments: Vec<String>,
+    pub env: HashMap<String, String>,
+}
@@ -3,8 +3,8 @@ use crate::{
         all_language_settings, AllLanguageSettingsContent, LanguageSettingsContent,
     },
     task_context::ContextProvider,
-    with_parser, CachedLspAdapter, File, Language, LanguageConfig, LanguageId, LanguageMatcher,
-    LanguageServerName, LspAdapter, LspAdapterDelegate, PLAIN_TEXT,
+    with_parser, CachedLspAdapter, File, FormatterProvider, Language, LanguageConfig, LanguageId,
+    LanguageMatcher, LanguageServerName, LspAdapter, LspAdapterDelegate, PLAIN_TEXT,
 };
 use anyhow::{anyhow, Context as _, Result};
 use collections::{hash_map, HashMap};
@@ -49,6 +49,7 @@ struct LanguageRegistryState {
     lsp_adapters: HashMap<Arc<str>, Vec<Arc<CachedLspAdapter>>>,
     available_lsp_adapters:
         HashMap<LanguageServerName, Arc<dyn Fn() -> Arc<CachedLspAdapter> + 'static + Send + Sync>>,
+    formatters: HashMap<Arc<str>, Arc<dyn FormatterProvider>>,
     loading_languages: HashMap<LanguageId, Vec<oneshot::Sender<Result<Arc<Language>>>>>,
     subscription: (watch::Sender<()>, watch::Receiver<()>),
     theme: Option<Arc<Theme>>,
@@ -159,6 +160,7 @@ impl LanguageRegistry {
                 loading_languages: Default::default(),
                 lsp_adapters: Default::default(),
                 available_lsp_adapters: HashMap::default(),
+                formatters: HashMap::default(),
                 subscription: watch::channel(),
                 theme: Default::default(),
                 version: 0,
@@ -273,6 +275,23 @@ impl LanguageRegistry {
             .push(CachedLspAdapter::new(adapter, false));
     }
 
+    /// Registers a formatter, which can then be selected by name in the `formatter` setting.
+    pub fn register_formatter(&self, formatter: Arc<dyn FormatterProvider>) {
+        self.state
+            .write()
+            .formatters
+            .insert(formatter.name(), formatter);
+    }
+
+    pub fn remove_formatter(&self, name: &str) {
+        self.state.write().formatters.remove(name);
+    }
+
+    /// Returns the formatter registered with the given name.
+    pub fn formatter(&self, name: &str) -> Option<Arc<dyn FormatterProvider>> {
+        self.state.read().formatters.get(name).cloned()
+    }
+
     #[cfg(any(feature = "test-support", test))]
     pub fn register_fake_lsp_adapter(
         &self,
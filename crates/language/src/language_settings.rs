@@ -399,6 +399,8 @@ pub enum FormatOnSave {
     },
     /// Files should be formatted using code actions executed by language servers.
     CodeActions(HashMap<String, bool>),
+    /// Files should be formatted using a formatter provided by an extension, selected by its name.
+    Extension(Arc<str>),
 }
 
 /// Controls how whitespace should be displayedin the editor.
@@ -441,6 +443,8 @@ pub enum Formatter {
     },
     /// Files should be formatted using code actions executed by language servers.
     CodeActions(HashMap<String, bool>),
+    /// Files should be formatted using a formatter provided by an extension, selected by its name.
+    Extension(Arc<str>),
 }
 
 /// The settings for indent guides.
@@ -898,6 +902,25 @@ pub struct PrettierSettings {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_extension_formatter_deserialization() {
+        let settings: LanguageSettingsContent = serde_json::from_str(
+            r#"{
+                "formatter": { "extension": "ruff" },
+                "format_on_save": { "extension": "ruff" }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            settings.formatter,
+            Some(Formatter::Extension("ruff".into()))
+        );
+        assert_eq!(
+            settings.format_on_save,
+            Some(FormatOnSave::Extension("ruff".into()))
+        );
+    }
+
     #[test]
     pub fn test_resolve_language_servers() {
         fn language_server_names(names: &[&str]) -> Vec<LanguageServerName> {
@@ -4,7 +4,7 @@ use crate::{Location, Runnable};
 
 use anyhow::Result;
 use collections::HashMap;
-use gpui::AppContext;
+use gpui::{AppContext, Task};
 use task::{TaskTemplates, TaskVariables};
 use text::BufferId;
 
@@ -26,8 +26,8 @@ pub trait ContextProvider: Send + Sync {
         _variables: &TaskVariables,
         _location: &Location,
         _cx: &mut AppContext,
-    ) -> Result<TaskVariables> {
-        Ok(TaskVariables::default())
+    ) -> Task<Result<TaskVariables>> {
+        Task::ready(Ok(TaskVariables::default()))
     }
 
     /// Provides all tasks, associated with the current language.
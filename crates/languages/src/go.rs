@@ -460,7 +460,7 @@ impl ContextProvider for GoContextProvider {
         variables: &TaskVariables,
         location: &Location,
         cx: &mut gpui::AppContext,
-    ) -> Result<TaskVariables> {
+    ) -> Task<Result<TaskVariables>> {
         let local_abs_path = location
             .buffer
             .read(cx)
@@ -494,11 +494,11 @@ impl ContextProvider for GoContextProvider {
         let go_subtest_variable = extract_subtest_name(_subtest_name.unwrap_or(""))
             .map(|subtest_name| (GO_SUBTEST_NAME_TASK_VARIABLE.clone(), subtest_name));
 
-        Ok(TaskVariables::from_iter(
+        Task::ready(Ok(TaskVariables::from_iter(
             [go_package_variable, go_subtest_variable]
                 .into_iter()
                 .flatten(),
-        ))
+        )))
     }
 
     fn associated_tasks(
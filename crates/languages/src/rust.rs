@@ -2,7 +2,7 @@ use anyhow::{anyhow, bail, Context, Result};
 use async_compression::futures::bufread::GzipDecoder;
 use async_trait::async_trait;
 use futures::{io::BufReader, StreamExt};
-use gpui::{AppContext, AsyncAppContext};
+use gpui::{AppContext, AsyncAppContext, Task};
 use http::github::{latest_github_release, GitHubLspBinaryVersion};
 pub use language::*;
 use language_settings::all_language_settings;
@@ -392,7 +392,7 @@ impl ContextProvider for RustContextProvider {
         task_variables: &TaskVariables,
         location: &Location,
         cx: &mut gpui::AppContext,
-    ) -> Result<TaskVariables> {
+    ) -> Task<Result<TaskVariables>> {
         let local_abs_path = location
             .buffer
             .read(cx)
@@ -409,10 +409,10 @@ impl ContextProvider for RustContextProvider {
             if let Some((package_name, bin_name)) = local_abs_path
                 .and_then(|local_abs_path| package_name_and_bin_name_from_abs_path(local_abs_path))
             {
-                return Ok(TaskVariables::from_iter([
+                return Task::ready(Ok(TaskVariables::from_iter([
                     (RUST_PACKAGE_TASK_VARIABLE.clone(), package_name),
                     (RUST_BIN_NAME_TASK_VARIABLE.clone(), bin_name),
-                ]));
+                ])));
             }
         }
 
@@ -420,13 +420,13 @@ impl ContextProvider for RustContextProvider {
             .and_then(|local_abs_path| local_abs_path.parent())
             .and_then(human_readable_package_name)
         {
-            return Ok(TaskVariables::from_iter([(
+            return Task::ready(Ok(TaskVariables::from_iter([(
                 RUST_PACKAGE_TASK_VARIABLE.clone(),
                 package_name,
-            )]));
+            )])));
         }
 
-        Ok(TaskVariables::default())
+        Task::ready(Ok(TaskVariables::default()))
     }
 
     fn associated_tasks(
@@ -628,6 +628,40 @@ impl Element for MarkdownElement {
                                 builder.push_text_style(self.style.link.clone())
                             }
                         }
+                        MarkdownTag::Table(_) => {
+                            builder.push_div(
+                                div()
+                                    .flex()
+                                    .flex_col()
+                                    .mb_2()
+                                    .border_1()
+                                    .border_color(self.style.rule_color),
+                                range,
+                                markdown_end,
+                            );
+                        }
+                        MarkdownTag::TableHead => {
+                            builder.push_text_style(TextStyleRefinement {
+                                font_weight: Some(FontWeight::BOLD),
+                                ..Default::default()
+                            });
+                            builder.push_div(
+                                div()
+                                    .flex()
+                                    .border_b_1()
+                                    .border_color(self.style.rule_color),
+                                range,
+                                markdown_end,
+                            );
+                        }
+                        MarkdownTag::TableRow => {
+                            builder.push_div(div().flex(), range, markdown_end);
+                        }
+                        MarkdownTag::TableCell => {
+                            // Without `w_0`, cells are sized by their contents rather than
+                            // sharing the width of the table.
+                            builder.push_div(div().flex_1().w_0().px_2(), range, markdown_end);
+                        }
                         MarkdownTag::MetadataBlock(_) => {}
                         _ => log::error!("unsupported markdown tag {:?}", tag),
                     }
@@ -669,6 +703,13 @@ impl Element for MarkdownElement {
                             builder.pop_text_style()
                         }
                     }
+                    MarkdownTagEnd::Table => builder.pop_div(),
+                    MarkdownTagEnd::TableHead => {
+                        builder.pop_div();
+                        builder.pop_text_style()
+                    }
+                    MarkdownTagEnd::TableRow => builder.pop_div(),
+                    MarkdownTagEnd::TableCell => builder.pop_div(),
                     _ => log::error!("unsupported markdown tag end: {:?}", tag),
                 },
                 MarkdownEvent::Text => {
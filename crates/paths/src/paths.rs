@@ -242,6 +242,12 @@ pub fn local_tasks_file_relative_path() -> &'static Path {
     LOCAL_TASKS_FILE_RELATIVE_PATH.get_or_init(|| Path::new(".zed/tasks.json"))
 }
 
+/// Returns the relative path to the folder of assistant rules files within a project.
+pub fn local_assistant_rules_folder_relative_path() -> &'static Path {
+    static LOCAL_ASSISTANT_RULES_FOLDER_RELATIVE_PATH: OnceLock<&Path> = OnceLock::new();
+    LOCAL_ASSISTANT_RULES_FOLDER_RELATIVE_PATH.get_or_init(|| Path::new(".zed/assistant"))
+}
+
 /// Returns the relative path to a `.vscode/tasks.json` file within a project.
 pub fn local_vscode_tasks_file_relative_path() -> &'static Path {
     static LOCAL_VSCODE_TASKS_FILE_RELATIVE_PATH: OnceLock<&Path> = OnceLock::new();
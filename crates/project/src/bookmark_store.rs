@@ -0,0 +1,311 @@
+//! Bookmarks are user-placed markers in project files that can be listed and jumped to.
+//!
+//! While the buffer of a bookmarked file is open, the bookmark is anchored to the buffer's text,
+//! so it follows the edits made around it. Once the buffer is released (or before it was ever
+//! opened, e.g. when bookmarks are restored from the database), the bookmark falls back to the
+//! last known point and gets re-anchored as soon as the buffer is opened again.
+
+use crate::{
+    buffer_store::{BufferStore, BufferStoreEvent},
+    Item as _, ProjectPath,
+};
+use collections::HashMap;
+use gpui::{AppContext, EntityId, EventEmitter, Model, ModelContext, Subscription, WeakModel};
+use language::{Buffer, Point, ToPoint as _};
+use std::sync::Arc;
+use text::Anchor;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BookmarkId(usize);
+
+#[derive(Clone, Debug)]
+pub struct Bookmark {
+    pub id: BookmarkId,
+    pub project_path: ProjectPath,
+    pub label: Option<Arc<str>>,
+    /// The point of the bookmark, as of the last time its buffer was observed.
+    point: Point,
+    anchor: Option<(WeakModel<Buffer>, Anchor)>,
+}
+
+impl Bookmark {
+    /// Returns the current position of the bookmark in its file.
+    pub fn point(&self, cx: &AppContext) -> Point {
+        self.anchor
+            .as_ref()
+            .and_then(|(buffer, anchor)| Some(anchor.to_point(buffer.upgrade()?.read(cx))))
+            .unwrap_or(self.point)
+    }
+
+    /// Returns the anchor of the bookmark, if its buffer is currently open.
+    pub fn anchor(&self) -> Option<Anchor> {
+        let (buffer, anchor) = self.anchor.as_ref()?;
+        buffer.upgrade().map(|_| *anchor)
+    }
+
+    /// Returns the buffer the bookmark is anchored in, if it's currently open.
+    pub fn buffer(&self) -> Option<Model<Buffer>> {
+        self.anchor.as_ref()?.0.upgrade()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BookmarkStoreEvent {
+    BookmarksChanged,
+}
+
+/// Keeps track of all bookmarks placed in the files of a project.
+pub struct BookmarkStore {
+    bookmarks: Vec<Bookmark>,
+    next_bookmark_id: usize,
+    buffer_release_subscriptions: HashMap<EntityId, Subscription>,
+    buffer_store: Model<BufferStore>,
+    _buffer_store_subscription: Subscription,
+}
+
+impl EventEmitter<BookmarkStoreEvent> for BookmarkStore {}
+
+impl BookmarkStore {
+    pub fn new(buffer_store: Model<BufferStore>, cx: &mut ModelContext<Self>) -> Self {
+        Self {
+            bookmarks: Vec::new(),
+            next_bookmark_id: 0,
+            buffer_release_subscriptions: HashMap::default(),
+            _buffer_store_subscription: cx.subscribe(&buffer_store, Self::on_buffer_store_event),
+            buffer_store,
+        }
+    }
+
+    /// Returns all bookmarks, in the order they were added.
+    pub fn bookmarks(&self) -> &[Bookmark] {
+        &self.bookmarks
+    }
+
+    pub fn bookmark(&self, id: BookmarkId) -> Option<&Bookmark> {
+        self.bookmarks.iter().find(|bookmark| bookmark.id == id)
+    }
+
+    /// Returns the bookmarks anchored in the given buffer.
+    pub fn bookmarks_for_buffer<'a>(
+        &'a self,
+        buffer: &'a Model<Buffer>,
+    ) -> impl 'a + Iterator<Item = (BookmarkId, Anchor)> {
+        let buffer_id = buffer.entity_id();
+        self.bookmarks.iter().filter_map(move |bookmark| {
+            let (bookmark_buffer, anchor) = bookmark.anchor.as_ref()?;
+            (bookmark_buffer.entity_id() == buffer_id).then_some((bookmark.id, *anchor))
+        })
+    }
+
+    /// Adds a bookmark at the given position of a buffer, or removes the existing bookmark
+    /// on the position's row.
+    pub fn toggle(
+        &mut self,
+        buffer: &Model<Buffer>,
+        position: Anchor,
+        cx: &mut ModelContext<Self>,
+    ) -> Option<BookmarkId> {
+        let row = position.to_point(buffer.read(cx)).row;
+        let existing = self
+            .bookmarks_for_buffer(buffer)
+            .find(|(_, anchor)| anchor.to_point(buffer.read(cx)).row == row)
+            .map(|(id, _)| id);
+        if let Some(existing) = existing {
+            self.remove(existing, cx);
+            None
+        } else {
+            self.add(buffer, position, None, cx)
+        }
+    }
+
+    /// Adds a bookmark at the given position of a buffer. Buffers without a file can't be bookmarked.
+    pub fn add(
+        &mut self,
+        buffer: &Model<Buffer>,
+        position: Anchor,
+        label: Option<String>,
+        cx: &mut ModelContext<Self>,
+    ) -> Option<BookmarkId> {
+        let project_path = buffer.read(cx).project_path(cx)?;
+        let point = position.to_point(buffer.read(cx));
+        let id = self.next_id();
+        self.bookmarks.push(Bookmark {
+            id,
+            project_path,
+            label: label.map(Arc::from),
+            point,
+            anchor: Some((buffer.downgrade(), position)),
+        });
+        self.observe_buffer_release(buffer, cx);
+        cx.emit(BookmarkStoreEvent::BookmarksChanged);
+        Some(id)
+    }
+
+    /// Adds a bookmark to a file that may not be open yet.
+    /// The bookmark gets anchored once a buffer for the file is opened.
+    pub fn add_detached(
+        &mut self,
+        project_path: ProjectPath,
+        point: Point,
+        label: Option<String>,
+        cx: &mut ModelContext<Self>,
+    ) -> BookmarkId {
+        let id = self.next_id();
+        self.bookmarks.push(Bookmark {
+            id,
+            project_path: project_path.clone(),
+            label: label.map(Arc::from),
+            point,
+            anchor: None,
+        });
+
+        let open_buffer = self
+            .buffer_store
+            .read(cx)
+            .buffers()
+            .find(|buffer| buffer.read(cx).project_path(cx).as_ref() == Some(&project_path));
+        if let Some(buffer) = open_buffer {
+            self.attach_to_buffer(&buffer, cx);
+        }
+
+        cx.emit(BookmarkStoreEvent::BookmarksChanged);
+        id
+    }
+
+    pub fn remove(&mut self, id: BookmarkId, cx: &mut ModelContext<Self>) {
+        let Some(ix) = self.bookmarks.iter().position(|bookmark| bookmark.id == id) else {
+            return;
+        };
+        let bookmark = self.bookmarks.remove(ix);
+
+        // Stop observing the bookmark's buffer once no bookmark is anchored in it.
+        if let Some((buffer, _)) = bookmark.anchor {
+            let buffer_id = buffer.entity_id();
+            let is_buffer_bookmarked = self.bookmarks.iter().any(|bookmark| {
+                bookmark
+                    .anchor
+                    .as_ref()
+                    .map_or(false, |(buffer, _)| buffer.entity_id() == buffer_id)
+            });
+            if !is_buffer_bookmarked {
+                self.buffer_release_subscriptions.remove(&buffer_id);
+            }
+        }
+
+        cx.emit(BookmarkStoreEvent::BookmarksChanged);
+    }
+
+    pub fn set_label(
+        &mut self,
+        id: BookmarkId,
+        label: Option<String>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if let Some(bookmark) = self.bookmarks.iter_mut().find(|bookmark| bookmark.id == id) {
+            bookmark.label = label.filter(|label| !label.is_empty()).map(Arc::from);
+            cx.emit(BookmarkStoreEvent::BookmarksChanged);
+        }
+    }
+
+    pub fn clear(&mut self, cx: &mut ModelContext<Self>) {
+        self.buffer_release_subscriptions.clear();
+        if !self.bookmarks.is_empty() {
+            self.bookmarks.clear();
+            cx.emit(BookmarkStoreEvent::BookmarksChanged);
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn observed_buffer_count(&self) -> usize {
+        self.buffer_release_subscriptions.len()
+    }
+
+    fn next_id(&mut self) -> BookmarkId {
+        let id = BookmarkId(self.next_bookmark_id);
+        self.next_bookmark_id += 1;
+        id
+    }
+
+    fn on_buffer_store_event(
+        &mut self,
+        _: Model<BufferStore>,
+        event: &BufferStoreEvent,
+        cx: &mut ModelContext<Self>,
+    ) {
+        match event {
+            BufferStoreEvent::BufferAdded(buffer) => self.attach_to_buffer(buffer, cx),
+            BufferStoreEvent::BufferChangedFilePath { buffer, .. }
+            | BufferStoreEvent::BufferSaved {
+                buffer,
+                has_changed_file: true,
+                ..
+            } => {
+                let Some(project_path) = buffer.read(cx).project_path(cx) else {
+                    return;
+                };
+                let buffer_id = buffer.entity_id();
+                let mut changed = false;
+                for bookmark in &mut self.bookmarks {
+                    if bookmark
+                        .anchor
+                        .as_ref()
+                        .map_or(false, |(buffer, _)| buffer.entity_id() == buffer_id)
+                        && bookmark.project_path != project_path
+                    {
+                        bookmark.project_path = project_path.clone();
+                        changed = true;
+                    }
+                }
+                if changed {
+                    cx.emit(BookmarkStoreEvent::BookmarksChanged);
+                }
+            }
+            BufferStoreEvent::BufferSaved { .. } => {}
+        }
+    }
+
+    fn attach_to_buffer(&mut self, buffer: &Model<Buffer>, cx: &mut ModelContext<Self>) {
+        let Some(project_path) = buffer.read(cx).project_path(cx) else {
+            return;
+        };
+
+        let snapshot = buffer.read(cx).snapshot();
+        let mut attached = false;
+        for bookmark in &mut self.bookmarks {
+            let is_detached = bookmark
+                .anchor
+                .as_ref()
+                .map_or(true, |(buffer, _)| buffer.upgrade().is_none());
+            if is_detached && bookmark.project_path == project_path {
+                let point = snapshot.clip_point(bookmark.point, text::Bias::Left);
+                bookmark.anchor = Some((buffer.downgrade(), snapshot.anchor_before(point)));
+                attached = true;
+            }
+        }
+
+        if attached {
+            self.observe_buffer_release(buffer, cx);
+            cx.emit(BookmarkStoreEvent::BookmarksChanged);
+        }
+    }
+
+    fn observe_buffer_release(&mut self, buffer: &Model<Buffer>, cx: &mut ModelContext<Self>) {
+        let buffer_id = buffer.entity_id();
+        self.buffer_release_subscriptions
+            .entry(buffer_id)
+            .or_insert_with(|| {
+                cx.observe_release(buffer, move |this, buffer, _| {
+                    this.buffer_release_subscriptions.remove(&buffer_id);
+                    for bookmark in &mut this.bookmarks {
+                        if let Some((bookmark_buffer, anchor)) = bookmark.anchor.take() {
+                            if bookmark_buffer.entity_id() == buffer_id {
+                                bookmark.point = anchor.to_point(&*buffer);
+                            } else {
+                                bookmark.anchor = Some((bookmark_buffer, anchor));
+                            }
+                        }
+                    }
+                })
+            });
+    }
+}
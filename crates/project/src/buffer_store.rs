@@ -1,7 +1,10 @@
-use crate::ProjectPath;
+use crate::{project_settings::ProjectSettings, ProjectPath};
 use anyhow::{anyhow, Context as _, Result};
 use collections::{hash_map, HashMap};
-use futures::{channel::oneshot, StreamExt as _};
+use futures::{
+    channel::{mpsc, oneshot},
+    StreamExt as _,
+};
 use gpui::{
     AppContext, AsyncAppContext, Context as _, EventEmitter, Model, ModelContext, Task, WeakModel,
 };
@@ -13,10 +16,11 @@ use rpc::{
     proto::{self, AnyProtoClient, PeerId},
     ErrorExt as _, TypedEnvelope,
 };
+use settings::Settings as _;
 use std::{io, path::Path, sync::Arc};
 use text::BufferId;
 use util::{debug_panic, maybe, ResultExt as _};
-use worktree::{File, ProjectEntryId, RemoteWorktree, Worktree};
+use worktree::{File, FileTooLarge, ProjectEntryId, RemoteWorktree, Worktree};
 
 /// A set of open buffers.
 pub struct BufferStore {
@@ -32,6 +36,25 @@ pub struct BufferStore {
     loading_remote_buffers_by_id: HashMap<BufferId, Model<Buffer>>,
     remote_buffer_listeners:
         HashMap<BufferId, Vec<oneshot::Sender<Result<Model<Buffer>, anyhow::Error>>>>,
+    loading_large_files: HashMap<ProjectPath, LargeFileLoad>,
+}
+
+/// The progress of a file being opened in large-file mode, see [`Buffer::is_large_file`].
+#[derive(Clone, Debug)]
+pub struct LargeFileLoad {
+    pub path: ProjectPath,
+    pub bytes_loaded: u64,
+    pub total_bytes: u64,
+}
+
+impl LargeFileLoad {
+    pub fn percentage(&self) -> u64 {
+        if self.total_bytes == 0 {
+            100
+        } else {
+            (self.bytes_loaded * 100 / self.total_bytes).min(100)
+        }
+    }
 }
 
 enum OpenBuffer {
@@ -71,6 +94,7 @@ impl BufferStore {
             local_buffer_ids_by_path: Default::default(),
             local_buffer_ids_by_entry_id: Default::default(),
             loading_buffers_by_path: Default::default(),
+            loading_large_files: Default::default(),
         }
     }
 
@@ -133,29 +157,24 @@ impl BufferStore {
         worktree: Model<Worktree>,
         cx: &mut ModelContext<Self>,
     ) -> Task<Result<Model<Buffer>>> {
-        let load_buffer = worktree.update(cx, |worktree, cx| {
-            let load_file = worktree.load_file(path.as_ref(), cx);
-            let reservation = cx.reserve_model();
-            let buffer_id = BufferId::from(reservation.entity_id().as_non_zero_u64());
-            cx.spawn(move |_, mut cx| async move {
-                let loaded = load_file.await?;
-                let text_buffer = cx
-                    .background_executor()
-                    .spawn(async move { text::Buffer::new(0, buffer_id, loaded.text) })
-                    .await;
-                cx.insert_model(reservation, |_| {
-                    Buffer::build(
-                        text_buffer,
-                        loaded.diff_base,
-                        Some(loaded.file),
-                        Capability::ReadWrite,
-                    )
-                })
-            })
-        });
+        let large_file_threshold = ProjectSettings::get_global(cx).large_files.threshold();
+        let load_buffer =
+            Self::load_local_buffer(path.clone(), large_file_threshold, &worktree, cx);
 
         cx.spawn(move |this, mut cx| async move {
-            let buffer = match load_buffer.await {
+            let load_result = match load_buffer.await {
+                Err(error) => match error.downcast_ref::<FileTooLarge>() {
+                    Some(&FileTooLarge { size }) => {
+                        this.update(&mut cx, |this, cx| {
+                            this.load_large_local_buffer(path.clone(), size, worktree.clone(), cx)
+                        })?
+                        .await
+                    }
+                    None => Err(error),
+                },
+                result => result,
+            };
+            let buffer = match load_result {
                 Ok(buffer) => Ok(buffer),
                 Err(error) if is_not_found_error(&error) => cx.new_model(|cx| {
                     let buffer_id = BufferId::from(cx.entity_id().as_non_zero_u64());
@@ -184,6 +203,107 @@ impl BufferStore {
         })
     }
 
+    fn load_local_buffer(
+        path: Arc<Path>,
+        max_size: u64,
+        worktree: &Model<Worktree>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Model<Buffer>>> {
+        worktree.update(cx, |worktree, cx| {
+            let load_file = worktree.load_file(path.as_ref(), Some(max_size), cx);
+            let reservation = cx.reserve_model();
+            let buffer_id = BufferId::from(reservation.entity_id().as_non_zero_u64());
+            cx.spawn(move |_, mut cx| async move {
+                let loaded = load_file.await?;
+                let text_buffer = cx
+                    .background_executor()
+                    .spawn(async move { text::Buffer::new(0, buffer_id, loaded.text) })
+                    .await;
+                cx.insert_model(reservation, |_| {
+                    let mut buffer = Buffer::build(
+                        text_buffer,
+                        loaded.diff_base,
+                        Some(loaded.file),
+                        Capability::ReadWrite,
+                    );
+                    buffer.set_encoding(loaded.encoding);
+                    buffer
+                })
+            })
+        })
+    }
+
+    /// Loads a file exceeding the large file threshold in chunks, keeping track of its progress.
+    /// The resulting buffer has no diff base, and is marked as a large file so that it's never
+    /// parsed nor registered with language servers.
+    fn load_large_local_buffer(
+        &mut self,
+        path: Arc<Path>,
+        size: u64,
+        worktree: Model<Worktree>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Model<Buffer>>> {
+        let project_path = ProjectPath {
+            worktree_id: worktree.read(cx).id(),
+            path: path.clone(),
+        };
+        let (progress_tx, mut progress_rx) = mpsc::unbounded();
+        let load_buffer = worktree.update(cx, |worktree, cx| {
+            let load_file = worktree.load_large_file(path.as_ref(), progress_tx, cx);
+            let reservation = cx.reserve_model();
+            let buffer_id = BufferId::from(reservation.entity_id().as_non_zero_u64());
+            cx.spawn(move |_, mut cx| async move {
+                let loaded = load_file.await?;
+                let text_buffer = cx
+                    .background_executor()
+                    .spawn(async move {
+                        text::Buffer::new_normalized(0, buffer_id, loaded.line_ending, loaded.text)
+                    })
+                    .await;
+                cx.insert_model(reservation, |_| {
+                    let mut buffer =
+                        Buffer::build(text_buffer, None, Some(loaded.file), Capability::ReadWrite);
+                    buffer.set_encoding(loaded.encoding);
+                    buffer.mark_as_large_file();
+                    buffer
+                })
+            })
+        });
+
+        self.loading_large_files.insert(
+            project_path.clone(),
+            LargeFileLoad {
+                path: project_path.clone(),
+                bytes_loaded: 0,
+                total_bytes: size,
+            },
+        );
+        cx.notify();
+
+        cx.spawn(move |this, mut cx| async move {
+            while let Some(bytes_loaded) = progress_rx.next().await {
+                this.update(&mut cx, |this, cx| {
+                    if let Some(load) = this.loading_large_files.get_mut(&project_path) {
+                        load.bytes_loaded = bytes_loaded;
+                        cx.notify();
+                    }
+                })?;
+            }
+
+            let buffer = load_buffer.await;
+            this.update(&mut cx, |this, cx| {
+                this.loading_large_files.remove(&project_path);
+                cx.notify();
+            })?;
+            buffer
+        })
+    }
+
+    /// Returns the files currently being opened in large-file mode.
+    pub fn loading_large_files(&self) -> impl Iterator<Item = &LargeFileLoad> {
+        self.loading_large_files.values()
+    }
+
     fn open_remote_buffer_internal(
         &self,
         path: &Arc<Path>,
@@ -299,13 +419,14 @@ impl BufferStore {
         let buffer = buffer_handle.read(cx);
         let text = buffer.as_rope().clone();
         let line_ending = buffer.line_ending();
+        let encoding = buffer.encoding();
         let version = buffer.version();
         if buffer.file().is_some_and(|file| !file.is_created()) {
             has_changed_file = true;
         }
 
         let save = worktree.update(cx, |worktree, cx| {
-            worktree.write_file(path.as_ref(), text, line_ending, cx)
+            worktree.write_file(path.as_ref(), text, line_ending, encoding, cx)
         });
 
         cx.spawn(move |this, mut cx| async move {
@@ -1,3 +1,4 @@
+pub mod bookmark_store;
 pub mod buffer_store;
 pub mod connection_manager;
 pub mod debounced_delay;
@@ -16,6 +17,7 @@ mod yarn;
 
 use anyhow::{anyhow, bail, Context as _, Result};
 use async_trait::async_trait;
+use bookmark_store::BookmarkStore;
 use buffer_store::{BufferStore, BufferStoreEvent};
 use client::{
     proto, Client, Collaborator, DevServerProjectId, PendingEntitySubscription, ProjectId,
@@ -51,9 +53,9 @@ use language::{
     },
     range_from_lsp, Bias, Buffer, BufferSnapshot, CachedLspAdapter, Capability, CodeLabel,
     ContextProvider, Diagnostic, DiagnosticEntry, DiagnosticSet, Diff, Documentation,
-    Event as BufferEvent, File as _, Language, LanguageRegistry, LanguageServerName, LocalFile,
-    LspAdapterDelegate, Patch, PendingLanguageServer, PointUtf16, TextBufferSnapshot, ToOffset,
-    ToPointUtf16, Transaction, Unclipped,
+    Event as BufferEvent, File as _, FormatterOutput, Language, LanguageRegistry,
+    LanguageServerName, LocalFile, LspAdapterDelegate, Patch, PendingLanguageServer, PointUtf16,
+    TextBufferSnapshot, ToOffset, ToPointUtf16, Transaction, Unclipped,
 };
 use log::error;
 use lsp::{
@@ -199,6 +201,7 @@ pub struct Project {
     collaborators: HashMap<proto::PeerId, Collaborator>,
     client_subscriptions: Vec<client::Subscription>,
     buffer_store: Model<BufferStore>,
+    bookmark_store: Model<BookmarkStore>,
     _subscriptions: Vec<gpui::Subscription>,
     shared_buffers: HashMap<proto::PeerId, HashSet<BufferId>>,
     #[allow(clippy::type_complexity)]
@@ -213,11 +216,15 @@ pub struct Project {
     _maintain_workspace_config: Task<Result<()>>,
     terminals: Terminals,
     current_lsp_settings: HashMap<Arc<str>, LspSettings>,
+    current_assistant_rules_folder: PathBuf,
     node: Option<Arc<dyn NodeRuntime>>,
     default_prettier: DefaultPrettier,
     prettiers_per_worktree: HashMap<WorktreeId, HashSet<Option<PathBuf>>>,
     prettier_instances: HashMap<PathBuf, PrettierInstance>,
     tasks: Model<Inventory>,
+    /// The contents of the Markdown files in each local worktree's assistant rules folder,
+    /// keyed by path.
+    assistant_rules: BTreeMap<(WorktreeId, Arc<Path>), Arc<str>>,
     hosted_project_id: Option<ProjectId>,
     dev_server_project_id: Option<client::DevServerProjectId>,
     search_history: SearchHistory,
@@ -321,6 +328,7 @@ pub enum Event {
     WorktreeRemoved(WorktreeId),
     WorktreeUpdatedEntries(WorktreeId, UpdatedEntriesSet),
     WorktreeUpdatedGitRepositories,
+    AssistantRulesChanged,
     DiskBasedDiagnosticsStarted {
         language_server_id: LanguageServerId,
     },
@@ -590,6 +598,7 @@ enum FormatOperation {
     Lsp(Vec<(Range<Anchor>, String)>),
     External(Diff),
     Prettier(Diff),
+    Extension(Vec<(Range<Anchor>, String)>),
 }
 
 impl FormatTrigger {
@@ -717,6 +726,7 @@ impl Project {
             let buffer_store = cx.new_model(|_| BufferStore::new(false));
             cx.subscribe(&buffer_store, Self::on_buffer_store_event)
                 .detach();
+            let bookmark_store = cx.new_model(|cx| BookmarkStore::new(buffer_store.clone(), cx));
 
             let yarn = YarnPathStore::new(fs.clone(), cx);
 
@@ -726,6 +736,7 @@ impl Project {
                 buffer_ordered_messages_tx: tx,
                 collaborators: Default::default(),
                 buffer_store,
+                bookmark_store,
                 shared_buffers: Default::default(),
                 loading_local_worktrees: Default::default(),
                 buffer_snapshots: Default::default(),
@@ -766,11 +777,15 @@ impl Project {
                     local_handles: Vec::new(),
                 },
                 current_lsp_settings: ProjectSettings::get_global(cx).lsp.clone(),
+                current_assistant_rules_folder: ProjectSettings::get_global(cx)
+                    .assistant_rules_folder()
+                    .to_path_buf(),
                 node: Some(node),
                 default_prettier: DefaultPrettier::default(),
                 prettiers_per_worktree: HashMap::default(),
                 prettier_instances: HashMap::default(),
                 tasks,
+                assistant_rules: BTreeMap::default(),
                 hosted_project_id: None,
                 dev_server_project_id: None,
                 search_history: Self::new_search_history(),
@@ -859,12 +874,14 @@ impl Project {
             let buffer_store = cx.new_model(|_| BufferStore::new(true));
             cx.subscribe(&buffer_store, Self::on_buffer_store_event)
                 .detach();
+            let bookmark_store = cx.new_model(|cx| BookmarkStore::new(buffer_store.clone(), cx));
 
             let mut this = Self {
                 worktrees: Vec::new(),
                 worktrees_reordered: false,
                 buffer_ordered_messages_tx: tx,
                 buffer_store,
+                bookmark_store,
                 shared_buffers: Default::default(),
                 loading_local_worktrees: Default::default(),
                 active_entry: None,
@@ -926,11 +943,15 @@ impl Project {
                     local_handles: Vec::new(),
                 },
                 current_lsp_settings: ProjectSettings::get_global(cx).lsp.clone(),
+                current_assistant_rules_folder: ProjectSettings::get_global(cx)
+                    .assistant_rules_folder()
+                    .to_path_buf(),
                 node: None,
                 default_prettier: DefaultPrettier::default(),
                 prettiers_per_worktree: HashMap::default(),
                 prettier_instances: HashMap::default(),
                 tasks,
+                assistant_rules: BTreeMap::default(),
                 hosted_project_id: None,
                 dev_server_project_id: response
                     .payload
@@ -1180,6 +1201,12 @@ impl Project {
         }
         self.current_lsp_settings = new_lsp_settings;
 
+        let assistant_rules_folder = ProjectSettings::get_global(cx).assistant_rules_folder();
+        if assistant_rules_folder != self.current_assistant_rules_folder {
+            self.current_assistant_rules_folder = assistant_rules_folder.to_path_buf();
+            self.reload_assistant_rules(cx);
+        }
+
         // Stop all newly-disabled language servers.
         for (worktree_id, adapter_name) in language_servers_to_stop {
             self.stop_language_server(worktree_id, adapter_name, cx)
@@ -1316,6 +1343,14 @@ impl Project {
         &self.tasks
     }
 
+    pub fn buffer_store(&self) -> &Model<BufferStore> {
+        &self.buffer_store
+    }
+
+    pub fn bookmark_store(&self) -> &Model<BookmarkStore> {
+        &self.bookmark_store
+    }
+
     pub fn snippets(&self) -> &Model<SnippetProvider> {
         &self.snippets
     }
@@ -2129,6 +2164,9 @@ impl Project {
     ) {
         let buffer = buffer_handle.read(cx);
         let buffer_id = buffer.remote_id();
+        if buffer.is_large_file() {
+            return;
+        }
 
         if let Some(file) = File::from_dyn(buffer.file()) {
             if !file.is_local() {
@@ -2750,6 +2788,7 @@ impl Project {
                                         }
                                     }
                                 });
+                                this.stop_replaced_language_servers(cx);
                             })
                             .ok();
                     }
@@ -2838,6 +2877,10 @@ impl Project {
     ) {
         // If the buffer has a language, set it and start the language server if we haven't already.
         let buffer = buffer_handle.read(cx);
+        // Large files are neither highlighted nor sent to language servers.
+        if buffer.is_large_file() {
+            return;
+        }
         let Some(file) = buffer.file() else {
             return;
         };
@@ -3742,6 +3785,35 @@ impl Project {
         Ok(())
     }
 
+    /// Stops the language servers whose adapters are no longer registered, such as the ones
+    /// provided by an extension that was reloaded, so that buffers start them again with the
+    /// adapters that replaced them.
+    fn stop_replaced_language_servers(&mut self, cx: &mut ModelContext<Self>) {
+        let replaced_servers = self
+            .language_server_ids
+            .iter()
+            .filter_map(|((worktree_id, adapter_name), server_id)| {
+                let LanguageServerState::Running {
+                    language, adapter, ..
+                } = self.language_servers.get(server_id)?
+                else {
+                    return None;
+                };
+                let is_registered = self
+                    .languages
+                    .lsp_adapters(language)
+                    .iter()
+                    .any(|registered| Arc::ptr_eq(&registered.adapter, &adapter.adapter));
+                (!is_registered).then(|| (*worktree_id, adapter_name.clone()))
+            })
+            .collect::<Vec<_>>();
+
+        for (worktree_id, adapter_name) in replaced_servers {
+            self.stop_language_server(worktree_id, adapter_name, cx)
+                .detach();
+        }
+    }
+
     // Returns a list of all of the worktrees which no longer have a language server and the root path
     // for the stopped server
     fn stop_language_server(
@@ -5025,6 +5097,7 @@ impl Project {
                         buffer_abs_path,
                         command,
                         arguments,
+                        &HashMap::default(),
                         &mut cx,
                     )
                     .await
@@ -5034,6 +5107,21 @@ impl Project {
                     ))?
                     .map(FormatOperation::External);
                 }
+                (Formatter::Extension(formatter_name), FormatOnSave::On | FormatOnSave::Off)
+                | (_, FormatOnSave::Extension(formatter_name)) => {
+                    let buffer_abs_path = buffer_abs_path.as_ref().map(|path| path.as_path());
+                    format_operation = Self::format_via_extension(
+                        &project,
+                        buffer,
+                        buffer_abs_path,
+                        formatter_name,
+                        &mut cx,
+                    )
+                    .await
+                    .with_context(|| {
+                        format!("failed to format via extension formatter {formatter_name:?}")
+                    })?;
+                }
                 (Formatter::Auto, FormatOnSave::On | FormatOnSave::Off) => {
                     let prettier = if prettier_settings.allowed {
                         prettier_support::format_with_prettier(&project, buffer, &mut cx)
@@ -5089,7 +5177,7 @@ impl Project {
                 // in the buffer's undo history.
                 if let Some(operation) = format_operation {
                     match operation {
-                        FormatOperation::Lsp(edits) => {
+                        FormatOperation::Lsp(edits) | FormatOperation::Extension(edits) => {
                             b.edit(edits, None, cx);
                         }
                         FormatOperation::External(diff) => {
@@ -5174,6 +5262,7 @@ impl Project {
         buffer_abs_path: Option<&Path>,
         command: &str,
         arguments: &[String],
+        env: &HashMap<String, String>,
         cx: &mut AsyncAppContext,
     ) -> Result<Option<Diff>> {
         let working_dir_path = buffer.update(cx, |buffer, cx| {
@@ -5193,6 +5282,7 @@ impl Project {
         }
 
         let mut child = child
+            .envs(env)
             .args(arguments.iter().map(|arg| {
                 if let Some(buffer_abs_path) = buffer_abs_path {
                     arg.replace("{buffer_path}", &buffer_abs_path.to_string_lossy())
@@ -5233,6 +5323,80 @@ impl Project {
         ))
     }
 
+    async fn format_via_extension(
+        this: &WeakModel<Self>,
+        buffer: &Model<Buffer>,
+        buffer_abs_path: Option<&Path>,
+        formatter_name: &str,
+        cx: &mut AsyncAppContext,
+    ) -> Result<Option<FormatOperation>> {
+        let (formatter, language_name, snapshot, delegate) = this.update(cx, |this, cx| {
+            let formatter = this
+                .languages
+                .formatter(formatter_name)
+                .ok_or_else(|| anyhow!("no extension provides a formatter with this name"))?;
+            let buffer = buffer.read(cx);
+            let language_name = buffer
+                .language()
+                .map(|language| language.name())
+                .ok_or_else(|| anyhow!("the buffer has no language"))?;
+            if !formatter.languages().is_empty() && !formatter.languages().contains(&language_name)
+            {
+                return Err(anyhow!("the formatter doesn't support {language_name}"));
+            }
+
+            let worktree = File::from_dyn(buffer.file())
+                .map(|file| file.worktree.clone())
+                .or_else(|| this.visible_worktrees(cx).next())
+                .ok_or_else(|| anyhow!("no worktree to run the formatter in"))?;
+            let delegate: Arc<dyn LspAdapterDelegate> =
+                ProjectLspAdapterDelegate::new(this, &worktree, cx);
+            anyhow::Ok((formatter, language_name, buffer.text_snapshot(), delegate))
+        })??;
+
+        let output = formatter
+            .format(
+                language_name,
+                snapshot.text(),
+                buffer_abs_path.map(Path::to_path_buf),
+                delegate,
+            )
+            .await?;
+        match output {
+            FormatterOutput::Command(command) => {
+                let diff = Self::format_via_external_command(
+                    buffer,
+                    buffer_abs_path,
+                    &command.path.to_string_lossy(),
+                    &command.arguments,
+                    &command.env,
+                    cx,
+                )
+                .await?;
+                Ok(diff.map(FormatOperation::External))
+            }
+            FormatterOutput::Edits(edits) => {
+                // The edits refer to the text that was formatted, so anchor them in its snapshot.
+                let edits = edits
+                    .into_iter()
+                    .map(|(range, new_text)| {
+                        let start =
+                            snapshot.clip_offset(range.start.min(snapshot.len()), Bias::Left);
+                        let end = snapshot.clip_offset(range.end.min(snapshot.len()), Bias::Right);
+                        if start > end {
+                            return Err(anyhow!("invalid edit range {range:?}"));
+                        }
+                        Ok((
+                            snapshot.anchor_before(start)..snapshot.anchor_after(end),
+                            new_text,
+                        ))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Some(FormatOperation::Extension(edits)))
+            }
+        }
+    }
+
     #[inline(never)]
     fn definition_impl(
         &self,
@@ -7667,6 +7831,13 @@ impl Project {
             inventory.remove_worktree_sources(id_to_remove);
         });
 
+        let rules_count = self.assistant_rules.len();
+        self.assistant_rules
+            .retain(|(worktree_id, _), _| *worktree_id != id_to_remove);
+        if self.assistant_rules.len() != rules_count {
+            cx.emit(Event::AssistantRulesChanged);
+        }
+
         self.worktrees.retain(|worktree| {
             if let Some(worktree) = worktree.upgrade() {
                 let id = worktree.read(cx).id();
@@ -7996,6 +8167,8 @@ impl Project {
         let remote_worktree_id = worktree.read(cx).id();
 
         let mut settings_contents = Vec::new();
+        let mut rules_contents = Vec::new();
+        let assistant_rules_folder = self.current_assistant_rules_folder.clone();
         for (path, _, change) in changes.iter() {
             let removed = change == &PathChange::Removed;
             let abs_path = match worktree.read(cx).absolutize(path) {
@@ -8043,6 +8216,21 @@ impl Project {
                         );
                     }
                 })
+            } else if path.parent() == Some(assistant_rules_folder.as_path())
+                && path
+                    .extension()
+                    .map_or(false, |extension| extension == "md")
+            {
+                let path = path.clone();
+                let fs = self.fs.clone();
+                rules_contents.push(async move {
+                    let content = if removed {
+                        None
+                    } else {
+                        fs.load(&abs_path).await.log_err()
+                    };
+                    (path, content)
+                });
             } else if path.ends_with(local_vscode_tasks_file_relative_path()) {
                 self.task_inventory().update(cx, |task_inventory, cx| {
                     if removed {
@@ -8072,6 +8260,25 @@ impl Project {
             }
         }
 
+        if !rules_contents.is_empty() {
+            cx.spawn(move |this, mut cx| async move {
+                let rules_contents = futures::future::join_all(rules_contents).await;
+                this.update(&mut cx, |this, cx| {
+                    for (path, content) in rules_contents {
+                        let key = (remote_worktree_id, path);
+                        if let Some(content) = content {
+                            this.assistant_rules.insert(key, content.into());
+                        } else {
+                            this.assistant_rules.remove(&key);
+                        }
+                    }
+                    cx.emit(Event::AssistantRulesChanged);
+                })
+                .ok();
+            })
+            .detach();
+        }
+
         if settings_contents.is_empty() {
             return;
         }
@@ -8110,6 +8317,59 @@ impl Project {
         .detach();
     }
 
+    /// Replaces the assistant rules with the Markdown files in the configured folder of
+    /// each local worktree.
+    fn reload_assistant_rules(&mut self, cx: &mut ModelContext<Self>) {
+        let mut rules_contents = Vec::new();
+        for worktree in self.worktrees() {
+            let worktree = worktree.read(cx);
+            if !worktree.is_local() {
+                continue;
+            }
+            let worktree_id = worktree.id();
+            for entry in worktree.child_entries(&self.current_assistant_rules_folder) {
+                let is_markdown = entry
+                    .path
+                    .extension()
+                    .map_or(false, |extension| extension == "md");
+                if !entry.is_file() || !is_markdown {
+                    continue;
+                }
+                if let Some(abs_path) = worktree.absolutize(&entry.path).log_err() {
+                    let path = entry.path.clone();
+                    let fs = self.fs.clone();
+                    rules_contents.push(async move {
+                        (worktree_id, path, fs.load(&abs_path).await.log_err())
+                    });
+                }
+            }
+        }
+
+        cx.spawn(move |this, mut cx| async move {
+            let rules_contents = futures::future::join_all(rules_contents).await;
+            this.update(&mut cx, |this, cx| {
+                this.assistant_rules.clear();
+                for (worktree_id, path, content) in rules_contents {
+                    if let Some(content) = content {
+                        this.assistant_rules
+                            .insert((worktree_id, path), content.into());
+                    }
+                }
+                cx.emit(Event::AssistantRulesChanged);
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Returns the contents of the assistant rules files in the project's local worktrees,
+    /// along with their worktree and path relative to it.
+    pub fn assistant_rules(&self) -> impl Iterator<Item = (WorktreeId, &Arc<Path>, &Arc<str>)> {
+        self.assistant_rules
+            .iter()
+            .map(|((worktree_id, path), content)| (*worktree_id, path, content))
+    }
+
     pub fn set_active_path(&mut self, entry: Option<ProjectPath>, cx: &mut ModelContext<Self>) {
         let new_active_entry = entry.and_then(|project_path| {
             let worktree = self.worktree_for_id(project_path.worktree_id, cx)?;
@@ -10468,18 +10728,14 @@ impl Project {
             let cwd = self.task_cwd(cx).log_err().flatten();
 
             cx.spawn(|project, cx| async move {
-                let mut task_variables = cx
-                    .update(|cx| {
-                        combine_task_variables(
-                            captured_variables,
-                            location,
-                            BasicContextProvider::new(project.upgrade()?),
-                            cx,
-                        )
-                        .log_err()
-                    })
-                    .ok()
-                    .flatten()?;
+                let mut task_variables = combine_task_variables(
+                    captured_variables,
+                    location,
+                    BasicContextProvider::new(project.upgrade()?),
+                    cx,
+                )
+                .await
+                .log_err()?;
                 // Remove all custom entries starting with _, as they're not intended for use by the end user.
                 task_variables.sweep();
                 Some(TaskContext {
@@ -10658,25 +10914,25 @@ impl Project {
     }
 }
 
-fn combine_task_variables(
+async fn combine_task_variables(
     mut captured_variables: TaskVariables,
     location: Location,
     baseline: BasicContextProvider,
-    cx: &mut AppContext,
+    mut cx: AsyncAppContext,
 ) -> anyhow::Result<TaskVariables> {
     let language_context_provider = location
         .buffer
-        .read(cx)
-        .language()
+        .read_with(&cx, |buffer, _| buffer.language().cloned())?
         .and_then(|language| language.context_provider());
-    let baseline = baseline
-        .build_context(&captured_variables, &location, cx)
+    let baseline = cx
+        .update(|cx| baseline.build_context(&captured_variables, &location, cx))?
+        .await
         .context("building basic default context")?;
     captured_variables.extend(baseline);
     if let Some(provider) = language_context_provider {
         captured_variables.extend(
-            provider
-                .build_context(&captured_variables, &location, cx)
+            cx.update(|cx| provider.build_context(&captured_variables, &location, cx))?
+                .await
                 .context("building provider context")?,
         );
     }
@@ -3,7 +3,11 @@ use gpui::AppContext;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsSources};
-use std::{sync::Arc, time::Duration};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct ProjectSettings {
@@ -24,6 +28,26 @@ pub struct ProjectSettings {
     /// Configuration for how direnv configuration should be loaded
     #[serde(default)]
     pub load_direnv: DirenvSettings,
+
+    /// Configuration for opening files that are too large to be edited with all
+    /// language features enabled.
+    #[serde(default)]
+    pub large_files: LargeFileSettings,
+
+    /// The folder, relative to the root of each worktree, whose Markdown files
+    /// are included as assistant rules.
+    ///
+    /// Default: .zed/assistant
+    #[serde(default)]
+    pub assistant_rules_folder: Option<PathBuf>,
+}
+
+impl ProjectSettings {
+    pub fn assistant_rules_folder(&self) -> &Path {
+        self.assistant_rules_folder
+            .as_deref()
+            .unwrap_or(paths::local_assistant_rules_folder_relative_path())
+    }
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
@@ -73,6 +97,29 @@ impl GitSettings {
     }
 }
 
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct LargeFileSettings {
+    /// Files larger than this size (in bytes) are opened in large-file mode:
+    /// they are loaded in chunks, and neither highlighted nor sent to language servers.
+    ///
+    /// Default: 20971520
+    pub threshold: Option<u64>,
+    /// Whether files opened in large-file mode are read-only.
+    ///
+    /// Default: true
+    pub read_only: Option<bool>,
+}
+
+impl LargeFileSettings {
+    pub fn threshold(&self) -> u64 {
+        self.threshold.unwrap_or(20 * 1024 * 1024)
+    }
+
+    pub fn read_only(&self) -> bool {
+        self.read_only.unwrap_or(true)
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum GitGutterSetting {
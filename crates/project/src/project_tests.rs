@@ -1,11 +1,13 @@
 use crate::{Event, *};
+use async_trait::async_trait;
 use fs::FakeFs;
 use futures::{future, StreamExt};
 use gpui::{AppContext, SemanticVersion, UpdateGlobal};
 use language::{
-    language_settings::{AllLanguageSettings, LanguageSettingsContent},
-    tree_sitter_rust, tree_sitter_typescript, Diagnostic, FakeLspAdapter, LanguageConfig,
-    LanguageMatcher, LineEnding, OffsetRangeExt, Point, ToPoint,
+    language_settings::{AllLanguageSettings, Formatter, LanguageSettingsContent},
+    tree_sitter_rust, tree_sitter_typescript, Diagnostic, Encoding, FakeLspAdapter,
+    FormatterCommand, FormatterOutput, FormatterProvider, LanguageConfig, LanguageMatcher,
+    LineEnding, LspAdapterDelegate, OffsetRangeExt, Point, ToPoint,
 };
 use lsp::NumberOrString;
 use parking_lot::Mutex;
@@ -294,6 +296,157 @@ async fn test_managing_project_specific_settings(cx: &mut gpui::TestAppContext)
     );
 }
 
+#[gpui::test]
+async fn test_assistant_rules(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/the-root",
+        json!({
+            ".zed": {
+                "assistant": {
+                    "errors.md": "Use anyhow, never unwrap in library code.",
+                    "notes.txt": "Not a rules file.",
+                },
+            },
+            "a.rs": "fn a() {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/the-root".as_ref()], cx).await;
+    cx.executor().run_until_parked();
+    let rules = |cx: &mut gpui::TestAppContext| {
+        project.read_with(cx, |project, _| {
+            project
+                .assistant_rules()
+                .map(|(_, path, content)| {
+                    (path.to_string_lossy().into_owned(), content.to_string())
+                })
+                .collect::<Vec<_>>()
+        })
+    };
+    assert_eq!(
+        rules(cx),
+        [(
+            ".zed/assistant/errors.md".to_string(),
+            "Use anyhow, never unwrap in library code.".to_string()
+        )]
+    );
+
+    fs.save(
+        "/the-root/.zed/assistant/style.md".as_ref(),
+        &"Prefer iterators.".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    fs.remove_file(
+        "/the-root/.zed/assistant/errors.md".as_ref(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(
+        rules(cx),
+        [(
+            ".zed/assistant/style.md".to_string(),
+            "Prefer iterators.".to_string()
+        )]
+    );
+}
+
+#[gpui::test]
+async fn test_assistant_rules_folder_setting(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/the-root",
+        json!({
+            ".zed": {
+                "assistant": { "errors.md": "Use anyhow." },
+            },
+            "docs": {
+                "rules": {
+                    "style.md": "Prefer iterators.",
+                    "notes.txt": "Not a rules file.",
+                },
+            },
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/the-root".as_ref()], cx).await;
+    cx.executor().run_until_parked();
+    let rules = |cx: &mut gpui::TestAppContext| {
+        project.read_with(cx, |project, _| {
+            project
+                .assistant_rules()
+                .map(|(_, path, content)| {
+                    (path.to_string_lossy().into_owned(), content.to_string())
+                })
+                .collect::<Vec<_>>()
+        })
+    };
+    assert_eq!(
+        rules(cx),
+        [(
+            ".zed/assistant/errors.md".to_string(),
+            "Use anyhow.".to_string()
+        )]
+    );
+
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |settings, cx| {
+            settings.update_user_settings::<project_settings::ProjectSettings>(cx, |settings| {
+                settings.assistant_rules_folder = Some("docs/rules".into());
+            });
+        })
+    });
+    cx.executor().run_until_parked();
+    assert_eq!(
+        rules(cx),
+        [(
+            "docs/rules/style.md".to_string(),
+            "Prefer iterators.".to_string()
+        )]
+    );
+
+    // Changes to the rules in the configured folder are picked up, but not the ones
+    // in the default folder.
+    fs.save(
+        "/the-root/docs/rules/tests.md".as_ref(),
+        &"Test every request.".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    fs.save(
+        "/the-root/.zed/assistant/errors.md".as_ref(),
+        &"Use thiserror.".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(
+        rules(cx),
+        [
+            (
+                "docs/rules/style.md".to_string(),
+                "Prefer iterators.".to_string()
+            ),
+            (
+                "docs/rules/tests.md".to_string(),
+                "Test every request.".to_string()
+            )
+        ]
+    );
+}
+
 #[gpui::test]
 async fn test_managing_language_servers(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -2775,6 +2928,82 @@ async fn test_apply_code_actions_with_commands(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_large_file_mode(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |settings, cx| {
+            settings.update_user_settings::<project_settings::ProjectSettings>(cx, |settings| {
+                settings.large_files.threshold = Some(64);
+            });
+        });
+    });
+
+    let large_text = "fn main() {}\n".repeat(10);
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "large.rs": large_text.clone(),
+            "small.rs": "fn main() {}\n",
+        }),
+    )
+    .await;
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers =
+        language_registry.register_fake_lsp_adapter("Rust", FakeLspAdapter::default());
+
+    let large_buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/large.rs", cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    large_buffer.update(cx, |buffer, _| {
+        assert!(buffer.is_large_file());
+        assert_eq!(buffer.text(), large_text);
+        assert!(buffer.language().is_none());
+    });
+    assert!(
+        fake_servers.try_next().is_err(),
+        "Opening a large file doesn't start language servers"
+    );
+
+    let small_buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/small.rs", cx)
+        })
+        .await
+        .unwrap();
+    let _fake_server = fake_servers.next().await.unwrap();
+    cx.executor().run_until_parked();
+    small_buffer.update(cx, |buffer, _| {
+        assert!(!buffer.is_large_file());
+        assert_eq!(
+            buffer.language().map(|language| language.name()),
+            Some("Rust".into())
+        );
+    });
+    project.update(cx, |project, cx| {
+        assert_eq!(
+            project
+                .language_servers_for_buffer(small_buffer.read(cx), cx)
+                .count(),
+            1
+        );
+        assert_eq!(
+            project
+                .language_servers_for_buffer(large_buffer.read(cx), cx)
+                .count(),
+            0,
+            "Large files aren't registered with running language servers"
+        );
+    });
+}
+
 #[gpui::test(iterations = 10)]
 async fn test_save_file(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -2807,6 +3036,288 @@ async fn test_save_file(cx: &mut gpui::TestAppContext) {
     assert_eq!(new_text, buffer.update(cx, |buffer, _| buffer.text()));
 }
 
+#[gpui::test]
+async fn test_save_file_with_encoding(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.create_dir(Path::new("/dir")).await.unwrap();
+    fs.insert_file(
+        "/dir/settings.ini",
+        b"[Param\xe8tres]\r\nr\xe9pertoire=C:\\Donn\xe9es\r\n".to_vec(),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/settings.ini", cx))
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, cx| {
+        assert_eq!(buffer.text(), "[Paramètres]\nrépertoire=C:\\Données\n");
+        assert_eq!(buffer.encoding().name(), "windows-1252");
+        assert_eq!(buffer.line_ending(), LineEnding::Windows);
+        buffer.edit([(buffer.len()..buffer.len(), "activé=oui\n")], None, cx);
+    });
+
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        fs.load_bytes(Path::new("/dir/settings.ini")).await.unwrap(),
+        b"[Param\xe8tres]\r\nr\xe9pertoire=C:\\Donn\xe9es\r\nactiv\xe9=oui\r\n"
+    );
+
+    // Characters that can't be represented in the file's encoding fail the save, leaving the
+    // file untouched.
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "🍐")], None, cx));
+    assert!(project
+        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+        .await
+        .is_err());
+    assert_eq!(
+        fs.load_bytes(Path::new("/dir/settings.ini")).await.unwrap(),
+        b"[Param\xe8tres]\r\nr\xe9pertoire=C:\\Donn\xe9es\r\nactiv\xe9=oui\r\n"
+    );
+}
+
+#[gpui::test]
+async fn test_reload_with_encoding(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.create_dir(Path::new("/dir")).await.unwrap();
+    fs.insert_file("/dir/notes.txt", "café\n".as_bytes().to_vec())
+        .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/notes.txt", cx))
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "café\n");
+        assert!(buffer.encoding().is_utf8());
+    });
+
+    let windows_1252 = Encoding::all()
+        .into_iter()
+        .find(|encoding| encoding.name() == "windows-1252")
+        .unwrap();
+    buffer
+        .update(cx, |buffer, cx| {
+            buffer.reload_with_encoding(windows_1252, cx)
+        })
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "cafÃ©\n");
+        assert_eq!(buffer.encoding(), windows_1252);
+        assert!(!buffer.is_dirty());
+    });
+
+    // Saving keeps the contents on disk unchanged, since they're encoded back the same way.
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        fs.load_bytes(Path::new("/dir/notes.txt")).await.unwrap(),
+        "café\n".as_bytes()
+    );
+
+    buffer
+        .update(cx, |buffer, cx| {
+            buffer.reload_with_encoding(Encoding::default(), cx)
+        })
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "café\n");
+        assert!(buffer.encoding().is_utf8());
+    });
+}
+
+#[gpui::test]
+async fn test_format_via_extension_edits(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "main.rs": "one two\n" }))
+        .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    // The end of the second edit is past the end of the text, and gets clipped to it.
+    language_registry.register_formatter(Arc::new(FakeFormatter {
+        output: FormatterOutput::Edits(vec![
+            (0..3, "ONE".to_string()),
+            (4..100, "TWO\n".to_string()),
+        ]),
+    }));
+    set_extension_formatter(cx);
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/main.rs", cx)
+        })
+        .await
+        .unwrap();
+    project
+        .update(cx, |project, cx| {
+            project.format(
+                HashSet::from_iter([buffer.clone()]),
+                true,
+                FormatTrigger::Manual,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, _| assert_eq!(buffer.text(), "ONE TWO\n"));
+}
+
+#[gpui::test]
+#[cfg(not(windows))]
+async fn test_format_via_extension_command(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.executor().allow_parking();
+
+    let dir = temp_tree(json!({ "main.rs": "one two\n" }));
+    let project = Project::test(Arc::new(RealFs::default()), [dir.path()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    language_registry.register_formatter(Arc::new(FakeFormatter {
+        output: FormatterOutput::Command(FormatterCommand {
+            path: "tr".into(),
+            arguments: vec!["a-z".into(), "A-Z".into()],
+            env: Default::default(),
+        }),
+    }));
+    set_extension_formatter(cx);
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(dir.path().join("main.rs"), cx)
+        })
+        .await
+        .unwrap();
+    project
+        .update(cx, |project, cx| {
+            project.format(
+                HashSet::from_iter([buffer.clone()]),
+                true,
+                FormatTrigger::Manual,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, _| assert_eq!(buffer.text(), "ONE TWO\n"));
+}
+
+struct FakeFormatter {
+    output: FormatterOutput,
+}
+
+#[async_trait]
+impl FormatterProvider for FakeFormatter {
+    fn name(&self) -> Arc<str> {
+        "fake-formatter".into()
+    }
+
+    fn languages(&self) -> &[Arc<str>] {
+        &[]
+    }
+
+    async fn format(
+        &self,
+        language_name: Arc<str>,
+        text: String,
+        _: Option<PathBuf>,
+        _: Arc<dyn LspAdapterDelegate>,
+    ) -> Result<FormatterOutput> {
+        assert_eq!(language_name.as_ref(), "Rust");
+        assert_eq!(text, "one two\n");
+        Ok(self.output.clone())
+    }
+}
+
+fn set_extension_formatter(cx: &mut gpui::TestAppContext) {
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |settings, cx| {
+            settings.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                settings.defaults.formatter = Some(Formatter::Extension("fake-formatter".into()));
+            });
+        });
+    });
+}
+
+#[gpui::test]
+async fn test_bookmarks(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "file1": "one\ntwo\nthree\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let bookmark_store = project.read_with(cx, |project, _| project.bookmark_store().clone());
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/file1", cx))
+        .await
+        .unwrap();
+
+    let bookmark_id = bookmark_store.update(cx, |store, cx| {
+        let anchor = buffer.read(cx).anchor_before(Point::new(1, 0));
+        store.add(&buffer, anchor, Some("two".into()), cx).unwrap()
+    });
+
+    // Bookmarks follow the edits made to their buffers.
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "zero\n")], None, cx));
+    bookmark_store.read_with(cx, |store, cx| {
+        let bookmark = store.bookmark(bookmark_id).unwrap();
+        assert_eq!(bookmark.label.as_deref(), Some("two"));
+        assert_eq!(bookmark.point(cx), Point::new(2, 0));
+        assert!(bookmark.anchor().is_some());
+    });
+
+    // Once the buffer is released, the last known position is kept...
+    drop(buffer);
+    cx.executor().run_until_parked();
+    bookmark_store.read_with(cx, |store, cx| {
+        let bookmark = store.bookmark(bookmark_id).unwrap();
+        assert_eq!(bookmark.point(cx), Point::new(2, 0));
+        assert!(bookmark.anchor().is_none());
+    });
+
+    // ...and the bookmark gets anchored again when the file is re-opened.
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/file1", cx))
+        .await
+        .unwrap();
+    bookmark_store.read_with(cx, |store, _| {
+        assert_eq!(store.bookmarks_for_buffer(&buffer).count(), 1);
+    });
+
+    // Toggling a bookmark on the same row removes it.
+    bookmark_store.update(cx, |store, cx| {
+        let anchor = buffer.read(cx).anchor_after(Point::new(2, 2));
+        assert_eq!(store.toggle(&buffer, anchor, cx), None);
+        assert!(store.bookmarks().is_empty());
+
+        // The buffer is no longer observed once it has no bookmarks.
+        assert_eq!(store.observed_buffer_count(), 0);
+    });
+}
+
 #[gpui::test(iterations = 30)]
 async fn test_file_changes_multiple_times_on_disk(cx: &mut gpui::TestAppContext) {
     init_test(cx);
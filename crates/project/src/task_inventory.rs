@@ -544,7 +544,7 @@ impl ContextProvider for BasicContextProvider {
         _: &TaskVariables,
         location: &Location,
         cx: &mut AppContext,
-    ) -> Result<TaskVariables> {
+    ) -> Task<Result<TaskVariables>> {
         let buffer = location.buffer.read(cx);
         let buffer_snapshot = buffer.snapshot();
         let symbols = buffer_snapshot.symbols_containing(location.range.start, None);
@@ -621,7 +621,7 @@ impl ContextProvider for BasicContextProvider {
             task_variables.insert(VariableName::File, path_as_string);
         }
 
-        Ok(task_variables)
+        Task::ready(Ok(task_variables))
     }
 }
 
@@ -0,0 +1,316 @@
+//! Code cells in plain source files, delimited by percent-format markers such as `# %%`.
+
+use crate::jupyter_settings::JupyterSettings;
+use collections::HashSet;
+use editor::{
+    display_map::{BlockContext, BlockDisposition, BlockId, BlockProperties, BlockStyle},
+    Anchor, Editor, EditorMode, ToPoint as _,
+};
+use gpui::{AppContext, ViewContext};
+use language::{Language, Point, Subscription};
+use multi_buffer::MultiBufferRow;
+use settings::Settings as _;
+use std::{ops::Range, sync::Arc};
+use ui::prelude::*;
+
+/// A cell of code, spanning from its marker to the next one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CodeCell {
+    /// The rows of the cell, including its marker.
+    pub rows: Range<u32>,
+    /// The rows of the code in the cell, without its marker or trailing blank lines.
+    pub content: Range<u32>,
+}
+
+/// The markers that start a cell in the given language, either configured in the settings or
+/// derived from the language's line comments, e.g. `# %%` or `// %%`.
+pub fn cell_markers(language: &Arc<Language>, cx: &AppContext) -> Vec<String> {
+    if let Some(markers) = JupyterSettings::get_global(cx)
+        .cell_markers
+        .get(language.name().as_ref())
+    {
+        return markers.clone();
+    }
+
+    language
+        .default_scope()
+        .line_comment_prefixes()
+        .iter()
+        .map(|prefix| format!("{} %%", prefix.trim_end()))
+        .collect()
+}
+
+/// Whether the line starts a cell. Whitespace within markers is optional, so that `#%%`
+/// matches the `# %%` marker.
+pub fn is_cell_marker(line: &str, markers: &[String]) -> bool {
+    markers.iter().any(|marker| {
+        let mut rest = line;
+        for word in marker.split_whitespace() {
+            match rest.trim_start().strip_prefix(word) {
+                Some(remainder) => rest = remainder,
+                None => return false,
+            }
+        }
+        !marker.trim().is_empty()
+    })
+}
+
+/// Splits the lines of a file into cells. Returns no cells when the file contains no markers.
+pub fn code_cells<'a>(
+    lines: impl IntoIterator<Item = &'a str>,
+    markers: &[String],
+) -> Vec<CodeCell> {
+    fn code_cell(rows: Range<u32>, content: Option<Range<u32>>) -> CodeCell {
+        let content = content.unwrap_or(rows.start + 1..rows.start + 1);
+        CodeCell { rows, content }
+    }
+
+    let mut cells = Vec::new();
+    let mut has_markers = false;
+    let mut cell_start = 0;
+    let mut content: Option<Range<u32>> = None;
+    let mut row_count = 0;
+    for (row, line) in (0..).zip(lines) {
+        row_count = row + 1;
+        if is_cell_marker(line, markers) {
+            // Code before the first marker only forms a cell when there is any.
+            if has_markers || content.is_some() {
+                cells.push(code_cell(cell_start..row, content.take()));
+            }
+            has_markers = true;
+            cell_start = row;
+        } else if !line.trim().is_empty() {
+            let content_start = content.as_ref().map_or(row, |content| content.start);
+            content = Some(content_start..row + 1);
+        }
+    }
+
+    if has_markers {
+        cells.push(code_cell(cell_start..row_count, content));
+    }
+    cells
+}
+
+/// The range of a cell's code in the buffer, or `None` if the cell is empty.
+pub fn content_range(cell: &CodeCell, line_len: impl Fn(u32) -> u32) -> Option<Range<Point>> {
+    if cell.content.is_empty() {
+        return None;
+    }
+    let last_row = cell.content.end - 1;
+    Some(Point::new(cell.content.start, 0)..Point::new(last_row, line_len(last_row)))
+}
+
+/// Keeps subtle separators above the cell markers of an editor in sync with its contents.
+pub fn track_cell_separators(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if !matches!(editor.mode(), EditorMode::Full) {
+        return;
+    }
+    let multi_buffer = editor.buffer().clone();
+    let Some(buffer) = multi_buffer.read(cx).as_singleton() else {
+        return;
+    };
+
+    let mut separators = CellSeparators {
+        edits: buffer.update(cx, |buffer, _| buffer.subscribe()),
+        markers: Vec::new(),
+        separators: Vec::new(),
+    };
+    separators.refresh(editor, true, cx);
+    cx.subscribe(&multi_buffer, move |editor, _, event, cx| match event {
+        multi_buffer::Event::Edited { .. } => separators.refresh(editor, false, cx),
+        multi_buffer::Event::LanguageChanged(_) | multi_buffer::Event::Reloaded => {
+            separators.refresh(editor, true, cx)
+        }
+        _ => {}
+    })
+    .detach();
+}
+
+struct CellSeparators {
+    /// The edits to the buffer since the separators were last refreshed.
+    edits: Subscription,
+    markers: Vec<String>,
+    /// The separators, along with the position of the marker each one is above.
+    separators: Vec<(Anchor, BlockId)>,
+}
+
+impl CellSeparators {
+    /// Updates the separators of the edited rows, or of every row when `rescan` is set.
+    fn refresh(&mut self, editor: &mut Editor, rescan: bool, cx: &mut ViewContext<Editor>) {
+        let edits = self.edits.consume();
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let markers = match snapshot.language_at(Point::zero()) {
+            Some(language) if JupyterSettings::enabled(cx) => cell_markers(language, cx),
+            _ => Vec::new(),
+        };
+
+        let edited_rows = if rescan || markers != self.markers {
+            vec![0..snapshot.max_point().row + 1]
+        } else {
+            edited_rows(edits.into_inner().into_iter().map(|edit| {
+                snapshot.offset_to_point(edit.new.start).row
+                    ..snapshot.offset_to_point(edit.new.end).row + 1
+            }))
+        };
+        self.markers = markers;
+
+        // The markers of the separators in the edited rows may have changed, so those rows
+        // are scanned for markers again.
+        let mut removed_block_ids = HashSet::default();
+        self.separators.retain(|(position, block_id)| {
+            let row = position.to_point(&snapshot).row;
+            let edited = edited_rows.iter().any(|rows| rows.contains(&row));
+            if edited {
+                removed_block_ids.insert(*block_id);
+            }
+            !edited
+        });
+
+        let mut marker_positions = Vec::new();
+        if !self.markers.is_empty() {
+            for rows in &edited_rows {
+                let last_row = rows.end - 1;
+                let range = Point::new(rows.start, 0)
+                    ..Point::new(last_row, snapshot.line_len(MultiBufferRow(last_row)));
+                let text = snapshot.text_for_range(range).collect::<String>();
+                // No separator goes above a marker on the first row of the file.
+                marker_positions.extend(
+                    (rows.start..)
+                        .zip(text.lines())
+                        .filter(|(row, line)| *row > 0 && is_cell_marker(line, &self.markers))
+                        .map(|(row, _)| snapshot.anchor_before(Point::new(row, 0))),
+                );
+            }
+        }
+        if removed_block_ids.is_empty() && marker_positions.is_empty() {
+            return;
+        }
+
+        editor.remove_blocks(removed_block_ids, None, cx);
+        let blocks = marker_positions
+            .iter()
+            .map(|position| BlockProperties {
+                position: *position,
+                height: 1,
+                style: BlockStyle::Sticky,
+                render: Box::new(|cx: &mut BlockContext| {
+                    h_flex()
+                        .size_full()
+                        .pl(cx.gutter_dimensions.width)
+                        .child(div().w_full().h_px().bg(cx.theme().colors().border_variant))
+                        .into_any_element()
+                }),
+                disposition: BlockDisposition::Above,
+            })
+            .collect::<Vec<_>>();
+        let block_ids = editor.insert_blocks(blocks, None, cx);
+        self.separators
+            .extend(marker_positions.into_iter().zip(block_ids));
+    }
+}
+
+/// Merges the row ranges of edits, which are sorted but may share rows.
+fn edited_rows(rows: impl IntoIterator<Item = Range<u32>>) -> Vec<Range<u32>> {
+    let mut merged: Vec<Range<u32>> = Vec::new();
+    for rows in rows {
+        match merged.last_mut() {
+            Some(last) if rows.start <= last.end => last.end = last.end.max(rows.end),
+            _ => merged.push(rows),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn markers() -> Vec<String> {
+        vec!["# %%".to_string()]
+    }
+
+    #[test]
+    fn test_is_cell_marker() {
+        let markers = markers();
+        assert!(is_cell_marker("# %%", &markers));
+        assert!(is_cell_marker("#%% Load data", &markers));
+        assert!(is_cell_marker("  # %% [markdown]", &markers));
+        assert!(!is_cell_marker("# %", &markers));
+        assert!(!is_cell_marker("## %%", &markers));
+        assert!(!is_cell_marker("print('# %%')", &markers));
+
+        let markers = vec!["// %%".to_string()];
+        assert!(is_cell_marker("// %%", &markers));
+        assert!(is_cell_marker("//%%", &markers));
+        assert!(!is_cell_marker("# %%", &markers));
+    }
+
+    #[test]
+    fn test_code_cells() {
+        let markers = markers();
+        assert_eq!(
+            code_cells("import os\nprint(1)\n".lines(), &markers),
+            vec![]
+        );
+
+        let text = "import os\n\n# %% First\nx = 1\n\ny = 2\n\n\n#%%\n\nprint(x + y)\n# %%\n";
+        assert_eq!(
+            code_cells(text.lines(), &markers),
+            vec![
+                CodeCell {
+                    rows: 0..2,
+                    content: 0..1,
+                },
+                CodeCell {
+                    rows: 2..8,
+                    content: 3..6,
+                },
+                CodeCell {
+                    rows: 8..11,
+                    content: 10..11,
+                },
+                CodeCell {
+                    rows: 11..12,
+                    content: 12..12,
+                },
+            ]
+        );
+
+        // Blank lines before the first marker don't form a cell.
+        let text = "\n\n# %%\nx = 1";
+        assert_eq!(
+            code_cells(text.lines(), &markers),
+            vec![CodeCell {
+                rows: 2..4,
+                content: 3..4,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_content_range() {
+        let cell = CodeCell {
+            rows: 2..8,
+            content: 3..6,
+        };
+        assert_eq!(
+            content_range(&cell, |_| 5),
+            Some(Point::new(3, 0)..Point::new(5, 5))
+        );
+
+        let empty = CodeCell {
+            rows: 11..12,
+            content: 12..12,
+        };
+        assert_eq!(content_range(&empty, |_| 0), None);
+    }
+
+    #[test]
+    fn test_edited_rows() {
+        assert_eq!(edited_rows([]), vec![]);
+        assert_eq!(
+            edited_rows([0..1, 0..2, 3..4, 4..6, 8..9]),
+            vec![0..2, 3..6, 8..9]
+        );
+    }
+}
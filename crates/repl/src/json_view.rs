@@ -0,0 +1,252 @@
+//! A collapsible tree for `application/json` outputs.
+
+use collections::HashSet;
+use gpui::{ClickEvent, Render, View};
+use serde_json::Value;
+use ui::{prelude::*, Disclosure};
+
+use crate::outputs::LineHeight;
+
+/// The nodes nested at most this deep are expanded when the tree is first shown.
+const EXPANDED_DEPTH: usize = 1;
+
+/// JsonView renders JSON data as a tree whose objects and arrays can be expanded and collapsed.
+pub struct JsonView {
+    value: Value,
+    /// The paths of the nodes whose expansion differs from the default.
+    toggled_paths: HashSet<String>,
+}
+
+/// A line of the tree: a key and value, the start of an expanded object or array, or its end.
+#[derive(Debug, PartialEq, Eq)]
+struct TreeLine {
+    depth: usize,
+    path: String,
+    key: Option<String>,
+    text: String,
+    /// Whether the node is expanded, for nodes that can be expanded.
+    expanded: Option<bool>,
+}
+
+impl JsonView {
+    pub fn new(value: Value) -> Self {
+        Self {
+            value,
+            toggled_paths: HashSet::default(),
+        }
+    }
+
+    fn toggle(&mut self, path: &str, cx: &mut ViewContext<Self>) {
+        if !self.toggled_paths.remove(path) {
+            self.toggled_paths.insert(path.to_string());
+        }
+        cx.notify();
+    }
+
+    fn lines(&self) -> Vec<TreeLine> {
+        let mut lines = Vec::new();
+        tree_lines(
+            &self.value,
+            None,
+            String::new(),
+            0,
+            &self.toggled_paths,
+            &mut lines,
+        );
+        lines
+    }
+}
+
+fn tree_lines(
+    value: &Value,
+    key: Option<String>,
+    path: String,
+    depth: usize,
+    toggled_paths: &HashSet<String>,
+    lines: &mut Vec<TreeLine>,
+) {
+    let children: Vec<(String, &Value)> = match value {
+        Value::Object(map) => map
+            .iter()
+            .map(|(key, value)| (key.clone(), value))
+            .collect(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(ix, value)| (ix.to_string(), value))
+            .collect(),
+        value => {
+            lines.push(TreeLine {
+                depth,
+                path,
+                key,
+                text: value.to_string(),
+                expanded: None,
+            });
+            return;
+        }
+    };
+
+    let (open, close) = if value.is_object() {
+        ("{", "}")
+    } else {
+        ("[", "]")
+    };
+    if children.is_empty() {
+        lines.push(TreeLine {
+            depth,
+            path,
+            key,
+            text: format!("{open}{close}"),
+            expanded: None,
+        });
+        return;
+    }
+
+    let expanded = (depth < EXPANDED_DEPTH) != toggled_paths.contains(&path);
+    if !expanded {
+        let summary = match value {
+            Value::Object(_) if children.len() == 1 => "1 key".to_string(),
+            Value::Object(_) => format!("{} keys", children.len()),
+            _ if children.len() == 1 => "1 item".to_string(),
+            _ => format!("{} items", children.len()),
+        };
+        lines.push(TreeLine {
+            depth,
+            path,
+            key,
+            text: format!("{open}…{close} {summary}"),
+            expanded: Some(false),
+        });
+        return;
+    }
+
+    lines.push(TreeLine {
+        depth,
+        path: path.clone(),
+        key,
+        text: open.to_string(),
+        expanded: Some(true),
+    });
+    for (child_key, child) in children {
+        // Escape the key as in a JSON pointer, so that paths are unique.
+        let child_path = format!("{path}/{}", child_key.replace('~', "~0").replace('/', "~1"));
+        tree_lines(
+            child,
+            Some(child_key),
+            child_path,
+            depth + 1,
+            toggled_paths,
+            lines,
+        );
+    }
+    lines.push(TreeLine {
+        depth,
+        path: format!("{path}/"),
+        key: None,
+        text: close.to_string(),
+        expanded: None,
+    });
+}
+
+impl Render for JsonView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let colors = cx.theme().colors();
+        let line_height = cx.line_height();
+
+        v_flex()
+            .w_full()
+            .children(self.lines().into_iter().map(|line| {
+                let disclosure = line.expanded.map(|expanded| {
+                    let path = line.path.clone();
+                    Disclosure::new(
+                        SharedString::from(format!("disclosure-{}", line.path)),
+                        expanded,
+                    )
+                    .on_click(cx.listener(move |this, _: &ClickEvent, cx| this.toggle(&path, cx)))
+                });
+
+                h_flex()
+                    .h(line_height)
+                    .pl(line_height * line.depth as f32)
+                    .gap_1()
+                    .child(div().w(line_height).flex_none().children(disclosure))
+                    .children(line.key.map(|key| {
+                        div()
+                            .text_color(colors.text_accent)
+                            .child(format!("{key}:"))
+                    }))
+                    .child(line.text)
+            }))
+    }
+}
+
+impl LineHeight for View<JsonView> {
+    fn num_lines(&self, cx: &mut WindowContext) -> u8 {
+        self.read(cx).lines().len().min(u8::MAX as usize) as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn render_lines(view: &JsonView) -> Vec<String> {
+        view.lines()
+            .into_iter()
+            .map(|line| {
+                let marker = match line.expanded {
+                    Some(true) => "v ",
+                    Some(false) => "> ",
+                    None => "",
+                };
+                let key = line.key.map(|key| format!("{key}: ")).unwrap_or_default();
+                format!("{}{marker}{key}{}", "  ".repeat(line.depth), line.text)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_json_tree() {
+        let mut view = JsonView::new(json!({
+            "name": "zed",
+            "tags": ["editor", "rust"],
+            "owner": {"id": 1},
+            "empty": {},
+        }));
+        assert_eq!(
+            render_lines(&view),
+            [
+                "v {",
+                "  name: \"zed\"",
+                "  > tags: […] 2 items",
+                "  > owner: {…} 1 key",
+                "  empty: {}",
+                "}",
+            ]
+        );
+
+        view.toggled_paths.insert("/tags".to_string());
+        view.toggled_paths.insert(String::new());
+        assert_eq!(render_lines(&view), ["> {…} 4 keys"]);
+
+        view.toggled_paths.remove("");
+        assert_eq!(
+            render_lines(&view),
+            [
+                "v {",
+                "  name: \"zed\"",
+                "  v tags: [",
+                "    0: \"editor\"",
+                "    1: \"rust\"",
+                "  ]",
+                "  > owner: {…} 1 key",
+                "  empty: {}",
+                "}",
+            ]
+        );
+
+        assert_eq!(render_lines(&JsonView::new(json!(42))), ["42"]);
+    }
+}
@@ -0,0 +1,325 @@
+//! A client for the REST and websocket APIs of Jupyter servers, such as JupyterLab, to start
+//! kernels on them or connect to the kernels they are already running.
+
+use crate::kernels::{JupyterMessageChannel, KernelChannels, KernelSource, KernelSpecification};
+use anyhow::{anyhow, Context as _, Result};
+use async_tungstenite::tungstenite::{self, Message as WebSocketMessage};
+use collections::HashMap;
+use futures::{
+    channel::mpsc, stream::BoxStream, AsyncReadExt as _, Sink, SinkExt as _, StreamExt as _,
+};
+use gpui::AsyncAppContext;
+use http::{AsyncBody, HttpClient, Method, Request, Url};
+use runtimelib::{JupyterKernelspec, JupyterMessage, JupyterMessageContent};
+use serde::Deserialize;
+use serde_json::Value;
+use std::pin::Pin;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JupyterServer {
+    pub url: String,
+    pub token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct KernelSpecsResponse {
+    kernelspecs: HashMap<String, KernelSpecResponse>,
+}
+
+#[derive(Deserialize)]
+struct KernelSpecResponse {
+    name: String,
+    spec: JupyterKernelspec,
+}
+
+#[derive(Deserialize)]
+struct KernelResponse {
+    id: String,
+    name: String,
+}
+
+type WebSocketSink = Pin<Box<dyn Sink<WebSocketMessage, Error = tungstenite::Error> + Send>>;
+type WebSocketStream = BoxStream<'static, Result<WebSocketMessage, tungstenite::Error>>;
+
+impl JupyterServer {
+    /// The host and port of the server, which identify it to the user.
+    pub fn host(&self) -> String {
+        Url::parse(&self.url)
+            .ok()
+            .and_then(|url| {
+                let host = url.host_str()?;
+                Some(match url.port() {
+                    Some(port) => format!("{host}:{port}"),
+                    None => host.to_string(),
+                })
+            })
+            .unwrap_or_else(|| self.url.clone())
+    }
+
+    fn api_url(&self, path: &str) -> String {
+        format!("{}/api/{}", self.url.trim_end_matches('/'), path)
+    }
+
+    async fn request(
+        &self,
+        http_client: &dyn HttpClient,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> Result<String> {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(self.api_url(path))
+            .header("Content-Type", "application/json");
+        if let Some(token) = &self.token {
+            request = request.header("Authorization", format!("token {token}"));
+        }
+        let body = match body {
+            Some(body) => AsyncBody::from(serde_json::to_string(&body)?),
+            None => AsyncBody::empty(),
+        };
+
+        let mut response = http_client.send(request.body(body)?).await?;
+        let mut text = String::new();
+        response.body_mut().read_to_string(&mut text).await?;
+        anyhow::ensure!(
+            response.status().is_success(),
+            "Jupyter server at {} responded with {}: {}",
+            self.host(),
+            response.status(),
+            text
+        );
+        Ok(text)
+    }
+
+    /// The kernels that can be started on the server, followed by the kernels it is running.
+    pub async fn kernel_specifications(
+        &self,
+        http_client: &dyn HttpClient,
+    ) -> Result<Vec<KernelSpecification>> {
+        let response = self
+            .request(http_client, Method::GET, "kernelspecs", None)
+            .await?;
+        let mut kernelspecs = serde_json::from_str::<KernelSpecsResponse>(&response)?
+            .kernelspecs
+            .into_values()
+            .collect::<Vec<_>>();
+        kernelspecs.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let response = self
+            .request(http_client, Method::GET, "kernels", None)
+            .await?;
+        let kernels = serde_json::from_str::<Vec<KernelResponse>>(&response)?;
+
+        let host = self.host();
+        let mut specifications = kernelspecs
+            .iter()
+            .map(|kernelspec| KernelSpecification {
+                name: format!("{host}/{}", kernelspec.name),
+                kernelspec: kernelspec.spec.clone(),
+                source: KernelSource::JupyterServer {
+                    server: self.clone(),
+                    kernel_name: kernelspec.name.clone(),
+                    kernel_id: None,
+                },
+            })
+            .collect::<Vec<_>>();
+
+        for kernel in kernels {
+            let Some(kernelspec) = kernelspecs
+                .iter()
+                .find(|kernelspec| kernelspec.name == kernel.name)
+            else {
+                continue;
+            };
+            let short_id = kernel.id.get(..8).unwrap_or(&kernel.id);
+            specifications.push(KernelSpecification {
+                name: format!("{host}/{} ({short_id})", kernel.name),
+                kernelspec: kernelspec.spec.clone(),
+                source: KernelSource::JupyterServer {
+                    server: self.clone(),
+                    kernel_name: kernel.name,
+                    kernel_id: Some(kernel.id),
+                },
+            });
+        }
+
+        Ok(specifications)
+    }
+
+    /// Starts a kernel on the server, returning its ID.
+    pub async fn start_kernel(
+        &self,
+        http_client: &dyn HttpClient,
+        kernel_name: &str,
+    ) -> Result<String> {
+        let body = serde_json::json!({ "name": kernel_name });
+        let response = self
+            .request(http_client, Method::POST, "kernels", Some(body))
+            .await?;
+        Ok(serde_json::from_str::<KernelResponse>(&response)?.id)
+    }
+
+    pub async fn delete_kernel(&self, http_client: &dyn HttpClient, kernel_id: &str) -> Result<()> {
+        self.request(
+            http_client,
+            Method::DELETE,
+            &format!("kernels/{kernel_id}"),
+            None,
+        )
+        .await?;
+        Ok(())
+    }
+
+    fn channels_url(&self, kernel_id: &str, session_id: &str) -> Result<Url> {
+        let mut url = Url::parse(&self.api_url(&format!("kernels/{kernel_id}/channels")))?;
+        let scheme = match url.scheme() {
+            "http" => "ws",
+            "https" => "wss",
+            scheme => anyhow::bail!("unsupported scheme for a Jupyter server: {scheme}"),
+        };
+        url.set_scheme(scheme)
+            .map_err(|_| anyhow!("invalid Jupyter server URL: {}", self.url))?;
+        url.query_pairs_mut().append_pair("session_id", session_id);
+        if let Some(token) = &self.token {
+            url.query_pairs_mut().append_pair("token", token);
+        }
+        Ok(url)
+    }
+
+    /// Connects to the websocket of a kernel on the server, which carries the messages of all
+    /// of the kernel's channels.
+    pub async fn connect(&self, kernel_id: &str, cx: &AsyncAppContext) -> Result<KernelChannels> {
+        let url = self.channels_url(kernel_id, &uuid::Uuid::new_v4().to_string())?;
+        let host = url
+            .host_str()
+            .zip(url.port_or_known_default())
+            .ok_or_else(|| anyhow!("missing host in Jupyter server URL: {}", self.url))?;
+        let stream = smol::net::TcpStream::connect(host).await?;
+        let request = tungstenite::http::Request::builder()
+            .uri(url.as_str())
+            .body(())?;
+
+        let (mut sink, mut stream): (WebSocketSink, WebSocketStream) = if url.scheme() == "wss" {
+            let (socket, _) =
+                async_tungstenite::async_std::client_async_tls(request, stream).await?;
+            let (sink, stream) = socket.split();
+            (Box::pin(sink), stream.boxed())
+        } else {
+            let (socket, _) = async_tungstenite::client_async(request, stream).await?;
+            let (sink, stream) = socket.split();
+            (Box::pin(sink), stream.boxed())
+        };
+
+        let (request_tx, mut request_rx) = mpsc::channel::<JupyterMessage>(100);
+        let (mut messages_tx, messages_rx) = mpsc::channel(100);
+
+        let send_task = cx.background_executor().spawn(async move {
+            while let Some(message) = request_rx.next().await {
+                let text = serialize_message(&message)?;
+                sink.send(WebSocketMessage::Text(text)).await?;
+            }
+            anyhow::Ok(())
+        });
+
+        let receive_task = cx.background_executor().spawn(async move {
+            while let Some(message) = stream.next().await {
+                match message? {
+                    WebSocketMessage::Text(text) => match deserialize_message(&text) {
+                        Ok(message) => messages_tx.send(message).await?,
+                        Err(error) => log::warn!("Invalid message from Jupyter server: {error:?}"),
+                    },
+                    WebSocketMessage::Close(_) => break,
+                    _ => {}
+                }
+            }
+            anyhow::Ok(())
+        });
+
+        let mut messages = JupyterMessageChannel::new();
+        messages.push(messages_rx);
+        Ok(KernelChannels {
+            request_tx,
+            messages_rx: messages,
+            tasks: vec![send_task, receive_task],
+        })
+    }
+}
+
+/// The channel of the kernel a request is sent on, which the server needs to route it.
+fn channel(content: &JupyterMessageContent) -> &'static str {
+    match content {
+        JupyterMessageContent::DebugRequest(_)
+        | JupyterMessageContent::InterruptRequest(_)
+        | JupyterMessageContent::ShutdownRequest(_) => "control",
+        _ => "shell",
+    }
+}
+
+fn serialize_message(message: &JupyterMessage) -> Result<String> {
+    let mut value = serde_json::to_value(message)?;
+    value["channel"] = channel(&message.content).into();
+    Ok(serde_json::to_string(&value)?)
+}
+
+fn deserialize_message(text: &str) -> Result<JupyterMessage> {
+    let mut value = serde_json::from_str::<Value>(text)?;
+    // Messages that aren't replies have an empty parent header rather than none.
+    if value
+        .get("parent_header")
+        .and_then(Value::as_object)
+        .map_or(false, |header| header.is_empty())
+    {
+        value["parent_header"] = Value::Null;
+    }
+    serde_json::from_value(value).context("failed to parse Jupyter message")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use runtimelib::{ExecuteRequest, ShutdownRequest};
+
+    #[test]
+    fn test_urls() {
+        let server = JupyterServer {
+            url: "http://gpu-box:8888/".into(),
+            token: Some("secret".into()),
+        };
+        assert_eq!(server.host(), "gpu-box:8888");
+        assert_eq!(server.api_url("kernels"), "http://gpu-box:8888/api/kernels");
+        assert_eq!(
+            server.channels_url("1234", "abcd").unwrap().as_str(),
+            "ws://gpu-box:8888/api/kernels/1234/channels?session_id=abcd&token=secret"
+        );
+
+        let server = JupyterServer {
+            url: "https://example.com/jupyter".into(),
+            token: None,
+        };
+        assert_eq!(server.host(), "example.com");
+        assert_eq!(
+            server.channels_url("1234", "abcd").unwrap().as_str(),
+            "wss://example.com/jupyter/api/kernels/1234/channels?session_id=abcd"
+        );
+
+        let server = JupyterServer {
+            url: "ftp://example.com".into(),
+            token: None,
+        };
+        assert!(server.channels_url("1234", "abcd").is_err());
+    }
+
+    #[test]
+    fn test_channel() {
+        let message: JupyterMessage = ShutdownRequest { restart: false }.into();
+        assert_eq!(channel(&message.content), "control");
+
+        let message: JupyterMessage = ExecuteRequest {
+            code: "1 + 1".into(),
+            ..Default::default()
+        }
+        .into();
+        assert_eq!(channel(&message.content), "shell");
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, path::PathBuf};
 
 use editor::EditorSettings;
 use gpui::AppContext;
@@ -21,6 +21,9 @@ pub struct JupyterSettings {
     pub dock: JupyterDockPosition,
     pub default_width: Pixels,
     pub kernel_selections: HashMap<String, String>,
+    pub cell_markers: HashMap<String, Vec<String>>,
+    pub connection_files: Vec<ConnectionFileSettings>,
+    pub servers: Vec<JupyterServerSettings>,
 }
 
 impl JupyterSettings {
@@ -46,6 +49,43 @@ pub struct JupyterSettingsContent {
     ///
     /// Default: `{}`
     pub kernel_selections: Option<HashMap<String, String>>,
+    /// Markers that start a code cell, for each language. By default, cells start with the
+    /// language's line comment followed by `%%`, e.g. `# %%` or `// %%`.
+    ///
+    /// Default: `{}`
+    pub cell_markers: Option<HashMap<String, Vec<String>>>,
+    /// Kernels that are already running, to connect to through their connection files, e.g.
+    /// kernels in a container with forwarded ports.
+    ///
+    /// Default: `[]`
+    pub connection_files: Option<Vec<ConnectionFileSettings>>,
+    /// Jupyter servers whose kernels can be started or connected to.
+    ///
+    /// Default: `[]`
+    pub servers: Option<Vec<JupyterServerSettings>>,
+}
+
+#[derive(Clone, Serialize, Deserialize, JsonSchema, Debug, PartialEq, Eq)]
+pub struct ConnectionFileSettings {
+    /// The name to show for the kernel.
+    pub name: String,
+    /// The path to the kernel's connection file.
+    pub path: PathBuf,
+    /// The language of the kernel, e.g. `python`.
+    pub language: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, JsonSchema, Debug, PartialEq, Eq)]
+pub struct JupyterServerSettings {
+    /// The URL of the server, e.g. `http://localhost:8888`.
+    pub url: String,
+    /// The environment variable to read the server's token from. When unset, or when the
+    /// variable isn't set, the token is read from the keychain entry for the server's URL, and
+    /// requests are sent without a token if there is none.
+    ///
+    /// Default: null
+    #[serde(default)]
+    pub token_env_var: Option<String>,
 }
 
 impl JupyterSettingsContent {
@@ -60,6 +100,9 @@ impl Default for JupyterSettingsContent {
             dock: Some(JupyterDockPosition::Right),
             default_width: Some(640.0),
             kernel_selections: Some(HashMap::new()),
+            cell_markers: Some(HashMap::new()),
+            connection_files: Some(Vec::new()),
+            servers: Some(Vec::new()),
         }
     }
 }
@@ -92,6 +135,22 @@ impl Settings for JupyterSettings {
                     settings.kernel_selections.insert(k.clone(), v.clone());
                 }
             }
+
+            if let Some(source) = &value.cell_markers {
+                for (language, markers) in source {
+                    settings
+                        .cell_markers
+                        .insert(language.clone(), markers.clone());
+                }
+            }
+
+            if let Some(connection_files) = &value.connection_files {
+                settings.connection_files = connection_files.clone();
+            }
+
+            if let Some(servers) = &value.servers {
+                settings.servers = servers.clone();
+            }
         }
 
         Ok(settings)
@@ -149,4 +208,94 @@ mod tests {
             Pixels::from(800.0)
         );
     }
+
+    #[gpui::test]
+    fn test_deserialize_cell_markers(cx: &mut AppContext) {
+        let store = settings::SettingsStore::test(cx);
+        cx.set_global(store);
+
+        EditorSettings::register(cx);
+        JupyterSettings::register(cx);
+
+        assert!(JupyterSettings::get_global(cx).cell_markers.is_empty());
+
+        SettingsStore::update_global(cx, |store, cx| {
+            store
+                .set_user_settings(
+                    r#"{
+                        "jupyter": {
+                            "cell_markers": { "Haskell": ["-- %%", "{- %% -}"] }
+                        }
+                    }"#,
+                    cx,
+                )
+                .unwrap();
+        });
+
+        assert_eq!(
+            JupyterSettings::get_global(cx).cell_markers.get("Haskell"),
+            Some(&vec!["-- %%".to_string(), "{- %% -}".to_string()])
+        );
+        assert_eq!(
+            JupyterSettings::get_global(cx).cell_markers.get("Python"),
+            None
+        );
+    }
+
+    #[gpui::test]
+    fn test_deserialize_remote_kernel_settings(cx: &mut AppContext) {
+        let store = settings::SettingsStore::test(cx);
+        cx.set_global(store);
+
+        EditorSettings::register(cx);
+        JupyterSettings::register(cx);
+
+        assert!(JupyterSettings::get_global(cx).connection_files.is_empty());
+        assert!(JupyterSettings::get_global(cx).servers.is_empty());
+
+        SettingsStore::update_global(cx, |store, cx| {
+            store
+                .set_user_settings(
+                    r#"{
+                        "jupyter": {
+                            "connection_files": [
+                                {
+                                    "name": "GPU box",
+                                    "path": "/remote/kernel-1234.json",
+                                    "language": "python"
+                                }
+                            ],
+                            "servers": [
+                                { "url": "http://localhost:8888" },
+                                { "url": "https://example.com/jupyter", "token_env_var": "JUPYTER_TOKEN" }
+                            ]
+                        }
+                    }"#,
+                    cx,
+                )
+                .unwrap();
+        });
+
+        assert_eq!(
+            JupyterSettings::get_global(cx).connection_files,
+            vec![ConnectionFileSettings {
+                name: "GPU box".to_string(),
+                path: PathBuf::from("/remote/kernel-1234.json"),
+                language: "python".to_string(),
+            }]
+        );
+        assert_eq!(
+            JupyterSettings::get_global(cx).servers,
+            vec![
+                JupyterServerSettings {
+                    url: "http://localhost:8888".to_string(),
+                    token_env_var: None,
+                },
+                JupyterServerSettings {
+                    url: "https://example.com/jupyter".to_string(),
+                    token_env_var: Some("JUPYTER_TOKEN".to_string()),
+                },
+            ]
+        );
+    }
 }
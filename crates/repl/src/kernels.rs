@@ -1,33 +1,76 @@
+use crate::{
+    jupyter_server::JupyterServer,
+    jupyter_settings::{ConnectionFileSettings, JupyterServerSettings, JupyterSettings},
+};
 use anyhow::{Context as _, Result};
 use futures::{
     channel::mpsc::{self, Receiver},
-    future::Shared,
+    future::{BoxFuture, Shared},
     stream::{self, SelectAll, StreamExt},
-    SinkExt as _,
+    Future, FutureExt as _, SinkExt as _,
 };
-use gpui::{AppContext, EntityId, Task};
+use gpui::{AppContext, AsyncAppContext, EntityId, Task};
+use http::HttpClient;
 use project::Fs;
 use runtimelib::{
     dirs, ConnectionInfo, ExecutionState, JupyterKernelspec, JupyterMessage, JupyterMessageContent,
     KernelInfoReply,
 };
+use settings::Settings as _;
 use smol::{net::TcpListener, process::Command};
 use std::{
+    env,
     fmt::Debug,
     net::{IpAddr, Ipv4Addr, SocketAddr},
     path::PathBuf,
     sync::Arc,
 };
 use ui::{Color, Indicator};
+use util::ResultExt as _;
 
 #[derive(Debug, Clone)]
 pub struct KernelSpecification {
     pub name: String,
-    pub path: PathBuf,
     pub kernelspec: JupyterKernelspec,
+    pub source: KernelSource,
+}
+
+/// Where a kernel runs, and whether Zed starts it or connects to one that is already running.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KernelSource {
+    /// A kernel installed on this machine, which is launched from the kernelspec in the given
+    /// directory.
+    Local(PathBuf),
+    /// A kernel that is already running, whose ports are described by a connection file.
+    ConnectionFile(PathBuf),
+    /// A kernel managed by a Jupyter server, which is started on the server unless the ID of a
+    /// running kernel is given.
+    JupyterServer {
+        server: JupyterServer,
+        kernel_name: String,
+        kernel_id: Option<String>,
+    },
 }
 
 impl KernelSpecification {
+    /// Where the kernel runs, for kernels that don't run on this machine.
+    pub fn location(&self) -> Option<String> {
+        match &self.source {
+            KernelSource::Local(_) => None,
+            KernelSource::ConnectionFile(path) => {
+                Some(format!("Connection file {}", path.display()))
+            }
+            KernelSource::JupyterServer { server, .. } => {
+                Some(format!("Jupyter server {}", server.url))
+            }
+        }
+    }
+
+    /// Whether the kernel is launched by Zed, rather than connected to.
+    pub fn is_local(&self) -> bool {
+        matches!(self.source, KernelSource::Local(_))
+    }
+
     #[must_use]
     fn command(&self, connection_path: &PathBuf) -> anyhow::Result<Command> {
         let argv = &self.kernelspec.argv;
@@ -115,24 +158,53 @@ impl Kernel {
 }
 
 pub struct RunningKernel {
-    pub process: smol::process::Child,
-    _shell_task: Task<anyhow::Result<()>>,
-    _iopub_task: Task<anyhow::Result<()>>,
-    _control_task: Task<anyhow::Result<()>>,
-    _routing_task: Task<anyhow::Result<()>>,
-    connection_path: PathBuf,
+    connection: KernelConnection,
+    _tasks: Vec<Task<anyhow::Result<()>>>,
     pub request_tx: mpsc::Sender<JupyterMessage>,
     pub execution_state: ExecutionState,
     pub kernel_info: Option<KernelInfoReply>,
 }
 
-type JupyterMessageChannel = stream::SelectAll<Receiver<JupyterMessage>>;
+enum KernelConnection {
+    /// A kernel process launched by Zed, along with the connection file written for it.
+    Process {
+        process: smol::process::Child,
+        connection_path: PathBuf,
+    },
+    /// A kernel that was already running, which is left running when disconnecting from it.
+    ConnectionFile,
+    /// A kernel on a Jupyter server, which is deleted on shutdown if Zed started it.
+    JupyterServer {
+        server: JupyterServer,
+        kernel_id: String,
+        started: bool,
+        http_client: Arc<dyn HttpClient>,
+    },
+}
+
+pub(crate) type JupyterMessageChannel = stream::SelectAll<Receiver<JupyterMessage>>;
+
+/// The channels to send requests to a kernel and receive its messages, along with the tasks
+/// that carry the messages over its sockets.
+pub(crate) struct KernelChannels {
+    pub request_tx: mpsc::Sender<JupyterMessage>,
+    pub messages_rx: JupyterMessageChannel,
+    pub tasks: Vec<Task<anyhow::Result<()>>>,
+}
 
 impl Debug for RunningKernel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("RunningKernel")
-            .field("process", &self.process)
-            .finish()
+        let mut debug = f.debug_struct("RunningKernel");
+        match &self.connection {
+            KernelConnection::Process { process, .. } => debug.field("process", process),
+            KernelConnection::ConnectionFile => debug.field("connection", &"connection file"),
+            KernelConnection::JupyterServer {
+                server, kernel_id, ..
+            } => debug
+                .field("server", &server.url)
+                .field("kernel_id", kernel_id),
+        };
+        debug.finish()
     }
 }
 
@@ -142,6 +214,141 @@ impl RunningKernel {
         entity_id: EntityId,
         fs: Arc<dyn Fs>,
         cx: &mut AppContext,
+    ) -> Task<anyhow::Result<(Self, JupyterMessageChannel)>> {
+        match kernel_specification.source.clone() {
+            KernelSource::Local(_) => Self::launch(kernel_specification, entity_id, fs, cx),
+            KernelSource::ConnectionFile(connection_path) => Self::attach(connection_path, fs, cx),
+            KernelSource::JupyterServer {
+                server,
+                kernel_name,
+                kernel_id,
+            } => Self::connect_to_server(server, kernel_name, kernel_id, cx),
+        }
+    }
+
+    /// Whether shutting down should stop the kernel. Kernels that were already running are only
+    /// disconnected from, since they may be in use elsewhere.
+    pub fn owns_kernel(&self) -> bool {
+        match &self.connection {
+            KernelConnection::Process { .. } => true,
+            KernelConnection::ConnectionFile => false,
+            KernelConnection::JupyterServer { started, .. } => *started,
+        }
+    }
+
+    /// Stops the kernel if Zed started it.
+    pub fn kill(&mut self) -> BoxFuture<'static, ()> {
+        match &mut self.connection {
+            KernelConnection::Process { process, .. } => {
+                process.kill().ok();
+                futures::future::ready(()).boxed()
+            }
+            KernelConnection::ConnectionFile => futures::future::ready(()).boxed(),
+            KernelConnection::JupyterServer {
+                server,
+                kernel_id,
+                started,
+                http_client,
+            } => {
+                if !*started {
+                    return futures::future::ready(()).boxed();
+                }
+                let server = server.clone();
+                let kernel_id = kernel_id.clone();
+                let http_client = http_client.clone();
+                async move {
+                    server
+                        .delete_kernel(http_client.as_ref(), &kernel_id)
+                        .await
+                        .log_err();
+                }
+                .boxed()
+            }
+        }
+    }
+
+    fn running(
+        connection: KernelConnection,
+        channels: KernelChannels,
+    ) -> (Self, JupyterMessageChannel) {
+        (
+            Self {
+                connection,
+                _tasks: channels.tasks,
+                request_tx: channels.request_tx,
+                execution_state: ExecutionState::Busy,
+                kernel_info: None,
+            },
+            channels.messages_rx,
+        )
+    }
+
+    /// Connects to a kernel that is already running, through its connection file.
+    fn attach(
+        connection_path: PathBuf,
+        fs: Arc<dyn Fs>,
+        cx: &mut AppContext,
+    ) -> Task<anyhow::Result<(Self, JupyterMessageChannel)>> {
+        cx.spawn(|cx| async move {
+            let content = fs
+                .load(&connection_path)
+                .await
+                .with_context(|| format!("Failed to read connection file {connection_path:?}"))?;
+            let connection_info = serde_json::from_str::<ConnectionInfo>(&content)
+                .with_context(|| format!("Invalid connection file {connection_path:?}"))?;
+            let channels = connect_sockets(&connection_info, &cx).await?;
+            Ok(Self::running(KernelConnection::ConnectionFile, channels))
+        })
+    }
+
+    /// Starts a kernel on a Jupyter server, or connects to one it is already running.
+    fn connect_to_server(
+        server: JupyterServer,
+        kernel_name: String,
+        kernel_id: Option<String>,
+        cx: &mut AppContext,
+    ) -> Task<anyhow::Result<(Self, JupyterMessageChannel)>> {
+        let http_client = cx.http_client();
+        cx.spawn(|cx| async move {
+            let started = kernel_id.is_none();
+            let kernel_id = match kernel_id {
+                Some(kernel_id) => kernel_id,
+                None => {
+                    server
+                        .start_kernel(http_client.as_ref(), &kernel_name)
+                        .await?
+                }
+            };
+
+            let channels = match server.connect(&kernel_id, &cx).await {
+                Ok(channels) => channels,
+                Err(error) => {
+                    if started {
+                        server
+                            .delete_kernel(http_client.as_ref(), &kernel_id)
+                            .await
+                            .log_err();
+                    }
+                    return Err(error);
+                }
+            };
+
+            let connection = KernelConnection::JupyterServer {
+                server,
+                kernel_id,
+                started,
+                http_client,
+            };
+            Ok(Self::running(connection, channels))
+        })
+    }
+
+    /// Launches a kernel installed on this machine.
+    fn launch(
+        kernel_specification: KernelSpecification,
+        entity_id: EntityId,
+        fs: Arc<dyn Fs>,
+        cx: &mut AppContext,
     ) -> Task<anyhow::Result<(Self, JupyterMessageChannel)>> {
         cx.spawn(|cx| async move {
             let ip = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
@@ -177,97 +384,104 @@ impl RunningKernel {
                 .spawn()
                 .context("failed to start the kernel process")?;
 
-            let mut iopub_socket = connection_info.create_client_iopub_connection("").await?;
-            let mut shell_socket = connection_info.create_client_shell_connection().await?;
-            let mut control_socket = connection_info.create_client_control_connection().await?;
+            let channels = connect_sockets(&connection_info, &cx).await?;
+            let connection = KernelConnection::Process {
+                process,
+                connection_path,
+            };
+            Ok(Self::running(connection, channels))
+        })
+    }
+}
 
-            let (mut iopub, iosub) = futures::channel::mpsc::channel(100);
+/// Connects to the ZeroMQ sockets of a kernel.
+async fn connect_sockets(
+    connection_info: &ConnectionInfo,
+    cx: &AsyncAppContext,
+) -> Result<KernelChannels> {
+    let mut iopub_socket = connection_info.create_client_iopub_connection("").await?;
+    let mut shell_socket = connection_info.create_client_shell_connection().await?;
+    let mut control_socket = connection_info.create_client_control_connection().await?;
 
-            let (request_tx, mut request_rx) =
-                futures::channel::mpsc::channel::<JupyterMessage>(100);
+    let (mut iopub, iosub) = futures::channel::mpsc::channel(100);
 
-            let (mut control_reply_tx, control_reply_rx) = futures::channel::mpsc::channel(100);
-            let (mut shell_reply_tx, shell_reply_rx) = futures::channel::mpsc::channel(100);
+    let (request_tx, mut request_rx) = futures::channel::mpsc::channel::<JupyterMessage>(100);
 
-            let mut messages_rx = SelectAll::new();
-            messages_rx.push(iosub);
-            messages_rx.push(control_reply_rx);
-            messages_rx.push(shell_reply_rx);
+    let (mut control_reply_tx, control_reply_rx) = futures::channel::mpsc::channel(100);
+    let (mut shell_reply_tx, shell_reply_rx) = futures::channel::mpsc::channel(100);
 
-            let _iopub_task = cx.background_executor().spawn({
-                async move {
-                    while let Ok(message) = iopub_socket.read().await {
-                        iopub.send(message).await?;
-                    }
-                    anyhow::Ok(())
-                }
-            });
+    let mut messages_rx = SelectAll::new();
+    messages_rx.push(iosub);
+    messages_rx.push(control_reply_rx);
+    messages_rx.push(shell_reply_rx);
 
-            let (mut control_request_tx, mut control_request_rx) =
-                futures::channel::mpsc::channel(100);
-            let (mut shell_request_tx, mut shell_request_rx) = futures::channel::mpsc::channel(100);
-
-            let _routing_task = cx.background_executor().spawn({
-                async move {
-                    while let Some(message) = request_rx.next().await {
-                        match message.content {
-                            JupyterMessageContent::DebugRequest(_)
-                            | JupyterMessageContent::InterruptRequest(_)
-                            | JupyterMessageContent::ShutdownRequest(_) => {
-                                control_request_tx.send(message).await?;
-                            }
-                            _ => {
-                                shell_request_tx.send(message).await?;
-                            }
-                        }
+    let iopub_task = cx.background_executor().spawn({
+        async move {
+            while let Ok(message) = iopub_socket.read().await {
+                iopub.send(message).await?;
+            }
+            anyhow::Ok(())
+        }
+    });
+
+    let (mut control_request_tx, mut control_request_rx) = futures::channel::mpsc::channel(100);
+    let (mut shell_request_tx, mut shell_request_rx) = futures::channel::mpsc::channel(100);
+
+    let routing_task = cx.background_executor().spawn({
+        async move {
+            while let Some(message) = request_rx.next().await {
+                match message.content {
+                    JupyterMessageContent::DebugRequest(_)
+                    | JupyterMessageContent::InterruptRequest(_)
+                    | JupyterMessageContent::ShutdownRequest(_) => {
+                        control_request_tx.send(message).await?;
                     }
-                    anyhow::Ok(())
-                }
-            });
-
-            let _shell_task = cx.background_executor().spawn({
-                async move {
-                    while let Some(message) = shell_request_rx.next().await {
-                        shell_socket.send(message).await.ok();
-                        let reply = shell_socket.read().await?;
-                        shell_reply_tx.send(reply).await?;
+                    _ => {
+                        shell_request_tx.send(message).await?;
                     }
-                    anyhow::Ok(())
                 }
-            });
+            }
+            anyhow::Ok(())
+        }
+    });
+
+    let shell_task = cx.background_executor().spawn({
+        async move {
+            while let Some(message) = shell_request_rx.next().await {
+                shell_socket.send(message).await.ok();
+                let reply = shell_socket.read().await?;
+                shell_reply_tx.send(reply).await?;
+            }
+            anyhow::Ok(())
+        }
+    });
+
+    let control_task = cx.background_executor().spawn({
+        async move {
+            while let Some(message) = control_request_rx.next().await {
+                control_socket.send(message).await.ok();
+                let reply = control_socket.read().await?;
+                control_reply_tx.send(reply).await?;
+            }
+            anyhow::Ok(())
+        }
+    });
 
-            let _control_task = cx.background_executor().spawn({
-                async move {
-                    while let Some(message) = control_request_rx.next().await {
-                        control_socket.send(message).await.ok();
-                        let reply = control_socket.read().await?;
-                        control_reply_tx.send(reply).await?;
-                    }
-                    anyhow::Ok(())
-                }
-            });
-
-            anyhow::Ok((
-                Self {
-                    process,
-                    request_tx,
-                    _shell_task,
-                    _iopub_task,
-                    _control_task,
-                    _routing_task,
-                    connection_path,
-                    execution_state: ExecutionState::Busy,
-                    kernel_info: None,
-                },
-                messages_rx,
-            ))
-        })
-    }
+    anyhow::Ok(KernelChannels {
+        request_tx,
+        messages_rx,
+        tasks: vec![iopub_task, routing_task, shell_task, control_task],
+    })
 }
 
 impl Drop for RunningKernel {
     fn drop(&mut self) {
-        std::fs::remove_file(&self.connection_path).ok();
+        if let KernelConnection::Process {
+            connection_path, ..
+        } = &self.connection
+        {
+            std::fs::remove_file(connection_path).ok();
+        }
 
         self.request_tx.close_channel();
     }
@@ -296,8 +510,8 @@ async fn read_kernelspec_at(
 
     Ok(KernelSpecification {
         name: kernel_name,
-        path,
         kernelspec: spec,
+        source: KernelSource::Local(path),
     })
 }
 
@@ -322,7 +536,92 @@ async fn read_kernels_dir(path: PathBuf, fs: &dyn Fs) -> anyhow::Result<Vec<Kern
     Ok(valid_kernelspecs)
 }
 
-pub async fn kernel_specifications(fs: Arc<dyn Fs>) -> anyhow::Result<Vec<KernelSpecification>> {
+/// The kernels installed on this machine, followed by the remote kernels configured in the
+/// settings.
+pub fn kernel_specifications(
+    fs: Arc<dyn Fs>,
+    cx: &AppContext,
+) -> impl Future<Output = anyhow::Result<Vec<KernelSpecification>>> {
+    let settings = JupyterSettings::get_global(cx);
+    let connection_files = settings.connection_files.clone();
+    let servers = settings
+        .servers
+        .iter()
+        .map(|settings| (settings.url.clone(), jupyter_server_token(settings, cx)))
+        .collect::<Vec<_>>();
+    let http_client = cx.http_client();
+
+    async move {
+        let mut kernel_specifications = local_kernel_specifications(fs).await?;
+        kernel_specifications.extend(
+            connection_files
+                .iter()
+                .filter_map(|settings| connection_file_specification(settings).log_err()),
+        );
+
+        let servers = servers.into_iter().map(|(url, token)| {
+            let http_client = http_client.clone();
+            async move {
+                let server = JupyterServer {
+                    token: token
+                        .await
+                        .with_context(|| format!("Failed to read the token of {url}"))?,
+                    url,
+                };
+                server
+                    .kernel_specifications(http_client.as_ref())
+                    .await
+                    .with_context(|| format!("Failed to list kernels of {}", server.url))
+            }
+        });
+        for server_specifications in futures::future::join_all(servers).await {
+            kernel_specifications.extend(server_specifications.log_err().into_iter().flatten());
+        }
+
+        Ok(kernel_specifications)
+    }
+}
+
+/// Reads the token of a Jupyter server from the environment variable configured for it, or
+/// else from the keychain, where it's stored under the server's URL.
+fn jupyter_server_token(
+    settings: &JupyterServerSettings,
+    cx: &AppContext,
+) -> Task<Result<Option<String>>> {
+    if let Some(token) = settings
+        .token_env_var
+        .as_ref()
+        .and_then(|token_env_var| env::var(token_env_var).ok())
+    {
+        return Task::ready(Ok(Some(token)));
+    }
+
+    let read_credentials = cx.read_credentials(&settings.url);
+    cx.background_executor().spawn(async move {
+        match read_credentials.await? {
+            Some((_, token)) => Ok(Some(String::from_utf8(token)?)),
+            None => Ok(None),
+        }
+    })
+}
+
+fn connection_file_specification(
+    settings: &ConnectionFileSettings,
+) -> anyhow::Result<KernelSpecification> {
+    // The kernel is already running, so there's no command to launch it with.
+    let kernelspec = serde_json::from_value::<JupyterKernelspec>(serde_json::json!({
+        "argv": [],
+        "display_name": settings.name,
+        "language": settings.language,
+    }))?;
+    Ok(KernelSpecification {
+        name: settings.name.clone(),
+        kernelspec,
+        source: KernelSource::ConnectionFile(settings.path.clone()),
+    })
+}
+
+async fn local_kernel_specifications(fs: Arc<dyn Fs>) -> anyhow::Result<Vec<KernelSpecification>> {
     let data_dirs = dirs::data_dirs();
     let kernel_dirs = data_dirs
         .iter()
@@ -396,4 +695,26 @@ mod test {
             vec!["deno", "python"]
         );
     }
+
+    #[gpui::test]
+    async fn test_jupyter_server_token(cx: &mut TestAppContext) {
+        let settings = |token_env_var: &str| JupyterServerSettings {
+            url: "http://localhost:8888".into(),
+            token_env_var: Some(token_env_var.into()),
+        };
+
+        env::set_var("ZED_TEST_JUPYTER_TOKEN", "secret");
+        let token = cx
+            .update(|cx| jupyter_server_token(&settings("ZED_TEST_JUPYTER_TOKEN"), cx))
+            .await
+            .unwrap();
+        assert_eq!(token.as_deref(), Some("secret"));
+
+        // Without the variable, the token is read from the keychain, which has none.
+        let token = cx
+            .update(|cx| jupyter_server_token(&settings("ZED_TEST_UNSET_JUPYTER_TOKEN"), cx))
+            .await
+            .unwrap();
+        assert_eq!(token, None);
+    }
 }
@@ -0,0 +1,310 @@
+//! A readable plain text fallback for `text/latex` outputs, which converts common math
+//! commands to their Unicode equivalents, e.g. `$\frac{1}{2} \alpha^2$` to `1/2 α²`.
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Converts LaTeX math to plain text. Commands that have no plain text equivalent are kept as-is.
+pub fn latex_to_text(source: &str) -> String {
+    let source = strip_math_delimiters(source.trim());
+    let mut chars = source.chars().peekable();
+    let text = convert_group(&mut chars);
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn strip_math_delimiters(source: &str) -> &str {
+    for (start, end) in [("$$", "$$"), ("\\[", "\\]"), ("\\(", "\\)"), ("$", "$")] {
+        if let Some(inner) = source
+            .strip_prefix(start)
+            .and_then(|source| source.strip_suffix(end))
+        {
+            return inner;
+        }
+    }
+    source
+}
+
+/// Converts text until the end of the input or the closing brace of the current group.
+fn convert_group(chars: &mut Peekable<Chars>) -> String {
+    let mut text = String::new();
+    while let Some(char) = chars.next() {
+        if char == '}' {
+            break;
+        }
+        text.push_str(&convert_token(char, chars));
+    }
+    text
+}
+
+fn convert_token(char: char, chars: &mut Peekable<Chars>) -> String {
+    match char {
+        '\\' => convert_command(chars),
+        '{' => convert_group(chars),
+        '^' => {
+            let argument = convert_argument(chars);
+            script(&argument, '^', superscript)
+        }
+        '_' => {
+            let argument = convert_argument(chars);
+            script(&argument, '_', subscript)
+        }
+        '&' | '~' => " ".to_string(),
+        '$' => String::new(),
+        char => char.to_string(),
+    }
+}
+
+/// Converts the argument of a command or script, which is either a group or a single token.
+fn convert_argument(chars: &mut Peekable<Chars>) -> String {
+    while chars.next_if(|char| char.is_whitespace()).is_some() {}
+    match chars.next() {
+        Some(char) => convert_token(char, chars),
+        None => String::new(),
+    }
+}
+
+fn convert_command(chars: &mut Peekable<Chars>) -> String {
+    let mut name = String::new();
+    while let Some(char) = chars.next_if(|char| char.is_ascii_alphabetic()) {
+        name.push(char);
+    }
+    if name.is_empty() {
+        return match chars.next() {
+            Some('\\') => "\n".to_string(),
+            Some(',' | ';' | ':' | ' ') => " ".to_string(),
+            Some('!') | None => String::new(),
+            Some(char) => char.to_string(),
+        };
+    }
+
+    match name.as_str() {
+        "frac" | "dfrac" | "tfrac" => {
+            let numerator = convert_argument(chars);
+            let denominator = convert_argument(chars);
+            format!(
+                "{}/{}",
+                parenthesize(&numerator),
+                parenthesize(&denominator)
+            )
+        }
+        "sqrt" => {
+            // Skip the degree of the root, e.g. `\sqrt[3]{x}`.
+            if chars.next_if_eq(&'[').is_some() {
+                while chars.next().map_or(false, |char| char != ']') {}
+            }
+            format!("√{}", parenthesize(&convert_argument(chars)))
+        }
+        "text" | "textbf" | "textit" | "textrm" | "mathrm" | "mathbf" | "mathit" | "mathsf"
+        | "mathtt" | "mathcal" | "operatorname" | "boldsymbol" => convert_argument(chars),
+        "mathbb" => convert_argument(chars)
+            .chars()
+            .map(|char| match char {
+                'C' => 'ℂ',
+                'N' => 'ℕ',
+                'Q' => 'ℚ',
+                'R' => 'ℝ',
+                'Z' => 'ℤ',
+                char => char,
+            })
+            .collect(),
+        "begin" | "end" => {
+            convert_argument(chars);
+            String::new()
+        }
+        "left" | "right" | "displaystyle" | "limits" | "nolimits" | "big" | "Big" | "bigg"
+        | "Bigg" => String::new(),
+        "quad" | "qquad" => "  ".to_string(),
+        "sin" | "cos" | "tan" | "log" | "ln" | "exp" | "lim" | "max" | "min" | "det" | "dim" => {
+            name
+        }
+        _ => match symbol(&name) {
+            Some(symbol) => symbol.to_string(),
+            None => format!("\\{name}"),
+        },
+    }
+}
+
+fn parenthesize(text: &str) -> String {
+    if text.chars().count() > 1 && !text.chars().all(char::is_alphanumeric) {
+        format!("({text})")
+    } else {
+        text.to_string()
+    }
+}
+
+fn script(text: &str, marker: char, map: fn(char) -> Option<char>) -> String {
+    if let Some(mapped) = text.chars().map(map).collect::<Option<String>>() {
+        mapped
+    } else if text.chars().count() == 1 {
+        format!("{marker}{text}")
+    } else {
+        format!("{marker}({text})")
+    }
+}
+
+fn superscript(char: char) -> Option<char> {
+    Some(match char {
+        '0' => '⁰',
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4' => '⁴',
+        '5' => '⁵',
+        '6' => '⁶',
+        '7' => '⁷',
+        '8' => '⁸',
+        '9' => '⁹',
+        '+' => '⁺',
+        '-' => '⁻',
+        '=' => '⁼',
+        '(' => '⁽',
+        ')' => '⁾',
+        'i' => 'ⁱ',
+        'n' => 'ⁿ',
+        _ => return None,
+    })
+}
+
+fn subscript(char: char) -> Option<char> {
+    Some(match char {
+        '0' => '₀',
+        '1' => '₁',
+        '2' => '₂',
+        '3' => '₃',
+        '4' => '₄',
+        '5' => '₅',
+        '6' => '₆',
+        '7' => '₇',
+        '8' => '₈',
+        '9' => '₉',
+        '+' => '₊',
+        '-' => '₋',
+        '=' => '₌',
+        '(' => '₍',
+        ')' => '₎',
+        'a' => 'ₐ',
+        'e' => 'ₑ',
+        'i' => 'ᵢ',
+        'j' => 'ⱼ',
+        'o' => 'ₒ',
+        'x' => 'ₓ',
+        _ => return None,
+    })
+}
+
+fn symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" | "varepsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" | "vartheta" => "θ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "pi" => "π",
+        "rho" => "ρ",
+        "sigma" => "σ",
+        "tau" => "τ",
+        "upsilon" => "υ",
+        "phi" | "varphi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        "cdot" => "·",
+        "times" => "×",
+        "div" => "÷",
+        "pm" => "±",
+        "mp" => "∓",
+        "leq" | "le" => "≤",
+        "geq" | "ge" => "≥",
+        "neq" | "ne" => "≠",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "sim" => "∼",
+        "propto" => "∝",
+        "infty" => "∞",
+        "partial" => "∂",
+        "nabla" => "∇",
+        "sum" => "∑",
+        "prod" => "∏",
+        "int" => "∫",
+        "oint" => "∮",
+        "in" => "∈",
+        "notin" => "∉",
+        "subset" => "⊂",
+        "subseteq" => "⊆",
+        "supset" => "⊃",
+        "supseteq" => "⊇",
+        "cup" => "∪",
+        "cap" => "∩",
+        "forall" => "∀",
+        "exists" => "∃",
+        "emptyset" | "varnothing" => "∅",
+        "neg" | "lnot" => "¬",
+        "land" | "wedge" => "∧",
+        "lor" | "vee" => "∨",
+        "to" | "rightarrow" => "→",
+        "leftarrow" | "gets" => "←",
+        "leftrightarrow" => "↔",
+        "Rightarrow" | "implies" => "⇒",
+        "Leftarrow" => "⇐",
+        "Leftrightarrow" | "iff" => "⇔",
+        "mapsto" => "↦",
+        "ldots" | "dots" => "…",
+        "cdots" => "⋯",
+        "langle" => "⟨",
+        "rangle" => "⟩",
+        "circ" => "∘",
+        "hbar" => "ℏ",
+        "ell" => "ℓ",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latex_to_text() {
+        assert_eq!(
+            latex_to_text(r"$$\frac{1}{2} \alpha^2 + \beta_{i}$$"),
+            "1/2 α² + βᵢ"
+        );
+        assert_eq!(latex_to_text(r"$\sqrt{x^2 + y^2}$"), "√(x² + y²)");
+        assert_eq!(
+            latex_to_text(r"\[ f: \mathbb{R} \to \mathbb{R} \]"),
+            "f: ℝ → ℝ"
+        );
+        assert_eq!(
+            latex_to_text(r"$\sum_{k=1}^{n} k = \frac{n(n+1)}{2}$"),
+            "∑_(k=1)ⁿ k = (n(n+1))/2"
+        );
+        assert_eq!(
+            latex_to_text(r"\begin{align} a &= b \\ c &= d \end{align}"),
+            "a  = b\nc  = d"
+        );
+        assert_eq!(latex_to_text(r"$\unknown x$"), r"\unknown x");
+    }
+}
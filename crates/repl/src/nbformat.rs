@@ -0,0 +1,509 @@
+//! Reading and writing Jupyter notebooks in the nbformat v4 JSON format.
+//!
+//! Notebooks and their cells are kept as JSON objects, and only the fields we edit are
+//! replaced, so that saving preserves key order and any metadata we don't interpret.
+
+use crate::KernelSpecification;
+use anyhow::{Context as _, Result};
+use runtimelib::JupyterMessageContent;
+use serde::Serialize as _;
+use serde_json::{json, ser::PrettyFormatter, Map, Value};
+
+const DEFAULT_INDENT: usize = 1;
+
+#[derive(Clone, Debug)]
+pub struct Notebook {
+    json: Map<String, Value>,
+    pub cells: Vec<Cell>,
+    indent: usize,
+    trailing_newline: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CellKind {
+    Code,
+    Markdown,
+    Raw,
+}
+
+#[derive(Clone, Debug)]
+pub struct Cell {
+    json: Map<String, Value>,
+}
+
+impl Notebook {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut json = match serde_json::from_str(text).context("invalid notebook JSON")? {
+            Value::Object(json) => json,
+            _ => anyhow::bail!("notebook must be a JSON object"),
+        };
+
+        let version = json.get("nbformat").and_then(Value::as_u64);
+        anyhow::ensure!(
+            version == Some(4),
+            "unsupported nbformat version {}",
+            version.map_or("(missing)".to_string(), |version| version.to_string())
+        );
+
+        // Leave an empty array in place of the cells, so that they are written back at the
+        // same position in the notebook.
+        let cells = json
+            .get_mut("cells")
+            .and_then(Value::as_array_mut)
+            .context("notebook has no cells")?;
+        let cells = std::mem::take(cells)
+            .into_iter()
+            .map(|cell| match cell {
+                Value::Object(json) => Ok(Cell { json }),
+                _ => anyhow::bail!("notebook cells must be JSON objects"),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Jupyter writes notebooks with a single space of indentation, but other tools differ.
+        let indent = text
+            .lines()
+            .nth(1)
+            .map(|line| line.len() - line.trim_start_matches(' ').len())
+            .filter(|indent| *indent > 0)
+            .unwrap_or(DEFAULT_INDENT);
+
+        Ok(Self {
+            json,
+            cells,
+            indent,
+            trailing_newline: text.ends_with('\n'),
+        })
+    }
+
+    pub fn to_json_string(&self) -> Result<String> {
+        let mut json = self.json.clone();
+        json.insert(
+            "cells".into(),
+            Value::Array(
+                self.cells
+                    .iter()
+                    .map(|cell| Value::Object(cell.json.clone()))
+                    .collect(),
+            ),
+        );
+
+        let indent = " ".repeat(self.indent);
+        let mut serializer = serde_json::Serializer::with_formatter(
+            Vec::new(),
+            PrettyFormatter::with_indent(indent.as_bytes()),
+        );
+        json.serialize(&mut serializer)?;
+        let mut text = String::from_utf8(serializer.into_inner())?;
+        if self.trailing_newline {
+            text.push('\n');
+        }
+        Ok(text)
+    }
+
+    /// The language of the notebook's code cells, as recorded by the kernel that last ran it.
+    pub fn language_name(&self) -> Option<&str> {
+        let metadata = self.json.get("metadata")?;
+        metadata
+            .pointer("/language_info/name")
+            .or_else(|| metadata.pointer("/kernelspec/language"))
+            .and_then(Value::as_str)
+    }
+
+    pub fn kernel_name(&self) -> Option<&str> {
+        self.json
+            .get("metadata")?
+            .pointer("/kernelspec/name")
+            .and_then(Value::as_str)
+    }
+
+    pub fn set_kernelspec(&mut self, kernel_specification: &KernelSpecification) {
+        let metadata = self
+            .json
+            .entry("metadata")
+            .or_insert_with(|| Value::Object(Map::new()));
+        let Some(metadata) = metadata.as_object_mut() else {
+            return;
+        };
+
+        let kernelspec = metadata
+            .entry("kernelspec")
+            .or_insert_with(|| Value::Object(Map::new()));
+        if !kernelspec.is_object() {
+            *kernelspec = Value::Object(Map::new());
+        }
+        if let Some(kernelspec) = kernelspec.as_object_mut() {
+            kernelspec.insert(
+                "display_name".into(),
+                kernel_specification.kernelspec.display_name.clone().into(),
+            );
+            kernelspec.insert(
+                "language".into(),
+                kernel_specification.kernelspec.language.clone().into(),
+            );
+            kernelspec.insert("name".into(), kernel_specification.name.clone().into());
+        }
+    }
+}
+
+impl Cell {
+    pub fn kind(&self) -> CellKind {
+        match self.json.get("cell_type").and_then(Value::as_str) {
+            Some("code") => CellKind::Code,
+            Some("markdown") => CellKind::Markdown,
+            _ => CellKind::Raw,
+        }
+    }
+
+    pub fn source(&self) -> String {
+        self.json
+            .get("source")
+            .and_then(join_lines)
+            .unwrap_or_default()
+    }
+
+    /// Replaces the source of the cell, keeping it either a single string or a list of lines,
+    /// whichever the notebook used before.
+    pub fn set_source(&mut self, source: &str) {
+        if self.source() == source {
+            return;
+        }
+
+        let source = match self.json.get("source") {
+            Some(Value::String(_)) => Value::String(source.to_string()),
+            _ => split_lines(source),
+        };
+        self.json.insert("source".into(), source);
+    }
+
+    pub fn outputs(&self) -> &[Value] {
+        self.json
+            .get("outputs")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    pub fn push_output(&mut self, output: Value) {
+        match self.json.get_mut("outputs") {
+            Some(Value::Array(outputs)) => outputs.push(output),
+            _ => {
+                self.json
+                    .insert("outputs".into(), Value::Array(vec![output]));
+            }
+        }
+    }
+
+    pub fn clear_outputs(&mut self) {
+        if self.kind() == CellKind::Code {
+            self.json.insert("outputs".into(), Value::Array(Vec::new()));
+        }
+    }
+
+    pub fn execution_count(&self) -> Option<u64> {
+        self.json.get("execution_count").and_then(Value::as_u64)
+    }
+
+    pub fn set_execution_count(&mut self, execution_count: Option<u64>) {
+        if self.kind() == CellKind::Code {
+            self.json
+                .insert("execution_count".into(), execution_count.into());
+        }
+    }
+}
+
+/// Converts a message from the kernel into the output it should be saved as, if any.
+pub fn output_for_message(message: &JupyterMessageContent) -> Option<Value> {
+    // Keys are listed in sorted order, matching the notebooks Jupyter writes.
+    match message {
+        JupyterMessageContent::ExecuteResult(result) => {
+            let result = serde_json::to_value(result).ok()?;
+            Some(json!({
+                "data": split_data_lines(&result["data"]),
+                "execution_count": result["execution_count"],
+                "metadata": result["metadata"],
+                "output_type": "execute_result",
+            }))
+        }
+        JupyterMessageContent::DisplayData(display) => {
+            let display = serde_json::to_value(display).ok()?;
+            Some(json!({
+                "data": split_data_lines(&display["data"]),
+                "metadata": display["metadata"],
+                "output_type": "display_data",
+            }))
+        }
+        JupyterMessageContent::StreamContent(stream) => {
+            let stream = serde_json::to_value(stream).ok()?;
+            Some(json!({
+                "name": stream["name"],
+                "output_type": "stream",
+                "text": split_lines(stream["text"].as_str().unwrap_or_default()),
+            }))
+        }
+        JupyterMessageContent::ErrorOutput(error) => Some(json!({
+            "ename": error.ename,
+            "evalue": error.evalue,
+            "output_type": "error",
+            "traceback": error.traceback,
+        })),
+        _ => None,
+    }
+}
+
+/// Joins a multi-line string, which nbformat stores either as a string or as a list of lines.
+pub fn join_lines(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Array(lines) => lines
+            .iter()
+            .map(|line| line.as_str())
+            .collect::<Option<String>>(),
+        _ => None,
+    }
+}
+
+/// Splits a multi-line string into lines that keep their trailing newlines, as Jupyter does.
+pub fn split_lines(text: &str) -> Value {
+    Value::Array(
+        text.split_inclusive('\n')
+            .map(|line| Value::String(line.to_string()))
+            .collect(),
+    )
+}
+
+/// Joins the textual values of a MIME bundle. JSON values are left untouched.
+pub fn join_data_lines(data: &Value) -> Value {
+    map_text_data(data, |value| {
+        join_lines(value).map_or_else(|| value.clone(), Value::String)
+    })
+}
+
+fn split_data_lines(data: &Value) -> Value {
+    map_text_data(data, |value| match value {
+        Value::String(text) => split_lines(text),
+        _ => value.clone(),
+    })
+}
+
+fn map_text_data(data: &Value, mut f: impl FnMut(&Value) -> Value) -> Value {
+    let Some(data) = data.as_object() else {
+        return data.clone();
+    };
+
+    Value::Object(
+        data.iter()
+            .map(|(mime_type, value)| {
+                let value = if mime_type.ends_with("json") {
+                    value.clone()
+                } else {
+                    f(value)
+                };
+                (mime_type.clone(), value)
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KernelSource;
+    use runtimelib::JupyterKernelspec;
+    use std::path::PathBuf;
+
+    const NOTEBOOK: &str = r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "id": "intro",
+   "metadata": {
+    "tags": ["intro"]
+   },
+   "source": [
+    "# Title\n",
+    "Some text"
+   ]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 3,
+   "id": "sum",
+   "metadata": {
+    "collapsed": false,
+    "zebra": 1
+   },
+   "outputs": [
+    {
+     "name": "stdout",
+     "output_type": "stream",
+     "text": [
+      "3\n"
+     ]
+    }
+   ],
+   "source": "print(1 + 2)"
+  }
+ ],
+ "metadata": {
+  "kernelspec": {
+   "display_name": "Python 3",
+   "language": "python",
+   "name": "python3"
+  },
+  "language_info": {
+   "name": "python",
+   "version": "3.12.0"
+  }
+ },
+ "nbformat": 4,
+ "nbformat_minor": 5
+}
+"##;
+
+    #[test]
+    fn test_round_trip() {
+        let notebook = Notebook::parse(NOTEBOOK).unwrap();
+        assert_eq!(notebook.cells.len(), 2);
+        assert_eq!(notebook.language_name(), Some("python"));
+        assert_eq!(notebook.kernel_name(), Some("python3"));
+
+        let markdown = &notebook.cells[0];
+        assert_eq!(markdown.kind(), CellKind::Markdown);
+        assert_eq!(markdown.source(), "# Title\nSome text");
+
+        let code = &notebook.cells[1];
+        assert_eq!(code.kind(), CellKind::Code);
+        assert_eq!(code.source(), "print(1 + 2)");
+        assert_eq!(code.execution_count(), Some(3));
+        assert_eq!(code.outputs().len(), 1);
+
+        let reparsed = Notebook::parse(&notebook.to_json_string().unwrap()).unwrap();
+        assert_eq!(
+            reparsed.to_json_string().unwrap(),
+            notebook.to_json_string().unwrap()
+        );
+        assert_eq!(
+            normalize(&notebook.to_json_string().unwrap()),
+            normalize(NOTEBOOK)
+        );
+    }
+
+    #[test]
+    fn test_edits_preserve_layout() {
+        let mut notebook = Notebook::parse(NOTEBOOK).unwrap();
+
+        notebook.cells[0].set_source("# Title\nOther text");
+        notebook.cells[1].set_source("print(2 + 2)");
+        notebook.cells[1].clear_outputs();
+        notebook.cells[1].set_execution_count(None);
+        notebook.cells[1].push_output(json!({
+            "name": "stdout",
+            "output_type": "stream",
+            "text": split_lines("4\n"),
+        }));
+        notebook.cells[1].set_execution_count(Some(4));
+
+        let text = notebook.to_json_string().unwrap();
+        let json: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(
+            json["cells"][0]["source"],
+            json!(["# Title\n", "Other text"])
+        );
+        assert_eq!(json["cells"][1]["source"], json!("print(2 + 2)"));
+        assert_eq!(json["cells"][1]["outputs"][0]["text"], json!(["4\n"]));
+        assert_eq!(json["cells"][1]["execution_count"], json!(4));
+
+        // Keys stay in their original order, and unknown metadata is preserved.
+        let keys = json["cells"][1]
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            [
+                "cell_type",
+                "execution_count",
+                "id",
+                "metadata",
+                "outputs",
+                "source"
+            ]
+        );
+        assert_eq!(json["cells"][1]["metadata"]["zebra"], json!(1));
+        assert_eq!(json["cells"][0]["metadata"]["tags"], json!(["intro"]));
+        assert_eq!(
+            json["metadata"]["language_info"]["version"],
+            json!("3.12.0")
+        );
+        assert!(text.starts_with("{\n \"cells\": [\n  {\n"));
+        assert!(text.ends_with("}\n"));
+    }
+
+    #[test]
+    fn test_set_kernelspec() {
+        let mut notebook = Notebook::parse(NOTEBOOK).unwrap();
+        notebook.set_kernelspec(&KernelSpecification {
+            name: "deno".into(),
+            kernelspec: serde_json::from_value::<JupyterKernelspec>(json!({
+                "argv": ["deno", "jupyter", "--kernel", "--conn", "{connection_file}"],
+                "display_name": "Deno",
+                "language": "typescript",
+            }))
+            .unwrap(),
+            source: KernelSource::Local(PathBuf::from("/jupyter/kernels/deno")),
+        });
+
+        assert_eq!(notebook.kernel_name(), Some("deno"));
+        let json: Value = serde_json::from_str(&notebook.to_json_string().unwrap()).unwrap();
+        assert_eq!(
+            json["metadata"]["kernelspec"],
+            json!({ "display_name": "Deno", "language": "typescript", "name": "deno" })
+        );
+    }
+
+    #[test]
+    fn test_invalid_notebooks() {
+        assert!(Notebook::parse("{ totally a notebook }").is_err());
+        assert!(Notebook::parse(r#"{ "cells": [], "nbformat": 3 }"#).is_err());
+        assert!(Notebook::parse(r#"{ "nbformat": 4 }"#).is_err());
+    }
+
+    #[test]
+    fn test_data_lines() {
+        let data = json!({
+            "application/json": { "a": ["b", "c"] },
+            "text/plain": ["1\n", "2"],
+        });
+        let joined = join_data_lines(&data);
+        assert_eq!(
+            joined,
+            json!({ "application/json": { "a": ["b", "c"] }, "text/plain": "1\n2" })
+        );
+        assert_eq!(split_data_lines(&joined), data);
+    }
+
+    /// Collapses whitespace outside of strings, so that differently wrapped arrays compare equal.
+    fn normalize(text: &str) -> String {
+        let mut normalized = String::new();
+        let mut in_string = false;
+        let mut escaped = false;
+        for character in text.chars() {
+            if in_string {
+                normalized.push(character);
+                match character {
+                    '\\' if !escaped => escaped = true,
+                    '"' if !escaped => in_string = false,
+                    _ => escaped = false,
+                }
+            } else if character == '"' {
+                in_string = true;
+                normalized.push(character);
+            } else if !character.is_whitespace() {
+                normalized.push(character);
+            }
+        }
+        normalized
+    }
+}
@@ -0,0 +1,1125 @@
+use crate::{
+    jupyter_settings::JupyterSettings,
+    kernels::{kernel_specifications, Kernel, KernelSpecification, RunningKernel},
+    nbformat::{self, CellKind, Notebook},
+    outputs::{ErrorView, ExecutionStatus, ExecutionView, OutputType},
+    stdio::TerminalOutput,
+};
+use anyhow::{Context as _, Result};
+use collections::{HashMap, HashSet};
+use editor::{Editor, EditorEvent, EditorMode, MultiBuffer};
+use futures::{FutureExt as _, StreamExt as _};
+use gpui::{
+    actions, AnyElement, AppContext, EventEmitter, FocusHandle, FocusableView, Model, Subscription,
+    Task, View, ViewContext,
+};
+use language::Buffer;
+use project::{Fs, Project, ProjectEntryId, ProjectPath};
+use runtimelib::{
+    ExecuteRequest, JupyterMessage, JupyterMessageContent, MimeBundle, ShutdownRequest,
+};
+use serde_json::Value;
+use std::{ffi::OsStr, sync::Arc, time::Duration};
+use ui::{prelude::*, ContextMenu, PopoverMenu, Tooltip};
+use util::ResultExt as _;
+use workspace::item::{Item, ItemEvent, ProjectItem, TabContentParams};
+
+actions!(notebook, [RunCell, RunAll, RestartAndClear]);
+
+pub fn init(cx: &mut AppContext) {
+    workspace::register_project_item::<NotebookEditor>(cx);
+}
+
+/// A Jupyter notebook file, backed by the buffer of the file so that saving goes through the
+/// project like any other file.
+pub struct NotebookItem {
+    buffer: Model<Buffer>,
+    notebook: Notebook,
+}
+
+impl project::Item for NotebookItem {
+    fn try_open(
+        project: &Model<Project>,
+        path: &ProjectPath,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Model<Self>>>> {
+        if !JupyterSettings::enabled(cx)
+            || path.path.extension().and_then(OsStr::to_str) != Some("ipynb")
+        {
+            return None;
+        }
+
+        let open_buffer = project.update(cx, |project, cx| project.open_buffer(path.clone(), cx));
+        Some(cx.spawn(|mut cx| async move {
+            let buffer = open_buffer.await?;
+            let text = buffer.read_with(&cx, |buffer, _| buffer.text())?;
+            let notebook = Notebook::parse(&text).context("failed to parse notebook")?;
+            cx.new_model(|_| NotebookItem { buffer, notebook })
+        }))
+    }
+
+    fn entry_id(&self, cx: &AppContext) -> Option<ProjectEntryId> {
+        project::Item::entry_id(self.buffer.read(cx), cx)
+    }
+
+    fn project_path(&self, cx: &AppContext) -> Option<ProjectPath> {
+        project::Item::project_path(self.buffer.read(cx), cx)
+    }
+}
+
+struct NotebookCell {
+    kind: CellKind,
+    editor: View<Editor>,
+    execution_view: Option<View<ExecutionView>>,
+    /// Set when the kernel asks to clear the outputs once the next output arrives.
+    clear_outputs_on_next_output: bool,
+    _subscription: Subscription,
+}
+
+pub struct NotebookEditor {
+    item: Model<NotebookItem>,
+    project: Model<Project>,
+    fs: Arc<dyn Fs>,
+    focus_handle: FocusHandle,
+    notebook: Notebook,
+    cells: Vec<NotebookCell>,
+    active_cell_ix: usize,
+    kernel_specifications: Vec<KernelSpecification>,
+    kernel_specification: Option<KernelSpecification>,
+    kernel: Kernel,
+    /// Maps the ids of execute requests to the cells they were sent for.
+    executions: HashMap<String, usize>,
+    messaging_task: Task<()>,
+    dirty: bool,
+    _load_languages: Task<Result<()>>,
+    _load_kernel_specifications: Task<Result<()>>,
+}
+
+impl NotebookEditor {
+    fn new(project: Model<Project>, item: Model<NotebookItem>, cx: &mut ViewContext<Self>) -> Self {
+        let fs = project.read(cx).fs().clone();
+        let notebook = item.read(cx).notebook.clone();
+
+        let load_kernel_specifications = kernel_specifications(fs.clone(), cx);
+        let _load_kernel_specifications = cx.spawn(|this, mut cx| async move {
+            let kernel_specifications = load_kernel_specifications.await?;
+            this.update(&mut cx, |this, cx| {
+                this.kernel_specification =
+                    this.default_kernel_specification(&kernel_specifications);
+                this.kernel_specifications = kernel_specifications;
+                cx.notify();
+            })
+        });
+
+        let mut this = Self {
+            item,
+            project,
+            fs,
+            focus_handle: cx.focus_handle(),
+            notebook: notebook.clone(),
+            cells: Vec::new(),
+            active_cell_ix: 0,
+            kernel_specifications: Vec::new(),
+            kernel_specification: None,
+            kernel: Kernel::Shutdown,
+            executions: HashMap::default(),
+            messaging_task: Task::ready(()),
+            dirty: false,
+            _load_languages: Task::ready(Ok(())),
+            _load_kernel_specifications,
+        };
+        this.set_notebook(notebook, cx);
+        this
+    }
+
+    /// Replaces the cells being edited with those of the given notebook.
+    fn set_notebook(&mut self, notebook: Notebook, cx: &mut ViewContext<Self>) {
+        self.executions.clear();
+        self.cells = notebook
+            .cells
+            .iter()
+            .enumerate()
+            .map(|(ix, cell)| {
+                let buffer = cx.new_model(|cx| Buffer::local(cell.source(), cx));
+                let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+                let editor = cx.new_view(|cx| {
+                    let mut editor = Editor::new(
+                        EditorMode::AutoHeight {
+                            max_lines: usize::MAX,
+                        },
+                        buffer,
+                        None,
+                        false,
+                        cx,
+                    );
+                    editor.set_show_gutter(false, cx);
+                    editor
+                });
+                let _subscription = cx.subscribe(&editor, move |this, _, event, cx| match event {
+                    EditorEvent::Focused => {
+                        this.active_cell_ix = ix;
+                        cx.notify();
+                    }
+                    EditorEvent::BufferEdited => this.set_dirty(cx),
+                    _ => {}
+                });
+
+                let outputs = cell.outputs();
+                let execution_view = (!outputs.is_empty()).then(|| {
+                    cx.new_view(|cx| {
+                        let mut execution_view = ExecutionView::new(ExecutionStatus::Finished, cx);
+                        execution_view.outputs = outputs
+                            .iter()
+                            .filter_map(|output| output_type(output, cx))
+                            .collect();
+                        execution_view
+                    })
+                });
+
+                NotebookCell {
+                    kind: cell.kind(),
+                    editor,
+                    execution_view,
+                    clear_outputs_on_next_output: false,
+                    _subscription,
+                }
+            })
+            .collect();
+        self.active_cell_ix = self.active_cell_ix.min(self.cells.len().saturating_sub(1));
+        self._load_languages = self.load_languages(&notebook, cx);
+        self.notebook = notebook;
+        cx.notify();
+    }
+
+    fn load_languages(&self, notebook: &Notebook, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        let languages = self.project.read(cx).languages().clone();
+        let code_language = notebook
+            .language_name()
+            .map(|name| languages.language_for_name(name));
+        let markdown_language = languages.language_for_name("Markdown");
+        let buffers = self
+            .cells
+            .iter()
+            .filter_map(|cell| {
+                let buffer = cell.editor.read(cx).buffer().read(cx).as_singleton()?;
+                Some((cell.kind, buffer))
+            })
+            .collect::<Vec<_>>();
+
+        cx.spawn(|_, mut cx| async move {
+            let code_language = match code_language {
+                Some(code_language) => code_language.await.log_err(),
+                None => None,
+            };
+            let markdown_language = markdown_language.await.log_err();
+            for (kind, buffer) in buffers {
+                let language = match kind {
+                    CellKind::Code => code_language.clone(),
+                    CellKind::Markdown => markdown_language.clone(),
+                    CellKind::Raw => None,
+                };
+                if language.is_some() {
+                    buffer.update(&mut cx, |buffer, cx| buffer.set_language(language, cx))?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Picks the kernel the notebook was last run with, or else one for its language.
+    fn default_kernel_specification(
+        &self,
+        kernel_specifications: &[KernelSpecification],
+    ) -> Option<KernelSpecification> {
+        let kernel_name = self.notebook.kernel_name();
+        let language_name = self.notebook.language_name();
+        kernel_specifications
+            .iter()
+            .find(|specification| Some(specification.name.as_str()) == kernel_name)
+            .or_else(|| {
+                kernel_specifications.iter().find(|specification| {
+                    language_name.map_or(false, |language_name| {
+                        specification
+                            .kernelspec
+                            .language
+                            .eq_ignore_ascii_case(language_name)
+                    })
+                })
+            })
+            .cloned()
+    }
+
+    fn set_dirty(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.dirty {
+            self.dirty = true;
+            cx.emit(ItemEvent::UpdateTab);
+        }
+        cx.emit(ItemEvent::Edit);
+        cx.notify();
+    }
+
+    fn select_kernel(
+        &mut self,
+        kernel_specification: KernelSpecification,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if self
+            .kernel_specification
+            .as_ref()
+            .map_or(false, |selected| selected.name == kernel_specification.name)
+        {
+            return;
+        }
+
+        self.notebook.set_kernelspec(&kernel_specification);
+        self.kernel_specification = Some(kernel_specification);
+        self.shutdown_kernel(cx).detach();
+        self.set_dirty(cx);
+    }
+
+    fn start_kernel(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(kernel_specification) = self.kernel_specification.clone() else {
+            self.kernel = Kernel::ErroredLaunch("No kernel selected".into());
+            cx.notify();
+            return;
+        };
+
+        let kernel = RunningKernel::new(kernel_specification, cx.entity_id(), self.fs.clone(), cx);
+        let pending_kernel = cx
+            .spawn(|this, mut cx| async move {
+                let kernel = kernel.await;
+                this.update(&mut cx, |this, cx| {
+                    // The kernel may have been shut down while it was starting.
+                    if !matches!(this.kernel, Kernel::StartingKernel(_)) {
+                        return;
+                    }
+
+                    match kernel {
+                        Ok((kernel, mut messages_rx)) => {
+                            this.kernel = Kernel::RunningKernel(kernel);
+                            this.messaging_task = cx.spawn(|this, mut cx| async move {
+                                while let Some(message) = messages_rx.next().await {
+                                    this.update(&mut cx, |this, cx| this.route(&message, cx))
+                                        .ok();
+                                }
+
+                                // The connection to a remote kernel can drop while it's running.
+                                this.update(&mut cx, |this, cx| {
+                                    if matches!(this.kernel, Kernel::RunningKernel(_)) {
+                                        this.kernel = Kernel::ErroredLaunch(
+                                            "Disconnected from the kernel".to_string(),
+                                        );
+                                        cx.notify();
+                                    }
+                                })
+                                .ok();
+                            });
+                        }
+                        Err(error) => {
+                            this.kernel = Kernel::ErroredLaunch(error.to_string());
+                        }
+                    }
+                    cx.notify();
+                })
+                .ok();
+            })
+            .shared();
+
+        self.kernel = Kernel::StartingKernel(pending_kernel);
+        cx.notify();
+    }
+
+    fn shutdown_kernel(&mut self, cx: &mut ViewContext<Self>) -> Task<()> {
+        self.messaging_task = Task::ready(());
+        match std::mem::replace(&mut self.kernel, Kernel::Shutdown) {
+            Kernel::RunningKernel(mut kernel) => {
+                self.kernel = Kernel::ShuttingDown;
+                cx.notify();
+
+                let mut request_tx = kernel.request_tx.clone();
+                cx.spawn(|this, mut cx| async move {
+                    // Kernels that were already running are only disconnected from.
+                    if kernel.owns_kernel() {
+                        let message: JupyterMessage = ShutdownRequest { restart: false }.into();
+                        request_tx.try_send(message).ok();
+
+                        // Give the kernel a bit of time to clean up
+                        cx.background_executor().timer(Duration::from_secs(3)).await;
+                    }
+
+                    kernel.kill().await;
+                    drop(kernel);
+
+                    this.update(&mut cx, |this, cx| {
+                        if matches!(this.kernel, Kernel::ShuttingDown) {
+                            this.kernel = Kernel::Shutdown;
+                            cx.notify();
+                        }
+                    })
+                    .ok();
+                })
+            }
+            _ => {
+                cx.notify();
+                Task::ready(())
+            }
+        }
+    }
+
+    fn run_cell(&mut self, _: &RunCell, cx: &mut ViewContext<Self>) {
+        self.execute_cell(self.active_cell_ix, cx);
+    }
+
+    fn run_all(&mut self, _: &RunAll, cx: &mut ViewContext<Self>) {
+        for ix in 0..self.cells.len() {
+            self.execute_cell(ix, cx);
+        }
+    }
+
+    fn restart_and_clear(&mut self, _: &RestartAndClear, cx: &mut ViewContext<Self>) {
+        self.executions.clear();
+        for (cell, notebook_cell) in self.cells.iter_mut().zip(&mut self.notebook.cells) {
+            cell.execution_view = None;
+            cell.clear_outputs_on_next_output = false;
+            notebook_cell.clear_outputs();
+            notebook_cell.set_execution_count(None);
+        }
+        self.set_dirty(cx);
+
+        let shutdown = self.shutdown_kernel(cx);
+        cx.spawn(|this, mut cx| async move {
+            shutdown.await;
+            this.update(&mut cx, |this, cx| this.start_kernel(cx))
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn execute_cell(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(cell) = self.cells.get_mut(ix) else {
+            return;
+        };
+        if cell.kind != CellKind::Code {
+            return;
+        }
+
+        let code = cell.editor.read(cx).text(cx);
+        let message: JupyterMessage = ExecuteRequest {
+            code,
+            ..ExecuteRequest::default()
+        }
+        .into();
+
+        if matches!(self.kernel, Kernel::Shutdown | Kernel::ErroredLaunch(_)) {
+            self.start_kernel(cx);
+        }
+        let status = match &self.kernel {
+            Kernel::RunningKernel(_) => ExecutionStatus::Queued,
+            Kernel::StartingKernel(_) => ExecutionStatus::ConnectingToKernel,
+            Kernel::ErroredLaunch(error) => ExecutionStatus::KernelErrored(error.clone()),
+            Kernel::ShuttingDown => ExecutionStatus::ShuttingDown,
+            Kernel::Shutdown => ExecutionStatus::Shutdown,
+        };
+
+        let cell = &mut self.cells[ix];
+        cell.execution_view = Some(cx.new_view(|cx| ExecutionView::new(status, cx)));
+        cell.clear_outputs_on_next_output = false;
+        self.notebook.cells[ix].clear_outputs();
+        self.notebook.cells[ix].set_execution_count(None);
+        self.executions.retain(|_, cell_ix| *cell_ix != ix);
+        self.executions.insert(message.header.msg_id.clone(), ix);
+        self.set_dirty(cx);
+
+        match &mut self.kernel {
+            Kernel::RunningKernel(kernel) => {
+                kernel.request_tx.try_send(message).ok();
+            }
+            Kernel::StartingKernel(task) => {
+                // Queue up the execution as a task to run after the kernel starts
+                let task = task.clone();
+                cx.spawn(|this, mut cx| async move {
+                    task.await;
+                    this.update(&mut cx, |this, _| {
+                        if let Kernel::RunningKernel(kernel) = &mut this.kernel {
+                            kernel.request_tx.try_send(message).ok();
+                        }
+                    })
+                    .ok();
+                })
+                .detach();
+            }
+            _ => {}
+        }
+    }
+
+    fn route(&mut self, message: &JupyterMessage, cx: &mut ViewContext<Self>) {
+        match &message.content {
+            JupyterMessageContent::Status(status) => {
+                self.kernel.set_execution_state(&status.execution_state);
+                cx.notify();
+            }
+            JupyterMessageContent::KernelInfoReply(reply) => {
+                self.kernel.set_kernel_info(reply);
+                cx.notify();
+            }
+            _ => {}
+        }
+
+        let Some(ix) = message
+            .parent_header
+            .as_ref()
+            .and_then(|header| self.executions.get(&header.msg_id))
+            .copied()
+        else {
+            return;
+        };
+        let (Some(cell), Some(notebook_cell)) =
+            (self.cells.get_mut(ix), self.notebook.cells.get_mut(ix))
+        else {
+            return;
+        };
+
+        if let Some(execution_view) = &cell.execution_view {
+            execution_view.update(cx, |execution_view, cx| {
+                execution_view.push_message(&message.content, cx)
+            });
+        }
+
+        match &message.content {
+            JupyterMessageContent::ExecuteInput(input) => {
+                let execution_count = serde_json::to_value(input)
+                    .ok()
+                    .and_then(|input| input["execution_count"].as_u64());
+                notebook_cell.set_execution_count(execution_count);
+            }
+            JupyterMessageContent::ClearOutput(options) => {
+                if options.wait {
+                    cell.clear_outputs_on_next_output = true;
+                } else {
+                    notebook_cell.clear_outputs();
+                }
+            }
+            content => {
+                let Some(output) = nbformat::output_for_message(content) else {
+                    return;
+                };
+                if cell.clear_outputs_on_next_output {
+                    cell.clear_outputs_on_next_output = false;
+                    notebook_cell.clear_outputs();
+                }
+                notebook_cell.push_output(output);
+            }
+        }
+        self.set_dirty(cx);
+    }
+
+    /// Writes the notebook, with the current contents of the cell editors, to its buffer.
+    fn update_buffer(&mut self, cx: &mut ViewContext<Self>) -> Result<Model<Buffer>> {
+        for (cell, notebook_cell) in self.cells.iter().zip(&mut self.notebook.cells) {
+            notebook_cell.set_source(&cell.editor.read(cx).text(cx));
+        }
+        let text = self.notebook.to_json_string()?;
+
+        let buffer = self.item.read(cx).buffer.clone();
+        buffer.update(cx, |buffer, cx| {
+            if buffer.text() != text {
+                buffer.set_text(text, cx);
+            }
+        });
+        Ok(buffer)
+    }
+
+    fn did_save(&mut self, cx: &mut ViewContext<Self>) {
+        let notebook = self.notebook.clone();
+        self.item.update(cx, |item, _| item.notebook = notebook);
+        self.dirty = false;
+        cx.emit(ItemEvent::UpdateTab);
+        cx.notify();
+    }
+
+    fn render_toolbar(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let view = cx.view().downgrade();
+        let kernel_specifications = self.kernel_specifications.clone();
+        let selected_kernel = self
+            .kernel_specification
+            .as_ref()
+            .map(|specification| specification.name.clone());
+        let kernel_label = match &self.kernel_specification {
+            Some(specification) => specification.kernelspec.display_name.clone(),
+            None if self.kernel_specifications.is_empty() => "No Kernels Available".to_string(),
+            None => "Select Kernel".to_string(),
+        };
+
+        h_flex()
+            .w_full()
+            .px_2()
+            .py_1()
+            .gap_2()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                Button::new("run-all", "Run All")
+                    .icon(IconName::Play)
+                    .icon_position(IconPosition::Start)
+                    .icon_size(IconSize::Small)
+                    .tooltip(|cx| Tooltip::for_action("Run All Cells", &RunAll, cx))
+                    .on_click(|_, cx| cx.dispatch_action(Box::new(RunAll))),
+            )
+            .child(
+                Button::new("restart-and-clear", "Restart & Clear")
+                    .icon(IconName::Rerun)
+                    .icon_position(IconPosition::Start)
+                    .icon_size(IconSize::Small)
+                    .tooltip(|cx| {
+                        Tooltip::for_action(
+                            "Restart Kernel and Clear Outputs",
+                            &RestartAndClear,
+                            cx,
+                        )
+                    })
+                    .on_click(|_, cx| cx.dispatch_action(Box::new(RestartAndClear))),
+            )
+            .child(div().flex_1())
+            .child(self.kernel.dot())
+            .child(
+                PopoverMenu::new("kernel-menu")
+                    .trigger(
+                        Button::new("select-kernel", kernel_label)
+                            .disabled(self.kernel_specifications.is_empty())
+                            .icon(IconName::ChevronDown)
+                            .icon_position(IconPosition::End)
+                            .icon_size(IconSize::Small),
+                    )
+                    .menu(move |cx| {
+                        let view = view.clone();
+                        let selected_kernel = selected_kernel.clone();
+                        let kernel_specifications = kernel_specifications.clone();
+                        Some(ContextMenu::build(cx, move |mut menu, _| {
+                            for specification in kernel_specifications {
+                                let view = view.clone();
+                                let is_selected =
+                                    selected_kernel.as_ref() == Some(&specification.name);
+                                menu = menu.toggleable_entry(
+                                    specification.kernelspec.display_name.clone(),
+                                    is_selected,
+                                    None,
+                                    move |cx| {
+                                        view.update(cx, |this, cx| {
+                                            this.select_kernel(specification.clone(), cx)
+                                        })
+                                        .ok();
+                                    },
+                                );
+                            }
+                            menu
+                        }))
+                    }),
+            )
+    }
+
+    fn render_cell(
+        &self,
+        ix: usize,
+        cell: &NotebookCell,
+        cx: &mut ViewContext<Self>,
+    ) -> AnyElement {
+        let prompt = match cell.kind {
+            CellKind::Code => {
+                let execution_count = self.notebook.cells[ix]
+                    .execution_count()
+                    .map_or(" ".to_string(), |count| count.to_string());
+                format!("[{execution_count}]:")
+            }
+            CellKind::Markdown | CellKind::Raw => String::new(),
+        };
+        let is_active = ix == self.active_cell_ix;
+
+        h_flex()
+            .w_full()
+            .items_start()
+            .gap_2()
+            .child(
+                div().w(rems(4.)).pt_1().flex_none().child(
+                    Label::new(prompt)
+                        .color(Color::Muted)
+                        .size(LabelSize::Small),
+                ),
+            )
+            .child(
+                v_flex()
+                    .flex_1()
+                    .min_w_0()
+                    .gap_1()
+                    .child(
+                        div()
+                            .p_2()
+                            .rounded_md()
+                            .border_1()
+                            .border_color(if is_active {
+                                cx.theme().colors().border_focused
+                            } else {
+                                cx.theme().colors().border
+                            })
+                            .when(cell.kind == CellKind::Code, |this| {
+                                this.bg(cx.theme().colors().editor_background)
+                            })
+                            .child(cell.editor.clone()),
+                    )
+                    .children(
+                        cell.execution_view
+                            .clone()
+                            .map(|execution_view| div().px_2().child(execution_view)),
+                    ),
+            )
+            .into_any_element()
+    }
+}
+
+/// Converts an output saved in a notebook into one that can be rendered.
+fn output_type(output: &Value, cx: &mut WindowContext) -> Option<OutputType> {
+    match output.get("output_type")?.as_str()? {
+        "execute_result" | "display_data" => {
+            let data = nbformat::join_data_lines(output.get("data")?);
+            let data = serde_json::from_value::<MimeBundle>(data).log_err()?;
+            Some(OutputType::new(&data, cx))
+        }
+        "stream" => {
+            let text = nbformat::join_lines(output.get("text")?)?;
+            Some(OutputType::Stream(TerminalOutput::from(&text)))
+        }
+        "error" => {
+            let traceback = output
+                .get("traceback")?
+                .as_array()?
+                .iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .join("\n");
+            Some(OutputType::ErrorOutput(ErrorView {
+                ename: output.get("ename")?.as_str()?.to_string(),
+                evalue: output.get("evalue")?.as_str()?.to_string(),
+                traceback: TerminalOutput::from(&traceback),
+            }))
+        }
+        _ => None,
+    }
+}
+
+impl ProjectItem for NotebookEditor {
+    type Item = NotebookItem;
+
+    fn for_project_item(
+        project: Model<Project>,
+        item: Model<Self::Item>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        Self::new(project, item, cx)
+    }
+}
+
+impl EventEmitter<ItemEvent> for NotebookEditor {}
+
+impl FocusableView for NotebookEditor {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        match self.cells.get(self.active_cell_ix) {
+            Some(cell) => cell.editor.read(cx).focus_handle(cx),
+            None => self.focus_handle.clone(),
+        }
+    }
+}
+
+impl Item for NotebookEditor {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn tab_content(&self, params: TabContentParams, cx: &WindowContext) -> AnyElement {
+        let title = project::Item::project_path(self.item.read(cx), cx)
+            .and_then(|path| {
+                path.path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+            })
+            .unwrap_or_else(|| "untitled.ipynb".to_string());
+
+        h_flex()
+            .gap_2()
+            .child(
+                Icon::new(IconName::Code)
+                    .size(IconSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(
+                Label::new(title)
+                    .single_line()
+                    .color(if params.selected {
+                        Color::Default
+                    } else {
+                        Color::Muted
+                    })
+                    .italic(params.preview),
+            )
+            .into_any_element()
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &AppContext,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::Item),
+    ) {
+        f(self.item.entity_id(), self.item.read(cx))
+    }
+
+    fn is_singleton(&self, _cx: &AppContext) -> bool {
+        true
+    }
+
+    fn is_dirty(&self, _cx: &AppContext) -> bool {
+        self.dirty
+    }
+
+    fn has_conflict(&self, cx: &AppContext) -> bool {
+        self.item.read(cx).buffer.read(cx).has_conflict()
+    }
+
+    fn can_save(&self, _cx: &AppContext) -> bool {
+        true
+    }
+
+    fn save(
+        &mut self,
+        _format: bool,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        let buffer = match self.update_buffer(cx) {
+            Ok(buffer) => buffer,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        let save = project.update(cx, |project, cx| project.save_buffer(buffer, cx));
+        cx.spawn(|this, mut cx| async move {
+            save.await?;
+            this.update(&mut cx, |this, cx| this.did_save(cx))
+        })
+    }
+
+    fn save_as(
+        &mut self,
+        project: Model<Project>,
+        path: ProjectPath,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        let buffer = match self.update_buffer(cx) {
+            Ok(buffer) => buffer,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        let save = project.update(cx, |project, cx| project.save_buffer_as(buffer, path, cx));
+        cx.spawn(|this, mut cx| async move {
+            save.await?;
+            this.update(&mut cx, |this, cx| this.did_save(cx))
+        })
+    }
+
+    fn reload(&mut self, project: Model<Project>, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        let buffer = self.item.read(cx).buffer.clone();
+        let reload = project.update(cx, |project, cx| {
+            project.reload_buffers(HashSet::from_iter([buffer.clone()]), false, cx)
+        });
+        cx.spawn(|this, mut cx| async move {
+            reload.await?;
+            let text = buffer.read_with(&cx, |buffer, _| buffer.text())?;
+            let notebook = Notebook::parse(&text).context("failed to parse notebook")?;
+            this.update(&mut cx, |this, cx| {
+                this.item
+                    .update(cx, |item, _| item.notebook = notebook.clone());
+                this.set_notebook(notebook, cx);
+                this.dirty = false;
+                cx.emit(ItemEvent::UpdateTab);
+            })
+        })
+    }
+}
+
+impl Render for NotebookEditor {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let cells = self
+            .cells
+            .iter()
+            .enumerate()
+            .map(|(ix, cell)| self.render_cell(ix, cell, cx))
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .key_context("NotebookEditor")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::run_cell))
+            .on_action(cx.listener(Self::run_all))
+            .on_action(cx.listener(Self::restart_and_clear))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_toolbar(cx))
+            .child(
+                v_flex()
+                    .id("notebook-cells")
+                    .flex_1()
+                    .p_4()
+                    .gap_3()
+                    .overflow_y_scroll()
+                    .children(cells),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::{Settings as _, SettingsStore};
+    use workspace::{item::ItemHandle, Workspace};
+
+    // Written the way `Notebook::to_json_string` formats notebooks, with keys out of
+    // alphabetical order, so that saving it unchanged must reproduce it exactly.
+    const NOTEBOOK: &str = r##"{
+ "metadata": {
+  "language_info": {
+   "name": "python",
+   "version": "3.12.0"
+  },
+  "kernelspec": {
+   "name": "python3",
+   "display_name": "Python 3",
+   "language": "python"
+  }
+ },
+ "nbformat": 4,
+ "nbformat_minor": 5,
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "id": "intro",
+   "metadata": {
+    "tags": [
+     "intro"
+    ]
+   },
+   "source": [
+    "# Title\n",
+    "Some text"
+   ]
+  },
+  {
+   "id": "sum",
+   "cell_type": "code",
+   "metadata": {
+    "zebra": 1,
+    "collapsed": false
+   },
+   "execution_count": 3,
+   "source": "print(1 + 2)",
+   "outputs": [
+    {
+     "name": "stdout",
+     "output_type": "stream",
+     "text": [
+      "3\n"
+     ]
+    }
+   ]
+  }
+ ]
+}
+"##;
+
+    #[gpui::test]
+    async fn test_open_edit_and_save_notebook(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (workspace, project, fs, cx) = build_workspace(cx).await;
+
+        let notebook_editor = open_path(&workspace, &project, "notebook.ipynb", cx)
+            .await
+            .unwrap()
+            .downcast::<NotebookEditor>()
+            .expect("notebooks should open in a notebook editor");
+        cx.run_until_parked();
+
+        notebook_editor.update(cx, |notebook_editor, cx| {
+            assert_eq!(
+                notebook_editor
+                    .cells
+                    .iter()
+                    .map(|cell| (cell.kind, cell.editor.read(cx).text(cx)))
+                    .collect::<Vec<_>>(),
+                [
+                    (CellKind::Markdown, "# Title\nSome text".to_string()),
+                    (CellKind::Code, "print(1 + 2)".to_string()),
+                ]
+            );
+            assert!(notebook_editor.cells[0].execution_view.is_none());
+            assert!(notebook_editor.cells[1].execution_view.is_some());
+            assert!(!notebook_editor.is_dirty(cx));
+        });
+
+        // Saving an unedited notebook writes it back unchanged.
+        notebook_editor
+            .update(cx, |notebook_editor, cx| {
+                notebook_editor.save(false, project.clone(), cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            fs.load("/root/notebook.ipynb".as_ref()).await.unwrap(),
+            NOTEBOOK
+        );
+
+        notebook_editor.update(cx, |notebook_editor, cx| {
+            notebook_editor.cells[1]
+                .editor
+                .update(cx, |editor, cx| editor.set_text("print(2 + 2)", cx));
+        });
+        cx.run_until_parked();
+        notebook_editor.update(cx, |notebook_editor, cx| {
+            assert!(notebook_editor.is_dirty(cx))
+        });
+
+        // Only the edited source changes, and everything else stays where it was.
+        notebook_editor
+            .update(cx, |notebook_editor, cx| {
+                notebook_editor.save(false, project.clone(), cx)
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        notebook_editor.update(cx, |notebook_editor, cx| {
+            assert!(!notebook_editor.is_dirty(cx))
+        });
+        let saved = fs.load("/root/notebook.ipynb".as_ref()).await.unwrap();
+        assert_eq!(saved, NOTEBOOK.replace("print(1 + 2)", "print(2 + 2)"));
+        assert_eq!(
+            Notebook::parse(&saved).unwrap().cells[1].source(),
+            "print(2 + 2)"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_restart_and_clear_outputs(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (workspace, project, fs, cx) = build_workspace(cx).await;
+
+        let notebook_editor = open_path(&workspace, &project, "notebook.ipynb", cx)
+            .await
+            .unwrap()
+            .downcast::<NotebookEditor>()
+            .unwrap();
+        cx.run_until_parked();
+
+        // No kernels are available, so clearing the outputs leaves the kernel errored.
+        notebook_editor.update(cx, |notebook_editor, cx| {
+            notebook_editor.restart_and_clear(&RestartAndClear, cx);
+            assert!(notebook_editor.cells[1].execution_view.is_none());
+            assert!(notebook_editor.is_dirty(cx));
+        });
+        cx.run_until_parked();
+        notebook_editor.update(cx, |notebook_editor, _| {
+            assert!(matches!(notebook_editor.kernel, Kernel::ErroredLaunch(_)))
+        });
+
+        notebook_editor
+            .update(cx, |notebook_editor, cx| {
+                notebook_editor.save(false, project.clone(), cx)
+            })
+            .await
+            .unwrap();
+        let saved: Value =
+            serde_json::from_str(&fs.load("/root/notebook.ipynb".as_ref()).await.unwrap()).unwrap();
+        assert_eq!(saved["cells"][1]["outputs"], json!([]));
+        assert_eq!(saved["cells"][1]["execution_count"], Value::Null);
+        assert_eq!(saved["cells"][1]["metadata"]["zebra"], json!(1));
+        assert_eq!(
+            saved["metadata"]["language_info"]["version"],
+            json!("3.12.0")
+        );
+    }
+
+    #[gpui::test]
+    async fn test_open_malformed_or_disabled_notebooks(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (workspace, project, _, cx) = build_workspace(cx).await;
+
+        let item = open_path(&workspace, &project, "malformed.ipynb", cx).await;
+        assert!(item.is_err(), "malformed notebooks should fail to open");
+
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store
+                    .set_user_settings(r#"{ "jupyter": { "enabled": false } }"#, cx)
+                    .unwrap();
+            });
+        });
+        for path in ["notebook.ipynb", "malformed.ipynb"] {
+            let item = open_path(&workspace, &project, path, cx).await.unwrap();
+            assert!(
+                item.downcast::<Editor>().is_some(),
+                "{path} should open as text"
+            );
+        }
+    }
+
+    async fn build_workspace(
+        cx: &mut TestAppContext,
+    ) -> (
+        View<Workspace>,
+        Model<Project>,
+        Arc<FakeFs>,
+        &mut VisualTestContext,
+    ) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "notebook.ipynb": NOTEBOOK,
+                "malformed.ipynb": "{ totally a notebook }",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        (workspace, project, fs, cx)
+    }
+
+    fn open_path(
+        workspace: &View<Workspace>,
+        project: &Model<Project>,
+        path: &str,
+        cx: &mut VisualTestContext,
+    ) -> Task<Result<Box<dyn ItemHandle>>> {
+        let worktree_id = project.update(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+        workspace.update(cx, |workspace, cx| {
+            workspace.open_path((worktree_id, path), None, true, cx)
+        })
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            editor::init(cx);
+            JupyterSettings::register(cx);
+            init(cx);
+
+            SettingsStore::update_global(cx, |store, cx| {
+                store
+                    .set_user_settings(r#"{ "jupyter": { "enabled": true } }"#, cx)
+                    .unwrap();
+            });
+        });
+    }
+}
@@ -1,8 +1,17 @@
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::sync::Arc;
 
+use crate::json_view::JsonView;
+use crate::latex::latex_to_text;
 use crate::stdio::TerminalOutput;
 use anyhow::Result;
-use gpui::{img, AnyElement, FontWeight, ImageData, Render, TextRun, View};
+use gpui::{
+    img, AnyElement, FontWeight, ImageData, Render, StyleRefinement, TextRun, TextStyleRefinement,
+    View,
+};
+use html_to_markdown::{convert_html_to_markdown, TagHandler};
+use markdown::{Markdown, MarkdownStyle};
 use runtimelib::datatable::TableSchema;
 use runtimelib::media::datatable::TabularDataResource;
 use runtimelib::{ExecutionState, JupyterMessageContent, MimeBundle, MimeType};
@@ -20,10 +29,15 @@ pub trait LineHeight: Sized {
 // When deciding what to render from a collection of mediatypes, we need to rank them in order of importance
 fn rank_mime_type(mimetype: &MimeType) -> usize {
     match mimetype {
-        MimeType::DataTable(_) => 6,
-        MimeType::Png(_) => 4,
-        MimeType::Jpeg(_) => 3,
-        MimeType::Markdown(_) => 2,
+        MimeType::DataTable(_) => 8,
+        MimeType::Png(_) => 7,
+        MimeType::Jpeg(_) => 6,
+        MimeType::Svg(_) => 5,
+        MimeType::Markdown(_) => 4,
+        // Only tables are converted from HTML, which is how Pandas and Polars show data frames
+        MimeType::Html(html) if html.contains("<table") => 3,
+        MimeType::Json(_) => 3,
+        MimeType::Latex(_) => 2,
         MimeType::Plain(_) => 1,
         // All other media types are not supported in Zed at this time
         _ => 0,
@@ -80,6 +94,22 @@ impl ImageView {
             image: Arc::new(gpui_image_data),
         });
     }
+
+    fn from_svg(svg: &str, cx: &WindowContext) -> Result<Self> {
+        let image = cx.rasterize_svg(svg.as_bytes())?;
+
+        // The image is rasterized for the display, so size it in logical pixels.
+        let size = image.size();
+        let scale_factor = cx.scale_factor();
+        let height = (u32::from(size.height) as f32 / scale_factor).round() as u32;
+        let width = (u32::from(size.width) as f32 / scale_factor).round() as u32;
+
+        Ok(ImageView {
+            height,
+            width,
+            image: Arc::new(image),
+        })
+    }
 }
 
 impl LineHeight for ImageView {
@@ -95,6 +125,92 @@ impl LineHeight for ImageView {
     }
 }
 
+/// MarkdownView renders Markdown, as well as HTML tables converted to Markdown.
+pub struct MarkdownView {
+    markdown: View<Markdown>,
+    num_lines: u8,
+}
+
+impl MarkdownView {
+    fn new(source: String, cx: &mut WindowContext) -> Self {
+        // Paragraphs are spaced apart and have a taller line height than the buffer, so
+        // overestimate the lines they will take up rather than cutting them off.
+        let num_lines = (source.lines().count() as f32 * 1.3).ceil();
+        let num_lines = num_lines.min(u8::MAX as f32) as u8;
+
+        let style = markdown_style(cx);
+        let markdown = cx.new_view(|cx| Markdown::new(source, style, None, cx, None));
+        Self {
+            markdown,
+            num_lines,
+        }
+    }
+
+    fn from_html(html: &str, cx: &mut WindowContext) -> Result<Self> {
+        let mut handlers: Vec<TagHandler> = vec![
+            Rc::new(RefCell::new(
+                html_to_markdown::markdown::WebpageChromeRemover,
+            )),
+            Rc::new(RefCell::new(html_to_markdown::markdown::ParagraphHandler)),
+            Rc::new(RefCell::new(html_to_markdown::markdown::HeadingHandler)),
+            Rc::new(RefCell::new(html_to_markdown::markdown::ListHandler)),
+            Rc::new(RefCell::new(html_to_markdown::markdown::TableHandler::new())),
+            Rc::new(RefCell::new(html_to_markdown::markdown::StyledTextHandler)),
+            Rc::new(RefCell::new(html_to_markdown::markdown::CodeHandler)),
+        ];
+        let markdown = convert_html_to_markdown(html.as_bytes(), &mut handlers)?;
+        Ok(Self::new(markdown, cx))
+    }
+
+    fn render(&self) -> AnyElement {
+        self.markdown.clone().into_any_element()
+    }
+}
+
+impl LineHeight for MarkdownView {
+    fn num_lines(&self, _cx: &mut WindowContext) -> u8 {
+        self.num_lines
+    }
+}
+
+fn markdown_style(cx: &WindowContext) -> MarkdownStyle {
+    let buffer_font = ThemeSettings::get_global(cx).buffer_font.clone();
+    let colors = cx.theme().colors();
+
+    let mut base_text_style = cx.text_style();
+    base_text_style.refine(&TextStyleRefinement {
+        font_family: Some(buffer_font.family.clone()),
+        color: Some(colors.editor_foreground),
+        ..Default::default()
+    });
+
+    MarkdownStyle {
+        base_text_style,
+        code_block: StyleRefinement::default()
+            .my_1()
+            .p_2()
+            .bg(colors.editor_background),
+        inline_code: TextStyleRefinement {
+            background_color: Some(colors.editor_background),
+            ..Default::default()
+        },
+        link: TextStyleRefinement {
+            color: Some(colors.text_accent),
+            ..Default::default()
+        },
+        rule_color: colors.border,
+        block_quote_border_color: colors.border,
+        block_quote: TextStyleRefinement {
+            color: Some(colors.text_muted),
+            ..Default::default()
+        },
+        syntax: cx.theme().syntax().clone(),
+        selection_background_color: cx.theme().players().local().selection,
+        heading: StyleRefinement::default().font_weight(FontWeight::BOLD),
+        ..Default::default()
+    }
+}
+
 /// TableView renders a static table inline in a buffer.
 /// It uses the https://specs.frictionlessdata.io/tabular-data-resource/ specification for data interchange.
 pub struct TableView {
@@ -312,6 +428,8 @@ pub enum OutputType {
     ErrorOutput(ErrorView),
     Message(String),
     Table(TableView),
+    Markdown(MarkdownView),
+    Json(View<JsonView>),
     ClearOutputWaitMarker,
 }
 
@@ -321,11 +439,12 @@ impl OutputType {
             // Note: in typical frontends we would show the execute_result.execution_count
             // Here we can just handle either
             Self::Plain(stdio) => Some(stdio.render(cx)),
-            // Self::Markdown(markdown) => Some(markdown.render(theme)),
             Self::Stream(stdio) => Some(stdio.render(cx)),
             Self::Image(image) => Some(image.render(cx)),
             Self::Message(message) => Some(div().child(message.clone()).into_any_element()),
             Self::Table(table) => Some(table.render(cx)),
+            Self::Markdown(markdown) => Some(markdown.render()),
+            Self::Json(json) => Some(json.clone().into_any_element()),
             Self::ErrorOutput(error_view) => error_view.render(cx),
             Self::ClearOutputWaitMarker => None,
         };
@@ -336,11 +455,27 @@ impl OutputType {
     pub fn new(data: &MimeBundle, cx: &mut WindowContext) -> Self {
         match data.richest(rank_mime_type) {
             Some(MimeType::Plain(text)) => OutputType::Plain(TerminalOutput::from(text)),
-            Some(MimeType::Markdown(text)) => OutputType::Plain(TerminalOutput::from(text)),
+            Some(MimeType::Markdown(text)) => {
+                OutputType::Markdown(MarkdownView::new(text.clone(), cx))
+            }
+            Some(MimeType::Html(html)) => match MarkdownView::from_html(html, cx) {
+                Ok(view) => OutputType::Markdown(view),
+                Err(error) => OutputType::Message(format!("Failed to convert HTML: {}", error)),
+            },
+            Some(MimeType::Latex(latex)) => {
+                OutputType::Plain(TerminalOutput::from(&latex_to_text(latex)))
+            }
+            Some(MimeType::Json(json)) => {
+                OutputType::Json(cx.new_view(|_| JsonView::new(json.clone())))
+            }
             Some(MimeType::Png(data)) | Some(MimeType::Jpeg(data)) => match ImageView::from(data) {
                 Ok(view) => OutputType::Image(view),
                 Err(error) => OutputType::Message(format!("Failed to load image: {}", error)),
             },
+            Some(MimeType::Svg(svg)) => match ImageView::from_svg(svg, cx) {
+                Ok(view) => OutputType::Image(view),
+                Err(error) => OutputType::Message(format!("Failed to render SVG: {}", error)),
+            },
             Some(MimeType::DataTable(data)) => OutputType::Table(TableView::new(data.clone(), cx)),
             // Any other media types are not supported
             _ => OutputType::Message("Unsupported media type".to_string()),
@@ -357,6 +492,8 @@ impl LineHeight for OutputType {
             Self::Image(image) => image.num_lines(cx),
             Self::Message(message) => message.lines().count() as u8,
             Self::Table(table) => table.num_lines(cx),
+            Self::Markdown(markdown) => markdown.num_lines(cx),
+            Self::Json(json) => json.num_lines(cx),
             Self::ErrorOutput(error_view) => error_view.num_lines(cx),
             Self::ClearOutputWaitMarker => 0,
         }
@@ -470,6 +607,11 @@ impl ExecutionView {
             self.outputs.clear();
         }
 
+        // Expanding or collapsing a JSON tree changes the height of the output
+        if let OutputType::Json(json) = &output {
+            cx.observe(json, |_, _, cx| cx.notify()).detach();
+        }
+
         self.outputs.push(output);
 
         cx.notify();
@@ -3,16 +3,23 @@ use gpui::{AppContext, PlatformDispatcher};
 use settings::Settings as _;
 use std::{sync::Arc, time::Duration};
 
+mod code_cells;
+mod json_view;
+mod jupyter_server;
 mod jupyter_settings;
 mod kernels;
+mod latex;
+mod nbformat;
+mod notebook_editor;
 mod outputs;
 mod runtime_panel;
 mod session;
 mod stdio;
 
 pub use jupyter_settings::JupyterSettings;
-pub use kernels::{Kernel, KernelSpecification};
-pub use runtime_panel::Run;
+pub use kernels::{Kernel, KernelSource, KernelSpecification};
+pub use notebook_editor::{NotebookEditor, RestartAndClear, RunAll, RunCell};
+pub use runtime_panel::{ClearOutputs, Run, RunCell, RunCellAndAdvance, RunCellsAbove};
 pub use runtime_panel::{RuntimePanel, SessionSupport};
 pub use runtimelib::ExecutionState;
 pub use session::Session;
@@ -44,5 +51,6 @@ fn zed_dispatcher(cx: &mut AppContext) -> impl Dispatcher {
 pub fn init(cx: &mut AppContext) {
     set_dispatcher(zed_dispatcher(cx));
     JupyterSettings::register(cx);
-    runtime_panel::init(cx)
+    runtime_panel::init(cx);
+    notebook_editor::init(cx)
 }
@@ -1,12 +1,15 @@
 use crate::{
-    jupyter_settings::{JupyterDockPosition, JupyterSettings},
+    code_cells::{self, CodeCell},
+    jupyter_settings::{
+        ConnectionFileSettings, JupyterDockPosition, JupyterServerSettings, JupyterSettings,
+    },
     kernels::{kernel_specifications, KernelSpecification},
     session::{Session, SessionEvent},
 };
 use anyhow::{Context as _, Result};
 use collections::HashMap;
-use editor::{Anchor, Editor, RangeToAnchorExt};
-use futures::StreamExt as _;
+use editor::{scroll::Autoscroll, Anchor, Editor, RangeToAnchorExt};
+use futures::{channel::mpsc::UnboundedSender, StreamExt as _};
 use gpui::{
     actions, prelude::*, AppContext, AsyncWindowContext, EntityId, EventEmitter, FocusHandle,
     FocusOutEvent, FocusableView, Subscription, Task, View, WeakView,
@@ -23,7 +26,10 @@ use workspace::{
     Workspace,
 };
 
-actions!(repl, [Run, ClearOutputs]);
+actions!(
+    repl,
+    [Run, RunCell, RunCellAndAdvance, RunCellsAbove, ClearOutputs]
+);
 actions!(repl_panel, [ToggleFocus]);
 
 pub fn init(cx: &mut AppContext) {
@@ -44,15 +50,43 @@ pub struct RuntimePanel {
     width: Option<Pixels>,
     sessions: HashMap<EntityId, View<Session>>,
     kernel_specifications: Vec<KernelSpecification>,
+    /// The remote kernel settings the kernel specifications were last loaded with.
+    remote_kernel_settings: (Vec<ConnectionFileSettings>, Vec<JupyterServerSettings>),
     _subscriptions: Vec<Subscription>,
     _editor_events_task: Task<()>,
 }
 
 pub enum ReplEvent {
     Run(WeakView<Editor>),
+    RunCell {
+        editor: WeakView<Editor>,
+        advance: bool,
+    },
+    RunCellsAbove(WeakView<Editor>),
     ClearOutputs(WeakView<Editor>),
 }
 
+/// Forwards an action dispatched on the editor to the runtime panel, when the REPL is enabled.
+fn register_editor_action<A: gpui::Action>(
+    editor: &mut Editor,
+    event_tx: &UnboundedSender<ReplEvent>,
+    cx: &mut ViewContext<Editor>,
+    event: impl Fn(&A, WeakView<Editor>) -> ReplEvent + 'static,
+) {
+    let editor_view = cx.view().downgrade();
+    let event_tx = event_tx.clone();
+    editor
+        .register_action(move |action: &A, cx: &mut WindowContext| {
+            if !JupyterSettings::enabled(cx) {
+                return;
+            }
+            event_tx
+                .unbounded_send(event(action, editor_view.clone()))
+                .ok();
+        })
+        .detach();
+}
+
 impl RuntimePanel {
     pub fn load(
         workspace: WeakView<Workspace>,
@@ -76,38 +110,47 @@ impl RuntimePanel {
                         cx.on_focus_out(&focus_handle, Self::focus_out),
                         cx.observe_global::<SettingsStore>(move |this, cx| {
                             this.set_enabled(JupyterSettings::enabled(cx), cx);
+                            if this.remote_kernel_settings != Self::remote_kernel_settings(cx) {
+                                this.refresh_kernelspecs(cx).detach_and_log_err(cx);
+                            }
                         }),
                         cx.observe_new_views(
                             move |editor: &mut Editor, cx: &mut ViewContext<Editor>| {
-                                let editor_view = cx.view().downgrade();
-                                let run_event_tx = repl_editor_event_tx.clone();
-                                let clear_event_tx = repl_editor_event_tx.clone();
-                                editor
-                                    .register_action(move |_: &Run, cx: &mut WindowContext| {
-                                        if !JupyterSettings::enabled(cx) {
-                                            return;
-                                        }
-                                        run_event_tx
-                                            .unbounded_send(ReplEvent::Run(editor_view.clone()))
-                                            .ok();
-                                    })
-                                    .detach();
-
-                                let editor_view = cx.view().downgrade();
-                                editor
-                                    .register_action(
-                                        move |_: &ClearOutputs, cx: &mut WindowContext| {
-                                            if !JupyterSettings::enabled(cx) {
-                                                return;
-                                            }
-                                            clear_event_tx
-                                                .unbounded_send(ReplEvent::ClearOutputs(
-                                                    editor_view.clone(),
-                                                ))
-                                                .ok();
-                                        },
-                                    )
-                                    .detach();
+                                let event_tx = repl_editor_event_tx.clone();
+                                register_editor_action(editor, &event_tx, cx, |_: &Run, editor| {
+                                    ReplEvent::Run(editor)
+                                });
+                                register_editor_action(
+                                    editor,
+                                    &event_tx,
+                                    cx,
+                                    |_: &RunCell, editor| ReplEvent::RunCell {
+                                        editor,
+                                        advance: false,
+                                    },
+                                );
+                                register_editor_action(
+                                    editor,
+                                    &event_tx,
+                                    cx,
+                                    |_: &RunCellAndAdvance, editor| ReplEvent::RunCell {
+                                        editor,
+                                        advance: true,
+                                    },
+                                );
+                                register_editor_action(
+                                    editor,
+                                    &event_tx,
+                                    cx,
+                                    |_: &RunCellsAbove, editor| ReplEvent::RunCellsAbove(editor),
+                                );
+                                register_editor_action(
+                                    editor,
+                                    &event_tx,
+                                    cx,
+                                    |_: &ClearOutputs, editor| ReplEvent::ClearOutputs(editor),
+                                );
+                                code_cells::track_cell_separators(editor, cx);
                             },
                         ),
                     ];
@@ -120,6 +163,12 @@ impl RuntimePanel {
                                     ReplEvent::Run(editor) => {
                                         runtime_panel.run(editor, cx).log_err();
                                     }
+                                    ReplEvent::RunCell { editor, advance } => {
+                                        runtime_panel.run_cell(editor, advance, cx).log_err();
+                                    }
+                                    ReplEvent::RunCellsAbove(editor) => {
+                                        runtime_panel.run_cells_above(editor, cx).log_err();
+                                    }
                                     ReplEvent::ClearOutputs(editor) => {
                                         runtime_panel.clear_outputs(editor, cx);
                                     }
@@ -134,6 +183,7 @@ impl RuntimePanel {
                         width: None,
                         focus_handle,
                         kernel_specifications: Vec::new(),
+                        remote_kernel_settings: Self::remote_kernel_settings(cx),
                         sessions: Default::default(),
                         _subscriptions: subscriptions,
                         enabled: JupyterSettings::enabled(cx),
@@ -144,8 +194,10 @@ impl RuntimePanel {
                 })
             })?;
 
-            view.update(&mut cx, |this, cx| this.refresh_kernelspecs(cx))?
-                .await?;
+            // Jupyter servers can be slow to respond, so don't wait for them to load the panel.
+            view.update(&mut cx, |this, cx| {
+                this.refresh_kernelspecs(cx).detach_and_log_err(cx)
+            })?;
 
             Ok(view)
         })
@@ -227,8 +279,16 @@ impl RuntimePanel {
         buffer.language_at(selection.head()).cloned()
     }
 
+    fn remote_kernel_settings(
+        cx: &AppContext,
+    ) -> (Vec<ConnectionFileSettings>, Vec<JupyterServerSettings>) {
+        let settings = JupyterSettings::get_global(cx);
+        (settings.connection_files.clone(), settings.servers.clone())
+    }
+
     pub fn refresh_kernelspecs(&mut self, cx: &mut ViewContext<Self>) -> Task<anyhow::Result<()>> {
-        let kernel_specifications = kernel_specifications(self.fs.clone());
+        self.remote_kernel_settings = Self::remote_kernel_settings(cx);
+        let kernel_specifications = kernel_specifications(self.fs.clone(), cx);
         cx.spawn(|this, mut cx| async move {
             let kernel_specifications = kernel_specifications.await?;
 
@@ -277,38 +337,158 @@ impl RuntimePanel {
             None => return Ok(()),
         };
 
+        let session = self.get_or_create_session(editor, &language, cx)?;
+        session.update(cx, |session, cx| {
+            session.execute(&selected_text, anchor_range, cx);
+        });
+
+        anyhow::Ok(())
+    }
+
+    /// Runs the code cell containing the cursor, or the selection or current line when the
+    /// file has no cell markers.
+    pub fn run_cell(
+        &mut self,
+        editor: WeakView<Editor>,
+        advance: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> anyhow::Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let Some((cells, current_ix)) = self.code_cells(editor.clone(), cx) else {
+            return self.run(editor, cx);
+        };
+        self.run_code_cells(editor.clone(), &cells[current_ix..current_ix + 1], cx)?;
+
+        if advance {
+            if let Some(editor) = editor.upgrade() {
+                editor.update(cx, |editor, cx| {
+                    let max_point = editor.buffer().read(cx).snapshot(cx).max_point();
+                    let target = match cells.get(current_ix + 1) {
+                        Some(next_cell) => Point::new(next_cell.content.start, 0).min(max_point),
+                        None => max_point,
+                    };
+                    editor.change_selections(Some(Autoscroll::fit()), cx, |selections| {
+                        selections.select_ranges([target..target])
+                    });
+                });
+            }
+        }
+
+        anyhow::Ok(())
+    }
+
+    /// Runs every code cell above the one containing the cursor.
+    pub fn run_cells_above(
+        &mut self,
+        editor: WeakView<Editor>,
+        cx: &mut ViewContext<Self>,
+    ) -> anyhow::Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let Some((cells, current_ix)) = self.code_cells(editor.clone(), cx) else {
+            return Ok(());
+        };
+        self.run_code_cells(editor, &cells[..current_ix], cx)
+    }
+
+    /// The code cells of the editor's buffer, and the index of the cell containing the cursor.
+    fn code_cells(
+        &self,
+        editor: WeakView<Editor>,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<(Vec<CodeCell>, usize)> {
+        let editor = editor.upgrade()?;
+        let editor = editor.read(cx);
+        let cursor = editor.selections.newest::<Point>(cx).head();
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let language = snapshot.language_at(cursor)?.clone();
+
+        let markers = code_cells::cell_markers(&language, cx);
+        let text = snapshot.text();
+        let cells = code_cells::code_cells(text.lines(), &markers);
+        if cells.is_empty() {
+            return None;
+        }
+
+        let current_ix = cells
+            .iter()
+            .rposition(|cell| cell.rows.start <= cursor.row)
+            .unwrap_or(0);
+        Some((cells, current_ix))
+    }
+
+    fn run_code_cells(
+        &mut self,
+        editor: WeakView<Editor>,
+        cells: &[CodeCell],
+        cx: &mut ViewContext<Self>,
+    ) -> anyhow::Result<()> {
+        let Some(editor_view) = editor.upgrade() else {
+            return Ok(());
+        };
+        let snapshot = editor_view.read(cx).buffer().read(cx).snapshot(cx);
+
+        for cell in cells {
+            let Some(range) =
+                code_cells::content_range(cell, |row| snapshot.line_len(MultiBufferRow(row)))
+            else {
+                continue;
+            };
+            let Some(language) = snapshot.language_at(range.start).cloned() else {
+                continue;
+            };
+
+            let code = snapshot.text_for_range(range.clone()).collect::<String>();
+            // Outputs are attached at the end of the cell's code.
+            let anchor_range = range.to_anchors(&snapshot);
+            let session = self.get_or_create_session(editor.clone(), &language, cx)?;
+            session.update(cx, |session, cx| {
+                session.execute(&code, anchor_range, cx);
+            });
+        }
+
+        anyhow::Ok(())
+    }
+
+    fn get_or_create_session(
+        &mut self,
+        editor: WeakView<Editor>,
+        language: &Language,
+        cx: &mut ViewContext<Self>,
+    ) -> anyhow::Result<View<Session>> {
         let entity_id = editor.entity_id();
+        if let Some(session) = self.sessions.get(&entity_id) {
+            return Ok(session.clone());
+        }
 
         let kernel_specification = self
-            .kernelspec(&language, cx)
+            .kernelspec(language, cx)
             .with_context(|| format!("No kernel found for language: {}", language.name()))?;
 
-        let session = self.sessions.entry(entity_id).or_insert_with(|| {
-            let view =
-                cx.new_view(|cx| Session::new(editor, self.fs.clone(), kernel_specification, cx));
-            cx.notify();
+        let session =
+            cx.new_view(|cx| Session::new(editor, self.fs.clone(), kernel_specification, cx));
+        cx.notify();
 
-            let subscription = cx.subscribe(
-                &view,
-                |panel: &mut RuntimePanel, _session: View<Session>, event: &SessionEvent, _cx| {
-                    match event {
-                        SessionEvent::Shutdown(shutdown_event) => {
-                            panel.sessions.remove(&shutdown_event.entity_id());
-                        }
+        let subscription = cx.subscribe(
+            &session,
+            |panel: &mut RuntimePanel, _session: View<Session>, event: &SessionEvent, _cx| {
+                match event {
+                    SessionEvent::Shutdown(shutdown_event) => {
+                        panel.sessions.remove(&shutdown_event.entity_id());
                     }
-                },
-            );
-
-            subscription.detach();
-
-            view
-        });
+                }
+            },
+        );
 
-        session.update(cx, |session, cx| {
-            session.execute(&selected_text, anchor_range, cx);
-        });
+        subscription.detach();
 
-        anyhow::Ok(())
+        self.sessions.insert(entity_id, session.clone());
+        Ok(session)
     }
 
     pub fn clear_outputs(&mut self, editor: WeakView<Editor>, cx: &mut ViewContext<Self>) {
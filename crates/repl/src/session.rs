@@ -16,8 +16,8 @@ use gpui::{
 use language::Point;
 use project::Fs;
 use runtimelib::{
-    ExecuteRequest, InterruptRequest, JupyterMessage, JupyterMessageContent, KernelInfoRequest,
-    ShutdownRequest,
+    ExecuteRequest, ExecutionState, InterruptRequest, JupyterMessage, JupyterMessageContent,
+    KernelInfoRequest, ShutdownRequest,
 };
 use settings::Settings as _;
 use std::{ops::Range, sync::Arc, time::Duration};
@@ -39,6 +39,7 @@ struct EditorBlock {
     invalidation_anchor: Anchor,
     block_id: BlockId,
     execution_view: View<ExecutionView>,
+    _execution_view_subscription: Subscription,
 }
 
 impl EditorBlock {
@@ -81,12 +82,34 @@ impl EditorBlock {
             (block_id, invalidation_anchor)
         })?;
 
+        // Resize the block whenever the outputs change, e.g. when new output arrives or a JSON
+        // tree is expanded.
+        let _execution_view_subscription = cx.observe(&execution_view, {
+            let editor = editor.clone();
+            move |_, execution_view, cx| {
+                editor
+                    .update(cx, |editor, cx| {
+                        let mut replacements = HashMap::default();
+                        replacements.insert(
+                            block_id,
+                            (
+                                Some(execution_view.num_lines(cx).saturating_add(1)),
+                                Self::create_output_area_render(execution_view.clone()),
+                            ),
+                        );
+                        editor.replace_blocks(replacements, None, cx);
+                    })
+                    .ok();
+            }
+        });
+
         anyhow::Ok(Self {
             editor,
             code_range,
             invalidation_anchor,
             block_id,
             execution_view,
+            _execution_view_subscription,
         })
     }
 
@@ -94,20 +117,6 @@ impl EditorBlock {
         self.execution_view.update(cx, |execution_view, cx| {
             execution_view.push_message(&message.content, cx);
         });
-
-        self.editor
-            .update(cx, |editor, cx| {
-                let mut replacements = HashMap::default();
-                replacements.insert(
-                    self.block_id,
-                    (
-                        Some(self.execution_view.num_lines(cx).saturating_add(1)),
-                        Self::create_output_area_render(self.execution_view.clone()),
-                    ),
-                );
-                editor.replace_blocks(replacements, None, cx);
-            })
-            .ok();
     }
 
     fn create_output_area_render(execution_view: View<ExecutionView>) -> RenderBlock {
@@ -172,6 +181,18 @@ impl Session {
                                         })
                                         .ok();
                                 }
+
+                                // The connection to a remote kernel can drop while it's running.
+                                session
+                                    .update(&mut cx, |session, cx| {
+                                        if matches!(session.kernel, Kernel::RunningKernel(_)) {
+                                            session.kernel = Kernel::ErroredLaunch(
+                                                "Disconnected from the kernel".to_string(),
+                                            );
+                                            cx.notify();
+                                        }
+                                    })
+                                    .ok();
                             });
 
                             // For some reason sending a kernel info request will brick the ark (R) kernel.
@@ -395,13 +416,16 @@ impl Session {
                 let mut request_tx = kernel.request_tx.clone();
 
                 cx.spawn(|this, mut cx| async move {
-                    let message: JupyterMessage = ShutdownRequest { restart: false }.into();
-                    request_tx.try_send(message).ok();
+                    // Kernels that were already running are only disconnected from.
+                    if kernel.owns_kernel() {
+                        let message: JupyterMessage = ShutdownRequest { restart: false }.into();
+                        request_tx.try_send(message).ok();
 
-                    // Give the kernel a bit of time to clean up
-                    cx.background_executor().timer(Duration::from_secs(3)).await;
+                        // Give the kernel a bit of time to clean up
+                        cx.background_executor().timer(Duration::from_secs(3)).await;
+                    }
 
-                    kernel.process.kill().ok();
+                    kernel.kill().await;
 
                     this.update(&mut cx, |this, cx| {
                         cx.emit(SessionEvent::Shutdown(this.editor.clone()));
@@ -434,9 +458,13 @@ impl Render for Session {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
         let mut buttons = vec![];
 
+        let shutdown_label = match &self.kernel {
+            Kernel::RunningKernel(kernel) if !kernel.owns_kernel() => "Disconnect",
+            _ => "Shutdown",
+        };
         buttons.push(
             ButtonLike::new("shutdown")
-                .child(Label::new("Shutdown"))
+                .child(Label::new(shutdown_label))
                 .style(ButtonStyle::Subtle)
                 .on_click(cx.listener(move |session, _, cx| {
                     session.shutdown(cx);
@@ -460,8 +488,15 @@ impl Render for Session {
                     name.push_str(&info.language_info.name);
                     name.push_str(")");
                 }
+                name.push_str(match kernel.execution_state {
+                    ExecutionState::Idle => " - Idle",
+                    ExecutionState::Busy => " - Busy",
+                });
                 name
             }
+            Kernel::StartingKernel(_) if !self.kernel_specification.is_local() => {
+                format!("{} (Connecting)", self.kernel_specification.name)
+            }
             Kernel::StartingKernel(_) => format!("{} (Starting)", self.kernel_specification.name),
             Kernel::ErroredLaunch(err) => {
                 format!("{} (Error: {})", self.kernel_specification.name, err)
@@ -478,6 +513,11 @@ impl Render for Session {
                     .child(self.kernel.dot())
                     .child(Label::new(status_text)),
             )
+            .children(self.kernel_specification.location().map(|location| {
+                Label::new(location)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted)
+            }))
             .child(h_flex().gap_2().children(buttons));
     }
 }
@@ -0,0 +1,190 @@
+use heed::types::{SerdeBincode, Str};
+use semantic_index::{Embedding, VectorIndex};
+use std::{cmp::Ordering, time::Instant};
+
+const TOPIC_COUNT: usize = 512;
+const QUERY_COUNT: usize = 100;
+const LIMIT: usize = 10;
+const RESCORED_CANDIDATES_PER_RESULT: usize = 8;
+
+/// Compares searching synthetic embeddings by scanning every chunk against searching them
+/// with the vector index and rescoring its candidates.
+fn main() {
+    env_logger::init();
+
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() > 4 {
+        eprintln!(
+            "Usage: cargo run --release --example search_benchmark -p semantic_index -- [file_count] [chunks_per_file] [dimensions]"
+        );
+        return;
+    }
+    let arg = |ix: usize, default: usize| {
+        args.get(ix)
+            .map(|arg| arg.parse().expect("arguments must be numbers"))
+            .unwrap_or(default)
+    };
+    let file_count = arg(1, 40_000);
+    let chunks_per_file = arg(2, 8);
+    let dimensions = arg(3, 256);
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let env = unsafe {
+        heed::EnvOpenOptions::new()
+            .map_size(16 * 1024 * 1024 * 1024)
+            .max_dbs(8)
+            .open(temp_dir.path())
+            .unwrap()
+    };
+    let mut txn = env.write_txn().unwrap();
+    let db: heed::Database<Str, SerdeBincode<Vec<Embedding>>> =
+        env.create_database(&mut txn, Some("files")).unwrap();
+    let vector_index = VectorIndex::create(&env, &mut txn, "files").unwrap();
+    txn.commit().unwrap();
+
+    // Chunks are clustered around topics, like the chunks of a real codebase.
+    let mut rng = Rng(0x5eed);
+    let topics = (0..TOPIC_COUNT)
+        .map(|_| rng.vector(dimensions))
+        .collect::<Vec<_>>();
+    let chunk = |rng: &mut Rng| {
+        let topic = &topics[rng.next() as usize % TOPIC_COUNT];
+        let noise = rng.vector(dimensions);
+        Embedding::new(
+            topic
+                .iter()
+                .zip(noise)
+                .map(|(topic, noise)| topic + 0.5 * noise)
+                .collect(),
+        )
+    };
+
+    let insert_start = Instant::now();
+    for batch_start in (0..file_count).step_by(1024) {
+        let files = (batch_start..(batch_start + 1024).min(file_count))
+            .map(|ix| {
+                let embeddings = (0..chunks_per_file)
+                    .map(|_| chunk(&mut rng))
+                    .collect::<Vec<_>>();
+                (format!("file-{ix:08}"), embeddings)
+            })
+            .collect::<Vec<_>>();
+
+        let mut txn = env.write_txn().unwrap();
+        for (key, embeddings) in &files {
+            db.put(&mut txn, key, embeddings).unwrap();
+        }
+        vector_index
+            .insert_files(
+                &mut txn,
+                files
+                    .iter()
+                    .map(|(key, embeddings)| (key.as_str(), embeddings.iter().collect())),
+            )
+            .unwrap();
+        txn.commit().unwrap();
+    }
+    println!(
+        "Inserted {} chunks in {:?}",
+        file_count * chunks_per_file,
+        insert_start.elapsed()
+    );
+
+    let train_start = Instant::now();
+    let trained = vector_index
+        .train_if_needed(&env, db, |embeddings| embeddings.iter().collect())
+        .unwrap();
+    println!("Trained: {trained} in {:?}", train_start.elapsed());
+
+    let queries = (0..QUERY_COUNT)
+        .map(|_| chunk(&mut rng))
+        .collect::<Vec<_>>();
+    let txn = env.read_txn().unwrap();
+
+    let scan_start = Instant::now();
+    let exact_results = queries
+        .iter()
+        .map(|query| {
+            let mut results = Vec::new();
+            for entry in db.iter(&txn).unwrap() {
+                let (key, embeddings) = entry.unwrap();
+                for (chunk_ix, embedding) in embeddings.into_iter().enumerate() {
+                    results.push((embedding.similarity(query), key.to_string(), chunk_ix));
+                }
+            }
+            top_results(results)
+        })
+        .collect::<Vec<_>>();
+    let scan_elapsed = scan_start.elapsed();
+
+    let index_start = Instant::now();
+    let index_results = queries
+        .iter()
+        .map(|query| {
+            let candidates = vector_index
+                .search(&txn, query, LIMIT * RESCORED_CANDIDATES_PER_RESULT)
+                .unwrap()
+                .expect("vector index wasn't trained");
+            let mut results = Vec::new();
+            for (key, chunk_ixs) in candidates {
+                let embeddings = db.get(&txn, &key).unwrap().unwrap();
+                for chunk_ix in chunk_ixs {
+                    let score = embeddings[chunk_ix].clone().similarity(query);
+                    results.push((score, key.clone(), chunk_ix));
+                }
+            }
+            top_results(results)
+        })
+        .collect::<Vec<_>>();
+    let index_elapsed = index_start.elapsed();
+
+    let found_count = exact_results
+        .iter()
+        .zip(&index_results)
+        .map(|(exact, approximate)| {
+            exact
+                .iter()
+                .filter(|result| approximate.contains(result))
+                .count()
+        })
+        .sum::<usize>();
+    println!(
+        "Full scan: {:?} per query",
+        scan_elapsed / QUERY_COUNT as u32
+    );
+    println!(
+        "Vector index: {:?} per query",
+        index_elapsed / QUERY_COUNT as u32
+    );
+    println!(
+        "Recall@{LIMIT}: {:.3}",
+        found_count as f32 / (QUERY_COUNT * LIMIT) as f32
+    );
+}
+
+fn top_results(mut results: Vec<(f32, String, usize)>) -> Vec<(String, usize)> {
+    results.sort_unstable_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+    results
+        .into_iter()
+        .take(LIMIT)
+        .map(|(_, key, chunk_ix)| (key, chunk_ix))
+        .collect()
+}
+
+/// A xorshift generator, so that runs are reproducible.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn vector(&mut self, dimensions: usize) -> Vec<f32> {
+        (0..dimensions)
+            .map(|_| (self.next() % 2000) as f32 / 1000. - 1.)
+            .collect()
+    }
+}
@@ -0,0 +1,525 @@
+use crate::{chunking::Chunk, SearchFilter};
+use anyhow::Result;
+use collections::{hash_map, BTreeMap, Bound, HashMap};
+use heed::types::{SerdeBincode, Str};
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, ops::Range, path::Path, sync::Arc};
+
+/// BM25 term frequency saturation.
+const K1: f32 = 1.2;
+/// BM25 chunk length normalization.
+const B: f32 = 0.75;
+const STATS_KEY: &str = "stats";
+/// The longest key LMDB accepts.
+const MAX_KEY_LEN: usize = 511;
+/// Longer words, such as those in minified code or encoded data, aren't indexed.
+const MAX_TERM_LEN: usize = 64;
+
+/// A BM25 index over the identifiers and words in a worktree's chunks, which finds
+/// exact identifiers that embedding similarity misses.
+#[derive(Clone, Copy)]
+pub(crate) struct KeywordIndex {
+    stats: heed::Database<Str, SerdeBincode<Stats>>,
+    files: heed::Database<Str, SerdeBincode<IndexedFile>>,
+    postings: heed::Database<Str, SerdeBincode<Vec<(u32, u32)>>>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Stats {
+    chunk_count: u64,
+    token_count: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct IndexedFile {
+    path: Arc<Path>,
+    language: Option<Arc<str>>,
+    chunks: Vec<IndexedChunk>,
+    terms: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct IndexedChunk {
+    range: Range<usize>,
+    token_count: u32,
+}
+
+/// The terms in each of a file's chunks, ready to be inserted into a [`KeywordIndex`].
+pub(crate) struct KeywordFile {
+    path: Arc<Path>,
+    language: Option<Arc<str>>,
+    chunks: Vec<KeywordChunk>,
+}
+
+struct KeywordChunk {
+    range: Range<usize>,
+    token_count: u32,
+    term_counts: BTreeMap<String, u32>,
+}
+
+impl KeywordFile {
+    pub fn new(path: Arc<Path>, language: Option<Arc<str>>, text: &str, chunks: &[Chunk]) -> Self {
+        let chunks = chunks
+            .iter()
+            .map(|chunk| {
+                let tokens = tokenize(text.get(chunk.range.clone()).unwrap_or_default());
+                let mut term_counts = BTreeMap::<String, u32>::new();
+                let token_count = tokens.len() as u32;
+                for token in tokens {
+                    *term_counts.entry(token).or_default() += 1;
+                }
+                KeywordChunk {
+                    range: chunk.range.clone(),
+                    token_count,
+                    term_counts,
+                }
+            })
+            .collect();
+        Self {
+            path,
+            language,
+            chunks,
+        }
+    }
+}
+
+pub(crate) struct KeywordSearchResult {
+    pub path: Arc<Path>,
+    pub range: Range<usize>,
+    pub score: f32,
+}
+
+impl KeywordIndex {
+    /// The number of databases opened by [`Self::create`].
+    pub const DATABASE_COUNT: u32 = 3;
+
+    pub fn create(env: &heed::Env, txn: &mut heed::RwTxn, name: &str) -> Result<Self> {
+        Ok(Self {
+            stats: env.create_database(txn, Some(&format!("{name}:keyword-stats")))?,
+            files: env.create_database(txn, Some(&format!("{name}:keyword-files")))?,
+            postings: env.create_database(txn, Some(&format!("{name}:keyword-postings")))?,
+        })
+    }
+
+    pub fn contains(&self, txn: &heed::RoTxn, key: &str) -> Result<bool> {
+        Ok(self.files.get(txn, key)?.is_some())
+    }
+
+    pub fn language(&self, txn: &heed::RoTxn, key: &str) -> Result<Option<Arc<str>>> {
+        Ok(self.files.get(txn, key)?.and_then(|file| file.language))
+    }
+
+    /// Replaces the terms indexed for each of the given database keys.
+    pub fn insert_files<'a>(
+        &self,
+        txn: &mut heed::RwTxn,
+        files: impl IntoIterator<Item = (&'a str, &'a KeywordFile)>,
+    ) -> Result<()> {
+        let mut stats = self.stats.get(txn, STATS_KEY)?.unwrap_or_default();
+        for (key, file) in files {
+            self.remove_file(txn, key, &mut stats)?;
+            if posting_key_len(MAX_TERM_LEN, key) > MAX_KEY_LEN {
+                log::warn!("not indexing the keywords in {key:?}, whose key is too long");
+                continue;
+            }
+
+            let mut postings_by_term = BTreeMap::<&str, Vec<(u32, u32)>>::new();
+            for (chunk_ix, chunk) in file.chunks.iter().enumerate() {
+                for (term, count) in &chunk.term_counts {
+                    postings_by_term
+                        .entry(term)
+                        .or_default()
+                        .push((chunk_ix as u32, *count));
+                }
+                stats.chunk_count += 1;
+                stats.token_count += chunk.token_count as u64;
+            }
+            for (term, postings) in &postings_by_term {
+                self.postings.put(txn, &posting_key(term, key), postings)?;
+            }
+
+            self.files.put(
+                txn,
+                key,
+                &IndexedFile {
+                    path: file.path.clone(),
+                    language: file.language.clone(),
+                    chunks: file
+                        .chunks
+                        .iter()
+                        .map(|chunk| IndexedChunk {
+                            range: chunk.range.clone(),
+                            token_count: chunk.token_count,
+                        })
+                        .collect(),
+                    terms: postings_by_term.into_keys().map(Into::into).collect(),
+                },
+            )?;
+        }
+        self.stats.put(txn, STATS_KEY, &stats)?;
+        Ok(())
+    }
+
+    /// Removes the terms indexed for every database key in `range`.
+    pub fn delete_range(
+        &self,
+        txn: &mut heed::RwTxn,
+        range: &(Bound<&str>, Bound<&str>),
+    ) -> Result<()> {
+        let keys = self
+            .files
+            .range(txn, range)?
+            .map(|entry| Ok(entry?.0.to_string()))
+            .collect::<Result<Vec<_>>>()?;
+        if keys.is_empty() {
+            return Ok(());
+        }
+
+        let mut stats = self.stats.get(txn, STATS_KEY)?.unwrap_or_default();
+        for key in keys {
+            self.remove_file(txn, &key, &mut stats)?;
+        }
+        self.stats.put(txn, STATS_KEY, &stats)?;
+        Ok(())
+    }
+
+    /// Returns the `limit` chunks that best match the query's terms, ranked with BM25.
+    pub fn search(
+        &self,
+        txn: &heed::RoTxn,
+        query: &str,
+        limit: usize,
+        filter: &SearchFilter,
+    ) -> Result<Vec<KeywordSearchResult>> {
+        let stats = self.stats.get(txn, STATS_KEY)?.unwrap_or_default();
+        if stats.chunk_count == 0 {
+            return Ok(Vec::new());
+        }
+        let chunk_count = stats.chunk_count as f32;
+        let average_token_count = stats.token_count as f32 / chunk_count;
+
+        let mut terms = tokenize(query);
+        terms.sort_unstable();
+        terms.dedup();
+
+        // Files are `None` when they're excluded by the filter.
+        let mut files = HashMap::<String, Option<IndexedFile>>::default();
+        let mut scores = HashMap::<(String, u32), f32>::default();
+        for term in terms {
+            let postings = self
+                .postings
+                .prefix_iter(txn, &term_prefix(&term))?
+                .map(|entry| {
+                    let (posting_key, postings) = entry?;
+                    Ok((posting_key[term.len() + 1..].to_string(), postings))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let document_frequency = postings
+                .iter()
+                .map(|(_, postings)| postings.len())
+                .sum::<usize>() as f32;
+            let inverse_document_frequency =
+                ((chunk_count - document_frequency + 0.5) / (document_frequency + 0.5) + 1.).ln();
+
+            for (key, postings) in postings {
+                let file = match files.entry(key.clone()) {
+                    hash_map::Entry::Occupied(entry) => entry.into_mut(),
+                    hash_map::Entry::Vacant(entry) => {
+                        let file = self
+                            .files
+                            .get(txn, &key)?
+                            .filter(|file| filter.matches(&file.path, file.language.as_deref()));
+                        entry.insert(file)
+                    }
+                };
+                let Some(file) = file else {
+                    continue;
+                };
+
+                for (chunk_ix, term_count) in postings {
+                    let Some(chunk) = file.chunks.get(chunk_ix as usize) else {
+                        continue;
+                    };
+                    let term_count = term_count as f32;
+                    let length_norm =
+                        K1 * (1. - B + B * chunk.token_count as f32 / average_token_count);
+                    *scores.entry((key.clone(), chunk_ix)).or_default() +=
+                        inverse_document_frequency * term_count * (K1 + 1.)
+                            / (term_count + length_norm);
+                }
+            }
+        }
+
+        let mut scores = scores.into_iter().collect::<Vec<_>>();
+        scores.sort_unstable_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+        scores.truncate(limit);
+        Ok(scores
+            .into_iter()
+            .filter_map(|((key, chunk_ix), score)| {
+                let file = files.get(&key)?.as_ref()?;
+                Some(KeywordSearchResult {
+                    path: file.path.clone(),
+                    range: file.chunks.get(chunk_ix as usize)?.range.clone(),
+                    score,
+                })
+            })
+            .collect())
+    }
+
+    fn remove_file(&self, txn: &mut heed::RwTxn, key: &str, stats: &mut Stats) -> Result<()> {
+        if let Some(file) = self.files.get(txn, key)? {
+            for term in &file.terms {
+                self.postings.delete(txn, &posting_key(term, key))?;
+            }
+            self.files.delete(txn, key)?;
+            stats.chunk_count = stats.chunk_count.saturating_sub(file.chunks.len() as u64);
+            stats.token_count = stats.token_count.saturating_sub(
+                file.chunks
+                    .iter()
+                    .map(|chunk| chunk.token_count as u64)
+                    .sum(),
+            );
+        }
+        Ok(())
+    }
+}
+
+// Terms never contain slashes, and neither do database keys.
+fn term_prefix(term: &str) -> String {
+    format!("{term}/")
+}
+
+fn posting_key(term: &str, key: &str) -> String {
+    format!("{term}/{key}")
+}
+
+fn posting_key_len(term_len: usize, key: &str) -> usize {
+    term_len + 1 + key.len()
+}
+
+/// Splits text into lowercase words, indexing the parts of `snake_case` and `camelCase`
+/// identifiers as well as the identifiers themselves. Words longer than [`MAX_TERM_LEN`]
+/// bytes are skipped.
+pub(crate) fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    for word in text.split(|char: char| !char.is_alphanumeric() && char != '_') {
+        if word.chars().count() < 2 || word.len() > MAX_TERM_LEN {
+            continue;
+        }
+
+        let subwords = split_identifier(word);
+        if subwords.len() > 1 {
+            tokens.extend(
+                subwords
+                    .into_iter()
+                    .filter(|subword| subword.chars().count() > 1)
+                    .map(str::to_lowercase),
+            );
+        }
+        tokens.push(word.to_lowercase());
+    }
+    // Lowercasing can make some words longer.
+    tokens.retain(|token| token.len() <= MAX_TERM_LEN);
+    tokens
+}
+
+fn split_identifier(word: &str) -> Vec<&str> {
+    let mut subwords = Vec::new();
+    for part in word.split('_').filter(|part| !part.is_empty()) {
+        let chars = part.char_indices().collect::<Vec<_>>();
+        let mut start = 0;
+        for (ix, window) in chars.windows(2).enumerate() {
+            let (prev, (offset, char)) = (window[0].1, window[1]);
+            let next_is_lowercase = chars
+                .get(ix + 2)
+                .map_or(false, |(_, next)| next.is_lowercase());
+            let starts_subword = char.is_uppercase()
+                && (prev.is_lowercase()
+                    || prev.is_ascii_digit()
+                    || (prev.is_uppercase() && next_is_lowercase));
+            if starts_subword {
+                subwords.push(&part[start..offset]);
+                start = offset;
+            }
+        }
+        subwords.push(&part[start..]);
+    }
+    subwords
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::paths::PathMatcher;
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("fn toPoint(HTTPServer, max_len) -> a"),
+            [
+                "fn",
+                "to",
+                "point",
+                "topoint",
+                "http",
+                "server",
+                "httpserver",
+                "max",
+                "len",
+                "max_len"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keyword_index() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let env = unsafe {
+            heed::EnvOpenOptions::new()
+                .map_size(16 * 1024 * 1024)
+                .max_dbs(8)
+                .open(temp_dir.path())
+                .unwrap()
+        };
+        let mut txn = env.write_txn().unwrap();
+        let index = KeywordIndex::create(&env, &mut txn, "test").unwrap();
+
+        let file = |path: &str, language: &str, chunks: &[&str]| {
+            let text = chunks.concat();
+            let mut offset = 0;
+            let chunks = chunks
+                .iter()
+                .map(|chunk| {
+                    offset += chunk.len();
+                    Chunk {
+                        range: offset - chunk.len()..offset,
+                        digest: Default::default(),
+                    }
+                })
+                .collect::<Vec<_>>();
+            KeywordFile::new(
+                Path::new(path).into(),
+                Some(language.into()),
+                &text,
+                &chunks,
+            )
+        };
+        let files = [
+            (
+                "a.rs",
+                file(
+                    "a.rs",
+                    "Rust",
+                    &[
+                        "fn anchor_to_point(anchor: Anchor) -> Point {}\n",
+                        "fn unrelated() {}\n",
+                    ],
+                ),
+            ),
+            (
+                "b.ts",
+                file("b.ts", "TypeScript", &["function toPoint(anchor) {}\n"]),
+            ),
+            (
+                "c.md",
+                file("c.md", "Markdown", &["Converting a point into an offset\n"]),
+            ),
+        ];
+        index
+            .insert_files(&mut txn, files.iter().map(|(key, file)| (*key, file)))
+            .unwrap();
+
+        let search = |txn: &heed::RoTxn, query: &str, filter: &SearchFilter| {
+            index
+                .search(txn, query, 10, filter)
+                .unwrap()
+                .into_iter()
+                .map(|result| (result.path.to_string_lossy().into_owned(), result.range))
+                .collect::<Vec<_>>()
+        };
+
+        let no_filter = SearchFilter::default();
+        assert_eq!(
+            search(&txn, "anchor_to_point", &no_filter),
+            [("a.rs".to_string(), 0..47), ("b.ts".to_string(), 0..28)]
+        );
+        assert_eq!(
+            search(
+                &txn,
+                "anchor",
+                &SearchFilter {
+                    languages: vec!["typescript".into()],
+                    ..Default::default()
+                }
+            ),
+            [("b.ts".to_string(), 0..28)]
+        );
+        assert_eq!(
+            search(
+                &txn,
+                "point",
+                &SearchFilter {
+                    path: Some(PathMatcher::new(&["*.md".into()]).unwrap()),
+                    ..Default::default()
+                }
+            ),
+            [("c.md".to_string(), 0..34)]
+        );
+
+        index
+            .delete_range(
+                &mut txn,
+                &(Bound::Included("a.rs"), Bound::Included("b.ts")),
+            )
+            .unwrap();
+        assert_eq!(
+            search(&txn, "anchor point", &no_filter),
+            [("c.md".to_string(), 0..34)]
+        );
+    }
+
+    #[test]
+    fn test_long_terms_and_keys() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let env = unsafe {
+            heed::EnvOpenOptions::new()
+                .map_size(16 * 1024 * 1024)
+                .max_dbs(8)
+                .open(temp_dir.path())
+                .unwrap()
+        };
+        let mut txn = env.write_txn().unwrap();
+        let index = KeywordIndex::create(&env, &mut txn, "test").unwrap();
+
+        let long_token = "a".repeat(1000);
+        let text = format!("const DATA = \"{long_token}\";\n");
+        let chunks = [Chunk {
+            range: 0..text.len(),
+            digest: Default::default(),
+        }];
+        let minified = KeywordFile::new(Path::new("data.js").into(), None, &text, &chunks);
+        let long_key = format!("{}/deep.js", "directory/".repeat(48));
+        let deep = KeywordFile::new(Path::new(&long_key).into(), None, &text, &chunks);
+        index
+            .insert_files(
+                &mut txn,
+                [("data.js", &minified), (long_key.as_str(), &deep)],
+            )
+            .unwrap();
+        txn.commit().unwrap();
+
+        let txn = env.read_txn().unwrap();
+        let search = |query: &str| {
+            index
+                .search(&txn, query, 10, &SearchFilter::default())
+                .unwrap()
+                .into_iter()
+                .map(|result| result.path.to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+        // The file is indexed without its long token, and the file whose key is too long
+        // isn't indexed at all.
+        assert_eq!(search("data"), ["data.js"]);
+        assert!(search(&long_token).is_empty());
+    }
+}
@@ -1,4 +1,5 @@
-use crate::ProjectIndex;
+use crate::{ProjectIndex, SearchFilter};
+use editor::Editor;
 use gpui::{
     canvas, div, list, uniform_list, AnyElement, AppContext, CursorStyle, EventEmitter,
     FocusHandle, FocusableView, IntoElement, ListOffset, ListState, Model, MouseMoveEvent, Render,
@@ -6,13 +7,18 @@ use gpui::{
 };
 use project::WorktreeId;
 use settings::Settings;
-use std::{path::Path, sync::Arc};
+use std::{ops::Range, path::Path, sync::Arc};
 use theme::ThemeSettings;
 use ui::prelude::*;
+use util::ResultExt;
 use workspace::item::{Item, TabContentParams};
 
+const SEARCH_RESULT_COUNT: usize = 50;
+
 pub struct ProjectIndexDebugView {
     index: Model<ProjectIndex>,
+    query_editor: View<Editor>,
+    showing_search_results: bool,
     rows: Vec<Row>,
     selected_path: Option<PathState>,
     hovered_row_ix: Option<usize>,
@@ -30,17 +36,34 @@ struct PathState {
 enum Row {
     Worktree(Arc<Path>),
     Entry(WorktreeId, Arc<Path>),
+    SearchResult {
+        worktree_id: WorktreeId,
+        path: Arc<Path>,
+        range: Range<usize>,
+        score: f32,
+    },
 }
 
 impl ProjectIndexDebugView {
     pub fn new(index: Model<ProjectIndex>, cx: &mut ViewContext<Self>) -> Self {
+        let query_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Search the index (--path=<glob> --language=<name>)", cx);
+            editor
+        });
         let mut this = Self {
+            query_editor,
+            showing_search_results: false,
             rows: Vec::new(),
             list_scroll_handle: UniformListScrollHandle::new(),
             selected_path: None,
             hovered_row_ix: None,
             focus_handle: cx.focus_handle(),
-            _subscription: cx.subscribe(&index, |this, _, _, cx| this.update_rows(cx)),
+            _subscription: cx.subscribe(&index, |this, _, _, cx| {
+                if !this.showing_search_results {
+                    this.update_rows(cx)
+                }
+            }),
             index,
         };
         this.update_rows(cx);
@@ -75,6 +98,40 @@ impl ProjectIndexDebugView {
         .detach();
     }
 
+    fn search(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let query = self.query_editor.read(cx).text(cx);
+        if query.trim().is_empty() {
+            self.showing_search_results = false;
+            self.update_rows(cx);
+            return;
+        }
+        let Some((query, filter)) = SearchFilter::extract(&query).log_err() else {
+            return;
+        };
+
+        self.showing_search_results = true;
+        let search = self
+            .index
+            .read(cx)
+            .hybrid_search(query, SEARCH_RESULT_COUNT, filter, cx);
+        cx.spawn(|this, mut cx| async move {
+            let results = search.await?;
+            this.update(&mut cx, |this, cx| {
+                this.rows = results
+                    .into_iter()
+                    .map(|result| Row::SearchResult {
+                        worktree_id: result.worktree.read(cx).id(),
+                        path: result.path,
+                        range: result.range,
+                        score: result.score,
+                    })
+                    .collect();
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn handle_path_click(
         &mut self,
         worktree_id: WorktreeId,
@@ -244,6 +301,29 @@ impl Render for ProjectIndexDebugView {
                                         this.handle_path_click(worktree_id, file_path.clone(), cx);
                                     }
                                 })),
+                            Row::SearchResult {
+                                worktree_id,
+                                path,
+                                range,
+                                score,
+                            } => div()
+                                .id(ix)
+                                .pl_8()
+                                .child(Label::new(format!(
+                                    "{}:{}..{} (score: {:.4})",
+                                    path.to_string_lossy(),
+                                    range.start,
+                                    range.end,
+                                    score
+                                )))
+                                .cursor(CursorStyle::PointingHand)
+                                .on_click(cx.listener({
+                                    let worktree_id = *worktree_id;
+                                    let path = path.clone();
+                                    move |this, _, cx| {
+                                        this.handle_path_click(worktree_id, path.clone(), cx);
+                                    }
+                                })),
                         })
                         .collect()
                 },
@@ -253,15 +333,29 @@ impl Render for ProjectIndexDebugView {
             .text_bg(cx.theme().colors().background)
             .into_any_element();
 
-            canvas(
-                move |bounds, cx| {
-                    list.prepaint_as_root(bounds.origin, bounds.size.into(), cx);
-                    list
-                },
-                |_, mut list, cx| list.paint(cx),
-            )
-            .size_full()
-            .into_any_element()
+            v_flex()
+                .key_context("ProjectIndexDebugView")
+                .on_action(cx.listener(Self::search))
+                .size_full()
+                .child(
+                    div()
+                        .p_1()
+                        .border_b_1()
+                        .border_color(cx.theme().colors().border)
+                        .child(self.query_editor.clone()),
+                )
+                .child(
+                    canvas(
+                        move |bounds, cx| {
+                            list.prepaint_as_root(bounds.origin, bounds.size.into(), cx);
+                            list
+                        },
+                        |_, mut list, cx| list.paint(cx),
+                    )
+                    .flex_1()
+                    .w_full(),
+                )
+                .into_any_element()
         }
     }
 }
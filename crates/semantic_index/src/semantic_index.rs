@@ -1,6 +1,8 @@
 mod chunking;
 mod embedding;
+mod keyword_index;
 mod project_index_debug_view;
+mod vector_index;
 
 use anyhow::{anyhow, Context as _, Result};
 use chunking::{chunk_text, Chunk};
@@ -14,6 +16,7 @@ use gpui::{
     Model, ModelContext, Subscription, Task, WeakModel,
 };
 use heed::types::{SerdeBincode, Str};
+use keyword_index::{KeywordFile, KeywordIndex};
 use language::LanguageRegistry;
 use parking_lot::Mutex;
 use project::{Entry, Project, ProjectEntryId, UpdatedEntriesSet, Worktree, WorktreeId};
@@ -29,10 +32,29 @@ use std::{
     sync::{Arc, Weak},
     time::{Duration, SystemTime},
 };
-use util::ResultExt;
+use util::{paths::PathMatcher, ResultExt};
 use worktree::Snapshot;
 
 pub use project_index_debug_view::ProjectIndexDebugView;
+pub use vector_index::VectorIndex;
+
+/// The number of candidates retrieved from the vector index per requested search result,
+/// which are then rescored exactly.
+const RESCORED_CANDIDATES_PER_RESULT: usize = 8;
+
+/// The number of results retrieved from each of the vector and keyword searches per
+/// requested hybrid search result.
+const FUSED_CANDIDATES_PER_RESULT: usize = 4;
+
+/// The rank offset used by reciprocal rank fusion, which dampens the influence of the
+/// top few results of each search.
+const RECIPROCAL_RANK_FUSION_K: f32 = 60.;
+
+/// The number of worktrees whose indices can be stored in the database.
+const MAX_WORKTREE_COUNT: u32 = 3000;
+/// Each worktree has a database of its embedded files, plus the databases of its vector
+/// and keyword indices.
+const DATABASES_PER_WORKTREE: u32 = 1 + VectorIndex::DATABASE_COUNT + KeywordIndex::DATABASE_COUNT;
 
 pub struct SemanticIndex {
     embedding_provider: Arc<dyn EmbeddingProvider>,
@@ -55,7 +77,7 @@ impl SemanticIndex {
                 unsafe {
                     heed::EnvOpenOptions::new()
                         .map_size(1024 * 1024 * 1024)
-                        .max_dbs(3000)
+                        .max_dbs(MAX_WORKTREE_COUNT * DATABASES_PER_WORKTREE)
                         .open(db_path)
                 }
             })
@@ -125,6 +147,15 @@ enum WorktreeIndexHandle {
     },
 }
 
+impl WorktreeIndexHandle {
+    async fn index(self) -> Result<Model<WorktreeIndex>> {
+        match self {
+            WorktreeIndexHandle::Loading { index } => index.await.map_err(|error| anyhow!(error)),
+            WorktreeIndexHandle::Loaded { index } => Ok(index),
+        }
+    }
+}
+
 impl ProjectIndex {
     fn new(
         project: Model<Project>,
@@ -282,51 +313,81 @@ impl ProjectIndex {
         }
     }
 
+    /// Returns the chunks whose embeddings are most similar to the query's.
     pub fn search(
         &self,
         query: String,
         limit: usize,
         cx: &AppContext,
     ) -> Task<Result<Vec<SearchResult>>> {
-        let (chunks_tx, chunks_rx) = channel::bounded(1024);
-        let mut worktree_scan_tasks = Vec::new();
-        for worktree_index in self.worktree_indices.values() {
-            let worktree_index = worktree_index.clone();
-            let chunks_tx = chunks_tx.clone();
-            worktree_scan_tasks.push(cx.spawn(|cx| async move {
-                let index = match worktree_index {
-                    WorktreeIndexHandle::Loading { index } => {
-                        index.clone().await.map_err(|error| anyhow!(error))?
-                    }
-                    WorktreeIndexHandle::Loaded { index } => index.clone(),
-                };
+        self.vector_search(query, limit, SearchFilter::default(), cx)
+    }
 
-                index
-                    .read_with(&cx, |index, cx| {
-                        let worktree_id = index.worktree.read(cx).id();
-                        let db_connection = index.db_connection.clone();
-                        let db = index.db;
-                        cx.background_executor().spawn(async move {
-                            let txn = db_connection
-                                .read_txn()
-                                .context("failed to create read transaction")?;
-                            let db_entries = db.iter(&txn).context("failed to iterate database")?;
-                            for db_entry in db_entries {
-                                let (_key, db_embedded_file) = db_entry?;
-                                for chunk in db_embedded_file.chunks {
-                                    chunks_tx
-                                        .send((worktree_id, db_embedded_file.path.clone(), chunk))
-                                        .await?;
-                                }
-                            }
-                            anyhow::Ok(())
-                        })
-                    })?
-                    .await
-            }));
-        }
-        drop(chunks_tx);
+    /// Combines the results of searching by embedding similarity and by keywords with
+    /// reciprocal rank fusion, so that results are found both by meaning and by exact
+    /// identifiers. The scores of the results are their fused scores.
+    pub fn hybrid_search(
+        &self,
+        query: String,
+        limit: usize,
+        filter: SearchFilter,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<SearchResult>>> {
+        let candidate_count = limit * FUSED_CANDIDATES_PER_RESULT;
+        let vector_search = self.vector_search(query.clone(), candidate_count, filter.clone(), cx);
+        let keyword_search = self.keyword_search(query, candidate_count, filter, cx);
+        cx.spawn(|_| async move {
+            let (vector_results, keyword_results) = futures::join!(vector_search, keyword_search);
+            // Keyword results are still useful when the query can't be embedded.
+            let vector_results = vector_results
+                .context("failed to search by embedding")
+                .log_err()
+                .unwrap_or_default();
+            Ok(fuse_search_results(
+                [vector_results, keyword_results?],
+                limit,
+            ))
+        })
+    }
 
+    fn keyword_search(
+        &self,
+        query: String,
+        limit: usize,
+        filter: SearchFilter,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<SearchResult>>> {
+        let worktree_indices = self.worktree_indices.values().cloned().collect::<Vec<_>>();
+        cx.spawn(|cx| async move {
+            let mut search_results = Vec::new();
+            for worktree_index in worktree_indices {
+                let index = worktree_index.index().await?;
+                let (worktree, results) = index.read_with(&cx, |index, cx| {
+                    let results = index.keyword_search(query.clone(), limit, filter.clone(), cx);
+                    (index.worktree.clone(), results)
+                })?;
+                search_results.extend(results.await?.into_iter().map(|result| SearchResult {
+                    worktree: worktree.clone(),
+                    path: result.path,
+                    range: result.range,
+                    score: result.score,
+                }));
+            }
+            search_results
+                .sort_unstable_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+            search_results.truncate(limit);
+            Ok(search_results)
+        })
+    }
+
+    fn vector_search(
+        &self,
+        query: String,
+        limit: usize,
+        filter: SearchFilter,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<SearchResult>>> {
+        let worktree_indices = self.worktree_indices.values().cloned().collect::<Vec<_>>();
         let project = self.project.clone();
         let embedding_provider = self.embedding_provider.clone();
         cx.spawn(|cx| async move {
@@ -342,6 +403,29 @@ impl ProjectIndex {
                 .next()
                 .ok_or_else(|| anyhow!("no embedding for query"))?;
 
+            let (chunks_tx, chunks_rx) = channel::bounded(1024);
+            let mut worktree_scan_tasks = Vec::new();
+            for worktree_index in worktree_indices {
+                let chunks_tx = chunks_tx.clone();
+                let query_embedding = query_embedding.clone();
+                let filter = filter.clone();
+                worktree_scan_tasks.push(cx.spawn(|cx| async move {
+                    let index = worktree_index.index().await?;
+                    index
+                        .read_with(&cx, |index, cx| {
+                            index.search_chunks(
+                                query_embedding,
+                                limit * RESCORED_CANDIDATES_PER_RESULT,
+                                filter,
+                                chunks_tx,
+                                cx,
+                            )
+                        })?
+                        .await
+                }));
+            }
+            drop(chunks_tx);
+
             let mut results_by_worker = Vec::new();
             for _ in 0..cx.background_executor().num_cpus() {
                 results_by_worker.push(Vec::<WorktreeSearchResult>::new());
@@ -465,6 +549,83 @@ pub struct SearchResult {
     pub score: f32,
 }
 
+/// Ranks results by the sum of their reciprocal ranks in each list of results.
+fn fuse_search_results(
+    result_lists: impl IntoIterator<Item = Vec<SearchResult>>,
+    limit: usize,
+) -> Vec<SearchResult> {
+    let mut fused_results = Vec::<SearchResult>::new();
+    let mut fused_result_ixs = HashMap::default();
+    for results in result_lists {
+        for (rank, mut result) in results.into_iter().enumerate() {
+            let score = 1. / (RECIPROCAL_RANK_FUSION_K + rank as f32 + 1.);
+            let key = (
+                result.worktree.entity_id(),
+                result.path.clone(),
+                result.range.clone(),
+            );
+            if let Some(ix) = fused_result_ixs.get(&key) {
+                fused_results[*ix].score += score;
+            } else {
+                fused_result_ixs.insert(key, fused_results.len());
+                result.score = score;
+                fused_results.push(result);
+            }
+        }
+    }
+    fused_results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+    fused_results.truncate(limit);
+    fused_results
+}
+
+/// Restricts search results to files matching path globs or written in given languages.
+#[derive(Clone, Debug, Default)]
+pub struct SearchFilter {
+    pub path: Option<PathMatcher>,
+    pub languages: Vec<String>,
+}
+
+impl SearchFilter {
+    /// Removes `--path=<glob>` and `--language=<name>` arguments from a query, returning
+    /// the rest of the query and the filter they describe.
+    pub fn extract(query: &str) -> Result<(String, Self)> {
+        let mut path_globs = Vec::new();
+        let mut languages = Vec::new();
+        let mut words = Vec::new();
+        for word in query.split(' ') {
+            if let Some(glob) = word.strip_prefix("--path=") {
+                path_globs.push(glob.to_string());
+            } else if let Some(language) = word.strip_prefix("--language=") {
+                languages.push(language.to_string());
+            } else {
+                words.push(word);
+            }
+        }
+
+        let path = if path_globs.is_empty() {
+            None
+        } else {
+            Some(PathMatcher::new(&path_globs).context("invalid path glob")?)
+        };
+        Ok((words.join(" "), Self { path, languages }))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.path.is_none() && self.languages.is_empty()
+    }
+
+    pub(crate) fn matches(&self, path: &Path, language: Option<&str>) -> bool {
+        let path_matches = self.path.as_ref().map_or(true, |glob| glob.is_match(path));
+        let language_matches = self.languages.is_empty()
+            || language.map_or(false, |language| {
+                self.languages
+                    .iter()
+                    .any(|filter| filter.eq_ignore_ascii_case(language))
+            });
+        path_matches && language_matches
+    }
+}
+
 pub struct WorktreeSearchResult {
     pub worktree_id: WorktreeId,
     pub path: Arc<Path>,
@@ -485,6 +646,8 @@ struct WorktreeIndex {
     worktree: Model<Worktree>,
     db_connection: heed::Env,
     db: heed::Database<Str, SerdeBincode<EmbeddedFile>>,
+    vector_index: VectorIndex,
+    keyword_index: KeywordIndex,
     language_registry: Arc<LanguageRegistry>,
     fs: Arc<dyn Fs>,
     embedding_provider: Arc<dyn EmbeddingProvider>,
@@ -505,7 +668,7 @@ impl WorktreeIndex {
     ) -> Task<Result<Model<Self>>> {
         let worktree_abs_path = worktree.read(cx).abs_path();
         cx.spawn(|mut cx| async move {
-            let db = cx
+            let (db, vector_index, keyword_index) = cx
                 .background_executor()
                 .spawn({
                     let db_connection = db_connection.clone();
@@ -513,8 +676,17 @@ impl WorktreeIndex {
                         let mut txn = db_connection.write_txn()?;
                         let db_name = worktree_abs_path.to_string_lossy();
                         let db = db_connection.create_database(&mut txn, Some(&db_name))?;
+                        let vector_index = VectorIndex::create(&db_connection, &mut txn, &db_name)?;
+                        let keyword_index =
+                            KeywordIndex::create(&db_connection, &mut txn, &db_name)?;
                         txn.commit()?;
-                        anyhow::Ok(db)
+
+                        // Builds the vector index for databases that were created before it.
+                        vector_index
+                            .train_if_needed(&db_connection, db, EmbeddedFile::embeddings)
+                            .context("failed to train vector index")
+                            .log_err();
+                        anyhow::Ok((db, vector_index, keyword_index))
                     }
                 })
                 .await?;
@@ -523,6 +695,8 @@ impl WorktreeIndex {
                     worktree,
                     db_connection,
                     db,
+                    vector_index,
+                    keyword_index,
                     status_tx,
                     language_registry,
                     fs,
@@ -538,6 +712,8 @@ impl WorktreeIndex {
         worktree: Model<Worktree>,
         db_connection: heed::Env,
         db: heed::Database<Str, SerdeBincode<EmbeddedFile>>,
+        vector_index: VectorIndex,
+        keyword_index: KeywordIndex,
         status: channel::Sender<()>,
         language_registry: Arc<LanguageRegistry>,
         fs: Arc<dyn Fs>,
@@ -554,6 +730,8 @@ impl WorktreeIndex {
         Self {
             db_connection,
             db,
+            vector_index,
+            keyword_index,
             worktree,
             language_registry,
             fs,
@@ -586,11 +764,13 @@ impl WorktreeIndex {
         let worktree = self.worktree.read(cx).snapshot();
         let worktree_abs_path = worktree.abs_path().clone();
         let scan = self.scan_entries(worktree, cx);
+        let backfill =
+            self.backfill_keywords(worktree_abs_path.clone(), scan.entries_without_keywords, cx);
         let chunk = self.chunk_files(worktree_abs_path, scan.updated_entries, cx);
         let embed = Self::embed_files(self.embedding_provider.clone(), chunk.files, cx);
         let persist = self.persist_embeddings(scan.deleted_entry_ranges, embed.files, cx);
         async move {
-            futures::try_join!(scan.task, chunk.task, embed.task, persist)?;
+            futures::try_join!(scan.task, backfill, chunk.task, embed.task, persist)?;
             Ok(())
         }
     }
@@ -615,8 +795,10 @@ impl WorktreeIndex {
     fn scan_entries(&self, worktree: Snapshot, cx: &AppContext) -> ScanEntries {
         let (updated_entries_tx, updated_entries_rx) = channel::bounded(512);
         let (deleted_entry_ranges_tx, deleted_entry_ranges_rx) = channel::bounded(128);
+        let (entries_without_keywords_tx, entries_without_keywords_rx) = channel::bounded(512);
         let db_connection = self.db_connection.clone();
         let db = self.db;
+        let keyword_index = self.keyword_index;
         let entries_being_indexed = self.entry_ids_being_indexed.clone();
         let task = cx.background_executor().spawn(async move {
             let txn = db_connection
@@ -670,6 +852,8 @@ impl WorktreeIndex {
                 if entry.mtime != saved_mtime {
                     let handle = entries_being_indexed.insert(entry.id);
                     updated_entries_tx.send((entry.clone(), handle)).await?;
+                } else if !keyword_index.contains(&txn, &entry_db_key)? {
+                    entries_without_keywords_tx.send(entry.clone()).await?;
                 }
             }
 
@@ -686,6 +870,7 @@ impl WorktreeIndex {
         ScanEntries {
             updated_entries: updated_entries_rx,
             deleted_entry_ranges: deleted_entry_ranges_rx,
+            entries_without_keywords: entries_without_keywords_rx,
             task,
         }
     }
@@ -698,6 +883,8 @@ impl WorktreeIndex {
     ) -> ScanEntries {
         let (updated_entries_tx, updated_entries_rx) = channel::bounded(512);
         let (deleted_entry_ranges_tx, deleted_entry_ranges_rx) = channel::bounded(128);
+        // Updated entries are always re-chunked, which indexes their keywords too.
+        let (_, entries_without_keywords_rx) = channel::bounded(1);
         let entries_being_indexed = self.entry_ids_being_indexed.clone();
         let task = cx.background_executor().spawn(async move {
             for (path, entry_id, status) in updated_entries.iter() {
@@ -730,10 +917,73 @@ impl WorktreeIndex {
         ScanEntries {
             updated_entries: updated_entries_rx,
             deleted_entry_ranges: deleted_entry_ranges_rx,
+            entries_without_keywords: entries_without_keywords_rx,
             task,
         }
     }
 
+    /// Indexes the keywords of files that were embedded before the keyword index existed,
+    /// reusing their stored chunks instead of embedding them again.
+    fn backfill_keywords(
+        &self,
+        worktree_abs_path: Arc<Path>,
+        entries: channel::Receiver<Entry>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        let db_connection = self.db_connection.clone();
+        let db = self.db;
+        let keyword_index = self.keyword_index;
+        let language_registry = self.language_registry.clone();
+        let fs = self.fs.clone();
+        cx.background_executor().spawn(async move {
+            let mut entries = entries.chunks_timeout(512, Duration::from_secs(2));
+            while let Some(entries) = entries.next().await {
+                let mut keyword_files = Vec::with_capacity(entries.len());
+                for entry in entries {
+                    let key = db_key_for_path(&entry.path);
+                    let txn = db_connection
+                        .read_txn()
+                        .context("failed to create read transaction")?;
+                    let Some(embedded_file) = db.get(&txn, &key)? else {
+                        continue;
+                    };
+                    drop(txn);
+
+                    let entry_abs_path = worktree_abs_path.join(&entry.path);
+                    let Some(text) = fs.load(&entry_abs_path).await.log_err() else {
+                        continue;
+                    };
+                    let language = language_registry
+                        .language_for_file_path(&entry.path)
+                        .await
+                        .ok();
+                    let chunks = embedded_file
+                        .chunks
+                        .into_iter()
+                        .map(|embedded_chunk| embedded_chunk.chunk)
+                        .collect::<Vec<_>>();
+                    let keywords = KeywordFile::new(
+                        entry.path,
+                        language.map(|language| language.name()),
+                        &text,
+                        &chunks,
+                    );
+                    keyword_files.push((key, keywords));
+                }
+
+                let mut txn = db_connection.write_txn()?;
+                keyword_index.insert_files(
+                    &mut txn,
+                    keyword_files
+                        .iter()
+                        .map(|(key, keywords)| (key.as_str(), keywords)),
+                )?;
+                txn.commit()?;
+            }
+            Ok(())
+        })
+    }
+
     fn chunk_files(
         &self,
         worktree_abs_path: Arc<Path>,
@@ -766,6 +1016,7 @@ impl WorktreeIndex {
                                     .ok();
                                 let chunked_file = ChunkedFile {
                                     chunks: chunk_text(&text, language.as_ref(), &entry.path),
+                                    language: language.map(|language| language.name()),
                                     handle,
                                     path: entry.path,
                                     mtime: entry.mtime,
@@ -835,6 +1086,12 @@ impl WorktreeIndex {
 
                 let mut embeddings = embeddings.into_iter();
                 for chunked_file in chunked_files {
+                    let keywords = KeywordFile::new(
+                        chunked_file.path.clone(),
+                        chunked_file.language.clone(),
+                        &chunked_file.text,
+                        &chunked_file.chunks,
+                    );
                     let mut embedded_file = EmbeddedFile {
                         path: chunked_file.path,
                         mtime: chunked_file.mtime,
@@ -856,7 +1113,7 @@ impl WorktreeIndex {
 
                     if embedded_all_chunks {
                         embedded_files_tx
-                            .send((embedded_file, chunked_file.handle))
+                            .send((embedded_file, keywords, chunked_file.handle))
                             .await?;
                     }
                 }
@@ -873,11 +1130,13 @@ impl WorktreeIndex {
     fn persist_embeddings(
         &self,
         mut deleted_entry_ranges: channel::Receiver<(Bound<String>, Bound<String>)>,
-        embedded_files: channel::Receiver<(EmbeddedFile, IndexingEntryHandle)>,
+        embedded_files: channel::Receiver<(EmbeddedFile, KeywordFile, IndexingEntryHandle)>,
         cx: &AppContext,
     ) -> Task<Result<()>> {
         let db_connection = self.db_connection.clone();
         let db = self.db;
+        let vector_index = self.vector_index;
+        let keyword_index = self.keyword_index;
         cx.background_executor().spawn(async move {
             while let Some(deletion_range) = deleted_entry_ranges.next().await {
                 let mut txn = db_connection.write_txn()?;
@@ -885,27 +1144,134 @@ impl WorktreeIndex {
                 let end = deletion_range.1.as_ref().map(|end| end.as_str());
                 log::debug!("deleting embeddings in range {:?}", &(start, end));
                 db.delete_range(&mut txn, &(start, end))?;
+                vector_index.delete_range(&mut txn, &(start, end))?;
+                keyword_index.delete_range(&mut txn, &(start, end))?;
                 txn.commit()?;
             }
 
             let mut embedded_files = embedded_files.chunks_timeout(4096, Duration::from_secs(2));
             while let Some(embedded_files) = embedded_files.next().await {
                 let mut txn = db_connection.write_txn()?;
-                for (file, _) in &embedded_files {
+                let mut indexed_files = Vec::with_capacity(embedded_files.len());
+                for (file, keywords, _) in &embedded_files {
                     log::debug!("saving embedding for file {:?}", file.path);
                     let key = db_key_for_path(&file.path);
                     db.put(&mut txn, &key, file)?;
+                    indexed_files.push((key, file, keywords));
                 }
+                vector_index.insert_files(
+                    &mut txn,
+                    indexed_files
+                        .iter()
+                        .map(|(key, file, _)| (key.as_str(), file.embeddings())),
+                )?;
+                keyword_index.insert_files(
+                    &mut txn,
+                    indexed_files
+                        .iter()
+                        .map(|(key, _, keywords)| (key.as_str(), *keywords)),
+                )?;
                 txn.commit()?;
 
+                drop(indexed_files);
                 drop(embedded_files);
                 log::debug!("committed");
             }
 
+            vector_index
+                .train_if_needed(&db_connection, db, EmbeddedFile::embeddings)
+                .context("failed to train vector index")
+                .log_err();
+
+            Ok(())
+        })
+    }
+
+    /// Sends the chunks retrieved from the vector index to be rescored exactly, or
+    /// every chunk if the vector index hasn't been trained yet.
+    fn search_chunks(
+        &self,
+        query_embedding: Embedding,
+        candidate_count: usize,
+        filter: SearchFilter,
+        chunks_tx: channel::Sender<(WorktreeId, Arc<Path>, EmbeddedChunk)>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        let worktree_id = self.worktree.read(cx).id();
+        let db_connection = self.db_connection.clone();
+        let db = self.db;
+        let vector_index = self.vector_index;
+        let keyword_index = self.keyword_index;
+        cx.background_executor().spawn(async move {
+            let txn = db_connection
+                .read_txn()
+                .context("failed to create read transaction")?;
+            // Languages are only known for files in the keyword index.
+            let matches_filter = |key: &str| -> Result<bool> {
+                if filter.is_empty() {
+                    return Ok(true);
+                }
+                let language = if filter.languages.is_empty() {
+                    None
+                } else {
+                    keyword_index.language(&txn, key)?
+                };
+                Ok(filter.matches(&path_for_db_key(key), language.as_deref()))
+            };
+
+            // The filter is applied while searching the vector index, so that files
+            // ranked below the candidate cut are found when they're the only matches.
+            if let Some(candidates) =
+                vector_index.search(&txn, &query_embedding, candidate_count, &matches_filter)?
+            {
+                for (db_key, chunk_ixs) in candidates {
+                    let Some(db_embedded_file) = db.get(&txn, &db_key)? else {
+                        continue;
+                    };
+                    for chunk_ix in chunk_ixs {
+                        if let Some(chunk) = db_embedded_file.chunks.get(chunk_ix) {
+                            chunks_tx
+                                .send((worktree_id, db_embedded_file.path.clone(), chunk.clone()))
+                                .await?;
+                        }
+                    }
+                }
+                return Ok(());
+            }
+
+            let db_entries = db.iter(&txn).context("failed to iterate database")?;
+            for db_entry in db_entries {
+                let (key, db_embedded_file) = db_entry?;
+                if !matches_filter(key)? {
+                    continue;
+                }
+                for chunk in db_embedded_file.chunks {
+                    chunks_tx
+                        .send((worktree_id, db_embedded_file.path.clone(), chunk))
+                        .await?;
+                }
+            }
             Ok(())
         })
     }
 
+    fn keyword_search(
+        &self,
+        query: String,
+        limit: usize,
+        filter: SearchFilter,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<keyword_index::KeywordSearchResult>>> {
+        let db_connection = self.db_connection.clone();
+        let keyword_index = self.keyword_index;
+        cx.background_executor().spawn(async move {
+            let txn = db_connection
+                .read_txn()
+                .context("failed to create read transaction")?;
+            keyword_index.search(&txn, &query, limit, &filter)
+        })
+    }
+
     fn paths(&self, cx: &AppContext) -> Task<Result<Vec<Arc<Path>>>> {
         let connection = self.db_connection.clone();
         let db = self.db;
@@ -954,6 +1320,7 @@ impl WorktreeIndex {
 struct ScanEntries {
     updated_entries: channel::Receiver<(Entry, IndexingEntryHandle)>,
     deleted_entry_ranges: channel::Receiver<(Bound<String>, Bound<String>)>,
+    entries_without_keywords: channel::Receiver<Entry>,
     task: Task<Result<()>>,
 }
 
@@ -965,13 +1332,14 @@ struct ChunkFiles {
 struct ChunkedFile {
     pub path: Arc<Path>,
     pub mtime: Option<SystemTime>,
+    pub language: Option<Arc<str>>,
     pub handle: IndexingEntryHandle,
     pub text: String,
     pub chunks: Vec<Chunk>,
 }
 
 struct EmbedFiles {
-    files: channel::Receiver<(EmbeddedFile, IndexingEntryHandle)>,
+    files: channel::Receiver<(EmbeddedFile, KeywordFile, IndexingEntryHandle)>,
     task: Task<Result<()>>,
 }
 
@@ -982,6 +1350,12 @@ struct EmbeddedFile {
     chunks: Vec<EmbeddedChunk>,
 }
 
+impl EmbeddedFile {
+    fn embeddings(&self) -> Vec<&Embedding> {
+        self.chunks.iter().map(|chunk| &chunk.embedding).collect()
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct EmbeddedChunk {
     chunk: Chunk,
@@ -1036,6 +1410,10 @@ fn db_key_for_path(path: &Arc<Path>) -> String {
     path.to_string_lossy().replace('/', "\0")
 }
 
+fn path_for_db_key(key: &str) -> PathBuf {
+    PathBuf::from(key.replace('\0', "/"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1183,6 +1561,18 @@ mod tests {
         assert!(content.contains("garbage in, garbage out"));
     }
 
+    #[test]
+    fn test_search_filter() {
+        let (query, filter) =
+            SearchFilter::extract("--path=src/**/*.rs anchor to point --language=Rust").unwrap();
+        assert_eq!(query, "anchor to point");
+        assert_eq!(filter.languages, ["Rust"]);
+        assert!(filter.matches(Path::new("src/editor/anchor.rs"), Some("rust")));
+        assert!(!filter.matches(Path::new("docs/anchor.md"), Some("rust")));
+        assert!(!filter.matches(Path::new("src/anchor.rs"), None));
+        assert!(SearchFilter::extract("anchor").unwrap().1.is_empty());
+    }
+
     #[gpui::test]
     async fn test_embed_files(cx: &mut TestAppContext) {
         cx.executor().allow_parking();
@@ -1207,6 +1597,7 @@ mod tests {
             .send_blocking(ChunkedFile {
                 path: Path::new("test1.md").into(),
                 mtime: None,
+                language: None,
                 handle: indexing_entries.insert(ProjectEntryId::from_proto(0)),
                 text: "abcdefghijklmnop".to_string(),
                 chunks: [0..4, 4..8, 8..12, 12..16]
@@ -1222,6 +1613,7 @@ mod tests {
             .send_blocking(ChunkedFile {
                 path: Path::new("test2.md").into(),
                 mtime: None,
+                language: None,
                 handle: indexing_entries.insert(ProjectEntryId::from_proto(1)),
                 text: "qrstuvwxyz".to_string(),
                 chunks: [0..4, 4..8, 8..10]
@@ -1241,7 +1633,7 @@ mod tests {
 
         let mut embedded_files_rx = embed_files_task.files;
         let mut embedded_files = Vec::new();
-        while let Some((embedded_file, _)) = embedded_files_rx.next().await {
+        while let Some((embedded_file, _, _)) = embedded_files_rx.next().await {
             embedded_files.push(embedded_file);
         }
 
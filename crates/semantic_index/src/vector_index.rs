@@ -0,0 +1,527 @@
+use crate::Embedding;
+use anyhow::Result;
+use collections::{BTreeMap, Bound, HashMap};
+use heed::types::{SerdeBincode, Str};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// Below this many chunks, scanning every chunk is fast enough that no index is trained.
+const MIN_TRAINING_CHUNK_COUNT: usize = 4096;
+/// The index is retrained once the number of chunks grows by this factor, so that the
+/// number of lists keeps up with the size of the worktree.
+const RETRAIN_GROWTH_FACTOR: usize = 4;
+const MIN_LIST_COUNT: usize = 16;
+const MAX_LIST_COUNT: usize = 256;
+const TRAINING_SAMPLES_PER_LIST: usize = 32;
+const TRAINING_ITERATIONS: usize = 8;
+/// The fraction of lists (as a divisor) that are scanned for each query.
+const PROBE_DIVISOR: usize = 8;
+const MIN_PROBE_COUNT: usize = 4;
+const METADATA_KEY: &str = "metadata";
+/// The length of the `{list:08x}/` prefix of posting keys.
+const LIST_PREFIX_LEN: usize = 9;
+
+/// An inverted file index over the embeddings stored in a worktree's database.
+///
+/// Embeddings are clustered around centroids trained with k-means, and each cluster's
+/// embeddings are stored in a list of int8-quantized postings. Searching only scans the
+/// lists whose centroids are closest to the query, producing candidates that callers
+/// rescore exactly against the full-precision embeddings.
+#[derive(Clone, Copy)]
+pub struct VectorIndex {
+    metadata: heed::Database<Str, SerdeBincode<Metadata>>,
+    files: heed::Database<Str, SerdeBincode<IndexedFile>>,
+    postings: heed::Database<Str, SerdeBincode<Vec<Posting>>>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Metadata {
+    centroids: Vec<Embedding>,
+    chunk_count: usize,
+    trained_chunk_count: usize,
+}
+
+impl Metadata {
+    fn needs_training(&self) -> bool {
+        if self.centroids.is_empty() {
+            self.chunk_count >= MIN_TRAINING_CHUNK_COUNT
+        } else {
+            self.chunk_count > self.trained_chunk_count * RETRAIN_GROWTH_FACTOR
+        }
+    }
+}
+
+/// The lists containing a file's chunks, used to remove them when the file changes.
+#[derive(Serialize, Deserialize)]
+struct IndexedFile {
+    chunk_count: usize,
+    lists: Vec<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Posting {
+    chunk_ix: u32,
+    embedding: QuantizedEmbedding,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct QuantizedEmbedding {
+    scale: f32,
+    values: Vec<i8>,
+}
+
+impl QuantizedEmbedding {
+    fn new(embedding: &Embedding) -> Self {
+        let embedding = embedding.as_slice();
+        let max = embedding
+            .iter()
+            .fold(0f32, |max, value| max.max(value.abs()));
+        let scale = if max > 0. { max / i8::MAX as f32 } else { 1. };
+        Self {
+            scale,
+            values: embedding
+                .iter()
+                .map(|value| (value / scale).round() as i8)
+                .collect(),
+        }
+    }
+
+    fn similarity(&self, query: &[f32]) -> f32 {
+        let dot = self
+            .values
+            .iter()
+            .zip(query)
+            .map(|(value, query)| *value as f32 * query)
+            .sum::<f32>();
+        dot * self.scale
+    }
+}
+
+impl VectorIndex {
+    /// The number of databases opened by [`Self::create`].
+    pub const DATABASE_COUNT: u32 = 3;
+
+    /// Opens the databases backing the index, creating them if needed. `name` must be
+    /// unique within the environment.
+    pub fn create(env: &heed::Env, txn: &mut heed::RwTxn, name: &str) -> Result<Self> {
+        Ok(Self {
+            metadata: env.create_database(txn, Some(&format!("{name}:ann-metadata")))?,
+            files: env.create_database(txn, Some(&format!("{name}:ann-files")))?,
+            postings: env.create_database(txn, Some(&format!("{name}:ann-postings")))?,
+        })
+    }
+
+    /// Replaces the embeddings indexed for each of the given database keys.
+    pub fn insert_files<'a>(
+        &self,
+        txn: &mut heed::RwTxn,
+        files: impl IntoIterator<Item = (&'a str, Vec<&'a Embedding>)>,
+    ) -> Result<()> {
+        let mut metadata = self.metadata.get(txn, METADATA_KEY)?.unwrap_or_default();
+        for (key, embeddings) in files {
+            self.remove_file(txn, key, &mut metadata)?;
+            self.assign_file(txn, key, &embeddings, &mut metadata)?;
+        }
+        self.metadata.put(txn, METADATA_KEY, &metadata)?;
+        Ok(())
+    }
+
+    /// Removes the embeddings indexed for every database key in `range`.
+    pub fn delete_range(
+        &self,
+        txn: &mut heed::RwTxn,
+        range: &(Bound<&str>, Bound<&str>),
+    ) -> Result<()> {
+        let keys = self
+            .files
+            .range(txn, range)?
+            .map(|entry| Ok(entry?.0.to_string()))
+            .collect::<Result<Vec<_>>>()?;
+        if keys.is_empty() {
+            return Ok(());
+        }
+
+        let mut metadata = self.metadata.get(txn, METADATA_KEY)?.unwrap_or_default();
+        for key in keys {
+            self.remove_file(txn, &key, &mut metadata)?;
+        }
+        self.metadata.put(txn, METADATA_KEY, &metadata)?;
+        Ok(())
+    }
+
+    /// Rebuilds the index from `db` if it has never been built, or if the number of
+    /// chunks has grown enough for its centroids to be stale. Returns whether the index
+    /// was rebuilt. The index is read and rebuilt in a single write transaction, so that
+    /// files committed to `db` meanwhile can't be left out.
+    pub fn train_if_needed<T>(
+        &self,
+        env: &heed::Env,
+        db: heed::Database<Str, SerdeBincode<T>>,
+        embeddings: impl Fn(&T) -> Vec<&Embedding>,
+    ) -> Result<bool>
+    where
+        T: 'static + Serialize + DeserializeOwned,
+    {
+        let mut txn = env.write_txn()?;
+        let needs_training = match self.metadata.get(&txn, METADATA_KEY)? {
+            Some(metadata) => metadata.needs_training(),
+            None => !db.is_empty(&txn)?,
+        };
+        if !needs_training {
+            return Ok(false);
+        }
+
+        let mut keys = Vec::new();
+        let mut chunk_count = 0;
+        for entry in db.iter(&txn)? {
+            let (key, value) = entry?;
+            keys.push(key.to_string());
+            chunk_count += embeddings(&value).len();
+        }
+
+        let mut centroids = Vec::new();
+        if chunk_count >= MIN_TRAINING_CHUNK_COUNT {
+            let list_count =
+                ((chunk_count as f64).sqrt() as usize).clamp(MIN_LIST_COUNT, MAX_LIST_COUNT);
+            let sample_count = (list_count * TRAINING_SAMPLES_PER_LIST).min(chunk_count);
+            let stride = chunk_count / sample_count;
+            let mut samples = Vec::with_capacity(sample_count);
+            let mut chunk_ix = 0;
+            for entry in db.iter(&txn)? {
+                let (_, value) = entry?;
+                for embedding in embeddings(&value) {
+                    if chunk_ix % stride == 0 && samples.len() < sample_count {
+                        samples.push(embedding.clone());
+                    }
+                    chunk_ix += 1;
+                }
+            }
+
+            log::info!(
+                "training vector index with {} lists from {} of {} chunks",
+                list_count,
+                samples.len(),
+                chunk_count
+            );
+            centroids = train_centroids(&samples, list_count);
+        }
+
+        self.files.clear(&mut txn)?;
+        self.postings.clear(&mut txn)?;
+        let mut metadata = Metadata {
+            centroids,
+            chunk_count: 0,
+            trained_chunk_count: chunk_count,
+        };
+        for key in keys {
+            let Some(value) = db.get(&txn, &key)? else {
+                continue;
+            };
+            self.assign_file(&mut txn, &key, &embeddings(&value), &mut metadata)?;
+        }
+        self.metadata.put(&mut txn, METADATA_KEY, &metadata)?;
+        txn.commit()?;
+        Ok(true)
+    }
+
+    /// Returns the database keys and chunk indices of approximately the `candidate_count`
+    /// embeddings most similar to `query` among the files whose key is accepted by
+    /// `matches`, or `None` if the index hasn't been trained. More lists are probed until
+    /// `candidate_count` embeddings are found, so narrow filters still return results.
+    pub fn search(
+        &self,
+        txn: &heed::RoTxn,
+        query: &Embedding,
+        candidate_count: usize,
+        mut matches: impl FnMut(&str) -> Result<bool>,
+    ) -> Result<Option<Vec<(String, Vec<usize>)>>> {
+        let Some(metadata) = self.metadata.get(txn, METADATA_KEY)? else {
+            return Ok(None);
+        };
+        if metadata.centroids.is_empty() {
+            return Ok(None);
+        }
+
+        let probe_count = (metadata.centroids.len() / PROBE_DIVISOR).max(MIN_PROBE_COUNT);
+        let query = query.as_slice();
+        let mut candidates = Vec::<(f32, String, usize)>::with_capacity(candidate_count + 1);
+        let mut matching_keys = HashMap::<String, bool>::default();
+        let lists = nearest_centroids(&metadata.centroids, query, metadata.centroids.len());
+        for (ix, list) in lists.into_iter().enumerate() {
+            if ix >= probe_count && candidates.len() >= candidate_count {
+                break;
+            }
+            for entry in self.postings.prefix_iter(txn, &list_prefix(list))? {
+                let (posting_key, postings) = entry?;
+                let key = &posting_key[LIST_PREFIX_LEN..];
+                let key_matches = match matching_keys.get(key) {
+                    Some(key_matches) => *key_matches,
+                    None => {
+                        let key_matches = matches(key)?;
+                        matching_keys.insert(key.to_string(), key_matches);
+                        key_matches
+                    }
+                };
+                if !key_matches {
+                    continue;
+                }
+                for posting in postings {
+                    let score = posting.embedding.similarity(query);
+                    let is_full = candidates.len() >= candidate_count;
+                    if is_full
+                        && candidates
+                            .last()
+                            .map_or(true, |(min_score, _, _)| score <= *min_score)
+                    {
+                        continue;
+                    }
+
+                    let ix = candidates.partition_point(|(probe, _, _)| *probe >= score);
+                    candidates.insert(ix, (score, key.to_string(), posting.chunk_ix as usize));
+                    candidates.truncate(candidate_count);
+                }
+            }
+        }
+
+        let mut chunk_ixs_by_key = BTreeMap::<String, Vec<usize>>::new();
+        for (_, key, chunk_ix) in candidates {
+            chunk_ixs_by_key.entry(key).or_default().push(chunk_ix);
+        }
+        Ok(Some(chunk_ixs_by_key.into_iter().collect()))
+    }
+
+    fn assign_file(
+        &self,
+        txn: &mut heed::RwTxn,
+        key: &str,
+        embeddings: &[&Embedding],
+        metadata: &mut Metadata,
+    ) -> Result<()> {
+        let mut postings_by_list = BTreeMap::<u32, Vec<Posting>>::new();
+        if !metadata.centroids.is_empty() {
+            for (chunk_ix, embedding) in embeddings.iter().enumerate() {
+                let list = nearest_centroids(&metadata.centroids, embedding.as_slice(), 1)[0];
+                postings_by_list.entry(list).or_default().push(Posting {
+                    chunk_ix: chunk_ix as u32,
+                    embedding: QuantizedEmbedding::new(embedding),
+                });
+            }
+        }
+
+        for (list, postings) in &postings_by_list {
+            self.postings.put(txn, &posting_key(*list, key), postings)?;
+        }
+        self.files.put(
+            txn,
+            key,
+            &IndexedFile {
+                chunk_count: embeddings.len(),
+                lists: postings_by_list.into_keys().collect(),
+            },
+        )?;
+        metadata.chunk_count += embeddings.len();
+        Ok(())
+    }
+
+    fn remove_file(&self, txn: &mut heed::RwTxn, key: &str, metadata: &mut Metadata) -> Result<()> {
+        if let Some(file) = self.files.get(txn, key)? {
+            for list in file.lists {
+                self.postings.delete(txn, &posting_key(list, key))?;
+            }
+            self.files.delete(txn, key)?;
+            metadata.chunk_count = metadata.chunk_count.saturating_sub(file.chunk_count);
+        }
+        Ok(())
+    }
+}
+
+fn list_prefix(list: u32) -> String {
+    format!("{list:08x}/")
+}
+
+fn posting_key(list: u32, key: &str) -> String {
+    format!("{list:08x}/{key}")
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+/// Returns the indices of the `count` centroids most similar to `embedding`.
+fn nearest_centroids(centroids: &[Embedding], embedding: &[f32], count: usize) -> Vec<u32> {
+    let mut scores = centroids
+        .iter()
+        .enumerate()
+        .map(|(ix, centroid)| (ix as u32, dot(centroid.as_slice(), embedding)))
+        .collect::<Vec<_>>();
+    scores.sort_unstable_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+    scores.truncate(count);
+    scores.into_iter().map(|(ix, _)| ix).collect()
+}
+
+/// Clusters the samples with spherical k-means, seeding the centroids with evenly
+/// spaced samples so that training is deterministic.
+fn train_centroids(samples: &[Embedding], list_count: usize) -> Vec<Embedding> {
+    let list_count = list_count.min(samples.len());
+    if list_count == 0 {
+        return Vec::new();
+    }
+
+    let stride = samples.len() / list_count;
+    let mut centroids = (0..list_count)
+        .map(|ix| samples[ix * stride].clone())
+        .collect::<Vec<_>>();
+    let dimensions = centroids[0].as_slice().len();
+    for _ in 0..TRAINING_ITERATIONS {
+        let mut sums = vec![vec![0f32; dimensions]; list_count];
+        for sample in samples {
+            let sample = sample.as_slice();
+            let list = nearest_centroids(&centroids, sample, 1)[0] as usize;
+            for (sum, value) in sums[list].iter_mut().zip(sample) {
+                *sum += value;
+            }
+        }
+
+        for (centroid, sum) in centroids.iter_mut().zip(sums) {
+            // Leave the centroids of empty clusters where they are.
+            if sum.iter().any(|value| *value != 0.) {
+                *centroid = Embedding::new(sum);
+            }
+        }
+    }
+    centroids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_embeddings(count: usize, dimensions: usize, seed: &mut u64) -> Vec<Embedding> {
+        let mut next = move || {
+            *seed ^= *seed << 13;
+            *seed ^= *seed >> 7;
+            *seed ^= *seed << 17;
+            (*seed % 2000) as f32 / 1000. - 1.
+        };
+        (0..count)
+            .map(|_| Embedding::new((0..dimensions).map(|_| next()).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn test_quantized_similarity() {
+        let mut seed = 42;
+        let embeddings = random_embeddings(2, 64, &mut seed);
+        let quantized = QuantizedEmbedding::new(&embeddings[0]);
+        let exact = embeddings[0].clone().similarity(&embeddings[1]);
+        let approximate = quantized.similarity(embeddings[1].as_slice());
+        assert!(
+            (exact - approximate).abs() < 0.01,
+            "{exact} != {approximate}"
+        );
+    }
+
+    #[test]
+    fn test_vector_index() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let env = unsafe {
+            heed::EnvOpenOptions::new()
+                .map_size(64 * 1024 * 1024)
+                .max_dbs(8)
+                .open(temp_dir.path())
+                .unwrap()
+        };
+        let mut txn = env.write_txn().unwrap();
+        let db: heed::Database<Str, SerdeBincode<Vec<Embedding>>> =
+            env.create_database(&mut txn, Some("files")).unwrap();
+        let index = VectorIndex::create(&env, &mut txn, "files").unwrap();
+        txn.commit().unwrap();
+
+        let mut seed = 7;
+        let files = (0..MIN_TRAINING_CHUNK_COUNT / 8)
+            .map(|ix| (format!("file-{ix:05}"), random_embeddings(8, 16, &mut seed)))
+            .collect::<Vec<_>>();
+        let mut txn = env.write_txn().unwrap();
+        for (key, embeddings) in &files[1..] {
+            db.put(&mut txn, key, embeddings).unwrap();
+        }
+        index
+            .insert_files(
+                &mut txn,
+                files[1..]
+                    .iter()
+                    .map(|(key, embeddings)| (key.as_str(), embeddings.iter().collect())),
+            )
+            .unwrap();
+        txn.commit().unwrap();
+
+        // There aren't enough chunks to train the index yet.
+        assert!(!index
+            .train_if_needed(&env, db, |file| file.iter().collect())
+            .unwrap());
+        let txn = env.read_txn().unwrap();
+        let query = files[0].1[3].clone();
+        assert!(index
+            .search(&txn, &query, 10, |_| Ok(true))
+            .unwrap()
+            .is_none());
+        drop(txn);
+
+        let mut txn = env.write_txn().unwrap();
+        db.put(&mut txn, &files[0].0, &files[0].1).unwrap();
+        index
+            .insert_files(
+                &mut txn,
+                [(files[0].0.as_str(), files[0].1.iter().collect())],
+            )
+            .unwrap();
+        txn.commit().unwrap();
+        assert!(index
+            .train_if_needed(&env, db, |file| file.iter().collect())
+            .unwrap());
+        assert!(!index
+            .train_if_needed(&env, db, |file| file.iter().collect())
+            .unwrap());
+
+        // The query's own embedding is always among the candidates.
+        let txn = env.read_txn().unwrap();
+        let candidates = index
+            .search(&txn, &query, 10, |_| Ok(true))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            candidates.iter().map(|(_, ixs)| ixs.len()).sum::<usize>(),
+            10
+        );
+        assert!(candidates
+            .iter()
+            .any(|(key, ixs)| *key == files[0].0 && ixs.contains(&3)));
+        drop(txn);
+
+        // Deleted files are no longer returned.
+        let mut txn = env.write_txn().unwrap();
+        let key = files[0].0.as_str();
+        index
+            .delete_range(&mut txn, &(Bound::Included(key), Bound::Included(key)))
+            .unwrap();
+        txn.commit().unwrap();
+        let txn = env.read_txn().unwrap();
+        let candidates = index
+            .search(&txn, &query, 10, |_| Ok(true))
+            .unwrap()
+            .unwrap();
+        assert!(candidates.iter().all(|(key, _)| *key != files[0].0));
+
+        // Files outside the probed lists are found when they're the only ones that match.
+        let target = files.last().unwrap().0.as_str();
+        let candidates = index
+            .search(&txn, &query, 10, |key| Ok(key == target))
+            .unwrap()
+            .unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].0, target);
+        let mut chunk_ixs = candidates[0].1.clone();
+        chunk_ixs.sort();
+        assert_eq!(chunk_ixs, (0..8).collect::<Vec<_>>());
+    }
+}
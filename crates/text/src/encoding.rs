@@ -0,0 +1,242 @@
+use anyhow::{anyhow, Result};
+use std::{borrow::Cow, fmt, str};
+
+const UTF_8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF_16LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF_16BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+/// The character encoding of a file on disk, and whether its contents start with a byte order mark.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Encoding {
+    encoding: &'static encoding_rs::Encoding,
+    bom: bool,
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Self {
+            encoding: encoding_rs::UTF_8,
+            bom: false,
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl Encoding {
+    /// The encodings that files can be explicitly reopened or saved with.
+    pub fn all() -> Vec<Self> {
+        let with_bom = |encoding| Self {
+            encoding,
+            bom: true,
+        };
+        let without_bom = |encoding| Self {
+            encoding,
+            bom: false,
+        };
+
+        let mut encodings = vec![
+            without_bom(encoding_rs::UTF_8),
+            with_bom(encoding_rs::UTF_8),
+            with_bom(encoding_rs::UTF_16LE),
+            with_bom(encoding_rs::UTF_16BE),
+        ];
+        encodings.extend(
+            [
+                encoding_rs::WINDOWS_1252,
+                encoding_rs::ISO_8859_15,
+                encoding_rs::WINDOWS_1250,
+                encoding_rs::ISO_8859_2,
+                encoding_rs::WINDOWS_1251,
+                encoding_rs::KOI8_R,
+                encoding_rs::KOI8_U,
+                encoding_rs::IBM866,
+                encoding_rs::WINDOWS_1253,
+                encoding_rs::ISO_8859_7,
+                encoding_rs::WINDOWS_1254,
+                encoding_rs::WINDOWS_1255,
+                encoding_rs::ISO_8859_8,
+                encoding_rs::WINDOWS_1256,
+                encoding_rs::ISO_8859_6,
+                encoding_rs::WINDOWS_1257,
+                encoding_rs::ISO_8859_4,
+                encoding_rs::WINDOWS_1258,
+                encoding_rs::WINDOWS_874,
+                encoding_rs::MACINTOSH,
+                encoding_rs::SHIFT_JIS,
+                encoding_rs::EUC_JP,
+                encoding_rs::ISO_2022_JP,
+                encoding_rs::GBK,
+                encoding_rs::GB18030,
+                encoding_rs::BIG5,
+                encoding_rs::EUC_KR,
+            ]
+            .into_iter()
+            .map(without_bom),
+        );
+        encodings
+    }
+
+    /// Guesses the encoding of the given file contents, based on their byte order mark if
+    /// they have one, and on heuristics otherwise. Returns `None` if the contents don't look
+    /// like text in any encoding.
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        if let Some((encoding, _)) = encoding_rs::Encoding::for_bom(bytes) {
+            return Some(Self {
+                encoding,
+                bom: true,
+            });
+        }
+        if let Some(encoding) = detect_utf16_without_bom(bytes) {
+            return Some(Self {
+                encoding,
+                bom: false,
+            });
+        }
+        if str::from_utf8(bytes).is_ok() {
+            return Some(Self::default());
+        }
+        // Null bytes don't occur in text encoded with any of the single or multi-byte
+        // encodings detected below, so they most likely belong to a binary file.
+        if bytes.contains(&0) {
+            return None;
+        }
+
+        let mut detector = chardetng::EncodingDetector::new();
+        detector.feed(bytes, true);
+        Some(Self {
+            encoding: detector.guess(None, true),
+            bom: false,
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        if self.bom && self.encoding == encoding_rs::UTF_8 {
+            "UTF-8 with BOM"
+        } else {
+            self.encoding.name()
+        }
+    }
+
+    pub fn is_utf8(&self) -> bool {
+        self.encoding == encoding_rs::UTF_8
+    }
+
+    pub fn has_bom(&self) -> bool {
+        self.bom
+    }
+
+    /// The byte order mark written at the start of files saved with this encoding.
+    pub fn bom(&self) -> &'static [u8] {
+        if !self.bom {
+            &[]
+        } else if self.encoding == encoding_rs::UTF_8 {
+            UTF_8_BOM
+        } else if self.encoding == encoding_rs::UTF_16LE {
+            UTF_16LE_BOM
+        } else if self.encoding == encoding_rs::UTF_16BE {
+            UTF_16BE_BOM
+        } else {
+            &[]
+        }
+    }
+
+    /// Decodes the given file contents, stripping their byte order mark. Malformed sequences
+    /// are replaced with the replacement character.
+    pub fn decode(&self, bytes: Vec<u8>) -> String {
+        if self.is_utf8() && !bytes.starts_with(UTF_8_BOM) {
+            match String::from_utf8(bytes) {
+                Ok(text) => return text,
+                Err(error) => {
+                    return String::from_utf8_lossy(error.as_bytes()).into_owned();
+                }
+            }
+        }
+
+        self.encoding.decode_with_bom_removal(&bytes).0.into_owned()
+    }
+
+    /// Returns a decoder for file contents that are read in several chunks, which strips their
+    /// byte order mark like [`Encoding::decode`].
+    pub fn decoder(&self) -> Decoder {
+        Decoder(self.encoding.new_decoder_with_bom_removal())
+    }
+
+    /// Encodes the given text, without a byte order mark. Fails if the text contains
+    /// characters that can't be represented in this encoding.
+    pub fn encode<'a>(&self, text: &'a str) -> Result<Cow<'a, [u8]>> {
+        if self.is_utf8() {
+            Ok(Cow::Borrowed(text.as_bytes()))
+        } else if self.encoding == encoding_rs::UTF_16LE {
+            Ok(Cow::Owned(
+                text.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+            ))
+        } else if self.encoding == encoding_rs::UTF_16BE {
+            Ok(Cow::Owned(
+                text.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+            ))
+        } else {
+            let (bytes, _, had_unmappable_characters) = self.encoding.encode(text);
+            if had_unmappable_characters {
+                Err(anyhow!(
+                    "the text contains characters that can't be represented in {}",
+                    self.name()
+                ))
+            } else {
+                Ok(bytes)
+            }
+        }
+    }
+}
+
+/// Decodes file contents read in chunks, see [`Encoding::decoder`].
+pub struct Decoder(encoding_rs::Decoder);
+
+impl Decoder {
+    /// Decodes the next chunk of the contents. Sequences that are incomplete at the end of the
+    /// chunk are decoded along with the next one, or replaced with the replacement character
+    /// if `is_last` is true.
+    pub fn decode(&mut self, bytes: &[u8], is_last: bool) -> String {
+        let capacity = self
+            .0
+            .max_utf8_buffer_length(bytes.len())
+            .unwrap_or(bytes.len());
+        let mut text = String::with_capacity(capacity);
+        let (result, _, _) = self.0.decode_to_string(bytes, &mut text, is_last);
+        debug_assert_eq!(result, encoding_rs::CoderResult::InputEmpty);
+        text
+    }
+}
+
+/// Detects UTF-16 without a byte order mark from the proportion of null bytes at even and odd
+/// offsets, which is high in one of them for mostly-ASCII text.
+fn detect_utf16_without_bom(bytes: &[u8]) -> Option<&'static encoding_rs::Encoding> {
+    if bytes.is_empty() || bytes.len() % 2 != 0 {
+        return None;
+    }
+
+    let sample = &bytes[..bytes.len().min(1024)];
+    let pair_count = sample.len() / 2;
+    let mut even_nulls = 0;
+    let mut odd_nulls = 0;
+    for pair in sample.chunks_exact(2) {
+        if pair[0] == 0 {
+            even_nulls += 1;
+        }
+        if pair[1] == 0 {
+            odd_nulls += 1;
+        }
+    }
+
+    if odd_nulls * 2 > pair_count && even_nulls * 10 < pair_count {
+        Some(encoding_rs::UTF_16LE)
+    } else if even_nulls * 2 > pair_count && odd_nulls * 10 < pair_count {
+        Some(encoding_rs::UTF_16BE)
+    } else {
+        None
+    }
+}
@@ -189,6 +189,48 @@ fn test_line_endings() {
     buffer.check_invariants();
 }
 
+#[test]
+fn test_encodings() {
+    let text = "naïve café";
+
+    let utf8 = Encoding::detect(text.as_bytes()).unwrap();
+    assert!(utf8.is_utf8());
+    assert!(!utf8.has_bom());
+
+    let utf8_with_bom = [&[0xEF, 0xBB, 0xBF], text.as_bytes()].concat();
+    let encoding = Encoding::detect(&utf8_with_bom).unwrap();
+    assert_eq!(encoding.name(), "UTF-8 with BOM");
+    assert_eq!(encoding.decode(utf8_with_bom.clone()), text);
+    assert_eq!(
+        [encoding.bom(), &encoding.encode(text).unwrap()].concat(),
+        utf8_with_bom
+    );
+
+    let utf16 = text
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect::<Vec<_>>();
+    let encoding = Encoding::detect(&utf16).unwrap();
+    assert_eq!(encoding.name(), "UTF-16LE");
+    assert!(!encoding.has_bom());
+    assert_eq!(encoding.decode(utf16.clone()), text);
+    assert_eq!(encoding.encode(text).unwrap().as_ref(), utf16.as_slice());
+
+    let latin1 =
+        b"Cette phrase en fran\xe7ais est cod\xe9e en Windows-1252, comme \xe0 l'\xe9poque.";
+    let encoding = Encoding::detect(latin1).unwrap();
+    assert_eq!(encoding.name(), "windows-1252");
+    let decoded = encoding.decode(latin1.to_vec());
+    assert!(decoded.contains("français"));
+    assert_eq!(encoding.encode(&decoded).unwrap().as_ref(), latin1);
+    assert!(encoding.encode("🍐").is_err());
+
+    assert_eq!(
+        Encoding::detect(b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR\xff"),
+        None
+    );
+}
+
 #[test]
 fn test_line_len() {
     let mut buffer = Buffer::new(0, BufferId::new(1).unwrap(), "".into());
@@ -0,0 +1,270 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use fs::normalize_path;
+use gpui::{AssetSource, SharedString};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The directories of the loaded icon themes, keyed by the icon themes' names.
+///
+/// These are the only directories outside of the bundled assets that icons are read from.
+static ICON_THEME_DIRS: RwLock<BTreeMap<SharedString, PathBuf>> = RwLock::new(BTreeMap::new());
+
+pub(crate) fn add_icon_theme_dir(icon_theme_name: SharedString, icons_root_dir: &Path) {
+    ICON_THEME_DIRS
+        .write()
+        .unwrap()
+        .insert(icon_theme_name, normalize_path(icons_root_dir));
+}
+
+pub(crate) fn remove_icon_theme_dirs(icon_theme_names: &[SharedString]) {
+    ICON_THEME_DIRS
+        .write()
+        .unwrap()
+        .retain(|name, _| !icon_theme_names.contains(name));
+}
+
+/// Returns the path on disk of the given icon, if it's within the directory of a loaded icon
+/// theme.
+fn icon_theme_icon_path(path: &str) -> Option<PathBuf> {
+    let path = Path::new(path);
+    if !path.is_absolute() {
+        return None;
+    }
+
+    let path = normalize_path(path);
+    ICON_THEME_DIRS
+        .read()
+        .unwrap()
+        .values()
+        .any(|icons_root_dir| path.starts_with(icons_root_dir))
+        .then_some(path)
+}
+
+/// An [`AssetSource`] that reads the icons of the loaded icon themes from their directories, and
+/// loads any other asset from the wrapped source.
+pub struct IconThemeAssets<A>(pub A);
+
+impl<A: AssetSource> AssetSource for IconThemeAssets<A> {
+    fn load(&self, path: &str) -> Result<Option<Cow<'static, [u8]>>> {
+        if let Some(icon_path) = icon_theme_icon_path(path) {
+            let icon = std::fs::read(icon_path)
+                .with_context(|| format!("could not read icon at path {path:?}"))?;
+            return Ok(Some(icon.into()));
+        }
+
+        self.0.load(path)
+    }
+
+    fn list(&self, path: &str) -> Result<Vec<SharedString>> {
+        self.0.list(path)
+    }
+}
+
+/// A set of icons for files and folders, used in place of Zed's built-in file icons.
+#[derive(Debug, PartialEq)]
+pub struct IconTheme {
+    pub name: SharedString,
+    /// The icons for files with specific names, such as `Cargo.toml`.
+    pub file_names: HashMap<String, SharedString>,
+    /// The icons for files with specific suffixes, such as `rs` or `d.ts`.
+    pub file_suffixes: HashMap<String, SharedString>,
+    /// The icon for the files that no other icon matches.
+    pub default_file: Option<SharedString>,
+    /// The icons for collapsed and expanded folders.
+    pub folder: Option<FolderIcons>,
+    /// The icons for collapsed and expanded chevrons, shown when folder icons are turned off.
+    pub chevron: Option<FolderIcons>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FolderIcons {
+    pub collapsed: SharedString,
+    pub expanded: SharedString,
+}
+
+impl IconTheme {
+    /// Returns an icon theme for the given content, whose icon paths are relative to the given
+    /// directory.
+    pub fn from_content(content: IconThemeContent, icons_root_dir: &Path) -> Self {
+        let icon_path = |path: String| -> SharedString {
+            icons_root_dir
+                .join(path)
+                .to_string_lossy()
+                .to_string()
+                .into()
+        };
+        let folder_icons = |icons: FolderIconsContent| FolderIcons {
+            collapsed: icon_path(icons.collapsed),
+            expanded: icon_path(icons.expanded),
+        };
+
+        Self {
+            name: content.name.into(),
+            file_names: content
+                .file_names
+                .into_iter()
+                .map(|(name, path)| (name, icon_path(path)))
+                .collect(),
+            file_suffixes: content
+                .file_suffixes
+                .into_iter()
+                .map(|(suffix, path)| (suffix, icon_path(path)))
+                .collect(),
+            default_file: content.default_file.map(icon_path),
+            folder: content.folder.map(folder_icons),
+            chevron: content.chevron.map(folder_icons),
+        }
+    }
+
+    /// Returns the icon for the file at the given path.
+    ///
+    /// An icon for the file's name takes precedence over icons for its suffixes, and longer
+    /// suffixes take precedence over shorter ones.
+    pub fn file_icon(&self, path: &Path) -> Option<SharedString> {
+        let file_name = path.file_name()?.to_str()?;
+        if let Some(icon) = self.file_names.get(file_name) {
+            return Some(icon.clone());
+        }
+
+        file_name
+            .match_indices('.')
+            .filter_map(|(ix, _)| self.file_suffixes.get(&file_name[ix + 1..]))
+            .next()
+            .or(self.default_file.as_ref())
+            .cloned()
+    }
+
+    /// Returns the icon for a collapsed or expanded folder.
+    pub fn folder_icon(&self, expanded: bool) -> Option<SharedString> {
+        self.folder.as_ref().map(|icons| icons.get(expanded))
+    }
+
+    /// Returns the chevron icon for a collapsed or expanded folder.
+    pub fn chevron_icon(&self, expanded: bool) -> Option<SharedString> {
+        self.chevron.as_ref().map(|icons| icons.get(expanded))
+    }
+}
+
+impl FolderIcons {
+    fn get(&self, expanded: bool) -> SharedString {
+        if expanded {
+            self.expanded.clone()
+        } else {
+            self.collapsed.clone()
+        }
+    }
+}
+
+/// The contents of an icon theme file, in which icons are paths to SVG files.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct IconThemeContent {
+    pub name: String,
+    /// The icons for files with specific names, such as `Cargo.toml`.
+    #[serde(default)]
+    pub file_names: HashMap<String, String>,
+    /// The icons for files with specific suffixes, without the leading dot, such as `rs` or
+    /// `d.ts`.
+    #[serde(default)]
+    pub file_suffixes: HashMap<String, String>,
+    /// The icon for the files that no other icon matches.
+    #[serde(default)]
+    pub default_file: Option<String>,
+    /// The icons for collapsed and expanded folders.
+    #[serde(default)]
+    pub folder: Option<FolderIconsContent>,
+    /// The icons for collapsed and expanded chevrons, shown when folder icons are turned off.
+    #[serde(default)]
+    pub chevron: Option<FolderIconsContent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct FolderIconsContent {
+    pub collapsed: String,
+    pub expanded: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_icon() {
+        let icon_theme = IconTheme::from_content(
+            IconThemeContent {
+                name: "Test Icons".into(),
+                file_names: HashMap::from_iter([("Cargo.toml".into(), "icons/cargo.svg".into())]),
+                file_suffixes: HashMap::from_iter([
+                    ("toml".into(), "icons/toml.svg".into()),
+                    ("ts".into(), "icons/typescript.svg".into()),
+                    ("d.ts".into(), "icons/typescript-declaration.svg".into()),
+                ]),
+                default_file: Some("icons/file.svg".into()),
+                folder: Some(FolderIconsContent {
+                    collapsed: "icons/folder.svg".into(),
+                    expanded: "icons/folder-open.svg".into(),
+                }),
+                chevron: None,
+            },
+            Path::new("/extensions/test-icons"),
+        );
+
+        let file_icon = |path: &str| icon_theme.file_icon(Path::new(path));
+        assert_eq!(
+            file_icon("project/Cargo.toml"),
+            Some("/extensions/test-icons/icons/cargo.svg".into())
+        );
+        assert_eq!(
+            file_icon("project/rustfmt.toml"),
+            Some("/extensions/test-icons/icons/toml.svg".into())
+        );
+        assert_eq!(
+            file_icon("src/index.d.ts"),
+            Some("/extensions/test-icons/icons/typescript-declaration.svg".into())
+        );
+        assert_eq!(
+            file_icon("src/index.ts"),
+            Some("/extensions/test-icons/icons/typescript.svg".into())
+        );
+        assert_eq!(
+            file_icon("README"),
+            Some("/extensions/test-icons/icons/file.svg".into())
+        );
+
+        assert_eq!(
+            icon_theme.folder_icon(true),
+            Some("/extensions/test-icons/icons/folder-open.svg".into())
+        );
+        assert_eq!(icon_theme.chevron_icon(false), None);
+    }
+    #[test]
+    #[cfg(not(windows))]
+    fn test_icon_theme_icon_path() {
+        add_icon_theme_dir(
+            "Test Path Icons".into(),
+            Path::new("/extensions/test-path-icons"),
+        );
+
+        assert_eq!(
+            icon_theme_icon_path("/extensions/test-path-icons/icons/file.svg"),
+            Some(PathBuf::from("/extensions/test-path-icons/icons/file.svg"))
+        );
+        assert_eq!(
+            icon_theme_icon_path("/extensions/test-path-icons/../../etc/passwd"),
+            None
+        );
+        assert_eq!(icon_theme_icon_path("/etc/passwd"), None);
+        assert_eq!(icon_theme_icon_path("icons/file.svg"), None);
+
+        remove_icon_theme_dirs(&["Test Path Icons".into()]);
+        assert_eq!(
+            icon_theme_icon_path("/extensions/test-path-icons/icons/file.svg"),
+            None
+        );
+    }
+}
@@ -11,10 +11,11 @@ use parking_lot::RwLock;
 use refineable::Refineable;
 use util::ResultExt;
 
+use crate::icon_theme::{add_icon_theme_dir, remove_icon_theme_dirs};
 use crate::{
-    try_parse_color, AccentColors, Appearance, AppearanceContent, PlayerColors, StatusColors,
-    SyntaxTheme, SystemColors, Theme, ThemeColors, ThemeContent, ThemeFamily, ThemeFamilyContent,
-    ThemeStyles,
+    try_parse_color, AccentColors, Appearance, AppearanceContent, IconTheme, IconThemeContent,
+    PlayerColors, StatusColors, SyntaxTheme, SystemColors, Theme, ThemeColors, ThemeContent,
+    ThemeFamily, ThemeFamilyContent, ThemeStyles,
 };
 
 #[derive(Debug, Clone)]
@@ -36,6 +37,7 @@ impl Global for GlobalThemeRegistry {}
 
 struct ThemeRegistryState {
     themes: HashMap<SharedString, Arc<Theme>>,
+    icon_themes: HashMap<SharedString, Arc<IconTheme>>,
 }
 
 pub struct ThemeRegistry {
@@ -65,6 +67,7 @@ impl ThemeRegistry {
         let registry = Self {
             state: RwLock::new(ThemeRegistryState {
                 themes: HashMap::default(),
+                icon_themes: HashMap::default(),
             }),
             assets,
         };
@@ -290,6 +293,65 @@ impl ThemeRegistry {
 
         Ok(())
     }
+
+    pub async fn read_icon_theme(
+        icon_theme_path: &Path,
+        fs: Arc<dyn Fs>,
+    ) -> Result<IconThemeContent> {
+        let reader = fs.open_sync(icon_theme_path).await?;
+        let icon_theme: IconThemeContent = serde_json_lenient::from_reader(reader)?;
+        Ok(icon_theme)
+    }
+
+    /// Loads the icon theme from the specified path and adds it to the registry.
+    ///
+    /// The paths of the icons in the icon theme are relative to the given directory, which is the
+    /// only directory that [`IconThemeAssets`](crate::IconThemeAssets) read its icons from.
+    pub async fn load_icon_theme(
+        &self,
+        icon_theme_path: &Path,
+        icons_root_dir: &Path,
+        fs: Arc<dyn Fs>,
+    ) -> Result<()> {
+        let icon_theme = Self::read_icon_theme(icon_theme_path, fs).await?;
+        let icon_theme = IconTheme::from_content(icon_theme, icons_root_dir);
+        add_icon_theme_dir(icon_theme.name.clone(), icons_root_dir);
+        self.state
+            .write()
+            .icon_themes
+            .insert(icon_theme.name.clone(), Arc::new(icon_theme));
+        Ok(())
+    }
+
+    /// Removes the icon themes with the given names from the registry.
+    pub fn remove_icon_themes(&self, icon_themes_to_remove: &[SharedString]) {
+        remove_icon_theme_dirs(icon_themes_to_remove);
+        self.state
+            .write()
+            .icon_themes
+            .retain(|name, _| !icon_themes_to_remove.contains(name))
+    }
+
+    pub fn list_icon_theme_names(&self) -> Vec<SharedString> {
+        let mut names = self
+            .state
+            .read()
+            .icon_themes
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    pub fn get_icon_theme(&self, name: &str) -> Result<Arc<IconTheme>> {
+        self.state
+            .read()
+            .icon_themes
+            .get(name)
+            .ok_or_else(|| anyhow!("icon theme not found: {}", name))
+            .cloned()
+    }
 }
 
 impl Default for ThemeRegistry {
@@ -1,10 +1,10 @@
 use crate::one_themes::one_dark;
-use crate::{Appearance, SyntaxTheme, Theme, ThemeRegistry, ThemeStyleContent};
+use crate::{Appearance, IconTheme, SyntaxTheme, Theme, ThemeRegistry, ThemeStyleContent};
 use anyhow::Result;
 use derive_more::{Deref, DerefMut};
 use gpui::{
-    px, AppContext, Font, FontFeatures, FontStyle, FontWeight, Global, Pixels, Subscription,
-    ViewContext, WindowContext,
+    px, AppContext, Font, FontFeatures, FontStyle, FontWeight, Global, Pixels, SharedString,
+    Subscription, ViewContext, WindowContext,
 };
 use refineable::Refineable;
 use schemars::{
@@ -91,6 +91,7 @@ pub struct ThemeSettings {
     pub active_theme: Arc<Theme>,
     pub theme_overrides: Option<ThemeStyleContent>,
     pub ui_density: UiDensity,
+    pub icon_theme: Option<SharedString>,
 }
 
 impl ThemeSettings {
@@ -120,6 +121,12 @@ impl ThemeSettings {
             }
         }
     }
+
+    /// Returns the selected icon theme, if there is one and it's available.
+    pub fn active_icon_theme(&self, cx: &AppContext) -> Option<Arc<IconTheme>> {
+        let icon_theme = self.icon_theme.as_ref()?;
+        ThemeRegistry::global(cx).get_icon_theme(icon_theme).ok()
+    }
 }
 
 /// The appearance of the system.
@@ -253,6 +260,10 @@ pub struct ThemeSettingsContent {
     /// The name of the Zed theme to use.
     #[serde(default)]
     pub theme: Option<ThemeSelection>,
+    /// The name of the icon theme to use for files and folders, which is provided by an
+    /// extension. Zed's built-in icons are used if this isn't set.
+    #[serde(default)]
+    pub icon_theme: Option<String>,
 
     /// UNSTABLE: Expect many elements to be broken.
     ///
@@ -465,6 +476,7 @@ impl settings::Settings for ThemeSettings {
                 .unwrap(),
             theme_overrides: None,
             ui_density: defaults.ui_density.unwrap_or(UiDensity::Default),
+            icon_theme: defaults.icon_theme.clone().map(Into::into),
         };
 
         for value in sources.user.into_iter().chain(sources.release_channel) {
@@ -503,6 +515,10 @@ impl settings::Settings for ThemeSettings {
                 }
             }
 
+            if let Some(value) = &value.icon_theme {
+                this.icon_theme = Some(value.clone().into());
+            }
+
             this.theme_overrides.clone_from(&value.theme_overrides);
             this.apply_theme_overrides();
 
@@ -535,6 +551,18 @@ impl settings::Settings for ThemeSettings {
             ..Default::default()
         };
 
+        let icon_theme_names = ThemeRegistry::global(cx)
+            .list_icon_theme_names()
+            .into_iter()
+            .map(|icon_theme_name| Value::String(icon_theme_name.to_string()))
+            .chain([Value::Null])
+            .collect();
+        let icon_theme_name_schema = SchemaObject {
+            instance_type: Some(vec![InstanceType::String, InstanceType::Null].into()),
+            enum_values: Some(icon_theme_names),
+            ..Default::default()
+        };
+
         let available_fonts = params
             .font_names
             .iter()
@@ -548,6 +576,7 @@ impl settings::Settings for ThemeSettings {
         };
         root_schema.definitions.extend([
             ("ThemeName".into(), theme_name_schema.into()),
+            ("IconThemeName".into(), icon_theme_name_schema.into()),
             ("FontFamilies".into(), fonts_schema.into()),
         ]);
 
@@ -558,6 +587,10 @@ impl settings::Settings for ThemeSettings {
             .unwrap()
             .properties
             .extend([
+                (
+                    "icon_theme".to_owned(),
+                    Schema::new_ref("#/definitions/IconThemeName".into()),
+                ),
                 (
                     "buffer_font_family".to_owned(),
                     Schema::new_ref("#/definitions/FontFamilies".into()),
@@ -8,6 +8,7 @@
 
 mod default_colors;
 mod default_theme;
+mod icon_theme;
 mod one_themes;
 pub mod prelude;
 mod registry;
@@ -21,6 +22,7 @@ use std::sync::Arc;
 use ::settings::{Settings, SettingsStore};
 pub use default_colors::*;
 pub use default_theme::*;
+pub use icon_theme::*;
 pub use registry::*;
 pub use scale::*;
 pub use schema::*;
@@ -0,0 +1,312 @@
+use client::telemetry::Telemetry;
+use fs::Fs;
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    impl_actions, AppContext, DismissEvent, EventEmitter, FocusableView, Render, SharedString,
+    UpdateGlobal, View, ViewContext, VisualContext, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use serde::Deserialize;
+use settings::{update_settings_file, Settings, SettingsStore};
+use std::sync::Arc;
+use theme::{ThemeRegistry, ThemeSettings};
+use ui::{prelude::*, v_flex, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ui::HighlightedLabel, ModalView, Workspace};
+
+/// The label of the entry that selects Zed's built-in file icons.
+const DEFAULT_ICON_THEME_LABEL: &str = "Zed (Default)";
+
+#[derive(PartialEq, Clone, Default, Debug, Deserialize)]
+pub struct Toggle {
+    /// A list of icon theme names to filter the icon theme selector down to.
+    pub icon_themes_filter: Option<Vec<String>>,
+}
+
+impl_actions!(icon_theme_selector, [Toggle]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(
+        |workspace: &mut Workspace, _cx: &mut ViewContext<Workspace>| {
+            workspace.register_action(toggle);
+        },
+    )
+    .detach();
+}
+
+pub fn toggle(workspace: &mut Workspace, toggle: &Toggle, cx: &mut ViewContext<Workspace>) {
+    let fs = workspace.app_state().fs.clone();
+    let telemetry = workspace.client().telemetry().clone();
+    workspace.toggle_modal(cx, |cx| {
+        let delegate = IconThemeSelectorDelegate::new(
+            cx.view().downgrade(),
+            fs,
+            telemetry,
+            toggle.icon_themes_filter.as_ref(),
+            cx,
+        );
+        IconThemeSelector::new(delegate, cx)
+    });
+}
+
+impl ModalView for IconThemeSelector {}
+
+pub struct IconThemeSelector {
+    picker: View<Picker<IconThemeSelectorDelegate>>,
+}
+
+impl EventEmitter<DismissEvent> for IconThemeSelector {}
+
+impl FocusableView for IconThemeSelector {
+    fn focus_handle(&self, cx: &AppContext) -> gpui::FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for IconThemeSelector {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl IconThemeSelector {
+    pub fn new(delegate: IconThemeSelectorDelegate, cx: &mut ViewContext<Self>) -> Self {
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+pub struct IconThemeSelectorDelegate {
+    fs: Arc<dyn Fs>,
+    /// The names of the icon themes, where `None` stands for Zed's built-in icons.
+    icon_themes: Vec<Option<SharedString>>,
+    matches: Vec<StringMatch>,
+    original_icon_theme: Option<SharedString>,
+    selection_completed: bool,
+    selected_index: usize,
+    telemetry: Arc<Telemetry>,
+    view: WeakView<IconThemeSelector>,
+}
+
+impl IconThemeSelectorDelegate {
+    fn new(
+        weak_view: WeakView<IconThemeSelector>,
+        fs: Arc<dyn Fs>,
+        telemetry: Arc<Telemetry>,
+        icon_themes_filter: Option<&Vec<String>>,
+        cx: &mut ViewContext<IconThemeSelector>,
+    ) -> Self {
+        let original_icon_theme = ThemeSettings::get_global(cx).icon_theme.clone();
+
+        let registry = ThemeRegistry::global(cx);
+        let mut icon_themes = Vec::new();
+        if icon_themes_filter.is_none() {
+            icon_themes.push(None);
+        }
+        icon_themes.extend(
+            registry
+                .list_icon_theme_names()
+                .into_iter()
+                .filter(|name| {
+                    if let Some(icon_themes_filter) = icon_themes_filter {
+                        icon_themes_filter.contains(&name.to_string())
+                    } else {
+                        true
+                    }
+                })
+                .map(Some),
+        );
+
+        let matches = icon_themes
+            .iter()
+            .enumerate()
+            .map(|(id, name)| StringMatch {
+                candidate_id: id,
+                score: 0.0,
+                positions: Default::default(),
+                string: icon_theme_label(name),
+            })
+            .collect();
+        let mut this = Self {
+            fs,
+            icon_themes,
+            matches,
+            original_icon_theme: original_icon_theme.clone(),
+            selected_index: 0,
+            selection_completed: false,
+            telemetry,
+            view: weak_view,
+        };
+
+        this.select_if_matching(&original_icon_theme);
+        this
+    }
+
+    fn selected_icon_theme(&self) -> Option<Option<SharedString>> {
+        let mat = self.matches.get(self.selected_index)?;
+        self.icon_themes.get(mat.candidate_id).cloned()
+    }
+
+    fn show_selected_icon_theme(
+        &mut self,
+        cx: &mut ViewContext<Picker<IconThemeSelectorDelegate>>,
+    ) {
+        if let Some(icon_theme) = self.selected_icon_theme() {
+            Self::set_icon_theme(icon_theme, cx);
+        }
+    }
+
+    fn select_if_matching(&mut self, icon_theme: &Option<SharedString>) {
+        self.selected_index = self
+            .matches
+            .iter()
+            .position(|mat| self.icon_themes.get(mat.candidate_id) == Some(icon_theme))
+            .unwrap_or(self.selected_index);
+    }
+
+    fn set_icon_theme(icon_theme: Option<SharedString>, cx: &mut AppContext) {
+        SettingsStore::update_global(cx, |store, cx| {
+            let mut theme_settings = store.get::<ThemeSettings>(None).clone();
+            theme_settings.icon_theme = icon_theme;
+            store.override_global(theme_settings);
+            cx.refresh();
+        });
+    }
+}
+
+impl PickerDelegate for IconThemeSelectorDelegate {
+    type ListItem = ui::ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Select Icon Theme...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<IconThemeSelectorDelegate>>) {
+        self.selection_completed = true;
+
+        let icon_theme = ThemeSettings::get_global(cx).icon_theme.clone();
+
+        self.telemetry
+            .report_setting_event("icon_theme", icon_theme_label(&icon_theme));
+
+        update_settings_file::<ThemeSettings>(self.fs.clone(), cx, move |settings| {
+            settings.icon_theme = icon_theme.map(|name| name.to_string());
+        });
+
+        self.view
+            .update(cx, |_, cx| {
+                cx.emit(DismissEvent);
+            })
+            .ok();
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<IconThemeSelectorDelegate>>) {
+        if !self.selection_completed {
+            Self::set_icon_theme(self.original_icon_theme.clone(), cx);
+            self.selection_completed = true;
+        }
+
+        self.view
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        cx: &mut ViewContext<Picker<IconThemeSelectorDelegate>>,
+    ) {
+        self.selected_index = ix;
+        self.show_selected_icon_theme(cx);
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        cx: &mut ViewContext<Picker<IconThemeSelectorDelegate>>,
+    ) -> gpui::Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .icon_themes
+            .iter()
+            .enumerate()
+            .map(|(id, name)| {
+                let label = icon_theme_label(name);
+                StringMatchCandidate {
+                    id,
+                    char_bag: label.as_str().into(),
+                    string: label,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                this.delegate.matches = matches;
+                this.delegate.selected_index = this
+                    .delegate
+                    .selected_index
+                    .min(this.delegate.matches.len().saturating_sub(1));
+                this.delegate.show_selected_icon_theme(cx);
+            })
+            .log_err();
+        })
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let icon_theme_match = &self.matches[ix];
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(HighlightedLabel::new(
+                    icon_theme_match.string.clone(),
+                    icon_theme_match.positions.clone(),
+                )),
+        )
+    }
+}
+
+fn icon_theme_label(icon_theme: &Option<SharedString>) -> String {
+    match icon_theme {
+        Some(name) => name.to_string(),
+        None => DEFAULT_ICON_THEME_LABEL.to_string(),
+    }
+}
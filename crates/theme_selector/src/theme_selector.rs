@@ -1,3 +1,5 @@
+pub mod icon_theme_selector;
+
 use client::telemetry::Telemetry;
 use feature_flags::FeatureFlagAppExt;
 use fs::Fs;
@@ -27,6 +29,7 @@ impl_actions!(theme_selector, [Toggle]);
 actions!(theme_selector, [Reload]);
 
 pub fn init(cx: &mut AppContext) {
+    icon_theme_selector::init(cx);
     cx.observe_new_views(
         |workspace: &mut Workspace, _cx: &mut ViewContext<Workspace>| {
             workspace.register_action(toggle);
@@ -514,6 +514,11 @@ impl TitleBar {
                         menu.action("Settings", zed_actions::OpenSettings.boxed_clone())
                             .action("Key Bindings", Box::new(zed_actions::OpenKeymap))
                             .action("Themes…", theme_selector::Toggle::default().boxed_clone())
+                            .action(
+                                "Icon Themes…",
+                                theme_selector::icon_theme_selector::Toggle::default()
+                                    .boxed_clone(),
+                            )
                             .action("Extensions", extensions_ui::Extensions.boxed_clone())
                             .separator()
                             .action("Sign Out", client::SignOut.boxed_clone())
@@ -543,6 +548,11 @@ impl TitleBar {
                         menu.action("Settings", zed_actions::OpenSettings.boxed_clone())
                             .action("Key Bindings", Box::new(zed_actions::OpenKeymap))
                             .action("Themes…", theme_selector::Toggle::default().boxed_clone())
+                            .action(
+                                "Icon Themes…",
+                                theme_selector::icon_theme_selector::Toggle::default()
+                                    .boxed_clone(),
+                            )
                             .action("Extensions", extensions_ui::Extensions.boxed_clone())
                     })
                     .into()
@@ -19,8 +19,8 @@ use uuid::Uuid;
 use crate::WorkspaceId;
 
 use model::{
-    GroupId, LocalPaths, PaneId, SerializedItem, SerializedPane, SerializedPaneGroup,
-    SerializedWorkspace,
+    GroupId, LocalPaths, PaneId, SerializedBookmark, SerializedItem, SerializedPane,
+    SerializedPaneGroup, SerializedWorkspace,
 };
 
 use self::model::{
@@ -344,6 +344,20 @@ define_connection! {
     sql!(
         ALTER TABLE workspaces ADD COLUMN local_paths_order BLOB;
     ),
+    sql!(
+        CREATE TABLE bookmarks (
+            workspace_id INTEGER NOT NULL,
+            position INTEGER NOT NULL,
+            abs_path BLOB NOT NULL,
+            row INTEGER NOT NULL,
+            column INTEGER NOT NULL,
+            label TEXT,
+            PRIMARY KEY(workspace_id, position),
+            FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+        ) STRICT;
+    ),
     ];
 }
 
@@ -443,6 +457,11 @@ impl WorkspaceDb {
             centered_layout: centered_layout.unwrap_or(false),
             display,
             docks,
+            bookmarks: self
+                .get_bookmarks(workspace_id)
+                .context("Getting bookmarks")
+                .log_err()
+                .unwrap_or_default(),
         })
     }
 
@@ -536,6 +555,11 @@ impl WorkspaceDb {
             centered_layout: centered_layout.unwrap_or(false),
             display,
             docks,
+            bookmarks: self
+                .get_bookmarks(workspace_id)
+                .context("Getting bookmarks")
+                .log_err()
+                .unwrap_or_default(),
         })
     }
 
@@ -653,6 +677,9 @@ impl WorkspaceDb {
                 Self::save_pane_group(conn, workspace.id, &workspace.center_group, None)
                     .context("save pane group in save workspace")?;
 
+                Self::save_bookmarks(conn, workspace.id, &workspace.bookmarks)
+                    .context("save bookmarks in save workspace")?;
+
                 Ok(())
             })
             .log_err();
@@ -951,6 +978,34 @@ impl WorkspaceDb {
         Ok(())
     }
 
+    fn get_bookmarks(&self, workspace_id: WorkspaceId) -> Result<Vec<SerializedBookmark>> {
+        self.select_bound(sql!(
+            SELECT abs_path, row, column, label FROM bookmarks
+            WHERE workspace_id = ?
+                ORDER BY position
+        ))?(workspace_id)
+    }
+
+    fn save_bookmarks(
+        conn: &Connection,
+        workspace_id: WorkspaceId,
+        bookmarks: &[SerializedBookmark],
+    ) -> Result<()> {
+        conn.exec_bound(sql!(
+            DELETE FROM bookmarks WHERE workspace_id = ?
+        ))?(workspace_id)
+        .context("Clearing old bookmarks")?;
+
+        let mut insert = conn.exec_bound(sql!(
+            INSERT INTO bookmarks(workspace_id, position, abs_path, row, column, label) VALUES (?, ?, ?, ?, ?, ?)
+        )).context("Preparing insertion")?;
+        for (position, bookmark) in bookmarks.iter().enumerate() {
+            insert((workspace_id, position, bookmark))?;
+        }
+
+        Ok(())
+    }
+
     query! {
         pub async fn update_timestamp(workspace_id: WorkspaceId) -> Result<()> {
             UPDATE workspaces
@@ -986,6 +1041,7 @@ mod tests {
     use super::*;
     use db::open_test_db;
     use gpui;
+    use std::path::PathBuf;
 
     #[gpui::test]
     async fn test_next_id_stability() {
@@ -1065,6 +1121,7 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
+            bookmarks: Vec::new(),
         };
 
         let workspace_2 = SerializedWorkspace {
@@ -1075,6 +1132,7 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
+            bookmarks: Vec::new(),
         };
 
         db.save_workspace(workspace_1.clone()).await;
@@ -1177,6 +1235,20 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
+            bookmarks: vec![
+                SerializedBookmark {
+                    abs_path: PathBuf::from("/tmp/a.rs"),
+                    row: 10,
+                    column: 4,
+                    label: Some("entry point".to_string()),
+                },
+                SerializedBookmark {
+                    abs_path: PathBuf::from("/tmp2/b.rs"),
+                    row: 0,
+                    column: 0,
+                    label: None,
+                },
+            ],
         };
 
         db.save_workspace(workspace.clone()).await;
@@ -1209,6 +1281,7 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
+            bookmarks: Vec::new(),
         };
 
         let mut workspace_2 = SerializedWorkspace {
@@ -1219,6 +1292,7 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
+            bookmarks: Vec::new(),
         };
 
         db.save_workspace(workspace_1.clone()).await;
@@ -1259,6 +1333,7 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
+            bookmarks: Vec::new(),
         };
 
         db.save_workspace(workspace_3.clone()).await;
@@ -1293,6 +1368,7 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
+            bookmarks: Vec::new(),
         }
     }
 
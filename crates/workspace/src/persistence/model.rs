@@ -154,6 +154,7 @@ pub(crate) struct SerializedWorkspace {
     pub(crate) centered_layout: bool,
     pub(crate) display: Option<Uuid>,
     pub(crate) docks: DockStructure,
+    pub(crate) bookmarks: Vec<SerializedBookmark>,
 }
 
 #[derive(Debug, PartialEq, Clone, Default)]
@@ -444,3 +445,45 @@ impl Column for SerializedItem {
         ))
     }
 }
+
+/// A bookmark placed in one of the workspace's files, see [`project::bookmark_store`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SerializedBookmark {
+    pub abs_path: PathBuf,
+    pub row: u32,
+    pub column: u32,
+    pub label: Option<String>,
+}
+
+impl StaticColumnCount for SerializedBookmark {
+    fn column_count() -> usize {
+        4
+    }
+}
+
+impl Bind for &SerializedBookmark {
+    fn bind(&self, statement: &Statement, start_index: i32) -> Result<i32> {
+        let next_index = statement.bind(&self.abs_path, start_index)?;
+        let next_index = statement.bind(&self.row, next_index)?;
+        let next_index = statement.bind(&self.column, next_index)?;
+        statement.bind(&self.label, next_index)
+    }
+}
+
+impl Column for SerializedBookmark {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (abs_path, next_index) = PathBuf::column(statement, start_index)?;
+        let (row, next_index) = u32::column(statement, next_index)?;
+        let (column, next_index) = u32::column(statement, next_index)?;
+        let (label, next_index) = Option::<String>::column(statement, next_index)?;
+        Ok((
+            SerializedBookmark {
+                abs_path,
+                row,
+                column,
+                label,
+            },
+            next_index,
+        ))
+    }
+}
@@ -53,7 +53,9 @@ pub use persistence::{
     WorkspaceDb, DB as WORKSPACE_DB,
 };
 use postage::stream::Stream;
-use project::{Project, ProjectEntryId, ProjectPath, Worktree, WorktreeId};
+use project::{
+    bookmark_store::BookmarkStoreEvent, Project, ProjectEntryId, ProjectPath, Worktree, WorktreeId,
+};
 use serde::Deserialize;
 use settings::Settings;
 use shared_screen::SharedScreen;
@@ -92,7 +94,10 @@ pub use workspace_settings::{
 };
 
 use crate::persistence::{
-    model::{DockData, DockStructure, SerializedItem, SerializedPane, SerializedPaneGroup},
+    model::{
+        DockData, DockStructure, SerializedBookmark, SerializedItem, SerializedPane,
+        SerializedPaneGroup,
+    },
     SerializedAxis,
 };
 use crate::{notifications::NotificationId, persistence::model::LocalPathsOrder};
@@ -815,6 +820,7 @@ impl Workspace {
             active_call = Some((call, subscriptions));
         }
 
+        let bookmark_store = project.read(cx).bookmark_store().clone();
         let subscriptions = vec![
             cx.observe_window_activation(Self::on_window_activation_changed),
             cx.observe_window_bounds(move |this, cx| {
@@ -865,6 +871,9 @@ impl Workspace {
                 this.serialize_workspace(cx);
                 cx.notify();
             }),
+            cx.subscribe(&bookmark_store, |this, _, _: &BookmarkStoreEvent, cx| {
+                this.serialize_workspace(cx)
+            }),
             cx.on_release(|this, window, cx| {
                 this.app_state.workspace_store.update(cx, |store, _| {
                     let window = window.downcast::<Self>().unwrap();
@@ -3760,10 +3769,32 @@ impl Workspace {
             None
         };
 
+        fn build_serialized_bookmarks(
+            project: &Project,
+            cx: &AppContext,
+        ) -> Vec<SerializedBookmark> {
+            project
+                .bookmark_store()
+                .read(cx)
+                .bookmarks()
+                .iter()
+                .filter_map(|bookmark| {
+                    let point = bookmark.point(cx);
+                    Some(SerializedBookmark {
+                        abs_path: project.absolute_path(&bookmark.project_path, cx)?,
+                        row: point.row,
+                        column: point.column,
+                        label: bookmark.label.as_ref().map(|label| label.to_string()),
+                    })
+                })
+                .collect()
+        }
+
         // don't save workspace state for the empty workspace.
         if let Some(location) = location {
             let center_group = build_serialized_pane_group(&self.center.root, cx);
             let docks = build_serialized_docks(self, cx);
+            let bookmarks = build_serialized_bookmarks(self.project.read(cx), cx);
             let window_bounds = Some(SerializedWindowBounds(cx.window_bounds()));
             let serialized_workspace = SerializedWorkspace {
                 id: database_id,
@@ -3773,6 +3804,7 @@ impl Workspace {
                 display: Default::default(),
                 docks,
                 centered_layout: self.centered_layout,
+                bookmarks,
             };
             return cx.spawn(|_| persistence::DB.save_workspace(serialized_workspace));
         }
@@ -3841,6 +3873,8 @@ impl Workspace {
                     }
                 }
 
+                workspace.restore_bookmarks(serialized_workspace.bookmarks, cx);
+
                 let docks = serialized_workspace.docks;
 
                 for (dock, serialized_dock) in [
@@ -3870,6 +3904,41 @@ impl Workspace {
         })
     }
 
+    fn restore_bookmarks(&self, bookmarks: Vec<SerializedBookmark>, cx: &mut WindowContext) {
+        let project = self.project.clone();
+        let bookmarks = bookmarks
+            .into_iter()
+            .filter_map(|bookmark| {
+                let project = project.read(cx);
+                let project_path = project.worktrees().find_map(|worktree| {
+                    let worktree = worktree.read(cx);
+                    let path = bookmark.abs_path.strip_prefix(worktree.abs_path()).ok()?;
+                    Some(ProjectPath {
+                        worktree_id: worktree.id(),
+                        path: path.into(),
+                    })
+                })?;
+                Some((project_path, bookmark))
+            })
+            .collect::<Vec<_>>();
+
+        project
+            .read(cx)
+            .bookmark_store()
+            .clone()
+            .update(cx, |store, cx| {
+                store.clear(cx);
+                for (project_path, bookmark) in bookmarks {
+                    store.add_detached(
+                        project_path,
+                        language::Point::new(bookmark.row, bookmark.column),
+                        bookmark.label,
+                        cx,
+                    );
+                }
+            });
+    }
+
     fn actions(&self, div: Div, cx: &mut ViewContext<Self>) -> Div {
         self.add_workspace_actions_listeners(div, cx)
             .on_action(cx.listener(Self::close_inactive_items_and_panes))
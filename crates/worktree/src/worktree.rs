@@ -47,7 +47,7 @@ use std::{
     ffi::OsStr,
     fmt,
     future::Future,
-    mem,
+    io, mem,
     ops::{AddAssign, Deref, DerefMut, Sub},
     path::{Path, PathBuf},
     pin::Pin,
@@ -58,7 +58,7 @@ use std::{
     time::{Duration, Instant, SystemTime},
 };
 use sum_tree::{Bias, Edit, SeekTarget, SumTree, TreeMap, TreeSet};
-use text::{LineEnding, Rope};
+use text::{Encoding, LineEnding, Rope};
 use util::{paths::home_dir, ResultExt};
 pub use worktree_settings::WorktreeSettings;
 
@@ -67,6 +67,8 @@ pub const FS_WATCH_LATENCY: Duration = Duration::from_millis(100);
 #[cfg(not(feature = "test-support"))]
 pub const FS_WATCH_LATENCY: Duration = Duration::from_millis(100);
 
+const LARGE_FILE_CHUNK_SIZE: usize = 1024 * 1024;
+
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, PartialOrd, Ord)]
 pub struct WorktreeId(usize);
 
@@ -104,9 +106,36 @@ pub enum CreatedEntry {
 pub struct LoadedFile {
     pub file: Arc<File>,
     pub text: String,
+    pub encoding: Encoding,
     pub diff_base: Option<String>,
 }
 
+pub struct LoadedLargeFile {
+    pub file: Arc<File>,
+    pub text: Rope,
+    pub line_ending: LineEnding,
+    pub encoding: Encoding,
+}
+
+/// Returned by [`Worktree::load_file`] for files exceeding the given maximum size, which can be
+/// loaded with [`Worktree::load_large_file`] instead.
+#[derive(Debug)]
+pub struct FileTooLarge {
+    pub size: u64,
+}
+
+impl fmt::Display for FileTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "file is too large to be loaded at once ({} bytes)",
+            self.size
+        )
+    }
+}
+
+impl std::error::Error for FileTooLarge {}
+
 pub struct LocalWorktree {
     snapshot: LocalSnapshot,
     scan_requests_tx: channel::Sender<ScanRequest>,
@@ -658,13 +687,30 @@ impl Worktree {
         }
     }
 
+    /// Loads a file at once. Fails with [`FileTooLarge`] if the file is larger than `max_size`.
     pub fn load_file(
         &self,
         path: &Path,
+        max_size: Option<u64>,
         cx: &mut ModelContext<Worktree>,
     ) -> Task<Result<LoadedFile>> {
         match self {
-            Worktree::Local(this) => this.load_file(path, cx),
+            Worktree::Local(this) => this.load_file(path, max_size, cx),
+            Worktree::Remote(_) => {
+                Task::ready(Err(anyhow!("remote worktrees can't yet load files")))
+            }
+        }
+    }
+
+    /// Loads a file in chunks, sending the number of bytes read so far through `progress_tx`.
+    pub fn load_large_file(
+        &self,
+        path: &Path,
+        progress_tx: UnboundedSender<u64>,
+        cx: &mut ModelContext<Worktree>,
+    ) -> Task<Result<LoadedLargeFile>> {
+        match self {
+            Worktree::Local(this) => this.load_large_file(path, progress_tx, cx),
             Worktree::Remote(_) => {
                 Task::ready(Err(anyhow!("remote worktrees can't yet load files")))
             }
@@ -676,10 +722,11 @@ impl Worktree {
         path: &Path,
         text: Rope,
         line_ending: LineEnding,
+        encoding: Encoding,
         cx: &mut ModelContext<Worktree>,
     ) -> Task<Result<Arc<File>>> {
         match self {
-            Worktree::Local(this) => this.write_file(path, text, line_ending, cx),
+            Worktree::Local(this) => this.write_file(path, text, line_ending, encoding, cx),
             Worktree::Remote(_) => {
                 Task::ready(Err(anyhow!("remote worktree can't yet write files")))
             }
@@ -1237,7 +1284,12 @@ impl LocalWorktree {
         self.git_repositories.get(&repo.work_directory.0)
     }
 
-    fn load_file(&self, path: &Path, cx: &mut ModelContext<Worktree>) -> Task<Result<LoadedFile>> {
+    fn load_file(
+        &self,
+        path: &Path,
+        max_size: Option<u64>,
+        cx: &mut ModelContext<Worktree>,
+    ) -> Task<Result<LoadedFile>> {
         let path = Arc::from(path);
         let abs_path = self.absolutize(&path);
         let fs = self.fs.clone();
@@ -1246,32 +1298,35 @@ impl LocalWorktree {
 
         cx.spawn(|this, mut cx| async move {
             let abs_path = abs_path?;
-            let text = fs.load(&abs_path).await?;
+            // Missing files are reported when loading their contents.
+            let metadata = fs
+                .metadata(&abs_path)
+                .await
+                .with_context(|| format!("loading FS metadata for {abs_path:?}"))
+                .log_err()
+                .flatten();
+            if let Some((max_size, metadata)) = max_size.zip(metadata.as_ref()) {
+                if !metadata.is_dir && metadata.len > max_size {
+                    return Err(FileTooLarge { size: metadata.len }.into());
+                }
+            }
+
+            let (text, encoding) = fs.load_with_encoding(&abs_path, None).await?;
             let mut index_task = None;
             let snapshot = this.update(&mut cx, |this, _| this.as_local().unwrap().snapshot())?;
             if let Some(repo) = snapshot.repository_for_path(&path) {
                 if let Some(repo_path) = repo.relativize(&snapshot, &path).log_err() {
                     if let Some(git_repo) = snapshot.git_repositories.get(&*repo.work_directory) {
                         let git_repo = git_repo.repo_ptr.clone();
-                        index_task = Some(cx.background_executor().spawn({
-                            let fs = fs.clone();
-                            let abs_path = abs_path.clone();
-                            async move {
-                                let metadata = fs
-                                    .metadata(&abs_path)
-                                    .await
-                                    .with_context(|| {
-                                        format!("loading file and FS metadata for {abs_path:?}")
-                                    })
-                                    .log_err()
-                                    .flatten()?;
-                                if metadata.is_dir || metadata.is_symlink {
-                                    None
-                                } else {
-                                    git_repo.load_index_text(&repo_path)
-                                }
-                            }
-                        }));
+                        let is_file = metadata
+                            .as_ref()
+                            .map_or(false, |metadata| !metadata.is_dir && !metadata.is_symlink);
+                        if is_file {
+                            index_task = Some(
+                                cx.background_executor()
+                                    .spawn(async move { git_repo.load_index_text(&repo_path) }),
+                            );
+                        }
                     }
                 }
             }
@@ -1285,38 +1340,92 @@ impl LocalWorktree {
             let worktree = this
                 .upgrade()
                 .ok_or_else(|| anyhow!("worktree was dropped"))?;
-            let file = match entry.await? {
-                Some(entry) => File::for_entry(entry, worktree),
-                None => {
-                    let metadata = fs
-                        .metadata(&abs_path)
-                        .await
-                        .with_context(|| {
-                            format!("Loading metadata for excluded file {abs_path:?}")
-                        })?
-                        .with_context(|| {
-                            format!("Excluded file {abs_path:?} got removed during loading")
-                        })?;
-                    Arc::new(File {
-                        entry_id: None,
-                        worktree,
-                        path,
-                        mtime: Some(metadata.mtime),
-                        is_local: true,
-                        is_deleted: false,
-                        is_private,
-                    })
-                }
-            };
+            let file = Self::file_for_loaded_entry(
+                entry.await?,
+                worktree,
+                path,
+                &abs_path,
+                is_private,
+                metadata,
+            )?;
 
             Ok(LoadedFile {
                 file,
                 text,
+                encoding,
                 diff_base,
             })
         })
     }
 
+    fn load_large_file(
+        &self,
+        path: &Path,
+        progress_tx: UnboundedSender<u64>,
+        cx: &mut ModelContext<Worktree>,
+    ) -> Task<Result<LoadedLargeFile>> {
+        let path = Arc::from(path);
+        let abs_path = self.absolutize(&path);
+        let fs = self.fs.clone();
+        let entry = self.refresh_entry(path.clone(), None, cx);
+        let is_private = self.is_path_private(path.as_ref());
+
+        cx.spawn(|this, _| async move {
+            let abs_path = abs_path?;
+            let reader = fs.open_sync(&abs_path).await?;
+            let (text, line_ending, encoding) =
+                smol::unblock(move || read_in_chunks(reader, progress_tx)).await?;
+
+            let worktree = this
+                .upgrade()
+                .ok_or_else(|| anyhow!("worktree was dropped"))?;
+            let entry = entry.await?;
+            // Only excluded files, which have no entry, need their metadata to be loaded.
+            let metadata = if entry.is_none() {
+                fs.metadata(&abs_path).await?
+            } else {
+                None
+            };
+            let file = Self::file_for_loaded_entry(
+                entry, worktree, path, &abs_path, is_private, metadata,
+            )?;
+
+            Ok(LoadedLargeFile {
+                file,
+                text,
+                line_ending,
+                encoding,
+            })
+        })
+    }
+
+    fn file_for_loaded_entry(
+        entry: Option<Entry>,
+        worktree: Model<Worktree>,
+        path: Arc<Path>,
+        abs_path: &Path,
+        is_private: bool,
+        metadata: Option<fs::Metadata>,
+    ) -> Result<Arc<File>> {
+        match entry {
+            Some(entry) => Ok(File::for_entry(entry, worktree)),
+            None => {
+                let metadata = metadata.with_context(|| {
+                    format!("Excluded file {abs_path:?} got removed during loading")
+                })?;
+                Ok(Arc::new(File {
+                    entry_id: None,
+                    worktree,
+                    path,
+                    mtime: Some(metadata.mtime),
+                    is_local: true,
+                    is_deleted: false,
+                    is_private,
+                }))
+            }
+        }
+    }
+
     /// Find the lowest path in the worktree's datastructures that is an ancestor
     fn lowest_ancestor(&self, path: &Path) -> PathBuf {
         let mut lowest_ancestor = None;
@@ -1399,6 +1508,7 @@ impl LocalWorktree {
         path: impl Into<Arc<Path>>,
         text: Rope,
         line_ending: LineEnding,
+        encoding: Encoding,
         cx: &mut ModelContext<Worktree>,
     ) -> Task<Result<Arc<File>>> {
         let path = path.into();
@@ -1411,7 +1521,10 @@ impl LocalWorktree {
         let write = cx.background_executor().spawn({
             let fs = fs.clone();
             let abs_path = abs_path.clone();
-            async move { fs.save(&abs_path, &text, line_ending).await }
+            async move {
+                fs.save_with_encoding(&abs_path, &text, line_ending, encoding)
+                    .await
+            }
         });
 
         cx.spawn(move |this, mut cx| async move {
@@ -2832,6 +2945,66 @@ impl BackgroundScannerState {
     }
 }
 
+/// Reads text in chunks of [`LARGE_FILE_CHUNK_SIZE`] bytes, decoding and normalizing the line
+/// endings of each chunk as it's read, so that the raw contents are never held in memory all at
+/// once. The encoding is detected from the first chunk.
+fn read_in_chunks(
+    mut reader: Box<dyn io::Read>,
+    progress_tx: UnboundedSender<u64>,
+) -> Result<(Rope, LineEnding, Encoding)> {
+    let mut text = Rope::new();
+    let mut line_ending = None;
+    let mut decoder = None;
+    let mut buffer = vec![0; LARGE_FILE_CHUNK_SIZE];
+    let mut pending_carriage_return = false;
+    let mut bytes_read = 0;
+    loop {
+        let chunk_len = reader.read(&mut buffer)?;
+        let is_last = chunk_len == 0;
+        let bytes = &buffer[..chunk_len];
+        bytes_read += chunk_len as u64;
+
+        let (_, decoder) = match &mut decoder {
+            Some(decoder) => decoder,
+            None => {
+                // The first chunk may end in the middle of a character, which shouldn't rule
+                // out UTF-8.
+                let sample = match std::str::from_utf8(bytes) {
+                    Err(error) if error.error_len().is_none() => &bytes[..error.valid_up_to()],
+                    _ => bytes,
+                };
+                let encoding =
+                    Encoding::detect(sample).ok_or_else(|| anyhow!("not a text file"))?;
+                decoder.insert((encoding, encoding.decoder()))
+            }
+        };
+
+        // Incomplete sequences are kept by the decoder, and trailing carriage returns (which
+        // may be followed by a line feed) are kept here, until the next chunk is read.
+        let mut chunk = decoder.decode(bytes, is_last);
+        if mem::take(&mut pending_carriage_return) {
+            chunk.insert(0, '\r');
+        }
+        if !is_last && chunk.ends_with('\r') {
+            chunk.pop();
+            pending_carriage_return = true;
+        }
+        if !chunk.is_empty() {
+            line_ending.get_or_insert_with(|| LineEnding::detect(&chunk));
+            LineEnding::normalize(&mut chunk);
+            text.push(&chunk);
+        }
+
+        if is_last {
+            break;
+        }
+        progress_tx.unbounded_send(bytes_read).ok();
+    }
+
+    let encoding = decoder.map(|(encoding, _)| encoding).unwrap_or_default();
+    Ok((text, line_ending.unwrap_or_default(), encoding))
+}
+
 async fn build_gitignore(abs_path: &Path, fs: &dyn Fs) -> Result<Gitignore> {
     let contents = fs.load(abs_path).await?;
     let parent = abs_path.parent().unwrap_or_else(|| Path::new("/"));
@@ -3031,6 +3204,18 @@ impl language::LocalFile for File {
         cx.background_executor()
             .spawn(async move { fs.load(&abs_path?).await })
     }
+
+    fn load_with_encoding(
+        &self,
+        encoding: Option<Encoding>,
+        cx: &AppContext,
+    ) -> Task<Result<(String, Encoding)>> {
+        let worktree = self.worktree.read(cx).as_local().unwrap();
+        let abs_path = worktree.absolutize(&self.path);
+        let fs = worktree.fs.clone();
+        cx.background_executor()
+            .spawn(async move { fs.load_with_encoding(&abs_path?, encoding).await })
+    }
 }
 
 impl File {
@@ -1,9 +1,10 @@
 use crate::{
-    worktree_settings::WorktreeSettings, Entry, EntryKind, Event, PathChange, Snapshot, Worktree,
-    WorktreeModelHandle,
+    worktree_settings::WorktreeSettings, Entry, EntryKind, Event, FileTooLarge, PathChange,
+    Snapshot, Worktree, WorktreeModelHandle,
 };
 use anyhow::Result;
 use fs::{FakeFs, Fs, RealFs, RemoveOptions};
+use futures::StreamExt as _;
 use git::{repository::GitFileStatus, GITIGNORE};
 use gpui::{BorrowAppContext, ModelContext, Task, TestAppContext};
 use parking_lot::Mutex;
@@ -13,6 +14,7 @@ use rand::prelude::*;
 use serde_json::json;
 use settings::{Settings, SettingsStore};
 use std::{env, fmt::Write, mem, path::Path, sync::Arc};
+use text::LineEnding;
 use util::{test::temp_tree, ResultExt};
 
 #[gpui::test]
@@ -455,7 +457,7 @@ async fn test_open_gitignored_files(cx: &mut TestAppContext) {
     let prev_read_dir_count = fs.read_dir_call_count();
     let loaded = tree
         .update(cx, |tree, cx| {
-            tree.load_file("one/node_modules/b/b1.js".as_ref(), cx)
+            tree.load_file("one/node_modules/b/b1.js".as_ref(), None, cx)
         })
         .await
         .unwrap();
@@ -495,7 +497,7 @@ async fn test_open_gitignored_files(cx: &mut TestAppContext) {
     let prev_read_dir_count = fs.read_dir_call_count();
     let loaded = tree
         .update(cx, |tree, cx| {
-            tree.load_file("one/node_modules/a/a2.js".as_ref(), cx)
+            tree.load_file("one/node_modules/a/a2.js".as_ref(), None, cx)
         })
         .await
         .unwrap();
@@ -850,6 +852,7 @@ async fn test_write_file(cx: &mut TestAppContext) {
             Path::new("tracked-dir/file.txt"),
             "hello".into(),
             Default::default(),
+            Default::default(),
             cx,
         )
     })
@@ -860,6 +863,7 @@ async fn test_write_file(cx: &mut TestAppContext) {
             Path::new("ignored-dir/file.txt"),
             "world".into(),
             Default::default(),
+            Default::default(),
             cx,
         )
     })
@@ -874,6 +878,75 @@ async fn test_write_file(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_load_large_file(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.executor().allow_parking();
+    // Spans several chunks, whose boundaries split multi-byte characters and line endings.
+    let content = "é\r\n".repeat(1_000_000);
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree("/root", json!({ "large.log": content.clone() }))
+        .await;
+
+    let tree = Worktree::local(
+        Path::new("/root"),
+        true,
+        fs.clone(),
+        Default::default(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+    cx.read(|cx| tree.read(cx).as_local().unwrap().scan_complete())
+        .await;
+
+    let error = tree
+        .update(cx, |tree, cx| {
+            tree.load_file(Path::new("large.log"), Some(1024 * 1024), cx)
+        })
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(
+        error.downcast_ref::<FileTooLarge>().map(|error| error.size),
+        Some(content.len() as u64)
+    );
+
+    let (progress_tx, progress_rx) = futures::channel::mpsc::unbounded();
+    let loaded = tree
+        .update(cx, |tree, cx| {
+            tree.load_large_file(Path::new("large.log"), progress_tx, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(loaded.line_ending, LineEnding::Windows);
+    assert_eq!(loaded.text.to_string(), "é\n".repeat(1_000_000));
+    assert_eq!(loaded.file.path.as_ref(), Path::new("large.log"));
+
+    let progress = progress_rx.collect::<Vec<_>>().await;
+    assert!(progress.len() > 1);
+    assert_eq!(progress.last(), Some(&(content.len() as u64)));
+
+    // Files in other encodings are decoded as they're read.
+    let content = "naïve café\r\n".repeat(200_000);
+    let utf16 = [0xFF, 0xFE]
+        .into_iter()
+        .chain(content.encode_utf16().flat_map(u16::to_le_bytes))
+        .collect::<Vec<u8>>();
+    fs.insert_file("/root/large-utf16.log", utf16).await;
+    let (progress_tx, _) = futures::channel::mpsc::unbounded();
+    let loaded = tree
+        .update(cx, |tree, cx| {
+            tree.load_large_file(Path::new("large-utf16.log"), progress_tx, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(loaded.encoding.name(), "UTF-16LE");
+    assert!(loaded.encoding.has_bom());
+    assert_eq!(loaded.line_ending, LineEnding::Windows);
+    assert_eq!(loaded.text.to_string(), "naïve café\n".repeat(200_000));
+}
+
 #[gpui::test]
 async fn test_file_scan_exclusions(cx: &mut TestAppContext) {
     init_test(cx);
@@ -1658,8 +1731,13 @@ fn randomly_mutate_worktree(
                 })
             } else {
                 log::info!("overwriting file {:?} ({})", entry.path, entry.id.0);
-                let task =
-                    worktree.write_file(entry.path.clone(), "".into(), Default::default(), cx);
+                let task = worktree.write_file(
+                    entry.path.clone(),
+                    "".into(),
+                    Default::default(),
+                    Default::default(),
+                    cx,
+                );
                 cx.background_executor().spawn(async move {
                     task.await?;
                     Ok(())
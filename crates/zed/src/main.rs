@@ -39,7 +39,7 @@ use std::{
     process,
     sync::Arc,
 };
-use theme::{ActiveTheme, SystemAppearance, ThemeRegistry, ThemeSettings};
+use theme::{ActiveTheme, IconThemeAssets, SystemAppearance, ThemeRegistry, ThemeSettings};
 use util::{maybe, parse_env_output, with_clone, ResultExt, TryFutureExt};
 use uuid::Uuid;
 use welcome::{show_welcome_view, BaseKeymap, FIRST_OPEN};
@@ -191,9 +191,11 @@ fn init_ui(app_state: Arc<AppState>, cx: &mut AppContext) -> Result<()> {
 
     go_to_line::init(cx);
     file_finder::init(cx);
+    file_history::init(cx);
     tab_switcher::init(cx);
     outline::init(cx);
     project_symbols::init(cx);
+    bookmarks::init(cx);
     project_panel::init(Assets, cx);
     outline_panel::init(Assets, cx);
     tasks_ui::init(cx);
@@ -204,6 +206,7 @@ fn init_ui(app_state: Arc<AppState>, cx: &mut AppContext) -> Result<()> {
 
     journal::init(app_state.clone(), cx);
     language_selector::init(cx);
+    encoding_selector::init(cx);
     theme_selector::init(cx);
     language_tools::init(cx);
     call::init(app_state.client.clone(), app_state.user_store.clone(), cx);
@@ -301,7 +304,7 @@ fn main() {
     init_logger();
 
     log::info!("========== starting zed ==========");
-    let app = App::new().with_assets(Assets);
+    let app = App::new().with_assets(IconThemeAssets(Assets));
 
     let (installation_id, existing_installation_id_found) = app
         .background_executor()
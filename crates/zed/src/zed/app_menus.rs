@@ -21,6 +21,10 @@ pub fn app_menus() -> Vec<Menu<'static>> {
                         MenuItem::action("Open Default Key Bindings", super::OpenDefaultKeymap),
                         MenuItem::action("Open Local Settings", super::OpenLocalSettings),
                         MenuItem::action("Select Theme...", theme_selector::Toggle::default()),
+                        MenuItem::action(
+                            "Select Icon Theme...",
+                            theme_selector::icon_theme_selector::Toggle::default(),
+                        ),
                     ],
                 }),
                 MenuItem::action("Extensions", extensions_ui::Extensions),